@@ -19,13 +19,14 @@
 //! Substrate chain configurations.
 
 use sc_chain_spec::ChainSpecExtension;
-use sp_core::{Pair, Public, crypto::UncheckedInto, sr25519};
+use sp_core::{Pair, Public, crypto::UncheckedInto, sr25519, H160};
 use serde::{Serialize, Deserialize};
 use node_runtime::{
-	AuthorityDiscoveryConfig, BabeConfig, BalancesConfig, ContractsConfig, CouncilConfig,
-	DemocracyConfig,GrandpaConfig, ImOnlineConfig, SessionConfig, SessionKeys, StakerStatus,
-	StakingConfig, ElectionsConfig, IndicesConfig, SocietyConfig, SudoConfig, SystemConfig,
-	TechnicalCommitteeConfig, wasm_binary_unwrap,
+	AuthorityDiscoveryConfig, BabeConfig, BalancesConfig, ChainBridgeConfig, ContractsConfig,
+	CouncilConfig, DemocracyConfig, DidConfig, EvmConfig, GrandpaConfig, ImOnlineConfig,
+	MissionTokensConfig, SessionConfig, SessionKeys, StakerStatus, StakingConfig, ElectionsConfig,
+	IndicesConfig, SocietyConfig, SudoConfig, SystemConfig, TechnicalCommitteeConfig,
+	UsernameRegistryConfig, ValidatorRegistryConfig, wasm_binary_unwrap,
 };
 use node_runtime::Block;
 use node_runtime::constants::currency::*;
@@ -37,12 +38,16 @@ use sp_consensus_babe::{AuthorityId as BabeId};
 use pallet_im_online::sr25519::{AuthorityId as ImOnlineId};
 use sp_authority_discovery::AuthorityId as AuthorityDiscoveryId;
 use sp_runtime::{Perbill, traits::{Verify, IdentifyAccount}};
+use std::collections::BTreeMap;
 
 pub use node_primitives::{AccountId, Balance, Signature};
 pub use node_runtime::GenesisConfig;
 
 type AccountPublic = <Signature as Verify>::Signer;
 
+/// The first and last mission token ids of the test networks.
+pub const MISSION_TOKEN_IDS: (u32, u32) = (1, 17);
+
 const STAGING_TELEMETRY_URL: &str = "wss://telemetry.polkadot.io/submit/";
 
 /// Node `ChainSpec` extensions.
@@ -58,6 +63,79 @@ pub struct Extensions {
 	pub bad_blocks: sc_client_api::BadBlocks<Block>,
 }
 
+/// Genesis state of the pallets specific to this network.
+#[derive(Clone)]
+pub struct NetworkGenesis {
+	/// The first and last valid mission token ids.
+	pub mission_token_ids: (u32, u32),
+	/// Mission token balances, as `(mission_token_id, account, balance)`.
+	pub mission_balances: Vec<(u32, AccountId, Balance)>,
+	/// Registrars of the username registry.
+	pub registrars: Vec<AccountId>,
+	/// Validators registered for a mission, as `(validator, mission_token_id)`.
+	pub validators: Vec<(AccountId, u32)>,
+	/// Relayers of the chain bridge.
+	pub bridge_relayers: Vec<AccountId>,
+	/// Number of relayer votes required to execute a bridge proposal.
+	pub bridge_threshold: u32,
+	/// Chains whitelisted for bridge transfers.
+	pub bridge_chains: Vec<u8>,
+	/// Bridge resource ids and the methods they map to.
+	pub bridge_resources: Vec<([u8; 32], Vec<u8>)>,
+	/// EVM accounts, keyed by their address.
+	pub evm_accounts: BTreeMap<H160, pallet_evm::GenesisAccount>,
+	/// Owners of DID identities, as `(identity, owner)`.
+	pub did_owners: Vec<(AccountId, AccountId)>,
+	/// Delegates of DID identities, as `(identity, delegate_type, delegate)`.
+	pub did_delegates: Vec<(AccountId, Vec<u8>, AccountId)>,
+}
+
+impl Default for NetworkGenesis {
+	fn default() -> Self {
+		NetworkGenesis {
+			mission_token_ids: MISSION_TOKEN_IDS,
+			mission_balances: vec![],
+			registrars: vec![],
+			validators: vec![],
+			bridge_relayers: vec![],
+			bridge_threshold: 1,
+			bridge_chains: vec![],
+			bridge_resources: vec![],
+			evm_accounts: BTreeMap::new(),
+			did_owners: vec![],
+			did_delegates: vec![],
+		}
+	}
+}
+
+impl NetworkGenesis {
+	/// The genesis of a development or local test network: every endowed account holds some of
+	/// every mission token, the sudo account is the only registrar and bridge relayer, and the
+	/// stash of each authority is registered as a validator of a mission.
+	pub fn testnet(
+		initial_authorities: &[(AccountId, AccountId, GrandpaId, BabeId, ImOnlineId, AuthorityDiscoveryId)],
+		root_key: &AccountId,
+		endowed_accounts: &[AccountId],
+	) -> Self {
+		const MISSION_ENDOWMENT: Balance = 1_000 * DOLLARS;
+
+		let (first_mission, last_mission) = MISSION_TOKEN_IDS;
+		let missions = (first_mission..=last_mission).collect::<Vec<_>>();
+		NetworkGenesis {
+			mission_balances: missions.iter()
+				.flat_map(|id| endowed_accounts.iter().map(move |k| (*id, k.clone(), MISSION_ENDOWMENT)))
+				.collect(),
+			registrars: vec![root_key.clone()],
+			validators: initial_authorities.iter()
+				.zip(missions.iter().cycle())
+				.map(|(x, id)| (x.0.clone(), *id))
+				.collect(),
+			bridge_relayers: vec![root_key.clone()],
+			..Default::default()
+		}
+	}
+}
+
 /// Specialized `ChainSpec`.
 pub type ChainSpec = sc_service::GenericChainSpec<
 	GenesisConfig,
@@ -147,10 +225,18 @@ fn staging_testnet_config_genesis() -> GenesisConfig {
 
 	let endowed_accounts: Vec<AccountId> = vec![root_key.clone()];
 
+	// Mission tokens are not handed out on a live network; they are minted as validators earn
+	// era points.
+	let network_genesis = NetworkGenesis {
+		mission_balances: vec![],
+		..NetworkGenesis::testnet(&initial_authorities, &root_key, &endowed_accounts)
+	};
+
 	testnet_genesis(
 		initial_authorities,
 		root_key,
 		Some(endowed_accounts),
+		Some(network_genesis),
 		false,
 	)
 }
@@ -217,6 +303,7 @@ pub fn testnet_genesis(
 	)>,
 	root_key: AccountId,
 	endowed_accounts: Option<Vec<AccountId>>,
+	network_genesis: Option<NetworkGenesis>,
 	enable_println: bool,
 ) -> GenesisConfig {
 	let endowed_accounts: Vec<AccountId> = endowed_accounts.unwrap_or_else(|| {
@@ -236,6 +323,9 @@ pub fn testnet_genesis(
 		]
 	});
 	let num_endowed_accounts = endowed_accounts.len();
+	let network_genesis = network_genesis.unwrap_or_else(|| {
+		NetworkGenesis::testnet(&initial_authorities, &root_key, &endowed_accounts)
+	});
 
 	const ENDOWMENT: Balance = 7_777_377 * DOLLARS;
 	const STASH: Balance = 100 * DOLLARS;
@@ -322,6 +412,31 @@ pub fn testnet_genesis(
 			max_members: 999,
 		}),
 		pallet_vesting: Some(Default::default()),
+		pallet_evm: Some(EvmConfig {
+			accounts: network_genesis.evm_accounts,
+		}),
+		pallet_did: Some(DidConfig {
+			owners: network_genesis.did_owners,
+			delegates: network_genesis.did_delegates,
+		}),
+		pallet_mission_tokens: Some(MissionTokensConfig {
+			min_mission_token_id: network_genesis.mission_token_ids.0,
+			max_mission_token_id: network_genesis.mission_token_ids.1,
+			balances: network_genesis.mission_balances,
+		}),
+		pallet_social_treasury: Some(Default::default()),
+		pallet_validator_registry: Some(ValidatorRegistryConfig {
+			validators: network_genesis.validators,
+		}),
+		pallet_username_registry: Some(UsernameRegistryConfig {
+			registrars: network_genesis.registrars,
+		}),
+		pallet_chainbridge: Some(ChainBridgeConfig {
+			relayer_threshold: network_genesis.bridge_threshold,
+			relayers: network_genesis.bridge_relayers,
+			chains: network_genesis.bridge_chains,
+			resources: network_genesis.bridge_resources,
+		}),
 	}
 }
 
@@ -332,6 +447,7 @@ fn development_config_genesis() -> GenesisConfig {
 		],
 		get_account_id_from_seed::<sr25519::Public>("Alice"),
 		None,
+		None,
 		true,
 	)
 }
@@ -359,6 +475,7 @@ fn local_testnet_genesis() -> GenesisConfig {
 		],
 		get_account_id_from_seed::<sr25519::Public>("Alice"),
 		None,
+		None,
 		false,
 	)
 }
@@ -392,6 +509,7 @@ pub(crate) mod tests {
 			],
 			get_account_id_from_seed::<sr25519::Public>("Alice"),
 			None,
+			None,
 			false,
 		)
 	}
@@ -457,4 +575,89 @@ pub(crate) mod tests {
 	fn test_staging_test_net_chain_spec() {
 		staging_testnet_config().build_storage().unwrap();
 	}
+
+	#[test]
+	fn test_only_test_networks_endow_missions() {
+		let alice = get_account_id_from_seed::<sr25519::Public>("Alice");
+		let mut ext = sp_io::TestExternalities::new(development_config().build_storage().unwrap());
+		ext.execute_with(|| {
+			for id in MISSION_TOKEN_IDS.0..=MISSION_TOKEN_IDS.1 {
+				assert_eq!(node_runtime::MissionTokens::free_balance(&alice, id), 1_000 * DOLLARS);
+			}
+		});
+
+		let mut ext = sp_io::TestExternalities::new(
+			staging_testnet_config().build_storage().unwrap()
+		);
+		ext.execute_with(|| {
+			let root_key: AccountId =
+				hex!["02115fc5e45c37015ccf1a78107565d0577d0c75c56fa36683e004d4a365321c"].into();
+			for id in MISSION_TOKEN_IDS.0..=MISSION_TOKEN_IDS.1 {
+				assert_eq!(node_runtime::MissionTokens::free_balance(&root_key, id), 0);
+			}
+			// The rest of the network genesis is kept.
+			assert_eq!(node_runtime::UsernameRegistry::registrars(), vec![Some(root_key.clone())]);
+			assert!(node_runtime::ChainBridge::relayers(&root_key));
+		});
+	}
+
+	#[test]
+	fn test_network_genesis_is_built() {
+		let alice = get_account_id_from_seed::<sr25519::Public>("Alice");
+		let bob = get_account_id_from_seed::<sr25519::Public>("Bob");
+		let alice_stash = get_account_id_from_seed::<sr25519::Public>("Alice//stash");
+		let contract = H160::repeat_byte(1);
+		let network_genesis = NetworkGenesis {
+			mission_token_ids: (2, 20),
+			mission_balances: vec![(20, bob.clone(), 5 * DOLLARS)],
+			registrars: vec![bob.clone(), alice.clone()],
+			validators: vec![(alice_stash.clone(), 2)],
+			bridge_relayers: vec![alice.clone(), bob.clone()],
+			bridge_threshold: 2,
+			bridge_chains: vec![1],
+			bridge_resources: vec![([7; 32], b"Example.transfer".to_vec())],
+			evm_accounts: vec![(contract, pallet_evm::GenesisAccount {
+				nonce: 1.into(),
+				balance: 0.into(),
+				storage: Default::default(),
+				code: vec![0x60, 0x00],
+			})].into_iter().collect(),
+			did_owners: vec![(alice.clone(), bob.clone())],
+			did_delegates: vec![(bob.clone(), b"sigAuth".to_vec(), alice.clone())],
+		};
+		let storage = testnet_genesis(
+			vec![authority_keys_from_seed("Alice")],
+			alice.clone(),
+			None,
+			Some(network_genesis),
+			false,
+		).build_storage().unwrap();
+
+		sp_io::TestExternalities::new(storage).execute_with(|| {
+			assert_eq!(node_runtime::MissionTokens::min_mission_token_id(), 2);
+			assert_eq!(node_runtime::MissionTokens::max_mission_token_id(), 20);
+			assert_eq!(node_runtime::MissionTokens::free_balance(&bob, 20), 5 * DOLLARS);
+			assert_eq!(node_runtime::MissionTokens::free_balance(&alice, 20), 0);
+			assert_eq!(
+				node_runtime::UsernameRegistry::registrars(),
+				vec![Some(bob.clone()), Some(alice.clone())],
+			);
+			assert_eq!(node_runtime::ValidatorRegistry::mission_of(&alice_stash), 2);
+			assert_eq!(node_runtime::ValidatorRegistry::validators(2), vec![alice_stash.clone()]);
+			assert_eq!(node_runtime::ChainBridge::relayer_threshold(), 2);
+			assert_eq!(node_runtime::ChainBridge::relayer_count(), 2);
+			assert!(node_runtime::ChainBridge::relayers(&bob));
+			assert_eq!(node_runtime::ChainBridge::chains(1), Some(0));
+			assert_eq!(
+				node_runtime::ChainBridge::resources([7; 32]),
+				Some(b"Example.transfer".to_vec()),
+			);
+			assert_eq!(node_runtime::Evm::account_codes(contract), vec![0x60, 0x00]);
+			assert_eq!(node_runtime::Evm::account_basic(&contract).nonce, 1.into());
+			assert_eq!(node_runtime::Did::owner_of(&alice), Some(bob.clone()));
+			assert!(
+				node_runtime::Did::delegate_of((bob.clone(), b"sigAuth".to_vec(), alice.clone())).is_some()
+			);
+		});
+	}
 }
@@ -36,10 +36,11 @@ pub enum Subcommand {
 	/// Key management cli utilities
 	Key(KeySubcommand),
 
-	/// The custom inspect subcommmand for decoding blocks and extrinsics.
+	/// The custom inspect subcommmand for decoding blocks, extrinsics, events and storage.
 	#[structopt(
 		name = "inspect",
-		about = "Decode given block or extrinsic using current native runtime."
+		about = "Decode given block or extrinsic using current native runtime, \
+		or chain data using the metadata of the chain."
 	)]
 	Inspect(node_inspect::cli::InspectCmd),
 
@@ -47,6 +48,20 @@ pub enum Subcommand {
 	#[structopt(name = "benchmark", about = "Benchmark runtime pallets.")]
 	Benchmark(frame_benchmarking_cli::BenchmarkCmd),
 
+	/// The custom dry-run-upgrade subcommand running the storage migrations of a runtime offline.
+	#[structopt(
+		name = "dry-run-upgrade",
+		about = "Dry-run the storage migrations of a runtime against an exported state."
+	)]
+	DryRunUpgrade(frame_migration_cli::DryRunUpgradeCmd),
+
+	/// The custom tx subcommand constructing and signing transactions offline.
+	#[structopt(
+		name = "tx",
+		about = "Construct and sign transactions of the native runtime offline."
+	)]
+	Tx(frame_tx_cli::TxCmd),
+
 	/// Verify a signature for a message, provided on STDIN, with a given (public or secret) key.
 	Verify(VerifyCmd),
 
@@ -79,4 +94,10 @@ pub enum Subcommand {
 
 	/// Revert the chain to a previous state.
 	Revert(sc_cli::RevertCmd),
+
+	/// Export the history of block seals and votes signed by this node.
+	ExportSlashingProtection(sc_cli::ExportSlashingProtectionCmd),
+
+	/// Import the history of block seals and votes signed with keys moved over from another node.
+	ImportSlashingProtection(sc_cli::ImportSlashingProtectionCmd),
 }
@@ -17,9 +17,14 @@
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
 use crate::{chain_spec, service, Cli, Subcommand};
+use frame_tx_cli::{ExtraParams, RuntimeMetadataPrefixed, TxRuntime};
 use node_executor::Executor;
-use node_runtime::{Block, RuntimeApi};
+use node_inspect::decode::{TypeDef, TypeRegistry};
+use node_primitives::Index;
+use node_runtime::{Address, Block, Call, Runtime, RuntimeApi, SignedExtra};
 use sc_cli::{Result, SubstrateCli, RuntimeVersion, Role, ChainSpec};
+use sp_runtime::traits::SignedExtension;
+use std::convert::TryFrom;
 use sc_service::PartialComponents;
 use crate::service::{new_partial, new_full_base, NewFullBase};
 
@@ -65,6 +70,99 @@ impl SubstrateCli for Cli {
 	}
 }
 
+/// The transactions of the native runtime, for the `tx` subcommand.
+struct NodeTxRuntime;
+
+impl TxRuntime for NodeTxRuntime {
+	type Call = Call;
+	type Address = Address;
+	type Extra = SignedExtra;
+
+	const TYPE_ALIASES: &'static [(&'static str, &'static str)] = &[
+		("AccountIndex", "u32"),
+		("Balance", "u128"),
+		("BlockNumber", "u32"),
+		("EraIndex", "u32"),
+		("Index", "u32"),
+		("MemberCount", "u32"),
+		("MissionTokenId", "u32"),
+		("Moment", "u64"),
+		("ProposalIndex", "u32"),
+		("ReferendumIndex", "u32"),
+		("SessionIndex", "u32"),
+	];
+
+	const TRANSACTION_VERSION: u32 = node_runtime::VERSION.transaction_version;
+
+	fn metadata() -> RuntimeMetadataPrefixed {
+		Runtime::metadata()
+	}
+
+	/// Must be kept in line with [`SignedExtra`], as `sign` in `bin/node/testing/src/keyring.rs`.
+	fn signed_extra(
+		params: &ExtraParams,
+	) -> Result<(SignedExtra, <SignedExtra as SignedExtension>::AdditionalSigned)> {
+		let nonce = Index::try_from(params.nonce).map_err(|_| "Nonce out of range")?;
+		let extra = (
+			frame_system::CheckSpecVersion::new(),
+			frame_system::CheckTxVersion::new(),
+			frame_system::CheckGenesis::new(),
+			frame_system::CheckEra::from(params.era),
+			frame_system::CheckNonce::from(nonce),
+			frame_system::CheckWeight::new(),
+			pallet_transaction_payment::ChargeTransactionPayment::from(params.tip),
+		);
+		let additional = (
+			params.spec_version,
+			params.transaction_version,
+			params.genesis_hash,
+			params.era_hash,
+			(),
+			(),
+			(),
+		);
+		Ok((extra, additional))
+	}
+}
+
+/// The types of the runtime named by its metadata, for decoding chain data with `inspect`.
+fn inspect_type_registry() -> TypeRegistry {
+	TypeRegistry::default()
+		.with_aliases(NodeTxRuntime::TYPE_ALIASES)
+		.with_aliases(&[
+			("Signature", "MultiSignature"),
+			("MissionTokenBalance", "u128"),
+			("RoundIndex", "u32"),
+			("ProjectIndex", "u32"),
+			("GrantIndex", "u32"),
+			("StreamIndex", "u64"),
+			("ChainId", "u8"),
+			("DepositNonce", "u64"),
+			("ResourceId", "[u8; 32]"),
+		])
+		.with_type("ProxyType", TypeDef::Enum(&[
+			("Any", &[]),
+			("NonTransfer", &[]),
+			("Governance", &[]),
+			("Staking", &[]),
+		]))
+		.with_type("Attribute", TypeDef::Struct(&[
+			("name", "Vec<u8>"),
+			("value", "Vec<u8>"),
+			("validity", "BlockNumber"),
+			("creation", "Moment"),
+			("nonce", "u64"),
+		]))
+		.with_type("AttributeTransaction", TypeDef::Struct(&[
+			("signature", "Signature"),
+			("name", "Vec<u8>"),
+			("value", "Vec<u8>"),
+			("validity", "u32"),
+			("signer", "AccountId"),
+			("identity", "AccountId"),
+		]))
+}
+
 /// Parse command line arguments into service configuration.
 pub fn run() -> Result<()> {
 	let cli = Cli::from_args();
@@ -80,7 +178,7 @@ pub fn run() -> Result<()> {
 		Some(Subcommand::Inspect(cmd)) => {
 			let runner = cli.create_runner(cmd)?;
 
-			runner.sync_run(|config| cmd.run::<Block, RuntimeApi, Executor>(config))
+			runner.sync_run(|config| cmd.run::<Block, RuntimeApi, Executor>(config, inspect_type_registry()))
 		}
 		Some(Subcommand::Benchmark(cmd)) => {
 			if cfg!(feature = "runtime-benchmarks") {
@@ -92,6 +190,12 @@ pub fn run() -> Result<()> {
 				You can enable it with `--features runtime-benchmarks`.".into())
 			}
 		}
+		Some(Subcommand::DryRunUpgrade(cmd)) => {
+			let runner = cli.create_runner(cmd)?;
+
+			runner.sync_run(|config| cmd.run::<Block, Executor>(config))
+		}
+		Some(Subcommand::Tx(cmd)) => cmd.run::<NodeTxRuntime>(),
 		Some(Subcommand::Key(cmd)) => cmd.run(),
 		Some(Subcommand::Sign(cmd)) => cmd.run(),
 		Some(Subcommand::Verify(cmd)) => cmd.run(),
@@ -155,5 +259,37 @@ pub fn run() -> Result<()> {
 				Ok((cmd.run(client, backend), task_manager))
 			})
 		},
+		Some(Subcommand::ExportSlashingProtection(cmd)) => {
+			let runner = cli.create_runner(cmd)?;
+			runner.async_run(|config| {
+				let PartialComponents { client, task_manager, ..}
+					= new_partial(&config)?;
+				Ok((cmd.run(client), task_manager))
+			})
+		},
+		Some(Subcommand::ImportSlashingProtection(cmd)) => {
+			let runner = cli.create_runner(cmd)?;
+			runner.async_run(|config| {
+				let PartialComponents { client, task_manager, ..}
+					= new_partial(&config)?;
+				Ok((cmd.run(client), task_manager))
+			})
+		},
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use codec::{Decode, Encode};
+	use node_inspect::decode::MetadataDecoder;
+
+	#[test]
+	fn inspect_type_registry_should_know_all_event_types() {
+		// The metadata of the runtime is only decoded once it went through the codec.
+		let metadata = RuntimeMetadataPrefixed::decode(&mut &Runtime::metadata().encode()[..]).unwrap();
+		let registry = inspect_type_registry();
+		let decoder = MetadataDecoder::new(&metadata, &registry).unwrap();
+		assert_eq!(decoder.unknown_event_types().unwrap(), Vec::<String>::new());
 	}
 }
@@ -193,6 +193,10 @@ pub fn new_full_base(
 			block_announce_validator_builder: None,
 			finality_proof_request_builder: None,
 			finality_proof_provider: Some(finality_proof_provider.clone()),
+			warp_sync_provider: Some(grandpa::WarpSyncProofProvider::new_for_service(
+				backend.clone(),
+				client.clone(),
+			)),
 		})?;
 
 	if config.offchain_worker.enabled {
@@ -415,6 +419,7 @@ pub fn new_light_base(config: Configuration) -> Result<(
 			block_announce_validator_builder: None,
 			finality_proof_request_builder: Some(finality_proof_request_builder),
 			finality_proof_provider: Some(finality_proof_provider),
+			warp_sync_provider: None,
 		})?;
 	network_starter.start_network();
 
@@ -0,0 +1,59 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+#![cfg(unix)]
+
+use assert_cmd::cargo::cargo_bin;
+use std::process::Command;
+use tempfile::tempdir;
+
+pub mod common;
+
+#[test]
+fn dry_run_upgrade_works() {
+	let base_path = tempdir().expect("could not create a temp dir");
+
+	common::run_dev_node_for_a_while(base_path.path());
+
+	let output = Command::new(cargo_bin("substrate"))
+		.args(&["export-state", "--dev", "--pruning", "archive", "-d"])
+		.arg(base_path.path())
+		.output()
+		.unwrap();
+	assert!(output.status.success());
+
+	let spec = base_path.path().join("exported_state.json");
+	std::fs::write(&spec, &output.stdout).unwrap();
+
+	let output = Command::new(cargo_bin("substrate"))
+		.args(&["dry-run-upgrade", "-d"])
+		.arg(base_path.path())
+		.arg("--chain")
+		.arg(&spec)
+		.output()
+		.unwrap();
+	assert!(output.status.success());
+
+	// Every upgrade hook ran, in the order of a real upgrade.
+	let report = String::from_utf8(output.stdout).unwrap();
+	let lines: Vec<_> = report.lines().collect();
+	assert!(lines[0].starts_with("System: "));
+	assert!(lines.iter().any(|l| l.starts_with("MissionTokens::RecomputeTotalIssuance: ")));
+	assert!(lines[lines.len() - 2].starts_with("AllModules: "));
+	assert!(lines[lines.len() - 1].starts_with("Total weight: "));
+}
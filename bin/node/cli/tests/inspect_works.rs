@@ -37,4 +37,29 @@ fn inspect_works() {
 		.status()
 		.unwrap();
 	assert!(status.success());
+
+	let inspect_json = |args: &[&str]| {
+		let output = Command::new(cargo_bin("substrate"))
+			.args(&["inspect", "--dev", "--pruning", "archive", "-d"])
+			.arg(base_path.path())
+			.args(args)
+			.output()
+			.unwrap();
+		assert!(output.status.success());
+		serde_json::from_slice::<serde_json::Value>(&output.stdout).unwrap()
+	};
+
+	let block = inspect_json(&["block", "1", "--json"]);
+	assert_eq!(block["number"], 1);
+	assert_eq!(block["extrinsics"][0]["call"]["pallet"], "Timestamp");
+	assert!(block["events"].is_array());
+
+	let extrinsic = inspect_json(&["extrinsic", "1:0", "--json"]);
+	assert_eq!(extrinsic, block["extrinsics"][0]);
+
+	let events = inspect_json(&["events", "1"]);
+	assert_eq!(events, block["events"]);
+
+	let number = inspect_json(&["storage", "System.Number", "--at", "1"]);
+	assert_eq!(number[0]["value"], 1);
 }
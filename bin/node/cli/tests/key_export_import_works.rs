@@ -0,0 +1,93 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use assert_cmd::cargo::cargo_bin;
+use std::{fs, path::Path, process::Command};
+use tempfile::tempdir;
+
+fn assert_no_file_contains(dir: &Path, secret: &[u8]) {
+	for entry in fs::read_dir(dir).unwrap() {
+		let content = fs::read(entry.unwrap().path()).unwrap();
+		assert!(!content.windows(secret.len()).any(|w| w == secret));
+	}
+}
+
+#[test]
+fn key_export_import_works() {
+	let dir = tempdir().expect("could not create a temp dir");
+	let source = dir.path().join("source");
+	let target = dir.path().join("target");
+	let export = dir.path().join("export.json");
+	fs::write(dir.path().join("source-password"), "source password").unwrap();
+	fs::write(dir.path().join("target-password"), "target password").unwrap();
+	fs::write(dir.path().join("export-password"), "export password").unwrap();
+
+	let status = Command::new(cargo_bin("substrate"))
+		.args(&["key", "insert", "--suri", "//Alice", "--key-type", "babe", "-d"])
+		.arg(&source)
+		.arg("--password-filename")
+		.arg(dir.path().join("source-password"))
+		.status()
+		.unwrap();
+	assert!(status.success());
+	assert_no_file_contains(&source.join("keystore"), b"//Alice");
+
+	let status = Command::new(cargo_bin("substrate"))
+		.args(&["key", "export", "-d"])
+		.arg(&source)
+		.arg("--password-filename")
+		.arg(dir.path().join("source-password"))
+		.arg("--export-password-filename")
+		.arg(dir.path().join("export-password"))
+		.arg("--output")
+		.arg(&export)
+		.status()
+		.unwrap();
+	assert!(status.success());
+	let exported = fs::read(&export).unwrap();
+	assert!(!exported.windows(7).any(|w| w == b"//Alice"));
+
+	let output = Command::new(cargo_bin("substrate"))
+		.args(&["key", "import", "-d"])
+		.arg(&target)
+		.arg("--password-filename")
+		.arg(dir.path().join("target-password"))
+		.arg("--export-password-filename")
+		.arg(dir.path().join("export-password"))
+		.arg("--input")
+		.arg(&export)
+		.output()
+		.unwrap();
+	assert!(output.status.success());
+	assert!(String::from_utf8_lossy(&output.stdout).contains("Imported babe key 0x"));
+	assert_no_file_contains(&target.join("keystore"), b"//Alice");
+
+	// The source keystore only opens with its own password.
+	let status = Command::new(cargo_bin("substrate"))
+		.args(&["key", "export", "-d"])
+		.arg(&source)
+		.arg("--password-filename")
+		.arg(dir.path().join("target-password"))
+		.arg("--export-password-filename")
+		.arg(dir.path().join("export-password"))
+		.arg("--output")
+		.arg(dir.path().join("other-export.json"))
+		.status()
+		.unwrap();
+	assert!(!status.success());
+}
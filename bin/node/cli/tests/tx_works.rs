@@ -0,0 +1,67 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use assert_cmd::cargo::cargo_bin;
+use codec::Decode;
+use node_runtime::{Address, BalancesCall, Call, UncheckedExtrinsic};
+use sp_core::crypto::{AccountId32, Ss58Codec};
+use std::process::Command;
+
+const ALICE: &str = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+const BOB: &str = "5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty";
+
+fn tx(args: &[&str]) -> String {
+	let output = Command::new(cargo_bin("substrate"))
+		.arg("tx")
+		.args(args)
+		.output()
+		.unwrap();
+	assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+	String::from_utf8(output.stdout).unwrap().trim().to_owned()
+}
+
+#[test]
+fn tx_works() {
+	let genesis_hash = format!("0x{}", "00".repeat(32));
+	let payload = tx(&[
+		"payload", "Balances.transfer",
+		"--args", &format!(r#"["{}", "1000000000000"]"#, BOB),
+		"--nonce", "0",
+		"--genesis-hash", &genesis_hash,
+		"--spec-version", "1",
+	]);
+	assert!(payload.starts_with("0x"));
+
+	let signature = tx(&["sign", "--payload", &payload, "--suri", "//Alice"]);
+	let extrinsic = tx(&["assemble", "--payload", &payload, "--signer", ALICE, "--signature", &signature]);
+	assert!(extrinsic.starts_with("0x"));
+
+	// The extrinsic is the transfer signed by Alice.
+	let encoded = sp_core::bytes::from_hex(&extrinsic).unwrap();
+	let xt = UncheckedExtrinsic::decode(&mut &encoded[..]).unwrap();
+	let bob = AccountId32::from_ss58check(BOB).unwrap();
+	assert_eq!(xt.function, Call::Balances(BalancesCall::transfer(Address::from(bob), 1_000_000_000_000)));
+	let (signer, _, _) = xt.signature.unwrap();
+	assert_eq!(signer, Address::from(AccountId32::from_ss58check(ALICE).unwrap()));
+
+	let status = Command::new(cargo_bin("substrate"))
+		.args(&["tx", "assemble", "--payload", &payload, "--signer", BOB, "--signature", &signature])
+		.status()
+		.unwrap();
+	assert!(!status.success());
+}
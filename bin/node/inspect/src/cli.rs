@@ -50,6 +50,12 @@ pub enum InspectSubCmd {
 		/// a block.
 		#[structopt(value_name = "HASH or NUMBER or BYTES")]
 		input: String,
+
+		/// Decode with the metadata of the chain instead of the native runtime, and print JSON.
+		///
+		/// Raw bytes are decoded with the metadata of the best block.
+		#[structopt(long)]
+		json: bool,
 	},
 	/// Decode extrinsic with native version of runtime and print out the details.
 	Extrinsic {
@@ -60,5 +66,32 @@ pub enum InspectSubCmd {
 		/// representing SCALE encoding of an extrinsic.
 		#[structopt(value_name = "BLOCK:INDEX or BYTES")]
 		input: String,
+
+		/// Decode with the metadata of the chain instead of the native runtime, and print JSON.
+		///
+		/// Raw bytes are decoded with the metadata of the best block.
+		#[structopt(long)]
+		json: bool,
+	},
+	/// Decode the events of a block with the metadata of the chain and print them as JSON.
+	Events {
+		/// Address of the block, either a block hash (no 0x prefix) or a number.
+		#[structopt(value_name = "HASH or NUMBER")]
+		input: String,
+	},
+	/// Decode storage with the metadata of the chain and print it as JSON.
+	Storage {
+		/// The storage to print out.
+		///
+		/// Can be either a 0x-prefixed hex storage key, or a storage item in the form
+		/// of `{prefix}.{item}` (e.g. `MissionTokens.TotalIssuance`), printing all of its values.
+		#[structopt(value_name = "KEY or PREFIX.ITEM")]
+		key: String,
+
+		/// The block to read the storage at, either a block hash (no 0x prefix) or a number.
+		///
+		/// Defaults to the best block.
+		#[structopt(long, value_name = "HASH or NUMBER")]
+		at: Option<String>,
 	},
 }
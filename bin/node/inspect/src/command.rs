@@ -19,6 +19,7 @@
 //! Command ran by the CLI
 
 use crate::cli::{InspectCmd, InspectSubCmd};
+use crate::decode::TypeRegistry;
 use crate::Inspector;
 use sc_cli::{CliConfiguration, ImportParams, Result, SharedParams};
 use sc_service::{new_full_client, Configuration, NativeExecutionDispatch};
@@ -27,7 +28,9 @@ use std::str::FromStr;
 
 impl InspectCmd {
 	/// Run the inspect command, passing the inspector.
-	pub fn run<B, RA, EX>(&self, config: Configuration) -> Result<()>
+	///
+	/// `registry` gives the types of the runtime, used to decode by the metadata of the chain.
+	pub fn run<B, RA, EX>(&self, config: Configuration, registry: TypeRegistry) -> Result<()>
 	where
 		B: Block,
 		B::Hash: FromStr,
@@ -35,18 +38,32 @@ impl InspectCmd {
 		EX: NativeExecutionDispatch + 'static,
 	{
 		let client = new_full_client::<B, RA, EX>(&config)?;
-		let inspect = Inspector::<B>::new(client);
+		let inspect = Inspector::<B>::new(client).with_registry(registry);
 
 		match &self.command {
-			InspectSubCmd::Block { input } => {
+			InspectSubCmd::Block { input, json } => {
 				let input = input.parse()?;
-				let res = inspect.block(input).map_err(|e| format!("{}", e))?;
+				let res = if *json { inspect.block_json(input) } else { inspect.block(input) }
+					.map_err(|e| format!("{}", e))?;
 				println!("{}", res);
 				Ok(())
 			}
-			InspectSubCmd::Extrinsic { input } => {
+			InspectSubCmd::Extrinsic { input, json } => {
 				let input = input.parse()?;
-				let res = inspect.extrinsic(input).map_err(|e| format!("{}", e))?;
+				let res = if *json { inspect.extrinsic_json(input) } else { inspect.extrinsic(input) }
+					.map_err(|e| format!("{}", e))?;
+				println!("{}", res);
+				Ok(())
+			}
+			InspectSubCmd::Events { input } => {
+				let input = input.parse()?;
+				let res = inspect.events_json(input).map_err(|e| format!("{}", e))?;
+				println!("{}", res);
+				Ok(())
+			}
+			InspectSubCmd::Storage { key, at } => {
+				let at = at.as_ref().map(|at| at.parse()).transpose()?;
+				let res = inspect.storage_json(key, at).map_err(|e| format!("{}", e))?;
 				println!("{}", res);
 				Ok(())
 			}
@@ -0,0 +1,869 @@
+// This file is part of Substrate.
+//
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Decoding of SCALE encoded chain data to JSON, driven by the runtime metadata.
+//!
+//! The metadata only names the types of call arguments, event arguments and storage items.
+//! How a named type is encoded is looked up in a [`TypeRegistry`], which knows the primitive
+//! and the common FRAME types and is extended with the types of a given runtime.
+
+use std::collections::HashMap;
+use codec::{Compact, Decode, Input};
+use frame_metadata::{
+	DecodeDifferent, ModuleMetadata, RuntimeMetadata, RuntimeMetadataPrefixed,
+	StorageEntryModifier, StorageEntryType, StorageHasher,
+};
+use serde_json::{json, Map, Value};
+use sp_core::{crypto::{AccountId32, Ss58Codec}, hashing::twox_128, U256};
+use sp_runtime::generic::Era;
+
+/// Error decoding chain data by the metadata.
+#[derive(Debug, derive_more::Display)]
+pub enum Error {
+	/// The data does not match the types of the metadata.
+	Codec(codec::Error),
+	/// The metadata is not supported, or does not describe the data.
+	#[display(fmt = "Invalid metadata: {}", _0)]
+	Metadata(String),
+	/// A type named by the metadata is not known to the registry.
+	#[display(fmt = "Unknown type `{}`, it must be added to the type registry", _0)]
+	UnknownType(String),
+}
+
+impl std::error::Error for Error {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		match *self {
+			Self::Codec(ref e) => Some(e),
+			Self::Metadata(_) | Self::UnknownType(_) => None,
+		}
+	}
+}
+
+impl From<codec::Error> for Error {
+	fn from(e: codec::Error) -> Self {
+		Self::Codec(e)
+	}
+}
+
+/// The encoding of a type known to a [`TypeRegistry`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TypeDef {
+	/// Encoded as the given type.
+	Alias(&'static str),
+	/// A struct, encoded as its fields in order, given as `(name, type)`.
+	Struct(&'static [(&'static str, &'static str)]),
+	/// An enum, encoded as the index of the variant followed by its fields.
+	///
+	/// The variants are given in the order of their index, as `(name, fields)`. Fields of
+	/// tuple variants have an empty name.
+	Enum(&'static [(&'static str, &'static [(&'static str, &'static str)])]),
+}
+
+/// Types of FRAME that are part of the metadata of most runtimes.
+const FRAME_TYPES: &[(&str, TypeDef)] = &[
+	("Weight", TypeDef::Alias("u64")),
+	("RefCount", TypeDef::Alias("u32")),
+	("LockIdentifier", TypeDef::Alias("[u8; 8]")),
+	("Percent", TypeDef::Alias("u8")),
+	("Permill", TypeDef::Alias("u32")),
+	("Perbill", TypeDef::Alias("u32")),
+	("DispatchClass", TypeDef::Enum(&[("Normal", &[]), ("Operational", &[]), ("Mandatory", &[])])),
+	("Pays", TypeDef::Enum(&[("Yes", &[]), ("No", &[])])),
+	("DispatchInfo", TypeDef::Struct(&[
+		("weight", "Weight"),
+		("class", "DispatchClass"),
+		("pays_fee", "Pays"),
+	])),
+	("DispatchError", TypeDef::Enum(&[
+		("Other", &[]),
+		("CannotLookup", &[]),
+		("BadOrigin", &[]),
+		("Module", &[("index", "u8"), ("error", "u8")]),
+	])),
+	("Phase", TypeDef::Enum(&[
+		("ApplyExtrinsic", &[("", "u32")]),
+		("Finalization", &[]),
+		("Initialization", &[]),
+	])),
+	("MultiSignature", TypeDef::Enum(&[
+		("Ed25519", &[("", "[u8; 64]")]),
+		("Sr25519", &[("", "[u8; 64]")]),
+		("Ecdsa", &[("", "[u8; 65]")]),
+	])),
+	("AccountInfo", TypeDef::Struct(&[
+		("nonce", "Index"),
+		("refcount", "RefCount"),
+		("data", "AccountData"),
+	])),
+	("AccountData", TypeDef::Struct(&[
+		("free", "Balance"),
+		("reserved", "Balance"),
+		("misc_frozen", "Balance"),
+		("fee_frozen", "Balance"),
+	])),
+	("Reasons", TypeDef::Enum(&[("Fee", &[]), ("Misc", &[]), ("All", &[])])),
+	("BalanceLock", TypeDef::Struct(&[
+		("id", "LockIdentifier"),
+		("amount", "Balance"),
+		("reasons", "Reasons"),
+	])),
+	("Status", TypeDef::Enum(&[("Free", &[]), ("Reserved", &[])])),
+	("DispatchResult", TypeDef::Alias("Result<(), DispatchError>")),
+	("AccountIndex", TypeDef::Alias("u32")),
+	("PropIndex", TypeDef::Alias("u32")),
+	("RegistrarIndex", TypeDef::Alias("u32")),
+	("BountyIndex", TypeDef::Alias("u32")),
+	("CallHash", TypeDef::Alias("[u8; 32]")),
+	("Kind", TypeDef::Alias("[u8; 16]")),
+	("OpaqueTimeSlot", TypeDef::Alias("Vec<u8>")),
+	("AuthorityId", TypeDef::Alias("[u8; 32]")),
+	("AuthorityWeight", TypeDef::Alias("u64")),
+	("AuthorityList", TypeDef::Alias("Vec<(AuthorityId, AuthorityWeight)>")),
+	("TaskAddress", TypeDef::Alias("(BlockNumber, u32)")),
+	("Timepoint", TypeDef::Struct(&[("height", "BlockNumber"), ("index", "u32")])),
+	("VoteThreshold", TypeDef::Enum(&[
+		("SuperMajorityApprove", &[]),
+		("SuperMajorityAgainst", &[]),
+		("SimpleMajority", &[]),
+	])),
+	("ElectionCompute", TypeDef::Enum(&[("OnChain", &[]), ("Signed", &[]), ("Unsigned", &[])])),
+	("IndividualExposure", TypeDef::Struct(&[("who", "AccountId"), ("value", "Compact<Balance>")])),
+	("Exposure", TypeDef::Struct(&[
+		("total", "Compact<Balance>"),
+		("own", "Compact<Balance>"),
+		("others", "Vec<IndividualExposure>"),
+	])),
+	("IdentificationTuple", TypeDef::Alias("(AccountId, Exposure)")),
+	("Log", TypeDef::Struct(&[("address", "H160"), ("topics", "Vec<H256>"), ("data", "Vec<u8>")])),
+];
+
+/// The data the signed extensions of FRAME add to an extrinsic.
+const FRAME_SIGNED_EXTENSIONS: &[(&str, &str)] = &[
+	("CheckEra", "Era"),
+	("CheckMortality", "Era"),
+	("CheckNonce", "Compact<Index>"),
+	("ChargeTransactionPayment", "Compact<Balance>"),
+];
+
+/// The encoding of the types named by the metadata.
+///
+/// Besides the registered types, the primitive types, `Compact`, `Vec`, `Option`, `Result`,
+/// `Box`, tuples, fixed size arrays, hashes, `AccountId` (as SS58), `Address` (of
+/// `pallet-indices`), `Era` and `Call` are known.
+#[derive(Debug, Clone)]
+pub struct TypeRegistry {
+	types: HashMap<&'static str, TypeDef>,
+	signed_extensions: HashMap<&'static str, &'static str>,
+}
+
+impl Default for TypeRegistry {
+	fn default() -> Self {
+		TypeRegistry {
+			types: FRAME_TYPES.iter().cloned().collect(),
+			signed_extensions: FRAME_SIGNED_EXTENSIONS.iter().cloned().collect(),
+		}
+	}
+}
+
+impl TypeRegistry {
+	/// Register the type `name`.
+	pub fn with_type(mut self, name: &'static str, def: TypeDef) -> Self {
+		self.types.insert(name, def);
+		self
+	}
+
+	/// Register the types named first as aliases of the types named second.
+	pub fn with_aliases(mut self, aliases: &[(&'static str, &'static str)]) -> Self {
+		for (name, alias) in aliases {
+			self.types.insert(*name, TypeDef::Alias(*alias));
+		}
+		self
+	}
+
+	/// Register the type of the data the signed extension `name` adds to an extrinsic.
+	///
+	/// Signed extensions that are not registered are assumed to add no data.
+	pub fn with_signed_extension(mut self, name: &'static str, ty: &'static str) -> Self {
+		self.signed_extensions.insert(name, ty);
+		self
+	}
+}
+
+/// Returns the decoded side of a metadata item.
+fn decoded<B, O>(item: &DecodeDifferent<B, O>) -> Result<&O, Error> {
+	match item {
+		DecodeDifferent::Decoded(item) => Ok(item),
+		DecodeDifferent::Encode(_) => Err(Error::Metadata("metadata must be decoded before use".into())),
+	}
+}
+
+/// Decodes chain data by the metadata of a runtime.
+pub struct MetadataDecoder<'a> {
+	registry: &'a TypeRegistry,
+	modules: &'a [ModuleMetadata],
+	extrinsic_version: u8,
+	signed_extensions: Vec<&'a str>,
+}
+
+impl<'a> MetadataDecoder<'a> {
+	/// Create a decoder for the given metadata, which must have been decoded.
+	pub fn new(metadata: &'a RuntimeMetadataPrefixed, registry: &'a TypeRegistry) -> Result<Self, Error> {
+		let metadata = match &metadata.1 {
+			RuntimeMetadata::V12(metadata) => metadata,
+			_ => return Err(Error::Metadata("only version 12 of the metadata is supported".into())),
+		};
+		Ok(MetadataDecoder {
+			registry,
+			modules: decoded(&metadata.modules)?,
+			extrinsic_version: metadata.extrinsic.version,
+			signed_extensions: metadata.extrinsic.signed_extensions.iter()
+				.map(|name| decoded(name).map(|name| name.as_str()))
+				.collect::<Result<_, _>>()?,
+		})
+	}
+
+	/// Decode an extrinsic, with its length prefix, as `{ "signature", "call" }`.
+	///
+	/// The signature of a signed extrinsic is given as `{ "signer", "signature", "extra" }`,
+	/// with the data of the signed extensions keyed by their name.
+	pub fn decode_extrinsic(&self, extrinsic: &[u8]) -> Result<Value, Error> {
+		let bytes = <Vec<u8>>::decode(&mut &extrinsic[..])?;
+		let input = &mut &bytes[..];
+		let version = u8::decode(input)?;
+		if version & 0b0111_1111 != self.extrinsic_version {
+			return Err(codec::Error::from("Unsupported extrinsic version").into())
+		}
+		let signature = if version & 0b1000_0000 != 0 {
+			let signer = self.decode_value("Address", input)?;
+			let signature = self.decode_value("Signature", input)?;
+			let mut extra = Map::new();
+			for name in &self.signed_extensions {
+				let ty = self.registry.signed_extensions.get(*name).unwrap_or(&"()");
+				let value = self.decode_value(ty, input)?;
+				if !value.is_null() {
+					extra.insert(name.to_string(), value);
+				}
+			}
+			json!({ "signer": signer, "signature": signature, "extra": extra })
+		} else {
+			Value::Null
+		};
+		let call = self.decode_call(input)?;
+		ensure_consumed(input)?;
+		Ok(json!({ "signature": signature, "call": call }))
+	}
+
+	/// Decode a call as `{ "pallet", "call", "args" }`, with the arguments keyed by their name.
+	pub fn decode_call(&self, input: &mut &[u8]) -> Result<Value, Error> {
+		let module = self.module(u8::decode(input)?)?;
+		let call_index = u8::decode(input)?;
+		let function = module.calls.as_ref()
+			.map(decoded)
+			.transpose()?
+			.and_then(|calls| calls.get(call_index as usize))
+			.ok_or_else(|| Error::Metadata(format!(
+				"pallet `{}` has no call {}", decoded(&module.name).unwrap_or(&String::new()), call_index,
+			)))?;
+		let mut args = Map::new();
+		for arg in decoded(&function.arguments)? {
+			args.insert(decoded(&arg.name)?.clone(), self.decode_value(decoded(&arg.ty)?, input)?);
+		}
+		Ok(json!({ "pallet": decoded(&module.name)?, "call": decoded(&function.name)?, "args": args }))
+	}
+
+	/// Decode the value of `System::Events`.
+	///
+	/// Each record is given as `{ "phase", "pallet", "event", "args", "topics" }`.
+	///
+	/// The length of an event is only known by decoding its arguments, so the first event
+	/// with an argument of a type unknown to the registry ends the records. It is given as
+	/// `{ "phase", "pallet", "event", "raw" }`, with the rest of the data as hex.
+	pub fn decode_events(&self, events: &[u8]) -> Result<Value, Error> {
+		let input = &mut &events[..];
+		let count = <Compact<u32>>::decode(input)?.0;
+		let mut records = Vec::new();
+		for _ in 0..count {
+			let phase = self.decode_value("Phase", input)?;
+			let module = self.module(u8::decode(input)?)?;
+			let event_index = u8::decode(input)?;
+			let event = module.event.as_ref()
+				.map(decoded)
+				.transpose()?
+				.and_then(|events| events.get(event_index as usize))
+				.ok_or_else(|| Error::Metadata(format!(
+					"pallet `{}` has no event {}", decoded(&module.name).unwrap_or(&String::new()), event_index,
+				)))?;
+			let arguments = decoded(&event.arguments)?;
+			if arguments.iter().any(|ty| !self.is_known(ty)) {
+				records.push(json!({
+					"phase": phase,
+					"pallet": decoded(&module.name)?,
+					"event": decoded(&event.name)?,
+					"raw": hex_value(input),
+				}));
+				return Ok(records.into())
+			}
+			let mut args = Vec::new();
+			for ty in arguments {
+				args.push(self.decode_value(ty, input)?);
+			}
+			records.push(json!({
+				"phase": phase,
+				"pallet": decoded(&module.name)?,
+				"event": decoded(&event.name)?,
+				"args": args,
+				"topics": self.decode_value("Vec<Hash>", input)?,
+			}));
+		}
+		ensure_consumed(input)?;
+		Ok(records.into())
+	}
+
+	/// The types of event arguments of the metadata that are not known to the registry.
+	pub fn unknown_event_types(&self) -> Result<Vec<String>, Error> {
+		let mut unknown = Vec::new();
+		for module in self.modules {
+			let events = match &module.event {
+				Some(events) => decoded(events)?,
+				None => continue,
+			};
+			for event in events {
+				for ty in decoded(&event.arguments)? {
+					if !self.is_known(ty) && !unknown.contains(ty) {
+						unknown.push(ty.clone());
+					}
+				}
+			}
+		}
+		Ok(unknown)
+	}
+
+	/// Decode a storage key and its value as `{ "pallet", "item", "keys", "value" }`.
+	///
+	/// The value is `null` if it is not in storage and the item has no default. The keys of
+	/// maps that are hashed without the key appended are given as `{ "hash" }`.
+	pub fn decode_storage(&self, key: &[u8], value: Option<&[u8]>) -> Result<Value, Error> {
+		let (prefix, entry) = self.storage_entry(key)?;
+		let input = &mut &key[32..];
+		let (keys, ty) = match &entry.ty {
+			StorageEntryType::Plain(ty) => (vec![], ty),
+			StorageEntryType::Map { hasher, key, value, .. } =>
+				(vec![self.decode_key(hasher, decoded(key)?, input)?], value),
+			StorageEntryType::DoubleMap { hasher, key1, key2, value, key2_hasher } => (vec![
+				self.decode_key(hasher, decoded(key1)?, input)?,
+				self.decode_key(key2_hasher, decoded(key2)?, input)?,
+			], value),
+		};
+		ensure_consumed(input)?;
+
+		let value = match (value, &entry.modifier) {
+			(Some(value), _) => Some(value),
+			(None, StorageEntryModifier::Default) => Some(decoded(&entry.default)?.as_slice()),
+			(None, StorageEntryModifier::Optional) => None,
+		};
+		let value = match value {
+			Some(value) => {
+				let input = &mut &value[..];
+				let value = self.decode_value(decoded(ty)?, input)?;
+				ensure_consumed(input)?;
+				value
+			},
+			None => Value::Null,
+		};
+		Ok(json!({ "pallet": prefix, "item": decoded(&entry.name)?, "keys": keys, "value": value }))
+	}
+
+	/// The storage key prefix of the item `Prefix.Item`, e.g. `System.Account`.
+	pub fn storage_prefix(&self, item: &str) -> Result<Vec<u8>, Error> {
+		let mut parts = item.splitn(2, '.');
+		let (prefix, name) = match (parts.next(), parts.next()) {
+			(Some(prefix), Some(name)) => (prefix, name),
+			_ => return Err(Error::Metadata(format!("storage item `{}` is not of the form `Prefix.Item`", item))),
+		};
+		let mut key = twox_128(prefix.as_bytes()).to_vec();
+		key.extend(&twox_128(name.as_bytes()));
+		self.storage_entry(&key).map(|_| key)
+	}
+
+	fn storage_entry(&self, key: &[u8]) -> Result<(&'a str, &'a frame_metadata::StorageEntryMetadata), Error> {
+		if key.len() >= 32 {
+			for module in self.modules {
+				let storage = match &module.storage {
+					Some(storage) => decoded(storage)?,
+					None => continue,
+				};
+				let prefix = decoded(&storage.prefix)?;
+				if key[..16] != twox_128(prefix.as_bytes()) {
+					continue
+				}
+				for entry in decoded(&storage.entries)? {
+					if key[16..32] == twox_128(decoded(&entry.name)?.as_bytes()) {
+						return Ok((prefix, entry))
+					}
+				}
+			}
+		}
+		Err(Error::Metadata(format!("no storage item for key 0x{}", hex::encode(key))))
+	}
+
+	fn decode_key(&self, hasher: &StorageHasher, ty: &str, input: &mut &[u8]) -> Result<Value, Error> {
+		let (hash_len, concat) = match hasher {
+			StorageHasher::Blake2_128 | StorageHasher::Twox128 => (16, false),
+			StorageHasher::Blake2_256 | StorageHasher::Twox256 => (32, false),
+			StorageHasher::Blake2_128Concat => (16, true),
+			StorageHasher::Twox64Concat => (8, true),
+			StorageHasher::Identity => (0, true),
+		};
+		let hash = read_bytes(input, hash_len)?;
+		if concat {
+			self.decode_value(ty, input)
+		} else {
+			Ok(json!({ "hash": hex_value(&hash) }))
+		}
+	}
+
+	fn module(&self, index: u8) -> Result<&'a ModuleMetadata, Error> {
+		self.modules.iter()
+			.find(|module| module.index == index)
+			.ok_or_else(|| Error::Metadata(format!("no pallet with index {}", index)))
+	}
+
+	/// Decode a value of the type named `ty`.
+	///
+	/// Integers that do not fit in `u64` are given as decimal strings, bytes as hex strings.
+	pub fn decode_value(&self, ty: &str, input: &mut &[u8]) -> Result<Value, Error> {
+		let ty = normalize(ty);
+		if ty.starts_with('[') && ty.ends_with(']') {
+			return self.decode_array(&ty, input)
+		}
+		let (name, params) = split_generics(&ty);
+		match self.registry.types.get(name) {
+			Some(TypeDef::Alias(alias)) => return self.decode_value(alias, input),
+			Some(TypeDef::Struct(fields)) => return self.decode_fields(fields, input),
+			Some(TypeDef::Enum(variants)) => {
+				let index = u8::decode(input)?;
+				let (name, fields) = variants.get(index as usize)
+					.ok_or_else(|| codec::Error::from("Invalid enum variant"))?;
+				return Ok(if fields.is_empty() {
+					(*name).into()
+				} else {
+					json!({ (*name): self.decode_fields(fields, input)? })
+				})
+			},
+			None => {},
+		}
+
+		Ok(match (name, params.as_slice()) {
+			("bool", []) => bool::decode(input)?.into(),
+			("u8", []) => u8::decode(input)?.into(),
+			("u16", []) => u16::decode(input)?.into(),
+			("u32", []) => u32::decode(input)?.into(),
+			("u64", []) => u64::decode(input)?.into(),
+			("u128", []) => number(u128::decode(input)?),
+			("i8", []) => i8::decode(input)?.into(),
+			("i16", []) => i16::decode(input)?.into(),
+			("i32", []) => i32::decode(input)?.into(),
+			("i64", []) => i64::decode(input)?.into(),
+			("U256", []) => {
+				let value = U256::decode(input)?;
+				if value <= U256::from(u64::max_value()) {
+					value.low_u64().into()
+				} else {
+					value.to_string().into()
+				}
+			},
+			("Compact", [_]) => number(<Compact<u128>>::decode(input)?.0),
+			("Box", [inner]) => self.decode_value(inner, input)?,
+			("Vec", ["u8"]) | ("Bytes", []) => hex_value(&<Vec<u8>>::decode(input)?),
+			("Vec", [inner]) => {
+				let len = <Compact<u32>>::decode(input)?.0;
+				let mut items = Vec::new();
+				for _ in 0..len {
+					items.push(self.decode_value(inner, input)?);
+				}
+				items.into()
+			},
+			("Option", [inner]) => match u8::decode(input)? {
+				0 => Value::Null,
+				1 => self.decode_value(inner, input)?,
+				_ => return Err(codec::Error::from("Invalid Option").into()),
+			},
+			("Result", [ok, err]) => match u8::decode(input)? {
+				0 => json!({ "Ok": self.decode_value(ok, input)? }),
+				1 => json!({ "Err": self.decode_value(err, input)? }),
+				_ => return Err(codec::Error::from("Invalid Result").into()),
+			},
+			("()", []) => Value::Null,
+			("()", items) => items.iter()
+				.map(|item| self.decode_value(item, input))
+				.collect::<Result<Vec<_>, _>>()?
+				.into(),
+			("Text", []) | ("String", []) => String::decode(input)?.into(),
+			("H160", []) => hex_value(&read_bytes(input, 20)?),
+			("H256", []) | ("Hash", []) => hex_value(&read_bytes(input, 32)?),
+			("H512", []) => hex_value(&read_bytes(input, 64)?),
+			("AccountId", []) => AccountId32::decode(input)?.to_ss58check().into(),
+			("LookupSource", []) | ("Address", []) => decode_address(input)?,
+			("Era", []) => match Era::decode(input)? {
+				Era::Immortal => "Immortal".into(),
+				Era::Mortal(period, phase) => json!({ "Mortal": { "period": period, "phase": phase } }),
+			},
+			("Call", []) => self.decode_call(input)?,
+			_ => return Err(Error::UnknownType(ty.clone())),
+		})
+	}
+
+	/// Whether [`decode_value`](Self::decode_value) knows the type named `ty`.
+	fn is_known(&self, ty: &str) -> bool {
+		let ty = normalize(ty);
+		if ty.starts_with('[') && ty.ends_with(']') {
+			let mut parts = ty[1..ty.len() - 1].splitn(2, ';');
+			return match (parts.next(), parts.next().and_then(|len| len.trim().parse::<usize>().ok())) {
+				(Some(inner), Some(_)) => self.is_known(inner.trim()),
+				_ => false,
+			}
+		}
+		let (name, params) = split_generics(&ty);
+		match self.registry.types.get(name) {
+			Some(TypeDef::Alias(alias)) => return self.is_known(alias),
+			Some(TypeDef::Struct(fields)) => return fields.iter().all(|(_, ty)| self.is_known(ty)),
+			Some(TypeDef::Enum(variants)) => return variants.iter()
+				.all(|(_, fields)| fields.iter().all(|(_, ty)| self.is_known(ty))),
+			None => {},
+		}
+		match (name, params.as_slice()) {
+			("bool", []) | ("u8", []) | ("u16", []) | ("u32", []) | ("u64", []) | ("u128", []) |
+			("i8", []) | ("i16", []) | ("i32", []) | ("i64", []) | ("U256", []) | ("Compact", [_]) |
+			("Bytes", []) | ("()", []) | ("Text", []) | ("String", []) | ("H160", []) | ("H256", []) |
+			("Hash", []) | ("H512", []) | ("AccountId", []) | ("LookupSource", []) | ("Address", []) |
+			("Era", []) | ("Call", []) => true,
+			("Box", [inner]) | ("Vec", [inner]) | ("Option", [inner]) => self.is_known(inner),
+			("Result", [ok, err]) => self.is_known(ok) && self.is_known(err),
+			("()", items) => items.iter().all(|item| self.is_known(item)),
+			_ => false,
+		}
+	}
+
+	fn decode_array(&self, ty: &str, input: &mut &[u8]) -> Result<Value, Error> {
+		let mut parts = ty[1..ty.len() - 1].splitn(2, ';');
+		let (inner, len) = match (parts.next(), parts.next().and_then(|len| len.trim().parse().ok())) {
+			(Some(inner), Some(len)) => (inner.trim(), len),
+			_ => return Err(Error::UnknownType(ty.into())),
+		};
+		if inner == "u8" {
+			return Ok(hex_value(&read_bytes(input, len)?))
+		}
+		let mut items = Vec::new();
+		for _ in 0..len {
+			items.push(self.decode_value(inner, input)?);
+		}
+		Ok(items.into())
+	}
+
+	/// Fields are given as an object keyed by their name, or as an array if unnamed.
+	fn decode_fields(&self, fields: &[(&str, &str)], input: &mut &[u8]) -> Result<Value, Error> {
+		match fields {
+			[("", ty)] => self.decode_value(ty, input),
+			_ if fields.iter().all(|(name, _)| name.is_empty()) => fields.iter()
+				.map(|(_, ty)| self.decode_value(ty, input))
+				.collect::<Result<Vec<_>, _>>()
+				.map(Into::into),
+			_ => {
+				let mut object = Map::new();
+				for (name, ty) in fields {
+					object.insert(name.to_string(), self.decode_value(ty, input)?);
+				}
+				Ok(object.into())
+			},
+		}
+	}
+}
+
+/// An address of `pallet-indices`, as `{ "id" }` or `{ "index" }`.
+fn decode_address(input: &mut &[u8]) -> Result<Value, Error> {
+	Ok(match u8::decode(input)? {
+		x @ 0x00..=0xef => json!({ "index": x }),
+		0xfc => json!({ "index": u16::decode(input)? }),
+		0xfd => json!({ "index": u32::decode(input)? }),
+		0xfe => json!({ "index": u64::decode(input)? }),
+		0xff => json!({ "id": AccountId32::decode(input)?.to_ss58check() }),
+		_ => return Err(codec::Error::from("Invalid address variant").into()),
+	})
+}
+
+fn ensure_consumed(input: &[u8]) -> Result<(), Error> {
+	if input.is_empty() {
+		Ok(())
+	} else {
+		Err(Error::Metadata(format!("{} bytes left after decoding", input.len())))
+	}
+}
+
+fn read_bytes(input: &mut &[u8], len: usize) -> Result<Vec<u8>, Error> {
+	let mut bytes = vec![0u8; len];
+	input.read(&mut bytes)?;
+	Ok(bytes)
+}
+
+fn hex_value(bytes: &[u8]) -> Value {
+	format!("0x{}", hex::encode(bytes)).into()
+}
+
+fn number(value: u128) -> Value {
+	if value <= u64::max_value() as u128 {
+		(value as u64).into()
+	} else {
+		value.to_string().into()
+	}
+}
+
+/// Strip the trait qualification from a type name, e.g. `<T as Trait>::Balance` to `Balance`.
+fn normalize(ty: &str) -> String {
+	let ty = ty.split_whitespace().collect::<Vec<_>>().join(" ");
+	if ty.ends_with("as StaticLookup>::Source") {
+		return "LookupSource".into()
+	}
+	let ty = match ty.rfind(">::") {
+		Some(index) if ty.starts_with('<') => &ty[index + 3..],
+		_ => &ty,
+	};
+	let ty = ty.trim_start_matches("T::");
+	match ty.find("Of<") {
+		// `BalanceOf<T>`
+		Some(index) if !ty[..index].contains('<') && !ty[index + 3..].contains('<') => ty[..index].into(),
+		_ => ty.into(),
+	}
+}
+
+/// Split a type name into its name and generic parameters, tuples are named `()`.
+fn split_generics(ty: &str) -> (&str, Vec<&str>) {
+	let (name, inner) = if ty.starts_with('(') && ty.ends_with(')') {
+		("()", &ty[1..ty.len() - 1])
+	} else {
+		match (ty.find('<'), ty.ends_with('>')) {
+			(Some(index), true) => (&ty[..index], &ty[index + 1..ty.len() - 1]),
+			_ => return (ty, Vec::new()),
+		}
+	};
+	let mut params = Vec::new();
+	let mut depth = 0;
+	let mut start = 0;
+	for (index, c) in inner.char_indices() {
+		match c {
+			'<' | '(' | '[' => depth += 1,
+			'>' | ')' | ']' => depth -= 1,
+			',' if depth == 0 => {
+				params.push(inner[start..index].trim());
+				start = index + 1;
+			},
+			_ => {},
+		}
+	}
+	if !inner[start..].trim().is_empty() {
+		params.push(inner[start..].trim());
+	}
+	(name, params)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use codec::Encode;
+	use frame_metadata::{
+		EventMetadata, ExtrinsicMetadata, FunctionArgumentMetadata, FunctionMetadata,
+		RuntimeMetadataV12, StorageEntryMetadata, StorageMetadata,
+	};
+
+	const ATTRIBUTE: TypeDef = TypeDef::Struct(&[
+		("name", "Vec<u8>"),
+		("value", "Vec<u8>"),
+		("validity", "BlockNumber"),
+		("creation", "Moment"),
+		("nonce", "u64"),
+	]);
+
+	fn registry() -> TypeRegistry {
+		TypeRegistry::default()
+			.with_aliases(&[
+				("Balance", "u128"),
+				("BlockNumber", "u32"),
+				("Index", "u32"),
+				("Moment", "u64"),
+				("MissionTokenId", "u32"),
+				("Signature", "MultiSignature"),
+			])
+			.with_type("Attribute", ATTRIBUTE)
+	}
+
+	fn d<B: 'static, T: 'static>(value: T) -> DecodeDifferent<B, Vec<T>> {
+		DecodeDifferent::Decoded(vec![value])
+	}
+
+	fn s(value: &str) -> DecodeDifferent<&'static str, String> {
+		DecodeDifferent::Decoded(value.into())
+	}
+
+	fn metadata() -> RuntimeMetadataPrefixed {
+		let module = ModuleMetadata {
+			name: s("MissionTokens"),
+			storage: Some(DecodeDifferent::Decoded(StorageMetadata {
+				prefix: s("MissionTokens"),
+				entries: d(StorageEntryMetadata {
+					name: s("TotalIssuance"),
+					modifier: StorageEntryModifier::Default,
+					ty: StorageEntryType::Map {
+						hasher: StorageHasher::Blake2_128Concat,
+						key: s("T::MissionTokenId"),
+						value: s("T::Balance"),
+						unused: false,
+					},
+					default: DecodeDifferent::Decoded(0u128.encode()),
+					documentation: DecodeDifferent::Decoded(vec![]),
+				}),
+			})),
+			calls: Some(d(FunctionMetadata {
+				name: s("transfer"),
+				arguments: DecodeDifferent::Decoded(vec![
+					FunctionArgumentMetadata { name: s("token_id"), ty: s("T::MissionTokenId") },
+					FunctionArgumentMetadata { name: s("dest"), ty: s("<T::Lookup as StaticLookup>::Source") },
+					FunctionArgumentMetadata { name: s("value"), ty: s("Compact<T::Balance>") },
+				]),
+				documentation: DecodeDifferent::Decoded(vec![]),
+			})),
+			event: Some(DecodeDifferent::Decoded(vec![
+				EventMetadata {
+					name: s("Transferred"),
+					arguments: DecodeDifferent::Decoded(vec!["MissionTokenId".into(), "AccountId".into(), "Balance".into()]),
+					documentation: DecodeDifferent::Decoded(vec![]),
+				},
+				EventMetadata {
+					name: s("Bridged"),
+					arguments: DecodeDifferent::Decoded(vec!["MissionTokenId".into(), "ResourceId".into()]),
+					documentation: DecodeDifferent::Decoded(vec![]),
+				},
+			])),
+			constants: DecodeDifferent::Decoded(vec![]),
+			errors: DecodeDifferent::Decoded(vec![]),
+			index: 7,
+		};
+		RuntimeMetadataPrefixed(0x6174656d, RuntimeMetadata::V12(RuntimeMetadataV12 {
+			modules: d(module),
+			extrinsic: ExtrinsicMetadata {
+				version: 4,
+				signed_extensions: vec![s("CheckGenesis"), s("CheckEra"), s("CheckNonce")],
+			},
+		}))
+	}
+
+	fn alice() -> AccountId32 {
+		AccountId32::from([1u8; 32])
+	}
+
+	#[test]
+	fn should_decode_registered_types() {
+		let registry = registry();
+		let metadata = metadata();
+		let decoder = MetadataDecoder::new(&metadata, &registry).unwrap();
+		let decode = |ty: &str, data: Vec<u8>| {
+			let input = &mut &data[..];
+			let value = decoder.decode_value(ty, input);
+			assert!(input.is_empty());
+			value
+		};
+
+		let attribute = (b"name".to_vec(), b"value".to_vec(), 10u32, 20u64, 1u64).encode();
+		assert_eq!(
+			decode("Attribute<T::BlockNumber, T::Moment>", attribute).unwrap(),
+			json!({ "name": "0x6e616d65", "value": "0x76616c7565", "validity": 10, "creation": 20, "nonce": 1 }),
+		);
+		assert_eq!(decode("BalanceOf<T>", u128::max_value().encode()).unwrap(), json!(u128::max_value().to_string()));
+		assert_eq!(
+			decode("Vec<(T::AccountId, Option<T::BlockNumber>)>", vec![(alice(), Some(5u32))].encode()).unwrap(),
+			json!([[alice().to_ss58check(), 5]]),
+		);
+		assert_eq!(decode("Signature", (1u8, [2u8; 64]).encode()).unwrap(), json!({ "Sr25519": hex_value(&[2u8; 64]) }));
+		assert_eq!(
+			decode("DispatchError", (3u8, 4u8, 5u8).encode()).unwrap(),
+			json!({ "Module": { "index": 4, "error": 5 } }),
+		);
+		assert_eq!(decode("LookupSource", (0xfcu8, 0x100u16).encode()).unwrap(), json!({ "index": 0x100 }));
+		assert_eq!(decode("U256", U256::from(7).encode()).unwrap(), json!(7));
+		assert_eq!(decode("U256", U256::max_value().encode()).unwrap(), json!(U256::max_value().to_string()));
+		assert_eq!(decode("Option<DispatchResult>", (1u8, 0u8).encode()).unwrap(), json!({ "Ok": null }));
+		assert!(matches!(decode("Unknown", vec![]), Err(Error::UnknownType(ty)) if ty == "Unknown"));
+	}
+
+	#[test]
+	fn should_decode_extrinsics_events_and_storage() {
+		let registry = registry();
+		let metadata = metadata();
+		let decoder = MetadataDecoder::new(&metadata, &registry).unwrap();
+
+		let call = (7u8, 0u8, 3u32, 0xffu8, alice(), Compact(100u128));
+		let extrinsic = (0x84u8, 0xffu8, alice(), 1u8, [2u8; 64], Era::Immortal, Compact(5u32), call.clone()).encode();
+		assert_eq!(decoder.decode_extrinsic(&extrinsic.encode()).unwrap(), json!({
+			"signature": {
+				"signer": { "id": alice().to_ss58check() },
+				"signature": { "Sr25519": hex_value(&[2u8; 64]) },
+				"extra": { "CheckEra": "Immortal", "CheckNonce": 5 },
+			},
+			"call": {
+				"pallet": "MissionTokens",
+				"call": "transfer",
+				"args": { "token_id": 3, "dest": { "id": alice().to_ss58check() }, "value": 100 },
+			},
+		}));
+		assert!(decoder.decode_extrinsic(&(0x04u8, call, 0u8).encode().encode()).is_err());
+
+		let events = vec![(0u8, 1u32, 7u8, 0u8, 3u32, alice(), 100u128, vec![[9u8; 32]])].encode();
+		assert_eq!(decoder.decode_events(&events).unwrap(), json!([{
+			"phase": { "ApplyExtrinsic": 1 },
+			"pallet": "MissionTokens",
+			"event": "Transferred",
+			"args": [3, alice().to_ss58check(), 100],
+			"topics": [hex_value(&[9u8; 32])],
+		}]));
+
+		assert_eq!(decoder.unknown_event_types().unwrap(), vec!["ResourceId".to_string()]);
+		let events = (
+			Compact(2u32),
+			(0u8, 1u32, 7u8, 0u8, 3u32, alice(), 100u128, Vec::<[u8; 32]>::new()),
+			(2u8, 7u8, 1u8, 3u32, [4u8; 32], Vec::<[u8; 32]>::new()),
+		).encode();
+		let records = decoder.decode_events(&events).unwrap();
+		assert_eq!(records[0]["args"], json!([3, alice().to_ss58check(), 100]));
+		assert_eq!(records[1], json!({
+			"phase": "Initialization",
+			"pallet": "MissionTokens",
+			"event": "Bridged",
+			"raw": hex_value(&(3u32, [4u8; 32], Vec::<[u8; 32]>::new()).encode()),
+		}));
+
+		let mut key = decoder.storage_prefix("MissionTokens.TotalIssuance").unwrap();
+		key.extend(&sp_core::hashing::blake2_128(&3u32.encode()));
+		key.extend(&3u32.encode());
+		assert_eq!(decoder.decode_storage(&key, Some(&50u128.encode()[..])).unwrap(), json!({
+			"pallet": "MissionTokens",
+			"item": "TotalIssuance",
+			"keys": [3],
+			"value": 50,
+		}));
+		assert_eq!(decoder.decode_storage(&key, None).unwrap()["value"], json!(0));
+		assert!(decoder.storage_prefix("MissionTokens.Unknown").is_err());
+	}
+}
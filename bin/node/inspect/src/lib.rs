@@ -21,11 +21,15 @@
 //!
 //! The blocks and extrinsics can either be retrieved from the database (on-chain),
 //! or a raw SCALE-encoding can be provided.
+//!
+//! Besides the `Debug` output of the native runtime, blocks, extrinsics, events and storage
+//! can be decoded to JSON with the metadata of the runtime stored on chain, see [`decode`].
 
 #![warn(missing_docs)]
 
 pub mod cli;
 pub mod command;
+pub mod decode;
 
 use std::{
 	fmt,
@@ -34,13 +38,19 @@ use std::{
 	str::FromStr,
 };
 use codec::{Encode, Decode};
-use sc_client_api::BlockBackend;
+use frame_metadata::RuntimeMetadataPrefixed;
+use sc_client_api::{
+	BlockBackend, CallExecutor, ExecutionStrategy, ExecutorProvider, StorageProvider,
+};
+use sc_service::client::Client;
+use serde_json::{json, Value};
 use sp_blockchain::HeaderBackend;
-use sp_core::hexdisplay::HexDisplay;
+use sp_core::{hexdisplay::HexDisplay, storage::{StorageData, StorageKey}};
 use sp_runtime::{
 	generic::BlockId,
-	traits::{Block, HashFor, NumberFor, Hash}
+	traits::{Block, HashFor, NumberFor, Hash, Header, SaturatedConversion, Zero}
 };
+use decode::{MetadataDecoder, TypeRegistry};
 
 /// A helper type for a generic block input.
 pub type BlockAddressFor<TBlock> = BlockAddress<
@@ -88,6 +98,8 @@ pub enum Error {
 	Blockchain(sp_blockchain::Error),
 	/// Given block has not been found.
 	NotFound(String),
+	/// Could not decode data by the metadata of the chain.
+	Decode(decode::Error),
 }
 
 impl std::error::Error for Error {
@@ -96,24 +108,67 @@ impl std::error::Error for Error {
 			Self::Codec(ref e) => Some(e),
 			Self::Blockchain(ref e) => Some(e),
 			Self::NotFound(_) => None,
+			Self::Decode(ref e) => Some(e),
 		}
 	}
 }
 
-/// A helper trait to access block headers and bodies.
+/// A helper trait to access the runtime metadata and the storage of blocks.
+pub trait StateAccess<TBlock: Block> {
+	/// The SCALE encoded metadata of the runtime in the state of the given block.
+	fn metadata(&self, id: &BlockId<TBlock>) -> sp_blockchain::Result<Vec<u8>>;
+
+	/// The value of a storage key in the state of the given block.
+	fn storage(&self, id: &BlockId<TBlock>, key: &StorageKey) -> sp_blockchain::Result<Option<StorageData>>;
+
+	/// The storage keys with the given prefix and their values in the state of the given block.
+	fn storage_pairs(
+		&self,
+		id: &BlockId<TBlock>,
+		prefix: &StorageKey,
+	) -> sp_blockchain::Result<Vec<(StorageKey, StorageData)>>;
+}
+
+impl<BE, E, TBlock, RA> StateAccess<TBlock> for Client<BE, E, TBlock, RA> where
+	BE: sc_client_api::Backend<TBlock>,
+	E: CallExecutor<TBlock>,
+	TBlock: Block,
+{
+	fn metadata(&self, id: &BlockId<TBlock>) -> sp_blockchain::Result<Vec<u8>> {
+		// The wasm code of the block is executed if the native runtime is of another version,
+		// so that the metadata of older runtimes can be retrieved.
+		self.executor().call(id, "Metadata_metadata", &[], ExecutionStrategy::NativeElseWasm, None)
+	}
+
+	fn storage(&self, id: &BlockId<TBlock>, key: &StorageKey) -> sp_blockchain::Result<Option<StorageData>> {
+		StorageProvider::storage(self, id, key)
+	}
+
+	fn storage_pairs(
+		&self,
+		id: &BlockId<TBlock>,
+		prefix: &StorageKey,
+	) -> sp_blockchain::Result<Vec<(StorageKey, StorageData)>> {
+		StorageProvider::storage_pairs(self, id, prefix)
+	}
+}
+
+/// A helper trait to access block headers and bodies, and their state.
 pub trait ChainAccess<TBlock: Block>:
 	HeaderBackend<TBlock> +
-	BlockBackend<TBlock>
+	BlockBackend<TBlock> +
+	StateAccess<TBlock>
 {}
 
 impl<T, TBlock> ChainAccess<TBlock> for T where
 	TBlock: Block,
-	T: sp_blockchain::HeaderBackend<TBlock> + sc_client_api::BlockBackend<TBlock>,
+	T: sp_blockchain::HeaderBackend<TBlock> + sc_client_api::BlockBackend<TBlock> + StateAccess<TBlock>,
 {}
 
 /// Blockchain inspector.
 pub struct Inspector<TBlock: Block, TPrinter: PrettyPrinter<TBlock> = DebugPrinter> {
 	printer: TPrinter,
+	registry: TypeRegistry,
 	chain: Box<dyn ChainAccess<TBlock>>,
 	_block: PhantomData<TBlock>,
 }
@@ -134,10 +189,17 @@ impl<TBlock: Block, TPrinter: PrettyPrinter<TBlock>> Inspector<TBlock, TPrinter>
 		Inspector {
 			chain: Box::new(chain) as _,
 			printer,
+			registry: Default::default(),
 			_block: Default::default(),
 		}
 	}
 
+	/// Customize the types known when decoding by the metadata of the chain.
+	pub fn with_registry(mut self, registry: TypeRegistry) -> Self {
+		self.registry = registry;
+		self
+	}
+
 	/// Get a pretty-printed block.
 	pub fn block(&self, input: BlockAddressFor<TBlock>) -> Result<String, Error> {
 		struct BlockPrinter<'a, A, B>(A, &'a B);
@@ -206,6 +268,144 @@ impl<TBlock: Block, TPrinter: PrettyPrinter<TBlock>> Inspector<TBlock, TPrinter>
 
 		Ok(format!("{}", ExtrinsicPrinter(ext, &self.printer)))
 	}
+
+	/// Get a block decoded by the metadata of the chain, as JSON.
+	///
+	/// A block of the chain is decoded by the runtime that produced it and includes its events,
+	/// raw bytes are decoded by the runtime of the best block.
+	pub fn block_json(&self, input: BlockAddressFor<TBlock>) -> Result<String, Error> {
+		let on_chain = !matches!(input, BlockAddress::Bytes(_));
+		let block = self.get_block(input)?;
+		let header = block.header();
+		let at = if on_chain { runtime_of::<TBlock>(header) } else { self.best() };
+		let metadata = self.metadata(&at)?;
+		let decoder = MetadataDecoder::new(&metadata, &self.registry)?;
+
+		let extrinsics = block.extrinsics().iter()
+			.map(|extrinsic| decoder.decode_extrinsic(&extrinsic.encode()))
+			.collect::<Result<Vec<_>, _>>()?;
+		let events = if on_chain {
+			self.decode_events(&decoder, &BlockId::hash(header.hash()))?
+		} else {
+			Value::Null
+		};
+		Ok(to_json(json!({
+			"number": (*header.number()).saturated_into::<u64>(),
+			"hash": format!("{:?}", header.hash()),
+			"parentHash": format!("{:?}", header.parent_hash()),
+			"extrinsics": extrinsics,
+			"events": events,
+		})))
+	}
+
+	/// Get an extrinsic decoded by the metadata of the chain, as JSON.
+	///
+	/// An extrinsic of the chain is decoded by the runtime that included it, raw bytes are
+	/// decoded by the runtime of the best block.
+	pub fn extrinsic_json(
+		&self,
+		input: ExtrinsicAddress<<HashFor<TBlock> as Hash>::Output, NumberFor<TBlock>>,
+	) -> Result<String, Error> {
+		let (at, extrinsic) = match input {
+			ExtrinsicAddress::Block(block, index) => {
+				let block = self.get_block(block)?;
+				let extrinsic = block.extrinsics()
+					.get(index)
+					.ok_or_else(|| Error::NotFound(format!(
+						"Could not find extrinsic {} in block {:?}", index, block
+					)))?
+					.encode();
+				(runtime_of::<TBlock>(block.header()), extrinsic)
+			},
+			ExtrinsicAddress::Bytes(bytes) => (self.best(), bytes),
+		};
+		let metadata = self.metadata(&at)?;
+		let decoder = MetadataDecoder::new(&metadata, &self.registry)?;
+		Ok(to_json(decoder.decode_extrinsic(&extrinsic)?))
+	}
+
+	/// Get the events of a block of the chain decoded by its metadata, as JSON.
+	pub fn events_json(&self, input: BlockAddressFor<TBlock>) -> Result<String, Error> {
+		let header = self.header(self.block_id(input)?)?;
+		let metadata = self.metadata(&runtime_of::<TBlock>(&header))?;
+		let decoder = MetadataDecoder::new(&metadata, &self.registry)?;
+		Ok(to_json(self.decode_events(&decoder, &BlockId::hash(header.hash()))?))
+	}
+
+	/// Get storage decoded by the metadata of the chain at the given block, as JSON.
+	///
+	/// `key` is either a hex encoded storage key, or a storage item as `Prefix.Item`, of which
+	/// all the values are returned. The best block is used if no block is given.
+	pub fn storage_json(
+		&self,
+		key: &str,
+		at: Option<BlockAddressFor<TBlock>>,
+	) -> Result<String, Error> {
+		let at = match at {
+			Some(at) => BlockId::hash(self.header(self.block_id(at)?)?.hash()),
+			None => self.best(),
+		};
+		let metadata = self.metadata(&at)?;
+		let decoder = MetadataDecoder::new(&metadata, &self.registry)?;
+
+		let value = if key.starts_with("0x") {
+			let key = sp_core::bytes::from_hex(key)
+				.map_err(|e| Error::NotFound(format!("Invalid storage key {}: {}", key, e)))?;
+			let value = self.chain.storage(&at, &StorageKey(key.clone()))?;
+			decoder.decode_storage(&key, value.as_ref().map(|value| &value.0[..]))?
+		} else {
+			let prefix = StorageKey(decoder.storage_prefix(key)?);
+			self.chain.storage_pairs(&at, &prefix)?.into_iter()
+				.map(|(key, value)| decoder.decode_storage(&key.0, Some(&value.0[..])))
+				.collect::<Result<Vec<_>, _>>()?
+				.into()
+		};
+		Ok(to_json(value))
+	}
+
+	fn decode_events(&self, decoder: &MetadataDecoder, at: &BlockId<TBlock>) -> Result<Value, Error> {
+		let key = StorageKey(decoder.storage_prefix("System.Events")?);
+		Ok(match self.chain.storage(at, &key)? {
+			Some(events) => decoder.decode_events(&events.0)?,
+			None => Value::Array(Vec::new()),
+		})
+	}
+
+	fn metadata(&self, at: &BlockId<TBlock>) -> Result<RuntimeMetadataPrefixed, Error> {
+		// The runtime returns the metadata as `OpaqueMetadata`, i.e. as encoded bytes.
+		let metadata = <Vec<u8>>::decode(&mut &self.chain.metadata(at)?[..])?;
+		Ok(RuntimeMetadataPrefixed::decode(&mut &metadata[..])?)
+	}
+
+	fn block_id(&self, input: BlockAddressFor<TBlock>) -> Result<BlockId<TBlock>, Error> {
+		match input {
+			BlockAddress::Hash(hash) => Ok(BlockId::hash(hash)),
+			BlockAddress::Number(number) => Ok(BlockId::number(number)),
+			BlockAddress::Bytes(_) => Err(Error::NotFound("Expected the hash or the number of a block".into())),
+		}
+	}
+
+	fn header(&self, id: BlockId<TBlock>) -> Result<TBlock::Header, Error> {
+		let not_found = format!("Could not find block {:?}", id);
+		self.chain.header(id)?.ok_or_else(|| Error::NotFound(not_found))
+	}
+
+	fn best(&self) -> BlockId<TBlock> {
+		BlockId::hash(self.chain.info().best_hash)
+	}
+}
+
+/// The block in whose state the runtime that produced the given block is, i.e. its parent.
+fn runtime_of<TBlock: Block>(header: &TBlock::Header) -> BlockId<TBlock> {
+	if header.number().is_zero() {
+		BlockId::hash(header.hash())
+	} else {
+		BlockId::hash(*header.parent_hash())
+	}
+}
+
+fn to_json(value: Value) -> String {
+	serde_json::to_string_pretty(&value).expect("JSON values can always be serialized; qed")
 }
 
 /// A block to retrieve.
@@ -17,10 +17,26 @@
 
 //! Some configurable implementations as associated type for the substrate runtime.
 
+use codec::Encode;
 use node_primitives::Balance;
-use sp_runtime::traits::Convert;
-use frame_support::traits::{OnUnbalanced, Currency};
-use crate::{Balances, Authorship, NegativeImbalance};
+use sp_core::{H160, H256, U256};
+use sp_runtime::{DispatchError, traits::Convert};
+use sp_std::prelude::*;
+use frame_support::{
+	storage::{IterableStorageMap, StorageMap},
+	traits::{OnUnbalanced, Currency, EnsureOrigin, ExistenceRequirement, Get},
+	weights::Weight,
+};
+use pallet_contracts::chain_extension::{ChainExtension, Environment};
+use pallet_evm::{
+	AddressMapping, Context, ExitError, ExitSucceed, Log, Precompiles, is_called_by_transaction,
+	precompiles::{ECRecover, Identity as IdentityPrecompile, Ripemd160, Sha256},
+};
+use pallet_metrics_reporter::{Metric, MetricsSource};
+use crate::{
+	AccountId, Balances, Authorship, Did, Evm, Identity, MissionTokens, NegativeImbalance, Runtime,
+	SocialTreasury, UsernameRegistry,
+};
 
 pub struct Author;
 impl OnUnbalanced<NegativeImbalance> for Author {
@@ -45,6 +61,731 @@ impl Convert<u128, Balance> for CurrencyToVoteHandler {
 	fn convert(x: u128) -> Balance { x * Self::factor() }
 }
 
+/// Accounts count as distinct persons for the quadratic funding of the social treasury when a
+/// registrar approved their username, or judged their identity to be good.
+pub struct VerifiedPersons;
+
+impl pallet_social_treasury::UniquePersons<AccountId> for VerifiedPersons {
+	fn is_unique_person(who: &AccountId) -> bool {
+		UsernameRegistry::has_approved_username(who) || Identity::identity(who).map_or(false, |id| {
+			id.judgements.iter().any(|(_, j)| matches!(
+				j,
+				pallet_identity::Judgement::KnownGood | pallet_identity::Judgement::Reasonable
+			))
+		})
+	}
+}
+
+/// Ensures `O` and acts for the pot of the social treasury, which cannot sign for itself.
+pub struct EnsureSocialTreasury<O>(sp_std::marker::PhantomData<O>);
+
+impl<Origin, O: EnsureOrigin<Origin>> EnsureOrigin<Origin> for EnsureSocialTreasury<O> {
+	type Success = AccountId;
+
+	fn try_origin(o: Origin) -> Result<AccountId, Origin> {
+		O::try_origin(o).map(|_| SocialTreasury::account_id())
+	}
+
+	#[cfg(feature = "runtime-benchmarks")]
+	fn successful_origin() -> Origin {
+		O::successful_origin()
+	}
+}
+
+/// Metrics of every mission: the issuance of its token, and the pot of the social treasury in it.
+pub struct MissionMetrics;
+
+impl MetricsSource for MissionMetrics {
+	fn metrics() -> Vec<Metric> {
+		let tokens = MissionTokens::min_mission_token_id()..=MissionTokens::max_mission_token_id();
+		tokens.flat_map(|token| vec![
+			Metric {
+				name: "mission_token_total_issuance",
+				labels: vec![("token", token.into())],
+				value: pallet_mission_tokens::TotalIssuance::<Runtime>::get(token),
+			},
+			Metric {
+				name: "social_treasury_pot",
+				labels: vec![("token", token.into())],
+				value: SocialTreasury::pot(token),
+			},
+		]).collect()
+	}
+}
+
+/// Number of registered usernames, and of decentralized identities updated on chain.
+///
+/// Counting iterates over the registrations, which is fine off chain.
+pub struct RegistrationMetrics;
+
+impl MetricsSource for RegistrationMetrics {
+	fn metrics() -> Vec<Metric> {
+		vec![
+			Metric {
+				name: "username_registrations",
+				labels: vec![],
+				value: pallet_username_registry::RegistrationOf::<Runtime>::iter().count() as u128,
+			},
+			Metric {
+				name: "did_identities",
+				labels: vec![],
+				value: pallet_did::UpdatedBy::<Runtime>::iter().count() as u128,
+			},
+		]
+	}
+}
+
+/// Mission tokens, decentralized identities and usernames for contracts.
+///
+/// Each function decodes its input as a SCALE encoded tuple, and returns `0` on success:
+///
+/// - `MISSION_TOKEN_BALANCE`: `(token_id, who)` writes the free `Balance` of `who`.
+/// - `MISSION_TOKEN_TRANSFER`: `(token_id, dest, value)` transfers `value` from the contract to
+///   `dest`, returning `1` if the transfer failed.
+/// - `DID_VALID_ATTRIBUTE`: `(identity, name, value)` returns `1` unless `identity` has the
+///   attribute `name` set to `value` and not expired.
+/// - `DID_VALID_DELEGATE`: `(identity, delegate_type, delegate)` returns `1` unless `delegate`
+///   is a valid delegate of `identity` of `delegate_type`.
+/// - `USERNAME_LOOKUP`: `username` writes the `AccountId` registered under `username`, returning
+///   `1` if there is none.
+///
+/// Gas is charged for the storage accessed by each function.
+pub struct SocialChainExtension;
+
+impl SocialChainExtension {
+	pub const MISSION_TOKEN_BALANCE: u32 = 0x0001;
+	pub const MISSION_TOKEN_TRANSFER: u32 = 0x0002;
+	pub const DID_VALID_ATTRIBUTE: u32 = 0x0101;
+	pub const DID_VALID_DELEGATE: u32 = 0x0102;
+	pub const USERNAME_LOOKUP: u32 = 0x0201;
+
+	/// Weight of the work of a function, on top of its storage accesses.
+	const BASE_WEIGHT: Weight = 10_000_000;
+
+	fn charge(env: &mut Environment<Runtime>, reads: Weight, writes: Weight) -> Result<(), DispatchError> {
+		let db = <Runtime as frame_system::Trait>::DbWeight::get();
+		env.charge_weight(Self::BASE_WEIGHT.saturating_add(db.reads_writes(reads, writes)))
+	}
+}
+
+impl ChainExtension<Runtime> for SocialChainExtension {
+	fn call(func_id: u32, env: &mut Environment<Runtime>) -> Result<u32, DispatchError> {
+		match func_id {
+			Self::MISSION_TOKEN_BALANCE => {
+				Self::charge(env, 1, 0)?;
+				let (token_id, who): (u32, AccountId) = env.read_as()?;
+				env.write(&MissionTokens::free_balance(who, token_id).encode());
+				Ok(0)
+			},
+			Self::MISSION_TOKEN_TRANSFER => {
+				Self::charge(env, 4, 2)?;
+				let (token_id, dest, value): (u32, AccountId, Balance) = env.read_as()?;
+				let result = MissionTokens::validate_mission_token_id(token_id).and_then(|_| {
+					MissionTokens::do_transfer(
+						env.address(),
+						&dest,
+						token_id,
+						value,
+						ExistenceRequirement::AllowDeath,
+					)
+				});
+				Ok(if result.is_ok() { 0 } else { 1 })
+			},
+			Self::DID_VALID_ATTRIBUTE => {
+				Self::charge(env, 2, 0)?;
+				let (identity, name, value): (AccountId, Vec<u8>, Vec<u8>) = env.read_as()?;
+				Ok(if Did::valid_attribute(&identity, &name, &value).is_ok() { 0 } else { 1 })
+			},
+			Self::DID_VALID_DELEGATE => {
+				Self::charge(env, 2, 0)?;
+				let (identity, delegate_type, delegate): (AccountId, Vec<u8>, AccountId) =
+					env.read_as()?;
+				Ok(if Did::valid_delegate(&identity, &delegate_type, &delegate).is_ok() { 0 } else { 1 })
+			},
+			Self::USERNAME_LOOKUP => {
+				Self::charge(env, 1, 0)?;
+				let username: Vec<u8> = env.read_as()?;
+				match UsernameRegistry::registration_of(username) {
+					Some(registration) => {
+						env.write(&registration.account_id.encode());
+						Ok(0)
+					},
+					None => Ok(1),
+				}
+			},
+			_ => Err(DispatchError::Other("Unknown chain extension function")),
+		}
+	}
+}
+
+/// The arguments of an ABI encoded call, following its selector.
+struct AbiInput<'a>(&'a [u8]);
+
+impl<'a> AbiInput<'a> {
+	/// Split `input` into the selector of the called function and its arguments.
+	fn new(input: &'a [u8]) -> Result<([u8; 4], Self), ExitError> {
+		if input.len() < 4 {
+			return Err(ExitError::Other("Input is too short"))
+		}
+		let mut selector = [0u8; 4];
+		selector.copy_from_slice(&input[..4]);
+		Ok((selector, AbiInput(&input[4..])))
+	}
+
+	fn word_at(&self, offset: usize) -> Result<&'a [u8], ExitError> {
+		let end = offset.checked_add(32).ok_or(ExitError::Other("Input is too short"))?;
+		self.0.get(offset..end).ok_or(ExitError::Other("Input is too short"))
+	}
+
+	fn usize_at(&self, offset: usize) -> Result<usize, ExitError> {
+		let value = U256::from_big_endian(self.word_at(offset)?);
+		if value > U256::from(u32::max_value()) {
+			return Err(ExitError::Other("Offset or length is too large"))
+		}
+		Ok(value.low_u32() as usize)
+	}
+
+	/// The `address` argument at `index`.
+	fn address(&self, index: usize) -> Result<H160, ExitError> {
+		Ok(H160::from_slice(&self.word_at(index * 32)?[12..]))
+	}
+
+	/// The `uint256` argument at `index`, which must fit a `Balance`.
+	fn balance(&self, index: usize) -> Result<Balance, ExitError> {
+		let value = U256::from_big_endian(self.word_at(index * 32)?);
+		if value > U256::from(Balance::max_value()) {
+			return Err(ExitError::Other("Value is too large"))
+		}
+		Ok(value.low_u128())
+	}
+
+	/// The `bytes32` argument at `index`, as an account.
+	fn account_id(&self, index: usize) -> Result<AccountId, ExitError> {
+		let mut account = [0u8; 32];
+		account.copy_from_slice(self.word_at(index * 32)?);
+		Ok(account.into())
+	}
+
+	/// The `bytes` argument at `index`.
+	fn bytes(&self, index: usize) -> Result<Vec<u8>, ExitError> {
+		let offset = self.usize_at(index * 32)?;
+		let len = self.usize_at(offset)?;
+		let start = offset.checked_add(32).ok_or(ExitError::Other("Input is too short"))?;
+		let end = start.checked_add(len).ok_or(ExitError::Other("Input is too short"))?;
+		self.0.get(start..end)
+			.map(|bytes| bytes.to_vec())
+			.ok_or(ExitError::Other("Input is too short"))
+	}
+}
+
+fn abi_uint(value: impl Into<U256>) -> Vec<u8> {
+	let mut word = vec![0u8; 32];
+	value.into().to_big_endian(&mut word);
+	word
+}
+
+fn abi_bool(value: bool) -> Vec<u8> {
+	abi_uint(value as u8)
+}
+
+/// The precompiles of the EVM.
+///
+/// Next to the builtin precompiles at addresses `1` to `4`, these are:
+///
+/// - at `DID`, answering `validDelegate(bytes32 identity, bytes delegateType, bytes32 delegate)`
+///   and `validAttribute(bytes32 identity, bytes name, bytes value)` with a `bool`.
+/// - at `MISSION_TOKENS + token_id`, an ERC-20 of each mission token, with `totalSupply`,
+///   `balanceOf`, `allowance`, `transfer`, `approve` and `transferFrom`.
+///
+/// EVM addresses hold mission tokens in the account they map to under the `AddressMapping` of
+/// the EVM. The `Transfer` and `Approval` logs of the ERC-20s are deposited as `Log` events of the
+/// EVM.
+///
+/// The precompiles write to the storage as they run, which the EVM does not revert along with the
+/// frame of a contract calling them. Only transactions failing as a whole are reverted, so
+/// `transfer`, `approve` and `transferFrom` are only accepted from transactions calling the
+/// ERC-20 directly, and fail when called by a contract.
+pub struct SocialPrecompiles;
+
+impl SocialPrecompiles {
+	/// Address of the DID precompile.
+	pub const DID: u64 = 0x0400;
+	/// Address of the ERC-20 of mission token `0`, the ERC-20 of a mission token being at this
+	/// address plus its id.
+	pub const MISSION_TOKENS: u64 = 1 << 32;
+
+	const TOTAL_SUPPLY: [u8; 4] = [0x18, 0x16, 0x0d, 0xdd];
+	const BALANCE_OF: [u8; 4] = [0x70, 0xa0, 0x82, 0x31];
+	const ALLOWANCE: [u8; 4] = [0xdd, 0x62, 0xed, 0x3e];
+	const TRANSFER: [u8; 4] = [0xa9, 0x05, 0x9c, 0xbb];
+	const APPROVE: [u8; 4] = [0x09, 0x5e, 0xa7, 0xb3];
+	const TRANSFER_FROM: [u8; 4] = [0x23, 0xb8, 0x72, 0xdd];
+	const VALID_DELEGATE: [u8; 4] = [0x07, 0x94, 0xf7, 0xde];
+	const VALID_ATTRIBUTE: [u8; 4] = [0xa8, 0x7a, 0x04, 0xa9];
+
+	/// `keccak256("Transfer(address,address,uint256)")`.
+	const TRANSFER_TOPIC: [u8; 32] = [
+		0xdd, 0xf2, 0x52, 0xad, 0x1b, 0xe2, 0xc8, 0x9b, 0x69, 0xc2, 0xb0, 0x68, 0xfc, 0x37, 0x8d, 0xaa,
+		0x95, 0x2b, 0xa7, 0xf1, 0x63, 0xc4, 0xa1, 0x16, 0x28, 0xf5, 0x5a, 0x4d, 0xf5, 0x23, 0xb3, 0xef,
+	];
+	/// `keccak256("Approval(address,address,uint256)")`.
+	const APPROVAL_TOPIC: [u8; 32] = [
+		0x8c, 0x5b, 0xe1, 0xe5, 0xeb, 0xec, 0x7d, 0x5b, 0xd1, 0x4f, 0x71, 0x42, 0x7d, 0x1e, 0x84, 0xf3,
+		0xdd, 0x03, 0x14, 0xc0, 0xf7, 0xb2, 0x29, 0x1e, 0x5b, 0x20, 0x0a, 0xc8, 0xc7, 0xc3, 0xb9, 0x25,
+	];
+
+	/// Gas of a call, on top of its storage accesses and logs.
+	const BASE_GAS: usize = 700;
+	/// Gas of a storage read, as `SLOAD`.
+	const READ_GAS: usize = 800;
+	/// Gas of a storage write, as `SSTORE` of a new value.
+	const WRITE_GAS: usize = 20_000;
+	/// Gas of a `Transfer` or `Approval` log, as `LOG3` with a word of data.
+	const LOG_GAS: usize = 1_756;
+
+	/// The address of the ERC-20 of `token_id`.
+	pub fn mission_token_address(token_id: u32) -> H160 {
+		H160::from_low_u64_be(Self::MISSION_TOKENS + token_id as u64)
+	}
+
+	/// The mission token of the ERC-20 at `address`, if any.
+	fn mission_token_id(address: H160) -> Option<u32> {
+		let low = address.to_low_u64_be();
+		if address[..12] != [0u8; 12] || low >> 32 != Self::MISSION_TOKENS >> 32 {
+			return None
+		}
+		let token_id = low as u32;
+		MissionTokens::validate_mission_token_id(token_id).ok().map(|_| token_id)
+	}
+
+	fn account(address: H160) -> AccountId {
+		<Runtime as pallet_evm::Trait>::AddressMapping::into_account_id(address)
+	}
+
+	fn cost(
+		target_gas: Option<usize>,
+		reads: usize,
+		writes: usize,
+		logs: usize,
+	) -> Result<usize, ExitError> {
+		let cost = Self::BASE_GAS + reads * Self::READ_GAS + writes * Self::WRITE_GAS +
+			logs * Self::LOG_GAS;
+		if target_gas.map_or(false, |target_gas| cost > target_gas) {
+			return Err(ExitError::OutOfGas)
+		}
+		Ok(cost)
+	}
+
+	fn deposit_log(address: H160, topic: [u8; 32], from: H160, to: H160, value: Balance) {
+		Evm::deposit_log(Log {
+			address,
+			topics: vec![H256::from(topic), H256::from(from), H256::from(to)],
+			data: abi_uint(value),
+		});
+	}
+
+	fn execute_did(
+		input: &[u8],
+		target_gas: Option<usize>,
+	) -> Result<(ExitSucceed, Vec<u8>, usize), ExitError> {
+		let (selector, args) = AbiInput::new(input)?;
+		let (valid, cost) = match selector {
+			Self::VALID_DELEGATE => {
+				let cost = Self::cost(target_gas, 2, 0, 0)?;
+				let (identity, delegate) = (args.account_id(0)?, args.account_id(2)?);
+				let valid = Did::valid_delegate(&identity, &args.bytes(1)?, &delegate).is_ok();
+				(valid, cost)
+			},
+			Self::VALID_ATTRIBUTE => {
+				let cost = Self::cost(target_gas, 2, 0, 0)?;
+				let identity = args.account_id(0)?;
+				let valid = Did::valid_attribute(&identity, &args.bytes(1)?, &args.bytes(2)?).is_ok();
+				(valid, cost)
+			},
+			_ => return Err(ExitError::Other("Unknown function")),
+		};
+		Ok((ExitSucceed::Returned, abi_bool(valid), cost))
+	}
+
+	fn execute_mission_token(
+		address: H160,
+		token_id: u32,
+		input: &[u8],
+		target_gas: Option<usize>,
+		context: &Context,
+	) -> Result<(ExitSucceed, Vec<u8>, usize), ExitError> {
+		let (selector, args) = AbiInput::new(input)?;
+		let caller = || if is_called_by_transaction(context) {
+			Ok(context.caller)
+		} else {
+			Err(ExitError::Other("Only callable by transactions"))
+		};
+		let dispatch_error = |e: DispatchError| ExitError::Other(e.into());
+		// the mission token is validated when resolving the address.
+		let (output, cost) = match selector {
+			Self::TOTAL_SUPPLY => {
+				let cost = Self::cost(target_gas, 3, 0, 0)?;
+				(abi_uint(pallet_mission_tokens::TotalIssuance::<Runtime>::get(token_id)), cost)
+			},
+			Self::BALANCE_OF => {
+				let cost = Self::cost(target_gas, 3, 0, 0)?;
+				let who = Self::account(args.address(0)?);
+				(abi_uint(MissionTokens::free_balance(who, token_id)), cost)
+			},
+			Self::ALLOWANCE => {
+				let cost = Self::cost(target_gas, 3, 0, 0)?;
+				let (owner, spender) = (Self::account(args.address(0)?), Self::account(args.address(1)?));
+				(abi_uint(MissionTokens::allowance((token_id, owner), spender)), cost)
+			},
+			Self::TRANSFER => {
+				let cost = Self::cost(target_gas, 6, 2, 1)?;
+				let (from, to, value) = (caller()?, args.address(0)?, args.balance(1)?);
+				MissionTokens::do_transfer(
+					&Self::account(from),
+					&Self::account(to),
+					token_id,
+					value,
+					ExistenceRequirement::AllowDeath,
+				).map_err(dispatch_error)?;
+				Self::deposit_log(address, Self::TRANSFER_TOPIC, from, to, value);
+				(abi_bool(true), cost)
+			},
+			Self::APPROVE => {
+				let cost = Self::cost(target_gas, 4, 1, 1)?;
+				let (owner, spender, amount) = (caller()?, args.address(0)?, args.balance(1)?);
+				MissionTokens::approve(&Self::account(owner), &Self::account(spender), token_id, amount)
+					.map_err(dispatch_error)?;
+				Self::deposit_log(address, Self::APPROVAL_TOPIC, owner, spender, amount);
+				(abi_bool(true), cost)
+			},
+			Self::TRANSFER_FROM => {
+				let cost = Self::cost(target_gas, 9, 3, 1)?;
+				let spender = caller()?;
+				let (from, to, value) = (args.address(0)?, args.address(1)?, args.balance(2)?);
+				MissionTokens::transfer_from(
+					&Self::account(spender),
+					&Self::account(from),
+					&Self::account(to),
+					token_id,
+					value,
+				).map_err(dispatch_error)?;
+				Self::deposit_log(address, Self::TRANSFER_TOPIC, from, to, value);
+				(abi_bool(true), cost)
+			},
+			_ => return Err(ExitError::Other("Unknown function")),
+		};
+		Ok((ExitSucceed::Returned, output, cost))
+	}
+}
+
+impl Precompiles for SocialPrecompiles {
+	fn execute(
+		address: H160,
+		input: &[u8],
+		target_gas: Option<usize>,
+		context: &Context,
+	) -> Option<Result<(ExitSucceed, Vec<u8>, usize), ExitError>> {
+		type Builtin = (ECRecover, Sha256, Ripemd160, IdentityPrecompile);
+		if let Some(result) = <Builtin as Precompiles>::execute(address, input, target_gas, context) {
+			return Some(result)
+		}
+		if address == H160::from_low_u64_be(Self::DID) {
+			return Some(Self::execute_did(input, target_gas))
+		}
+		Self::mission_token_id(address)
+			.map(|token_id| {
+				Self::execute_mission_token(address, token_id, input, target_gas, context)
+			})
+	}
+}
+
+#[cfg(test)]
+mod precompile_tests {
+	use super::*;
+	use pallet_evm::ExitReason;
+	use crate::{constants::currency::DOLLARS, Event, System};
+
+	fn call(selector: [u8; 4], args: &[&[u8]]) -> Vec<u8> {
+		let mut input = selector.to_vec();
+		args.iter().for_each(|arg| input.extend_from_slice(arg));
+		input
+	}
+
+	fn word(address: H160) -> H256 {
+		H256::from(address)
+	}
+
+	fn context(address: H160, caller: H160) -> Context {
+		Context { address, caller, apparent_value: U256::zero() }
+	}
+
+	fn new_test_ext() -> sp_io::TestExternalities {
+		let mut t: sp_io::TestExternalities =
+			frame_system::GenesisConfig::default().build_storage::<Runtime>().unwrap().into();
+		t.execute_with(|| System::set_block_number(1));
+		t
+	}
+
+	#[test]
+	fn mission_tokens_are_erc20s() {
+		new_test_ext().execute_with(|| {
+			let (alice, bob, charlie) = (H160::repeat_byte(1), H160::repeat_byte(2), H160::repeat_byte(3));
+			let token = SocialPrecompiles::mission_token_address(1);
+			let execute = |caller: H160, input: Vec<u8>| {
+				SocialPrecompiles::execute(token, &input, None, &context(token, caller))
+					.unwrap()
+					.map(|(_, output, _)| output)
+			};
+			let transact = |caller: H160, input: Vec<u8>| {
+				Evm::execute_call(caller, token, input, U256::zero(), 100_000, U256::one(), None, true)
+					.unwrap()
+			};
+			for who in &[alice, bob] {
+				let _ = Balances::deposit_creating(&SocialPrecompiles::account(*who), 10 * DOLLARS);
+			}
+			MissionTokens::mint(SocialPrecompiles::account(alice), 1, 100);
+
+			assert_eq!(execute(bob, call(SocialPrecompiles::TOTAL_SUPPLY, &[])), Ok(abi_uint(100u128)));
+			let balance_of = |who: H160| execute(bob, call(SocialPrecompiles::BALANCE_OF, &[word(who).as_bytes()]));
+			assert_eq!(balance_of(alice), Ok(abi_uint(100u128)));
+			let unknown_token = SocialPrecompiles::mission_token_address(18);
+			assert!(SocialPrecompiles::execute(unknown_token, &[], None, &context(unknown_token, alice)).is_none());
+			let not_a_token = H160::from_low_u64_be(1 << 33);
+			assert!(SocialPrecompiles::execute(not_a_token, &[], None, &context(not_a_token, alice)).is_none());
+
+			let transfer = call(SocialPrecompiles::TRANSFER, &[word(bob).as_bytes(), &abi_uint(40u128)]);
+			assert_eq!(execute(alice, transfer.clone()), Err(ExitError::Other("Only callable by transactions")));
+			let (reason, output, _, _) = transact(alice, transfer);
+			assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned));
+			assert_eq!(output, abi_bool(true));
+			assert_eq!(balance_of(alice), Ok(abi_uint(60u128)));
+			assert_eq!(balance_of(bob), Ok(abi_uint(40u128)));
+			assert!(System::events().iter().any(|record| matches!(
+				&record.event,
+				Event::pallet_evm(pallet_evm::RawEvent::Log(log))
+					if log.address == token && log.topics == vec![
+						H256::from(SocialPrecompiles::TRANSFER_TOPIC), word(alice), word(bob),
+					]
+			)));
+
+			let approve = call(SocialPrecompiles::APPROVE, &[word(bob).as_bytes(), &abi_uint(25u128)]);
+			assert_eq!(transact(alice, approve).1, abi_bool(true));
+			let allowance = call(SocialPrecompiles::ALLOWANCE, &[word(alice).as_bytes(), word(bob).as_bytes()]);
+			assert_eq!(execute(charlie, allowance.clone()), Ok(abi_uint(25u128)));
+			assert_eq!(
+				MissionTokens::allowance((1, SocialPrecompiles::account(alice)), SocialPrecompiles::account(bob)),
+				25,
+			);
+
+			let transfer_from = call(SocialPrecompiles::TRANSFER_FROM, &[
+				word(alice).as_bytes(), word(charlie).as_bytes(), &abi_uint(10u128),
+			]);
+			assert!(execute(bob, transfer_from.clone()).is_err());
+			assert_eq!(transact(bob, transfer_from).1, abi_bool(true));
+			assert_eq!(balance_of(alice), Ok(abi_uint(50u128)));
+			assert_eq!(balance_of(charlie), Ok(abi_uint(10u128)));
+			assert_eq!(execute(charlie, allowance), Ok(abi_uint(15u128)));
+		});
+	}
+
+	#[test]
+	fn contracts_cannot_move_mission_tokens() {
+		let (alice, bob) = (H160::repeat_byte(1), H160::repeat_byte(2));
+		let token = SocialPrecompiles::mission_token_address(1);
+
+		// contracts forwarding their input to the ERC-20, then stopping or reverting.
+		let mut forward = vec![
+			0x36, 0x60, 0x00, 0x60, 0x00, // CALLDATASIZE, PUSH1 0 (x2)
+			0x37, // CALLDATACOPY
+			0x60, 0x00, 0x60, 0x00, 0x36, 0x60, 0x00, 0x60, 0x00, // PUSH1 0 (x2), CALLDATASIZE, PUSH1 0 (x2)
+			0x64, // PUSH5
+		];
+		forward.extend_from_slice(&token.as_bytes()[15..]);
+		forward.extend_from_slice(&[
+			0x5a, // GAS
+			0xf1, // CALL
+			0x50, // POP
+		]);
+		let stopping = (H160::repeat_byte(3), [&forward[..], &[0x00]].concat()); // STOP
+		let reverting = (H160::repeat_byte(4), [&forward[..], &[
+			0x60, 0x00, 0x60, 0x00, // PUSH1 0 (x2)
+			0xfd, // REVERT
+		]].concat());
+
+		let mut t = frame_system::GenesisConfig::default().build_storage::<Runtime>().unwrap();
+		pallet_evm::GenesisConfig {
+			accounts: vec![stopping.clone(), reverting.clone()].into_iter()
+				.map(|(address, code)| (address, pallet_evm::GenesisAccount {
+					nonce: U256::one(),
+					balance: U256::zero(),
+					storage: Default::default(),
+					code,
+				}))
+				.collect(),
+		}.assimilate_storage::<Runtime>(&mut t).unwrap();
+		let mut t: sp_io::TestExternalities = t.into();
+
+		t.execute_with(|| {
+			System::set_block_number(1);
+			let _ = Balances::deposit_creating(&SocialPrecompiles::account(alice), 10 * DOLLARS);
+
+			let transfer = call(SocialPrecompiles::TRANSFER, &[word(bob).as_bytes(), &abi_uint(40u128)]);
+			for contract in &[stopping.0, reverting.0] {
+				MissionTokens::mint(SocialPrecompiles::account(*contract), 1, 100);
+				Evm::execute_call(alice, *contract, transfer.clone(), U256::zero(), 100_000, U256::one(), None, true)
+					.unwrap();
+				assert_eq!(MissionTokens::free_balance(SocialPrecompiles::account(*contract), 1), 100);
+			}
+			assert_eq!(MissionTokens::free_balance(SocialPrecompiles::account(bob), 1), 0);
+		});
+	}
+
+	#[test]
+	fn did_precompile_validates_delegates() {
+		new_test_ext().execute_with(|| {
+			let did = H160::from_low_u64_be(SocialPrecompiles::DID);
+			let identity = [1u8; 32];
+			let valid_delegate = |delegate: [u8; 32]| {
+				let mut delegate_type = b"sign".to_vec();
+				delegate_type.resize(32, 0);
+				let input = call(SocialPrecompiles::VALID_DELEGATE, &[
+					&identity, &abi_uint(96u8), &delegate, &abi_uint(4u8), &delegate_type,
+				]);
+				SocialPrecompiles::execute(did, &input, None, &context(did, did))
+					.unwrap()
+					.map(|(_, output, _)| output)
+			};
+
+			// an identity is its own delegate.
+			assert_eq!(valid_delegate(identity), Ok(abi_bool(true)));
+			assert_eq!(valid_delegate([2u8; 32]), Ok(abi_bool(false)));
+			assert_eq!(
+				SocialPrecompiles::execute(did, &SocialPrecompiles::VALID_DELEGATE, None, &context(did, did)),
+				Some(Err(ExitError::Other("Input is too short"))),
+			);
+			// offsets and lengths past the end of the input, which would overflow on 32 bits.
+			let input = call(SocialPrecompiles::VALID_DELEGATE, &[
+				&identity, &abi_uint(u32::max_value()), &identity,
+			]);
+			assert_eq!(
+				SocialPrecompiles::execute(did, &input, None, &context(did, did)),
+				Some(Err(ExitError::Other("Input is too short"))),
+			);
+			let input = call(SocialPrecompiles::VALID_DELEGATE, &[
+				&identity, &abi_uint(96u8), &identity, &abi_uint(u32::max_value()),
+			]);
+			assert_eq!(
+				SocialPrecompiles::execute(did, &input, None, &context(did, did)),
+				Some(Err(ExitError::Other("Input is too short"))),
+			);
+		});
+	}
+}
+
+#[cfg(test)]
+mod chain_extension_tests {
+	use super::*;
+	use frame_support::assert_ok;
+	use pallet_contracts::GasMeter;
+	use crate::{constants::currency::DOLLARS, System};
+
+	const GAS_LIMIT: Weight = 1_000_000_000_000;
+
+	fn new_test_ext() -> sp_io::TestExternalities {
+		let mut t: sp_io::TestExternalities =
+			frame_system::GenesisConfig::default().build_storage::<Runtime>().unwrap().into();
+		t.execute_with(|| System::set_block_number(1));
+		t
+	}
+
+	/// Call `func_id` as the contract at `address`, returning the result, the output and the gas
+	/// charged.
+	fn call(
+		func_id: u32,
+		address: &AccountId,
+		input: impl Encode,
+	) -> (Result<u32, DispatchError>, Vec<u8>, Weight) {
+		let mut gas_meter = GasMeter::<Runtime>::new(GAS_LIMIT);
+		let mut env = Environment::new(address.clone(), input.encode(), &mut gas_meter);
+		let result = SocialChainExtension::call(func_id, &mut env);
+		let output = env.into_output();
+		(result, output, GAS_LIMIT - gas_meter.gas_left())
+	}
+
+	fn charged(reads: Weight, writes: Weight) -> Weight {
+		let db = <Runtime as frame_system::Trait>::DbWeight::get();
+		SocialChainExtension::BASE_WEIGHT + db.reads_writes(reads, writes)
+	}
+
+	#[test]
+	fn contracts_transfer_their_mission_tokens() {
+		new_test_ext().execute_with(|| {
+			let (contract, bob) = (AccountId::from([1u8; 32]), AccountId::from([2u8; 32]));
+			let _ = Balances::deposit_creating(&contract, 10 * DOLLARS);
+			MissionTokens::mint(contract.clone(), 1, 100);
+
+			let balance_of = |who: &AccountId| {
+				call(SocialChainExtension::MISSION_TOKEN_BALANCE, &bob, (1u32, who.clone()))
+			};
+			assert_eq!(balance_of(&contract), (Ok(0), 100u128.encode(), charged(1, 0)));
+
+			let transfer = |token_id: u32, value: Balance| {
+				let input = (token_id, bob.clone(), value);
+				call(SocialChainExtension::MISSION_TOKEN_TRANSFER, &contract, input)
+			};
+			assert_eq!(transfer(1, 40), (Ok(0), vec![], charged(4, 2)));
+			assert_eq!(MissionTokens::free_balance(&contract, 1), 60);
+			assert_eq!(MissionTokens::free_balance(&bob, 1), 40);
+			assert_eq!(balance_of(&bob).1, 40u128.encode());
+
+			// failures are returned to the contract, and still charged.
+			assert_eq!(transfer(1, 61), (Ok(1), vec![], charged(4, 2)));
+			assert_eq!(transfer(18, 1), (Ok(1), vec![], charged(4, 2)));
+			assert_eq!(MissionTokens::free_balance(&contract, 1), 60);
+			assert_eq!(MissionTokens::free_balance(&bob, 1), 40);
+		});
+	}
+
+	#[test]
+	fn contracts_look_up_dids_and_usernames() {
+		new_test_ext().execute_with(|| {
+			let (contract, alice, bob) =
+				(AccountId::from([1u8; 32]), AccountId::from([2u8; 32]), AccountId::from([3u8; 32]));
+
+			let valid_delegate = |delegate: &AccountId| {
+				let input = (alice.clone(), b"sign".to_vec(), delegate.clone());
+				call(SocialChainExtension::DID_VALID_DELEGATE, &contract, input)
+			};
+			// an identity is its own delegate.
+			assert_eq!(valid_delegate(&alice), (Ok(0), vec![], charged(2, 0)));
+			assert_eq!(valid_delegate(&bob).0, Ok(1));
+
+			assert_ok!(Did::create_attribute(&alice, &alice, b"name", b"alice", None));
+			let valid_attribute = |value: &[u8]| {
+				let input = (alice.clone(), b"name".to_vec(), value.to_vec());
+				call(SocialChainExtension::DID_VALID_ATTRIBUTE, &contract, input)
+			};
+			assert_eq!(valid_attribute(b"alice"), (Ok(0), vec![], charged(2, 0)));
+			assert_eq!(valid_attribute(b"bob").0, Ok(1));
+
+			pallet_username_registry::RegistrationOf::<Runtime>::insert(
+				b"alice".to_vec(),
+				pallet_username_registry::Registration { judgements: vec![], account_id: alice.clone() },
+			);
+			let lookup = |username: &[u8]| {
+				call(SocialChainExtension::USERNAME_LOOKUP, &contract, username.to_vec())
+			};
+			assert_eq!(lookup(b"alice"), (Ok(0), alice.encode(), charged(1, 0)));
+			assert_eq!(lookup(b"bob"), (Ok(1), vec![], charged(1, 0)));
+
+			assert_eq!(
+				call(0xffff, &contract, ()).0,
+				Err(DispatchError::Other("Unknown chain extension function")),
+			);
+		});
+	}
+}
+
 #[cfg(test)]
 mod multiplier_tests {
 	use super::*;
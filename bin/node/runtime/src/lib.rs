@@ -49,7 +49,6 @@ use sp_runtime::{
 	impl_opaque_keys, generic, create_runtime_str, ModuleId, FixedPointNumber,
 	MultiSigner
 };
-use sp_runtime::curve::PiecewiseLinear;
 use sp_runtime::transaction_validity::{TransactionValidity, TransactionSource, TransactionPriority};
 use sp_runtime::traits::{
 	self, BlakeTwo256, Block as BlockT, StaticLookup, SaturatedConversion,
@@ -80,7 +79,10 @@ pub use pallet_staking::StakerStatus;
 
 /// Implementations of some helper traits passed into runtime modules as associated types.
 pub mod impls;
-use impls::{CurrencyToVoteHandler, Author};
+use impls::{
+	CurrencyToVoteHandler, Author, EnsureSocialTreasury, MissionMetrics, RegistrationMetrics,
+	SocialChainExtension, SocialPrecompiles, VerifiedPersons,
+};
 
 /// Constant values used within the runtime.
 pub mod constants;
@@ -111,10 +113,10 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	// and set impl_version to 0. If only runtime
 	// implementation changes and behavior does not, then leave spec_version as
 	// is and increment impl_version.
-	spec_version: 1,
+	spec_version: 2,
 	impl_version: 0,
 	apis: RUNTIME_API_VERSIONS,
-	transaction_version: 1,
+	transaction_version: 2,
 };
 
 /// Native version.
@@ -301,6 +303,18 @@ parameter_types! {
 	pub const ExpectedBlockTime: Moment = MILLISECS_PER_BLOCK;
 }
 
+/// The BABE epoch configuration of the runtime.
+///
+/// The choice of `c` parameter (where `1 - c` represents the probability of a slot being
+/// empty), is done in accordance to the slot duration and expected target block time, for
+/// safely resisting network delays of maximum two seconds.
+/// <https://research.web3.foundation/en/latest/polkadot/BABE/Babe/#6-practical-results>
+pub const BABE_EPOCH_CONFIG: sp_consensus_babe::BabeEpochConfiguration =
+	sp_consensus_babe::BabeEpochConfiguration {
+		c: PRIMARY_PROBABILITY,
+		allowed_slots: sp_consensus_babe::AllowedSlots::PrimaryAndSecondaryPlainSlots,
+	};
+
 impl pallet_babe::Trait for Runtime {
 	type EpochDuration = EpochDuration;
 	type ExpectedBlockTime = ExpectedBlockTime;
@@ -422,22 +436,10 @@ impl pallet_session::historical::Trait for Runtime {
 	type FullIdentificationOf = pallet_staking::ExposureOf<Runtime>;
 }
 
-pallet_staking_reward_curve::build! {
-	const REWARD_CURVE: PiecewiseLinear<'static> = curve!(
-		min_inflation: 0_025_000,
-		max_inflation: 0_100_000,
-		ideal_stake: 0_500_000,
-		falloff: 0_050_000,
-		max_piece_count: 40,
-		test_precision: 0_005_000,
-	);
-}
-
 parameter_types! {
 	pub const SessionsPerEra: sp_staking::SessionIndex = 6;
 	pub const BondingDuration: pallet_staking::EraIndex = 24 * 28;
 	pub const SlashDeferDuration: pallet_staking::EraIndex = 24 * 7; // 1/4 the bonding duration.
-	pub const RewardCurve: &'static PiecewiseLinear<'static> = &REWARD_CURVE;
 	pub const MaxNominatorRewardedPerValidator: u32 = 64;
 	pub const ElectionLookahead: BlockNumber = EPOCH_DURATION_IN_BLOCKS / 4;
 	pub const MaxIterations: u32 = 10;
@@ -463,7 +465,13 @@ impl pallet_staking::Trait for Runtime {
 		pallet_collective::EnsureProportionAtLeast<_3, _4, AccountId, CouncilCollective>
 	>;
 	type SessionInterface = Self;
-	type RewardCurve = RewardCurve;
+	type EraPayout = pallet_staking::SocialNetworkInflation;
+	/// A super-majority of the council can tune the inflation model.
+	type EraPayoutOrigin = EnsureOneOf<
+		AccountId,
+		EnsureRoot<AccountId>,
+		pallet_collective::EnsureProportionAtLeast<_3, _4, AccountId, CouncilCollective>
+	>;
 	type NextNewSession = Session;
 	type ElectionLookahead = ElectionLookahead;
 	type Call = Call;
@@ -683,6 +691,7 @@ impl pallet_contracts::Trait for Runtime {
 	type MaxDepth = pallet_contracts::DefaultMaxDepth;
 	type MaxValueSize = pallet_contracts::DefaultMaxValueSize;
 	type WeightPrice = pallet_transaction_payment::Module<Self>;
+	type ChainExtension = SocialChainExtension;
 }
 
 impl pallet_sudo::Trait for Runtime {
@@ -904,12 +913,7 @@ impl pallet_evm::Trait for Runtime {
 	type AddressMapping = pallet_evm::HashedAddressMapping<BlakeTwo256>;
 	type Currency = Balances;
 	type Event = Event;
-	type Precompiles = (
-		pallet_evm::precompiles::ECRecover,
-		pallet_evm::precompiles::Sha256,
-		pallet_evm::precompiles::Ripemd160,
-		pallet_evm::precompiles::Identity,
-	);
+	type Precompiles = SocialPrecompiles;
 	type ChainId = pallet_evm::SystemChainId;
 }
 
@@ -938,6 +942,70 @@ impl pallet_mission_tokens::Trait for Runtime {
 	>;
 }
 
+parameter_types! {
+	pub const MissionMinimumDeposit: u128 = 100 * DOLLARS;
+	pub const MissionVotingPeriod: BlockNumber = 7 * 24 * 60 * MINUTES;
+	pub const MissionEnactmentPeriod: BlockNumber = 2 * 24 * 60 * MINUTES;
+}
+
+impl pallet_mission_democracy::Trait for Runtime {
+	type Event = Event;
+	type Proposal = Call;
+	type MinimumDeposit = MissionMinimumDeposit;
+	type VotingPeriod = MissionVotingPeriod;
+	type EnactmentPeriod = MissionEnactmentPeriod;
+	type MaxVotes = MaxVotes;
+	// To cancel a mission referendum, 2/3 of the council must agree to it.
+	type CancellationOrigin = EnsureOneOf<
+		AccountId,
+		EnsureRoot<AccountId>,
+		pallet_collective::EnsureProportionAtLeast<_2, _3, AccountId, CouncilCollective>
+	>;
+	type Scheduler = Scheduler;
+	type PalletsOrigin = OriginCaller;
+}
+
+impl pallet_mission_council::Trait for Runtime {
+	type Event = Event;
+	type Origin = Origin;
+	type Proposal = Call;
+	type MotionDuration = CouncilMotionDuration;
+	type MaxProposals = CouncilMaxProposals;
+	type MaxMembers = CouncilMaxMembers;
+	// The council of a mission is elected by a referendum of the mission's token holders.
+	type MembershipOrigin = pallet_mission_democracy::EnsureMission<u32>;
+	type ForceOrigin = EnsureRootOrHalfCouncil;
+}
+
+parameter_types! {
+	pub const MissionStreamsModuleId: ModuleId = ModuleId(*b"py/mstrm");
+	pub const MinimumStreamDeposit: u128 = 1 * DOLLARS;
+}
+
+impl pallet_mission_streams::Trait for Runtime {
+	type Event = Event;
+	type ModuleId = MissionStreamsModuleId;
+	type MinimumDeposit = MinimumStreamDeposit;
+	type BlockNumberToBalance = ConvertInto;
+	/// Whoever may reject spending from the social treasury may cancel the streams paying out
+	/// its awards.
+	type CancelOrigin = EnsureSocialTreasury<EnsureOneOf<
+		AccountId,
+		EnsureRoot<AccountId>,
+		pallet_collective::EnsureMembers<_2, AccountId, CouncilCollective>
+	>>;
+}
+
+parameter_types! {
+	pub const MaxRoundProjects: u32 = 50;
+	pub const MaxProjectContributors: u32 = 100;
+	pub const MinimumContribution: u128 = 1 * DOLLARS;
+	pub const GrantReleaseDelay: BlockNumber = 1 * DAYS;
+	pub const MaxMilestones: u32 = 16;
+	// Worth in the reference unit of the oracle, taken to have as many decimals as the native token.
+	pub const MaxProposalValue: Balance = 1_000_000 * DOLLARS;
+}
+
 impl pallet_social_treasury::Trait for Runtime {
 	type ApproveOrigin = EnsureOneOf<
 		AccountId,
@@ -949,6 +1017,11 @@ impl pallet_social_treasury::Trait for Runtime {
 		EnsureRoot<AccountId>,
 		pallet_collective::EnsureMembers<_2, AccountId, CouncilCollective>
 	>;
+	/// A majority of a mission council can approve spending from the pot of its mission.
+	type MissionApproveOrigin = pallet_mission_council::EnsureMissionProportionMoreThan<_1, _2, u32>;
+	/// A two-thirds supermajority of a mission council can reject spending from the pot of its
+	/// mission.
+	type MissionRejectOrigin = pallet_mission_council::EnsureMissionProportionAtLeast<_2, _3, u32>;
 	type Tippers = Elections;
 	type TipCountdown = TipCountdown;
 	type TipFindersFee = TipFindersFee;
@@ -968,6 +1041,37 @@ impl pallet_social_treasury::Trait for Runtime {
 	type MaximumReasonLength = MaximumReasonLength;
 	type BurnDestination = ();
 	type WeightInfo = weights::pallet_social_treasury::WeightInfo;
+	type Streams = MissionStreams;
+	type BlockNumberToBalance = ConvertInto;
+	type UniquePersons = VerifiedPersons;
+	type MaxRoundProjects = MaxRoundProjects;
+	type MaxProjectContributors = MaxProjectContributors;
+	type MinimumContribution = MinimumContribution;
+	type GrantCall = Call;
+	type Scheduler = Scheduler;
+	type PalletsOrigin = OriginCaller;
+	type GrantReleaseDelay = GrantReleaseDelay;
+	type MaxMilestones = MaxMilestones;
+	type TokenValue = Oracle;
+	type MaxProposalValue = MaxProposalValue;
+}
+
+parameter_types! {
+	pub const MaxOracleFeeders: u32 = 16;
+	pub const OracleFeedInterval: BlockNumber = 10 * MINUTES;
+	pub const OracleStalePeriod: BlockNumber = 1 * HOURS;
+	pub const MinOracleFeeds: u32 = 3;
+}
+
+impl pallet_oracle::Trait for Runtime {
+	type AuthorityId = pallet_oracle::crypto::FeederAuthId;
+	type Event = Event;
+	type Call = Call;
+	type FeederOrigin = EnsureRootOrHalfCouncil;
+	type MaxFeeders = MaxOracleFeeders;
+	type FeedInterval = OracleFeedInterval;
+	type StalePeriod = OracleStalePeriod;
+	type MinFeeds = MinOracleFeeds;
 }
 
 impl pallet_validator_registry::Trait for Runtime {
@@ -989,6 +1093,18 @@ impl pallet_username_registry::Trait for Runtime {
 	type WeightInfo = weights::pallet_username_registry::WeightInfo;
 }
 
+parameter_types! {
+	pub const MetricsReportInterval: BlockNumber = 10 * MINUTES;
+	pub const MetricsReportMaxAttempts: u32 = 5;
+}
+
+impl pallet_metrics_reporter::Trait for Runtime {
+	type AuthorityId = pallet_metrics_reporter::sr25519::ReporterId;
+	type Metrics = (MissionMetrics, RegistrationMetrics);
+	type ReportInterval = MetricsReportInterval;
+	type MaxAttempts = MetricsReportMaxAttempts;
+}
+
 parameter_types! {
     pub const ChainId: u8 = 5;
     pub const ProposalLifetime: u32 = 50;
@@ -1040,13 +1156,18 @@ construct_runtime!(
 		Scheduler: pallet_scheduler::{Module, Call, Storage, Event<T>},
 		Proxy: pallet_proxy::{Module, Call, Storage, Event<T>},
 		Multisig: pallet_multisig::{Module, Call, Storage, Event<T>},
-		Evm: pallet_evm::{Module, Call, Storage, Event<T>},
-		Did: pallet_did::{Module, Call, Storage, Event<T>},
-		MissionTokens: pallet_mission_tokens::{Module, Call, Storage, Event<T>},
-		SocialTreasury: pallet_social_treasury::{Module, Call, Storage, Event<T>},
-		ValidatorRegistry: pallet_validator_registry::{Module, Call, Storage, Event<T>},
-		UsernameRegistry: pallet_username_registry::{Module, Call, Storage, Event<T>},
-		ChainBridge: pallet_chainbridge::{Module, Call, Storage, Event<T>},
+		Evm: pallet_evm::{Module, Call, Storage, Config, Event<T>},
+		Did: pallet_did::{Module, Call, Storage, Event<T>, Config<T>},
+		MissionTokens: pallet_mission_tokens::{Module, Call, Storage, Event<T>, Config<T>},
+		MissionDemocracy: pallet_mission_democracy::{Module, Call, Storage, Origin<T>, Event<T>},
+		MissionCouncil: pallet_mission_council::{Module, Call, Storage, Origin<T>, Event<T>},
+		MissionStreams: pallet_mission_streams::{Module, Call, Storage, Event<T>},
+		SocialTreasury: pallet_social_treasury::{Module, Call, Storage, Event<T>, Config},
+		ValidatorRegistry: pallet_validator_registry::{Module, Call, Storage, Event<T>, Config<T>},
+		UsernameRegistry: pallet_username_registry::{Module, Call, Storage, Event<T>, Config<T>},
+		ChainBridge: pallet_chainbridge::{Module, Call, Storage, Event<T>, Config<T>},
+		MetricsReporter: pallet_metrics_reporter::{Module},
+		Oracle: pallet_oracle::{Module, Call, Storage, Event<T>},
 	}
 );
 
@@ -1062,7 +1183,8 @@ pub type SignedBlock = generic::SignedBlock<Block>;
 pub type BlockId = generic::BlockId<Block>;
 /// The SignedExtension to the basic transaction logic.
 ///
-/// When you change this, you **MUST** modify [`sign`] in `bin/node/testing/src/keyring.rs`!
+/// When you change this, you **MUST** modify [`sign`] in `bin/node/testing/src/keyring.rs`
+/// and the `tx` subcommand in `bin/node/cli/src/command.rs`!
 ///
 /// [`sign`]: <../../testing/src/keyring.rs.html>
 pub type SignedExtra = (
@@ -1080,8 +1202,20 @@ pub type UncheckedExtrinsic = generic::UncheckedExtrinsic<Address, Call, Signatu
 pub type SignedPayload = generic::SignedPayload<Call, SignedExtra>;
 /// Extrinsic type that has already been checked.
 pub type CheckedExtrinsic = generic::CheckedExtrinsic<AccountId, Call, SignedExtra>;
+/// Storage migrations run on a runtime upgrade, in order.
+pub type Migrations = (
+	pallet_mission_tokens::migrations::RecomputeTotalIssuance<Runtime>,
+	pallet_staking::migrations::ScaleTerminalIssuance<Runtime>,
+);
 /// Executive: handles dispatch to the various modules.
-pub type Executive = frame_executive::Executive<Runtime, Block, frame_system::ChainContext<Runtime>, Runtime, AllModules>;
+pub type Executive = frame_executive::Executive<
+	Runtime,
+	Block,
+	frame_system::ChainContext<Runtime>,
+	Runtime,
+	AllModules,
+	frame_executive::Migrations<Migrations>,
+>;
 
 impl_runtime_apis! {
 	impl sp_api::Core<Block> for Runtime {
@@ -1146,6 +1280,10 @@ impl_runtime_apis! {
 			Grandpa::grandpa_authorities()
 		}
 
+		fn current_set_id() -> fg_primitives::SetId {
+			Grandpa::current_set_id()
+		}
+
 		fn submit_report_equivocation_unsigned_extrinsic(
 			equivocation_proof: fg_primitives::EquivocationProof<
 				<Block as BlockT>::Hash,
@@ -1175,18 +1313,13 @@ impl_runtime_apis! {
 
 	impl sp_consensus_babe::BabeApi<Block> for Runtime {
 		fn configuration() -> sp_consensus_babe::BabeGenesisConfiguration {
-			// The choice of `c` parameter (where `1 - c` represents the
-			// probability of a slot being empty), is done in accordance to the
-			// slot duration and expected target block time, for safely
-			// resisting network delays of maximum two seconds.
-			// <https://research.web3.foundation/en/latest/polkadot/BABE/Babe/#6-practical-results>
 			sp_consensus_babe::BabeGenesisConfiguration {
 				slot_duration: Babe::slot_duration(),
 				epoch_length: EpochDuration::get(),
-				c: PRIMARY_PROBABILITY,
+				c: BABE_EPOCH_CONFIG.c,
 				genesis_authorities: Babe::authorities(),
 				randomness: Babe::randomness(),
-				allowed_slots: sp_consensus_babe::AllowedSlots::PrimaryAndSecondaryPlainSlots,
+				allowed_slots: BABE_EPOCH_CONFIG.allowed_slots,
 			}
 		}
 
@@ -1194,6 +1327,14 @@ impl_runtime_apis! {
 			Babe::current_epoch_start()
 		}
 
+		fn current_epoch() -> sp_consensus_babe::Epoch {
+			Babe::current_epoch(BABE_EPOCH_CONFIG)
+		}
+
+		fn next_epoch() -> sp_consensus_babe::Epoch {
+			Babe::next_epoch(BABE_EPOCH_CONFIG)
+		}
+
 		fn generate_key_ownership_proof(
 			_slot_number: sp_consensus_babe::SlotNumber,
 			authority_id: sp_consensus_babe::AuthorityId,
@@ -1275,6 +1416,35 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl pallet_social_treasury_rpc_runtime_api::SocialTreasuryApi<
+		Block,
+		AccountId,
+		u128,
+		u32,
+	> for Runtime {
+		fn round_results(
+			round: pallet_social_treasury::RoundIndex,
+		) -> Option<pallet_social_treasury_rpc_runtime_api::RoundResults<AccountId, u128, u32>> {
+			SocialTreasury::round_results(round)
+		}
+	}
+
+	impl pallet_staking_rpc_runtime_api::StakingApi<Block, Balance> for Runtime {
+		fn era_payout_projection(
+			from: pallet_staking::EraIndex,
+			to: pallet_staking::EraIndex,
+		) -> Vec<pallet_staking_rpc_runtime_api::EraPayoutProjection<Balance>> {
+			let era_duration = SessionsPerEra::get() as u64 * EPOCH_DURATION_IN_SLOTS * SLOT_DURATION;
+			Staking::project_era_payouts(from, to, era_duration)
+		}
+	}
+
+	impl frame_executive::MigrationApi<Block> for Runtime {
+		fn dry_run_upgrade() -> frame_executive::MigrationReport {
+			Executive::dry_run_upgrade()
+		}
+	}
+
 	impl sp_session::SessionKeys<Block> for Runtime {
 		fn generate_session_keys(seed: Option<Vec<u8>>) -> Vec<u8> {
 			SessionKeys::generate(seed)
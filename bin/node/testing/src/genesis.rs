@@ -119,5 +119,12 @@ pub fn config_endowed(
 			max_members: 999,
 		}),
 		pallet_vesting: Some(Default::default()),
+		pallet_evm: Some(Default::default()),
+		pallet_did: Some(Default::default()),
+		pallet_mission_tokens: Some(Default::default()),
+		pallet_social_treasury: Some(Default::default()),
+		pallet_validator_registry: Some(Default::default()),
+		pallet_username_registry: Some(Default::default()),
+		pallet_chainbridge: Some(Default::default()),
 	}
 }
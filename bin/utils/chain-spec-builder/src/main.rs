@@ -16,7 +16,7 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
-use std::{fs, path::{Path, PathBuf}};
+use std::{collections::BTreeMap, fs, path::{Path, PathBuf}};
 
 use ansi_term::Style;
 use rand::{Rng, distributions::Alphanumeric, rngs::OsRng};
@@ -24,7 +24,153 @@ use structopt::StructOpt;
 
 use sc_keystore::{Store as Keystore};
 use node_cli::chain_spec::{self, AccountId};
-use sp_core::{sr25519, crypto::{Public, Ss58Codec}, traits::BareCryptoStore};
+use sp_core::{sr25519, crypto::{Public, Ss58Codec}, traits::BareCryptoStore, H160, U256};
+
+/// Genesis state of the pallets specific to this network. When none of it is given, the
+/// defaults of the test networks are used.
+#[derive(StructOpt)]
+#[structopt(rename_all = "kebab-case")]
+struct NetworkGenesisParams {
+	/// The first valid mission token id.
+	#[structopt(long)]
+	min_mission_token_id: Option<u32>,
+	/// The last valid mission token id.
+	#[structopt(long)]
+	max_mission_token_id: Option<u32>,
+	/// Mission token balance, as `<mission token id>:<address>:<balance>`.
+	#[structopt(long)]
+	mission_balance: Vec<String>,
+	/// Registrar of the username registry (SS58 format).
+	#[structopt(long)]
+	registrar: Vec<String>,
+	/// Validator registered for a mission, as `<address>:<mission token id>`.
+	#[structopt(long)]
+	validator: Vec<String>,
+	/// Relayer of the chain bridge (SS58 format).
+	#[structopt(long)]
+	bridge_relayer: Vec<String>,
+	/// Number of relayer votes required to execute a bridge proposal.
+	#[structopt(long)]
+	bridge_threshold: Option<u32>,
+	/// Chain id whitelisted for bridge transfers.
+	#[structopt(long)]
+	bridge_chain: Vec<u8>,
+	/// Bridge resource, as `<hex resource id>:<method>`.
+	#[structopt(long)]
+	bridge_resource: Vec<String>,
+	/// EVM account, as `<hex address>:<balance>`.
+	#[structopt(long)]
+	evm_account: Vec<String>,
+	/// Owner of a DID identity, as `<identity address>:<owner address>`.
+	#[structopt(long)]
+	did_owner: Vec<String>,
+	/// Delegate of a DID identity that never expires, as
+	/// `<identity address>:<delegate type>:<delegate address>`.
+	#[structopt(long)]
+	did_delegate: Vec<String>,
+}
+
+impl NetworkGenesisParams {
+	/// Parse the network genesis, if any of it was given.
+	fn network_genesis(&self) -> Result<Option<chain_spec::NetworkGenesis>, String> {
+		if self.min_mission_token_id.is_none() && self.max_mission_token_id.is_none()
+			&& self.mission_balance.is_empty() && self.registrar.is_empty() && self.validator.is_empty()
+			&& self.bridge_relayer.is_empty() && self.bridge_threshold.is_none()
+			&& self.bridge_chain.is_empty() && self.bridge_resource.is_empty()
+			&& self.evm_account.is_empty() && self.did_owner.is_empty() && self.did_delegate.is_empty()
+		{
+			return Ok(None)
+		}
+
+		let defaults = chain_spec::NetworkGenesis::default();
+		let mission_token_ids = (
+			self.min_mission_token_id.unwrap_or(defaults.mission_token_ids.0),
+			self.max_mission_token_id.unwrap_or(defaults.mission_token_ids.1),
+		);
+		if mission_token_ids.0 > mission_token_ids.1 {
+			return Err(format!(
+				"Minimum mission token id {} is greater than the maximum {}",
+				mission_token_ids.0,
+				mission_token_ids.1,
+			))
+		}
+
+		let mission_balances = self.mission_balance.iter().map(|arg| {
+			let mut parts = arg.splitn(3, ':');
+			match (parts.next(), parts.next(), parts.next()) {
+				(Some(id), Some(address), Some(balance)) => Ok((
+					parse_number(id)?,
+					parse_account(address)?,
+					parse_number(balance)?,
+				)),
+				_ => Err(format!("Invalid mission balance: {}", arg)),
+			}
+		}).collect::<Result<Vec<_>, String>>()?;
+
+		let validators = self.validator.iter().map(|arg| {
+			let (address, id) = split_pair(arg)?;
+			Ok((parse_account(address)?, parse_number(id)?))
+		}).collect::<Result<Vec<_>, String>>()?;
+
+		let bridge_resources = self.bridge_resource.iter().map(|arg| {
+			let (id, method) = split_pair(arg)?;
+			let id = parse_hex(id)?;
+			if id.len() != 32 {
+				return Err(format!("Resource id must be 32 bytes: {}", arg))
+			}
+			let mut resource_id = [0u8; 32];
+			resource_id.copy_from_slice(&id);
+			Ok((resource_id, method.as_bytes().to_vec()))
+		}).collect::<Result<Vec<_>, String>>()?;
+
+		let evm_accounts = self.evm_account.iter().map(|arg| {
+			let (address, balance) = split_pair(arg)?;
+			let address = parse_hex(address)?;
+			if address.len() != 20 {
+				return Err(format!("EVM address must be 20 bytes: {}", arg))
+			}
+			let account = pallet_evm::GenesisAccount {
+				nonce: U256::zero(),
+				balance: U256::from_dec_str(balance)
+					.map_err(|err| format!("Failed to parse balance {}: {:?}", balance, err))?,
+				storage: BTreeMap::new(),
+				code: vec![],
+			};
+			Ok((H160::from_slice(&address), account))
+		}).collect::<Result<BTreeMap<_, _>, String>>()?;
+
+		let did_owners = self.did_owner.iter().map(|arg| {
+			let (identity, owner) = split_pair(arg)?;
+			Ok((parse_account(identity)?, parse_account(owner)?))
+		}).collect::<Result<Vec<_>, String>>()?;
+
+		let did_delegates = self.did_delegate.iter().map(|arg| {
+			let mut parts = arg.splitn(3, ':');
+			match (parts.next(), parts.next(), parts.next()) {
+				(Some(identity), Some(delegate_type), Some(delegate)) if delegate_type.len() <= 64 => Ok((
+					parse_account(identity)?,
+					delegate_type.as_bytes().to_vec(),
+					parse_account(delegate)?,
+				)),
+				_ => Err(format!("Invalid DID delegate: {}", arg)),
+			}
+		}).collect::<Result<Vec<_>, String>>()?;
+
+		Ok(Some(chain_spec::NetworkGenesis {
+			mission_token_ids,
+			mission_balances,
+			registrars: self.registrar.iter().map(|a| parse_account(a)).collect::<Result<_, _>>()?,
+			validators,
+			bridge_relayers: self.bridge_relayer.iter().map(|a| parse_account(a)).collect::<Result<_, _>>()?,
+			bridge_threshold: self.bridge_threshold.unwrap_or(defaults.bridge_threshold),
+			bridge_chains: self.bridge_chain.clone(),
+			bridge_resources,
+			evm_accounts,
+			did_owners,
+			did_delegates,
+		}))
+	}
+}
 
 /// A utility to easily create a testnet chain spec definition with a given set
 /// of authorities and endowed accounts and/or generate random accounts.
@@ -46,6 +192,8 @@ enum ChainSpecBuilder {
 		/// The path where the chain spec should be saved.
 		#[structopt(long, short, default_value = "./chain_spec.json")]
 		chain_spec_path: PathBuf,
+		#[structopt(flatten)]
+		network: NetworkGenesisParams,
 	},
 	/// Create a new chain spec with the given number of authorities and endowed
 	/// accounts. Random keys will be generated as required.
@@ -66,6 +214,8 @@ enum ChainSpecBuilder {
 		/// `auth-0`, `auth-1`, etc.
 		#[structopt(long, short)]
 		keystore_path: Option<PathBuf>,
+		#[structopt(flatten)]
+		network: NetworkGenesisParams,
 	},
 }
 
@@ -85,6 +235,7 @@ fn genesis_constructor(
 	authority_seeds: &[String],
 	endowed_accounts: &[AccountId],
 	sudo_account: &AccountId,
+	network_genesis: Option<chain_spec::NetworkGenesis>,
 ) -> chain_spec::GenesisConfig {
 	let authorities = authority_seeds
 		.iter()
@@ -98,23 +249,41 @@ fn genesis_constructor(
 		authorities,
 		sudo_account.clone(),
 		Some(endowed_accounts.to_vec()),
+		network_genesis,
 		enable_println,
 	)
 }
 
+fn parse_account(address: &str) -> Result<AccountId, String> {
+	AccountId::from_string(address)
+		.map_err(|err| format!("Failed to parse account address: {:?}", err))
+}
+
+fn parse_number<N: std::str::FromStr>(number: &str) -> Result<N, String> {
+	number.parse().map_err(|_| format!("Failed to parse number: {}", number))
+}
+
+fn parse_hex(hex: &str) -> Result<Vec<u8>, String> {
+	sp_core::bytes::from_hex(hex).map_err(|err| format!("Failed to parse hex {}: {:?}", hex, err))
+}
+
+fn split_pair(arg: &str) -> Result<(&str, &str), String> {
+	let mut parts = arg.splitn(2, ':');
+	match (parts.next(), parts.next()) {
+		(Some(first), Some(second)) => Ok((first, second)),
+		_ => Err(format!("Expected two values separated by `:`: {}", arg)),
+	}
+}
+
 fn generate_chain_spec(
 	authority_seeds: Vec<String>,
 	endowed_accounts: Vec<String>,
 	sudo_account: String,
+	network_genesis: Option<chain_spec::NetworkGenesis>,
 ) -> Result<String, String> {
-	let parse_account = |address: &String| {
-		AccountId::from_string(address)
-			.map_err(|err| format!("Failed to parse account address: {:?}", err))
-	};
-
 	let endowed_accounts = endowed_accounts
 		.iter()
-		.map(parse_account)
+		.map(|address| parse_account(address))
 		.collect::<Result<Vec<_>, String>>()?;
 
 	let sudo_account = parse_account(&sudo_account)?;
@@ -123,7 +292,12 @@ fn generate_chain_spec(
 		"Custom",
 		"custom",
 		sc_chain_spec::ChainType::Live,
-		move || genesis_constructor(&authority_seeds, &endowed_accounts, &sudo_account),
+		move || genesis_constructor(
+			&authority_seeds,
+			&endowed_accounts,
+			&sudo_account,
+			network_genesis.clone(),
+		),
 		vec![],
 		None,
 		None,
@@ -225,8 +399,8 @@ fn main() -> Result<(), String> {
 	let builder = ChainSpecBuilder::from_args();
 	let chain_spec_path = builder.chain_spec_path().to_path_buf();
 
-	let (authority_seeds, endowed_accounts, sudo_account) = match builder {
-		ChainSpecBuilder::Generate { authorities, endowed, keystore_path, .. } => {
+	let (authority_seeds, endowed_accounts, sudo_account, network) = match builder {
+		ChainSpecBuilder::Generate { authorities, endowed, keystore_path, network, .. } => {
 			let authorities = authorities.max(1);
 			let rand_str = || -> String {
 				OsRng.sample_iter(&Alphanumeric)
@@ -259,10 +433,10 @@ fn main() -> Result<(), String> {
 			let sudo_account = chain_spec::get_account_id_from_seed::<sr25519::Public>(&sudo_seed)
 				.to_ss58check();
 
-			(authority_seeds, endowed_accounts, sudo_account)
+			(authority_seeds, endowed_accounts, sudo_account, network)
 		},
-		ChainSpecBuilder::New { authority_seeds, endowed_accounts, sudo_account, .. } => {
-			(authority_seeds, endowed_accounts, sudo_account)
+		ChainSpecBuilder::New { authority_seeds, endowed_accounts, sudo_account, network, .. } => {
+			(authority_seeds, endowed_accounts, sudo_account, network)
 		},
 	};
 
@@ -270,6 +444,7 @@ fn main() -> Result<(), String> {
 		authority_seeds,
 		endowed_accounts,
 		sudo_account,
+		network.network_genesis()?,
 	)?;
 
 	fs::write(chain_spec_path, json).map_err(|err| err.to_string())
@@ -0,0 +1,90 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use std::{fs, path::PathBuf, str::FromStr, sync::Arc};
+
+use structopt::StructOpt;
+
+use sc_keystore::{Store as Keystore, SignerAddress, Signer, remote::Listener};
+use sp_core::crypto::{SecretString, Zeroize};
+
+/// Environment variable the keystore password is read from if it is not given otherwise.
+const KEYSTORE_PASSWORD_ENV: &str = "KEYSTORE_PASSWORD";
+
+/// Serve the keys of a keystore to a node started with `--keystore-remote`.
+#[derive(StructOpt)]
+#[structopt(rename_all = "kebab-case")]
+struct RemoteSigner {
+	/// Path of the keystore holding the keys.
+	#[structopt(long, parse(from_os_str))]
+	keystore_path: PathBuf,
+	/// File recording the block seals and votes signed, which must survive restarts.
+	#[structopt(long, parse(from_os_str))]
+	signing_record: PathBuf,
+	/// Address to listen at: `unix:<PATH>` for a Unix socket, or a loopback TCP address.
+	#[structopt(long, default_value = "127.0.0.1:9955")]
+	listen: SignerAddress,
+	/// Enter the keystore password interactively.
+	#[structopt(long, conflicts_with = "password-filename")]
+	password_interactive: bool,
+	/// File that contains the keystore password.
+	#[structopt(long, parse(from_os_str))]
+	password_filename: Option<PathBuf>,
+}
+
+impl RemoteSigner {
+	/// The keystore password, falling back to the `KEYSTORE_PASSWORD` environment variable.
+	fn password(&self) -> Result<Option<SecretString>, String> {
+		let mut password = if self.password_interactive {
+			rpassword::read_password_from_tty(Some("Keystore password: "))
+				.map_err(|e| e.to_string())?
+		} else if let Some(file) = &self.password_filename {
+			fs::read_to_string(file).map_err(|e| e.to_string())?
+		} else if let Ok(password) = std::env::var(KEYSTORE_PASSWORD_ENV) {
+			password
+		} else {
+			return Ok(None);
+		};
+		let secret = SecretString::from_str(&password).map_err(|()| "Error reading password")?;
+		password.zeroize();
+		Ok(Some(secret))
+	}
+}
+
+fn main() -> Result<(), String> {
+	env_logger::init();
+	let options = RemoteSigner::from_args();
+
+	let keystore = Keystore::open(&options.keystore_path, options.password()?)
+		.map_err(|e| format!("Cannot open keystore: {}", e))?;
+	let signer = Signer::new(keystore, options.signing_record.clone())
+		.map_err(|e| format!("Cannot read signing record: {}", e))?;
+
+	#[cfg(unix)]
+	{
+		if let SignerAddress::Unix(path) = &options.listen {
+			// A socket left behind by a previous run.
+			let _ = fs::remove_file(path);
+		}
+	}
+	let listener = Listener::bind(&options.listen)
+		.map_err(|e| format!("Cannot listen at {}: {}", options.listen, e))?;
+	log::info!("Remote signer listening at {}", options.listen);
+
+	Arc::new(signer).serve(listener).map_err(|e| e.to_string())
+}
@@ -70,4 +70,26 @@ pub trait ProofProvider<Block: BlockT> {
 		storage_key: Option<&PrefixedStorageKey>,
 		key: &StorageKey,
 	) -> sp_blockchain::Result<ChangesProof<Block::Header>>;
+
+	/// Given a `BlockId`, iterate over the storage entries of the top trie, or of the child
+	/// trie given by `child_info`, that follow `start_key`, building a proof of the visited
+	/// entries until `size_limit` bytes of keys and values have been collected.
+	/// Returns the proof and the number of entries it covers.
+	fn read_proof_collection(
+		&self,
+		id: &BlockId<Block>,
+		child_info: Option<&ChildInfo>,
+		start_key: &[u8],
+		size_limit: usize,
+	) -> sp_blockchain::Result<(StorageProof, u32)>;
+
+	/// Verify a proof generated by `read_proof_collection` against the given state root.
+	/// Returns the proven entries and whether they reach the end of the trie.
+	fn verify_range_proof(
+		&self,
+		root: Block::Hash,
+		proof: StorageProof,
+		child_info: Option<&ChildInfo>,
+		start_key: &[u8],
+	) -> sp_blockchain::Result<(Vec<(Vec<u8>, Vec<u8>)>, bool)>;
 }
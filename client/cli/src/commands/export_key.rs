@@ -0,0 +1,71 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Implementation of the `export` subcommand
+
+use crate::{Error, KeystoreParams, SharedParams, utils};
+use structopt::StructOpt;
+use std::{fs::File, path::PathBuf};
+
+/// The `export` command
+#[derive(Debug, StructOpt)]
+#[structopt(
+	name = "export",
+	about = "Export the keys of the keystore of a node to a password-encrypted JSON file."
+)]
+pub struct ExportKeyCmd {
+	/// Key type of the keys to export, examples: "gran", or "imon".
+	/// If not given, all keys are exported.
+	#[structopt(long)]
+	key_type: Option<String>,
+
+	/// File to write the exported keys to.
+	#[structopt(long, value_name = "PATH", parse(from_os_str))]
+	output: PathBuf,
+
+	/// File that contains the password to encrypt the exported keys with.
+	/// If not given, you will be prompted for the password.
+	#[structopt(long, value_name = "PATH", parse(from_os_str))]
+	export_password_filename: Option<PathBuf>,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub keystore_params: KeystoreParams,
+}
+
+impl ExportKeyCmd {
+	/// Run the command
+	pub fn run(&self) -> Result<(), Error> {
+		let keystore = utils::open_keystore(&self.shared_params, &self.keystore_params)?;
+		let key_type = self.key_type.as_ref()
+			.map(|key_type| utils::key_type_from_str(key_type))
+			.transpose()?;
+		let password = utils::read_export_password(self.export_password_filename.as_ref())?;
+
+		let export = keystore.read()
+			.export_keys(key_type, &password)
+			.map_err(|e| Error::Other(format!("{}", e)))?;
+		serde_json::to_writer_pretty(File::create(&self.output)?, &export)
+			.map_err(|e| Error::Other(format!("{}", e)))?;
+
+		Ok(())
+	}
+}
@@ -0,0 +1,74 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{CliConfiguration, error, params::SharedParams};
+use log::info;
+use sc_client_api::AuxStore;
+use sc_slashing_protection::SlashingProtection;
+use sp_blockchain::HeaderBackend;
+use sp_runtime::traits::Block as BlockT;
+use std::{fs, io::Write, path::PathBuf, sync::Arc};
+use structopt::StructOpt;
+
+/// The `export-slashing-protection` command used to export the history of block seals and
+/// votes signed by the node, for moving its keys to another node.
+#[derive(Debug, StructOpt)]
+pub struct ExportSlashingProtectionCmd {
+	/// Output file name or stdout if unspecified.
+	#[structopt(parse(from_os_str))]
+	pub output: Option<PathBuf>,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+}
+
+impl ExportSlashingProtectionCmd {
+	/// Run the `export-slashing-protection` command
+	pub async fn run<B, C>(&self, client: Arc<C>) -> error::Result<()>
+	where
+		B: BlockT,
+		C: AuxStore + HeaderBackend<B> + 'static,
+	{
+		let genesis_hash = client.info().genesis_hash;
+		let interchange = SlashingProtection::new(client)
+			.export(genesis_hash.as_ref())
+			.map_err(|e| error::Error::Other(e.to_string()))?;
+		let json = serde_json::to_string_pretty(&interchange)
+			.map_err(|e| error::Error::Other(e.to_string()))?;
+
+		match &self.output {
+			Some(path) => {
+				info!("Exporting slashing protection history to {}", path.display());
+				fs::write(path, json)?;
+			}
+			None => {
+				if std::io::stdout().write_all(json.as_bytes()).is_err() {
+					let _ = std::io::stderr().write_all(b"Error writing to stdout\n");
+				}
+			}
+		}
+		Ok(())
+	}
+}
+
+impl CliConfiguration for ExportSlashingProtectionCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+}
@@ -0,0 +1,72 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Implementation of the `import` subcommand
+
+use crate::{Error, KeystoreParams, SharedParams, utils};
+use structopt::StructOpt;
+use std::{fs::File, path::PathBuf};
+use sc_keystore::KeystoreExport;
+use sp_core::hexdisplay::HexDisplay;
+
+/// The `import` command
+#[derive(Debug, StructOpt)]
+#[structopt(
+	name = "import",
+	about = "Import keys exported with `key export` into the keystore of a node."
+)]
+pub struct ImportKeyCmd {
+	/// File to read the exported keys from.
+	#[structopt(long, value_name = "PATH", parse(from_os_str))]
+	input: PathBuf,
+
+	/// File that contains the password the keys were exported with.
+	/// If not given, you will be prompted for the password.
+	#[structopt(long, value_name = "PATH", parse(from_os_str))]
+	export_password_filename: Option<PathBuf>,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub keystore_params: KeystoreParams,
+}
+
+impl ImportKeyCmd {
+	/// Run the command
+	pub fn run(&self) -> Result<(), Error> {
+		let export: KeystoreExport = serde_json::from_reader(File::open(&self.input)?)
+			.map_err(|e| Error::Input(format!("Invalid key export file: {}", e)))?;
+		let password = utils::read_export_password(self.export_password_filename.as_ref())?;
+		let keystore = utils::open_keystore(&self.shared_params, &self.keystore_params)?;
+
+		let imported = keystore.read()
+			.import_keys(&export, &password)
+			.map_err(|e| Error::Other(format!("{}", e)))?;
+		for (key_type, public) in imported {
+			println!(
+				"Imported {} key 0x{}",
+				String::from_utf8_lossy(&key_type.0),
+				HexDisplay::from(&public),
+			);
+		}
+
+		Ok(())
+	}
+}
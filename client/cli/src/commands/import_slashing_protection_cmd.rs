@@ -0,0 +1,69 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{CliConfiguration, error, params::SharedParams};
+use log::info;
+use sc_client_api::AuxStore;
+use sc_slashing_protection::{Interchange, SlashingProtection};
+use sp_blockchain::HeaderBackend;
+use sp_runtime::traits::Block as BlockT;
+use std::{fs, path::PathBuf, sync::Arc};
+use structopt::StructOpt;
+
+/// The `import-slashing-protection` command used to import the history of block seals and
+/// votes signed with keys moved over from another node, merging it into the one of this node.
+#[derive(Debug, StructOpt)]
+pub struct ImportSlashingProtectionCmd {
+	/// Input file, as written by `export-slashing-protection`.
+	#[structopt(parse(from_os_str))]
+	pub input: PathBuf,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+}
+
+impl ImportSlashingProtectionCmd {
+	/// Run the `import-slashing-protection` command
+	pub async fn run<B, C>(&self, client: Arc<C>) -> error::Result<()>
+	where
+		B: BlockT,
+		C: AuxStore + HeaderBackend<B> + 'static,
+	{
+		let json = fs::read(&self.input)?;
+		let interchange: Interchange = serde_json::from_slice(&json)
+			.map_err(|e| error::Error::Input(format!("Invalid slashing protection file: {}", e)))?;
+
+		let genesis_hash = client.info().genesis_hash;
+		SlashingProtection::new(client)
+			.import(&interchange, genesis_hash.as_ref())
+			.map_err(|e| error::Error::Other(e.to_string()))?;
+		info!(
+			"Imported {} block seals and {} votes",
+			interchange.babe.len(),
+			interchange.grandpa.len(),
+		);
+		Ok(())
+	}
+}
+
+impl CliConfiguration for ImportSlashingProtectionCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+}
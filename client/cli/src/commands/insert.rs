@@ -72,7 +72,9 @@ impl InsertCmd {
 					.map_err(|e| format!("{}", e))?;
 				(keystore, public)
 			},
-			_ => unreachable!("keystore_config always returns path and password; qed")
+			_ => return Err(Error::Other(
+				"keys of a remote signer are managed through its keystore".into()
+			)),
 		};
 
 		let key_type = KeyTypeId::try_from(self.key_type.as_str())
@@ -22,6 +22,8 @@ use structopt::StructOpt;
 
 use super::{
 	insert::InsertCmd,
+	export_key::ExportKeyCmd,
+	import_key::ImportKeyCmd,
 	inspect::InspectKeyCmd,
 	generate::GenerateCmd,
 	inspect_node_key::InspectNodeKeyCmd,
@@ -46,6 +48,12 @@ pub enum KeySubcommand {
 
 	/// Insert a key to the keystore of a node.
 	Insert(InsertCmd),
+
+	/// Export the keys of the keystore of a node to a password-encrypted file.
+	Export(ExportKeyCmd),
+
+	/// Import keys exported with `key export` into the keystore of a node.
+	Import(ImportKeyCmd),
 }
 
 impl KeySubcommand {
@@ -56,6 +64,8 @@ impl KeySubcommand {
 			KeySubcommand::Generate(cmd) => cmd.run(),
 			KeySubcommand::InspectKey(cmd) => cmd.run(),
 			KeySubcommand::Insert(cmd) => cmd.run(),
+			KeySubcommand::Export(cmd) => cmd.run(),
+			KeySubcommand::Import(cmd) => cmd.run(),
 			KeySubcommand::InspectNodeKey(cmd) => cmd.run(),
 		}
 	}
@@ -20,7 +20,9 @@ mod build_sync_spec_cmd;
 mod check_block_cmd;
 mod export_blocks_cmd;
 mod export_state_cmd;
+mod export_slashing_protection_cmd;
 mod import_blocks_cmd;
+mod import_slashing_protection_cmd;
 mod purge_chain_cmd;
 mod sign;
 mod verify;
@@ -30,6 +32,8 @@ mod run_cmd;
 mod generate_node_key;
 mod generate;
 mod insert;
+mod export_key;
+mod import_key;
 mod inspect_node_key;
 mod inspect;
 mod key;
@@ -41,11 +45,15 @@ pub use self::{
 	check_block_cmd::CheckBlockCmd,
 	export_blocks_cmd::ExportBlocksCmd,
 	export_state_cmd::ExportStateCmd,
+	export_slashing_protection_cmd::ExportSlashingProtectionCmd,
 	import_blocks_cmd::ImportBlocksCmd,
+	import_slashing_protection_cmd::ImportSlashingProtectionCmd,
 	purge_chain_cmd::PurgeChainCmd,
 	sign::SignCmd,
 	generate::GenerateCmd,
 	insert::InsertCmd,
+	export_key::ExportKeyCmd,
+	import_key::ImportKeyCmd,
 	inspect::InspectKeyCmd,
 	generate_node_key::GenerateNodeKeyCmd,
 	inspect_node_key::InspectNodeKeyCmd,
@@ -32,6 +32,7 @@ use sc_service::{
 };
 use sc_telemetry::TelemetryEndpoints;
 use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::path::{Path, PathBuf};
 use structopt::StructOpt;
 
 /// The `run` command used to run a node.
@@ -138,6 +139,19 @@ pub struct RunCmd {
 	#[structopt(long = "ws-max-connections", value_name = "COUNT")]
 	pub ws_max_connections: Option<usize>,
 
+	/// Maximum payload of RPC requests and responses, in MiB. Default is 15.
+	///
+	/// It also bounds the outbound buffer of a WS RPC connection: connections whose client does
+	/// not read the messages sent to it fast enough for them to fit in it are closed.
+	#[structopt(long = "rpc-max-payload", value_name = "MiB")]
+	pub rpc_max_payload: Option<usize>,
+
+	/// Maximum number of subscriptions open at once over a WS RPC connection. Default is 1024.
+	///
+	/// Overrides the `maxSubscriptions` of `--rpc-limits`.
+	#[structopt(long = "rpc-max-subscriptions-per-connection", value_name = "COUNT")]
+	pub rpc_max_subscriptions_per_connection: Option<usize>,
+
 	/// Specify browser Origins allowed to access the HTTP & WS RPC servers.
 	///
 	/// A comma-separated list of origins (protocol://domain or special `null`
@@ -147,6 +161,16 @@ pub struct RunCmd {
 	#[structopt(long = "rpc-cors", value_name = "ORIGINS", parse(try_from_str = parse_cors))]
 	pub rpc_cors: Option<Cors>,
 
+	/// Specify a JSON file of limits to the calls made to the HTTP & WS RPC servers.
+	///
+	/// The file can set an `allow` and a `deny` list of methods, token bucket rate limits
+	/// `perClient` and `perMethod` as `{ "burst": 10, "perSecond": 2 }`, the `maxSubscriptions`
+	/// of a connection, and the `maxRequestSize` and `maxResponseSize` in bytes. Behind a
+	/// reverse proxy, `"trustProxyHeaders": true` applies rate limits per client address as
+	/// reported in the `X-Forwarded-For` or `Forwarded` header.
+	#[structopt(long = "rpc-limits", value_name = "PATH", parse(from_os_str))]
+	pub rpc_limits: Option<PathBuf>,
+
 	/// Specify Prometheus data source server TCP Port.
 	#[structopt(long = "prometheus-port", value_name = "PORT")]
 	pub prometheus_port: Option<u16>,
@@ -409,6 +433,14 @@ impl CliConfiguration for RunCmd {
 		Ok(self.ws_max_connections)
 	}
 
+	fn rpc_max_payload(&self) -> Result<Option<usize>> {
+		Ok(self.rpc_max_payload)
+	}
+
+	fn rpc_max_subscriptions_per_connection(&self) -> Result<Option<usize>> {
+		Ok(self.rpc_max_subscriptions_per_connection)
+	}
+
 	fn rpc_cors(&self, is_dev: bool) -> Result<Option<Vec<String>>> {
 		Ok(self
 			.rpc_cors
@@ -430,6 +462,13 @@ impl CliConfiguration for RunCmd {
 			.into())
 	}
 
+	fn rpc_limits(&self) -> Result<Option<sc_service::config::RpcLimits>> {
+		self.rpc_limits
+			.as_ref()
+			.map(|path| sc_service::config::RpcLimits::from_json_file(path).map_err(Error::Input))
+			.transpose()
+	}
+
 	fn rpc_http(&self, default_listen_port: u16) -> Result<Option<SocketAddr>> {
 		let interface = rpc_interface(
 			self.rpc_external,
@@ -460,8 +499,8 @@ impl CliConfiguration for RunCmd {
 		Ok(self.rpc_methods.into())
 	}
 
-	fn transaction_pool(&self) -> Result<TransactionPoolOptions> {
-		Ok(self.pool_config.transaction_pool())
+	fn transaction_pool(&self, config_dir: &Path) -> Result<TransactionPoolOptions> {
+		Ok(self.pool_config.transaction_pool(config_dir))
 	}
 
 	fn max_runtime_instances(&self) -> Result<Option<usize>> {
@@ -622,4 +661,22 @@ mod tests {
 		assert!(is_node_name_valid("www.visit.me").is_err());
 		assert!(is_node_name_valid("email@domain").is_err());
 	}
+
+	#[test]
+	fn rpc_server_flags_are_parsed() {
+		let cmd = RunCmd::from_iter(&[
+			"substrate",
+			"--ws-max-connections", "10",
+			"--rpc-max-payload", "5",
+			"--rpc-max-subscriptions-per-connection", "3",
+		]);
+		assert_eq!(cmd.rpc_ws_max_connections().unwrap(), Some(10));
+		assert_eq!(cmd.rpc_max_payload().unwrap(), Some(5));
+		assert_eq!(cmd.rpc_max_subscriptions_per_connection().unwrap(), Some(3));
+
+		let cmd = RunCmd::from_iter(&["substrate"]);
+		assert_eq!(cmd.rpc_ws_max_connections().unwrap(), None);
+		assert_eq!(cmd.rpc_max_payload().unwrap(), None);
+		assert_eq!(cmd.rpc_max_subscriptions_per_connection().unwrap(), None);
+	}
 }
@@ -23,9 +23,12 @@ use sp_core::{
 	crypto::{Ss58Codec, Ss58AddressFormat},
 };
 use sp_runtime::{MultiSigner, traits::IdentifyAccount};
-use crate::{OutputType, error::{self, Error}};
+use crate::{OutputType, KeystoreParams, SharedParams, error::{self, Error}};
 use serde_json::json;
-use sp_core::crypto::{SecretString, Zeroize, ExposeSecret};
+use sp_core::crypto::{KeyTypeId, SecretString, Zeroize, ExposeSecret};
+use sc_keystore::{KeyStorePtr, Store as KeyStore};
+use sc_service::config::KeystoreConfig;
+use std::convert::TryFrom;
 
 /// Public key type for Runtime
 pub type PublicFor<P> = <P as sp_core::Pair>::Public;
@@ -50,6 +53,46 @@ pub fn read_uri(uri: Option<&String>) -> error::Result<String> {
 	Ok(uri)
 }
 
+/// helper method to fetch the password of exported keys either from a file or read from stdin
+pub fn read_export_password(file: Option<&PathBuf>) -> error::Result<SecretString> {
+	let mut password = if let Some(file) = file {
+		let mut content = std::fs::read_to_string(file)?;
+		let password = content.trim_end().to_owned();
+		content.zeroize();
+		password
+	} else {
+		rpassword::read_password_from_tty(Some("Export password: "))?
+	};
+	let secret = std::str::FromStr::from_str(password.as_str())
+		.map_err(|()| "Error reading password")?;
+	password.zeroize();
+
+	Ok(secret)
+}
+
+/// open the keystore of a node, decrypting it with the keystore password
+pub fn open_keystore(
+	shared_params: &SharedParams,
+	keystore_params: &KeystoreParams,
+) -> error::Result<KeyStorePtr> {
+	let base_path = shared_params.base_path.as_ref()
+		.ok_or_else(|| Error::Other("please supply base path".into()))?;
+
+	match keystore_params.keystore_config(base_path)? {
+		KeystoreConfig::Path { path, password } => KeyStore::open(path, password)
+			.map_err(|e| Error::Other(format!("{}", e))),
+		_ => Err(Error::Other("keys of a remote signer are managed through its keystore".into())),
+	}
+}
+
+/// parse a key type from its 4-character representation
+pub fn key_type_from_str(key_type: &str) -> error::Result<KeyTypeId> {
+	KeyTypeId::try_from(key_type)
+		.map_err(|_| {
+			Error::Other("Cannot convert argument to keytype: argument should be 4-character string".into())
+		})
+}
+
 /// print formatted pair from uri
 pub fn print_from_uri<Pair>(
 	uri: &str,
@@ -29,12 +29,12 @@ use names::{Generator, Name};
 use sc_client_api::execution_extensions::ExecutionStrategies;
 use sc_service::config::{
 	BasePath, Configuration, DatabaseConfig, ExtTransport, KeystoreConfig, NetworkConfiguration,
-	NodeKeyConfig, OffchainWorkerConfig, PrometheusConfig, PruningMode, Role, RpcMethods,
-	TaskExecutor, TelemetryEndpoints, TransactionPoolOptions, WasmExecutionMethod,
+	NodeKeyConfig, OffchainWorkerConfig, PrometheusConfig, PruningMode, Role, RpcLimits,
+	RpcMethods, TaskExecutor, TelemetryEndpoints, TransactionPoolOptions, WasmExecutionMethod,
 };
 use sc_service::{ChainSpec, TracingReceiver};
 use std::net::SocketAddr;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// The maximum number of characters for a node name.
 pub(crate) const NODE_NAME_MAX_LENGTH: usize = 64;
@@ -144,8 +144,9 @@ pub trait CliConfiguration<DCV: DefaultConfigurationValues = ()>: Sized {
 
 	/// Get the transaction pool options
 	///
-	/// By default this is `TransactionPoolOptions::default()`.
-	fn transaction_pool(&self) -> Result<TransactionPoolOptions> {
+	/// By default this is `TransactionPoolOptions::default()`. The `config_dir` is where a
+	/// transaction journal would be kept.
+	fn transaction_pool(&self, _config_dir: &Path) -> Result<TransactionPoolOptions> {
 		Ok(Default::default())
 	}
 
@@ -336,6 +337,27 @@ pub trait CliConfiguration<DCV: DefaultConfigurationValues = ()>: Sized {
 		Ok(Some(Vec::new()))
 	}
 
+	/// Get the maximum RPC payload in MiB (`None` if default).
+	///
+	/// By default this is `None`.
+	fn rpc_max_payload(&self) -> Result<Option<usize>> {
+		Ok(None)
+	}
+
+	/// Get the maximum number of subscriptions of a WS RPC connection (`None` if default).
+	///
+	/// By default this is `None`.
+	fn rpc_max_subscriptions_per_connection(&self) -> Result<Option<usize>> {
+		Ok(None)
+	}
+
+	/// Get the limits to the calls made to the RPC servers (`None` if unlimited)
+	///
+	/// By default this is `None`.
+	fn rpc_limits(&self) -> Result<Option<RpcLimits>> {
+		Ok(None)
+	}
+
 	/// Get the prometheus configuration (`None` if disabled)
 	///
 	/// By default this is `None`.
@@ -476,7 +498,7 @@ pub trait CliConfiguration<DCV: DefaultConfigurationValues = ()>: Sized {
 			impl_name: C::impl_name(),
 			impl_version: C::impl_version(),
 			task_executor,
-			transaction_pool: self.transaction_pool()?,
+			transaction_pool: self.transaction_pool(&config_dir)?,
 			network: self.network_config(
 				&chain_spec,
 				is_dev,
@@ -499,6 +521,9 @@ pub trait CliConfiguration<DCV: DefaultConfigurationValues = ()>: Sized {
 			rpc_methods: self.rpc_methods()?,
 			rpc_ws_max_connections: self.rpc_ws_max_connections()?,
 			rpc_cors: self.rpc_cors(is_dev)?,
+			rpc_limits: self.rpc_limits()?,
+			rpc_max_payload: self.rpc_max_payload()?,
+			rpc_max_subscriptions_per_connection: self.rpc_max_subscriptions_per_connection()?,
 			prometheus_config: self.prometheus_config(DCV::prometheus_listen_port())?,
 			telemetry_endpoints: self.telemetry_endpoints(&chain_spec)?,
 			telemetry_external_transport: self.telemetry_external_transport()?,
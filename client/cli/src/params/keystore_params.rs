@@ -18,6 +18,7 @@
 
 use crate::error::Result;
 use sc_service::config::KeystoreConfig;
+use sc_keystore::SignerAddress;
 use std::fs;
 use std::path::PathBuf;
 use structopt::StructOpt;
@@ -28,6 +29,9 @@ use std::str::FromStr;
 /// default sub directory for the key store
 const DEFAULT_KEYSTORE_CONFIG_PATH: &'static str = "keystore";
 
+/// environment variable the keystore password is read from if it is not given otherwise
+pub const KEYSTORE_PASSWORD_ENV: &'static str = "KEYSTORE_PASSWORD";
+
 /// Parameters of the keystore
 #[derive(Debug, StructOpt)]
 pub struct KeystoreParams {
@@ -35,6 +39,16 @@ pub struct KeystoreParams {
 	#[structopt(long = "keystore-path", value_name = "PATH", parse(from_os_str))]
 	pub keystore_path: Option<PathBuf>,
 
+	/// Use the keys of a remote signer instead of a keystore.
+	///
+	/// The address is either `unix:<PATH>` for a Unix socket, or a loopback TCP address.
+	#[structopt(
+		long = "keystore-remote",
+		value_name = "ADDRESS",
+		conflicts_with_all = &[ "keystore-path", "password-interactive", "password", "password-filename" ]
+	)]
+	pub keystore_remote: Option<SignerAddress>,
+
 	/// Use interactive shell for entering the password used by the keystore.
 	#[structopt(
 		long = "password-interactive",
@@ -43,6 +57,10 @@ pub struct KeystoreParams {
 	pub password_interactive: bool,
 
 	/// Password used by the keystore.
+	///
+	/// When a password is given, the keys are stored encrypted with it. If no password is
+	/// given by any of the options, it is read from the `KEYSTORE_PASSWORD` environment
+	/// variable.
 	#[structopt(
 		long = "password",
 		parse(try_from_str = secret_string_from_str),
@@ -69,6 +87,10 @@ pub fn secret_string_from_str(s: &str) -> std::result::Result<SecretString, Stri
 impl KeystoreParams {
 	/// Get the keystore configuration for the parameters
 	pub fn keystore_config(&self, base_path: &PathBuf) -> Result<KeystoreConfig> {
+		if let Some(ref address) = self.keystore_remote {
+			return Ok(KeystoreConfig::Remote(address.clone()));
+		}
+
 		let password = if self.password_interactive {
 			#[cfg(not(target_os = "unknown"))]
 			{
@@ -87,8 +109,15 @@ impl KeystoreParams {
 				.map_err(|()| "Error reading password")?;
 			password.zeroize();
 			Some(secret)
-		} else {
+		} else if self.password.is_some() {
 			self.password.clone()
+		} else if let Ok(mut password) = std::env::var(KEYSTORE_PASSWORD_ENV) {
+			let secret = std::str::FromStr::from_str(password.as_str())
+				.map_err(|()| "Error reading password")?;
+			password.zeroize();
+			Some(secret)
+		} else {
+			None
 		};
 
 		let path = self
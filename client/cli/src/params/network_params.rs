@@ -18,7 +18,7 @@
 
 use crate::params::node_key_params::NodeKeyParams;
 use sc_network::{
-	config::{NetworkConfiguration, NodeKeyConfig, NonReservedPeerMode, TransportConfig},
+	config::{NetworkConfiguration, NodeKeyConfig, NonReservedPeerMode, SyncMode, TransportConfig},
 	multiaddr::Protocol,
 };
 use sc_service::{ChainSpec, config::{Multiaddr, MultiaddrWithPeerId}};
@@ -102,6 +102,13 @@ pub struct NetworkParams {
 	/// By default this option is true for `--dev` and false otherwise.
 	#[structopt(long)]
 	pub discover_local: bool,
+
+	/// Sync to a recent finalized block by downloading finality proofs and its state, instead of
+	/// importing every block since genesis.
+	///
+	/// Only used when the database is empty.
+	#[structopt(long)]
+	pub warp_sync: bool,
 }
 
 impl NetworkParams {
@@ -162,6 +169,7 @@ impl NetworkParams {
 			},
 			max_parallel_downloads: self.max_parallel_downloads,
 			allow_non_globals_in_dht: self.discover_local || is_dev,
+			sync_mode: if self.warp_sync { SyncMode::Warp } else { SyncMode::Full },
 		}
 	}
 }
@@ -30,6 +30,14 @@ pub struct PruningParams {
 	/// 256 blocks.
 	#[structopt(long = "pruning", value_name = "PRUNING_MODE")]
 	pub pruning: Option<String>,
+
+	/// Keep a full state snapshot every given number of finalized blocks.
+	///
+	/// States older than the pruning window are then reconstructed from the
+	/// nearest preceding snapshot by replaying the following blocks, for unsafe
+	/// RPC calls only. Requires state pruning to be enabled.
+	#[structopt(long = "state-snapshot-interval", value_name = "BLOCKS")]
+	pub state_snapshot_interval: Option<u32>,
 }
 
 impl PruningParams {
@@ -39,7 +47,7 @@ impl PruningParams {
 		// `ArchiveAll`), otherwise we keep state for the last 256 blocks. if the
 		// node is an authority and pruning is enabled explicitly, then we error
 		// unless `unsafe_pruning` is set.
-		Ok(match &self.pruning {
+		let mode = match &self.pruning {
 			Some(ref s) if s == "archive" => PruningMode::ArchiveAll,
 			None if role.is_network_authority() => PruningMode::ArchiveAll,
 			None => PruningMode::default(),
@@ -56,6 +64,21 @@ impl PruningParams {
 					error::Error::Input("Invalid pruning mode specified".to_string())
 				})?)
 			}
-		})
+		};
+
+		match (mode, self.state_snapshot_interval) {
+			(mode, None) => Ok(mode),
+			(_, Some(0)) => Err(error::Error::Input(
+				"State snapshot interval must be greater than zero".to_string(),
+			)),
+			(PruningMode::Constrained(mut constraints), Some(interval)) => {
+				constraints.snapshot_interval = Some(interval);
+				Ok(PruningMode::Constrained(constraints))
+			}
+			(_, Some(_)) => Err(error::Error::Input(
+				"State snapshots require state pruning, they are not needed in archive mode"
+					.to_string(),
+			)),
+		}
 	}
 }
@@ -16,9 +16,15 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
-use sc_service::config::TransactionPoolOptions;
+use sc_service::config::{
+	TransactionPoolJournalOptions, TransactionPoolOptions, TransactionPoolSenderPolicy,
+};
+use std::{path::Path, time::Duration};
 use structopt::StructOpt;
 
+/// Name of the directory of the transaction pool journal, inside the chain directory.
+const DEFAULT_JOURNAL_PATH: &str = "txpool";
+
 /// Parameters used to create the pool configuration.
 #[derive(Debug, StructOpt)]
 pub struct TransactionPoolParams {
@@ -29,11 +35,47 @@ pub struct TransactionPoolParams {
 	/// Maximum number of kilobytes of all transactions stored in the pool.
 	#[structopt(long = "pool-kbytes", value_name = "COUNT", default_value = "20480")]
 	pub pool_kbytes: usize,
+
+	/// Maximum number of transactions of a single sender in the transaction pool.
+	///
+	/// Senders are identified by the prefix of the first tag provided by their transactions,
+	/// so this requires `--pool-sender-tag-prefix`.
+	#[structopt(long = "pool-sender-limit", value_name = "COUNT", requires = "pool-sender-tag-prefix")]
+	pub pool_sender_limit: Option<usize>,
+
+	/// Number of bytes of the first provided tag identifying the sender of a transaction.
+	///
+	/// When set, the lowest priority transaction of the sender with the most pooled
+	/// transactions is evicted first when the pool is full. Senders are not tracked by default.
+	/// Use 32 to match the 32 byte account id of the `(AccountId, Index)` tag provided by
+	/// nonce-checked transactions.
+	#[structopt(long = "pool-sender-tag-prefix", value_name = "BYTES")]
+	pub pool_sender_tag_prefix: Option<usize>,
+
+	/// Minimum priority increase in percent for a transaction to replace a pooled one.
+	#[structopt(long = "pool-replacement-bump", value_name = "PERCENT", default_value = "0")]
+	pub pool_replacement_bump: u8,
+
+	/// Persist the pooled transactions, to restore them after a restart.
+	///
+	/// The journal is written every 10 seconds and on shutdown.
+	#[structopt(long = "pool-journal")]
+	pub pool_journal: bool,
+
+	/// Maximum number of transactions kept in the transaction pool journal.
+	#[structopt(long = "pool-journal-limit", value_name = "COUNT", default_value = "8192")]
+	pub pool_journal_limit: usize,
+
+	/// Maximum age in seconds of the journaled transactions restored after a restart.
+	#[structopt(long = "pool-journal-age", value_name = "SECONDS", default_value = "3600")]
+	pub pool_journal_age: u64,
 }
 
 impl TransactionPoolParams {
 	/// Fill the given `PoolConfiguration` by looking at the cli parameters.
-	pub fn transaction_pool(&self) -> TransactionPoolOptions {
+	///
+	/// The journal, if enabled, is kept in `config_dir`.
+	pub fn transaction_pool(&self, config_dir: &Path) -> TransactionPoolOptions {
 		let mut opts = TransactionPoolOptions::default();
 
 		// ready queue
@@ -45,6 +87,22 @@ impl TransactionPoolParams {
 		opts.future.count = self.pool_limit / factor;
 		opts.future.total_bytes = self.pool_kbytes * 1024 / factor;
 
+		// sender policy
+		opts.sender_policy = TransactionPoolSenderPolicy {
+			sender_tag_prefix: self.pool_sender_tag_prefix,
+			max_per_sender: self.pool_sender_limit,
+			min_replacement_bump: self.pool_replacement_bump,
+		};
+
+		// journal
+		if self.pool_journal {
+			opts.journal = Some(TransactionPoolJournalOptions {
+				path: config_dir.join(DEFAULT_JOURNAL_PATH).join("journal"),
+				max_transactions: self.pool_journal_limit,
+				max_age: Duration::from_secs(self.pool_journal_age),
+			});
+		}
+
 		opts
 	}
 }
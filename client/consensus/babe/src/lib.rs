@@ -82,7 +82,7 @@ use sp_consensus::{ImportResult, CanAuthorWith};
 use sp_consensus::import_queue::{
 	BoxJustificationImport, BoxFinalityProofImport,
 };
-use sp_core::{crypto::Public, traits::BareCryptoStore};
+use sp_core::{crypto::Public, traits::{BareCryptoStore, SigningContext}};
 use sp_application_crypto::AppKey;
 use sp_runtime::{
 	generic::{BlockId, OpaqueDigestItemId}, Justification,
@@ -90,6 +90,7 @@ use sp_runtime::{
 };
 use sp_api::{ProvideRuntimeApi, NumberFor};
 use sc_keystore::KeyStorePtr;
+use sc_slashing_protection::SlashingProtection;
 use parking_lot::Mutex;
 use sp_inherents::{InherentDataProviders, InherentData};
 use sc_telemetry::{telemetry, CONSENSUS_TRACE, CONSENSUS_DEBUG};
@@ -178,6 +179,19 @@ impl EpochT for Epoch {
 	}
 }
 
+impl From<sp_consensus_babe::Epoch> for Epoch {
+	fn from(epoch: sp_consensus_babe::Epoch) -> Self {
+		Epoch {
+			epoch_index: epoch.epoch_index,
+			start_slot: epoch.start_slot,
+			duration: epoch.duration,
+			authorities: epoch.authorities,
+			randomness: epoch.randomness,
+			config: epoch.config,
+		}
+	}
+}
+
 impl Epoch {
 	/// Create the genesis epoch (epoch #0). This is defined to start at the slot of
 	/// the first block, so that has to be provided.
@@ -293,7 +307,7 @@ impl Config {
 				&b, |v| v == 1,
 			)?;
 			let has_api_v2 = a.has_api_with::<dyn BabeApi<B, Error = sp_blockchain::Error>, _>(
-				&b, |v| v == 2,
+				&b, |v| v >= 2,
 			)?;
 
 			if has_api_v1 {
@@ -378,7 +392,8 @@ pub fn start_babe<B, C, SC, E, I, SO, CAW, Error>(BabeParams {
 > where
 	B: BlockT,
 	C: ProvideRuntimeApi<B> + ProvideCache<B> + ProvideUncles<B> + BlockchainEvents<B>
-		+ HeaderBackend<B> + HeaderMetadata<B, Error = ClientError> + Send + Sync + 'static,
+		+ HeaderBackend<B> + HeaderMetadata<B, Error = ClientError> + AuxStore
+		+ Send + Sync + 'static,
 	C::Api: BabeApi<B>,
 	SC: SelectChain<B> + 'static,
 	E: Environment<B, Error = Error> + Send + Sync + 'static,
@@ -401,6 +416,7 @@ pub fn start_babe<B, C, SC, E, I, SO, CAW, Error>(BabeParams {
 		keystore,
 		epoch_changes: babe_link.epoch_changes.clone(),
 		slot_notification_sinks: slot_notification_sinks.clone(),
+		slashing_protection: SlashingProtection::new(client.clone()),
 		config: config.clone(),
 	};
 
@@ -471,6 +487,7 @@ struct BabeSlotWorker<B: BlockT, C, E, I, SO> {
 	keystore: KeyStorePtr,
 	epoch_changes: SharedEpochChanges<B, Epoch>,
 	slot_notification_sinks: SlotNotificationSinks<B>,
+	slashing_protection: SlashingProtection,
 	config: Config,
 }
 
@@ -592,16 +609,33 @@ impl<B, C, E, I, Error, SO> sc_consensus_slots::SimpleSlotWorker<B> for BabeSlot
 		sp_consensus::Error> + Send + 'static>
 	{
 		let keystore = self.keystore.clone();
-		Box::new(move |header, header_hash, body, storage_changes, (_, public), epoch_descriptor| {
+		let epoch_changes = self.epoch_changes.clone();
+		let slashing_protection = self.slashing_protection.clone();
+		let config = self.config.clone();
+		Box::new(move |header, header_hash, body, storage_changes, (pre_digest, public), epoch_descriptor| {
 			// sign the pre-sealed hash of the block and then
 			// add it to a digest item.
 			let public_type_pair = public.clone().into();
 			let public = public.to_raw_vec();
+
+			// refuse to author a block conflicting with one authored before.
+			let epoch_index = epoch_changes.lock()
+				.viable_epoch(&epoch_descriptor, |slot| Epoch::genesis(&config, slot))
+				.map(|epoch| epoch.as_ref().epoch_index)
+				.ok_or(sp_consensus::Error::InvalidAuthoritiesSet)?;
+			slashing_protection.check_babe_block(
+				&public,
+				epoch_index,
+				pre_digest.slot_number(),
+				header_hash.as_ref(),
+			).map_err(|e| sp_consensus::Error::CannotSign(public.clone(), e.to_string()))?;
+
 			let signature = keystore.read()
-				.sign_with(
+				.sign_with_context(
 					<AuthorityId as AppKey>::ID,
 					&public_type_pair,
-					header_hash.as_ref()
+					header_hash.as_ref(),
+					SigningContext::BabeSlot(pre_digest.slot_number()),
 				)
 				.map_err(|e| sp_consensus::Error::CannotSign(
 					public.clone(), e.to_string(),
@@ -1136,6 +1170,10 @@ impl<Block, Client, Inner> BlockImport<Block> for BabeBlockImport<Block, Client,
 			Err(e) => return Err(ConsensusError::ClientImport(e.to_string())),
 		}
 
+		if block.state.is_some() {
+			return self.import_state(block, new_cache);
+		}
+
 		let pre_digest = find_pre_digest::<Block>(&block.header)
 			.expect("valid babe headers must contain a predigest; \
 					 header has been already verified; qed");
@@ -1361,6 +1399,69 @@ impl<Block, Client, Inner> BlockImport<Block> for BabeBlockImport<Block, Client,
 	}
 }
 
+impl<Block, Client, Inner> BabeBlockImport<Block, Client, Inner> where
+	Block: BlockT,
+	Inner: BlockImport<Block, Transaction = sp_api::TransactionFor<Client, Block>> + Send + Sync,
+	Inner::Error: Into<ConsensusError>,
+	Client: AuxStore + ProvideRuntimeApi<Block> + Send + Sync,
+	Client::Api: BabeApi<Block> + ApiExt<Block>,
+{
+	/// Import a block together with its state, as a new finalized base of the chain.
+	///
+	/// Its ancestors are unknown, so the epoch changes are reset to the epochs of the runtime
+	/// at that block, and the weight of the chain is counted from it.
+	fn import_state(
+		&mut self,
+		mut block: BlockImportParams<Block, sp_api::TransactionFor<Client, Block>>,
+		new_cache: HashMap<CacheKeyId, Vec<u8>>,
+	) -> Result<ImportResult, ConsensusError> {
+		let hash = block.post_hash();
+		let number = *block.header.number();
+
+		let pre_digest = find_pre_digest::<Block>(&block.header)
+			.map_err(|e| ConsensusError::ClientImport(babe_err(e).into()))?;
+
+		aux_schema::write_block_weight(
+			hash,
+			pre_digest.added_weight(),
+			|values| block.auxiliary.extend(
+				values.iter().map(|(k, v)| (k.to_vec(), Some(v.to_vec())))
+			),
+		);
+		block.fork_choice = Some(ForkChoiceStrategy::Custom(true));
+
+		let import_result = match self.inner.import_block(block, new_cache) {
+			Ok(import_result) => import_result,
+			Err(e) => return Err(e.into()),
+		};
+
+		let at = BlockId::Hash(hash);
+		let runtime_api = self.client.runtime_api();
+		let current_epoch = runtime_api.current_epoch(&at)
+			.map_err(|e| ConsensusError::ClientImport(format!("{:?}", e)))?;
+		let next_epoch = runtime_api.next_epoch(&at)
+			.map_err(|e| ConsensusError::ClientImport(format!("{:?}", e)))?;
+
+		let epoch_index = current_epoch.epoch_index;
+		let mut epoch_changes = self.epoch_changes.lock();
+		epoch_changes.reset(hash, number, current_epoch.into(), next_epoch.into());
+		aux_schema::write_epoch_changes::<Block, _, _>(
+			&*epoch_changes,
+			|insert| self.client.insert_aux(insert, &[]),
+		).map_err(|e| ConsensusError::ClientImport(e.to_string()))?;
+
+		info!(
+			target: "babe",
+			"👶 Imported state of #{} ({}), resetting epoch changes to epoch {}",
+			number,
+			hash,
+			epoch_index,
+		);
+
+		Ok(import_result)
+	}
+}
+
 /// Gets the best finalized block and its slot, and prunes the given epoch tree.
 fn prune_finalized<Block, Client>(
 	client: Arc<Client>,
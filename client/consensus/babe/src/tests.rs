@@ -779,6 +779,132 @@ fn importing_epoch_change_block_prunes_tree() {
 	);
 }
 
+#[test]
+fn importing_block_with_state_resets_epoch_changes() {
+	use sc_client_api::StorageProvider;
+	use sp_consensus::{
+		ImportedState,
+		import_queue::{import_single_block, BlockImportResult, IncomingBlock},
+	};
+	use sp_core::storage::StorageKey;
+
+	let mut net = BabeTestNet::new(2);
+
+	// Build a chain on the first peer, its last block is the one we warp to.
+	let (source_client, warp_hash) = {
+		let peer = net.peer(0);
+		let data = peer.data.as_ref().expect("babe link set up during initialization");
+
+		let client = peer.client().as_full().expect("Only full clients are used in tests").clone();
+		let mut block_import = data.block_import.lock().take().expect("import set up during init");
+
+		let mut proposer_factory = DummyFactory {
+			client: client.clone(),
+			config: data.link.config.clone(),
+			epoch_changes: data.link.epoch_changes.clone(),
+			mutator: Arc::new(|_, _| ()),
+		};
+
+		let mut parent_header = client.header(&BlockId::Number(0)).unwrap().unwrap();
+		for _ in 0..10 {
+			let block_hash = propose_and_import_block(
+				&parent_header,
+				None,
+				&mut proposer_factory,
+				&mut block_import,
+			);
+			parent_header = client.header(&BlockId::Hash(block_hash)).unwrap().unwrap();
+		}
+
+		(client, parent_header.hash())
+	};
+
+	let warp_header = source_client.header(&BlockId::Hash(warp_hash)).unwrap().unwrap();
+	let top = source_client.storage_pairs(&BlockId::Hash(warp_hash), &StorageKey(Vec::new()))
+		.unwrap()
+		.into_iter()
+		.map(|(key, value)| (key.0, value.0))
+		.collect();
+
+	// The second peer knows nothing but genesis and imports the block together with its
+	// state through the import queue. The verifier would reject the block since its
+	// parent is unknown, so it must not be called.
+	let peers_client = net.peer(1).client().clone();
+	let mut verifier = net.make_verifier(peers_client, &Default::default(), &net.peers()[1].data);
+
+	let peer = net.peer(1);
+	let data = peer.data.as_ref().expect("babe link set up during initialization");
+
+	let client = peer.client().as_full().expect("Only full clients are used in tests").clone();
+	let mut block_import = data.block_import.lock().take().expect("import set up during init");
+	let epoch_changes = data.link.epoch_changes.clone();
+
+	let result = import_single_block(
+		&mut *block_import,
+		BlockOrigin::NetworkInitialSync,
+		IncomingBlock {
+			hash: warp_hash,
+			header: Some(warp_header.clone()),
+			body: None,
+			justification: None,
+			origin: None,
+			allow_missing_state: true,
+			import_existing: false,
+			state: Some(ImportedState { top, children: Vec::new() }),
+		},
+		&mut verifier,
+	).unwrap();
+
+	match result {
+		BlockImportResult::ImportedUnknown(10, _, _) => {},
+		r => panic!("Unexpected import result: {:?}", r),
+	}
+
+	let info = client.info();
+	assert_eq!(info.best_hash, warp_hash);
+	assert_eq!(info.finalized_hash, warp_hash);
+	assert!(aux_schema::load_block_weight(&*client, warp_hash).unwrap().is_some());
+
+	// The epoch changes only contain the warped block, seeded with the epochs of the runtime.
+	let runtime_api = client.runtime_api();
+	let current: Epoch = runtime_api.current_epoch(&BlockId::Hash(warp_hash)).unwrap().into();
+	let next: Epoch = runtime_api.next_epoch(&BlockId::Hash(warp_hash)).unwrap().into();
+
+	assert_eq!(
+		epoch_changes.lock().tree().iter().map(|(h, _, _)| *h).collect::<Vec<_>>(),
+		vec![warp_hash],
+	);
+
+	let epoch_for_child = |slot_number| epoch_changes.lock().epoch_data_for_child_of(
+		descendent_query(&*client),
+		&warp_hash,
+		10,
+		slot_number,
+		|_| unreachable!("the genesis epoch is not needed after a reset"),
+	).unwrap().unwrap();
+
+	assert_eq!(epoch_for_child(current.start_slot), current);
+	assert_eq!(epoch_for_child(next.start_slot), next);
+
+	// And the chain continues on top of the warped block.
+	let mut proposer_factory = DummyFactory {
+		client: client.clone(),
+		config: data.link.config.clone(),
+		epoch_changes: data.link.epoch_changes.clone(),
+		mutator: Arc::new(|_, _| ()),
+	};
+
+	let block_hash = propose_and_import_block(
+		&warp_header,
+		None,
+		&mut proposer_factory,
+		&mut block_import,
+	);
+
+	assert_eq!(client.info().best_hash, block_hash);
+	assert_eq!(client.info().best_number, 11);
+}
+
 #[test]
 #[should_panic]
 fn verify_slots_are_strictly_increasing() {
@@ -636,6 +636,29 @@ impl<Hash, Number, E: Epoch> EpochChanges<Hash, Number, E> where
 		}
 	}
 
+	/// Reset to the epochs live at the given block, for a chain starting from that block.
+	///
+	/// The block's ancestors don't need to be known: `current` is the epoch of the block and
+	/// `next` the epoch that was announced before it, they are bundled together in a single
+	/// entry of the tree signalled at the block itself, like the genesis epochs.
+	pub fn reset(&mut self, hash: Hash, number: Number, current: E, next: E) {
+		let persisted = PersistedEpoch::Genesis(current, next);
+		let header = PersistedEpochHeader::<E>::from(&persisted);
+
+		self.inner = ForkTree::new();
+		self.epochs.clear();
+
+		let res = self.inner.import(
+			hash,
+			number,
+			header,
+			&|_: &Hash, _: &Hash| Ok::<_, ClientError>(false),
+		);
+		debug_assert!(res.is_ok(), "importing into an empty tree can't fail; qed");
+
+		self.epochs.insert((hash, number), persisted);
+	}
+
 	/// Return the inner fork tree.
 	pub fn tree(&self) -> &ForkTree<Hash, Number, PersistedEpochHeader<E>> {
 		&self.inner
@@ -967,4 +990,51 @@ mod tests {
 			assert!(epoch_for_x_child_before_genesis.is_none());
 		}
 	}
+
+	#[test]
+	fn reset_epochs_do_not_need_ancestors() {
+		//
+		// (unknown ancestors) - W - X
+		//
+		let is_descendent_of = |base: &Hash, block: &Hash| -> Result<bool, TestError> {
+			match (base, block) {
+				(b"W", b"X") => Ok(true),
+				_ => panic!("ancestry of {:?} and {:?} should not be queried", base, block),
+			}
+		};
+		let make_genesis = |_: SlotNumber| -> Epoch { unreachable!("the genesis epoch is not used") };
+
+		let mut epoch_changes = EpochChanges::<_, _, Epoch>::new();
+		let genesis_epoch = Epoch { start_slot: 0, duration: 50 };
+		epoch_changes.import(
+			&|_: &Hash, _: &Hash| Ok::<_, TestError>(false),
+			*b"A",
+			1,
+			*b"0",
+			IncrementedEpoch(PersistedEpoch::Genesis(genesis_epoch.clone(), genesis_epoch.increment(()))),
+		).unwrap();
+
+		let current = Epoch { start_slot: 100, duration: 50 };
+		let next = current.increment(());
+		epoch_changes.reset(*b"W", 10, current.clone(), next.clone());
+		assert_eq!(epoch_changes.tree().iter().map(|(hash, _, _)| *hash).collect::<Vec<_>>(), vec![*b"W"]);
+
+		// a child of the block is in the current epoch
+		let descriptor = epoch_changes.epoch_descriptor_for_child_of(
+			&is_descendent_of,
+			b"W",
+			10,
+			120,
+		).unwrap().unwrap();
+		assert_eq!(epoch_changes.epoch_data(&descriptor, &make_genesis).unwrap(), current);
+
+		// a descendent of the block is in the next epoch once it started
+		let descriptor = epoch_changes.epoch_descriptor_for_child_of(
+			&is_descendent_of,
+			b"X",
+			11,
+			160,
+		).unwrap().unwrap();
+		assert_eq!(epoch_changes.epoch_data(&descriptor, &make_genesis).unwrap(), next);
+	}
 }
@@ -37,6 +37,7 @@ pub mod bench;
 mod children;
 mod cache;
 mod changes_tries_storage;
+mod snapshot;
 mod storage_cache;
 #[cfg(any(feature = "with-kvdb-rocksdb", test))]
 mod upgrade;
@@ -63,7 +64,7 @@ use codec::{Decode, Encode};
 use hash_db::Prefix;
 use sp_trie::{MemoryDB, PrefixedMemoryDB, prefixed_key};
 use sp_database::Transaction;
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 use sp_core::ChangesTrieConfiguration;
 use sp_core::offchain::storage::{OffchainOverlayedChange, OffchainOverlayedChanges};
 use sp_core::storage::{well_known_keys, ChildInfo};
@@ -590,6 +591,7 @@ pub struct BlockImportOperation<Block: BlockT> {
 	finalized_blocks: Vec<(BlockId<Block>, Option<Justification>)>,
 	set_head: Option<BlockId<Block>>,
 	commit_state: bool,
+	reset_storage: bool,
 }
 
 impl<Block: BlockT> BlockImportOperation<Block> {
@@ -683,6 +685,7 @@ impl<Block: BlockT> sc_client_api::backend::BlockImportOperation<Block> for Bloc
 		self.db_updates = transaction;
 		self.changes_trie_config_update = Some(changes_trie_config);
 		self.commit_state = true;
+		self.reset_storage = true;
 		Ok(root)
 	}
 
@@ -832,6 +835,9 @@ pub struct Backend<Block: BlockT> {
 	shared_cache: SharedCache<Block>,
 	import_lock: Arc<RwLock<()>>,
 	is_archive: bool,
+	snapshot_interval: Option<u32>,
+	snapshot_worker: Mutex<Option<(u64, std::thread::JoinHandle<()>)>>,
+	reconstructed_states: snapshot::ReconstructedStates<Block>,
 	io_stats: FrozenForDuration<(kvdb::IoStats, StateUsageInfo)>,
 	state_usage: Arc<StateUsageStats>,
 }
@@ -910,6 +916,9 @@ impl<Block: BlockT> Backend<Block> {
 			),
 			import_lock: Default::default(),
 			is_archive: is_archive_pruning,
+			snapshot_interval: config.pruning.snapshot_interval(),
+			snapshot_worker: Mutex::new(None),
+			reconstructed_states: Default::default(),
 			io_stats: FrozenForDuration::new(std::time::Duration::from_secs(1)),
 			state_usage: Arc::new(StateUsageStats::new()),
 		})
@@ -1061,11 +1070,149 @@ impl<Block: BlockT> Backend<Block> {
 			let commit = self.storage.state_db.canonicalize_block(&hash)
 				.map_err(|e: sc_state_db::Error<io::Error>| sp_blockchain::Error::from(format!("State database error: {:?}", e)))?;
 			apply_state_commit(transaction, commit);
+			self.snapshot_canonical(transaction, hash, new_canonical)?;
 		};
 
 		Ok(())
 	}
 
+	// starts a full state snapshot if the newly canonicalized block is due one, and drops
+	// the journaled state changes that will never be replayed.
+	fn snapshot_canonical(
+		&self,
+		transaction: &mut Transaction<DbHash>,
+		hash: Block::Hash,
+		number: u64,
+	) -> ClientResult<()> {
+		let interval = match self.snapshot_interval {
+			Some(interval) => interval,
+			None => return Ok(()),
+		};
+		let header = self.blockchain.header_metadata(hash)?;
+
+		let mut abandoned: Vec<Block::Hash> = children::read_children(
+			&*self.storage.db,
+			columns::META,
+			meta_keys::CHILDREN_PREFIX,
+			header.parent,
+		)?;
+		abandoned.retain(|child| *child != hash);
+		while let Some(block) = abandoned.pop() {
+			snapshot::delete_diff(transaction, &block);
+			abandoned.extend(children::read_children(
+				&*self.storage.db,
+				columns::META,
+				meta_keys::CHILDREN_PREFIX,
+				block,
+			)?);
+		}
+
+		let base = snapshot::snapshot_base(interval, number);
+		if base == number {
+			snapshot::delete_diff(transaction, &hash);
+			self.start_snapshot(hash, number, header.state_root);
+		} else {
+			let pending = self.snapshot_worker.lock().as_ref().map(|(pending, _)| *pending);
+			if pending != Some(base) && !snapshot::has_snapshot(&*self.storage.db, base) {
+				snapshot::delete_diff(transaction, &hash);
+			}
+		}
+		Ok(())
+	}
+
+	// writes the snapshot of a canonical block in the background, keeping its state
+	// pinned until it is written. waits for the previous snapshot to be written first.
+	fn start_snapshot(&self, hash: Block::Hash, number: u64, state_root: Block::Hash) {
+		let mut worker = self.snapshot_worker.lock();
+		if let Some((pending, handle)) = worker.take() {
+			if handle.join().is_err() {
+				warn!(target: "db", "State snapshot at #{} panicked", pending);
+			}
+		}
+		if self.storage.state_db.pin(&hash).is_err() {
+			warn!(target: "db", "Skipping state snapshot at #{}, the state of {:?} is not available", number, hash);
+			return
+		}
+
+		let state = RefTrackingState::new(
+			DbState::<Block>::new(self.storage.clone(), state_root),
+			self.storage.clone(),
+			Some(hash),
+		);
+		let db = self.storage.db.clone();
+		debug!(target: "db", "Writing state snapshot at #{} ({:?})", number, hash);
+		let spawned = std::thread::Builder::new()
+			.name("state-snapshot".into())
+			.spawn(move || {
+				if let Err(e) = snapshot::write_snapshot::<Block, _>(&*db, &state, number, hash, state_root) {
+					warn!(target: "db", "Error writing state snapshot at #{}: {:?}", number, e);
+				}
+			});
+		match spawned {
+			Ok(handle) => *worker = Some((number, handle)),
+			Err(e) => warn!(target: "db", "Error spawning state snapshot at #{}: {:?}", number, e),
+		}
+	}
+
+	/// Wait for the state snapshot being written, if any.
+	#[cfg(test)]
+	fn wait_for_snapshot(&self) {
+		if let Some((_, handle)) = self.snapshot_worker.lock().take() {
+			handle.join().expect("state snapshot does not panic");
+		}
+	}
+
+	// reconstructs a pruned canonical state from the nearest snapshot.
+	fn reconstruct_state(
+		&self,
+		block: BlockId<Block>,
+		hash: Block::Hash,
+		number: NumberFor<Block>,
+		state_root: Block::Hash,
+	) -> ClientResult<DbState<Block>> {
+		let number_u64 = number.saturated_into::<u64>();
+		let pruned = self.storage.state_db.first_unpruned().map_or(false, |first| number_u64 < first);
+		let interval = match self.snapshot_interval {
+			Some(interval) if pruned && self.blockchain.hash(number)? == Some(hash) => interval,
+			_ => return Err(self.state_unavailable(block, number_u64, None)),
+		};
+
+		if let Some(state) = self.reconstructed_states.get(&hash) {
+			return Ok(state)
+		}
+		let canonical_hash = |n: u64| self.blockchain.hash(n.saturated_into());
+		match snapshot::reconstruct::<Block>(&*self.storage.db, interval, number_u64, state_root, canonical_hash)? {
+			Some(state) => {
+				self.reconstructed_states.insert(hash, &state);
+				Ok(state)
+			},
+			None => Err(self.state_unavailable(block, number_u64, Some(interval))),
+		}
+	}
+
+	// error naming the range of blocks whose state is unavailable.
+	fn state_unavailable(
+		&self,
+		block: BlockId<Block>,
+		number: u64,
+		snapshot_interval: Option<u32>,
+	) -> ClientError {
+		let first_unpruned = match self.storage.state_db.first_unpruned() {
+			Some(first_unpruned) if number < first_unpruned => first_unpruned,
+			_ => return sp_blockchain::Error::UnknownBlock(
+				format!("State already discarded for {:?}", block)
+			),
+		};
+		let available_from = snapshot_interval
+			.and_then(|interval| snapshot::snapshot_after(&*self.storage.db, interval, number, first_unpruned))
+			.unwrap_or(first_unpruned);
+		sp_blockchain::Error::UnknownBlock(format!(
+			"State already discarded for {:?}: states of blocks #0 to #{} are unavailable",
+			block,
+			available_from - 1,
+		))
+	}
+
 	fn try_commit_operation(
 		&self,
 		mut operation: BlockImportOperation<Block>,
@@ -1104,7 +1251,15 @@ impl<Block: BlockT> Backend<Block> {
 			// blocks are keyed by number + hash.
 			let lookup_key = utils::number_and_hash_to_lookup_key(number, hash)?;
 
-			let (enacted, retracted) = if pending_block.leaf_state.is_best() {
+			// A non-genesis block imported with its full state becomes the new base of the
+			// chain: its ancestors are not known.
+			let is_new_base = operation.reset_storage && !number.is_zero();
+
+			let (enacted, retracted) = if is_new_base {
+				transaction.set_from_vec(columns::META, meta_keys::BEST_BLOCK, lookup_key.clone());
+				utils::insert_number_to_key_mapping(&mut transaction, columns::KEY_LOOKUP, number, hash)?;
+				(Default::default(), Default::default())
+			} else if pending_block.leaf_state.is_best() {
 				self.set_head_with_transaction(&mut transaction, parent_hash, (number, hash))?
 			} else {
 				(Default::default(), Default::default())
@@ -1189,13 +1344,30 @@ impl<Block: BlockT> Backend<Block> {
 						}
 				}
 				self.state_usage.tally_writes(ops, bytes);
+				if self.snapshot_interval.is_some() {
+					snapshot::write_diff(
+						&mut transaction,
+						&hash,
+						&operation.storage_updates,
+						&operation.child_storage_updates,
+					);
+				}
 				let number_u64 = number.saturated_into::<u64>();
-				let commit = self.storage.state_db.insert_block(
-					&hash,
-					number_u64,
-					&pending_block.header.parent_hash(),
-					changeset,
-				).map_err(|e: sc_state_db::Error<io::Error>|
+				let commit = if is_new_base {
+					self.storage.state_db.reset_base(
+						&hash,
+						number_u64,
+						&pending_block.header.parent_hash(),
+						changeset,
+					)
+				} else {
+					self.storage.state_db.insert_block(
+						&hash,
+						number_u64,
+						&pending_block.header.parent_hash(),
+						changeset,
+					)
+				}.map_err(|e: sc_state_db::Error<io::Error>|
 					sp_blockchain::Error::from(format!("State database error: {:?}", e))
 				)?;
 				apply_state_commit(&mut transaction, commit);
@@ -1211,13 +1383,20 @@ impl<Block: BlockT> Backend<Block> {
 			let is_best = pending_block.leaf_state.is_best();
 			let changes_trie_updates = operation.changes_trie_updates;
 			let changes_trie_config_update = operation.changes_trie_config_update;
-			changes_trie_cache_ops = Some(self.changes_tries_storage.commit(
-				&mut transaction,
-				changes_trie_updates,
+			let cache_parent = if is_new_base {
+				// the configuration of the new base follows the last finalized block.
+				let meta = self.blockchain.meta.read();
+				cache::ComplexBlockId::new(meta.finalized_hash, meta.finalized_number)
+			} else {
 				cache::ComplexBlockId::new(
 					*header.parent_hash(),
 					if number.is_zero() { Zero::zero() } else { number - One::one() },
-				),
+				)
+			};
+			changes_trie_cache_ops = Some(self.changes_tries_storage.commit(
+				&mut transaction,
+				changes_trie_updates,
+				cache_parent,
 				cache::ComplexBlockId::new(hash, number),
 				header,
 				finalized,
@@ -1230,7 +1409,9 @@ impl<Block: BlockT> Backend<Block> {
 
 			if finalized {
 				// TODO: ensure best chain contains this block.
-				self.ensure_sequential_finalization(header, Some(last_finalized_hash))?;
+				if !is_new_base {
+					self.ensure_sequential_finalization(header, Some(last_finalized_hash))?;
+				}
 				self.note_finalized(
 					&mut transaction,
 					true,
@@ -1354,6 +1535,7 @@ impl<Block: BlockT> Backend<Block> {
 			let commit = self.storage.state_db.canonicalize_block(&f_hash)
 				.map_err(|e: sc_state_db::Error<io::Error>| sp_blockchain::Error::from(format!("State database error: {:?}", e)))?;
 			apply_state_commit(transaction, commit);
+			self.snapshot_canonical(transaction, f_hash, f_num.saturated_into::<u64>())?;
 
 			if !f_num.is_zero() {
 				let new_changes_trie_cache_ops = self.changes_tries_storage.finalize(
@@ -1441,6 +1623,7 @@ impl<Block: BlockT> sc_client_api::backend::Backend<Block> for Backend<Block> {
 			finalized_blocks: Vec::new(),
 			set_head: None,
 			commit_state: false,
+			reset_storage: false,
 		})
 	}
 
@@ -1682,11 +1865,19 @@ impl<Block: BlockT> sc_client_api::backend::Backend<Block> for Backend<Block> {
 		match self.blockchain.header_metadata(hash) {
 			Ok(ref hdr) => {
 				if !self.have_state_at(&hash, hdr.number) {
-					return Err(
-						sp_blockchain::Error::UnknownBlock(
-							format!("State already discarded for {:?}", block)
-						)
-					)
+					let db_state = self.reconstruct_state(block, hash, hdr.number, hdr.state_root)?;
+					let state = RefTrackingState::new(db_state, self.storage.clone(), None);
+					let caching_state = CachingState::new(
+						state,
+						self.shared_cache.clone(),
+						None,
+					);
+					return Ok(SyncingCachingState::new(
+						caching_state,
+						self.state_usage.clone(),
+						self.blockchain.meta.clone(),
+						self.import_lock.clone(),
+					))
 				}
 				if let Ok(()) = self.storage.state_db.pin(&hash) {
 					let root = hdr.state_root;
@@ -1956,6 +2147,217 @@ pub(crate) mod tests {
 		}
 	}
 
+	fn import_counter_blocks(backend: &Backend<Block>, count: u64) {
+		let mut hash = Default::default();
+		for number in 0..count {
+			let mut op = backend.begin_operation().unwrap();
+			backend.begin_state_operation(&mut op, BlockId::Hash(hash)).unwrap();
+			let mut header = Header {
+				number,
+				parent_hash: hash,
+				state_root: Default::default(),
+				digest: Default::default(),
+				extrinsics_root: Default::default(),
+			};
+
+			let storage = vec![
+				(b"counter".to_vec(), Some(number.encode())),
+				(number.encode(), Some(vec![number as u8])),
+			];
+
+			let (root, overlay) = op.old_state.storage_root(
+				storage.iter()
+					.map(|(k, v)| (&k[..], v.as_ref().map(|v| &v[..])))
+			);
+			header.state_root = root.into();
+
+			if number == 0 {
+				op.reset_storage(Storage {
+					top: storage.into_iter().map(|(k, v)| (k, v.unwrap())).collect(),
+					children_default: Default::default(),
+				}).unwrap();
+			} else {
+				op.update_db_storage(overlay).unwrap();
+				op.update_storage(storage, Vec::new()).unwrap();
+			}
+			op.set_block_data(
+				header.clone(),
+				Some(vec![]),
+				None,
+				NewBlockState::Final,
+			).unwrap();
+
+			backend.commit_operation(op).unwrap();
+			hash = header.hash();
+		}
+	}
+
+	#[test]
+	fn pruned_state_is_reconstructed_from_snapshots() {
+		let backend = Backend::<Block>::new(DatabaseSettings {
+			state_cache_size: 16777216,
+			state_cache_child_ratio: Some((50, 100)),
+			pruning: PruningMode::keep_blocks_with_snapshots(1, 2),
+			source: DatabaseSettingsSrc::Custom(
+				sp_database::as_database(kvdb_memorydb::create(crate::utils::NUM_COLUMNS))
+			),
+		}, 0).unwrap();
+		import_counter_blocks(&backend, 8);
+		backend.wait_for_snapshot();
+
+		// the block of the last snapshot may still have been pinned while pruning.
+		for number in 0..6u64 {
+			let hash = backend.blockchain().hash(number).unwrap().unwrap();
+			assert!(!backend.have_state_at(&hash, number));
+		}
+		for number in 0..7u64 {
+			let state = backend.state_at(BlockId::Number(number)).unwrap();
+			assert_eq!(state.storage(b"counter").unwrap(), Some(number.encode()));
+			assert_eq!(state.storage(&0u64.encode()).unwrap(), Some(vec![0]));
+			assert_eq!(state.storage(&number.encode()).unwrap(), Some(vec![number as u8]));
+			assert_eq!(state.storage(&(number + 1).encode()).unwrap(), None);
+		}
+
+		// changes leading to a snapshot are never replayed.
+		let diff = |number: u64| {
+			let hash = backend.blockchain().hash(number).unwrap().unwrap();
+			backend.storage.db.get(columns::STATE_META, &snapshot::diff_key(&hash))
+		};
+		for number in 0..8u64 {
+			assert_eq!(diff(number).is_some(), number % 2 == 1);
+		}
+
+		let state = backend.state_at(BlockId::Number(7)).unwrap();
+		assert_eq!(state.storage(b"counter").unwrap(), Some(7u64.encode()));
+	}
+
+	#[test]
+	fn reconstructed_states_are_cached() {
+		let backend = Backend::<Block>::new(DatabaseSettings {
+			state_cache_size: 16777216,
+			state_cache_child_ratio: Some((50, 100)),
+			pruning: PruningMode::keep_blocks_with_snapshots(1, 4),
+			source: DatabaseSettingsSrc::Custom(
+				sp_database::as_database(kvdb_memorydb::create(crate::utils::NUM_COLUMNS))
+			),
+		}, 0).unwrap();
+		import_counter_blocks(&backend, 8);
+		backend.wait_for_snapshot();
+
+		let hash = backend.blockchain().hash(3).unwrap().unwrap();
+		assert!(backend.state_at(BlockId::Number(3)).is_ok());
+
+		// the cached state no longer needs the journaled changes.
+		let mut transaction = Transaction::new();
+		snapshot::delete_diff(&mut transaction, &hash);
+		backend.storage.db.commit(transaction).unwrap();
+		let state = backend.state_at(BlockId::Number(3)).unwrap();
+		assert_eq!(state.storage(b"counter").unwrap(), Some(3u64.encode()));
+	}
+
+	#[test]
+	fn journaled_changes_of_abandoned_forks_are_dropped() {
+		let backend = Backend::<Block>::new(DatabaseSettings {
+			state_cache_size: 16777216,
+			state_cache_child_ratio: Some((50, 100)),
+			pruning: PruningMode::keep_blocks_with_snapshots(10, 4),
+			source: DatabaseSettingsSrc::Custom(
+				sp_database::as_database(kvdb_memorydb::create(crate::utils::NUM_COLUMNS))
+			),
+		}, 10).unwrap();
+		let diff = |hash: H256| backend.storage.db.get(columns::STATE_META, &snapshot::diff_key(&hash));
+
+		let block0 = insert_header(&backend, 0, Default::default(), None, Default::default());
+		let block1_a = insert_header(&backend, 1, block0, None, Default::default());
+		let block1_b = insert_header(&backend, 1, block0, None, [1; 32].into());
+		let block2_b = insert_header(&backend, 2, block1_b, None, Default::default());
+		assert!(diff(block1_a).is_some() && diff(block1_b).is_some() && diff(block2_b).is_some());
+
+		backend.finalize_block(BlockId::hash(block1_a), None).unwrap();
+		backend.wait_for_snapshot();
+		assert!(diff(block1_a).is_some());
+		assert!(diff(block1_b).is_none());
+		assert!(diff(block2_b).is_none());
+	}
+
+	#[test]
+	fn pruned_state_error_names_unavailable_range() {
+		let backend = Backend::<Block>::new_test(1, 0);
+		import_counter_blocks(&backend, 4);
+
+		let err = backend.state_at(BlockId::Number(1)).err().unwrap();
+		assert!(err.to_string().contains("states of blocks #0 to #2 are unavailable"));
+		assert!(backend.state_at(BlockId::Number(3)).is_ok());
+	}
+
+	#[test]
+	fn state_imported_as_new_base() {
+		let backend = Backend::<Block>::new_test(2, 0);
+		import_counter_blocks(&backend, 1);
+
+		let base = {
+			let mut op = backend.begin_operation().unwrap();
+			let storage = vec![
+				(b"counter".to_vec(), 10u64.encode()),
+				(b"base".to_vec(), vec![1]),
+			];
+			let header = Header {
+				number: 10,
+				parent_hash: [9u8; 32].into(),
+				state_root: op.old_state.storage_root(
+					storage.iter().map(|(k, v)| (&k[..], Some(&v[..])))
+				).0.into(),
+				digest: Default::default(),
+				extrinsics_root: Default::default(),
+			};
+			op.reset_storage(Storage {
+				top: storage.into_iter().collect(),
+				children_default: Default::default(),
+			}).unwrap();
+			op.set_block_data(
+				header.clone(),
+				Some(vec![]),
+				None,
+				NewBlockState::Final,
+			).unwrap();
+			backend.commit_operation(op).unwrap();
+			header.hash()
+		};
+
+		let info = backend.blockchain().info();
+		assert_eq!((info.best_hash, info.best_number), (base, 10));
+		assert_eq!((info.finalized_hash, info.finalized_number), (base, 10));
+		assert_eq!(backend.blockchain().hash(10).unwrap(), Some(base));
+		assert_eq!(backend.blockchain().leaves().unwrap(), vec![base]);
+		let state = backend.state_at(BlockId::Number(10)).unwrap();
+		assert_eq!(state.storage(b"counter").unwrap(), Some(10u64.encode()));
+
+		// blocks are imported and finalized on top of the new base.
+		let mut op = backend.begin_operation().unwrap();
+		backend.begin_state_operation(&mut op, BlockId::Hash(base)).unwrap();
+		let storage = vec![(b"counter".to_vec(), Some(11u64.encode()))];
+		let (root, overlay) = op.old_state.storage_root(
+			storage.iter().map(|(k, v)| (&k[..], v.as_ref().map(|v| &v[..])))
+		);
+		let header = Header {
+			number: 11,
+			parent_hash: base,
+			state_root: root.into(),
+			digest: Default::default(),
+			extrinsics_root: Default::default(),
+		};
+		op.update_db_storage(overlay).unwrap();
+		op.update_storage(storage, Vec::new()).unwrap();
+		op.set_block_data(header.clone(), Some(vec![]), None, NewBlockState::Best).unwrap();
+		backend.commit_operation(op).unwrap();
+		backend.finalize_block(BlockId::Hash(header.hash()), None).unwrap();
+
+		let state = backend.state_at(BlockId::Number(11)).unwrap();
+		assert_eq!(state.storage(b"counter").unwrap(), Some(11u64.encode()));
+		assert_eq!(state.storage(b"base").unwrap(), Some(vec![1]));
+		assert_eq!(backend.blockchain().info().finalized_number, 11);
+	}
+
 	#[test]
 	fn delete_only_when_negative_rc() {
 		sp_tracing::try_init_simple();
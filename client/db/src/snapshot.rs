@@ -0,0 +1,366 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Periodic full state snapshots for pruned databases.
+//!
+//! When a snapshot interval is configured, the key-value changes of every block
+//! imported with state are journaled, and a full copy of the state is stored whenever
+//! a block with a number divisible by the interval is canonicalized. A state that has
+//! already been pruned is then reconstructed by loading the snapshot at the preceding
+//! multiple of the interval and replaying the journaled changes of the canonical blocks
+//! that follow it, so at most `interval - 1` blocks are replayed.
+//!
+//! Snapshots are written in chunks of their own transactions, off the import path, and
+//! only become visible once their header is written after the last chunk. The changes
+//! of blocks on abandoned forks and of blocks that are not preceded by a snapshot are
+//! dropped when their height is canonicalized.
+//!
+//! Everything is stored in the state meta column, under prefixes that do not collide
+//! with the keys of the state database.
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::Arc;
+
+use codec::{Decode, Encode};
+use hash_db::Prefix;
+use parking_lot::Mutex;
+use sp_blockchain::{Error as ClientError, Result as ClientResult};
+use sp_core::storage::{well_known_keys, ChildInfo};
+use sp_database::{Database, Transaction};
+use sp_runtime::traits::{Block as BlockT, HashFor};
+use sp_state_machine::{
+	backend::Backend as StateBackend, ChildStorageCollection, DBValue, InMemoryBackend,
+	StorageCollection, StorageKey, StorageValue,
+};
+use sp_trie::{HashDBT, MemoryDB};
+use crate::{columns, DbHash, DbState};
+
+/// Prefix of the journaled changes of a block, followed by the block hash.
+const DIFF_PREFIX: &[u8] = b"diff";
+/// Prefix of a snapshot header, followed by the block number.
+const SNAPSHOT_PREFIX: &[u8] = b"snap";
+/// Prefix of a snapshot chunk, followed by the block number and the chunk index.
+const CHUNK_PREFIX: &[u8] = b"chnk";
+/// Maximum number of key-value pairs stored in a single chunk.
+const CHUNK_SIZE: usize = 4096;
+/// Number of reconstructed states kept in memory.
+const RECONSTRUCTED_CACHE_SIZE: usize = 4;
+
+/// Snapshot entry: child trie storage key (`None` for the top trie), key and value.
+type SnapshotEntry = (Option<StorageKey>, StorageKey, StorageValue);
+
+/// Stored under the snapshot key once all chunks of a snapshot are written.
+#[derive(Encode, Decode)]
+struct SnapshotHeader<H> {
+	hash: H,
+	state_root: H,
+	chunks: u32,
+}
+
+pub fn diff_key<H: Encode>(hash: &H) -> Vec<u8> {
+	let mut key = DIFF_PREFIX.to_vec();
+	hash.using_encoded(|s| key.extend(s));
+	key
+}
+
+fn snapshot_key(number: u64) -> Vec<u8> {
+	let mut key = SNAPSHOT_PREFIX.to_vec();
+	key.extend(&number.to_be_bytes());
+	key
+}
+
+fn chunk_key(number: u64, index: u32) -> Vec<u8> {
+	let mut key = CHUNK_PREFIX.to_vec();
+	key.extend(&number.to_be_bytes());
+	key.extend(&index.to_be_bytes());
+	key
+}
+
+/// Journal the storage changes of an imported block.
+pub fn write_diff<H: Encode>(
+	transaction: &mut Transaction<DbHash>,
+	hash: &H,
+	storage_updates: &StorageCollection,
+	child_storage_updates: &ChildStorageCollection,
+) {
+	transaction.set_from_vec(
+		columns::STATE_META,
+		&diff_key(hash),
+		(storage_updates, child_storage_updates).encode(),
+	);
+}
+
+/// Drop the journaled changes of a block.
+pub fn delete_diff<H: Encode>(transaction: &mut Transaction<DbHash>, hash: &H) {
+	transaction.remove(columns::STATE_META, &diff_key(hash));
+}
+
+/// Writes snapshot entries in chunks of `CHUNK_SIZE`, each in its own transaction.
+struct ChunkWriter<'a> {
+	db: &'a dyn Database<DbHash>,
+	number: u64,
+	chunks: u32,
+	chunk: Vec<SnapshotEntry>,
+}
+
+impl<'a> ChunkWriter<'a> {
+	fn push(&mut self, entry: SnapshotEntry) -> ClientResult<()> {
+		self.chunk.push(entry);
+		if self.chunk.len() == CHUNK_SIZE {
+			self.flush()?;
+		}
+		Ok(())
+	}
+
+	fn flush(&mut self) -> ClientResult<()> {
+		let chunk = std::mem::replace(&mut self.chunk, Vec::with_capacity(CHUNK_SIZE));
+		let mut transaction = Transaction::new();
+		transaction.set_from_vec(columns::STATE_META, &chunk_key(self.number, self.chunks), chunk.encode());
+		commit(self.db, transaction)?;
+		self.chunks += 1;
+		Ok(())
+	}
+
+	/// Write the last chunk and return the number of chunks.
+	fn finish(mut self) -> ClientResult<u32> {
+		if !self.chunk.is_empty() {
+			self.flush()?;
+		}
+		Ok(self.chunks)
+	}
+}
+
+fn commit(db: &dyn Database<DbHash>, transaction: Transaction<DbHash>) -> ClientResult<()> {
+	db.commit(transaction)
+		.map_err(|e| ClientError::Backend(format!("Error writing state snapshot: {}", e)))
+}
+
+/// Store a full copy of `state`, the state of canonical block `number`.
+///
+/// The state is read as it is written, so it must stay available until this returns.
+pub fn write_snapshot<Block: BlockT, S: StateBackend<HashFor<Block>>>(
+	db: &dyn Database<DbHash>,
+	state: &S,
+	number: u64,
+	hash: Block::Hash,
+	state_root: Block::Hash,
+) -> ClientResult<()> {
+	let mut writer = ChunkWriter {
+		db,
+		number,
+		chunks: 0,
+		chunk: Vec::with_capacity(CHUNK_SIZE),
+	};
+
+	let mut child_roots = Vec::new();
+	let mut result = Ok(());
+	state.for_key_values_with_prefix(&[], |key, value| {
+		if result.is_err() {
+			return
+		}
+		if well_known_keys::is_child_storage_key(key) {
+			child_roots.push(key.to_vec());
+		} else {
+			result = writer.push((None, key.to_vec(), value.to_vec()));
+		}
+	});
+	result?;
+
+	let prefix = well_known_keys::DEFAULT_CHILD_STORAGE_KEY_PREFIX;
+	for prefixed_key in child_roots {
+		if !prefixed_key.starts_with(prefix) {
+			return Err(ClientError::Backend(
+				format!("Unsupported child trie type in state of block #{}", number)
+			))
+		}
+		let storage_key = prefixed_key[prefix.len()..].to_vec();
+		let child_info = ChildInfo::new_default(&storage_key);
+		state.for_keys_in_child_storage(&child_info, |key| {
+			if result.is_err() {
+				return
+			}
+			result = state.child_storage(&child_info, key)
+				.map_err(|e| ClientError::Backend(format!("Error reading state for snapshot: {}", e)))
+				.and_then(|value| value.ok_or_else(||
+					ClientError::Backend("Child trie key vanished while taking snapshot".into())
+				))
+				.and_then(|value| writer.push((Some(storage_key.clone()), key.to_vec(), value)));
+		});
+		result?;
+	}
+
+	let chunks = writer.finish()?;
+	let header = SnapshotHeader { hash, state_root, chunks };
+	let mut transaction = Transaction::new();
+	transaction.set_from_vec(columns::STATE_META, &snapshot_key(number), header.encode());
+	commit(db, transaction)
+}
+
+/// Whether the snapshot of block `number` is stored.
+pub fn has_snapshot(db: &dyn Database<DbHash>, number: u64) -> bool {
+	db.get(columns::STATE_META, &snapshot_key(number)).is_some()
+}
+
+fn read_header<Block: BlockT>(
+	db: &dyn Database<DbHash>,
+	number: u64,
+) -> ClientResult<Option<SnapshotHeader<Block::Hash>>> {
+	match db.get(columns::STATE_META, &snapshot_key(number)) {
+		Some(raw) => SnapshotHeader::decode(&mut &raw[..])
+			.map(Some)
+			.map_err(|e| ClientError::Backend(format!("Error decoding snapshot #{}: {}", number, e))),
+		None => Ok(None),
+	}
+}
+
+/// Number of the snapshot the state of block `number` is reconstructed from.
+pub fn snapshot_base(interval: u32, number: u64) -> u64 {
+	number - number % interval as u64
+}
+
+/// Number of the first snapshot after `number` and before `limit`.
+pub fn snapshot_after(db: &dyn Database<DbHash>, interval: u32, number: u64, limit: u64) -> Option<u64> {
+	let interval = interval as u64;
+	let mut candidate = (number / interval + 1) * interval;
+	while candidate < limit {
+		if has_snapshot(db, candidate) {
+			return Some(candidate)
+		}
+		candidate += interval;
+	}
+	None
+}
+
+/// Trie node storage of a reconstructed state.
+struct SnapshotStorage<Block: BlockT>(MemoryDB<HashFor<Block>>);
+
+impl<Block: BlockT> sp_state_machine::Storage<HashFor<Block>> for SnapshotStorage<Block> {
+	fn get(&self, key: &Block::Hash, prefix: Prefix) -> Result<Option<DBValue>, String> {
+		Ok(HashDBT::get(&self.0, key, prefix))
+	}
+}
+
+/// Recently reconstructed states, by block hash.
+pub struct ReconstructedStates<Block: BlockT> {
+	states: Mutex<VecDeque<(Block::Hash, DbState<Block>)>>,
+}
+
+impl<Block: BlockT> Default for ReconstructedStates<Block> {
+	fn default() -> Self {
+		ReconstructedStates { states: Mutex::new(VecDeque::with_capacity(RECONSTRUCTED_CACHE_SIZE)) }
+	}
+}
+
+impl<Block: BlockT> ReconstructedStates<Block> {
+	/// The reconstructed state of block `hash`, if it is cached.
+	pub fn get(&self, hash: &Block::Hash) -> Option<DbState<Block>> {
+		let mut states = self.states.lock();
+		let index = states.iter().position(|(cached, _)| cached == hash)?;
+		let entry = states.remove(index)?;
+		let state = DbState::<Block>::new(entry.1.backend_storage().clone(), *entry.1.root());
+		states.push_front(entry);
+		Some(state)
+	}
+
+	/// Cache the reconstructed state of block `hash`, evicting the least recently used one.
+	pub fn insert(&self, hash: Block::Hash, state: &DbState<Block>) {
+		let mut states = self.states.lock();
+		if states.len() == RECONSTRUCTED_CACHE_SIZE {
+			states.pop_back();
+		}
+		states.push_front((hash, DbState::<Block>::new(state.backend_storage().clone(), *state.root())));
+	}
+}
+
+/// Reconstruct the state of canonical block `number` from the snapshot at the preceding
+/// multiple of `interval`. `canonical_hash` resolves the hash of a canonical block by number.
+///
+/// Returns `None` if there is no such snapshot.
+pub fn reconstruct<Block: BlockT>(
+	db: &dyn Database<DbHash>,
+	interval: u32,
+	number: u64,
+	state_root: Block::Hash,
+	canonical_hash: impl Fn(u64) -> ClientResult<Option<Block::Hash>>,
+) -> ClientResult<Option<DbState<Block>>> {
+	let base = snapshot_base(interval, number);
+	let header = match read_header::<Block>(db, base)? {
+		Some(header) => header,
+		None => return Ok(None),
+	};
+	if canonical_hash(base)? != Some(header.hash) {
+		return Err(ClientError::Backend(format!("Snapshot #{} is not canonical", base)))
+	}
+
+	let mut top: BTreeMap<StorageKey, StorageValue> = BTreeMap::new();
+	let mut children: HashMap<StorageKey, BTreeMap<StorageKey, StorageValue>> = HashMap::new();
+	for index in 0..header.chunks {
+		let raw = db.get(columns::STATE_META, &chunk_key(base, index))
+			.ok_or_else(|| ClientError::Backend(format!("Missing chunk {} of snapshot #{}", index, base)))?;
+		let chunk: Vec<SnapshotEntry> = Decode::decode(&mut &raw[..])
+			.map_err(|e| ClientError::Backend(format!("Error decoding snapshot #{}: {}", base, e)))?;
+		for (child, key, value) in chunk {
+			match child {
+				Some(storage_key) => { children.entry(storage_key).or_default().insert(key, value); },
+				None => { top.insert(key, value); },
+			}
+		}
+	}
+
+	fn apply(map: &mut BTreeMap<StorageKey, StorageValue>, changes: StorageCollection) {
+		for (key, value) in changes {
+			match value {
+				Some(value) => { map.insert(key, value); },
+				None => { map.remove(&key); },
+			}
+		}
+	}
+
+	for block in base + 1 ..= number {
+		let hash = canonical_hash(block)?
+			.ok_or_else(|| ClientError::Backend(format!("Missing canonical block #{}", block)))?;
+		let raw = db.get(columns::STATE_META, &diff_key(&hash))
+			.ok_or_else(|| ClientError::Backend(format!("Missing state changes of block #{}", block)))?;
+		let (storage_updates, child_storage_updates): (StorageCollection, ChildStorageCollection) =
+			Decode::decode(&mut &raw[..])
+				.map_err(|e| ClientError::Backend(format!("Error decoding state changes of block #{}: {}", block, e)))?;
+		apply(&mut top, storage_updates);
+		for (storage_key, changes) in child_storage_updates {
+			apply(children.entry(storage_key).or_default(), changes);
+		}
+	}
+
+	let mut tries: HashMap<Option<ChildInfo>, BTreeMap<StorageKey, StorageValue>> = children
+		.into_iter()
+		.filter(|(_, data)| !data.is_empty())
+		.map(|(storage_key, data)| (Some(ChildInfo::new_default(&storage_key)), data))
+		.collect();
+	tries.insert(None, top);
+	let backend: InMemoryBackend<HashFor<Block>> = tries.into();
+	if *backend.root() != state_root {
+		return Err(ClientError::Backend(format!(
+			"State root mismatch reconstructing block #{} from snapshot #{}",
+			number,
+			base,
+		)))
+	}
+
+	let storage: Arc<dyn sp_state_machine::Storage<HashFor<Block>>> =
+		Arc::new(SnapshotStorage::<Block>(backend.into_storage()));
+	Ok(Some(DbState::<Block>::new(storage, state_root)))
+}
@@ -39,6 +39,7 @@ const SET_STATE_KEY: &[u8] = b"grandpa_completed_round";
 const CONCLUDED_ROUNDS: &[u8] = b"grandpa_concluded_rounds";
 const AUTHORITY_SET_KEY: &[u8] = b"grandpa_voters";
 const CONSENSUS_CHANGES_KEY: &[u8] = b"grandpa_consensus_changes";
+const AUTHORITY_SET_CHANGE_PREFIX: &[u8] = b"grandpa_set_change";
 
 const CURRENT_VERSION: u32 = 2;
 
@@ -398,6 +399,46 @@ pub(crate) fn update_authority_set<Block: BlockT, F, R>(
 	}
 }
 
+/// Record the finalized block at which the authority set with the given id was enacted.
+///
+/// Warp sync proofs are built from these records, they are only written when a change is
+/// enacted by finality.
+pub(crate) fn write_authority_set_change<H, N, F, R>(
+	set_id: SetId,
+	block: (H, N),
+	write_aux: F
+) -> R where
+	H: Encode,
+	N: Encode,
+	F: FnOnce(&[(&[u8], &[u8])]) -> R,
+{
+	let key = authority_set_change_key(set_id);
+	write_aux(&[(&key[..], block.encode().as_slice())])
+}
+
+/// Load the finalized block at which the authority set with the given id was enacted, if it
+/// was recorded.
+pub(crate) fn load_authority_set_change<B: AuxStore, H: Decode, N: Decode>(
+	backend: &B,
+	set_id: SetId,
+) -> ClientResult<Option<(H, N)>> {
+	load_decode(backend, &authority_set_change_key(set_id))
+}
+
+/// Load the id of the current authority set.
+pub(crate) fn load_current_set_id<B: AuxStore, H: Decode, N: Decode>(backend: &B)
+	-> ClientResult<Option<SetId>>
+{
+	load_decode::<_, AuthoritySet<H, N>>(backend, AUTHORITY_SET_KEY)
+		.map(|set| set.map(|set| set.set_id))
+}
+
+fn authority_set_change_key(set_id: SetId) -> Vec<u8> {
+	let mut key = AUTHORITY_SET_CHANGE_PREFIX.to_vec();
+	set_id.using_encoded(|id| key.extend(id));
+	key
+}
+
 /// Write voter set state.
 pub(crate) fn write_voter_set_state<Block: BlockT, B: AuxStore>(
 	backend: &B,
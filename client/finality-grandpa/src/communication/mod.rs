@@ -30,7 +30,7 @@
 //! under certain conditions that are used to un-stick the protocol.
 
 use futures::{prelude::*, channel::mpsc};
-use log::{debug, trace};
+use log::{debug, trace, warn};
 use parking_lot::Mutex;
 use prometheus_endpoint::Registry;
 use std::{pin::Pin, sync::Arc, task::{Context, Poll}};
@@ -41,7 +41,10 @@ use finality_grandpa::{voter, voter_set::VoterSet};
 use sc_network::{NetworkService, ReputationChange};
 use sc_network_gossip::{GossipEngine, Network as GossipNetwork};
 use parity_scale_codec::{Encode, Decode};
-use sp_runtime::traits::{Block as BlockT, Hash as HashT, Header as HeaderT, NumberFor};
+use sp_runtime::traits::{
+	Block as BlockT, Hash as HashT, Header as HeaderT, NumberFor, UniqueSaturatedInto,
+};
+use sc_slashing_protection::{SlashingProtection, VoteStage};
 use sc_telemetry::{telemetry, CONSENSUS_DEBUG, CONSENSUS_INFO};
 
 use crate::{
@@ -305,6 +308,7 @@ impl<B: BlockT, N: Network<B>> NetworkBridge<B, N> {
 		set_id: SetId,
 		voters: Arc<VoterSet<AuthorityId>>,
 		has_voted: HasVoted<B>,
+		slashing_protection: Option<SlashingProtection>,
 	) -> (
 		impl Stream<Item = SignedMessage<B>> + Unpin,
 		OutgoingMessages<B>,
@@ -384,6 +388,7 @@ impl<B: BlockT, N: Network<B>> NetworkBridge<B, N> {
 			network: self.gossip_engine.clone(),
 			sender: tx,
 			has_voted,
+			slashing_protection,
 		};
 
 		// Combine incoming votes from external GRANDPA nodes with outgoing
@@ -660,6 +665,7 @@ pub(crate) struct OutgoingMessages<Block: BlockT> {
 	sender: mpsc::Sender<SignedMessage<Block>>,
 	network: Arc<Mutex<GossipEngine<Block>>>,
 	has_voted: HasVoted<Block>,
+	slashing_protection: Option<SlashingProtection>,
 }
 
 impl<B: BlockT> Unpin for OutgoingMessages<B> {}
@@ -695,6 +701,34 @@ impl<Block: BlockT> Sink<Message<Block>> for OutgoingMessages<Block>
 		// when locals exist, sign messages on import
 		if let Some(ref keystore) = self.keystore {
 			let target_hash = *(msg.target().0);
+
+			// not voting is safer than casting a vote conflicting with one cast before.
+			let stage = match msg {
+				finality_grandpa::Message::Prevote(_) => Some(VoteStage::Prevote),
+				finality_grandpa::Message::Precommit(_) => Some(VoteStage::Precommit),
+				finality_grandpa::Message::PrimaryPropose(_) => None,
+			};
+			if let (Some(protection), Some(stage)) = (&self.slashing_protection, stage) {
+				let checked = protection.check_grandpa_vote(
+					keystore.local_id().as_ref(),
+					self.set_id,
+					self.round,
+					stage,
+					target_hash.as_ref(),
+					(*msg.target().1).unique_saturated_into(),
+				);
+				if let Err(e) = checked {
+					warn!(
+						target: "afg",
+						"Not voting for {:?} in round {} in set {}: {}",
+						target_hash,
+						self.round,
+						self.set_id,
+						e,
+					);
+					return Ok(());
+				}
+			}
 			let signed = sp_finality_grandpa::sign_message(
 				keystore.as_ref(),
 				msg,
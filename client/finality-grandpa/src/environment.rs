@@ -39,6 +39,7 @@ use sp_runtime::generic::BlockId;
 use sp_runtime::traits::{
 	Block as BlockT, Header as HeaderT, NumberFor, One, Zero,
 };
+use sc_slashing_protection::SlashingProtection;
 use sc_telemetry::{telemetry, CONSENSUS_DEBUG, CONSENSUS_INFO};
 
 use crate::{
@@ -423,6 +424,7 @@ pub(crate) struct Environment<Backend, Block: BlockT, C, N: NetworkT<Block>, SC,
 	pub(crate) voting_rule: VR,
 	pub(crate) metrics: Option<Metrics>,
 	pub(crate) justification_sender: Option<GrandpaJustificationSender<Block>>,
+	pub(crate) slashing_protection: SlashingProtection,
 	pub(crate) _phantom: PhantomData<Backend>,
 }
 
@@ -750,6 +752,7 @@ where
 			crate::communication::SetId(self.set_id),
 			self.voters.clone(),
 			has_voted,
+			Some(self.slashing_protection.clone()),
 		);
 
 		// schedule incoming messages from the network to be held until
@@ -1332,6 +1335,14 @@ where
 
 				return Err(e.into());
 			}
+
+			if let Some(new) = new_authorities.as_ref() {
+				crate::aux_schema::write_authority_set_change(
+					new.set_id,
+					(new.canon_hash, new.canon_number),
+					|insert| apply_aux(import_op, insert, &[]),
+				)?;
+			}
 		}
 
 		Ok(new_authorities.map(VoterCommand::ChangeAuthorities))
@@ -18,12 +18,15 @@
 
 use std::{sync::Arc, collections::HashMap};
 
-use log::debug;
-use parity_scale_codec::Encode;
+use log::{debug, info};
+use parity_scale_codec::{Decode, Encode};
 use parking_lot::RwLockWriteGuard;
 
+use fork_tree::ForkTree;
 use sp_blockchain::{BlockStatus, well_known_cache_keys};
-use sc_client_api::{backend::Backend, utils::is_descendent_of};
+use sc_client_api::{
+	backend::Backend, utils::is_descendent_of, CallExecutor, ExecutionStrategy, ExecutorProvider,
+};
 use sp_utils::mpsc::TracingUnboundedSender;
 use sp_api::{TransactionFor};
 
@@ -32,7 +35,7 @@ use sp_consensus::{
 	BlockCheckParams, BlockImportParams, BlockOrigin, ImportResult, JustificationImport,
 	SelectChain,
 };
-use sp_finality_grandpa::{ConsensusLog, ScheduledChange, SetId, GRANDPA_ENGINE_ID};
+use sp_finality_grandpa::{AuthorityList, ConsensusLog, ScheduledChange, SetId, GRANDPA_ENGINE_ID};
 use sp_runtime::Justification;
 use sp_runtime::generic::{BlockId, OpaqueDigestItemId};
 use sp_runtime::traits::{
@@ -432,6 +435,10 @@ impl<BE, Block: BlockT, Client, SC> BlockImport<Block>
 			Err(e) => return Err(ConsensusError::ClientImport(e.to_string())),
 		}
 
+		if block.state.is_some() {
+			return self.import_state(block, new_cache);
+		}
+
 		// on initial sync we will restrict logging under info to avoid spam.
 		let initial_sync = block.origin == BlockOrigin::NetworkInitialSync;
 
@@ -555,6 +562,76 @@ impl<BE, Block: BlockT, Client, SC> BlockImport<Block>
 	}
 }
 
+impl<BE, Block: BlockT, Client, SC> GrandpaBlockImport<BE, Block, Client, SC> where
+	NumberFor<Block>: finality_grandpa::BlockNumberOps,
+	BE: Backend<Block>,
+	Client: crate::ClientForGrandpa<Block, BE>,
+	for<'a> &'a Client:
+		BlockImport<Block, Error = ConsensusError, Transaction = TransactionFor<Client, Block>>,
+{
+	/// Import a block together with its state, as a new finalized base of the chain.
+	///
+	/// Its finality was proven by the warp sync proof it was obtained with, so the authority
+	/// set is reset to the one of the runtime at that block.
+	fn import_state(
+		&mut self,
+		block: BlockImportParams<Block, TransactionFor<Client, Block>>,
+		new_cache: HashMap<well_known_cache_keys::Id, Vec<u8>>,
+	) -> Result<ImportResult, ConsensusError> {
+		let hash = block.post_hash();
+		let number = *block.header.number();
+
+		// the justification is kept so that the client stores it with the block.
+		let imported_aux = match (&*self.inner).import_block(block, new_cache) {
+			Ok(ImportResult::Imported(aux)) => aux,
+			Ok(r) => return Ok(r),
+			Err(e) => return Err(ConsensusError::ClientImport(e.to_string())),
+		};
+
+		let at = BlockId::Hash(hash);
+		let authorities: AuthorityList = self.call_runtime_api(&at, "GrandpaApi_grandpa_authorities")?;
+		let set_id: SetId = self.call_runtime_api(&at, "GrandpaApi_current_set_id")?;
+
+		let mut authority_set = self.authority_set.inner().write();
+		*authority_set = AuthoritySet::new(authorities.clone(), set_id, ForkTree::new(), Vec::new())
+			.ok_or(ConsensusError::InvalidAuthoritiesSet)?;
+
+		let new_set = NewAuthoritySet {
+			canon_number: number,
+			canon_hash: hash,
+			set_id,
+			authorities,
+		};
+		crate::aux_schema::update_authority_set::<Block, _, _>(
+			&authority_set,
+			Some(&new_set),
+			|insert| self.inner.insert_aux(insert, &[]),
+		).map_err(|e| ConsensusError::ClientImport(e.to_string()))?;
+
+		info!(
+			target: "afg",
+			"👴 Imported state of #{} ({}), resetting GRANDPA to authority set {}",
+			number,
+			hash,
+			set_id,
+		);
+
+		let _ = self.send_voter_commands.unbounded_send(VoterCommand::ChangeAuthorities(new_set));
+
+		Ok(ImportResult::Imported(imported_aux))
+	}
+
+	fn call_runtime_api<R: Decode>(&self, at: &BlockId<Block>, method: &str) -> Result<R, ConsensusError> {
+		self.inner.executor()
+			.call(at, method, &[], ExecutionStrategy::NativeElseWasm, None)
+			.map_err(|e| ConsensusError::ClientImport(e.to_string()))
+			.and_then(|result| R::decode(&mut &result[..])
+				.map_err(|e| ConsensusError::ClientImport(
+					format!("failed to decode the result of {}: {}", method, e.what()),
+				)))
+	}
+}
+
 impl<Backend, Block: BlockT, Client, SC> GrandpaBlockImport<Backend, Block, Client, SC> {
 	pub(crate) fn new(
 		inner: Arc<Client>,
@@ -80,6 +80,7 @@ use sp_core::{
 };
 use sp_application_crypto::AppKey;
 use sp_utils::mpsc::{tracing_unbounded, TracingUnboundedReceiver};
+use sc_slashing_protection::SlashingProtection;
 use sc_telemetry::{telemetry, CONSENSUS_INFO, CONSENSUS_DEBUG};
 use parking_lot::RwLock;
 
@@ -123,6 +124,7 @@ mod notification;
 mod observer;
 mod until_imported;
 mod voting_rule;
+mod warp_proof;
 
 pub use authorities::SharedAuthoritySet;
 pub use finality_proof::{FinalityProofFragment, FinalityProofProvider, StorageAndProofProvider};
@@ -130,6 +132,7 @@ pub use notification::{GrandpaJustificationSender, GrandpaJustificationStream};
 pub use import::GrandpaBlockImport;
 pub use justification::GrandpaJustification;
 pub use light_import::{light_block_import, GrandpaLightBlockImport};
+pub use warp_proof::{WarpSyncFragment, WarpSyncProof, WarpSyncProofProvider};
 pub use voting_rule::{
 	BeforeBestBlockBy, ThreeQuartersOfTheUnfinalizedChain, VotingRule, VotingRulesBuilder
 };
@@ -862,6 +865,7 @@ where
 
 		let voters = persistent_data.authority_set.current_authorities();
 		let env = Arc::new(Environment {
+			slashing_protection: SlashingProtection::new(client.clone()),
 			client,
 			select_chain,
 			voting_rule,
@@ -1013,6 +1017,7 @@ where
 					voting_rule: self.env.voting_rule.clone(),
 					metrics: self.env.metrics.clone(),
 					justification_sender: self.env.justification_sender.clone(),
+					slashing_protection: self.env.slashing_protection.clone(),
 					_phantom: PhantomData,
 				});
 
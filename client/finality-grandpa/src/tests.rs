@@ -220,6 +220,10 @@ sp_api::mock_impl_runtime_apis! {
 			self.inner.genesis_authorities.clone()
 		}
 
+		fn current_set_id(&self) -> SetId {
+			0
+		}
+
 		fn submit_report_equivocation_unsigned_extrinsic(
 			_equivocation_proof: EquivocationProof<Hash, BlockNumber>,
 			_key_owner_proof: OpaqueKeyOwnershipProof,
@@ -1168,6 +1172,7 @@ fn voter_persists_its_votes() {
 			communication::SetId(0),
 			Arc::new(VoterSet::new(voters).unwrap()),
 			HasVoted::No,
+			None,
 		);
 
 		runtime.spawn(network);
@@ -1577,6 +1582,7 @@ where
 		voting_rule,
 		metrics: None,
 		justification_sender: None,
+		slashing_protection: SlashingProtection::new(link.client.clone()),
 		_phantom: PhantomData,
 	}
 }
@@ -0,0 +1,487 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2018-2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+//! GRANDPA warp sync proof generation and check.
+//!
+//! A warp sync proof lets a node that only knows the genesis authority set follow the changes
+//! of the set up to a recent finalized block, without downloading the blocks in between. It
+//! consists of one fragment per authority set change, each providing:
+//! 1) the header of the block enacting the change;
+//! 2) the justification of that block by the previous authority set;
+//! 3) proof of GRANDPA::authorities() at that block.
+//!
+//! The last fragment may instead provide the justification of the latest justified block
+//! without any change. The provider needs the state of the blocks enacting the changes, so
+//! proofs can only be served by nodes that have not pruned it. The blocks enacting the changes
+//! are found from the records written when the changes are finalized, so a node can only serve
+//! proofs for the changes it has finalized itself.
+//!
+//! Long proofs are split: when a proof is not finished, the next one must be requested from
+//! the block of its last fragment and checked against the authority set it enacts.
+
+use std::sync::Arc;
+use log::trace;
+
+use sp_blockchain::{Backend as BlockchainBackend, Error as ClientError, Result as ClientResult};
+use sc_client_api::{backend::{AuxStore, Backend}, StorageProof};
+use sc_network::config::{WarpSyncProvider, WarpSyncVerification};
+use parity_scale_codec::{Encode, Decode};
+use finality_grandpa::BlockNumberOps;
+use sp_runtime::{
+	Justification, generic::BlockId,
+	traits::{NumberFor, Block as BlockT, Header as HeaderT, HashFor, One, Saturating, Zero},
+};
+use sp_finality_grandpa::{AuthorityList, SetId, VersionedAuthorityList, GRANDPA_AUTHORITIES_KEY};
+
+use crate::aux_schema::{load_authority_set_change, load_current_set_id};
+use crate::finality_proof::{AuthoritySetForFinalityProver, StorageAndProofProvider};
+use crate::justification::GrandpaJustification;
+use crate::VoterSet;
+
+/// Maximum number of authority set changes proven in a single warp sync proof.
+const MAX_FRAGMENTS_IN_PROOF: usize = 8;
+
+/// Maximum number of blocks below the finalized block searched for the latest justified block.
+const MAX_BLOCKS_SEARCHED_FOR_JUSTIFICATION: u32 = 1024;
+
+/// Single fragment of the warp sync proof.
+#[derive(Debug, PartialEq, Encode, Decode)]
+pub struct WarpSyncFragment<Header: HeaderT> {
+	/// Header of the justified block.
+	pub header: Header,
+	/// Justification of the block, by the authority set preceding it.
+	pub justification: Justification,
+	/// Proof of the new authorities set, if the block enacts a change.
+	pub authorities_proof: Option<StorageProof>,
+}
+
+/// Warp sync proof, starting at the block requested by the caller.
+#[derive(Debug, PartialEq, Encode, Decode)]
+pub struct WarpSyncProof<Header: HeaderT> {
+	/// Fragments, in ascending block order.
+	pub fragments: Vec<WarpSyncFragment<Header>>,
+	/// True if the last fragment is for the latest justified block known to the prover.
+	pub is_finished: bool,
+}
+
+/// Warp sync proof provider for serving network requests.
+pub struct WarpSyncProofProvider<B, Block: BlockT> {
+	backend: Arc<B>,
+	authority_provider: Arc<dyn AuthoritySetForFinalityProver<Block>>,
+}
+
+impl<B, Block: BlockT> WarpSyncProofProvider<B, Block>
+	where B: Backend<Block> + Send + Sync + 'static
+{
+	/// Create new warp sync proof provider using:
+	///
+	/// - backend for accessing blockchain data;
+	/// - authority_provider for reading and proving the authorities set.
+	pub fn new<P>(
+		backend: Arc<B>,
+		authority_provider: P,
+	) -> Self
+		where P: AuthoritySetForFinalityProver<Block> + 'static,
+	{
+		WarpSyncProofProvider { backend, authority_provider: Arc::new(authority_provider) }
+	}
+
+	/// Create new warp sync proof provider for the service using:
+	///
+	/// - backend for accessing blockchain data;
+	/// - storage_and_proof_provider, which is generally a client.
+	pub fn new_for_service(
+		backend: Arc<B>,
+		storage_and_proof_provider: Arc<dyn StorageAndProofProvider<Block, B>>,
+	) -> Arc<Self> {
+		Arc::new(Self::new(backend, storage_and_proof_provider))
+	}
+}
+
+impl<B, Block> WarpSyncProvider<Block> for WarpSyncProofProvider<B, Block>
+	where
+		Block: BlockT,
+		NumberFor<Block>: BlockNumberOps,
+		B: Backend<Block> + Send + Sync + 'static,
+{
+	fn generate(&self, begin: Block::Hash) -> ClientResult<Vec<u8>> {
+		prove_warp_sync(&*self.backend.blockchain(), &*self.backend, &*self.authority_provider, begin)
+			.map(|proof| proof.encode())
+	}
+
+	fn initial_state(&self) -> ClientResult<Vec<u8>> {
+		let authorities = self.authority_provider.authorities(&BlockId::Number(Zero::zero()))?;
+		Ok((0 as SetId, authorities).encode())
+	}
+
+	fn verify(&self, proof: &[u8], state: &[u8]) -> ClientResult<WarpSyncVerification<Block>> {
+		let (set_id, authorities): (SetId, AuthorityList) = Decode::decode(&mut &state[..])
+			.map_err(|_| ClientError::Backend("Invalid warp sync verifier state".into()))?;
+		let proof = WarpSyncProof::<Block::Header>::decode(&mut &proof[..])
+			.map_err(|_| ClientError::BadJustification("failed to decode warp sync proof".into()))?;
+		let (set_id, authorities) = check_warp_sync_proof::<Block>(&proof, set_id, authorities)?;
+
+		let last = proof.fragments.into_iter().last()
+			.expect("check_warp_sync_proof fails on empty proofs; qed");
+		if proof.is_finished {
+			Ok(WarpSyncVerification::Complete {
+				header: last.header,
+				justification: last.justification,
+			})
+		} else {
+			Ok(WarpSyncVerification::Partial {
+				begin: last.header.hash(),
+				state: (set_id, authorities).encode(),
+			})
+		}
+	}
+}
+
+/// Prepare a warp sync proof of the latest justified block, starting at the finalized block
+/// `begin`.
+///
+/// At most `MAX_FRAGMENTS_IN_PROOF` authority set changes are proven, the blocks enacting them
+/// are looked up in the records kept in `aux`.
+pub(crate) fn prove_warp_sync<Block: BlockT, B: BlockchainBackend<Block>, A: AuxStore>(
+	blockchain: &B,
+	aux: &A,
+	authorities_provider: &dyn AuthoritySetForFinalityProver<Block>,
+	begin: Block::Hash,
+) -> ClientResult<WarpSyncProof<Block::Header>> {
+	let begin_number = blockchain.expect_block_number_from_id(&BlockId::Hash(begin))?;
+	let info = blockchain.info();
+	if begin_number > info.finalized_number {
+		return Err(ClientError::Backend(
+			format!("Cannot generate warp sync proof from non-finalized block: {}", begin),
+		));
+	}
+
+	let canonical_begin = blockchain.expect_block_hash_from_id(&BlockId::Number(begin_number))?;
+	if begin != canonical_begin {
+		return Err(ClientError::Backend(
+			format!("Cannot generate warp sync proof from non-canonical block: {}", begin),
+		));
+	}
+
+	let set_change = |set_id: SetId| {
+		load_authority_set_change::<_, Block::Hash, NumberFor<Block>>(aux, set_id)
+	};
+	let incomplete_history = |set_id: SetId| ClientError::Backend(
+		format!("Cannot generate warp sync proof: no record of the change to authority set {}", set_id),
+	);
+	let current_set_id = load_current_set_id::<_, Block::Hash, NumberFor<Block>>(aux)?.unwrap_or(0);
+
+	// Find the first set enacted after `begin`. Changes are recorded in order, missing
+	// records can only precede the ones that exist.
+	let (mut first, mut last) = (1, current_set_id + 1);
+	while first < last {
+		let middle = first + (last - first) / 2;
+		match set_change(middle)? {
+			Some((_, number)) if number > begin_number => last = middle,
+			_ => first = middle + 1,
+		}
+	}
+	if first > 1 && set_change(first - 1)?.is_none() {
+		return Err(incomplete_history(first - 1));
+	}
+
+	let mut fragments = Vec::new();
+	let mut set_id = first;
+	while set_id <= current_set_id && fragments.len() < MAX_FRAGMENTS_IN_PROOF {
+		let (_, number) = set_change(set_id)?.ok_or_else(|| incomplete_history(set_id))?;
+		let id = BlockId::Number(number);
+		let justification = blockchain.justification(id)?.ok_or_else(|| ClientError::Backend(
+			format!("Missing justification of block #{} enacting authority set {}", number, set_id),
+		))?;
+
+		fragments.push(WarpSyncFragment {
+			header: blockchain.expect_header(id)?,
+			justification,
+			authorities_proof: Some(authorities_provider.prove_authorities(&id)?),
+		});
+		set_id += 1;
+	}
+
+	let is_finished = set_id > current_set_id;
+	if is_finished {
+		let last_change = fragments.last().map(|fragment| *fragment.header.number()).unwrap_or(begin_number);
+		let lowest = std::cmp::max(
+			last_change,
+			info.finalized_number.saturating_sub(MAX_BLOCKS_SEARCHED_FOR_JUSTIFICATION.into()),
+		);
+		let mut number = info.finalized_number;
+		while number > lowest {
+			let id = BlockId::Number(number);
+			if let Some(justification) = blockchain.justification(id)? {
+				fragments.push(WarpSyncFragment {
+					header: blockchain.expect_header(id)?,
+					justification,
+					authorities_proof: None,
+				});
+				break;
+			}
+			number -= One::one();
+		}
+	}
+
+	trace!(
+		target: "afg",
+		"Built warp sync proof from {} of {} fragments (finished: {}).",
+		begin,
+		fragments.len(),
+		is_finished,
+	);
+
+	Ok(WarpSyncProof { fragments, is_finished })
+}
+
+/// Check a warp sync proof against the authorities set of the block it starts at.
+///
+/// Returns the authorities set after the last fragment.
+pub(crate) fn check_warp_sync_proof<Block: BlockT>(
+	proof: &WarpSyncProof<Block::Header>,
+	mut set_id: SetId,
+	mut authorities: AuthorityList,
+) -> ClientResult<(SetId, AuthorityList)>
+	where
+		NumberFor<Block>: BlockNumberOps,
+{
+	if proof.fragments.is_empty() {
+		return Err(ClientError::BadJustification("empty warp sync proof".into()));
+	}
+
+	let last_fragment_index = proof.fragments.len() - 1;
+	for (index, fragment) in proof.fragments.iter().enumerate() {
+		let voters = VoterSet::new(authorities.iter().cloned()).ok_or(
+			ClientError::Consensus(sp_consensus::Error::InvalidAuthoritiesSet),
+		)?;
+		GrandpaJustification::<Block>::decode_and_verify_finalizes(
+			&fragment.justification,
+			(fragment.header.hash(), *fragment.header.number()),
+			set_id,
+			&voters,
+		)?;
+
+		match &fragment.authorities_proof {
+			Some(authorities_proof) => {
+				authorities = sp_state_machine::read_proof_check::<HashFor<Block>, _>(
+					*fragment.header.state_root(),
+					authorities_proof.clone(),
+					std::iter::once(GRANDPA_AUTHORITIES_KEY),
+				)
+					.map_err(|e| ClientError::BadJustification(
+						format!("invalid authorities proof: {}", e),
+					))?
+					.remove(GRANDPA_AUTHORITIES_KEY)
+					.flatten()
+					.and_then(|encoded| VersionedAuthorityList::decode(&mut encoded.as_slice()).ok())
+					.map(|versioned| versioned.into())
+					.ok_or(ClientError::InvalidAuthoritiesSet)?;
+				set_id += 1;
+			}
+			// Only the last fragment may not change the authorities set.
+			None if index != last_fragment_index =>
+				return Err(ClientError::BadJustification("redundant warp sync proof".into())),
+			None => {},
+		}
+	}
+
+	Ok((set_id, authorities))
+}
+
+#[cfg(test)]
+mod tests {
+	use substrate_test_runtime_client::runtime::{Block, Header, H256};
+	use sc_client_api::NewBlockState;
+	use sc_client_api::in_mem::Blockchain as InMemoryBlockchain;
+	use crate::authorities::AuthoritySet;
+	use sp_core::crypto::Public;
+	use sp_finality_grandpa::AuthorityId;
+	use super::*;
+
+	fn header(number: u64) -> Header {
+		let parent_hash = match number {
+			0 => Default::default(),
+			_ => header(number - 1).hash(),
+		};
+		Header::new(number, H256::from_low_u64_be(0), H256::from_low_u64_be(0), parent_hash, Default::default())
+	}
+
+	fn test_blockchain(justified: &[u64], last: u64) -> InMemoryBlockchain<Block> {
+		let blockchain = InMemoryBlockchain::<Block>::new();
+		for number in 0..=last {
+			let justification = if justified.contains(&number) { Some(vec![number as u8]) } else { None };
+			blockchain.insert(header(number).hash(), header(number), justification, None, NewBlockState::Final)
+				.unwrap();
+		}
+		blockchain
+	}
+
+	fn authorities(id: u8) -> AuthorityList {
+		vec![(AuthorityId::from_slice(&[id; 32]), 1u64)]
+	}
+
+	fn record_set_changes(blockchain: &InMemoryBlockchain<Block>, changes: &[(SetId, u64)]) {
+		for (set_id, number) in changes {
+			crate::aux_schema::write_authority_set_change(
+				*set_id,
+				(header(*number).hash(), *number),
+				|insert| blockchain.insert_aux(insert, &[]),
+			).unwrap();
+		}
+
+		let current_set_id = changes.iter().map(|(set_id, _)| *set_id).max().unwrap_or(0);
+		let set = AuthoritySet::<H256, u64>::new(
+			authorities(current_set_id as u8),
+			current_set_id,
+			fork_tree::ForkTree::new(),
+			Vec::new(),
+		).unwrap();
+		crate::aux_schema::update_authority_set::<Block, _, _>(
+			&set,
+			None,
+			|insert| blockchain.insert_aux(insert, &[]),
+		).unwrap();
+	}
+
+	fn authorities_proof(block_id: &BlockId<Block>) -> ClientResult<StorageProof> {
+		match block_id {
+			BlockId::Number(n) => Ok(StorageProof::new(vec![vec![*n as u8]])),
+			_ => unreachable!("authorities are only proven by number: {:?}", block_id),
+		}
+	}
+
+	fn change_fragment(number: u64) -> WarpSyncFragment<Header> {
+		WarpSyncFragment {
+			header: header(number),
+			justification: vec![number as u8],
+			authorities_proof: Some(StorageProof::new(vec![vec![number as u8]])),
+		}
+	}
+
+	#[test]
+	fn warp_sync_proof_contains_authority_set_changes() {
+		// the set changes at #3 and #6, #8 is the latest justified block
+		let blockchain = test_blockchain(&[1, 3, 6, 8], 9);
+		record_set_changes(&blockchain, &[(1, 3), (2, 6)]);
+		let authorities_at = |block_id: BlockId<Block>| -> ClientResult<AuthorityList> {
+			unreachable!("authorities are only proven: {:?}", block_id)
+		};
+		let prove_authorities = |block_id: BlockId<Block>| authorities_proof(&block_id);
+
+		let proof = prove_warp_sync(
+			&blockchain,
+			&blockchain,
+			&(authorities_at, prove_authorities),
+			header(0).hash(),
+		).unwrap();
+
+		assert_eq!(proof, WarpSyncProof {
+			fragments: vec![
+				change_fragment(3),
+				change_fragment(6),
+				WarpSyncFragment {
+					header: header(8),
+					justification: vec![8],
+					authorities_proof: None,
+				},
+			],
+			is_finished: true,
+		});
+
+		// a proof from the last change only contains the latest justified block
+		let proof = prove_warp_sync(
+			&blockchain,
+			&blockchain,
+			&(authorities_at, prove_authorities),
+			header(6).hash(),
+		).unwrap();
+		assert_eq!(proof.fragments.iter().map(|f| f.header.number).collect::<Vec<_>>(), vec![8]);
+	}
+
+	#[test]
+	fn warp_sync_proof_is_split_after_max_fragments() {
+		// the set changes at every block from #1 to #10
+		let blockchain = test_blockchain(&(1..=10).collect::<Vec<_>>(), 12);
+		record_set_changes(&blockchain, &(1..=10).map(|n| (n, n)).collect::<Vec<_>>());
+		let authorities_at = |block_id: BlockId<Block>| -> ClientResult<AuthorityList> {
+			unreachable!("authorities are only proven: {:?}", block_id)
+		};
+		let prove_authorities = |block_id: BlockId<Block>| authorities_proof(&block_id);
+
+		let proof = prove_warp_sync(
+			&blockchain,
+			&blockchain,
+			&(authorities_at, prove_authorities),
+			header(0).hash(),
+		).unwrap();
+		assert_eq!(proof, WarpSyncProof {
+			fragments: (1..=8).map(change_fragment).collect(),
+			is_finished: false,
+		});
+
+		// the next proof starts at the last fragment
+		let proof = prove_warp_sync(
+			&blockchain,
+			&blockchain,
+			&(authorities_at, prove_authorities),
+			header(8).hash(),
+		).unwrap();
+		assert_eq!(proof, WarpSyncProof {
+			fragments: vec![change_fragment(9), change_fragment(10)],
+			is_finished: true,
+		});
+	}
+
+	#[test]
+	fn warp_sync_proof_fails_without_recorded_changes() {
+		// the change to set #1 was not recorded, e.g. the node was warp synced after it
+		let blockchain = test_blockchain(&[3, 6], 6);
+		record_set_changes(&blockchain, &[(2, 6)]);
+
+		assert!(prove_warp_sync(
+			&blockchain,
+			&blockchain,
+			&(
+				|_| unreachable!("should return before calling GetAuthorities"),
+				|_| unreachable!("should return before calling ProveAuthorities"),
+			),
+			header(0).hash(),
+		).is_err());
+	}
+
+	#[test]
+	fn warp_sync_proof_fails_for_non_finalized_block() {
+		let blockchain = test_blockchain(&[], 2);
+		blockchain.insert(header(3).hash(), header(3), None, None, NewBlockState::Best).unwrap();
+
+		assert!(prove_warp_sync(
+			&blockchain,
+			&blockchain,
+			&(
+				|_| unreachable!("should return before calling GetAuthorities"),
+				|_| unreachable!("should return before calling ProveAuthorities"),
+			),
+			header(3).hash(),
+		).is_err());
+	}
+
+	#[test]
+	fn empty_warp_sync_proof_is_rejected() {
+		let proof = WarpSyncProof::<Header> { fragments: Vec::new(), is_finished: true };
+		assert!(check_warp_sync_proof::<Block>(&proof, 0, authorities(0)).is_err());
+	}
+}
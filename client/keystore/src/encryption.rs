@@ -0,0 +1,212 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate. If not, see <http://www.gnu.org/licenses/>.
+
+//! Encryption of key files at rest and of exported keys.
+//!
+//! The encryption key is derived from a password with argon2id. Every secret is then sealed with
+//! ChaCha20-Poly1305 under a random nonce, authenticating the key type and public key it belongs
+//! to so that sealed secrets cannot be swapped between key files.
+
+use chacha20poly1305::{
+	ChaCha20Poly1305,
+	aead::{Aead, NewAead, Payload, generic_array::GenericArray},
+};
+use rand::{RngCore, rngs::OsRng};
+use serde::{Serialize, Deserialize};
+use sp_core::crypto::{ExposeSecret, KeyTypeId, SecretString, Zeroize};
+use crate::{Error, Result};
+
+/// Length of the salt of the key derivation.
+const SALT_LENGTH: usize = 16;
+/// Length of the nonce of a sealed secret.
+const NONCE_LENGTH: usize = 12;
+
+/// Memory cost of the key derivation, in KiB.
+#[cfg(not(test))]
+const MEM_COST: u32 = 64 * 1024;
+#[cfg(test)]
+const MEM_COST: u32 = 64;
+/// Number of passes of the key derivation.
+#[cfg(not(test))]
+const TIME_COST: u32 = 3;
+#[cfg(test)]
+const TIME_COST: u32 = 1;
+
+/// The plaintext sealed in the keystore metadata to check the password against.
+const PASSWORD_CHECK: &[u8] = b"substrate keystore";
+
+/// Parameters of the argon2id derivation of the encryption key from a password.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KdfParams {
+	#[serde(with = "hex_bytes")]
+	salt: Vec<u8>,
+	mem_cost: u32,
+	time_cost: u32,
+	lanes: u32,
+}
+
+impl KdfParams {
+	/// Parameters with a fresh random salt.
+	pub fn generate() -> Self {
+		let mut salt = vec![0; SALT_LENGTH];
+		OsRng.fill_bytes(&mut salt);
+		KdfParams { salt, mem_cost: MEM_COST, time_cost: TIME_COST, lanes: 1 }
+	}
+
+	/// Derive the encryption key from `password`.
+	pub fn derive_key(&self, password: &SecretString) -> Result<EncryptionKey> {
+		let config = argon2::Config {
+			variant: argon2::Variant::Argon2id,
+			version: argon2::Version::Version13,
+			mem_cost: self.mem_cost,
+			time_cost: self.time_cost,
+			lanes: self.lanes,
+			thread_mode: argon2::ThreadMode::Sequential,
+			secret: &[],
+			ad: &[],
+			hash_length: 32,
+		};
+		let mut hash = argon2::hash_raw(password.expose_secret().as_bytes(), &self.salt, &config)
+			.map_err(|_| Error::InvalidEncryptedData)?;
+		let mut key = [0u8; 32];
+		key.copy_from_slice(&hash);
+		hash.zeroize();
+		Ok(EncryptionKey(key))
+	}
+}
+
+/// A secret sealed with an [`EncryptionKey`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Sealed {
+	#[serde(with = "hex_bytes")]
+	nonce: Vec<u8>,
+	#[serde(with = "hex_bytes")]
+	ciphertext: Vec<u8>,
+}
+
+/// A key derived from a password, sealing and opening secrets.
+pub struct EncryptionKey([u8; 32]);
+
+impl EncryptionKey {
+	/// Seal `secret`, binding it to `key_type` and `public`.
+	pub fn seal(&self, key_type: KeyTypeId, public: &[u8], secret: &[u8]) -> Sealed {
+		let mut nonce = vec![0; NONCE_LENGTH];
+		OsRng.fill_bytes(&mut nonce);
+		let aad = associated_data(key_type, public);
+		let ciphertext = self.cipher()
+			.encrypt(GenericArray::from_slice(&nonce), Payload { msg: secret, aad: &aad })
+			.expect("ChaCha20-Poly1305 only fails on messages larger than 256 GiB; qed");
+		Sealed { nonce, ciphertext }
+	}
+
+	/// Open a secret sealed for `key_type` and `public`.
+	///
+	/// Fails with `InvalidPassword` if it was sealed with another key or for another key file.
+	pub fn open(&self, key_type: KeyTypeId, public: &[u8], sealed: &Sealed) -> Result<Vec<u8>> {
+		if sealed.nonce.len() != NONCE_LENGTH {
+			return Err(Error::InvalidEncryptedData);
+		}
+		let aad = associated_data(key_type, public);
+		self.cipher()
+			.decrypt(
+				GenericArray::from_slice(&sealed.nonce),
+				Payload { msg: &sealed.ciphertext, aad: &aad },
+			)
+			.map_err(|_| Error::InvalidPassword)
+	}
+
+	/// Seal the password check stored in the keystore metadata.
+	pub fn seal_check(&self) -> Sealed {
+		self.seal(KeyTypeId(*b"meta"), &[], PASSWORD_CHECK)
+	}
+
+	/// Whether `sealed` is the password check sealed with this key.
+	pub fn verify_check(&self, sealed: &Sealed) -> bool {
+		self.open(KeyTypeId(*b"meta"), &[], sealed).map_or(false, |check| check == PASSWORD_CHECK)
+	}
+
+	fn cipher(&self) -> ChaCha20Poly1305 {
+		ChaCha20Poly1305::new(GenericArray::from_slice(&self.0))
+	}
+}
+
+impl Drop for EncryptionKey {
+	fn drop(&mut self) {
+		self.0.zeroize();
+	}
+}
+
+fn associated_data(key_type: KeyTypeId, public: &[u8]) -> Vec<u8> {
+	let mut aad = key_type.0.to_vec();
+	aad.extend_from_slice(public);
+	aad
+}
+
+/// Serialization of bytes as a hex string.
+pub(crate) mod hex_bytes {
+	use serde::{Deserialize, Deserializer, Serializer, de::Error};
+
+	pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.serialize_str(&hex::encode(bytes))
+	}
+
+	pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+		let string = String::deserialize(deserializer)?;
+		hex::decode(&string).map_err(D::Error::custom)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::str::FromStr;
+
+	const KEY_TYPE: KeyTypeId = KeyTypeId(*b"test");
+
+	fn key(params: &KdfParams, password: &str) -> EncryptionKey {
+		params.derive_key(&SecretString::from_str(password).unwrap()).unwrap()
+	}
+
+	#[test]
+	fn sealed_secret_opens_with_same_password_only() {
+		let params = KdfParams::generate();
+		let sealed = key(&params, "password").seal(KEY_TYPE, b"public", b"secret");
+
+		assert_eq!(key(&params, "password").open(KEY_TYPE, b"public", &sealed).unwrap(), b"secret");
+		assert!(key(&params, "other").open(KEY_TYPE, b"public", &sealed).is_err());
+		assert!(key(&KdfParams::generate(), "password").open(KEY_TYPE, b"public", &sealed).is_err());
+	}
+
+	#[test]
+	fn sealed_secret_is_bound_to_its_key_file() {
+		let params = KdfParams::generate();
+		let key = key(&params, "password");
+		let sealed = key.seal(KEY_TYPE, b"public", b"secret");
+
+		assert!(key.open(KeyTypeId(*b"babe"), b"public", &sealed).is_err());
+		assert!(key.open(KEY_TYPE, b"other", &sealed).is_err());
+	}
+
+	#[test]
+	fn password_check_verifies() {
+		let params = KdfParams::generate();
+		let check = key(&params, "password").seal_check();
+
+		assert!(key(&params, "password").verify_check(&check));
+		assert!(!key(&params, "other").verify_check(&check));
+	}
+}
@@ -17,16 +17,31 @@
 //! Keystore (and session key management) for ed25519 based chains like Polkadot.
 
 #![warn(missing_docs)]
-use std::{collections::{HashMap, HashSet}, path::PathBuf, fs::{self, File}, io::{self, Write}, sync::Arc};
+use std::{
+	collections::{HashMap, HashSet}, path::{Path, PathBuf}, fs::{self, File}, io::{self, Write},
+	sync::Arc,
+};
 use sp_core::{
-	crypto::{IsWrappedBy, CryptoTypePublicPair, KeyTypeId, Pair as PairT, ExposeSecret, SecretString, Public},
-	traits::{BareCryptoStore, Error as TraitError},
+	crypto::{
+		IsWrappedBy, CryptoTypePublicPair, KeyTypeId, Pair as PairT, ExposeSecret, SecretString,
+		Public, Zeroize,
+	},
+	traits::{BareCryptoStore, Error as TraitError, SigningContext},
 	sr25519::{Public as Sr25519Public, Pair as Sr25519Pair},
 	vrf::{VRFTranscriptData, VRFSignature, make_transcript},
 	Encode,
 };
 use sp_application_crypto::{AppKey, AppPublic, AppPair, ed25519, sr25519, ecdsa};
 use parking_lot::RwLock;
+use serde::{Serialize, Deserialize};
+
+mod encryption;
+pub mod remote;
+pub mod signer;
+
+use encryption::{EncryptionKey, KdfParams, Sealed};
+pub use remote::{RemoteSigner, SignerAddress};
+pub use signer::Signer;
 
 /// Keystore pointer
 pub type KeyStorePtr = Arc<RwLock<Store>>;
@@ -56,6 +71,16 @@ pub enum Error {
 	/// Keystore unavailable
 	#[display(fmt="Keystore unavailable")]
 	Unavailable,
+	/// The keystore is encrypted, but no password was given.
+	#[display(fmt="Keystore is encrypted, but no password was given")]
+	PasswordRequired,
+	/// Encrypted key data is malformed.
+	#[display(fmt="Invalid encrypted key data")]
+	InvalidEncryptedData,
+	/// The remote signer failed.
+	#[display(fmt="Remote signer error: {}", _0)]
+	#[from(ignore)]
+	Remote(String),
 }
 
 /// Keystore Result
@@ -66,12 +91,14 @@ impl From<Error> for TraitError {
 		match error {
 			Error::KeyNotSupported(id) => TraitError::KeyNotSupported(id),
 			Error::PairNotFound(e) => TraitError::PairNotFound(e),
-			Error::InvalidSeed | Error::InvalidPhrase | Error::InvalidPassword => {
+			Error::InvalidSeed | Error::InvalidPhrase | Error::InvalidPassword |
+			Error::PasswordRequired | Error::InvalidEncryptedData => {
 				TraitError::ValidationError(error.to_string())
 			},
 			Error::Unavailable => TraitError::Unavailable,
 			Error::Io(e) => TraitError::Other(e.to_string()),
 			Error::Json(e) => TraitError::Other(e.to_string()),
+			Error::Remote(e) => TraitError::Other(e),
 		}
 	}
 }
@@ -86,16 +113,80 @@ impl std::error::Error for Error {
 	}
 }
 
+/// Name of the file holding the encryption metadata of an encrypted keystore.
+const METADATA_FILE: &str = "keystore.json";
+
+/// Version of the encrypted key file and key export formats.
+const FORMAT_VERSION: u32 = 1;
+
+/// Metadata of an encrypted keystore.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Metadata {
+	version: u32,
+	kdf: KdfParams,
+	password_check: Sealed,
+}
+
+/// The content of a key file.
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum KeyFile {
+	/// The secret URI in plain text, as written by keystores without a password.
+	Plain(String),
+	/// The secret URI encrypted with the keystore password.
+	Encrypted {
+		version: u32,
+		secret: Sealed,
+	},
+}
+
+/// How the key files of a store are encrypted.
+enum Encryption {
+	/// Key files are stored in plain text.
+	None,
+	/// Key files are encrypted, but no password was given to decrypt them.
+	Locked,
+	/// Key files are encrypted with the given key.
+	Unlocked(EncryptionKey),
+}
+
+/// Keys exported from a keystore, encrypted with a password of their own.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeystoreExport {
+	version: u32,
+	kdf: KdfParams,
+	keys: Vec<ExportedKey>,
+}
+
+/// A single exported key.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ExportedKey {
+	#[serde(with = "encryption::hex_bytes")]
+	key_type: Vec<u8>,
+	#[serde(with = "encryption::hex_bytes")]
+	public: Vec<u8>,
+	secret: Sealed,
+}
+
 /// Key store.
 ///
 /// Stores key pairs in a file system store + short lived key pairs in memory.
 ///
 /// Every pair that is being generated by a `seed`, will be placed in memory.
+///
+/// When opened with a password, the key files are encrypted with a key derived from it and key
+/// files still in plain text are encrypted on opening.
 pub struct Store {
 	path: Option<PathBuf>,
 	/// Map over `(KeyTypeId, Raw public key)` -> `Key phrase/seed`
 	additional: HashMap<(KeyTypeId, Vec<u8>), String>,
 	password: Option<SecretString>,
+	encryption: Encryption,
+	/// The signer keys are forwarded to instead, if any.
+	remote: Option<RemoteSigner>,
 }
 
 impl Store {
@@ -106,7 +197,15 @@ impl Store {
 		let path = path.into();
 		fs::create_dir_all(&path)?;
 
-		let instance = Self { path: Some(path), additional: HashMap::new(), password };
+		let encryption = Self::open_encryption(&path, password.as_ref())?;
+		let instance = Self {
+			path: Some(path),
+			additional: HashMap::new(),
+			password,
+			encryption,
+			remote: None,
+		};
+		instance.encrypt_plain_key_files()?;
 		Ok(Arc::new(RwLock::new(instance)))
 	}
 
@@ -115,10 +214,80 @@ impl Store {
 		Arc::new(RwLock::new(Self {
 			path: None,
 			additional: HashMap::new(),
-			password: None
+			password: None,
+			encryption: Encryption::None,
+			remote: None,
 		}))
 	}
 
+	/// Create a store forwarding key queries and signing to the remote signer at `address`.
+	///
+	/// The keys live in the signer, so none can be generated or inserted through this store.
+	pub fn open_remote(address: SignerAddress) -> Result<KeyStorePtr> {
+		Ok(Arc::new(RwLock::new(Self {
+			path: None,
+			additional: HashMap::new(),
+			password: None,
+			encryption: Encryption::None,
+			remote: Some(RemoteSigner::connect(address)?),
+		})))
+	}
+
+	/// Set up the encryption of the key files at `path`.
+	///
+	/// The metadata of an encrypted keystore is created the first time it is opened with a
+	/// password, and checked against the password every following time.
+	fn open_encryption(path: &Path, password: Option<&SecretString>) -> Result<Encryption> {
+		let metadata_path = path.join(METADATA_FILE);
+		let metadata = if metadata_path.exists() {
+			let metadata: Metadata = serde_json::from_reader(File::open(&metadata_path)?)?;
+			if metadata.version > FORMAT_VERSION {
+				return Err(Error::InvalidEncryptedData);
+			}
+			Some(metadata)
+		} else {
+			None
+		};
+
+		match (metadata, password) {
+			(None, None) => Ok(Encryption::None),
+			(Some(_), None) => Ok(Encryption::Locked),
+			(Some(metadata), Some(password)) => {
+				let key = metadata.kdf.derive_key(password)?;
+				if !key.verify_check(&metadata.password_check) {
+					return Err(Error::InvalidPassword);
+				}
+				Ok(Encryption::Unlocked(key))
+			},
+			(None, Some(password)) => {
+				let kdf = KdfParams::generate();
+				let key = kdf.derive_key(password)?;
+				let metadata = Metadata {
+					version: FORMAT_VERSION,
+					kdf,
+					password_check: key.seal_check(),
+				};
+				write_file(&metadata_path, &serde_json::to_vec(&metadata)?)?;
+				Ok(Encryption::Unlocked(key))
+			},
+		}
+	}
+
+	/// Encrypt the key files still in plain text, if the store is encrypted.
+	///
+	/// Files that cannot be read are left untouched.
+	fn encrypt_plain_key_files(&self) -> Result<()> {
+		if let Encryption::Unlocked(_) = self.encryption {
+			for (key_type, public) in self.stored_keys()? {
+				if let Ok(KeyFile::Plain(mut suri)) = self.read_key_file(&public, key_type) {
+					self.write_key_file(key_type, &public, &self.effective_suri(&suri))?;
+					suri.zeroize();
+				}
+			}
+		}
+		Ok(())
+	}
+
 	/// Get the key phrase for the given public key and key type from the in-memory store.
 	fn get_additional_pair(
 		&self,
@@ -141,12 +310,7 @@ impl Store {
 	///
 	/// Places it into the file system store.
 	fn insert_unknown(&self, key_type: KeyTypeId, suri: &str, public: &[u8]) -> Result<()> {
-		if let Some(path) = self.key_file_path(public, key_type) {
-			let mut file = File::create(path).map_err(Error::Io)?;
-			serde_json::to_writer(&file, &suri).map_err(Error::Json)?;
-			file.flush().map_err(Error::Io)?;
-		}
-		Ok(())
+		self.write_key_file(key_type, public, &self.effective_suri(suri))
 	}
 
 	/// Insert a new key.
@@ -174,11 +338,7 @@ impl Store {
 	/// Places it into the file system store.
 	pub fn generate_by_type<Pair: PairT>(&self, key_type: KeyTypeId) -> Result<Pair> {
 		let (pair, phrase, _) = Pair::generate_with_phrase(self.password());
-		if let Some(path) = self.key_file_path(pair.public().as_slice(), key_type) {
-			let mut file = File::create(path)?;
-			serde_json::to_writer(&file, &phrase)?;
-			file.flush()?;
-		}
+		self.write_key_file(key_type, pair.public().as_slice(), &self.effective_suri(&phrase))?;
 		Ok(pair)
 	}
 
@@ -209,27 +369,77 @@ impl Store {
 		self.insert_ephemeral_from_seed_by_type::<Pair::Generic>(seed, Pair::ID).map(Into::into)
 	}
 
-	/// Get the key phrase for a given public key and key type.
-	fn key_phrase_by_type(&self, public: &[u8], key_type: KeyTypeId) -> Result<String> {
+	/// Get the key phrase for a given public key and key type, along with the password to
+	/// derive the key with.
+	fn key_phrase_by_type(
+		&self,
+		public: &[u8],
+		key_type: KeyTypeId,
+	) -> Result<(String, Option<&str>)> {
 		if let Some(phrase) = self.get_additional_pair(public, key_type) {
-			return Ok(phrase.clone())
+			return Ok((phrase.clone(), self.password()))
+		}
+
+		match self.read_key_file(public, key_type)? {
+			KeyFile::Plain(phrase) => Ok((phrase, self.password())),
+			KeyFile::Encrypted { version, secret } => {
+				if version > FORMAT_VERSION {
+					return Err(Error::InvalidEncryptedData);
+				}
+				let key = match &self.encryption {
+					Encryption::Unlocked(key) => key,
+					_ => return Err(Error::PasswordRequired),
+				};
+				// The password of an encrypted key is part of its phrase already.
+				let phrase = String::from_utf8(key.open(key_type, public, &secret)?)
+					.map_err(|_| Error::InvalidEncryptedData)?;
+				Ok((phrase, None))
+			},
 		}
+	}
 
+	/// Read the key file of the given public key and key type.
+	fn read_key_file(&self, public: &[u8], key_type: KeyTypeId) -> Result<KeyFile> {
 		let path = self.key_file_path(public, key_type).ok_or_else(|| Error::Unavailable)?;
 		let file = File::open(path)?;
 
 		serde_json::from_reader(&file).map_err(Into::into)
 	}
 
+	/// Write the key file of the given public key and key type, encrypting `suri` if the store
+	/// is encrypted.
+	fn write_key_file(&self, key_type: KeyTypeId, public: &[u8], suri: &str) -> Result<()> {
+		if let Some(path) = self.key_file_path(public, key_type) {
+			let key_file = match &self.encryption {
+				Encryption::None => KeyFile::Plain(suri.into()),
+				Encryption::Locked => return Err(Error::PasswordRequired),
+				Encryption::Unlocked(key) => KeyFile::Encrypted {
+					version: FORMAT_VERSION,
+					secret: key.seal(key_type, public, suri.as_bytes()),
+				},
+			};
+			write_file(&path, &serde_json::to_vec(&key_file)?)?;
+		}
+		Ok(())
+	}
+
+	/// The secret URI a key is derived from, with the store password applied.
+	///
+	/// This is what key files are written with, so that an encrypted key no longer depends on
+	/// the password of the store it is opened with.
+	fn effective_suri(&self, suri: &str) -> String {
+		with_password(suri, self.password())
+	}
+
 	/// Get a key pair for the given public key and key type.
 	pub fn key_pair_by_type<Pair: PairT>(&self,
 		public: &Pair::Public,
 		key_type: KeyTypeId,
 	) -> Result<Pair> {
-		let phrase = self.key_phrase_by_type(public.as_slice(), key_type)?;
+		let (phrase, password) = self.key_phrase_by_type(public.as_slice(), key_type)?;
 		let pair = Pair::from_string(
 			&phrase,
-			self.password(),
+			password,
 		).map_err(|_| Error::InvalidPhrase)?;
 
 		if &pair.public() == public {
@@ -275,6 +485,19 @@ impl Store {
 			.filter_map(|k| if k.0 == id { Some(k.1.clone()) } else { None })
 			.collect();
 
+		public_keys.extend(
+			self.stored_keys()?
+				.into_iter()
+				.filter_map(|(key_type, public)| if key_type == id { Some(public) } else { None })
+		);
+
+		Ok(public_keys)
+	}
+
+	/// Returns the key type and raw public key of every key in the file system store.
+	fn stored_keys(&self) -> Result<Vec<(KeyTypeId, Vec<u8>)>> {
+		let mut keys = Vec::new();
+
 		if let Some(path) = &self.path {
 			for entry in fs::read_dir(&path)? {
 				let entry = entry?;
@@ -284,11 +507,9 @@ impl Store {
 				if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
 					match hex::decode(name) {
 						Ok(ref hex) if hex.len() > 4 => {
-							if &hex[0..4] != &id.0 {
-								continue;
-							}
-							let public = hex[4..].to_vec();
-							public_keys.push(public);
+							let mut key_type = [0u8; 4];
+							key_type.copy_from_slice(&hex[0..4]);
+							keys.push((KeyTypeId(key_type), hex[4..].to_vec()));
 						}
 						_ => continue,
 					}
@@ -296,8 +517,102 @@ impl Store {
 			}
 		}
 
-		Ok(public_keys)
+		Ok(keys)
+	}
+
+	/// Export the keys of the file system store, optionally only those of `key_type`, encrypted
+	/// with `password`.
+	///
+	/// Exported keys carry the password they are derived with, so they can be imported into a
+	/// store with another password.
+	pub fn export_keys(
+		&self,
+		key_type: Option<KeyTypeId>,
+		password: &SecretString,
+	) -> Result<KeystoreExport> {
+		let kdf = KdfParams::generate();
+		let export_key = kdf.derive_key(password)?;
+
+		let mut keys = Vec::new();
+		for (stored_type, public) in self.stored_keys()? {
+			if key_type.map_or(false, |key_type| key_type != stored_type) {
+				continue;
+			}
+			let (mut phrase, derivation_password) = self.key_phrase_by_type(&public, stored_type)?;
+			let mut suri = with_password(&phrase, derivation_password);
+			let secret = export_key.seal(stored_type, &public, suri.as_bytes());
+			phrase.zeroize();
+			suri.zeroize();
+			keys.push(ExportedKey { key_type: stored_type.0.to_vec(), public, secret });
+		}
+
+		Ok(KeystoreExport { version: FORMAT_VERSION, kdf, keys })
 	}
+
+	/// Import keys exported by [`Store::export_keys`], decrypting them with `password`.
+	///
+	/// Nothing is imported unless every key decrypts. Returns the key type and raw public key of
+	/// the imported keys.
+	pub fn import_keys(
+		&self,
+		export: &KeystoreExport,
+		password: &SecretString,
+	) -> Result<Vec<(KeyTypeId, Vec<u8>)>> {
+		if export.version > FORMAT_VERSION {
+			return Err(Error::InvalidEncryptedData);
+		}
+		let export_key = export.kdf.derive_key(password)?;
+
+		let mut secrets = Vec::with_capacity(export.keys.len());
+		for key in &export.keys {
+			if key.key_type.len() != 4 {
+				return Err(Error::InvalidEncryptedData);
+			}
+			let mut key_type = [0u8; 4];
+			key_type.copy_from_slice(&key.key_type);
+			let key_type = KeyTypeId(key_type);
+			let suri = String::from_utf8(export_key.open(key_type, &key.public, &key.secret)?)
+				.map_err(|_| Error::InvalidEncryptedData)?;
+			secrets.push((key_type, &key.public, suri));
+		}
+
+		let mut imported = Vec::with_capacity(secrets.len());
+		for (key_type, public, mut suri) in secrets {
+			// The exported secret URI carries its password already.
+			self.write_key_file(key_type, public, &suri)?;
+			suri.zeroize();
+			imported.push((key_type, public.clone()));
+		}
+		Ok(imported)
+	}
+}
+
+/// Apply `password` to the secret URI `suri`, replacing the password it may carry.
+fn with_password(suri: &str, password: Option<&str>) -> String {
+	match password {
+		Some(password) => {
+			let suri = suri.splitn(2, "///").next().unwrap_or(suri);
+			format!("{}///{}", suri, password)
+		},
+		None => suri.into(),
+	}
+}
+
+/// Write `content` to `path` through a temporary file, so that no truncated file is left
+/// behind. On unix, the file is only accessible to its owner.
+fn write_file(path: &Path, content: &[u8]) -> io::Result<()> {
+	let tmp_path = path.with_extension("tmp");
+	let mut options = fs::OpenOptions::new();
+	options.write(true).create(true).truncate(true);
+	#[cfg(unix)]
+	{
+		use std::os::unix::fs::OpenOptionsExt;
+		options.mode(0o600);
+	}
+	let mut file = options.open(&tmp_path)?;
+	file.write_all(content)?;
+	file.sync_all()?;
+	fs::rename(&tmp_path, path)
 }
 
 impl BareCryptoStore for Store {
@@ -305,6 +620,9 @@ impl BareCryptoStore for Store {
 		&self,
 		id: KeyTypeId
 	) -> std::result::Result<Vec<CryptoTypePublicPair>, TraitError> {
+		if let Some(remote) = &self.remote {
+			return remote.keys(id);
+		}
 		let raw_keys = self.raw_public_keys(id)?;
 		Ok(raw_keys.into_iter()
 			.fold(Vec::new(), |mut v, k| {
@@ -320,6 +638,9 @@ impl BareCryptoStore for Store {
 		id: KeyTypeId,
 		keys: Vec<CryptoTypePublicPair>
 	) -> std::result::Result<Vec<CryptoTypePublicPair>, TraitError> {
+		if let Some(remote) = &self.remote {
+			return remote.supported_keys(id, keys);
+		}
 		let all_keys = self.keys(id)?.into_iter().collect::<HashSet<_>>();
 		Ok(keys.into_iter()
 		   .filter(|key| all_keys.contains(key))
@@ -332,6 +653,9 @@ impl BareCryptoStore for Store {
 		key: &CryptoTypePublicPair,
 		msg: &[u8],
 	) -> std::result::Result<Vec<u8>, TraitError> {
+		if let Some(remote) = &self.remote {
+			return remote.sign_with(id, key, msg);
+		}
 		match key.0 {
 			ed25519::CRYPTO_ID => {
 				let pub_key = ed25519::Public::from_slice(key.1.as_slice());
@@ -358,7 +682,23 @@ impl BareCryptoStore for Store {
 		}
 	}
 
+	fn sign_with_context(
+		&self,
+		id: KeyTypeId,
+		key: &CryptoTypePublicPair,
+		msg: &[u8],
+		context: SigningContext,
+	) -> std::result::Result<Vec<u8>, TraitError> {
+		match &self.remote {
+			Some(remote) => remote.sign_with_context(id, key, msg, context),
+			None => self.sign_with(id, key, msg),
+		}
+	}
+
 	fn sr25519_public_keys(&self, key_type: KeyTypeId) -> Vec<sr25519::Public> {
+		if let Some(remote) = &self.remote {
+			return remote.sr25519_public_keys(key_type);
+		}
 		self.raw_public_keys(key_type)
 			.map(|v| {
 				v.into_iter()
@@ -373,6 +713,9 @@ impl BareCryptoStore for Store {
 		id: KeyTypeId,
 		seed: Option<&str>,
 	) -> std::result::Result<sr25519::Public, TraitError> {
+		if let Some(remote) = &mut self.remote {
+			return remote.sr25519_generate_new(id, seed);
+		}
 		let pair = match seed {
 			Some(seed) => self.insert_ephemeral_from_seed_by_type::<sr25519::Pair>(seed, id),
 			None => self.generate_by_type::<sr25519::Pair>(id),
@@ -382,6 +725,9 @@ impl BareCryptoStore for Store {
 	}
 
 	fn ed25519_public_keys(&self, key_type: KeyTypeId) -> Vec<ed25519::Public> {
+		if let Some(remote) = &self.remote {
+			return remote.ed25519_public_keys(key_type);
+		}
 		self.raw_public_keys(key_type)
 			.map(|v| {
 				v.into_iter()
@@ -396,6 +742,9 @@ impl BareCryptoStore for Store {
 		id: KeyTypeId,
 		seed: Option<&str>,
 	) -> std::result::Result<ed25519::Public, TraitError> {
+		if let Some(remote) = &mut self.remote {
+			return remote.ed25519_generate_new(id, seed);
+		}
 		let pair = match seed {
 			Some(seed) => self.insert_ephemeral_from_seed_by_type::<ed25519::Pair>(seed, id),
 			None => self.generate_by_type::<ed25519::Pair>(id),
@@ -405,6 +754,9 @@ impl BareCryptoStore for Store {
 	}
 
 	fn ecdsa_public_keys(&self, key_type: KeyTypeId) -> Vec<ecdsa::Public> {
+		if let Some(remote) = &self.remote {
+			return remote.ecdsa_public_keys(key_type);
+		}
 		self.raw_public_keys(key_type)
 			.map(|v| {
 				v.into_iter()
@@ -419,6 +771,9 @@ impl BareCryptoStore for Store {
 		id: KeyTypeId,
 		seed: Option<&str>,
 	) -> std::result::Result<ecdsa::Public, TraitError> {
+		if let Some(remote) = &mut self.remote {
+			return remote.ecdsa_generate_new(id, seed);
+		}
 		let pair = match seed {
 			Some(seed) => self.insert_ephemeral_from_seed_by_type::<ecdsa::Pair>(seed, id),
 			None => self.generate_by_type::<ecdsa::Pair>(id),
@@ -430,6 +785,9 @@ impl BareCryptoStore for Store {
 	fn insert_unknown(&mut self, key_type: KeyTypeId, suri: &str, public: &[u8])
 		-> std::result::Result<(), ()>
 	{
+		if let Some(remote) = &mut self.remote {
+			return remote.insert_unknown(key_type, suri, public);
+		}
 		Store::insert_unknown(self, key_type, suri, public).map_err(|_| ())
 	}
 
@@ -440,6 +798,9 @@ impl BareCryptoStore for Store {
 	}
 
 	fn has_keys(&self, public_keys: &[(Vec<u8>, KeyTypeId)]) -> bool {
+		if let Some(remote) = &self.remote {
+			return remote.has_keys(public_keys);
+		}
 		public_keys.iter().all(|(p, t)| self.key_phrase_by_type(&p, *t).is_ok())
 	}
 
@@ -449,6 +810,9 @@ impl BareCryptoStore for Store {
 		public: &Sr25519Public,
 		transcript_data: VRFTranscriptData,
 	) -> std::result::Result<VRFSignature, TraitError> {
+		if let Some(remote) = &self.remote {
+			return remote.sr25519_vrf_sign(key_type, public, transcript_data);
+		}
 		let transcript = make_transcript(transcript_data);
 		let pair = self.key_pair_by_type::<Sr25519Pair>(public, key_type)
 			.map_err(|e| TraitError::PairNotFound(e.to_string()))?;
@@ -589,4 +953,134 @@ mod tests {
 			store.read().sr25519_public_keys(SR25519).is_empty(),
 		);
 	}
+
+	fn password(password: &str) -> Option<SecretString> {
+		Some(FromStr::from_str(password).unwrap())
+	}
+
+	fn stored_files(temp_dir: &TempDir) -> Vec<Vec<u8>> {
+		fs::read_dir(temp_dir.path()).unwrap()
+			.map(|entry| fs::read(entry.unwrap().path()).unwrap())
+			.collect()
+	}
+
+	#[test]
+	fn encrypted_key_files_contain_no_secret_material() {
+		let temp_dir = TempDir::new().unwrap();
+		let store = Store::open(temp_dir.path(), password("hunter2")).unwrap();
+
+		let pair = store.write()
+			.insert_by_type::<sr25519::Pair>(SR25519, sp_core::crypto::DEV_PHRASE)
+			.unwrap();
+		let generated: ed25519::AppPair = store.write().generate().unwrap();
+
+		let secrets = [
+			sp_core::crypto::DEV_PHRASE.as_bytes().to_vec(),
+			b"bottom".to_vec(),
+			b"hunter2".to_vec(),
+			pair.to_raw_vec(),
+			hex::encode(pair.to_raw_vec()).into_bytes(),
+			generated.to_raw_vec(),
+			hex::encode(generated.to_raw_vec()).into_bytes(),
+		];
+		let files = stored_files(&temp_dir);
+		assert_eq!(files.len(), 3);
+		for file in files {
+			for secret in &secrets {
+				assert!(!file.windows(secret.len()).any(|w| w == &secret[..]));
+			}
+		}
+
+		let store = Store::open(temp_dir.path(), password("hunter2")).unwrap();
+		assert_eq!(
+			store.read().key_pair_by_type::<sr25519::Pair>(&pair.public(), SR25519).unwrap().public(),
+			pair.public(),
+		);
+		assert_eq!(
+			store.read().key_pair::<ed25519::AppPair>(&generated.public()).unwrap().public(),
+			generated.public(),
+		);
+	}
+
+	#[test]
+	fn plain_key_files_are_encrypted_on_opening_with_password() {
+		let temp_dir = TempDir::new().unwrap();
+		let store = Store::open(temp_dir.path(), None).unwrap();
+		store.write().insert_unknown(SR25519, "//Alice", &[1; 32]).unwrap();
+		let plain_file = stored_files(&temp_dir).pop().unwrap();
+		assert!(plain_file.windows(7).any(|w| w == b"//Alice"));
+		drop(store);
+
+		let store = Store::open(temp_dir.path(), password("password")).unwrap();
+		for file in stored_files(&temp_dir) {
+			assert!(!file.windows(7).any(|w| w == b"//Alice"));
+		}
+
+		// The key is still derived with the store password, as it was in plain text.
+		let pair = sr25519::Pair::from_string("//Alice", Some("password")).unwrap();
+		store.write().insert_unknown(SR25519, "//Alice", pair.public().as_ref()).unwrap();
+		assert_eq!(
+			store.read().key_pair_by_type::<sr25519::Pair>(&pair.public(), SR25519).unwrap().public(),
+			pair.public(),
+		);
+		assert_eq!(
+			store.read().key_phrase_by_type(&[1; 32], SR25519).unwrap(),
+			("//Alice///password".to_string(), None),
+		);
+	}
+
+	#[test]
+	fn encrypted_store_checks_password() {
+		let temp_dir = TempDir::new().unwrap();
+		let store = Store::open(temp_dir.path(), password("password")).unwrap();
+		let pair: sr25519::AppPair = store.write().generate().unwrap();
+		drop(store);
+
+		assert!(matches!(
+			Store::open(temp_dir.path(), password("wrong")),
+			Err(Error::InvalidPassword)
+		));
+
+		// Without a password, keys are listed but can neither be read nor added.
+		let store = Store::open(temp_dir.path(), None).unwrap();
+		assert_eq!(store.read().public_keys::<sr25519::AppPublic>().unwrap(), vec![pair.public()]);
+		assert!(matches!(
+			store.read().key_pair::<sr25519::AppPair>(&pair.public()),
+			Err(Error::PasswordRequired)
+		));
+		assert!(matches!(
+			store.write().generate::<sr25519::AppPair>(),
+			Err(Error::PasswordRequired)
+		));
+	}
+
+	#[test]
+	fn exported_keys_import_into_store_with_other_password() {
+		let temp_dir = TempDir::new().unwrap();
+		let store = Store::open(temp_dir.path(), password("password")).unwrap();
+		let pair: sr25519::AppPair = store.write().generate().unwrap();
+		let other: ed25519::AppPair = store.write().generate().unwrap();
+
+		let export_password = SecretString::from_str("export").unwrap();
+		let export = store.read().export_keys(Some(SR25519), &export_password).unwrap();
+		let export: KeystoreExport = serde_json::from_slice(
+			&serde_json::to_vec(&export).unwrap()
+		).unwrap();
+
+		let other_dir = TempDir::new().unwrap();
+		let other_store = Store::open(other_dir.path(), password("other")).unwrap();
+		assert!(matches!(
+			other_store.read().import_keys(&export, &SecretString::from_str("wrong").unwrap()),
+			Err(Error::InvalidPassword)
+		));
+		assert!(other_store.read().public_keys::<sr25519::AppPublic>().unwrap().is_empty());
+
+		let imported = other_store.read().import_keys(&export, &export_password).unwrap();
+		assert_eq!(imported, vec![(SR25519, pair.public().to_raw_vec())]);
+		assert_eq!(
+			other_store.read().key_pair::<sr25519::AppPair>(&pair.public()).unwrap().public(),
+			pair.public(),
+		);
+		assert!(other_store.read().key_pair::<ed25519::AppPair>(&other.public()).is_err());
+	}
 }
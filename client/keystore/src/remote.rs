@@ -0,0 +1,601 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate. If not, see <http://www.gnu.org/licenses/>.
+
+//! Keystore backend forwarding signing to a separate signer process.
+//!
+//! The node and the signer talk over a Unix socket or a local TCP connection. Every message is
+//! a SCALE encoded [`Request`] or [`Response`] prefixed with its length as a little endian
+//! `u32`. A connection starts with both ends exchanging [`PROTOCOL_VERSION`], and the signer
+//! closes connections of clients speaking another version.
+
+use std::{
+	fmt, io::{self, Read, Write}, net::{SocketAddr, TcpListener, TcpStream}, str::FromStr,
+	time::Duration,
+};
+#[cfg(unix)]
+use std::{os::unix::net::{UnixListener, UnixStream}, path::PathBuf};
+use codec::{Encode, Decode};
+use parking_lot::Mutex;
+use schnorrkel::vrf::{VRFOutput, VRFProof};
+use sp_core::{
+	crypto::{CryptoTypeId, CryptoTypePublicPair, KeyTypeId, Public},
+	traits::{BareCryptoStore, Error as TraitError, SigningContext},
+	vrf::{VRFSignature, VRFTranscriptData, VRFTranscriptValue},
+};
+use sp_application_crypto::{ed25519, sr25519, ecdsa};
+use crate::{Error, Result};
+
+/// Version of the protocol between the node and the signer.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Largest message accepted by either end.
+const MAX_MESSAGE_SIZE: usize = 1024 * 1024;
+
+/// How long to wait for the other end before giving up on a request.
+const IO_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A request of the node to the signer.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub enum Request {
+	/// Open the connection with the protocol version of the node.
+	Hello(u32),
+	/// List the keys of the given key type.
+	Keys(KeyTypeId),
+	/// Check that the signer has all of the given keys.
+	HasKeys(Vec<(Vec<u8>, KeyTypeId)>),
+	/// Sign a message.
+	SignWith {
+		/// Key type of the key to sign with.
+		key_type: KeyTypeId,
+		/// The key to sign with.
+		key: CryptoTypePublicPair,
+		/// The message to sign.
+		msg: Vec<u8>,
+		/// What the message is signed for.
+		context: Option<SigningContext>,
+	},
+	/// Produce a VRF signature of a transcript.
+	Sr25519VrfSign {
+		/// Key type of the key to sign with.
+		key_type: KeyTypeId,
+		/// The sr25519 public key to sign with.
+		public: Vec<u8>,
+		/// The transcript to sign.
+		transcript: Transcript,
+	},
+}
+
+/// A response of the signer to a [`Request`].
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub enum Response {
+	/// The protocol version of the signer.
+	Hello(u32),
+	/// The requested keys.
+	Keys(Vec<CryptoTypePublicPair>),
+	/// Whether the signer has all requested keys.
+	HasKeys(bool),
+	/// The SCALE encoded signature.
+	Signature(Vec<u8>),
+	/// A VRF signature.
+	VrfSignature {
+		/// The VRF output.
+		output: Vec<u8>,
+		/// The VRF proof.
+		proof: Vec<u8>,
+	},
+	/// The request failed.
+	Error(RemoteError),
+}
+
+/// Why the signer did not fulfill a request.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub enum RemoteError {
+	/// The signer speaks another protocol version.
+	UnsupportedVersion(u32),
+	/// The crypto of the key is not supported.
+	KeyNotSupported(KeyTypeId),
+	/// The signer has no such key.
+	PairNotFound(String),
+	/// The message conflicts with one signed before.
+	Refused(String),
+	/// Any other failure.
+	Other(String),
+}
+
+impl From<RemoteError> for TraitError {
+	fn from(error: RemoteError) -> Self {
+		match error {
+			RemoteError::UnsupportedVersion(version) => TraitError::Other(
+				format!("Remote signer speaks unsupported protocol version {}", version),
+			),
+			RemoteError::KeyNotSupported(id) => TraitError::KeyNotSupported(id),
+			RemoteError::PairNotFound(e) => TraitError::PairNotFound(e),
+			RemoteError::Refused(e) => TraitError::ValidationError(
+				format!("Remote signer refused to sign: {}", e),
+			),
+			RemoteError::Other(e) => TraitError::Other(e),
+		}
+	}
+}
+
+impl From<TraitError> for RemoteError {
+	fn from(error: TraitError) -> Self {
+		match error {
+			TraitError::KeyNotSupported(id) => RemoteError::KeyNotSupported(id),
+			TraitError::PairNotFound(e) => RemoteError::PairNotFound(e),
+			e => RemoteError::Other(e.to_string()),
+		}
+	}
+}
+
+/// An owned VRF transcript, as sent over the wire.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct Transcript {
+	/// The label of the transcript.
+	pub label: Vec<u8>,
+	/// The labelled items of the transcript.
+	pub items: Vec<(Vec<u8>, TranscriptValue)>,
+}
+
+/// A value of an item of a [`Transcript`].
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub enum TranscriptValue {
+	/// An array of bytes.
+	Bytes(Vec<u8>),
+	/// A `u64` integer.
+	U64(u64),
+}
+
+impl<'a> From<VRFTranscriptData<'a>> for Transcript {
+	fn from(data: VRFTranscriptData<'a>) -> Self {
+		Transcript {
+			label: data.label.to_vec(),
+			items: data.items.into_iter().map(|(label, value)| {
+				let value = match value {
+					VRFTranscriptValue::Bytes(bytes) => TranscriptValue::Bytes(bytes.to_vec()),
+					VRFTranscriptValue::U64(value) => TranscriptValue::U64(value),
+				};
+				(label.as_bytes().to_vec(), value)
+			}).collect(),
+		}
+	}
+}
+
+/// Address of a remote signer.
+///
+/// Parsed from `unix:<path>` for a Unix socket, or from a TCP socket address. The protocol is
+/// not authenticated, so TCP addresses must be loopback addresses.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignerAddress {
+	/// A local TCP address.
+	Tcp(SocketAddr),
+	/// The path of a Unix socket.
+	#[cfg(unix)]
+	Unix(PathBuf),
+}
+
+impl FromStr for SignerAddress {
+	type Err = String;
+
+	fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+		if let Some(path) = s.strip_prefix("unix:") {
+			#[cfg(unix)]
+			return Ok(SignerAddress::Unix(path.into()));
+			#[cfg(not(unix))]
+			return Err(format!("Unix sockets are not supported on this platform: {}", path));
+		}
+
+		let address = s.strip_prefix("tcp:").unwrap_or(s).parse::<SocketAddr>()
+			.map_err(|e| format!("Invalid signer address {}: {}", s, e))?;
+		if !address.ip().is_loopback() {
+			return Err(format!("Signer address {} is not a loopback address", address));
+		}
+		Ok(SignerAddress::Tcp(address))
+	}
+}
+
+impl fmt::Display for SignerAddress {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			SignerAddress::Tcp(address) => write!(f, "{}", address),
+			#[cfg(unix)]
+			SignerAddress::Unix(path) => write!(f, "unix:{}", path.display()),
+		}
+	}
+}
+
+/// A connection between the node and the signer.
+pub enum Connection {
+	/// A TCP connection.
+	Tcp(TcpStream),
+	/// A Unix socket connection.
+	#[cfg(unix)]
+	Unix(UnixStream),
+}
+
+impl Connection {
+	/// Connect to the signer at `address`.
+	pub fn connect(address: &SignerAddress) -> io::Result<Self> {
+		let connection = match address {
+			SignerAddress::Tcp(address) => {
+				let stream = TcpStream::connect_timeout(address, IO_TIMEOUT)?;
+				stream.set_nodelay(true)?;
+				Connection::Tcp(stream)
+			},
+			#[cfg(unix)]
+			SignerAddress::Unix(path) => Connection::Unix(UnixStream::connect(path)?),
+		};
+		connection.set_timeout(Some(IO_TIMEOUT))?;
+		Ok(connection)
+	}
+
+	/// Set the read and write timeout of the connection.
+	pub fn set_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+		match self {
+			Connection::Tcp(stream) => {
+				stream.set_read_timeout(timeout)?;
+				stream.set_write_timeout(timeout)
+			},
+			#[cfg(unix)]
+			Connection::Unix(stream) => {
+				stream.set_read_timeout(timeout)?;
+				stream.set_write_timeout(timeout)
+			},
+		}
+	}
+
+	/// Send a SCALE encoded message.
+	pub fn send<T: Encode>(&mut self, message: &T) -> io::Result<()> {
+		let message = message.encode();
+		if message.len() > MAX_MESSAGE_SIZE {
+			return Err(io::Error::new(io::ErrorKind::InvalidInput, "Message too large"));
+		}
+		let mut frame = (message.len() as u32).to_le_bytes().to_vec();
+		frame.extend(message);
+		self.write_all(&frame)?;
+		self.flush()
+	}
+
+	/// Receive a SCALE encoded message.
+	pub fn receive<T: Decode>(&mut self) -> io::Result<T> {
+		let mut length = [0u8; 4];
+		self.read_exact(&mut length)?;
+		let length = u32::from_le_bytes(length) as usize;
+		if length > MAX_MESSAGE_SIZE {
+			return Err(io::Error::new(io::ErrorKind::InvalidData, "Message too large"));
+		}
+		let mut message = vec![0; length];
+		self.read_exact(&mut message)?;
+		T::decode(&mut &message[..])
+			.map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.what()))
+	}
+}
+
+impl Read for Connection {
+	fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+		match self {
+			Connection::Tcp(stream) => stream.read(buf),
+			#[cfg(unix)]
+			Connection::Unix(stream) => stream.read(buf),
+		}
+	}
+}
+
+impl Write for Connection {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		match self {
+			Connection::Tcp(stream) => stream.write(buf),
+			#[cfg(unix)]
+			Connection::Unix(stream) => stream.write(buf),
+		}
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		match self {
+			Connection::Tcp(stream) => stream.flush(),
+			#[cfg(unix)]
+			Connection::Unix(stream) => stream.flush(),
+		}
+	}
+}
+
+/// A listener of the signer, accepting connections of nodes.
+pub enum Listener {
+	/// A TCP listener.
+	Tcp(TcpListener),
+	/// A Unix socket listener.
+	#[cfg(unix)]
+	Unix(UnixListener),
+}
+
+impl Listener {
+	/// Listen at `address`.
+	///
+	/// A Unix socket is only accessible to its owner.
+	pub fn bind(address: &SignerAddress) -> io::Result<Self> {
+		match address {
+			SignerAddress::Tcp(address) => TcpListener::bind(address).map(Listener::Tcp),
+			#[cfg(unix)]
+			SignerAddress::Unix(path) => {
+				use std::os::unix::fs::PermissionsExt;
+				let listener = UnixListener::bind(path)?;
+				std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+				Ok(Listener::Unix(listener))
+			},
+		}
+	}
+
+	/// The address the listener is bound to.
+	pub fn local_address(&self) -> io::Result<SignerAddress> {
+		match self {
+			Listener::Tcp(listener) => listener.local_addr().map(SignerAddress::Tcp),
+			#[cfg(unix)]
+			Listener::Unix(listener) => listener.local_addr()?
+				.as_pathname()
+				.map(|path| SignerAddress::Unix(path.into()))
+				.ok_or_else(|| io::Error::new(io::ErrorKind::Other, "Unnamed Unix socket")),
+		}
+	}
+
+	/// Wait for the next connection.
+	pub fn accept(&self) -> io::Result<Connection> {
+		match self {
+			Listener::Tcp(listener) => {
+				let (stream, _) = listener.accept()?;
+				stream.set_nodelay(true)?;
+				Ok(Connection::Tcp(stream))
+			},
+			#[cfg(unix)]
+			Listener::Unix(listener) => listener.accept().map(|(stream, _)| Connection::Unix(stream)),
+		}
+	}
+}
+
+/// Keystore backend forwarding key queries and signing to a remote signer.
+///
+/// Keys live in the signer only, so they can be neither generated nor inserted through this
+/// backend. A broken connection is reopened on the next request.
+pub struct RemoteSigner {
+	address: SignerAddress,
+	connection: Mutex<Option<Connection>>,
+}
+
+impl RemoteSigner {
+	/// Connect to the signer at `address`.
+	pub fn connect(address: SignerAddress) -> Result<Self> {
+		let connection = Self::open(&address)?;
+		Ok(RemoteSigner { address, connection: Mutex::new(Some(connection)) })
+	}
+
+	/// The address of the signer.
+	pub fn address(&self) -> &SignerAddress {
+		&self.address
+	}
+
+	/// Open a connection to the signer and agree on the protocol version.
+	fn open(address: &SignerAddress) -> Result<Connection> {
+		let mut connection = Connection::connect(address)?;
+		connection.send(&Request::Hello(PROTOCOL_VERSION))?;
+		match connection.receive()? {
+			Response::Hello(PROTOCOL_VERSION) => Ok(connection),
+			Response::Hello(version) | Response::Error(RemoteError::UnsupportedVersion(version)) =>
+				Err(Error::Remote(format!("unsupported protocol version {}", version))),
+			_ => Err(Error::Remote("unexpected handshake response".into())),
+		}
+	}
+
+	/// Send `request` to the signer, reconnecting once if the connection broke.
+	fn request(&self, request: Request) -> std::result::Result<Response, TraitError> {
+		let mut connection = self.connection.lock();
+		for _ in 0..2 {
+			if connection.is_none() {
+				*connection = Some(Self::open(&self.address).map_err(|e| {
+					log::warn!(target: "keystore", "Cannot connect to remote signer at {}: {}", self.address, e);
+					TraitError::Unavailable
+				})?);
+			}
+			let stream = connection.as_mut().expect("connection was opened above; qed");
+			match stream.send(&request).and_then(|_| stream.receive()) {
+				Ok(Response::Error(error)) => return Err(error.into()),
+				Ok(response) => return Ok(response),
+				Err(e) => {
+					log::debug!(target: "keystore", "Connection to remote signer at {} broke: {}", self.address, e);
+					*connection = None;
+				},
+			}
+		}
+		Err(TraitError::Unavailable)
+	}
+
+	/// The raw public keys of the given key type and crypto.
+	fn public_keys(&self, id: KeyTypeId, crypto: CryptoTypeId) -> Vec<Vec<u8>> {
+		self.keys(id)
+			.map(|keys| keys.into_iter().filter(|k| k.0 == crypto).map(|k| k.1).collect())
+			.unwrap_or_default()
+	}
+}
+
+/// The error of a request the remote signer does not answer as expected.
+fn unexpected(response: Response) -> TraitError {
+	TraitError::Other(format!("Unexpected response of remote signer: {:?}", response))
+}
+
+impl BareCryptoStore for RemoteSigner {
+	fn keys(&self, id: KeyTypeId) -> std::result::Result<Vec<CryptoTypePublicPair>, TraitError> {
+		match self.request(Request::Keys(id))? {
+			Response::Keys(keys) => Ok(keys),
+			response => Err(unexpected(response)),
+		}
+	}
+
+	fn supported_keys(
+		&self,
+		id: KeyTypeId,
+		keys: Vec<CryptoTypePublicPair>,
+	) -> std::result::Result<Vec<CryptoTypePublicPair>, TraitError> {
+		let all_keys = self.keys(id)?;
+		Ok(keys.into_iter().filter(|key| all_keys.contains(key)).collect())
+	}
+
+	fn sign_with(
+		&self,
+		id: KeyTypeId,
+		key: &CryptoTypePublicPair,
+		msg: &[u8],
+	) -> std::result::Result<Vec<u8>, TraitError> {
+		let request = Request::SignWith { key_type: id, key: key.clone(), msg: msg.to_vec(), context: None };
+		match self.request(request)? {
+			Response::Signature(signature) => Ok(signature),
+			response => Err(unexpected(response)),
+		}
+	}
+
+	fn sign_with_context(
+		&self,
+		id: KeyTypeId,
+		key: &CryptoTypePublicPair,
+		msg: &[u8],
+		context: SigningContext,
+	) -> std::result::Result<Vec<u8>, TraitError> {
+		let request = Request::SignWith {
+			key_type: id,
+			key: key.clone(),
+			msg: msg.to_vec(),
+			context: Some(context),
+		};
+		match self.request(request)? {
+			Response::Signature(signature) => Ok(signature),
+			response => Err(unexpected(response)),
+		}
+	}
+
+	fn sr25519_public_keys(&self, id: KeyTypeId) -> Vec<sr25519::Public> {
+		self.public_keys(id, sr25519::CRYPTO_ID).iter()
+			.map(|k| sr25519::Public::from_slice(k))
+			.collect()
+	}
+
+	fn sr25519_generate_new(
+		&mut self,
+		_id: KeyTypeId,
+		_seed: Option<&str>,
+	) -> std::result::Result<sr25519::Public, TraitError> {
+		Err(Error::Remote("keys are generated in the signer".into()).into())
+	}
+
+	fn ed25519_public_keys(&self, id: KeyTypeId) -> Vec<ed25519::Public> {
+		self.public_keys(id, ed25519::CRYPTO_ID).iter()
+			.map(|k| ed25519::Public::from_slice(k))
+			.collect()
+	}
+
+	fn ed25519_generate_new(
+		&mut self,
+		_id: KeyTypeId,
+		_seed: Option<&str>,
+	) -> std::result::Result<ed25519::Public, TraitError> {
+		Err(Error::Remote("keys are generated in the signer".into()).into())
+	}
+
+	fn ecdsa_public_keys(&self, id: KeyTypeId) -> Vec<ecdsa::Public> {
+		self.public_keys(id, ecdsa::CRYPTO_ID).iter()
+			.map(|k| ecdsa::Public::from_slice(k))
+			.collect()
+	}
+
+	fn ecdsa_generate_new(
+		&mut self,
+		_id: KeyTypeId,
+		_seed: Option<&str>,
+	) -> std::result::Result<ecdsa::Public, TraitError> {
+		Err(Error::Remote("keys are generated in the signer".into()).into())
+	}
+
+	fn insert_unknown(&mut self, _key_type: KeyTypeId, _suri: &str, _public: &[u8])
+		-> std::result::Result<(), ()>
+	{
+		Err(())
+	}
+
+	fn password(&self) -> Option<&str> {
+		None
+	}
+
+	fn has_keys(&self, public_keys: &[(Vec<u8>, KeyTypeId)]) -> bool {
+		match self.request(Request::HasKeys(public_keys.to_vec())) {
+			Ok(Response::HasKeys(has_keys)) => has_keys,
+			_ => false,
+		}
+	}
+
+	fn sr25519_vrf_sign(
+		&self,
+		key_type: KeyTypeId,
+		public: &sr25519::Public,
+		transcript_data: VRFTranscriptData,
+	) -> std::result::Result<VRFSignature, TraitError> {
+		let request = Request::Sr25519VrfSign {
+			key_type,
+			public: public.to_raw_vec(),
+			transcript: transcript_data.into(),
+		};
+		match self.request(request)? {
+			Response::VrfSignature { output, proof } => Ok(VRFSignature {
+				output: VRFOutput::from_bytes(&output)
+					.map_err(|e| TraitError::Other(e.to_string()))?,
+				proof: VRFProof::from_bytes(&proof)
+					.map_err(|e| TraitError::Other(e.to_string()))?,
+			}),
+			response => Err(unexpected(response)),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn signer_address_parses() {
+		assert_eq!(
+			"127.0.0.1:9955".parse::<SignerAddress>(),
+			Ok(SignerAddress::Tcp("127.0.0.1:9955".parse().unwrap())),
+		);
+		assert_eq!(
+			"tcp:[::1]:9955".parse::<SignerAddress>(),
+			Ok(SignerAddress::Tcp("[::1]:9955".parse().unwrap())),
+		);
+		#[cfg(unix)]
+		assert_eq!(
+			"unix:/run/signer.sock".parse::<SignerAddress>(),
+			Ok(SignerAddress::Unix("/run/signer.sock".into())),
+		);
+		assert!("0.0.0.0:9955".parse::<SignerAddress>().is_err());
+		assert!("10.0.0.1:9955".parse::<SignerAddress>().is_err());
+	}
+
+	#[test]
+	fn transcript_encodes_like_transcript_data() {
+		let data = VRFTranscriptData {
+			label: b"label",
+			items: vec![
+				("one", VRFTranscriptValue::U64(1)),
+				("two", VRFTranscriptValue::Bytes(b"two")),
+			],
+		};
+		assert_eq!(Transcript::from(data.clone()).encode(), data.encode());
+	}
+}
@@ -0,0 +1,403 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate. If not, see <http://www.gnu.org/licenses/>.
+
+//! Signer side of the remote signer protocol.
+//!
+//! The [`Signer`] answers the requests of nodes with the keys of a file keystore. Before signing
+//! a BABE block seal or a GRANDPA vote it checks the message against a [`SigningRecord`] of what
+//! it signed before, and refuses to sign messages that could be reported as an equivocation.
+
+use std::{
+	collections::{BTreeMap, HashSet}, fs::File, io, path::PathBuf, sync::Arc, thread,
+};
+use codec::Decode;
+use parking_lot::Mutex;
+use serde::{Serialize, Deserialize};
+use sp_core::{
+	crypto::{key_types, KeyTypeId, CryptoTypePublicPair, Public},
+	hashing::blake2_256,
+	traits::{BareCryptoStore, SigningContext},
+	vrf::{VRFTranscriptData, VRFTranscriptValue},
+};
+use sp_application_crypto::sr25519;
+use crate::{
+	KeyStorePtr, Result, write_file,
+	remote::{
+		Connection, Listener, PROTOCOL_VERSION, RemoteError, Request, Response, Transcript,
+		TranscriptValue,
+	},
+};
+
+/// Most distinct VRF transcript labels the signer accepts.
+///
+/// Transcript labels must live for the whole process, so every distinct label is leaked once.
+const MAX_TRANSCRIPT_LABELS: usize = 32;
+
+/// The last message signed at some position, such as a slot or a round.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LastSigned {
+	/// The position of the message, compared lexicographically.
+	position: (u64, u64),
+	/// Hash of the signed message.
+	#[serde(with = "crate::encryption::hex_bytes")]
+	payload_hash: Vec<u8>,
+}
+
+/// Record of the last BABE block seal and GRANDPA votes signed by each key.
+///
+/// Only moving forward is allowed: a key never signs at a position before its last one, and
+/// signs at the same position again only if the message is the same.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SigningRecord {
+	/// Last block seal by hex encoded BABE public key, at position `(0, slot)`.
+	babe: BTreeMap<String, LastSigned>,
+	/// Last vote by `<hex encoded GRANDPA public key>/<stage>`, at position `(set id, round)`.
+	grandpa: BTreeMap<String, LastSigned>,
+}
+
+impl SigningRecord {
+	/// Check that `payload` may be signed by `key` at `position`, and record it if so.
+	///
+	/// Returns whether the record changed.
+	fn check_and_record(
+		record: &mut BTreeMap<String, LastSigned>,
+		key: String,
+		position: (u64, u64),
+		payload: &[u8],
+	) -> std::result::Result<bool, String> {
+		let payload_hash = blake2_256(payload).to_vec();
+		match record.get(&key) {
+			Some(last) if position < last.position => Err(format!(
+				"{} already signed at {:?}, after {:?}", key, last.position, position,
+			)),
+			Some(last) if position == last.position && payload_hash != last.payload_hash =>
+				Err(format!("{} already signed another message at {:?}", key, position)),
+			Some(last) if position == last.position => Ok(false),
+			_ => {
+				record.insert(key, LastSigned { position, payload_hash });
+				Ok(true)
+			},
+		}
+	}
+
+	/// Check a BABE block seal for `slot`.
+	fn check_babe(&mut self, public: &[u8], slot: u64, payload: &[u8])
+		-> std::result::Result<bool, String>
+	{
+		Self::check_and_record(&mut self.babe, hex::encode(public), (0, slot), payload)
+	}
+
+	/// Check a GRANDPA vote, read from its localized payload.
+	///
+	/// The payload is the SCALE encoded vote followed by the round and set id. The vote starts
+	/// with its stage: prevote, precommit or primary proposal.
+	fn check_grandpa(&mut self, public: &[u8], payload: &[u8])
+		-> std::result::Result<bool, String>
+	{
+		if payload.len() < 17 {
+			return Err("Malformed GRANDPA vote".into());
+		}
+		let stage = match payload[0] {
+			0 => "prevote",
+			1 => "precommit",
+			2 => "primary-propose",
+			_ => return Err("Malformed GRANDPA vote".into()),
+		};
+		let mut tail = &payload[payload.len() - 16..];
+		let round = u64::decode(&mut tail).map_err(|e| e.what().to_string())?;
+		let set_id = u64::decode(&mut tail).map_err(|e| e.what().to_string())?;
+		let key = format!("{}/{}", hex::encode(public), stage);
+		Self::check_and_record(&mut self.grandpa, key, (set_id, round), payload)
+	}
+}
+
+/// A signer answering remote signer requests with the keys of a keystore.
+pub struct Signer {
+	store: KeyStorePtr,
+	record: Mutex<SigningRecord>,
+	record_path: PathBuf,
+	labels: Mutex<HashSet<&'static str>>,
+}
+
+impl Signer {
+	/// Create a signer for the keys of `store`, keeping its signing record at `record_path`.
+	pub fn new(store: KeyStorePtr, record_path: PathBuf) -> Result<Self> {
+		let record = if record_path.exists() {
+			serde_json::from_reader(File::open(&record_path)?)?
+		} else {
+			SigningRecord::default()
+		};
+
+		Ok(Signer {
+			store,
+			record: Mutex::new(record),
+			record_path,
+			labels: Mutex::new(HashSet::new()),
+		})
+	}
+
+	/// Answer the connections of `listener`, each on its own thread.
+	///
+	/// Only returns if accepting connections fails.
+	pub fn serve(self: Arc<Self>, listener: Listener) -> io::Result<()> {
+		loop {
+			let connection = listener.accept()?;
+			let signer = self.clone();
+			thread::Builder::new()
+				.name("remote-signer-connection".into())
+				.spawn(move || {
+					if let Err(e) = signer.serve_connection(connection) {
+						log::debug!(target: "keystore", "Remote signer connection closed: {}", e);
+					}
+				})?;
+		}
+	}
+
+	/// Answer the requests of a single connection until it closes.
+	fn serve_connection(&self, mut connection: Connection) -> io::Result<()> {
+		connection.set_timeout(None)?;
+		match connection.receive()? {
+			Request::Hello(PROTOCOL_VERSION) => connection.send(&Response::Hello(PROTOCOL_VERSION))?,
+			Request::Hello(version) => {
+				log::warn!(target: "keystore", "Node speaks unsupported protocol version {}", version);
+				return connection.send(&Response::Error(RemoteError::UnsupportedVersion(PROTOCOL_VERSION)));
+			},
+			_ => return Err(io::Error::new(io::ErrorKind::InvalidData, "Expected handshake")),
+		}
+
+		loop {
+			let request = connection.receive()?;
+			connection.send(&self.handle(request))?;
+		}
+	}
+
+	/// Answer a single request.
+	pub fn handle(&self, request: Request) -> Response {
+		match request {
+			Request::Hello(_) => Response::Hello(PROTOCOL_VERSION),
+			Request::Keys(id) => match self.store.read().keys(id) {
+				Ok(keys) => Response::Keys(keys),
+				Err(e) => Response::Error(e.into()),
+			},
+			Request::HasKeys(keys) => Response::HasKeys(self.store.read().has_keys(&keys)),
+			Request::SignWith { key_type, key, msg, context } =>
+				match self.sign_with(key_type, &key, &msg, context) {
+					Ok(signature) => Response::Signature(signature),
+					Err(e) => Response::Error(e),
+				},
+			Request::Sr25519VrfSign { key_type, public, transcript } =>
+				match self.sr25519_vrf_sign(key_type, &public, transcript) {
+					Ok(response) => response,
+					Err(e) => Response::Error(e),
+				},
+		}
+	}
+
+	/// Sign `msg`, checking block seals and votes against the signing record first.
+	fn sign_with(
+		&self,
+		key_type: KeyTypeId,
+		key: &CryptoTypePublicPair,
+		msg: &[u8],
+		context: Option<SigningContext>,
+	) -> std::result::Result<Vec<u8>, RemoteError> {
+		// Keep the record locked until signed, so that no conflicting message is checked in
+		// between.
+		let mut record = self.record.lock();
+		let mut updated = record.clone();
+		let changed = match (key_type, context) {
+			(key_types::BABE, Some(SigningContext::BabeSlot(slot))) =>
+				updated.check_babe(&key.1, slot, msg),
+			(key_types::BABE, None) =>
+				Err("BABE keys only sign block seals of a given slot".into()),
+			(key_types::GRANDPA, _) => updated.check_grandpa(&key.1, msg),
+			_ => Ok(false),
+		}.map_err(|e| {
+			log::warn!(target: "keystore", "Refused to sign: {}", e);
+			RemoteError::Refused(e)
+		})?;
+
+		// Not signing is safer than signing without a record of it.
+		if changed {
+			serde_json::to_vec(&updated)
+				.map_err(|e| e.to_string())
+				.and_then(|content| write_file(&self.record_path, &content).map_err(|e| e.to_string()))
+				.map_err(|e| RemoteError::Other(format!("Cannot write signing record: {}", e)))?;
+			*record = updated;
+		}
+
+		self.store.read().sign_with(key_type, key, msg).map_err(Into::into)
+	}
+
+	/// Produce a VRF signature of `transcript`.
+	fn sr25519_vrf_sign(
+		&self,
+		key_type: KeyTypeId,
+		public: &[u8],
+		transcript: Transcript,
+	) -> std::result::Result<Response, RemoteError> {
+		let label = self.intern(transcript.label)?;
+		let items = transcript.items.into_iter()
+			.map(|(label, value)| Ok((self.intern(label)?, value)))
+			.collect::<std::result::Result<Vec<_>, RemoteError>>()?;
+		let transcript_data = VRFTranscriptData {
+			label: label.as_bytes(),
+			items: items.iter().map(|(label, value)| {
+				let value = match value {
+					TranscriptValue::Bytes(bytes) => VRFTranscriptValue::Bytes(bytes),
+					TranscriptValue::U64(value) => VRFTranscriptValue::U64(*value),
+				};
+				(*label, value)
+			}).collect(),
+		};
+
+		let signature = self.store.read().sr25519_vrf_sign(
+			key_type,
+			&sr25519::Public::from_slice(public),
+			transcript_data,
+		)?;
+		Ok(Response::VrfSignature {
+			output: signature.output.to_bytes().to_vec(),
+			proof: signature.proof.to_bytes().to_vec(),
+		})
+	}
+
+	/// A `'static` copy of a transcript label.
+	fn intern(&self, label: Vec<u8>) -> std::result::Result<&'static str, RemoteError> {
+		let label = String::from_utf8(label)
+			.map_err(|_| RemoteError::Other("Transcript labels must be UTF-8".into()))?;
+		let mut labels = self.labels.lock();
+		if let Some(label) = labels.get(label.as_str()) {
+			return Ok(label);
+		}
+		if labels.len() >= MAX_TRANSCRIPT_LABELS {
+			return Err(RemoteError::Other("Too many distinct transcript labels".into()));
+		}
+		let label: &'static str = Box::leak(label.into_boxed_str());
+		labels.insert(label);
+		Ok(label)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_core::{Encode, Pair as _, testing::SR25519, vrf::make_transcript};
+	use tempfile::TempDir;
+	use crate::{RemoteSigner, SignerAddress, Store};
+
+	fn start_signer(dir: &TempDir) -> (KeyStorePtr, SignerAddress) {
+		let store = Store::open(dir.path().join("keystore"), None).unwrap();
+		let signer = Arc::new(Signer::new(store.clone(), dir.path().join("record.json")).unwrap());
+		let listener = Listener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+		let address = listener.local_address().unwrap();
+		thread::spawn(move || signer.serve(listener));
+		(store, address)
+	}
+
+	fn grandpa_vote(stage: u8, target: u8, round: u64, set_id: u64) -> Vec<u8> {
+		let mut payload = vec![stage];
+		payload.extend_from_slice(&[target; 32]);
+		payload.extend_from_slice(&7u32.encode());
+		payload.extend(round.encode());
+		payload.extend(set_id.encode());
+		payload
+	}
+
+	#[test]
+	fn remote_signer_signs_with_signer_keys() {
+		let dir = TempDir::new().unwrap();
+		let (store, address) = start_signer(&dir);
+		let pair = store.read().generate_by_type::<sr25519::Pair>(SR25519).unwrap();
+		let key = CryptoTypePublicPair(sr25519::CRYPTO_ID, pair.public().to_raw_vec());
+
+		let remote = RemoteSigner::connect(address).unwrap();
+		assert_eq!(remote.sr25519_public_keys(SR25519), vec![pair.public()]);
+		assert!(remote.has_keys(&[(pair.public().to_raw_vec(), SR25519)]));
+		assert!(!remote.has_keys(&[(vec![0; 32], SR25519)]));
+
+		let signature = remote.sign_with(SR25519, &key, b"message").unwrap();
+		let signature = sr25519::Signature::decode(&mut &signature[..]).unwrap();
+		assert!(sr25519::Pair::verify(&signature, b"message", &pair.public()));
+
+		let transcript_data = || VRFTranscriptData {
+			label: b"label",
+			items: vec![("slot", VRFTranscriptValue::U64(42))],
+		};
+		let remote_vrf = remote.sr25519_vrf_sign(SR25519, &pair.public(), transcript_data()).unwrap();
+		let local_vrf = store.read().sr25519_vrf_sign(SR25519, &pair.public(), transcript_data()).unwrap();
+		assert_eq!(remote_vrf.output, local_vrf.output);
+		assert!(pair.as_ref().public.vrf_verify(
+			make_transcript(transcript_data()),
+			&remote_vrf.output,
+			&remote_vrf.proof,
+		).is_ok());
+	}
+
+	#[test]
+	fn signer_refuses_conflicting_block_seals() {
+		let dir = TempDir::new().unwrap();
+		let (store, address) = start_signer(&dir);
+		let pair = store.read().generate_by_type::<sr25519::Pair>(key_types::BABE).unwrap();
+		let key = CryptoTypePublicPair(sr25519::CRYPTO_ID, pair.public().to_raw_vec());
+		let remote = Store::open_remote(address).unwrap();
+		let remote = remote.read();
+		let seal = |msg: &[u8], slot| remote.sign_with_context(
+			key_types::BABE,
+			&key,
+			msg,
+			SigningContext::BabeSlot(slot),
+		);
+
+		assert!(seal(b"block", 10).is_ok());
+		assert!(seal(b"block", 10).is_ok());
+		assert!(seal(b"other block", 10).is_err());
+		assert!(seal(b"old block", 9).is_err());
+		assert!(remote.sign_with(key_types::BABE, &key, b"block").is_err());
+		assert!(seal(b"next block", 11).is_ok());
+	}
+
+	#[test]
+	fn signer_refuses_conflicting_votes_after_restart() {
+		let dir = TempDir::new().unwrap();
+		let (store, address) = start_signer(&dir);
+		let pair = store.read().generate_by_type::<sr25519::Pair>(key_types::GRANDPA).unwrap();
+		let key = CryptoTypePublicPair(sr25519::CRYPTO_ID, pair.public().to_raw_vec());
+		let remote = RemoteSigner::connect(address).unwrap();
+
+		assert!(remote.sign_with(key_types::GRANDPA, &key, &grandpa_vote(0, 1, 5, 1)).is_ok());
+		assert!(remote.sign_with(key_types::GRANDPA, &key, &grandpa_vote(1, 1, 5, 1)).is_ok());
+		assert!(remote.sign_with(key_types::GRANDPA, &key, &grandpa_vote(0, 2, 5, 1)).is_err());
+		assert!(remote.sign_with(key_types::GRANDPA, &key, &grandpa_vote(0, 2, 4, 1)).is_err());
+		assert!(remote.sign_with(key_types::GRANDPA, &key, &grandpa_vote(0, 2, 1, 2)).is_ok());
+		assert!(remote.sign_with(key_types::GRANDPA, &key, &[0; 8]).is_err());
+
+		// A signer restarted on the same record still refuses.
+		let signer = Signer::new(store, dir.path().join("record.json")).unwrap();
+		let request = |payload| Request::SignWith {
+			key_type: key_types::GRANDPA,
+			key: key.clone(),
+			msg: payload,
+			context: None,
+		};
+		assert!(matches!(
+			signer.handle(request(grandpa_vote(1, 2, 5, 1))),
+			Response::Error(RemoteError::Refused(_)),
+		));
+		assert!(matches!(signer.handle(request(grandpa_vote(1, 1, 5, 1))), Response::Signature(_)));
+	}
+}
@@ -16,7 +16,8 @@
 
 use crate::{
 	config::{ProtocolId, Role}, block_requests, light_client_handler, finality_requests,
-	peer_info, request_responses, discovery::{DiscoveryBehaviour, DiscoveryConfig, DiscoveryOut},
+	peer_info, request_responses, warp_sync_requests,
+	discovery::{DiscoveryBehaviour, DiscoveryConfig, DiscoveryOut},
 	protocol::{message::{self, Roles}, CustomMessageOutcome, NotificationsSink, Protocol},
 	ObservedRole, DhtEvent, ExHashT,
 };
@@ -60,6 +61,8 @@ pub struct Behaviour<B: BlockT, H: ExHashT> {
 	block_requests: block_requests::BlockRequests<B>,
 	/// Finality proof request handling.
 	finality_proof_requests: finality_requests::FinalityProofRequests<B>,
+	/// Warp sync request handling.
+	warp_sync_requests: warp_sync_requests::WarpSyncRequests<B>,
 	/// Light client request handling.
 	light_client_handler: light_client_handler::LightClientHandler<B>,
 
@@ -183,6 +186,7 @@ impl<B: BlockT, H: ExHashT> Behaviour<B, H> {
 		local_public_key: PublicKey,
 		block_requests: block_requests::BlockRequests<B>,
 		finality_proof_requests: finality_requests::FinalityProofRequests<B>,
+		warp_sync_requests: warp_sync_requests::WarpSyncRequests<B>,
 		light_client_handler: light_client_handler::LightClientHandler<B>,
 		disco_config: DiscoveryConfig,
 		request_response_protocols: Vec<request_responses::ProtocolConfig>,
@@ -195,6 +199,7 @@ impl<B: BlockT, H: ExHashT> Behaviour<B, H> {
 				request_responses::RequestResponsesBehaviour::new(request_response_protocols.into_iter())?,
 			block_requests,
 			finality_proof_requests,
+			warp_sync_requests,
 			light_client_handler,
 			events: VecDeque::new(),
 			role,
@@ -361,6 +366,9 @@ Behaviour<B, H> {
 			CustomMessageOutcome::FinalityProofRequest { target, block_hash, request } => {
 				self.finality_proof_requests.send_request(&target, block_hash, request);
 			},
+			CustomMessageOutcome::WarpSyncRequest { target, request } => {
+				self.warp_sync_requests.send_request(&target, request);
+			},
 			CustomMessageOutcome::NotificationStreamOpened { remote, protocols, roles, notifications_sink } => {
 				let role = reported_roles_to_observed_role(&self.role, &remote, roles);
 				for engine_id in protocols {
@@ -473,6 +481,17 @@ impl<B: BlockT, H: ExHashT> NetworkBehaviourEventProcess<finality_requests::Even
 	}
 }
 
+impl<B: BlockT, H: ExHashT> NetworkBehaviourEventProcess<warp_sync_requests::Event<B>> for Behaviour<B, H> {
+	fn inject_event(&mut self, event: warp_sync_requests::Event<B>) {
+		match event {
+			warp_sync_requests::Event::Response { peer, request, proof } => {
+				let ev = self.substrate.on_warp_sync_response(peer, request, proof);
+				self.inject_event(ev);
+			}
+		}
+	}
+}
+
 impl<B: BlockT, H: ExHashT> NetworkBehaviourEventProcess<peer_info::PeerInfoEvent>
 	for Behaviour<B, H> {
 	fn inject_event(&mut self, event: peer_info::PeerInfoEvent) {
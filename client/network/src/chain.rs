@@ -20,7 +20,7 @@
 
 use sp_blockchain::{Error, HeaderBackend, HeaderMetadata};
 use sc_client_api::{BlockBackend, ProofProvider};
-use sp_runtime::traits::{Block as BlockT, BlockIdTo};
+use sp_runtime::{Justification, traits::{Block as BlockT, BlockIdTo}};
 
 /// Local client abstraction for the network.
 pub trait Client<Block: BlockT>: HeaderBackend<Block> + ProofProvider<Block> + BlockIdTo<Block, Error = Error>
@@ -44,3 +44,36 @@ impl<Block: BlockT> FinalityProofProvider<Block> for () {
 		Ok(None)
 	}
 }
+
+/// Outcome of verifying a warp sync proof.
+#[derive(Debug)]
+pub enum WarpSyncVerification<Block: BlockT> {
+	/// The proof is valid but does not reach the latest finalized block. The next proof should
+	/// be requested from `begin`, and verified against the updated verifier `state`.
+	Partial {
+		/// Finalized block the next proof starts at.
+		begin: Block::Hash,
+		/// Opaque verifier state for the next proof.
+		state: Vec<u8>,
+	},
+	/// The proof is valid and ends at a recent finalized block.
+	Complete {
+		/// Header of the finalized block.
+		header: Block::Header,
+		/// Justification proving finality of the block.
+		justification: Justification,
+	},
+}
+
+/// Warp sync proof provider.
+///
+/// Proves finality of a recent block to nodes that only know the genesis block, by following
+/// the changes of the finality authority set, and verifies such proofs.
+pub trait WarpSyncProvider<Block: BlockT>: Send + Sync {
+	/// Generate a proof of finality of a recent block, starting at the finalized block `begin`.
+	fn generate(&self, begin: Block::Hash) -> Result<Vec<u8>, Error>;
+	/// Opaque verifier state of the genesis block, used to verify the first proof.
+	fn initial_state(&self) -> Result<Vec<u8>, Error>;
+	/// Verify a proof against the given verifier state.
+	fn verify(&self, proof: &[u8], state: &[u8]) -> Result<WarpSyncVerification<Block>, Error>;
+}
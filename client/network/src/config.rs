@@ -21,7 +21,7 @@
 //! The [`Params`] struct is the struct that must be passed in order to initialize the networking.
 //! See the documentation of [`Params`].
 
-pub use crate::chain::{Client, FinalityProofProvider};
+pub use crate::chain::{Client, FinalityProofProvider, WarpSyncProvider, WarpSyncVerification};
 pub use crate::on_demand_layer::{AlwaysBadChecker, OnDemand};
 pub use crate::request_responses::{IncomingRequest, ProtocolConfig as RequestResponseConfig};
 pub use libp2p::{identity, core::PublicKey, wasm_ext::ExtTransport, build_multiaddr};
@@ -81,6 +81,12 @@ pub struct Params<B: BlockT, H: ExHashT> {
 	/// This object, if `Some`, is used when we need a proof of finality from another node.
 	pub finality_proof_request_builder: Option<BoxFinalityProofRequestBuilder<B>>,
 
+	/// Warp sync provider.
+	///
+	/// This object, if `Some`, is used to answer warp sync proof requests from other nodes,
+	/// and to verify the proofs we receive when [`SyncMode::Warp`] is configured.
+	pub warp_sync_provider: Option<Arc<dyn WarpSyncProvider<B>>>,
+
 	/// The `OnDemand` object acts as a "receiver" for block data requests from the client.
 	/// If `Some`, the network worker will process these requests and answer them.
 	/// Normally used only for light clients.
@@ -423,6 +429,8 @@ pub struct NetworkConfiguration {
 	pub max_parallel_downloads: u32,
 	/// Should we insert non-global addresses into the DHT?
 	pub allow_non_globals_in_dht: bool,
+	/// How a node without any blocks catches up with the chain.
+	pub sync_mode: SyncMode,
 }
 
 impl NetworkConfiguration {
@@ -455,6 +463,7 @@ impl NetworkConfiguration {
 			},
 			max_parallel_downloads: 5,
 			allow_non_globals_in_dht: false,
+			sync_mode: SyncMode::Full,
 		}
 	}
 
@@ -497,6 +506,16 @@ impl NetworkConfiguration {
 	}
 }
 
+/// Synchronization mode of a node starting from genesis.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SyncMode {
+	/// Download and import every block.
+	Full,
+	/// Download proofs of finality of a recent block and its state, then continue with regular
+	/// block sync from there. Requires a [`WarpSyncProvider`].
+	Warp,
+}
+
 /// Configuration for the transport layer.
 #[derive(Clone, Debug)]
 pub enum TransportConfig {
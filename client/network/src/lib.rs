@@ -258,6 +258,7 @@ mod schema;
 mod service;
 mod transport;
 mod utils;
+mod warp_sync_requests;
 
 pub mod config;
 pub mod error;
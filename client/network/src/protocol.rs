@@ -18,7 +18,7 @@
 
 use crate::{
 	ExHashT,
-	chain::Client,
+	chain::{Client, WarpSyncProvider},
 	config::{BoxFinalityProofRequestBuilder, ProtocolId, TransactionPool, TransactionImportFuture, TransactionImport},
 	error,
 	utils::{interval, LruHashSet},
@@ -48,7 +48,7 @@ use prometheus_endpoint::{
 	Registry, Gauge, Counter, GaugeVec,
 	PrometheusError, Opts, register, U64
 };
-use sync::{ChainSync, SyncState};
+use sync::{ChainSync, SyncState, warp::WarpSyncRequest};
 use std::borrow::Cow;
 use std::collections::{HashMap, HashSet, VecDeque, hash_map::Entry};
 use std::sync::Arc;
@@ -363,6 +363,7 @@ impl<B: BlockT, H: ExHashT> Protocol<B, H> {
 		chain: Arc<dyn Client<B>>,
 		transaction_pool: Arc<dyn TransactionPool<H, B>>,
 		finality_proof_request_builder: Option<BoxFinalityProofRequestBuilder<B>>,
+		warp_sync_provider: Option<Arc<dyn WarpSyncProvider<B>>>,
 		protocol_id: ProtocolId,
 		peerset_config: sc_peerset::PeersetConfig,
 		block_announce_validator: Box<dyn BlockAnnounceValidator<B> + Send>,
@@ -377,6 +378,7 @@ impl<B: BlockT, H: ExHashT> Protocol<B, H> {
 			finality_proof_request_builder,
 			block_announce_validator,
 			config.max_parallel_downloads,
+			warp_sync_provider,
 		);
 
 		let important_peers = {
@@ -1325,6 +1327,27 @@ impl<B: BlockT, H: ExHashT> Protocol<B, H> {
 		self.sync.on_finality_proof_import(request_block, finalization_result)
 	}
 
+	/// Must be called after a [`CustomMessageOutcome::WarpSyncRequest`] has been emitted,
+	/// to notify of the response having arrived.
+	pub fn on_warp_sync_response(
+		&mut self,
+		who: PeerId,
+		request: WarpSyncRequest<B>,
+		response: Vec<u8>,
+	) -> CustomMessageOutcome<B> {
+		trace!(target: "sync", "Warp sync response from {} for {:?}", who, request);
+		match self.sync.on_warp_sync_data(&who, request, response) {
+			Ok(sync::OnWarpSyncData::Nothing) => CustomMessageOutcome::None,
+			Ok(sync::OnWarpSyncData::Import(origin, blocks)) =>
+				CustomMessageOutcome::BlockImport(origin, blocks),
+			Err(sync::BadPeer(id, repu)) => {
+				self.behaviour.disconnect_peer(&id);
+				self.peerset_handle.report_peer(id, repu);
+				CustomMessageOutcome::None
+			}
+		}
+	}
+
 	/// Must be called after a [`CustomMessageOutcome::FinalityProofRequest`] has been emitted,
 	/// to notify of the response having arrived.
 	pub fn on_finality_proof_response(
@@ -1438,6 +1461,12 @@ pub enum CustomMessageOutcome<B: BlockT> {
 	/// If the request times out, or the peer responds in an invalid way, the peer has to be
 	/// disconnect. This will inform the state machine that the request it has emitted is stale.
 	FinalityProofRequest { target: PeerId, block_hash: B::Hash, request: Vec<u8> },
+	/// A new warp sync request must be emitted.
+	/// Once you have the response, you must call `Protocol::on_warp_sync_response`.
+	/// It is the responsibility of the handler to ensure that a timeout exists.
+	/// If the request times out, or the peer responds in an invalid way, the peer has to be
+	/// disconnect. This will inform the state machine that the request it has emitted is stale.
+	WarpSyncRequest { target: PeerId, request: WarpSyncRequest<B> },
 	/// Peer has a reported a new head of chain.
 	PeerNewBest(PeerId, NumberFor<B>),
 	None,
@@ -1542,6 +1571,10 @@ impl<B: BlockT, H: ExHashT> NetworkBehaviour for Protocol<B, H> {
 			};
 			self.pending_messages.push_back(event);
 		}
+		if let Some((id, request)) = self.sync.warp_sync_request() {
+			let event = CustomMessageOutcome::WarpSyncRequest { target: id, request };
+			self.pending_messages.push_back(event);
+		}
 		if let Poll::Ready(Some((tx_hash, result))) = self.pending_transactions.poll_next_unpin(cx) {
 			if let Some(peers) = self.pending_transactions_peers.remove(&tx_hash) {
 				peers.into_iter().for_each(|p| self.on_handle_transaction_import(p, result));
@@ -35,6 +35,7 @@ use sp_consensus::{BlockOrigin, BlockStatus,
 	import_queue::{IncomingBlock, BlockImportResult, BlockImportError}
 };
 use crate::{
+	chain::WarpSyncProvider,
 	config::BoxFinalityProofRequestBuilder,
 	protocol::message::{self, generic::FinalityProofRequest, BlockAnnounce, BlockAttributes, BlockRequest, BlockResponse,
 	FinalityProofResponse, Roles},
@@ -50,9 +51,11 @@ use sp_runtime::{
 };
 use sp_arithmetic::traits::Saturating;
 use std::{fmt, ops::Range, collections::{HashMap, HashSet, VecDeque}, sync::Arc};
+use warp::{WarpSync, WarpSyncRequest};
 
 mod blocks;
 mod extra_requests;
+pub mod warp;
 
 /// Maximum blocks to request in a single packet.
 const MAX_BLOCKS_TO_REQUEST: usize = 128;
@@ -106,6 +109,12 @@ mod rep {
 
 	/// Reputation change when a peer sent us invlid ancestry result.
 	pub const UNKNOWN_ANCESTOR:Rep = Rep::new(-(1 << 16), "DB Error");
+
+	/// Reputation change for peers which send us an invalid warp sync proof.
+	pub const BAD_WARP_PROOF: Rep = Rep::new(-(1 << 29), "Bad warp sync proof");
+
+	/// Reputation change for peers which send us an invalid state chunk.
+	pub const BAD_STATE: Rep = Rep::new(-(1 << 29), "Bad state");
 }
 
 enum PendingRequests {
@@ -192,6 +201,8 @@ pub struct ChainSync<B: BlockT> {
 	max_parallel_downloads: u32,
 	/// Total number of downloaded blocks.
 	downloaded_blocks: usize,
+	/// Warp sync in progress, if any. Regular block requests are suspended until it completes.
+	warp_sync: Option<WarpSync<B>>,
 }
 
 /// All the data we have about a Peer that we are trying to sync with
@@ -250,7 +261,11 @@ pub enum PeerSyncState<B: BlockT> {
 	/// Downloading justification for given block hash.
 	DownloadingJustification(B::Hash),
 	/// Downloading finality proof for given block hash.
-	DownloadingFinalityProof(B::Hash)
+	DownloadingFinalityProof(B::Hash),
+	/// Downloading a warp sync proof.
+	DownloadingWarpProof,
+	/// Downloading a chunk of the state of the warp sync target block.
+	DownloadingState,
 }
 
 impl<B: BlockT> PeerSyncState<B> {
@@ -329,6 +344,15 @@ pub enum OnBlockJustification<B: BlockT> {
 	}
 }
 
+/// Result of [`ChainSync::on_warp_sync_data`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OnWarpSyncData<B: BlockT> {
+	/// The response needs no further handling.
+	Nothing,
+	/// The state has been downloaded and the block should be imported.
+	Import(BlockOrigin, Vec<IncomingBlock<B>>),
+}
+
 /// Result of [`ChainSync::on_block_finality_proof`].
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum OnBlockFinalityProof<B: BlockT> {
@@ -352,6 +376,7 @@ impl<B: BlockT> ChainSync<B> {
 		request_builder: Option<BoxFinalityProofRequestBuilder<B>>,
 		block_announce_validator: Box<dyn BlockAnnounceValidator<B> + Send>,
 		max_parallel_downloads: u32,
+		warp_sync_provider: Option<Arc<dyn WarpSyncProvider<B>>>,
 	) -> Self {
 		let mut required_block_attributes = BlockAttributes::HEADER | BlockAttributes::JUSTIFICATION;
 
@@ -359,6 +384,19 @@ impl<B: BlockT> ChainSync<B> {
 			required_block_attributes |= BlockAttributes::BODY
 		}
 
+		// Warp sync only makes sense for a full node that has nothing but the genesis block.
+		let warp_sync = match warp_sync_provider {
+			Some(provider) if role.is_full() && info.best_number.is_zero() =>
+				match WarpSync::new(client.clone(), provider) {
+					Ok(warp_sync) => Some(warp_sync),
+					Err(e) => {
+						warn!(target: "sync", "💔 Unable to start warp sync: {:?}", e);
+						None
+					}
+				},
+			_ => None,
+		};
+
 		ChainSync {
 			client,
 			peers: HashMap::new(),
@@ -377,6 +415,7 @@ impl<B: BlockT> ChainSync<B> {
 			block_announce_validator,
 			max_parallel_downloads,
 			downloaded_blocks: 0,
+			warp_sync,
 		}
 	}
 
@@ -641,7 +680,7 @@ impl<B: BlockT> ChainSync<B> {
 
 	/// Get an iterator over all block requests of all peers.
 	pub fn block_requests(&mut self) -> impl Iterator<Item = (&PeerId, BlockRequest<B>)> + '_ {
-		if self.pending_requests.is_empty() {
+		if self.pending_requests.is_empty() || self.warp_sync.is_some() {
 			return Either::Left(std::iter::empty())
 		}
 		if self.queue_blocks.len() > MAX_IMPORTING_BLOCKS {
@@ -704,6 +743,75 @@ impl<B: BlockT> ChainSync<B> {
 		Either::Right(iter)
 	}
 
+	/// Get the next warp sync request to make, if warp sync is in progress.
+	///
+	/// Only one warp sync request is in flight at any time.
+	pub fn warp_sync_request(&mut self) -> Option<(PeerId, WarpSyncRequest<B>)> {
+		let warp_sync = self.warp_sync.as_ref()?;
+		if self.peers.values().any(|p| matches!(
+			p.state,
+			PeerSyncState::DownloadingWarpProof | PeerSyncState::DownloadingState
+		)) {
+			return None
+		}
+		let request = warp_sync.next_request();
+		// The state can only be served by peers that have the target block.
+		let min_number = warp_sync.target_number().unwrap_or_else(One::one);
+		let (id, peer) = self.peers.iter_mut()
+			.find(|(_, peer)| peer.state.is_available() && peer.best_number >= min_number)?;
+		peer.state = match request {
+			WarpSyncRequest::Proof { .. } => PeerSyncState::DownloadingWarpProof,
+			WarpSyncRequest::State { .. } => PeerSyncState::DownloadingState,
+		};
+		trace!(target: "sync", "New warp sync request for {}: {:?}", id, request);
+		Some((id.clone(), request))
+	}
+
+	/// Handle a response from the remote to a warp sync request that we made.
+	///
+	/// `request` must be the original request that triggered `response`.
+	pub fn on_warp_sync_data(
+		&mut self,
+		who: &PeerId,
+		request: WarpSyncRequest<B>,
+		response: Vec<u8>,
+	) -> Result<OnWarpSyncData<B>, BadPeer> {
+		if let Some(peer) = self.peers.get_mut(who) {
+			match peer.state {
+				PeerSyncState::DownloadingWarpProof | PeerSyncState::DownloadingState =>
+					peer.state = PeerSyncState::Available,
+				_ => {},
+			}
+		}
+		let warp_sync = match &mut self.warp_sync {
+			Some(warp_sync) if warp_sync.next_request() == request => warp_sync,
+			_ => {
+				trace!(target: "sync", "Ignoring stale warp sync response from {}", who);
+				return Ok(OnWarpSyncData::Nothing)
+			}
+		};
+		let block = match request {
+			WarpSyncRequest::Proof { .. } => {
+				warp_sync.on_warp_proof(who, &response)?;
+				None
+			}
+			WarpSyncRequest::State { .. } => warp_sync.on_state(who, &response)?,
+		};
+		match block {
+			Some(block) => {
+				self.warp_sync = None;
+				if let Some(header) = &block.header {
+					let number = *header.number();
+					info!("⏩ Warp sync complete, importing state of #{} ({})", number, block.hash);
+					self.on_block_queued(&block.hash, number);
+				}
+				self.queue_blocks.insert(block.hash);
+				Ok(OnWarpSyncData::Import(BlockOrigin::NetworkInitialSync, vec![block]))
+			}
+			None => Ok(OnWarpSyncData::Nothing),
+		}
+	}
+
 	/// Handle a response from the remote to a block request that we made.
 	///
 	/// `request` must be the original request that triggered `response`.
@@ -746,6 +854,7 @@ impl<B: BlockT> ChainSync<B> {
 										origin: block_data.origin,
 										allow_missing_state: true,
 										import_existing: false,
+										state: None,
 									}
 								}).collect()
 						}
@@ -765,6 +874,7 @@ impl<B: BlockT> ChainSync<B> {
 									origin: Some(who.clone()),
 									allow_missing_state: true,
 									import_existing: false,
+									state: None,
 								}
 							}).collect()
 						}
@@ -836,7 +946,9 @@ impl<B: BlockT> ChainSync<B> {
 
 						| PeerSyncState::Available
 						| PeerSyncState::DownloadingJustification(..)
-						| PeerSyncState::DownloadingFinalityProof(..) => Vec::new()
+						| PeerSyncState::DownloadingFinalityProof(..)
+						| PeerSyncState::DownloadingWarpProof
+						| PeerSyncState::DownloadingState => Vec::new()
 					}
 				} else {
 					// When request.is_none() this is a block announcement. Just accept blocks.
@@ -850,6 +962,7 @@ impl<B: BlockT> ChainSync<B> {
 							origin: Some(who.clone()),
 							allow_missing_state: true,
 							import_existing: false,
+							state: None,
 						}
 					}).collect()
 				}
@@ -1580,6 +1693,7 @@ mod test {
 			None,
 			block_announce_validator,
 			1,
+			None,
 		);
 
 		let (a1_hash, a1_number) = {
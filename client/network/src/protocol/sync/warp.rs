@@ -0,0 +1,243 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2017-2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+//! Warp sync state machine.
+//!
+//! A node starting from genesis first downloads proofs of finality of a recent block, then
+//! the whole state of that block in chunks proven against its state root. The block is then
+//! imported together with its state as the new base of the chain, and regular block sync
+//! continues from there.
+
+use codec::Decode;
+use crate::chain::{Client, WarpSyncProvider, WarpSyncVerification};
+use libp2p::PeerId;
+use log::{debug, trace};
+use sc_client_api::StorageProof;
+use sp_consensus::{ImportedState, import_queue::IncomingBlock};
+use sp_core::storage::{ChildInfo, well_known_keys};
+use sp_runtime::{Justification, traits::{Block as BlockT, Header, NumberFor}};
+use std::{collections::VecDeque, sync::Arc};
+use super::{BadPeer, rep};
+
+/// A request made by the warp sync state machine.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WarpSyncRequest<B: BlockT> {
+	/// Request a proof of finality of a recent block, starting at the finalized block `begin`.
+	Proof {
+		begin: B::Hash,
+	},
+	/// Request the state entries of `block` following the key `start`, in the default child
+	/// trie stored at `child_key`, or in the top trie if `child_key` is empty.
+	State {
+		block: B::Hash,
+		child_key: Vec<u8>,
+		start: Vec<u8>,
+	},
+}
+
+enum Phase<B: BlockT> {
+	/// Downloading proofs of finality.
+	WarpProof {
+		/// Block the next proof starts at.
+		begin: B::Hash,
+		/// Verifier state for the next proof.
+		verifier_state: Vec<u8>,
+	},
+	/// Downloading the state of the proven block.
+	State {
+		header: B::Header,
+		justification: Justification,
+		/// Child trie currently being downloaded, or `None` for the top trie.
+		child_key: Option<Vec<u8>>,
+		/// Last key received for the current trie.
+		start: Vec<u8>,
+		/// Downloaded top trie entries.
+		top: Vec<(Vec<u8>, Vec<u8>)>,
+		/// Downloaded child trie entries, by unprefixed child storage key.
+		children: Vec<(Vec<u8>, Vec<(Vec<u8>, Vec<u8>)>)>,
+		/// Child tries found in the top trie and not downloaded yet.
+		pending_children: VecDeque<Vec<u8>>,
+	},
+}
+
+/// Warp sync state machine, driven by `ChainSync`.
+pub(crate) struct WarpSync<B: BlockT> {
+	client: Arc<dyn Client<B>>,
+	provider: Arc<dyn WarpSyncProvider<B>>,
+	phase: Phase<B>,
+}
+
+impl<B: BlockT> WarpSync<B> {
+	/// Start warp sync from the genesis block.
+	pub(crate) fn new(
+		client: Arc<dyn Client<B>>,
+		provider: Arc<dyn WarpSyncProvider<B>>,
+	) -> Result<Self, sp_blockchain::Error> {
+		let verifier_state = provider.initial_state()?;
+		let begin = client.info().genesis_hash;
+		Ok(WarpSync {
+			client,
+			provider,
+			phase: Phase::WarpProof { begin, verifier_state },
+		})
+	}
+
+	/// Number of the block the state is downloaded for, once it is known.
+	pub(crate) fn target_number(&self) -> Option<NumberFor<B>> {
+		match &self.phase {
+			Phase::WarpProof { .. } => None,
+			Phase::State { header, .. } => Some(*header.number()),
+		}
+	}
+
+	/// The next request to make.
+	pub(crate) fn next_request(&self) -> WarpSyncRequest<B> {
+		match &self.phase {
+			Phase::WarpProof { begin, .. } => WarpSyncRequest::Proof { begin: *begin },
+			Phase::State { header, child_key, start, .. } => WarpSyncRequest::State {
+				block: header.hash(),
+				child_key: child_key.clone().unwrap_or_default(),
+				start: start.clone(),
+			},
+		}
+	}
+
+	/// Handle a warp sync proof received from `who`.
+	pub(crate) fn on_warp_proof(&mut self, who: &PeerId, proof: &[u8]) -> Result<(), BadPeer> {
+		let (begin, verifier_state) = match &self.phase {
+			Phase::WarpProof { begin, verifier_state } => (*begin, verifier_state),
+			Phase::State { .. } => return Ok(()),
+		};
+
+		match self.provider.verify(proof, verifier_state) {
+			Ok(WarpSyncVerification::Partial { begin: next, state }) => {
+				if next == begin {
+					debug!(target: "sync", "Warp sync proof from {} makes no progress", who);
+					return Err(BadPeer(who.clone(), rep::BAD_WARP_PROOF));
+				}
+				trace!(target: "sync", "Verified warp sync proof up to {}", next);
+				self.phase = Phase::WarpProof { begin: next, verifier_state: state };
+			}
+			Ok(WarpSyncVerification::Complete { header, justification }) => {
+				debug!(
+					target: "sync",
+					"Verified warp sync proof of #{} ({}), downloading state",
+					header.number(),
+					header.hash(),
+				);
+				self.phase = Phase::State {
+					header,
+					justification,
+					child_key: None,
+					start: Vec::new(),
+					top: Vec::new(),
+					children: Vec::new(),
+					pending_children: VecDeque::new(),
+				};
+			}
+			Err(e) => {
+				debug!(target: "sync", "Bad warp sync proof from {}: {:?}", who, e);
+				return Err(BadPeer(who.clone(), rep::BAD_WARP_PROOF));
+			}
+		}
+		Ok(())
+	}
+
+	/// Handle a state chunk proof received from `who`.
+	///
+	/// Returns the block to import once the whole state has been downloaded.
+	pub(crate) fn on_state(&mut self, who: &PeerId, proof: &[u8]) -> Result<Option<IncomingBlock<B>>, BadPeer> {
+		match &mut self.phase {
+			Phase::WarpProof { .. } => Ok(None),
+			Phase::State { header, justification, child_key, start, top, children, pending_children } => {
+				let proof = StorageProof::decode(&mut &proof[..]).map_err(|e| {
+					debug!(target: "sync", "Undecodable state proof from {}: {:?}", who, e);
+					BadPeer(who.clone(), rep::BAD_STATE)
+				})?;
+				let child_info = child_key.as_ref().map(|key| ChildInfo::new_default(key));
+				let (entries, complete) = self.client
+					.verify_range_proof(*header.state_root(), proof, child_info.as_ref(), start)
+					.map_err(|e| {
+						debug!(target: "sync", "Bad state proof from {}: {:?}", who, e);
+						BadPeer(who.clone(), rep::BAD_STATE)
+					})?;
+
+				if entries.is_empty() && !complete {
+					debug!(target: "sync", "Empty state response from {}", who);
+					return Err(BadPeer(who.clone(), rep::BAD_STATE));
+				}
+
+				trace!(
+					target: "sync",
+					"Received {} state entries from {} (child: {:?}, complete: {})",
+					entries.len(),
+					who,
+					child_key,
+					complete,
+				);
+
+				if let Some((last, _)) = entries.last() {
+					*start = last.clone();
+				}
+				match child_key {
+					None => {
+						let prefix = well_known_keys::DEFAULT_CHILD_STORAGE_KEY_PREFIX;
+						pending_children.extend(entries.iter()
+							.filter(|(key, _)| key.starts_with(prefix))
+							.map(|(key, _)| key[prefix.len()..].to_vec())
+						);
+						top.extend(entries);
+					}
+					Some(key) => match children.last_mut() {
+						Some((last, child)) if last == key => child.extend(entries),
+						_ => children.push((key.clone(), entries)),
+					},
+				}
+
+				if !complete {
+					return Ok(None);
+				}
+				*start = Vec::new();
+				*child_key = pending_children.pop_front();
+				if child_key.is_some() {
+					return Ok(None);
+				}
+
+				debug!(
+					target: "sync",
+					"Downloaded state of #{} ({}): {} top entries, {} child tries",
+					header.number(),
+					header.hash(),
+					top.len(),
+					children.len(),
+				);
+				Ok(Some(IncomingBlock {
+					hash: header.hash(),
+					header: Some(header.clone()),
+					body: None,
+					justification: Some(std::mem::take(justification)),
+					origin: Some(who.clone()),
+					allow_missing_state: true,
+					import_existing: false,
+					state: Some(ImportedState {
+						top: std::mem::take(top),
+						children: std::mem::take(children),
+					}),
+				}))
+			}
+		}
+	}
+}
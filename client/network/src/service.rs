@@ -30,7 +30,7 @@
 use crate::{
 	ExHashT, NetworkStateInfo, NetworkStatus,
 	behaviour::{self, Behaviour, BehaviourOut},
-	config::{parse_str_addr, NonReservedPeerMode, Params, Role, TransportConfig},
+	config::{parse_str_addr, NonReservedPeerMode, Params, Role, SyncMode, TransportConfig},
 	DhtEvent,
 	discovery::DiscoveryConfig,
 	error::Error,
@@ -38,7 +38,7 @@ use crate::{
 		NetworkState, NotConnectedPeer as NetworkStateNotConnectedPeer, Peer as NetworkStatePeer,
 	},
 	on_demand_layer::AlwaysBadChecker,
-	light_client_handler, block_requests, finality_requests,
+	light_client_handler, block_requests, finality_requests, warp_sync_requests,
 	protocol::{self, event::Event, NotifsHandlerError, LegacyConnectionKillError, NotificationsSink, Ready, sync::SyncState, PeerInfo, Protocol},
 	transport, ReputationChange,
 };
@@ -256,6 +256,11 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkWorker<B, H> {
 			params.chain.clone(),
 			params.transaction_pool,
 			params.finality_proof_request_builder,
+			if params.network_config.sync_mode == SyncMode::Warp {
+				params.warp_sync_provider.clone()
+			} else {
+				None
+			},
 			params.protocol_id.clone(),
 			peerset_config,
 			params.block_announce_validator,
@@ -278,6 +283,14 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkWorker<B, H> {
 				let config = finality_requests::Config::new(&params.protocol_id);
 				finality_requests::FinalityProofRequests::new(config, params.finality_proof_provider.clone())
 			};
+			let warp_sync_requests = {
+				let config = warp_sync_requests::Config::new(&params.protocol_id);
+				warp_sync_requests::WarpSyncRequests::new(
+					config,
+					params.chain.clone(),
+					params.warp_sync_provider.clone(),
+				)
+			};
 			let light_client_handler = {
 				let config = light_client_handler::Config::new(&params.protocol_id);
 				light_client_handler::LightClientHandler::new(
@@ -317,6 +330,7 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkWorker<B, H> {
 					local_public,
 					block_requests,
 					finality_proof_requests,
+					warp_sync_requests,
 					light_client_handler,
 					discovery_config,
 					params.network_config.request_response_protocols,
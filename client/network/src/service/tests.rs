@@ -98,6 +98,7 @@ fn build_test_full_node(config: config::NetworkConfiguration)
 		network_config: config,
 		chain: client.clone(),
 		finality_proof_provider: None,
+		warp_sync_provider: None,
 		finality_proof_request_builder: None,
 		on_demand: None,
 		transaction_pool: Arc::new(crate::config::EmptyTransactionPool),
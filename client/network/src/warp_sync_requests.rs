@@ -0,0 +1,440 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+//
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! `NetworkBehaviour` implementation which handles warp sync requests.
+//!
+//! Two kinds of requests are served over the same protocol: proofs of finality of a recent
+//! block, produced by the [`WarpSyncProvider`], and chunks of the state of a block together
+//! with the trie nodes proving them. Every request is coming in on a separate connection
+//! substream which gets closed after we have sent the response back. Requests are encoded as
+//! protocol buffers (cf. `warp.v1.proto`).
+
+use bytes::Bytes;
+use codec::{Encode, Decode};
+use crate::{
+	chain::{Client, WarpSyncProvider},
+	config::ProtocolId,
+	protocol::sync::warp::WarpSyncRequest,
+	schema,
+};
+use futures::{future::BoxFuture, prelude::*, stream::FuturesUnordered};
+use libp2p::{
+	core::{
+		Multiaddr,
+		PeerId,
+		connection::ConnectionId,
+		upgrade::{InboundUpgrade, OutboundUpgrade, ReadOneError, UpgradeInfo},
+		upgrade::{read_one, write_one}
+	},
+	swarm::{
+		NegotiatedSubstream,
+		NetworkBehaviour,
+		NetworkBehaviourAction,
+		NotifyHandler,
+		OneShotHandler,
+		OneShotHandlerConfig,
+		PollParameters,
+		SubstreamProtocol
+	}
+};
+use prost::Message;
+use sp_core::storage::ChildInfo;
+use sp_runtime::{generic::BlockId, traits::Block};
+use std::{
+	collections::VecDeque,
+	io,
+	iter,
+	marker::PhantomData,
+	sync::Arc,
+	time::Duration,
+	task::{Context, Poll}
+};
+
+// Type alias for convenience.
+pub type Error = Box<dyn std::error::Error + 'static>;
+
+/// Maximum size of the state entries proven in a single state response.
+///
+/// The proof itself is larger, as it also contains the trie nodes leading to the entries.
+const STATE_CHUNK_SIZE: usize = 2 * 1024 * 1024;
+
+/// Event generated by the warp sync requests behaviour.
+#[derive(Debug)]
+pub enum Event<B: Block> {
+	/// A response to a warp sync request has arrived.
+	Response {
+		peer: PeerId,
+		/// Request originally passed to `send_request`.
+		request: WarpSyncRequest<B>,
+		/// Proof returned by the remote.
+		proof: Vec<u8>,
+	},
+}
+
+/// Configuration options for `WarpSyncRequests`.
+#[derive(Debug, Clone)]
+pub struct Config {
+	max_request_len: usize,
+	max_response_len: usize,
+	inactivity_timeout: Duration,
+	protocol: Bytes,
+}
+
+impl Config {
+	/// Create a fresh configuration with the following options:
+	///
+	/// - max. request size = 1 MiB
+	/// - max. response size = 16 MiB
+	/// - inactivity timeout = 15s
+	pub fn new(id: &ProtocolId) -> Self {
+		let mut c = Config {
+			max_request_len: 1024 * 1024,
+			max_response_len: 16 * 1024 * 1024,
+			inactivity_timeout: Duration::from_secs(15),
+			protocol: Bytes::new(),
+		};
+		c.set_protocol(id);
+		c
+	}
+
+	/// Limit the max. length of incoming warp sync request bytes.
+	pub fn set_max_request_len(&mut self, v: usize) -> &mut Self {
+		self.max_request_len = v;
+		self
+	}
+
+	/// Limit the max. length of incoming warp sync response bytes.
+	pub fn set_max_response_len(&mut self, v: usize) -> &mut Self {
+		self.max_response_len = v;
+		self
+	}
+
+	/// Limit the max. duration the substream may remain inactive before closing it.
+	pub fn set_inactivity_timeout(&mut self, v: Duration) -> &mut Self {
+		self.inactivity_timeout = v;
+		self
+	}
+
+	/// Set protocol to use for upgrade negotiation.
+	pub fn set_protocol(&mut self, id: &ProtocolId) -> &mut Self {
+		let mut v = Vec::new();
+		v.extend_from_slice(b"/");
+		v.extend_from_slice(id.as_ref().as_bytes());
+		v.extend_from_slice(b"/warp-sync/1");
+		self.protocol = v.into();
+		self
+	}
+}
+
+/// The warp sync request handling behaviour.
+pub struct WarpSyncRequests<B: Block> {
+	/// This behaviour's configuration.
+	config: Config,
+	/// Blockchain client, used to prove state chunks.
+	chain: Arc<dyn Client<B>>,
+	/// How to construct warp sync proofs.
+	warp_sync_provider: Option<Arc<dyn WarpSyncProvider<B>>>,
+	/// Futures sending back the warp sync request responses.
+	outgoing: FuturesUnordered<BoxFuture<'static, ()>>,
+	/// Events to return as soon as possible from `poll`.
+	pending_events: VecDeque<NetworkBehaviourAction<OutboundProtocol<B>, Event<B>>>,
+}
+
+impl<B> WarpSyncRequests<B>
+where
+	B: Block,
+{
+	/// Initializes the behaviour.
+	///
+	/// State requests are always served. If the proof provider is `None`, warp sync proof
+	/// requests are answered with an error.
+	pub fn new(
+		cfg: Config,
+		chain: Arc<dyn Client<B>>,
+		warp_sync_provider: Option<Arc<dyn WarpSyncProvider<B>>>,
+	) -> Self {
+		WarpSyncRequests {
+			config: cfg,
+			chain,
+			warp_sync_provider,
+			outgoing: FuturesUnordered::new(),
+			pending_events: VecDeque::new(),
+		}
+	}
+
+	/// Issue a new warp sync request.
+	///
+	/// If the response doesn't arrive in time, or if the remote answers improperly, the target
+	/// will be disconnected.
+	pub fn send_request(&mut self, target: &PeerId, request: WarpSyncRequest<B>) {
+		use schema::v1::warp::warp_sync_request::Request;
+
+		let protobuf_rq = schema::v1::warp::WarpSyncRequest {
+			request: Some(match &request {
+				WarpSyncRequest::Proof { begin } => Request::Proof(schema::v1::warp::WarpProofRequest {
+					begin: begin.encode(),
+				}),
+				WarpSyncRequest::State { block, child_key, start } =>
+					Request::State(schema::v1::warp::StateRequest {
+						block: block.encode(),
+						child_key: child_key.clone(),
+						start: start.clone(),
+					}),
+			}),
+		};
+
+		let mut buf = Vec::with_capacity(protobuf_rq.encoded_len());
+		if let Err(err) = protobuf_rq.encode(&mut buf) {
+			log::warn!("failed to encode warp sync request {:?}: {:?}", protobuf_rq, err);
+			return;
+		}
+
+		log::trace!("enqueueing warp sync request to {:?}: {:?}", target, protobuf_rq);
+		self.pending_events.push_back(NetworkBehaviourAction::NotifyHandler {
+			peer_id: target.clone(),
+			handler: NotifyHandler::Any,
+			event: OutboundProtocol {
+				request: buf,
+				original_request: request,
+				max_response_size: self.config.max_response_len,
+				protocol: self.config.protocol.clone(),
+			},
+		});
+	}
+
+	/// Callback, invoked when a new warp sync request has been received from remote.
+	fn on_warp_sync_request(&mut self, peer: &PeerId, request: &schema::v1::warp::WarpSyncRequest)
+		-> Result<schema::v1::warp::WarpSyncResponse, Error>
+	{
+		use schema::v1::warp::warp_sync_request::Request;
+
+		let proof = match &request.request {
+			Some(Request::Proof(r)) => {
+				let begin = Decode::decode(&mut r.begin.as_ref())?;
+
+				log::trace!(target: "sync", "Warp sync proof request from {} starting at {}", peer, begin);
+
+				if let Some(provider) = &self.warp_sync_provider {
+					provider.generate(begin)?
+				} else {
+					return Err(From::from("Empty warp sync provider".to_string()))
+				}
+			}
+			Some(Request::State(r)) => {
+				let block: B::Hash = Decode::decode(&mut r.block.as_ref())?;
+
+				log::trace!(
+					target: "sync",
+					"State request from {} for {} (child: {:?}, start: {:?})",
+					peer, block, r.child_key, r.start,
+				);
+
+				let child_info = if r.child_key.is_empty() {
+					None
+				} else {
+					Some(ChildInfo::new_default(&r.child_key))
+				};
+				let (proof, _) = self.chain.read_proof_collection(
+					&BlockId::Hash(block),
+					child_info.as_ref(),
+					&r.start,
+					STATE_CHUNK_SIZE,
+				)?;
+				proof.encode()
+			}
+			None => return Err(From::from("Empty warp sync request".to_string())),
+		};
+
+		Ok(schema::v1::warp::WarpSyncResponse { proof })
+	}
+}
+
+impl<B> NetworkBehaviour for WarpSyncRequests<B>
+where
+	B: Block
+{
+	type ProtocolsHandler = OneShotHandler<InboundProtocol<B>, OutboundProtocol<B>, NodeEvent<B, NegotiatedSubstream>>;
+	type OutEvent = Event<B>;
+
+	fn new_handler(&mut self) -> Self::ProtocolsHandler {
+		let p = InboundProtocol {
+			max_request_len: self.config.max_request_len,
+			protocol: self.config.protocol.clone(),
+			marker: PhantomData,
+		};
+		let mut cfg = OneShotHandlerConfig::default();
+		cfg.keep_alive_timeout = self.config.inactivity_timeout;
+		OneShotHandler::new(SubstreamProtocol::new(p, ()), cfg)
+	}
+
+	fn addresses_of_peer(&mut self, _: &PeerId) -> Vec<Multiaddr> {
+		Vec::new()
+	}
+
+	fn inject_connected(&mut self, _peer: &PeerId) {
+	}
+
+	fn inject_disconnected(&mut self, _peer: &PeerId) {
+	}
+
+	fn inject_event(
+		&mut self,
+		peer: PeerId,
+		_connection: ConnectionId,
+		event: NodeEvent<B, NegotiatedSubstream>
+	) {
+		match event {
+			NodeEvent::Request(request, mut stream) => {
+				match self.on_warp_sync_request(&peer, &request) {
+					Ok(res) => {
+						log::trace!("enqueueing warp sync response for peer {}", peer);
+						let mut data = Vec::with_capacity(res.encoded_len());
+						if let Err(e) = res.encode(&mut data) {
+							log::debug!("error encoding warp sync response for peer {}: {}", peer, e)
+						} else {
+							let future = async move {
+								if let Err(e) = write_one(&mut stream, data).await {
+									log::debug!("error writing warp sync response: {}", e)
+								}
+							};
+							self.outgoing.push(future.boxed())
+						}
+					}
+					Err(e) => log::debug!("error handling warp sync request from peer {}: {}", peer, e)
+				}
+			}
+			NodeEvent::Response(response, request) => {
+				let ev = Event::Response {
+					peer,
+					request,
+					proof: response.proof,
+				};
+				self.pending_events.push_back(NetworkBehaviourAction::GenerateEvent(ev));
+			}
+		}
+	}
+
+	fn poll(&mut self, cx: &mut Context, _: &mut impl PollParameters)
+		-> Poll<NetworkBehaviourAction<OutboundProtocol<B>, Event<B>>>
+	{
+		if let Some(ev) = self.pending_events.pop_front() {
+			return Poll::Ready(ev);
+		}
+
+		while let Poll::Ready(Some(_)) = self.outgoing.poll_next_unpin(cx) {}
+		Poll::Pending
+	}
+}
+
+/// Output type of inbound and outbound substream upgrades.
+#[derive(Debug)]
+pub enum NodeEvent<B: Block, T> {
+	/// Incoming request from remote and substream to use for the response.
+	Request(schema::v1::warp::WarpSyncRequest, T),
+	/// Incoming response from remote.
+	Response(schema::v1::warp::WarpSyncResponse, WarpSyncRequest<B>),
+}
+
+/// Substream upgrade protocol.
+///
+/// We attempt to parse an incoming protobuf encoded request which will be handled by the
+/// `WarpSyncRequests` behaviour.
+#[derive(Debug, Clone)]
+pub struct InboundProtocol<B> {
+	/// The max. request length in bytes.
+	max_request_len: usize,
+	/// The protocol to use during upgrade negotiation.
+	protocol: Bytes,
+	/// Marker to pin the block type.
+	marker: PhantomData<B>,
+}
+
+impl<B: Block> UpgradeInfo for InboundProtocol<B> {
+	type Info = Bytes;
+	type InfoIter = iter::Once<Self::Info>;
+
+	fn protocol_info(&self) -> Self::InfoIter {
+		iter::once(self.protocol.clone())
+	}
+}
+
+impl<B, T> InboundUpgrade<T> for InboundProtocol<B>
+where
+	B: Block,
+	T: AsyncRead + AsyncWrite + Unpin + Send + 'static
+{
+	type Output = NodeEvent<B, T>;
+	type Error = ReadOneError;
+	type Future = BoxFuture<'static, Result<Self::Output, Self::Error>>;
+
+	fn upgrade_inbound(self, mut s: T, _: Self::Info) -> Self::Future {
+		async move {
+			let len = self.max_request_len;
+			let vec = read_one(&mut s, len).await?;
+			match schema::v1::warp::WarpSyncRequest::decode(&vec[..]) {
+				Ok(r) => Ok(NodeEvent::Request(r, s)),
+				Err(e) => Err(ReadOneError::Io(io::Error::new(io::ErrorKind::Other, e)))
+			}
+		}.boxed()
+	}
+}
+
+/// Substream upgrade protocol.
+///
+/// Sends a request to remote and awaits the response.
+#[derive(Debug, Clone)]
+pub struct OutboundProtocol<B: Block> {
+	/// The serialized protobuf request.
+	request: Vec<u8>,
+	/// The request, handed back together with the response.
+	original_request: WarpSyncRequest<B>,
+	/// The max. response length in bytes.
+	max_response_size: usize,
+	/// The protocol to use for upgrade negotiation.
+	protocol: Bytes,
+}
+
+impl<B: Block> UpgradeInfo for OutboundProtocol<B> {
+	type Info = Bytes;
+	type InfoIter = iter::Once<Self::Info>;
+
+	fn protocol_info(&self) -> Self::InfoIter {
+		iter::once(self.protocol.clone())
+	}
+}
+
+impl<B, T> OutboundUpgrade<T> for OutboundProtocol<B>
+where
+	B: Block,
+	T: AsyncRead + AsyncWrite + Unpin + Send + 'static
+{
+	type Output = NodeEvent<B, T>;
+	type Error = ReadOneError;
+	type Future = BoxFuture<'static, Result<Self::Output, Self::Error>>;
+
+	fn upgrade_outbound(self, mut s: T, _: Self::Info) -> Self::Future {
+		async move {
+			write_one(&mut s, &self.request).await?;
+			let vec = read_one(&mut s, self.max_response_size).await?;
+
+			schema::v1::warp::WarpSyncResponse::decode(&vec[..])
+				.map(|r| NodeEvent::Response(r, self.original_request))
+				.map_err(|e| {
+					ReadOneError::Io(io::Error::new(io::ErrorKind::Other, e))
+				})
+		}.boxed()
+	}
+}
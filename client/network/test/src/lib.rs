@@ -29,7 +29,7 @@ use std::{
 
 use libp2p::build_multiaddr;
 use log::trace;
-use sc_network::config::FinalityProofProvider;
+use sc_network::config::{FinalityProofProvider, WarpSyncProvider, WarpSyncVerification};
 use sp_blockchain::{
 	HeaderBackend, Result as ClientResult,
 	well_known_cache_keys::{self, Id as CacheKeyId},
@@ -51,7 +51,7 @@ use sp_consensus::Error as ConsensusError;
 use sp_consensus::{BlockOrigin, ForkChoiceStrategy, BlockImportParams, BlockCheckParams, JustificationImport};
 use futures::prelude::*;
 use sc_network::{NetworkWorker, NetworkService, config::ProtocolId};
-use sc_network::config::{NetworkConfiguration, TransportConfig, BoxFinalityProofRequestBuilder};
+use sc_network::config::{NetworkConfiguration, SyncMode, TransportConfig, BoxFinalityProofRequestBuilder};
 use libp2p::PeerId;
 use parking_lot::Mutex;
 use sp_core::H256;
@@ -59,6 +59,7 @@ use sc_network::config::ProtocolConfig;
 use sp_runtime::generic::{BlockId, OpaqueDigestItemId};
 use sp_runtime::traits::{Block as BlockT, Header as HeaderT, NumberFor};
 use sp_runtime::{ConsensusEngineId, Justification};
+use sp_runtime::codec::{Encode, Decode};
 use substrate_test_runtime_client::{self, AccountKeyring};
 use sc_service::client::Client;
 pub use sc_network::config::EmptyTransactionPool;
@@ -558,6 +559,33 @@ pub struct FullPeerConfig {
 	pub block_announce_validator: Option<Box<dyn BlockAnnounceValidator<Block> + Send + Sync>>,
 	/// List of notification protocols that the network must support.
 	pub notifications_protocols: Vec<(ConsensusEngineId, Cow<'static, str>)>,
+	/// Whether to catch up with warp sync instead of importing every block.
+	pub warp_sync: bool,
+}
+
+/// A warp sync provider proving the latest finalized block directly, by its header and
+/// justification. Verification accepts any such proof.
+pub struct TestWarpSyncProvider(PeersClient);
+
+impl WarpSyncProvider<Block> for TestWarpSyncProvider {
+	fn generate(&self, _begin: Hash) -> ClientResult<Vec<u8>> {
+		let finalized = BlockId::Hash(self.0.info().finalized_hash);
+		let header = self.0.header(&finalized)?
+			.ok_or_else(|| sp_blockchain::Error::UnknownBlock(format!("{}", finalized)))?;
+		let justification = self.0.justification(&finalized)?
+			.ok_or_else(|| sp_blockchain::Error::Backend("No justification for the finalized block".into()))?;
+		Ok((header, justification).encode())
+	}
+
+	fn initial_state(&self) -> ClientResult<Vec<u8>> {
+		Ok(Vec::new())
+	}
+
+	fn verify(&self, proof: &[u8], _state: &[u8]) -> ClientResult<WarpSyncVerification<Block>> {
+		let (header, justification) = Decode::decode(&mut &proof[..])
+			.map_err(|_| sp_blockchain::Error::BadJustification("failed to decode warp sync proof".into()))?;
+		Ok(WarpSyncVerification::Complete { header, justification })
+	}
 }
 
 pub trait TestNetFactory: Sized {
@@ -602,6 +630,14 @@ pub trait TestNetFactory: Sized {
 		None
 	}
 
+	/// Get warp sync proof provider (if supported).
+	fn make_warp_sync_provider(
+		&self,
+		_client: PeersClient,
+	) -> Option<Arc<dyn WarpSyncProvider<Block>>> {
+		None
+	}
+
 	fn default_config() -> ProtocolConfig {
 		ProtocolConfig::default()
 	}
@@ -669,6 +705,9 @@ pub trait TestNetFactory: Sized {
 		network_config.listen_addresses = vec![listen_addr.clone()];
 		network_config.allow_non_globals_in_dht = true;
 		network_config.notifications_protocols = config.notifications_protocols;
+		if config.warp_sync {
+			network_config.sync_mode = SyncMode::Warp;
+		}
 
 		let network = NetworkWorker::new(sc_network::config::Params {
 			role: Role::Full,
@@ -678,6 +717,9 @@ pub trait TestNetFactory: Sized {
 			finality_proof_provider: self.make_finality_proof_provider(
 				PeersClient::Full(client.clone(), backend.clone()),
 			),
+			warp_sync_provider: self.make_warp_sync_provider(
+				PeersClient::Full(client.clone(), backend.clone()),
+			),
 			finality_proof_request_builder,
 			on_demand: None,
 			transaction_pool: Arc::new(EmptyTransactionPool),
@@ -758,6 +800,7 @@ pub trait TestNetFactory: Sized {
 			finality_proof_provider: self.make_finality_proof_provider(
 				PeersClient::Light(client.clone(), backend.clone())
 			),
+			warp_sync_provider: None,
 			finality_proof_request_builder,
 			on_demand: None,
 			transaction_pool: Arc::new(EmptyTransactionPool),
@@ -928,6 +971,10 @@ impl TestNetFactory for TestNet {
 		PassThroughVerifier::new_with_fork_choice(false, self.fork_choice.clone())
 	}
 
+	fn make_warp_sync_provider(&self, client: PeersClient) -> Option<Arc<dyn WarpSyncProvider<Block>>> {
+		Some(Arc::new(TestWarpSyncProvider(client)))
+	}
+
 	fn peer(&mut self, i: usize) -> &mut Peer<()> {
 		&mut self.peers[i]
 	}
@@ -750,3 +750,37 @@ fn sync_blocks_when_block_announce_validator_says_it_is_new_best() {
 	// that flags all blocks as `is_new_best` and thus, it should have synced the blocks.
 	assert!(!net.peer(1).has_block(&block_hash));
 }
+
+#[test]
+fn warp_sync_imports_state_and_continues() {
+	use sc_client_api::StorageProvider;
+	use sp_core::storage::StorageKey;
+
+	sp_tracing::try_init_simple();
+	let mut net = TestNet::new(1);
+	net.peer(0).push_blocks(10, true);
+	net.peer(0).client().finalize_block(BlockId::Number(8), Some(vec![1, 2, 3]), true).unwrap();
+	let hash_5 = net.peer(0).client().header(&BlockId::Number(5)).unwrap().unwrap().hash();
+
+	net.add_full_peer_with_config(FullPeerConfig {
+		warp_sync: true,
+		..Default::default()
+	});
+	net.block_until_sync();
+
+	let info = net.peer(1).client().info();
+	assert_eq!(info.best_number, 10);
+	assert!(info.finalized_number >= 8);
+	// Blocks below the warp target are never downloaded.
+	assert!(!net.peer(1).has_block(&hash_5));
+	assert_eq!(
+		net.peer(1).client().justification(&BlockId::Number(8)).unwrap(),
+		Some(vec![1, 2, 3]),
+	);
+
+	let at = BlockId::Number(10);
+	let all = StorageKey(Vec::new());
+	let expected = net.peer(0).client().as_full().unwrap().storage_pairs(&at, &all).unwrap();
+	let imported = net.peer(1).client().as_full().unwrap().storage_pairs(&at, &all).unwrap();
+	assert_eq!(expected, imported);
+}
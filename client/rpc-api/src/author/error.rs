@@ -99,6 +99,8 @@ const POOL_CYCLE_DETECTED: i64 = POOL_INVALID_TX + 5;
 const POOL_IMMEDIATELY_DROPPED: i64 = POOL_INVALID_TX + 6;
 /// The key type crypto is not known.
 const UNSUPPORTED_KEY_TYPE: i64 = POOL_INVALID_TX + 7;
+/// The sender of the transaction already has too many transactions in the pool.
+const POOL_TOO_MANY_FROM_SENDER: i64 = POOL_INVALID_TX + 8;
 
 impl From<Error> for rpc::Error {
 	fn from(e: Error) -> Self {
@@ -158,6 +160,11 @@ impl From<Error> for rpc::Error {
 				message: "Immediately Dropped".into(),
 				data: Some("The transaction couldn't enter the pool because of the limit".into()),
 			},
+			Error::Pool(PoolError::TooManyFromSender) => rpc::Error {
+				code: rpc::ErrorCode::ServerError(POOL_TOO_MANY_FROM_SENDER),
+				message: "Too Many Transactions From Sender".into(),
+				data: Some("The sender already has the maximum number of transactions in the pool".into()),
+			},
 			Error::UnsupportedKeyType => rpc::Error {
 				code: rpc::ErrorCode::ServerError(UNSUPPORTED_KEY_TYPE),
 				message: "Unknown key type crypto" .into(),
@@ -17,7 +17,7 @@
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
 //! RPC Metadata
-use std::sync::Arc;
+use std::{net::IpAddr, sync::Arc};
 
 use jsonrpc_core::futures::sync::mpsc;
 use jsonrpc_pubsub::{Session, PubSubMetadata};
@@ -30,6 +30,7 @@ use jsonrpc_pubsub::{Session, PubSubMetadata};
 #[derive(Default, Clone)]
 pub struct Metadata {
 	session: Option<Arc<Session>>,
+	client_address: Option<IpAddr>,
 }
 
 impl jsonrpc_core::Metadata for Metadata {}
@@ -44,9 +45,20 @@ impl Metadata {
 	pub fn new(transport: mpsc::Sender<String>) -> Self {
 		Metadata {
 			session: Some(Arc::new(Session::new(transport))),
+			client_address: None,
 		}
 	}
 
+	/// Address of the client making the calls, if the transport tells it.
+	pub fn client_address(&self) -> Option<IpAddr> {
+		self.client_address
+	}
+
+	/// Set the address of the client making the calls.
+	pub fn set_client_address(&mut self, address: IpAddr) {
+		self.client_address = Some(address);
+	}
+
 	/// Create new `Metadata` for tests.
 	#[cfg(test)]
 	pub fn new_test() -> (mpsc::Receiver<String>, Self) {
@@ -27,6 +27,7 @@ use jsonrpc_pubsub::{typed::Subscriber, SubscriptionId};
 use sp_core::Bytes;
 use sp_core::storage::{StorageKey, StorageData, StorageChangeSet};
 use sp_version::RuntimeVersion;
+use sp_rpc::tracing::BlockTrace;
 use self::error::FutureResult;
 
 pub use self::gen_client::Client as StateClient;
@@ -107,6 +108,22 @@ pub trait StateApi<Hash> {
 	#[rpc(name = "state_getReadProof")]
 	fn read_proof(&self, keys: Vec<StorageKey>, hash: Option<Hash>) -> FutureResult<ReadProof<Hash>>;
 
+	/// Re-execute a block on the state of its parent and return the recorded spans and events.
+	///
+	/// `targets` is a comma separated list of tracing targets with optional levels, e.g.
+	/// `pallet=trace,frame=debug,state`, and defaults to `pallet,frame,state`.
+	/// `storage_keys` is a comma separated list of hex encoded prefixes the storage reads and
+	/// writes are filtered by; all of them are returned if it is omitted.
+	///
+	/// This method is unsafe, as executing blocks is expensive.
+	#[rpc(name = "state_traceBlock")]
+	fn trace_block(
+		&self,
+		block: Hash,
+		targets: Option<String>,
+		storage_keys: Option<String>,
+	) -> FutureResult<BlockTrace>;
+
 	/// New runtime version subscription
 	#[pubsub(
 		subscription = "state_runtimeVersion",
@@ -20,6 +20,7 @@
 
 #![warn(missing_docs)]
 
+pub mod limits;
 mod middleware;
 
 use std::io;
@@ -27,20 +28,26 @@ use jsonrpc_core::{IoHandlerExtension, MetaIoHandler};
 use log::error;
 use pubsub::PubSubMetadata;
 
+const MEGABYTE: usize = 1024 * 1024;
+
 /// Maximal payload accepted by RPC servers.
-const MAX_PAYLOAD: usize = 15 * 1024 * 1024;
+pub const RPC_MAX_PAYLOAD_DEFAULT: usize = 15 * MEGABYTE;
 
 /// Default maximum number of connections for WS RPC servers.
 const WS_MAX_CONNECTIONS: usize = 100;
 
+/// Default maximum number of subscriptions open at once over a WS RPC connection.
+pub const RPC_MAX_SUBSCRIPTIONS_PER_CONNECTION_DEFAULT: usize = 1024;
+
 /// The RPC IoHandler containing all requested APIs.
 pub type RpcHandler<T> = pubsub::PubSubHandler<T, RpcMiddleware>;
 
 pub use self::inner::*;
-pub use middleware::{RpcMiddleware, RpcMetrics};
+pub use limits::{RpcLimits, RateLimit};
+pub use middleware::{ClientAddress, RpcMiddleware, RpcMetrics};
 
 /// Construct rpc `IoHandler`
-pub fn rpc_handler<M: PubSubMetadata>(
+pub fn rpc_handler<M: PubSubMetadata + ClientAddress>(
 	extension: impl IoHandlerExtension<M>,
 	rpc_middleware: RpcMiddleware,
 ) -> RpcHandler<M> {
@@ -66,6 +73,7 @@ pub fn rpc_handler<M: PubSubMetadata>(
 #[cfg(not(target_os = "unknown"))]
 mod inner {
 	use super::*;
+	use std::cell::Cell;
 
 	/// Type alias for ipc server
 	pub type IpcServer = ipc::Server;
@@ -74,15 +82,35 @@ mod inner {
 	/// Type alias for ws server
 	pub type WsServer = ws::Server;
 
+	thread_local! {
+		/// The address of the client of the WS connection being opened on this thread.
+		///
+		/// The WS server only tells the meta extractor about the connection, not about the
+		/// request opening it. The request middleware sees the request right before the
+		/// metadata is extracted, on the same thread, and passes its address on here.
+		static WS_CLIENT_ADDRESS: Cell<Option<std::net::IpAddr>> = Cell::new(None);
+	}
+
 	/// Start HTTP server listening on given address.
 	///
 	/// **Note**: Only available if `not(target_os = "unknown")`.
-	pub fn start_http<M: pubsub::PubSubMetadata + Default>(
+	pub fn start_http<M: pubsub::PubSubMetadata + ClientAddress + Default>(
 		addr: &std::net::SocketAddr,
 		cors: Option<&Vec<String>>,
 		io: RpcHandler<M>,
+		maybe_max_payload_mb: Option<usize>,
 	) -> io::Result<http::Server> {
-		http::ServerBuilder::new(io)
+		let max_request_body_size = maybe_max_payload_mb.map(|mb| mb.saturating_mul(MEGABYTE))
+			.unwrap_or(RPC_MAX_PAYLOAD_DEFAULT);
+
+		http::ServerBuilder::with_meta_extractor(io, |request: &http::hyper::Request<http::hyper::Body>| {
+			let mut meta = M::default();
+			let header = |name: &str| request.headers().get(name).map(|value| value.as_bytes());
+			if let Some(address) = limits::forwarded_address(header("x-forwarded-for"), header("forwarded")) {
+				meta.set_client_address(address);
+			}
+			meta
+		})
 			.threads(4)
 			.health_api(("/health", "system_health"))
 			.allowed_hosts(hosts_filtering(cors.is_some()))
@@ -92,14 +120,14 @@ mod inner {
 				http::RestApi::Unsecure
 			})
 			.cors(map_cors::<http::AccessControlAllowOrigin>(cors))
-			.max_request_body_size(MAX_PAYLOAD)
+			.max_request_body_size(max_request_body_size)
 			.start_http(addr)
 	}
 
 	/// Start IPC server listening on given path.
 	///
 	/// **Note**: Only available if `not(target_os = "unknown")`.
-	pub fn start_ipc<M: pubsub::PubSubMetadata + Default>(
+	pub fn start_ipc<M: pubsub::PubSubMetadata + ClientAddress + Default>(
 		addr: &str,
 		io: RpcHandler<M>,
 	) -> io::Result<ipc::Server> {
@@ -116,14 +144,36 @@ mod inner {
 	/// Start WS server listening on given address.
 	///
 	/// **Note**: Only available if `not(target_os = "unknown")`.
-	pub fn start_ws<M: pubsub::PubSubMetadata + From<jsonrpc_core::futures::sync::mpsc::Sender<String>>> (
+	pub fn start_ws<
+		M: pubsub::PubSubMetadata + ClientAddress + From<jsonrpc_core::futures::sync::mpsc::Sender<String>>,
+	> (
 		addr: &std::net::SocketAddr,
 		max_connections: Option<usize>,
 		cors: Option<&Vec<String>>,
 		io: RpcHandler<M>,
+		maybe_max_payload_mb: Option<usize>,
 	) -> io::Result<ws::Server> {
-		ws::ServerBuilder::with_meta_extractor(io, |context: &ws::RequestContext| context.sender().into())
-			.max_payload(MAX_PAYLOAD)
+		let max_payload = maybe_max_payload_mb.map(|mb| mb.saturating_mul(MEGABYTE))
+			.unwrap_or(RPC_MAX_PAYLOAD_DEFAULT);
+
+		ws::ServerBuilder::with_meta_extractor(io, |context: &ws::RequestContext| {
+			let mut meta: M = context.sender().into();
+			if let Some(address) = WS_CLIENT_ADDRESS.with(Cell::take) {
+				meta.set_client_address(address);
+			}
+			meta
+		})
+			.request_middleware(|request: &ws::ws::Request| -> Option<ws::ws::Response> {
+				let address = limits::forwarded_address(
+					request.header("x-forwarded-for").map(|value| &value[..]),
+					request.header("forwarded").map(|value| &value[..]),
+				);
+				WS_CLIENT_ADDRESS.with(|cell| cell.set(address));
+				None
+			})
+			// Also bounds the inbound and outbound buffers of a connection, a connection whose
+			// outbound buffer would grow past it is closed.
+			.max_payload(max_payload)
 			.max_connections(max_connections.unwrap_or(WS_MAX_CONNECTIONS))
 			.allowed_origins(map_cors(cors))
 			.allowed_hosts(hosts_filtering(cors.is_some()))
@@ -159,3 +209,98 @@ mod inner {
 #[cfg(target_os = "unknown")]
 mod inner {
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::io::{Read, Write};
+	use std::net::{SocketAddr, TcpStream};
+	use std::time::Duration;
+	use jsonrpc_core::Value;
+	use sc_rpc_api::Metadata;
+
+	/// Size of the result of `test_large`.
+	const LARGE_RESULT: usize = MEGABYTE / 2;
+
+	fn start(max_connections: Option<usize>, max_payload_mb: Option<usize>) -> ws::Server {
+		let mut io = rpc_handler(MetaIoHandler::<Metadata>::default(), RpcMiddleware::new(None, "ws"));
+		io.add_method("test_large", |_| Ok(Value::String("x".repeat(LARGE_RESULT))));
+		let addr: SocketAddr = "127.0.0.1:0".parse().unwrap();
+		start_ws(&addr, max_connections, None, io, max_payload_mb).unwrap()
+	}
+
+	/// Open a WS connection, returning `None` if the server closes it during the handshake.
+	fn connect(addr: &SocketAddr) -> Option<TcpStream> {
+		let mut stream = TcpStream::connect(addr).unwrap();
+		stream.set_read_timeout(Some(Duration::from_secs(10))).unwrap();
+		write!(
+			stream,
+			"GET / HTTP/1.1\r\nHost: {}\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n\
+			Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\nSec-WebSocket-Version: 13\r\n\r\n",
+			addr,
+		).ok()?;
+
+		let mut response = Vec::new();
+		let mut byte = [0u8];
+		while !response.ends_with(b"\r\n\r\n") {
+			match stream.read(&mut byte) {
+				Ok(1) => response.push(byte[0]),
+				_ => return None,
+			}
+		}
+		assert!(response.starts_with(b"HTTP/1.1 101"));
+		Some(stream)
+	}
+
+	/// Send a call as a masked text frame.
+	fn call(stream: &mut TcpStream, method: &str, id: u64) -> io::Result<()> {
+		let payload = format!(r#"{{"jsonrpc":"2.0","method":"{}","params":[],"id":{}}}"#, method, id);
+		assert!(payload.len() < 126);
+		// A zero masking key leaves the payload as it is.
+		let mut frame = vec![0x81, 0x80 | payload.len() as u8, 0, 0, 0, 0];
+		frame.extend_from_slice(payload.as_bytes());
+		stream.write_all(&frame)
+	}
+
+	/// Whether the server closed the connection, reading anything it still sent.
+	fn is_closed(stream: &mut TcpStream) -> bool {
+		let mut buffer = vec![0u8; 64 * 1024];
+		loop {
+			match stream.read(&mut buffer) {
+				Ok(0) => return true,
+				Ok(_) => continue,
+				Err(e) => return e.kind() == io::ErrorKind::ConnectionReset,
+			}
+		}
+	}
+
+	#[test]
+	fn connections_over_the_limit_are_closed() {
+		let server = start(Some(1), None);
+
+		let mut first = connect(server.addr()).expect("first connection is accepted");
+		assert!(connect(server.addr()).is_none());
+
+		call(&mut first, "rpc_methods", 1).unwrap();
+		let mut header = [0u8; 2];
+		first.read_exact(&mut header).unwrap();
+		assert_eq!(header[0], 0x81);
+	}
+
+	#[test]
+	fn connections_not_reading_responses_are_closed() {
+		let server = start(None, Some(1));
+		let mut stream = connect(server.addr()).unwrap();
+
+		// Far more than the outbound buffer and what the kernel buffers of the socket hold.
+		let mut closed_while_sending = false;
+		for id in 0..128 {
+			if call(&mut stream, "test_large", id).is_err() {
+				closed_while_sending = true;
+				break;
+			}
+		}
+
+		assert!(closed_while_sending || is_closed(&mut stream));
+	}
+}
@@ -0,0 +1,532 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Rate limits and access policies for RPC calls.
+//!
+//! Rate limits are kept per client. The servers do not expose the address of their peers, so
+//! clients are told apart by the address a reverse proxy in front of the node reports, if
+//! [`RpcLimits::trust_proxy_headers`] is set. Otherwise calls over a WebSocket connection are
+//! accounted to that connection, and all HTTP calls to a single client.
+//!
+//! Subscriptions are limited per connection. A subscription is counted from the moment it is
+//! requested until the client unsubscribes from it or the connection is closed.
+
+use std::{
+	collections::{HashMap, HashSet}, fs, net::{IpAddr, SocketAddr}, path::Path, str, sync::Arc,
+	time::Instant,
+};
+
+use jsonrpc_core::{Error, ErrorCode};
+use parking_lot::Mutex;
+use pubsub::SubscriptionId;
+use serde::Deserialize;
+
+/// Error code of calls refused by a rate limit.
+pub const RATE_LIMITED: i64 = -32005;
+/// Error code of subscriptions refused for exceeding the subscriptions of a connection.
+pub const TOO_MANY_SUBSCRIPTIONS: i64 = -32006;
+/// Error code of requests refused for their size.
+pub const REQUEST_TOO_LARGE: i64 = -32007;
+/// Error code of calls whose response exceeds the maximal response size.
+pub const RESPONSE_TOO_LARGE: i64 = -32008;
+
+/// Number of calls after which the rate limits that are back to their initial state are dropped.
+const PRUNE_INTERVAL: usize = 1024;
+
+/// Limits to the RPC calls of clients.
+///
+/// Method patterns are method names, or prefixes of method names followed by `*`.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct RpcLimits {
+	/// Methods that may be called, or all methods if `None`.
+	#[serde(default)]
+	pub allow: Option<Vec<String>>,
+	/// Methods that may not be called, even if allowed.
+	#[serde(default)]
+	pub deny: Vec<String>,
+	/// Limit of the calls of a client, to any method.
+	#[serde(default)]
+	pub per_client: Option<RateLimit>,
+	/// Limits of the calls of a client to the given methods.
+	#[serde(default)]
+	pub per_method: HashMap<String, RateLimit>,
+	/// Maximal number of subscriptions open at once over a connection.
+	#[serde(default)]
+	pub max_subscriptions: Option<usize>,
+	/// Maximal size of a request, in bytes.
+	#[serde(default)]
+	pub max_request_size: Option<usize>,
+	/// Maximal size of the response to a call, in bytes.
+	#[serde(default)]
+	pub max_response_size: Option<usize>,
+	/// Whether clients are told apart by the address in the `X-Forwarded-For` or `Forwarded`
+	/// header of their requests. Only set this behind a reverse proxy adding these headers, as
+	/// clients can put anything in them.
+	#[serde(default)]
+	pub trust_proxy_headers: bool,
+}
+
+/// A token bucket limit: up to `burst` calls at once, refilled at `per_second` calls a second.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct RateLimit {
+	/// Number of calls that can be made at once.
+	pub burst: u32,
+	/// Number of calls a second that can be made in the long run.
+	pub per_second: f64,
+}
+
+impl RpcLimits {
+	/// Read limits from a JSON file.
+	pub fn from_json_file(path: &Path) -> Result<Self, String> {
+		let file = fs::File::open(path)
+			.map_err(|e| format!("Error opening RPC limits file `{}`: {}", path.display(), e))?;
+		serde_json::from_reader(file)
+			.map_err(|e| format!("Error parsing RPC limits file `{}`: {}", path.display(), e))
+	}
+
+	/// Whether `method` may be called at all.
+	pub fn is_allowed(&self, method: &str) -> bool {
+		let matches = |pattern: &String| match pattern.strip_suffix('*') {
+			Some(prefix) => method.starts_with(prefix),
+			None => method == pattern,
+		};
+		self.allow.as_ref().map_or(true, |allow| allow.iter().any(matches))
+			&& !self.deny.iter().any(matches)
+	}
+}
+
+/// The address of the client of a request, as reported by the reverse proxy in front of the
+/// node in the `X-Forwarded-For` or `Forwarded` header.
+///
+/// The last address of the headers is the one the proxy appended, the others were sent by the
+/// client and cannot be trusted.
+pub(crate) fn forwarded_address(
+	x_forwarded_for: Option<&[u8]>,
+	forwarded: Option<&[u8]>,
+) -> Option<IpAddr> {
+	let parse = |address: &str| {
+		let address = address.trim().trim_matches('"');
+		address.parse::<IpAddr>().ok()
+			.or_else(|| address.parse::<SocketAddr>().ok().map(|address| address.ip()))
+			.or_else(|| address.trim_start_matches('[').trim_end_matches(']').parse().ok())
+	};
+
+	if let Some(header) = x_forwarded_for {
+		return str::from_utf8(header).ok()?.rsplit(',').next().and_then(parse);
+	}
+	str::from_utf8(forwarded?).ok()?
+		.rsplit(',')
+		.next()?
+		.split(';')
+		.filter_map(|pair| {
+			let mut pair = pair.splitn(2, '=');
+			Some((pair.next()?.trim(), pair.next()?))
+		})
+		.find(|(key, _)| key.eq_ignore_ascii_case("for"))
+		.and_then(|(_, address)| parse(address))
+}
+
+/// A client whose calls are rate limited together.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum Client {
+	/// A client identified by its address.
+	Address(IpAddr),
+	/// A connection, identified by the address of its session.
+	Connection(usize),
+	/// Clients not making their calls over a connection nor telling their address.
+	Shared,
+}
+
+/// Why a call was refused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Rejection {
+	Denied,
+	RateLimited,
+	TooManySubscriptions,
+	RequestTooLarge,
+	ResponseTooLarge,
+}
+
+impl Rejection {
+	/// Label of the rejection in metrics.
+	pub fn label(&self) -> &'static str {
+		match self {
+			Rejection::Denied => "denied",
+			Rejection::RateLimited => "rate_limited",
+			Rejection::TooManySubscriptions => "too_many_subscriptions",
+			Rejection::RequestTooLarge => "request_too_large",
+			Rejection::ResponseTooLarge => "response_too_large",
+		}
+	}
+
+	/// The JSON-RPC error returned for a refused call.
+	pub fn error(&self) -> Error {
+		let (code, message) = match self {
+			// Same as for unsafe methods denied to external clients.
+			Rejection::Denied => return Error::method_not_found(),
+			Rejection::RateLimited => (RATE_LIMITED, "Rate limit exceeded"),
+			Rejection::TooManySubscriptions => (TOO_MANY_SUBSCRIPTIONS, "Too many subscriptions"),
+			Rejection::RequestTooLarge => (REQUEST_TOO_LARGE, "Request too large"),
+			Rejection::ResponseTooLarge => (RESPONSE_TOO_LARGE, "Response too large"),
+		};
+		Error { code: ErrorCode::ServerError(code), message: message.into(), data: None }
+	}
+}
+
+/// Whether a method opens or closes a subscription.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Subscription {
+	Subscribe,
+	Unsubscribe,
+}
+
+impl Subscription {
+	/// Tell the subscription methods, which all follow the naming of `chain_subscribeNewHeads`
+	/// and `author_submitAndWatchExtrinsic`, apart from the others.
+	pub fn of(method: &str) -> Option<Self> {
+		let method = method.to_lowercase();
+		if method.contains("unsubscribe") || method.contains("unwatch") {
+			Some(Subscription::Unsubscribe)
+		} else if method.contains("subscribe") || method.contains("watch") {
+			Some(Subscription::Subscribe)
+		} else {
+			None
+		}
+	}
+}
+
+struct Bucket {
+	tokens: f64,
+	updated: Instant,
+}
+
+impl Bucket {
+	fn new(limit: &RateLimit, now: Instant) -> Self {
+		Bucket { tokens: limit.burst as f64, updated: now }
+	}
+
+	fn take(&mut self, limit: &RateLimit, now: Instant) -> bool {
+		let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+		self.tokens = (self.tokens + elapsed * limit.per_second).min(limit.burst as f64);
+		self.updated = now;
+		if self.tokens >= 1.0 {
+			self.tokens -= 1.0;
+			true
+		} else {
+			false
+		}
+	}
+
+	/// Whether the bucket has been refilled, so that it is no different from a new one.
+	fn is_full(&self, limit: &RateLimit, now: Instant) -> bool {
+		let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+		self.tokens + elapsed * limit.per_second >= limit.burst as f64
+	}
+}
+
+#[derive(Default)]
+struct Buckets {
+	calls: Option<Bucket>,
+	methods: HashMap<String, Bucket>,
+}
+
+impl Buckets {
+	fn is_full(&self, limits: &RpcLimits, now: Instant) -> bool {
+		let calls_full = match (&self.calls, &limits.per_client) {
+			(Some(bucket), Some(limit)) => bucket.is_full(limit, now),
+			_ => true,
+		};
+		calls_full && self.methods.iter().all(|(method, bucket)| {
+			limits.per_method.get(method).map_or(true, |limit| bucket.is_full(limit, now))
+		})
+	}
+}
+
+#[derive(Default)]
+struct Connection {
+	/// Subscriptions open over the connection.
+	subscriptions: HashSet<SubscriptionId>,
+	/// Subscriptions requested over the connection whose id is not known yet.
+	pending: usize,
+}
+
+#[derive(Default)]
+struct State {
+	clients: HashMap<Client, Buckets>,
+	connections: HashMap<usize, Connection>,
+	calls_since_prune: usize,
+}
+
+/// Applies [`RpcLimits`] to the calls of clients.
+#[derive(Clone)]
+pub(crate) struct Limiter {
+	limits: Arc<RpcLimits>,
+	state: Arc<Mutex<State>>,
+}
+
+impl Limiter {
+	pub fn new(limits: RpcLimits) -> Self {
+		Limiter { limits: Arc::new(limits), state: Default::default() }
+	}
+
+	pub fn limits(&self) -> &RpcLimits {
+		&self.limits
+	}
+
+	/// The client calls are accounted to, given the address it tells and the connection they
+	/// are made over.
+	pub fn client(&self, address: Option<IpAddr>, connection: Option<usize>) -> Client {
+		match (address, connection) {
+			(Some(address), _) if self.limits.trust_proxy_headers => Client::Address(address),
+			(_, Some(connection)) => Client::Connection(connection),
+			_ => Client::Shared,
+		}
+	}
+
+	/// Start tracking the subscriptions of `connection`, returning whether they were not tracked
+	/// yet.
+	pub fn track(&self, connection: usize) -> bool {
+		let mut state = self.state.lock();
+		if state.connections.contains_key(&connection) {
+			return false;
+		}
+		state.connections.insert(connection, Connection::default());
+		true
+	}
+
+	/// Stop tracking a closed connection.
+	pub fn forget(&self, connection: usize) {
+		let mut state = self.state.lock();
+		state.connections.remove(&connection);
+		state.clients.remove(&Client::Connection(connection));
+	}
+
+	/// Account a call of `client` over `connection` to `method`, or tell why it is refused.
+	pub fn check_call(
+		&self,
+		client: Client,
+		connection: Option<usize>,
+		method: &str,
+		now: Instant,
+	) -> Result<(), Rejection> {
+		let limits = &*self.limits;
+		if !limits.is_allowed(method) {
+			return Err(Rejection::Denied);
+		}
+
+		let mut state = self.state.lock();
+		state.calls_since_prune += 1;
+		if state.calls_since_prune >= PRUNE_INTERVAL {
+			state.calls_since_prune = 0;
+			state.clients.retain(|_, buckets| !buckets.is_full(limits, now));
+		}
+
+		let buckets = state.clients.entry(client).or_default();
+		if let Some(limit) = &limits.per_client {
+			let bucket = buckets.calls.get_or_insert_with(|| Bucket::new(limit, now));
+			if !bucket.take(limit, now) {
+				return Err(Rejection::RateLimited);
+			}
+		}
+		if let Some(limit) = limits.per_method.get(method) {
+			let bucket = buckets.methods.entry(method.to_owned())
+				.or_insert_with(|| Bucket::new(limit, now));
+			if !bucket.take(limit, now) {
+				return Err(Rejection::RateLimited);
+			}
+		}
+
+		let connection = match (Subscription::of(method), connection) {
+			(Some(Subscription::Subscribe), Some(connection)) => connection,
+			_ => return Ok(()),
+		};
+		let connection = state.connections.entry(connection).or_default();
+		let open = connection.subscriptions.len() + connection.pending;
+		if limits.max_subscriptions.map_or(false, |max| open >= max) {
+			return Err(Rejection::TooManySubscriptions);
+		}
+		connection.pending += 1;
+		Ok(())
+	}
+
+	/// A subscription over `connection` accounted by `check_call` was given `id`, or failed to
+	/// open if `None`.
+	pub fn subscription_opened(&self, connection: usize, id: Option<SubscriptionId>) {
+		if let Some(connection) = self.state.lock().connections.get_mut(&connection) {
+			connection.pending = connection.pending.saturating_sub(1);
+			connection.subscriptions.extend(id);
+		}
+	}
+
+	/// The client unsubscribed from the subscription `id` over `connection`.
+	pub fn subscription_closed(&self, connection: usize, id: &SubscriptionId) {
+		if let Some(connection) = self.state.lock().connections.get_mut(&connection) {
+			connection.subscriptions.remove(id);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::time::Duration;
+
+	fn limits(json: &str) -> RpcLimits {
+		serde_json::from_str(json).unwrap()
+	}
+
+	#[test]
+	fn allow_and_deny_lists_are_applied() {
+		let limits = limits(r#"{
+			"allow": ["state_*", "system_name"],
+			"deny": ["state_getKeysPaged"]
+		}"#);
+
+		assert!(limits.is_allowed("state_getStorage"));
+		assert!(limits.is_allowed("system_name"));
+		assert!(!limits.is_allowed("system_nodeRoles"));
+		assert!(!limits.is_allowed("state_getKeysPaged"));
+		assert!(RpcLimits::default().is_allowed("state_getKeysPaged"));
+	}
+
+	#[test]
+	fn calls_are_rate_limited_per_client_and_method() {
+		let limiter = Limiter::new(limits(r#"{
+			"perClient": { "burst": 3, "perSecond": 1 },
+			"perMethod": { "state_getKeysPaged": { "burst": 1, "perSecond": 0.5 } }
+		}"#));
+		let (first, second) = (Client::Connection(1), Client::Connection(2));
+		let now = Instant::now();
+
+		assert_eq!(limiter.check_call(first, Some(1), "state_getKeysPaged", now), Ok(()));
+		assert_eq!(
+			limiter.check_call(first, Some(1), "state_getKeysPaged", now),
+			Err(Rejection::RateLimited),
+		);
+		assert_eq!(limiter.check_call(first, Some(1), "system_name", now), Ok(()));
+		assert_eq!(limiter.check_call(first, Some(1), "system_name", now), Err(Rejection::RateLimited));
+		assert_eq!(limiter.check_call(second, Some(2), "state_getKeysPaged", now), Ok(()));
+
+		// one call a second for the client, but one every two seconds for the method.
+		let later = now + Duration::from_secs(1);
+		assert_eq!(
+			limiter.check_call(first, Some(1), "state_getKeysPaged", later),
+			Err(Rejection::RateLimited),
+		);
+		let later = now + Duration::from_secs(3);
+		assert_eq!(limiter.check_call(first, Some(1), "state_getKeysPaged", later), Ok(()));
+	}
+
+	#[test]
+	fn clients_are_told_apart_by_forwarded_address_only_if_trusted() {
+		let address: IpAddr = "10.0.0.1".parse().unwrap();
+		let limiter = Limiter::new(limits(r#"{ "perClient": { "burst": 1, "perSecond": 0 } }"#));
+		assert_eq!(limiter.client(Some(address), Some(1)), Client::Connection(1));
+		assert_eq!(limiter.client(Some(address), None), Client::Shared);
+
+		let limiter = Limiter::new(limits(r#"{
+			"perClient": { "burst": 1, "perSecond": 0 },
+			"trustProxyHeaders": true
+		}"#));
+		assert_eq!(limiter.client(Some(address), Some(1)), Client::Address(address));
+		assert_eq!(limiter.client(None, Some(1)), Client::Connection(1));
+
+		// calls over all the connections of an address, or over HTTP, share their limits.
+		let now = Instant::now();
+		let client = limiter.client(Some(address), Some(1));
+		assert_eq!(limiter.check_call(client, Some(1), "system_name", now), Ok(()));
+		let client = limiter.client(Some(address), None);
+		assert_eq!(limiter.check_call(client, None, "system_name", now), Err(Rejection::RateLimited));
+	}
+
+	#[test]
+	fn forwarded_address_is_the_one_added_by_the_proxy() {
+		assert_eq!(
+			forwarded_address(Some(&b"1.1.1.1, 10.0.0.1"[..]), Some(&b"for=2.2.2.2"[..])),
+			Some("10.0.0.1".parse().unwrap()),
+		);
+		assert_eq!(
+			forwarded_address(None, Some(&b"for=1.1.1.1, for=\"[2001:db8::1]:4711\";proto=https"[..])),
+			Some("2001:db8::1".parse().unwrap()),
+		);
+		assert_eq!(forwarded_address(None, Some(&b"for=10.0.0.1:80"[..])), Some("10.0.0.1".parse().unwrap()));
+		assert_eq!(forwarded_address(Some(&b"unknown"[..]), None), None);
+		assert_eq!(forwarded_address(None, None), None);
+	}
+
+	#[test]
+	fn full_rate_limits_are_pruned() {
+		let limiter = Limiter::new(limits(r#"{ "perClient": { "burst": 2, "perSecond": 1 } }"#));
+		let now = Instant::now();
+		for connection in 0..PRUNE_INTERVAL - 1 {
+			let client = Client::Connection(connection);
+			assert_eq!(limiter.check_call(client, Some(connection), "system_name", now), Ok(()));
+		}
+		assert_eq!(limiter.state.lock().clients.len(), PRUNE_INTERVAL - 1);
+
+		let later = now + Duration::from_secs(1);
+		assert_eq!(limiter.check_call(Client::Shared, None, "system_name", later), Ok(()));
+		assert_eq!(limiter.state.lock().clients.len(), 1);
+	}
+
+	#[test]
+	fn subscriptions_are_limited_per_connection() {
+		let limiter = Limiter::new(limits(r#"{ "maxSubscriptions": 2 }"#));
+		let (client, now) = (Client::Connection(1), Instant::now());
+		let subscribe = |connection| limiter.check_call(
+			Client::Connection(connection),
+			Some(connection),
+			"chain_subscribeNewHeads",
+			now,
+		);
+		limiter.track(1);
+		limiter.track(2);
+
+		// subscriptions are counted as soon as they are requested.
+		assert_eq!(subscribe(1), Ok(()));
+		assert_eq!(
+			limiter.check_call(client, Some(1), "author_submitAndWatchExtrinsic", now),
+			Ok(()),
+		);
+		assert_eq!(subscribe(1), Err(Rejection::TooManySubscriptions));
+		assert_eq!(subscribe(2), Ok(()));
+
+		// one failed to open, the other one is open until unsubscribed.
+		limiter.subscription_opened(1, None);
+		limiter.subscription_opened(1, Some(SubscriptionId::Number(7)));
+		assert_eq!(subscribe(1), Ok(()));
+		assert_eq!(subscribe(1), Err(Rejection::TooManySubscriptions));
+
+		// unsubscribing from unknown ids or over other connections frees nothing.
+		limiter.subscription_closed(1, &SubscriptionId::Number(8));
+		limiter.subscription_closed(2, &SubscriptionId::Number(7));
+		assert_eq!(subscribe(1), Err(Rejection::TooManySubscriptions));
+
+		limiter.subscription_closed(1, &SubscriptionId::Number(7));
+		assert_eq!(subscribe(1), Ok(()));
+
+		// the subscriptions of a connection are forgotten with it.
+		limiter.forget(1);
+		assert_eq!(subscribe(1), Ok(()));
+		assert_eq!(Subscription::of("state_subscribeStorage"), Some(Subscription::Subscribe));
+		assert_eq!(Subscription::of("author_unwatchExtrinsic"), Some(Subscription::Unsubscribe));
+		assert_eq!(Subscription::of("state_getKeysPaged"), None);
+	}
+}
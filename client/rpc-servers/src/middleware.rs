@@ -18,21 +18,46 @@
 
 //! Middleware for RPC requests.
 
+use std::{net::IpAddr, time::Instant};
+
 use jsonrpc_core::{
-	Middleware as RequestMiddleware, Metadata,
+	Middleware as RequestMiddleware, Call, Output, Params, Version,
 	Request, Response, FutureResponse, FutureOutput
 };
 use prometheus_endpoint::{
 	Registry, CounterVec, PrometheusError,
 	Opts, register, U64
 };
+use pubsub::{PubSubMetadata, SubscriptionId};
+
+use futures::{future::{self, Either}, Future};
+
+use crate::limits::{Limiter, Rejection, RpcLimits, Subscription};
+
+/// Metadata of RPC calls telling the address of the client making them.
+pub trait ClientAddress {
+	/// The address of the client, if the transport tells it.
+	fn client_address(&self) -> Option<IpAddr>;
+
+	/// Set the address of the client.
+	fn set_client_address(&mut self, address: IpAddr);
+}
+
+impl ClientAddress for sc_rpc_api::Metadata {
+	fn client_address(&self) -> Option<IpAddr> {
+		sc_rpc_api::Metadata::client_address(self)
+	}
 
-use futures::{future::Either, Future};
+	fn set_client_address(&mut self, address: IpAddr) {
+		sc_rpc_api::Metadata::set_client_address(self, address)
+	}
+}
 
 /// Metrics for RPC middleware
 #[derive(Debug, Clone)]
 pub struct RpcMetrics {
 	rpc_calls: CounterVec<U64>,
+	rpc_calls_rejected: CounterVec<U64>,
 }
 
 impl RpcMetrics {
@@ -47,6 +72,13 @@ impl RpcMetrics {
 					),
 					&["protocol"]
 				).ok()?, r).ok()?,
+				rpc_calls_rejected: register(CounterVec::new(
+					Opts::new(
+						"rpc_calls_rejected_total",
+						"Number of rpc calls rejected by the rpc limits",
+					),
+					&["protocol", "reason"]
+				).ok()?, r).ok()?,
 			})
 		}).ok_or(PrometheusError::Msg("Cannot register metric".to_string()))
 	}
@@ -56,6 +88,7 @@ impl RpcMetrics {
 pub struct RpcMiddleware {
 	metrics: Option<RpcMetrics>,
 	transport_label: String,
+	limiter: Option<Limiter>,
 }
 
 impl RpcMiddleware {
@@ -65,11 +98,39 @@ impl RpcMiddleware {
 		RpcMiddleware {
 			metrics,
 			transport_label: String::from(transport_label),
+			limiter: None,
+		}
+	}
+
+	/// Apply `limits` to the calls handled by this middleware.
+	pub fn with_limits(mut self, limits: RpcLimits) -> Self {
+		self.limiter = Some(Limiter::new(limits));
+		self
+	}
+
+	fn reject(&self, rejection: Rejection) {
+		if let Some(ref metrics) = self.metrics {
+			metrics.rpc_calls_rejected
+				.with_label_values(&[self.transport_label.as_str(), rejection.label()])
+				.inc();
 		}
 	}
 }
 
-impl<M: Metadata> RequestMiddleware<M> for RpcMiddleware {
+/// The connection calls with `meta` are made over, if any, starting to track it if needed.
+///
+/// Connections are identified by the address of their session.
+fn connection<M: PubSubMetadata>(limiter: &Limiter, meta: &M) -> Option<usize> {
+	let session = meta.session()?;
+	let connection = &*session as *const _ as usize;
+	if limiter.track(connection) {
+		let limiter = limiter.clone();
+		session.on_drop(move || limiter.forget(connection));
+	}
+	Some(connection)
+}
+
+impl<M: PubSubMetadata + ClientAddress> RequestMiddleware<M> for RpcMiddleware {
 	type Future = FutureResponse;
 	type CallFuture = FutureOutput;
 
@@ -82,6 +143,87 @@ impl<M: Metadata> RequestMiddleware<M> for RpcMiddleware {
 			metrics.rpc_calls.with_label_values(&[self.transport_label.as_str()]).inc();
 		}
 
+		let max_request_size = self.limiter.as_ref().and_then(|l| l.limits().max_request_size);
+		if let Some(max) = max_request_size {
+			let size = serde_json::to_vec(&request).map(|r| r.len()).unwrap_or(usize::max_value());
+			if size > max {
+				self.reject(Rejection::RequestTooLarge);
+				let response = Response::from(Rejection::RequestTooLarge.error(), Some(Version::V2));
+				return Either::A(Box::new(future::ok(Some(response))));
+			}
+		}
+
 		Either::B(next(request, meta))
 	}
+
+	fn on_call<F, X>(&self, call: Call, meta: M, next: F) -> Either<FutureOutput, X>
+	where
+		F: Fn(Call, M) -> X + Send + Sync,
+		X: Future<Item = Option<Output>, Error = ()> + Send + 'static,
+	{
+		let limiter = match self.limiter {
+			Some(ref limiter) => limiter,
+			None => return Either::B(next(call, meta)),
+		};
+		let (method, params, id, jsonrpc) = match &call {
+			Call::MethodCall(call) => (&call.method, &call.params, Some(call.id.clone()), call.jsonrpc),
+			Call::Notification(notification) =>
+				(&notification.method, &notification.params, None, notification.jsonrpc),
+			Call::Invalid { .. } => return Either::B(next(call, meta)),
+		};
+		let subscription = Subscription::of(method);
+		// the subscription closed by an unsubscribe call, parsed like `jsonrpc_pubsub` does.
+		let unsubscribed = match (subscription, params) {
+			(Some(Subscription::Unsubscribe), Params::Array(params)) if params.len() == 1 =>
+				SubscriptionId::parse_value(&params[0]),
+			_ => None,
+		};
+
+		let connection = connection(limiter, &meta);
+		let client = limiter.client(meta.client_address(), connection);
+		if let Err(rejection) = limiter.check_call(client, connection, method, Instant::now()) {
+			self.reject(rejection);
+			// notifications get no response, not even an error.
+			let output = id.map(|id| Output::from(Err(rejection.error()), id, jsonrpc));
+			return Either::A(Box::new(future::ok(output)));
+		}
+
+		let limiter = limiter.clone();
+		let metrics = self.metrics.clone();
+		let transport_label = self.transport_label.clone();
+		Either::A(Box::new(next(call, meta).map(move |output| {
+			match (subscription, connection, &output) {
+				(Some(Subscription::Subscribe), Some(connection), Some(Output::Success(success))) =>
+					limiter.subscription_opened(connection, SubscriptionId::parse_value(&success.result)),
+				(Some(Subscription::Subscribe), Some(connection), _) =>
+					limiter.subscription_opened(connection, None),
+				// the subscription is closed now, whether the server still knew it or had ended it.
+				(Some(Subscription::Unsubscribe), Some(connection), Some(Output::Success(_))) =>
+					if let Some(id) = &unsubscribed {
+						limiter.subscription_closed(connection, id);
+					},
+				_ => {},
+			}
+			let output = output?;
+
+			let max = match limiter.limits().max_response_size {
+				Some(max) => max,
+				None => return Some(output),
+			};
+			let size = serde_json::to_vec(&output).map(|o| o.len()).unwrap_or(usize::max_value());
+			if size <= max {
+				return Some(output);
+			}
+			if let Some(metrics) = metrics {
+				metrics.rpc_calls_rejected
+					.with_label_values(&[transport_label.as_str(), Rejection::ResponseTooLarge.label()])
+					.inc();
+			}
+			Some(Output::from(
+				Err(Rejection::ResponseTooLarge.error()),
+				output.id().clone(),
+				output.jsonrpc(),
+			))
+		})))
+	}
 }
@@ -24,7 +24,7 @@ use log::warn;
 use jsonrpc_pubsub::{typed::Subscriber, SubscriptionId, manager::SubscriptionManager};
 use rpc::{Result as RpcResult, futures::{stream, Future, Sink, Stream, future::result}};
 
-use sc_rpc_api::state::ReadProof;
+use sc_rpc_api::{DenyUnsafe, state::ReadProof};
 use sc_client_api::backend::Backend;
 use sp_blockchain::{Result as ClientResult, Error as ClientError, HeaderMetadata, CachedHeaderMetadata, HeaderBackend};
 use sc_client_api::BlockchainEvents;
@@ -41,7 +41,9 @@ use sp_api::{Metadata, ProvideRuntimeApi, CallApiAt};
 
 use super::{StateBackend, ChildStateBackend, error::{FutureResult, Error, Result}, client_err};
 use std::marker::PhantomData;
-use sc_client_api::{CallExecutor, StorageProvider, ExecutorProvider, ProofProvider};
+use sc_client_api::{CallExecutor, StorageProvider, ExecutorProvider, ProofProvider, BlockBackend};
+use sc_tracing::block::BlockExecutor;
+use sp_rpc::tracing::BlockTrace;
 
 /// Ranges to query in state_queryStorage.
 struct QueryStorageRange<Block: BlockT> {
@@ -61,24 +63,48 @@ struct QueryStorageRange<Block: BlockT> {
 pub struct FullState<BE, Block: BlockT, Client> {
 	client: Arc<Client>,
 	subscriptions: SubscriptionManager,
+	deny_unsafe: DenyUnsafe,
 	_phantom: PhantomData<(BE, Block)>
 }
 
 impl<BE, Block: BlockT, Client> FullState<BE, Block, Client>
 	where
 		BE: Backend<Block>,
-		Client: StorageProvider<Block, BE> + HeaderBackend<Block>
+		Client: StorageProvider<Block, BE> + HeaderBackend<Block> + BlockBackend<Block>
 			+ HeaderMetadata<Block, Error = sp_blockchain::Error>,
 		Block: BlockT + 'static,
 {
 	/// Create new state API backend for full nodes.
-	pub fn new(client: Arc<Client>, subscriptions: SubscriptionManager) -> Self {
-		Self { client, subscriptions, _phantom: PhantomData }
+	///
+	/// Pruned states that the backend reconstructs from snapshots are only served if
+	/// unsafe calls are allowed.
+	pub fn new(client: Arc<Client>, subscriptions: SubscriptionManager, deny_unsafe: DenyUnsafe) -> Self {
+		Self { client, subscriptions, deny_unsafe, _phantom: PhantomData }
 	}
 
 	/// Returns given block hash or best block hash if None is passed.
 	fn block_or_best(&self, hash: Option<Block::Hash>) -> ClientResult<Block::Hash> {
-		Ok(hash.unwrap_or_else(|| self.client.info().best_hash))
+		match hash {
+			Some(hash) => {
+				self.ensure_state_not_pruned(hash)?;
+				Ok(hash)
+			},
+			None => Ok(self.client.info().best_hash),
+		}
+	}
+
+	/// Reconstructing a pruned state replays the changes of many blocks, so it is unsafe.
+	fn ensure_state_not_pruned(&self, hash: Block::Hash) -> ClientResult<()> {
+		if self.deny_unsafe.check_if_safe().is_ok() {
+			return Ok(())
+		}
+		match self.client.block_status(&BlockId::Hash(hash))? {
+			sp_consensus::BlockStatus::InChainPruned => Err(ClientError::UnknownBlock(format!(
+				"State already discarded for {:?}, it is only reconstructed for unsafe RPC calls",
+				hash,
+			))),
+			_ => Ok(()),
+		}
 	}
 
 	/// Splits the `query_storage` block range into 'filtered' and 'unfiltered' subranges.
@@ -92,6 +118,7 @@ impl<BE, Block: BlockT, Client> FullState<BE, Block, Client>
 		let to = self.block_or_best(to).map_err(|e| invalid_block::<Block>(from, to, e.to_string()))?;
 
 		let invalid_block_err = |e: ClientError| invalid_block::<Block>(from, Some(to), e.to_string());
+		self.ensure_state_not_pruned(from).map_err(invalid_block_err)?;
 		let from_meta = self.client.header_metadata(from).map_err(invalid_block_err)?;
 		let to_meta = self.client.header_metadata(to).map_err(invalid_block_err)?;
 
@@ -222,7 +249,7 @@ impl<BE, Block, Client> StateBackend<Block, Client> for FullState<BE, Block, Cli
 	Client: ExecutorProvider<Block> + StorageProvider<Block, BE> + ProofProvider<Block> + HeaderBackend<Block>
 		+ HeaderMetadata<Block, Error = sp_blockchain::Error> + BlockchainEvents<Block>
 		+ CallApiAt<Block, Error = sp_blockchain::Error> + ProvideRuntimeApi<Block>
-		+ Send + Sync + 'static,
+		+ BlockBackend<Block> + Send + Sync + 'static,
 	Client::Api: Metadata<Block, Error = sp_blockchain::Error>,
 {
 	fn call(
@@ -401,6 +428,19 @@ impl<BE, Block, Client> StateBackend<Block, Client> for FullState<BE, Block, Cli
 		))
 	}
 
+	fn trace_block(
+		&self,
+		block: Block::Hash,
+		targets: Option<String>,
+		storage_keys: Option<String>,
+	) -> FutureResult<BlockTrace> {
+		Box::new(result(
+			BlockExecutor::new(self.client.clone(), block, targets, storage_keys)
+				.trace_block()
+				.map_err(|e| Error::Client(Box::new(e))),
+		))
+	}
+
 	fn subscribe_runtime_version(
 		&self,
 		_meta: crate::Metadata,
@@ -527,7 +567,7 @@ impl<BE, Block, Client> ChildStateBackend<Block, Client> for FullState<BE, Block
 	Client: ExecutorProvider<Block> + StorageProvider<Block, BE> + HeaderBackend<Block>
 		+ HeaderMetadata<Block, Error = sp_blockchain::Error> + BlockchainEvents<Block>
 		+ CallApiAt<Block, Error = sp_blockchain::Error> + ProvideRuntimeApi<Block>
-		+ Send + Sync + 'static,
+		+ BlockBackend<Block> + Send + Sync + 'static,
 	Client::Api: Metadata<Block, Error = sp_blockchain::Error>,
 {
 	fn storage_keys(
@@ -51,6 +51,7 @@ use sp_core::{
 	Bytes, OpaqueMetadata,
 	storage::{StorageKey, PrefixedStorageKey, StorageData, StorageChangeSet},
 };
+use sp_rpc::tracing::BlockTrace;
 use sp_version::RuntimeVersion;
 use sp_runtime::{generic::BlockId, traits::{Block as BlockT, HashFor}};
 
@@ -295,6 +296,15 @@ impl<Block, F, Client> StateBackend<Block, Client> for LightState<Block, F, Clie
 		Box::new(result(Err(client_err(ClientError::NotAvailableOnLightClient))))
 	}
 
+	fn trace_block(
+		&self,
+		_block: Block::Hash,
+		_targets: Option<String>,
+		_storage_keys: Option<String>,
+	) -> FutureResult<BlockTrace> {
+		Box::new(result(Err(client_err(ClientError::NotAvailableOnLightClient))))
+	}
+
 	fn subscribe_storage(
 		&self,
 		_meta: crate::Metadata,
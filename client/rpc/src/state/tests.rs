@@ -58,7 +58,7 @@ fn should_return_storage() {
 		.add_extra_storage(b":map:acc2".to_vec(), vec![1, 2, 3])
 		.build();
 	let genesis_hash = client.genesis_hash();
-	let (client, child) = new_full(Arc::new(client), SubscriptionManager::new(Arc::new(TaskExecutor)));
+	let (client, child) = new_full(Arc::new(client), SubscriptionManager::new(Arc::new(TaskExecutor)), DenyUnsafe::No);
 	let key = StorageKey(KEY.to_vec());
 
 	assert_eq!(
@@ -96,7 +96,7 @@ fn should_return_child_storage() {
 		.add_child_storage(&child_info, "key", vec![42_u8])
 		.build());
 	let genesis_hash = client.genesis_hash();
-	let (_client, child) = new_full(client, SubscriptionManager::new(Arc::new(TaskExecutor)));
+	let (_client, child) = new_full(client, SubscriptionManager::new(Arc::new(TaskExecutor)), DenyUnsafe::No);
 	let child_key = prefixed_storage_key();
 	let key = StorageKey(b"key".to_vec());
 
@@ -131,7 +131,7 @@ fn should_return_child_storage() {
 fn should_call_contract() {
 	let client = Arc::new(substrate_test_runtime_client::new());
 	let genesis_hash = client.genesis_hash();
-	let (client, _child) = new_full(client, SubscriptionManager::new(Arc::new(TaskExecutor)));
+	let (client, _child) = new_full(client, SubscriptionManager::new(Arc::new(TaskExecutor)), DenyUnsafe::No);
 
 	assert_matches!(
 		client.call("balanceOf".into(), Bytes(vec![1,2,3]), Some(genesis_hash).into()).wait(),
@@ -145,7 +145,7 @@ fn should_notify_about_storage_changes() {
 
 	{
 		let mut client = Arc::new(substrate_test_runtime_client::new());
-		let (api, _child) = new_full(client.clone(), SubscriptionManager::new(Arc::new(TaskExecutor)));
+		let (api, _child) = new_full(client.clone(), SubscriptionManager::new(Arc::new(TaskExecutor)), DenyUnsafe::No);
 
 		api.subscribe_storage(Default::default(), subscriber, None.into());
 
@@ -179,7 +179,7 @@ fn should_send_initial_storage_changes_and_notifications() {
 
 	{
 		let mut client = Arc::new(substrate_test_runtime_client::new());
-		let (api, _child) = new_full(client.clone(), SubscriptionManager::new(Arc::new(TaskExecutor)));
+		let (api, _child) = new_full(client.clone(), SubscriptionManager::new(Arc::new(TaskExecutor)), DenyUnsafe::No);
 
 		let alice_balance_key = blake2_256(&runtime::system::balance_of_key(AccountKeyring::Alice.into()));
 
@@ -217,7 +217,7 @@ fn should_send_initial_storage_changes_and_notifications() {
 #[test]
 fn should_query_storage() {
 	fn run_tests(mut client: Arc<TestClient>, has_changes_trie_config: bool) {
-		let (api, _child) = new_full(client.clone(), SubscriptionManager::new(Arc::new(TaskExecutor)));
+		let (api, _child) = new_full(client.clone(), SubscriptionManager::new(Arc::new(TaskExecutor)), DenyUnsafe::No);
 
 		let mut add_block = |nonce| {
 			let mut builder = client.new_block(Default::default()).unwrap();
@@ -434,7 +434,7 @@ fn should_split_ranges() {
 #[test]
 fn should_return_runtime_version() {
 	let client = Arc::new(substrate_test_runtime_client::new());
-	let (api, _child) = new_full(client.clone(), SubscriptionManager::new(Arc::new(TaskExecutor)));
+	let (api, _child) = new_full(client.clone(), SubscriptionManager::new(Arc::new(TaskExecutor)), DenyUnsafe::No);
 
 	let result = "{\"specName\":\"test\",\"implName\":\"parity-test\",\"authoringVersion\":1,\
 		\"specVersion\":2,\"implVersion\":2,\"apis\":[[\"0xdf6acb689907609b\",3],\
@@ -457,7 +457,7 @@ fn should_notify_on_runtime_version_initially() {
 
 	{
 		let client = Arc::new(substrate_test_runtime_client::new());
-		let (api, _child) = new_full(client.clone(), SubscriptionManager::new(Arc::new(TaskExecutor)));
+		let (api, _child) = new_full(client.clone(), SubscriptionManager::new(Arc::new(TaskExecutor)), DenyUnsafe::No);
 
 		api.subscribe_runtime_version(Default::default(), subscriber);
 
@@ -483,3 +483,54 @@ fn should_deserialize_storage_key() {
 
 	assert_eq!(k.0.len(), 32);
 }
+
+#[test]
+fn should_trace_block() {
+	let mut client = Arc::new(substrate_test_runtime_client::new());
+	let mut builder = client.new_block(Default::default()).unwrap();
+	builder.push_transfer(runtime::Transfer {
+		from: AccountKeyring::Alice.into(),
+		to: AccountKeyring::Ferdie.into(),
+		amount: 42,
+		nonce: 0,
+	}).unwrap();
+	let block = builder.build().unwrap().block;
+	let hash = block.header.hash();
+	client.import(BlockOrigin::Own, block).unwrap();
+	let (api, _child) = new_full(
+		client.clone(),
+		SubscriptionManager::new(Arc::new(TaskExecutor)),
+		DenyUnsafe::No,
+	);
+
+	let trace = api.trace_block(hash, Some("state".into()), None).wait().unwrap();
+	assert_eq!(trace.block_hash, format!("{:?}", hash));
+	assert_eq!(trace.parent_hash, format!("{:?}", client.genesis_hash()));
+	assert!(!trace.events.is_empty());
+	assert!(trace.events.iter().all(|event| event.target == "state"));
+
+	let key = trace.events[0].data.string_values.get("key").unwrap();
+	let prefix = key[..key.len().min(4)].to_owned();
+	let filtered = api.trace_block(hash, Some("state".into()), Some(format!("0x{}", prefix)))
+		.wait()
+		.unwrap();
+	assert_eq!(filtered.storage_keys, prefix);
+	assert!(!filtered.events.is_empty());
+	assert!(filtered.events.iter().all(|event| event.data.string_values["key"].starts_with(&prefix)));
+}
+
+#[test]
+fn should_deny_unsafe_trace_block() {
+	let client = Arc::new(substrate_test_runtime_client::new());
+	let genesis_hash = client.genesis_hash();
+	let (api, _child) = new_full(
+		client,
+		SubscriptionManager::new(Arc::new(TaskExecutor)),
+		DenyUnsafe::Yes,
+	);
+
+	assert_matches!(
+		api.trace_block(genesis_hash, None, None).wait(),
+		Err(Error::UnsafeRpcCalled(_))
+	);
+}
@@ -29,7 +29,9 @@ use codec::{Encode, Decode};
 use hash_db::Prefix;
 use sp_core::{
 	convert_hash,
-	storage::{well_known_keys, ChildInfo, PrefixedStorageKey, StorageData, StorageKey},
+	storage::{
+		well_known_keys, ChildInfo, PrefixedStorageKey, Storage, StorageChild, StorageData, StorageKey,
+	},
 	ChangesTrieConfiguration, ExecutionContext, NativeOrEncoded,
 };
 use sc_telemetry::{telemetry, SUBSTRATE_INFO};
@@ -43,13 +45,14 @@ use sp_runtime::{
 };
 use sp_state_machine::{
 	DBValue, Backend as StateBackend, ChangesTrieAnchorBlockId,
-	prove_read, prove_child_read, ChangesTrieRootsStorage, ChangesTrieStorage,
+	prove_read, prove_child_read, prove_range_read_with_size, read_range_proof_check,
+	ChangesTrieRootsStorage, ChangesTrieStorage,
 	ChangesTrieConfigurationRange, key_changes, key_changes_proof,
 };
 use sc_executor::RuntimeVersion;
 use sp_consensus::{
 	Error as ConsensusError, BlockStatus, BlockImportParams, BlockCheckParams,
-	ImportResult, BlockOrigin, ForkChoiceStrategy, RecordProof,
+	ImportResult, BlockOrigin, ForkChoiceStrategy, RecordProof, ImportedState,
 };
 use sp_blockchain::{
 	self as blockchain,
@@ -613,9 +616,13 @@ impl<B, E, Block, RA> Client<B, E, Block, RA> where
 			fork_choice,
 			intermediates,
 			import_existing,
+			state,
 			..
 		} = import_block;
 
+		// A block with imported state becomes the finalized base of the chain.
+		let finalized = finalized || state.is_some();
+
 		assert!(justification.is_some() && finalized || justification.is_none());
 
 		if !intermediates.is_empty() {
@@ -647,6 +654,7 @@ impl<B, E, Block, RA> Client<B, E, Block, RA> where
 			justification,
 			body,
 			storage_changes,
+			state,
 			new_cache,
 			finalized,
 			auxiliary,
@@ -683,6 +691,7 @@ impl<B, E, Block, RA> Client<B, E, Block, RA> where
 		justification: Option<Justification>,
 		body: Option<Vec<Block::Extrinsic>>,
 		storage_changes: Option<sp_api::StorageChanges<backend::StateBackendFor<B, Block>, Block>>,
+		state: Option<ImportedState>,
 		new_cache: HashMap<CacheKeyId, Vec<u8>>,
 		finalized: bool,
 		aux: Vec<(Vec<u8>, Option<Vec<u8>>)>,
@@ -719,8 +728,27 @@ impl<B, E, Block, RA> Client<B, E, Block, RA> where
 			BlockOrigin::Genesis | BlockOrigin::NetworkInitialSync | BlockOrigin::File => false,
 		};
 
-		let storage_changes = match storage_changes {
-			Some(storage_changes) => {
+		let is_new_base = state.is_some();
+		let storage_changes = match (state, storage_changes) {
+			(Some(state), _) => {
+				let storage = Storage {
+					top: state.top.into_iter()
+						.filter(|(key, _)| !well_known_keys::is_child_storage_key(key))
+						.collect(),
+					children_default: state.children.into_iter().map(|(storage_key, data)| {
+						let child_info = ChildInfo::new_default(&storage_key);
+						(storage_key, StorageChild { data: data.into_iter().collect(), child_info })
+					}).collect(),
+				};
+				let state_root = operation.op.reset_storage(storage)?;
+				if state_root != *import_headers.post().state_root() {
+					return Err(Error::InvalidStateRoot);
+				}
+				operation.op.update_cache(new_cache);
+
+				None
+			},
+			(None, Some(storage_changes)) => {
 				self.backend.begin_state_operation(&mut operation.op, BlockId::Hash(parent_hash))?;
 
 				// ensure parent block is finalized to maintain invariant that
@@ -758,7 +786,7 @@ impl<B, E, Block, RA> Client<B, E, Block, RA> where
 
 				Some((main_sc, child_sc))
 			},
-			None => None,
+			(None, None) => None,
 		};
 
 		let is_new_best = finalized || match fork_choice {
@@ -774,7 +802,7 @@ impl<B, E, Block, RA> Client<B, E, Block, RA> where
 			NewBlockState::Normal
 		};
 
-		let tree_route = if is_new_best && info.best_hash != parent_hash {
+		let tree_route = if is_new_best && info.best_hash != parent_hash && !is_new_base {
 			let route_from_best = sp_blockchain::tree_route(
 				self.backend.blockchain(),
 				info.best_hash,
@@ -835,6 +863,11 @@ impl<B, E, Block, RA> Client<B, E, Block, RA> where
 			<Self as ProvideRuntimeApi<Block>>::Api: CoreApi<Block, Error = Error> +
 				ApiExt<Block, StateBackend = B::State>,
 	{
+		// A block with imported state does not need its parent and is not executed.
+		if import_block.state.is_some() {
+			return Ok(None)
+		}
+
 		let parent_hash = import_block.header.parent_hash();
 		let at = BlockId::Hash(*parent_hash);
 		let enact_state = match self.block_status(&at)? {
@@ -1256,6 +1289,29 @@ impl<B, E, Block, RA> ProofProvider<Block> for Client<B, E, Block, RA> where
 			cht::size(),
 		)
 	}
+
+	fn read_proof_collection(
+		&self,
+		id: &BlockId<Block>,
+		child_info: Option<&ChildInfo>,
+		start_key: &[u8],
+		size_limit: usize,
+	) -> sp_blockchain::Result<(StorageProof, u32)> {
+		self.state_at(id)
+			.and_then(|state| prove_range_read_with_size(state, child_info, start_key, size_limit)
+				.map_err(Into::into))
+	}
+
+	fn verify_range_proof(
+		&self,
+		root: Block::Hash,
+		proof: StorageProof,
+		child_info: Option<&ChildInfo>,
+		start_key: &[u8],
+	) -> sp_blockchain::Result<(Vec<(Vec<u8>, Vec<u8>)>, bool)> {
+		read_range_proof_check::<HashFor<Block>>(root, proof, child_info, start_key)
+			.map_err(Into::into)
+	}
 }
 
 
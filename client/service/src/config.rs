@@ -22,12 +22,17 @@ pub use sc_client_db::{Database, PruningMode, DatabaseSettingsSrc as DatabaseCon
 pub use sc_network::Multiaddr;
 pub use sc_network::config::{ExtTransport, MultiaddrWithPeerId, NetworkConfiguration, Role, NodeKeyConfig};
 pub use sc_executor::WasmExecutionMethod;
+pub use sc_rpc_server::RpcLimits;
 use sc_client_api::execution_extensions::ExecutionStrategies;
 
 use std::{io, future::Future, path::{PathBuf, Path}, pin::Pin, net::SocketAddr, sync::Arc};
-pub use sc_transaction_pool::txpool::Options as TransactionPoolOptions;
+pub use sc_transaction_pool::txpool::{
+	Options as TransactionPoolOptions, JournalOptions as TransactionPoolJournalOptions,
+	SenderPolicy as TransactionPoolSenderPolicy,
+};
 use sc_chain_spec::ChainSpec;
 use sp_core::crypto::SecretString;
+use sc_keystore::SignerAddress;
 pub use sc_telemetry::TelemetryEndpoints;
 use prometheus_endpoint::Registry;
 #[cfg(not(target_os = "unknown"))]
@@ -76,6 +81,12 @@ pub struct Configuration {
 	pub rpc_cors: Option<Vec<String>>,
 	/// RPC methods to expose (by default only a safe subset or all of them).
 	pub rpc_methods: RpcMethods,
+	/// Limits to the calls made to the HTTP & WS servers. `None` if unlimited.
+	pub rpc_limits: Option<RpcLimits>,
+	/// Maximum payload of RPC requests and responses, in MiB. `None` if default.
+	pub rpc_max_payload: Option<usize>,
+	/// Maximum number of subscriptions open at once over a WS RPC connection. `None` if default.
+	pub rpc_max_subscriptions_per_connection: Option<usize>,
 	/// Prometheus endpoint configuration. `None` if disabled.
 	pub prometheus_config: Option<PrometheusConfig>,
 	/// Telemetry service URL. `None` if disabled.
@@ -134,6 +145,8 @@ pub enum KeystoreConfig {
 	},
 	/// In-memory keystore. Recommended for in-browser nodes.
 	InMemory,
+	/// Keys held by a remote signer, for validators keeping their keys out of the node.
+	Remote(SignerAddress),
 }
 
 impl KeystoreConfig {
@@ -141,7 +154,7 @@ impl KeystoreConfig {
 	pub fn path(&self) -> Option<&Path> {
 		match self {
 			Self::Path { path, .. } => Some(path),
-			Self::InMemory => None,
+			Self::InMemory | Self::Remote(_) => None,
 		}
 	}
 }
@@ -66,7 +66,10 @@ pub use sc_chain_spec::{
 	NoExtension, ChainType,
 };
 pub use sp_transaction_pool::{TransactionPool, InPoolTransaction, error::IntoPoolError};
-pub use sc_transaction_pool::txpool::Options as TransactionPoolOptions;
+pub use sc_transaction_pool::txpool::{
+	Options as TransactionPoolOptions, JournalOptions as TransactionPoolJournalOptions,
+	SenderPolicy as TransactionPoolSenderPolicy,
+};
 pub use sc_rpc::Metadata as RpcMetadata;
 pub use sc_executor::NativeExecutionDispatch;
 #[doc(hidden)]
@@ -418,6 +421,15 @@ fn start_rpc_servers<
 		}
 	}
 
+	// limits apply to the servers that can be reached from other machines.
+	let rpc_middleware = |transport_label: &str| {
+		let mut limits = config.rpc_limits.clone().unwrap_or_default();
+		limits.max_subscriptions = config.rpc_max_subscriptions_per_connection
+			.or(limits.max_subscriptions)
+			.or(Some(sc_rpc_server::RPC_MAX_SUBSCRIPTIONS_PER_CONNECTION_DEFAULT));
+		sc_rpc_server::RpcMiddleware::new(rpc_metrics.cloned(), transport_label).with_limits(limits)
+	};
+
 	Ok(Box::new((
 		config.rpc_ipc.as_ref().map(|path| sc_rpc_server::start_ipc(
 			&*path, gen_handler(
@@ -432,8 +444,9 @@ fn start_rpc_servers<
 				config.rpc_cors.as_ref(),
 				gen_handler(
 					deny_unsafe(&address, &config.rpc_methods),
-					sc_rpc_server::RpcMiddleware::new(rpc_metrics.cloned(), "http")
+					rpc_middleware("http"),
 				),
+				config.rpc_max_payload,
 			),
 		)?.map(|s| waiting::HttpServer(Some(s))),
 		maybe_start_server(
@@ -444,8 +457,9 @@ fn start_rpc_servers<
 				config.rpc_cors.as_ref(),
 				gen_handler(
 					deny_unsafe(&address, &config.rpc_methods),
-					sc_rpc_server::RpcMiddleware::new(rpc_metrics.cloned(), "ws")
+					rpc_middleware("ws"),
 				),
+				config.rpc_max_payload,
 			),
 		)?.map(|s| waiting::WsServer(Some(s))),
 	)))
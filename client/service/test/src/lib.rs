@@ -253,6 +253,9 @@ fn node_config<G: RuntimeGenesis + 'static, E: ChainSpecExtension + Clone + 'sta
 		rpc_ipc: None,
 		rpc_ws: None,
 		rpc_ws_max_connections: None,
+		rpc_limits: None,
+		rpc_max_payload: None,
+		rpc_max_subscriptions_per_connection: None,
 		rpc_cors: None,
 		rpc_methods: Default::default(),
 		prometheus_config: None,
@@ -0,0 +1,495 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Slashing protection for block authorship and finality voting.
+//!
+//! A validator restored from a backup, or running twice with the same keys, can sign two
+//! conflicting messages and be slashed for equivocating. [`SlashingProtection`] keeps a record
+//! of what the node signed in the auxiliary storage of the client and is checked before signing:
+//!
+//! - for BABE, the last slot authored by each authority in each epoch, with the hash of the
+//!   authored header. Blocks are only authored in later slots, or again with the same header.
+//! - for GRANDPA, the prevote and precommit of each authority in each round and set. A vote is
+//!   only cast again for the same target.
+//!
+//! Only the latest epochs and rounds are kept, and signing for anything older is refused.
+//!
+//! The record can be exported to and imported from an [`Interchange`], to move a validator
+//! safely between machines.
+
+#![warn(missing_docs)]
+
+use std::{collections::BTreeMap, sync::Arc};
+use codec::{Encode, Decode};
+use parking_lot::Mutex;
+use sc_client_api::backend::AuxStore;
+use serde::{Serialize, Deserialize};
+
+/// Aux storage key of the BABE signing history.
+const BABE_HISTORY_KEY: &[u8] = b"slashing_protection_babe";
+/// Aux storage key of the GRANDPA signing history.
+const GRANDPA_HISTORY_KEY: &[u8] = b"slashing_protection_grandpa";
+
+/// Number of epochs of BABE history kept per authority.
+const BABE_EPOCHS_KEPT: u64 = 8;
+/// Number of rounds of GRANDPA history kept per authority.
+const GRANDPA_ROUNDS_KEPT: u64 = 256;
+
+/// Version of the interchange format.
+pub const INTERCHANGE_FORMAT_VERSION: u32 = 1;
+
+/// Slashing protection error.
+#[derive(Debug, derive_more::Display)]
+pub enum Error {
+	/// Signing would conflict with a message signed before.
+	#[display(fmt = "Refusing to sign: {}", _0)]
+	Conflict(String),
+	/// The signing history cannot be accessed.
+	#[display(fmt = "Signing history unavailable: {}", _0)]
+	Client(sp_blockchain::Error),
+	/// The signing history is corrupted.
+	#[display(fmt = "Signing history corrupted: {}", _0)]
+	Codec(codec::Error),
+	/// The interchange is for another chain.
+	#[display(fmt = "Interchange is for another chain")]
+	GenesisMismatch,
+	/// The interchange format version is not supported.
+	#[display(fmt = "Unsupported interchange format version {}", _0)]
+	UnsupportedVersion(u32),
+}
+
+impl std::error::Error for Error {}
+
+/// Slashing protection result.
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Stage of a GRANDPA vote.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Encode, Decode, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum VoteStage {
+	/// A prevote.
+	Prevote,
+	/// A precommit.
+	Precommit,
+}
+
+/// A block authored in a slot.
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+struct AuthoredBlock {
+	slot: u64,
+	header_hash: Vec<u8>,
+}
+
+/// The target of a vote.
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+struct VoteTarget {
+	hash: Vec<u8>,
+	number: u64,
+}
+
+/// Last block authored by authority and epoch.
+type BabeHistory = BTreeMap<(Vec<u8>, u64), AuthoredBlock>;
+/// Votes by authority, set id, round and stage.
+type GrandpaHistory = BTreeMap<(Vec<u8>, u64, u64, VoteStage), VoteTarget>;
+
+/// Storage of the signing history.
+trait HistoryStore: Send + Sync {
+	fn read(&self, key: &[u8]) -> sp_blockchain::Result<Option<Vec<u8>>>;
+	fn write(&self, key: &[u8], value: &[u8]) -> sp_blockchain::Result<()>;
+}
+
+impl<A: AuxStore + Send + Sync> HistoryStore for A {
+	fn read(&self, key: &[u8]) -> sp_blockchain::Result<Option<Vec<u8>>> {
+		self.get_aux(key)
+	}
+
+	fn write(&self, key: &[u8], value: &[u8]) -> sp_blockchain::Result<()> {
+		self.insert_aux(&[(key, value)], &[])
+	}
+}
+
+/// Record of the blocks and votes signed by the node, refusing to sign conflicting ones.
+#[derive(Clone)]
+pub struct SlashingProtection {
+	store: Arc<dyn HistoryStore>,
+	/// Held from checking a message until it is recorded.
+	lock: Arc<Mutex<()>>,
+}
+
+impl SlashingProtection {
+	/// Keep the signing history in the aux storage of `store`.
+	pub fn new<A: AuxStore + Send + Sync + 'static>(store: Arc<A>) -> Self {
+		SlashingProtection { store, lock: Arc::new(Mutex::new(())) }
+	}
+
+	/// Check that `authority` may author the block with `header_hash` in `slot` of `epoch`,
+	/// and record it if so.
+	pub fn check_babe_block(
+		&self,
+		authority: &[u8],
+		epoch: u64,
+		slot: u64,
+		header_hash: &[u8],
+	) -> Result<()> {
+		let _lock = self.lock.lock();
+		let mut history: BabeHistory = self.load(BABE_HISTORY_KEY)?;
+
+		let latest_epoch = latest_epoch(&history, authority);
+		if latest_epoch.map_or(false, |latest| epoch + BABE_EPOCHS_KEPT <= latest) {
+			return Err(Error::Conflict(format!(
+				"epoch {} is older than the signing history", epoch,
+			)));
+		}
+		match history.get(&(authority.to_vec(), epoch)) {
+			Some(last) if slot < last.slot => return Err(Error::Conflict(format!(
+				"authored slot {} of epoch {} already, after slot {}", last.slot, epoch, slot,
+			))),
+			Some(last) if slot == last.slot && header_hash != &last.header_hash[..] =>
+				return Err(Error::Conflict(format!("authored another block in slot {}", slot))),
+			Some(last) if slot == last.slot => return Ok(()),
+			_ => {},
+		}
+
+		history.insert(
+			(authority.to_vec(), epoch),
+			AuthoredBlock { slot, header_hash: header_hash.to_vec() },
+		);
+		prune_babe(&mut history);
+		self.store(BABE_HISTORY_KEY, &history)
+	}
+
+	/// Check that `authority` may vote for the given target at `stage` of `round` in `set_id`,
+	/// and record the vote if so.
+	pub fn check_grandpa_vote(
+		&self,
+		authority: &[u8],
+		set_id: u64,
+		round: u64,
+		stage: VoteStage,
+		target_hash: &[u8],
+		target_number: u64,
+	) -> Result<()> {
+		let _lock = self.lock.lock();
+		let mut history: GrandpaHistory = self.load(GRANDPA_HISTORY_KEY)?;
+
+		if let Some((latest_set, latest_round)) = latest_round(&history, authority) {
+			if set_id < latest_set || (set_id == latest_set && round + GRANDPA_ROUNDS_KEPT <= latest_round) {
+				return Err(Error::Conflict(format!(
+					"round {} of set {} is older than the signing history", round, set_id,
+				)));
+			}
+		}
+		let target = VoteTarget { hash: target_hash.to_vec(), number: target_number };
+		match history.get(&(authority.to_vec(), set_id, round, stage)) {
+			Some(last) if *last == target => return Ok(()),
+			Some(last) => return Err(Error::Conflict(format!(
+				"voted for #{} already at {:?} of round {} in set {}",
+				last.number, stage, round, set_id,
+			))),
+			None => {},
+		}
+
+		history.insert((authority.to_vec(), set_id, round, stage), target);
+		prune_grandpa(&mut history);
+		self.store(GRANDPA_HISTORY_KEY, &history)
+	}
+
+	/// Export the signing history of the chain with `genesis_hash`.
+	pub fn export(&self, genesis_hash: &[u8]) -> Result<Interchange> {
+		let _lock = self.lock.lock();
+		let babe: BabeHistory = self.load(BABE_HISTORY_KEY)?;
+		let grandpa: GrandpaHistory = self.load(GRANDPA_HISTORY_KEY)?;
+
+		Ok(Interchange {
+			metadata: InterchangeMetadata {
+				interchange_format_version: INTERCHANGE_FORMAT_VERSION,
+				genesis_hash: genesis_hash.to_vec(),
+			},
+			babe: babe.into_iter().map(|((authority, epoch), block)| InterchangeBlock {
+				authority,
+				epoch,
+				slot: block.slot,
+				header_hash: block.header_hash,
+			}).collect(),
+			grandpa: grandpa.into_iter().map(|((authority, set_id, round, stage), target)| {
+				InterchangeVote {
+					authority,
+					set_id,
+					round,
+					stage,
+					target_hash: target.hash,
+					target_number: target.number,
+				}
+			}).collect(),
+		})
+	}
+
+	/// Merge the signing history of `interchange` into the one of the chain with
+	/// `genesis_hash`.
+	///
+	/// The later of two authored slots is kept. Where the histories disagree on a block or vote
+	/// at the same position, the local one is kept; either refuses to sign anything else.
+	pub fn import(&self, interchange: &Interchange, genesis_hash: &[u8]) -> Result<()> {
+		let metadata = &interchange.metadata;
+		if metadata.interchange_format_version != INTERCHANGE_FORMAT_VERSION {
+			return Err(Error::UnsupportedVersion(metadata.interchange_format_version));
+		}
+		if metadata.genesis_hash != genesis_hash {
+			return Err(Error::GenesisMismatch);
+		}
+
+		let _lock = self.lock.lock();
+		let mut babe: BabeHistory = self.load(BABE_HISTORY_KEY)?;
+		for block in &interchange.babe {
+			let imported = AuthoredBlock { slot: block.slot, header_hash: block.header_hash.clone() };
+			babe.entry((block.authority.clone(), block.epoch))
+				.and_modify(|local| if imported.slot > local.slot {
+					*local = imported.clone();
+				})
+				.or_insert(imported);
+		}
+		prune_babe(&mut babe);
+
+		let mut grandpa: GrandpaHistory = self.load(GRANDPA_HISTORY_KEY)?;
+		for vote in &interchange.grandpa {
+			grandpa.entry((vote.authority.clone(), vote.set_id, vote.round, vote.stage))
+				.or_insert(VoteTarget { hash: vote.target_hash.clone(), number: vote.target_number });
+		}
+		prune_grandpa(&mut grandpa);
+
+		self.store(BABE_HISTORY_KEY, &babe)?;
+		self.store(GRANDPA_HISTORY_KEY, &grandpa)
+	}
+
+	fn load<T: Decode + Default>(&self, key: &[u8]) -> Result<T> {
+		match self.store.read(key).map_err(Error::Client)? {
+			Some(encoded) => T::decode(&mut &encoded[..]).map_err(Error::Codec),
+			None => Ok(T::default()),
+		}
+	}
+
+	fn store<T: Encode>(&self, key: &[u8], history: &T) -> Result<()> {
+		self.store.write(key, &history.encode()).map_err(Error::Client)
+	}
+}
+
+/// The latest epoch `authority` authored a block in.
+fn latest_epoch(history: &BabeHistory, authority: &[u8]) -> Option<u64> {
+	history.keys()
+		.filter(|(a, _)| a.as_slice() == authority)
+		.map(|(_, epoch)| *epoch)
+		.max()
+}
+
+/// The latest set id and round `authority` voted in.
+fn latest_round(history: &GrandpaHistory, authority: &[u8]) -> Option<(u64, u64)> {
+	history.keys()
+		.filter(|(a, _, _, _)| a.as_slice() == authority)
+		.map(|(_, set_id, round, _)| (*set_id, *round))
+		.max()
+}
+
+/// Drop the epochs of each authority older than the kept ones.
+fn prune_babe(history: &mut BabeHistory) {
+	let latest = history.keys().fold(BTreeMap::new(), |mut latest, (authority, epoch)| {
+		let entry = latest.entry(authority.clone()).or_insert(*epoch);
+		*entry = (*entry).max(*epoch);
+		latest
+	});
+	let stale = history.keys()
+		.filter(|(authority, epoch)| epoch + BABE_EPOCHS_KEPT <= latest[authority])
+		.cloned()
+		.collect::<Vec<_>>();
+	for key in stale {
+		history.remove(&key);
+	}
+}
+
+/// Drop the sets and rounds of each authority older than the kept ones.
+fn prune_grandpa(history: &mut GrandpaHistory) {
+	let latest = history.keys().fold(BTreeMap::new(), |mut latest, (authority, set_id, round, _)| {
+		let entry = latest.entry(authority.clone()).or_insert((*set_id, *round));
+		*entry = (*entry).max((*set_id, *round));
+		latest
+	});
+	let stale = history.keys()
+		.filter(|(authority, set_id, round, _)| {
+			let (latest_set, latest_round) = latest[authority];
+			*set_id < latest_set || round + GRANDPA_ROUNDS_KEPT <= latest_round
+		})
+		.cloned()
+		.collect::<Vec<_>>();
+	for key in stale {
+		history.remove(&key);
+	}
+}
+
+/// Signing history in the interchange format, to move a validator between machines.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Interchange {
+	/// What the history is of.
+	pub metadata: InterchangeMetadata,
+	/// Last block authored by each BABE authority in each epoch.
+	pub babe: Vec<InterchangeBlock>,
+	/// Votes of each GRANDPA authority.
+	pub grandpa: Vec<InterchangeVote>,
+}
+
+/// Metadata of an [`Interchange`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InterchangeMetadata {
+	/// Version of the interchange format.
+	pub interchange_format_version: u32,
+	/// Genesis hash of the chain the history is of.
+	#[serde(with = "sp_core::bytes")]
+	pub genesis_hash: Vec<u8>,
+}
+
+/// A block authored by a BABE authority.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InterchangeBlock {
+	/// Public key of the authority.
+	#[serde(with = "sp_core::bytes")]
+	pub authority: Vec<u8>,
+	/// Epoch index.
+	pub epoch: u64,
+	/// Slot number.
+	pub slot: u64,
+	/// Hash of the authored header, before sealing.
+	#[serde(with = "sp_core::bytes")]
+	pub header_hash: Vec<u8>,
+}
+
+/// A vote of a GRANDPA authority.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InterchangeVote {
+	/// Public key of the authority.
+	#[serde(with = "sp_core::bytes")]
+	pub authority: Vec<u8>,
+	/// Authority set id.
+	pub set_id: u64,
+	/// Round number.
+	pub round: u64,
+	/// Stage of the vote.
+	pub stage: VoteStage,
+	/// Hash of the voted block.
+	#[serde(with = "sp_core::bytes")]
+	pub target_hash: Vec<u8>,
+	/// Number of the voted block.
+	pub target_number: u64,
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::collections::HashMap;
+
+	#[derive(Default)]
+	struct MemoryAux(Mutex<HashMap<Vec<u8>, Vec<u8>>>);
+
+	impl AuxStore for MemoryAux {
+		fn insert_aux<
+			'a,
+			'b: 'a,
+			'c: 'a,
+			I: IntoIterator<Item=&'a(&'c [u8], &'c [u8])>,
+			D: IntoIterator<Item=&'a &'b [u8]>,
+		>(&self, insert: I, delete: D) -> sp_blockchain::Result<()> {
+			let mut aux = self.0.lock();
+			for (key, value) in insert {
+				aux.insert(key.to_vec(), value.to_vec());
+			}
+			for key in delete {
+				aux.remove(*key);
+			}
+			Ok(())
+		}
+
+		fn get_aux(&self, key: &[u8]) -> sp_blockchain::Result<Option<Vec<u8>>> {
+			Ok(self.0.lock().get(key).cloned())
+		}
+	}
+
+	const ALICE: &[u8] = &[1; 32];
+	const BOB: &[u8] = &[2; 32];
+	const GENESIS: &[u8] = &[0; 32];
+
+	fn protection() -> SlashingProtection {
+		SlashingProtection::new(Arc::new(MemoryAux::default()))
+	}
+
+	#[test]
+	fn babe_blocks_conflicting_with_history_are_refused() {
+		let protection = protection();
+
+		assert!(protection.check_babe_block(ALICE, 1, 10, b"a").is_ok());
+		assert!(protection.check_babe_block(ALICE, 1, 10, b"a").is_ok());
+		assert!(protection.check_babe_block(ALICE, 1, 10, b"b").is_err());
+		assert!(protection.check_babe_block(ALICE, 1, 9, b"c").is_err());
+		assert!(protection.check_babe_block(BOB, 1, 10, b"b").is_ok());
+		assert!(protection.check_babe_block(ALICE, 1, 11, b"c").is_ok());
+
+		assert!(protection.check_babe_block(ALICE, 1 + BABE_EPOCHS_KEPT, 100, b"d").is_ok());
+		assert!(protection.check_babe_block(ALICE, 1, 12, b"e").is_err());
+		assert!(protection.check_babe_block(ALICE, 2, 20, b"f").is_ok());
+	}
+
+	#[test]
+	fn grandpa_votes_conflicting_with_history_are_refused() {
+		let protection = protection();
+		let vote = |authority, set_id, round, stage, target: &[u8]| protection.check_grandpa_vote(
+			authority, set_id, round, stage, target, target[0] as u64,
+		);
+
+		assert!(vote(ALICE, 0, 5, VoteStage::Prevote, &[7]).is_ok());
+		assert!(vote(ALICE, 0, 5, VoteStage::Prevote, &[7]).is_ok());
+		assert!(vote(ALICE, 0, 5, VoteStage::Prevote, &[8]).is_err());
+		assert!(vote(ALICE, 0, 5, VoteStage::Precommit, &[8]).is_ok());
+		assert!(vote(BOB, 0, 5, VoteStage::Prevote, &[8]).is_ok());
+
+		assert!(vote(ALICE, 1, 1, VoteStage::Prevote, &[9]).is_ok());
+		assert!(vote(ALICE, 0, 6, VoteStage::Prevote, &[9]).is_err());
+		assert!(vote(ALICE, 1, 1 + GRANDPA_ROUNDS_KEPT, VoteStage::Prevote, &[9]).is_ok());
+		assert!(vote(ALICE, 1, 1, VoteStage::Prevote, &[9]).is_err());
+		assert!(vote(ALICE, 1, 2, VoteStage::Prevote, &[9]).is_ok());
+	}
+
+	#[test]
+	fn interchange_moves_history_between_nodes() {
+		let old = protection();
+		old.check_babe_block(ALICE, 1, 10, b"a").unwrap();
+		old.check_grandpa_vote(ALICE, 0, 5, VoteStage::Prevote, &[7], 7).unwrap();
+
+		let json = serde_json::to_string(&old.export(GENESIS).unwrap()).unwrap();
+		let interchange: Interchange = serde_json::from_str(&json).unwrap();
+
+		let new = protection();
+		new.check_babe_block(ALICE, 1, 8, b"b").unwrap();
+		assert!(matches!(new.import(&interchange, &[1; 32]), Err(Error::GenesisMismatch)));
+		new.import(&interchange, GENESIS).unwrap();
+
+		assert!(new.check_babe_block(ALICE, 1, 10, b"b").is_err());
+		assert!(new.check_babe_block(ALICE, 1, 10, b"a").is_ok());
+		assert!(new.check_grandpa_vote(ALICE, 0, 5, VoteStage::Prevote, &[8], 8).is_err());
+		assert_eq!(new.export(GENESIS).unwrap().babe.len(), 1);
+	}
+}
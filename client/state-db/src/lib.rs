@@ -141,6 +141,10 @@ pub struct Constraints {
 	pub max_blocks: Option<u32>,
 	/// Maximum memory in the pruning overlay.
 	pub max_mem: Option<usize>,
+	/// Keep a full state snapshot every this many canonical blocks, so that pruned
+	/// states can still be reconstructed. The state database itself ignores this value,
+	/// it is handled by the client backend.
+	pub snapshot_interval: Option<u32>,
 }
 
 /// Pruning mode.
@@ -160,9 +164,28 @@ impl PruningMode {
 		PruningMode::Constrained(Constraints {
 			max_blocks: Some(n),
 			max_mem: None,
+			snapshot_interval: None,
 		})
 	}
 
+	/// Create a mode that keeps given number of blocks and a full state snapshot
+	/// every `interval` blocks.
+	pub fn keep_blocks_with_snapshots(n: u32, interval: u32) -> PruningMode {
+		PruningMode::Constrained(Constraints {
+			max_blocks: Some(n),
+			max_mem: None,
+			snapshot_interval: Some(interval),
+		})
+	}
+
+	/// Interval between full state snapshots, if enabled.
+	pub fn snapshot_interval(&self) -> Option<u32> {
+		match self {
+			PruningMode::Constrained(c) => c.snapshot_interval.filter(|i| *i > 0),
+			PruningMode::ArchiveAll | PruningMode::ArchiveCanonical => None,
+		}
+	}
+
 	/// Is this an archive (either ArchiveAll or ArchiveCanonical) pruning mode?
 	pub fn is_archive(&self) -> bool {
 		match *self {
@@ -274,6 +297,28 @@ impl<BlockHash: Hash + MallocSizeOf, Key: Hash + MallocSizeOf> StateDbSync<Block
 		}
 	}
 
+	fn reset_base<E: fmt::Debug>(
+		&mut self,
+		hash: &BlockHash,
+		number: u64,
+		parent_hash: &BlockHash,
+		changeset: ChangeSet<Key>,
+	) -> Result<CommitSet<Key>, Error<E>> {
+		match self.mode {
+			PruningMode::ArchiveAll => self.insert_block(hash, number, parent_hash, changeset),
+			PruningMode::Constrained(_) | PruningMode::ArchiveCanonical => {
+				let mut commit = self.non_canonical.reset_base(parent_hash, number)?;
+				if let Some(ref mut pruning) = self.pruning {
+					pruning.reset_base(number, &mut commit);
+				}
+				let inserted = self.non_canonical.insert(hash, number, parent_hash, changeset)?;
+				commit.data = inserted.data;
+				commit.meta.inserted.extend(inserted.meta.inserted);
+				Ok(commit)
+			}
+		}
+	}
+
 	fn canonicalize_block<E: fmt::Debug>(
 		&mut self,
 		hash: &BlockHash,
@@ -301,6 +346,10 @@ impl<BlockHash: Hash + MallocSizeOf, Key: Hash + MallocSizeOf> StateDbSync<Block
 		return self.non_canonical.last_canonicalized_block_number()
 	}
 
+	fn first_unpruned(&self) -> Option<u64> {
+		self.pruning.as_ref().map(|pruning| pruning.pending())
+	}
+
 	fn is_pruned(&self, hash: &BlockHash, number: u64) -> bool {
 		match self.mode {
 			PruningMode::ArchiveAll => false,
@@ -463,6 +512,19 @@ impl<BlockHash: Hash + MallocSizeOf, Key: Hash + MallocSizeOf> StateDb<BlockHash
 		self.db.write().insert_block(hash, number, parent_hash, changeset)
 	}
 
+	/// Add a new non-canonical block whose parent is not known, making it the base of the
+	/// database. The block is expected to carry its full state in `changeset`. Fails if there
+	/// are any non-canonical blocks.
+	pub fn reset_base<E: fmt::Debug>(
+		&self,
+		hash: &BlockHash,
+		number: u64,
+		parent_hash: &BlockHash,
+		changeset: ChangeSet<Key>,
+	) -> Result<CommitSet<Key>, Error<E>> {
+		self.db.write().reset_base(hash, number, parent_hash, changeset)
+	}
+
 	/// Finalize a previously inserted block.
 	pub fn canonicalize_block<E: fmt::Debug>(
 		&self,
@@ -503,6 +565,12 @@ impl<BlockHash: Hash + MallocSizeOf, Key: Hash + MallocSizeOf> StateDb<BlockHash
 		return self.db.read().best_canonical()
 	}
 
+	/// Returns the number of the oldest canonical block whose state has not been pruned.
+	/// Returns `None` if the pruning mode never discards canonical states.
+	pub fn first_unpruned(&self) -> Option<u64> {
+		self.db.read().first_unpruned()
+	}
+
 	/// Check if block is pruned away.
 	pub fn is_pruned(&self, hash: &BlockHash, number: u64) -> bool {
 		return self.db.read().is_pruned(hash, number)
@@ -615,6 +683,7 @@ mod tests {
 		let (db, _) = make_test_db(PruningMode::Constrained(Constraints {
 			max_blocks: Some(0),
 			max_mem: None,
+			snapshot_interval: None,
 		}));
 		assert!(db.data_eq(&make_db(&[21, 3, 922, 94])));
 	}
@@ -624,6 +693,7 @@ mod tests {
 		let (db, sdb) = make_test_db(PruningMode::Constrained(Constraints {
 			max_blocks: Some(1),
 			max_mem: None,
+			snapshot_interval: None,
 		}));
 		assert!(sdb.is_pruned(&H256::from_low_u64_be(0), 0));
 		assert!(sdb.is_pruned(&H256::from_low_u64_be(1), 1));
@@ -637,6 +707,7 @@ mod tests {
 		let (db, sdb) = make_test_db(PruningMode::Constrained(Constraints {
 			max_blocks: Some(2),
 			max_mem: None,
+			snapshot_interval: None,
 		}));
 		assert!(sdb.is_pruned(&H256::from_low_u64_be(0), 0));
 		assert!(sdb.is_pruned(&H256::from_low_u64_be(1), 1));
@@ -645,6 +716,99 @@ mod tests {
 		assert!(db.data_eq(&make_db(&[1, 21, 3, 921, 922, 93, 94])));
 	}
 
+	#[test]
+	fn first_unpruned_follows_window() {
+		// three blocks are canonicalized, none of them is the genesis.
+		let (_, sdb) = make_test_db(PruningMode::ArchiveCanonical);
+		assert_eq!(sdb.first_unpruned(), None);
+		let (_, sdb) = make_test_db(PruningMode::keep_blocks(2));
+		assert_eq!(sdb.first_unpruned(), Some(1));
+		let (_, sdb) = make_test_db(PruningMode::keep_blocks(1));
+		assert_eq!(sdb.first_unpruned(), Some(2));
+	}
+
+	#[test]
+	fn reset_base_restarts_window() {
+		let mut db = make_db(&[91]);
+		let state_db = StateDb::new(PruningMode::keep_blocks(2), false, &db).unwrap();
+		db.commit(
+			&state_db
+				.insert_block::<io::Error>(
+					&H256::from_low_u64_be(1),
+					1,
+					&H256::from_low_u64_be(0),
+					make_changeset(&[1], &[]),
+				)
+				.unwrap(),
+		);
+		state_db.apply_pending();
+		db.commit(&state_db.canonicalize_block::<io::Error>(&H256::from_low_u64_be(1)).unwrap());
+		state_db.apply_pending();
+
+		db.commit(
+			&state_db
+				.reset_base::<io::Error>(
+					&H256::from_low_u64_be(10),
+					10,
+					&H256::from_low_u64_be(9),
+					make_changeset(&[10], &[]),
+				)
+				.unwrap(),
+		);
+		state_db.apply_pending();
+		db.commit(&state_db.canonicalize_block::<io::Error>(&H256::from_low_u64_be(10)).unwrap());
+		state_db.apply_pending();
+		assert_eq!(state_db.first_unpruned(), Some(10));
+		assert!(!state_db.is_pruned(&H256::from_low_u64_be(10), 10));
+		assert!(db.data_eq(&make_db(&[1, 10, 91])));
+
+		// the new base survives a restart and blocks follow it.
+		let state_db: StateDb<H256, H256> = StateDb::new(PruningMode::keep_blocks(2), false, &db).unwrap();
+		assert_eq!(state_db.first_unpruned(), Some(10));
+		db.commit(
+			&state_db
+				.insert_block::<io::Error>(
+					&H256::from_low_u64_be(11),
+					11,
+					&H256::from_low_u64_be(10),
+					make_changeset(&[11], &[]),
+				)
+				.unwrap(),
+		);
+		state_db.apply_pending();
+
+		// there is a non-canonical block now.
+		assert!(state_db
+			.reset_base::<io::Error>(
+				&H256::from_low_u64_be(20),
+				20,
+				&H256::from_low_u64_be(19),
+				make_changeset(&[20], &[]),
+			)
+			.is_err());
+	}
+
+	#[test]
+	fn snapshot_interval_keeps_constrained_mode() {
+		let mut db = make_db(&[]);
+		let state_db: StateDb<H256, H256> = StateDb::new(PruningMode::keep_blocks(2), false, &db).unwrap();
+		db.commit(
+			&state_db
+			.insert_block::<io::Error>(
+				&H256::from_low_u64_be(0),
+				0,
+				&H256::from_low_u64_be(0),
+				make_changeset(&[], &[]),
+			)
+			.unwrap(),
+		);
+		let new_mode = PruningMode::keep_blocks_with_snapshots(2, 100);
+		assert_eq!(new_mode.snapshot_interval(), Some(100));
+		assert_eq!(PruningMode::keep_blocks(2).snapshot_interval(), None);
+		let state_db: Result<StateDb<H256, H256>, _> = StateDb::new(new_mode, false, &db);
+		assert!(state_db.is_ok());
+	}
+
 	#[test]
 	fn detects_incompatible_mode() {
 		let mut db = make_db(&[]);
@@ -659,7 +823,7 @@ mod tests {
 			)
 			.unwrap(),
 		);
-		let new_mode = PruningMode::Constrained(Constraints { max_blocks: Some(2), max_mem: None });
+		let new_mode = PruningMode::Constrained(Constraints { max_blocks: Some(2), max_mem: None, snapshot_interval: None });
 		let state_db: Result<StateDb<H256, H256>, _> = StateDb::new(new_mode, false, &db);
 		assert!(state_db.is_err());
 	}
@@ -266,6 +266,20 @@ impl<BlockHash: Hash, Key: Hash> NonCanonicalOverlay<BlockHash, Key> {
 		Ok(commit)
 	}
 
+	/// Make `parent_hash` the last canonicalized block, so that a block at `number` can be
+	/// inserted without any of its ancestors. Expects no non-canonical blocks in the overlay.
+	pub fn reset_base<E: fmt::Debug>(&mut self, parent_hash: &BlockHash, number: u64) -> Result<CommitSet<Key>, Error<E>> {
+		if number == 0 || !self.levels.is_empty() || !self.pending_canonicalizations.is_empty() {
+			return Err(Error::InvalidBlockNumber);
+		}
+		let mut commit = CommitSet::default();
+		let last_canonicalized = (parent_hash.clone(), number - 1);
+		commit.meta.inserted.push((to_meta_key(LAST_CANONICAL, &()), last_canonicalized.encode()));
+		trace!(target: "state-db", "Reset last canonicalized block to #{} ({:?})", number - 1, parent_hash);
+		self.last_canonicalized = Some(last_canonicalized);
+		Ok(commit)
+	}
+
 	fn discard_journals(
 		&self,
 		level_index: usize,
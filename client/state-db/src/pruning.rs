@@ -163,6 +163,18 @@ impl<BlockHash: Hash, Key: Hash> RefWindow<BlockHash, Key> {
 		}
 	}
 
+	/// Drop all blocks from the window and restart it at block `number`. Keys scheduled for
+	/// deletion by the dropped blocks are never pruned. Adds changes to `commit`.
+	pub fn reset_base(&mut self, number: u64, commit: &mut CommitSet<Key>) {
+		trace!(target: "state-db", "Resetting pruning window to #{} ({} blocks dropped)", number, self.death_rows.len());
+		commit.meta.deleted.extend(self.death_rows.drain(..).map(|row| row.journal_key));
+		self.death_index.clear();
+		self.pending_number = number;
+		self.pending_canonicalizations = 0;
+		self.pending_prunings = 0;
+		commit.meta.inserted.push((to_meta_key(LAST_PRUNED, &()), number.saturating_sub(1).encode()));
+	}
+
 	/// Add a change set to the window. Creates a journal record and pushes it to `commit`
 	pub fn note_canonical(&mut self, hash: &BlockHash, commit: &mut CommitSet<Key>) {
 		trace!(target: "state-db", "Adding to pruning window: {:?} ({} inserted, {} deleted)", hash, commit.data.inserted.len(), commit.data.deleted.len());
@@ -0,0 +1,271 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Tracing of the execution of historical blocks.
+
+use std::{fmt, sync::Arc};
+
+use parking_lot::Mutex;
+use tracing::{Dispatch, Level, dispatcher};
+use tracing_subscriber::layer::SubscriberExt;
+
+use sc_client_api::BlockBackend;
+use sp_api::{Core, ProvideRuntimeApi};
+use sp_blockchain::HeaderBackend;
+use sp_rpc::tracing::{BlockTrace, Data, Event, Span};
+use sp_runtime::{generic::BlockId, traits::{Block as BlockT, Header}};
+
+use crate::{parse_target, ProfilingLayer, SpanDatum, TraceEvent, TraceHandler, Values};
+
+/// Targets traced if none are given.
+pub const DEFAULT_TARGETS: &str = "pallet,frame,state";
+
+/// Target of the storage access events.
+const STORAGE_TARGET: &str = "state";
+
+/// Error tracing a block.
+#[derive(Debug)]
+pub enum Error {
+	/// The header or body of the block is not available.
+	MissingBlockComponent(String),
+	/// Executing the block failed.
+	Dispatch(String),
+}
+
+impl fmt::Display for Error {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Error::MissingBlockComponent(e) => write!(f, "Missing block component: {}", e),
+			Error::Dispatch(e) => write!(f, "Block execution failed: {}", e),
+		}
+	}
+}
+
+impl std::error::Error for Error {}
+
+/// Collects the spans and events of a block execution.
+struct BlockTraceHandler {
+	spans: Arc<Mutex<Vec<SpanDatum>>>,
+	events: Arc<Mutex<Vec<TraceEvent>>>,
+}
+
+impl TraceHandler for BlockTraceHandler {
+	fn handle_span(&self, span: SpanDatum) {
+		self.spans.lock().push(span);
+	}
+
+	fn handle_event(&self, event: TraceEvent) {
+		self.events.lock().push(event);
+	}
+}
+
+/// Re-executes a block on the state of its parent, recording its spans and events.
+///
+/// Runtime spans are only recorded when the block is executed natively, or with a runtime
+/// built with the `with-tracing` feature.
+pub struct BlockExecutor<Block: BlockT, Client> {
+	client: Arc<Client>,
+	block: Block::Hash,
+	targets: Option<String>,
+	storage_keys: Option<String>,
+}
+
+impl<Block, Client> BlockExecutor<Block, Client>
+	where
+		Block: BlockT + 'static,
+		Client: HeaderBackend<Block> + BlockBackend<Block> + ProvideRuntimeApi<Block>
+			+ Send + Sync + 'static,
+		Client::Api: Core<Block, Error = sp_blockchain::Error>,
+{
+	/// Create a new executor tracing `block`.
+	///
+	/// `targets` is a comma separated list of targets with optional levels, as for
+	/// [`ProfilingLayer::new`], and defaults to [`DEFAULT_TARGETS`]. `storage_keys` is a comma
+	/// separated list of hex encoded key prefixes the storage events are filtered by.
+	pub fn new(
+		client: Arc<Client>,
+		block: Block::Hash,
+		targets: Option<String>,
+		storage_keys: Option<String>,
+	) -> Self {
+		Self { client, block, targets, storage_keys }
+	}
+
+	/// Execute the block and return its trace.
+	pub fn trace_block(&self) -> Result<BlockTrace, Error> {
+		let id = BlockId::Hash(self.block);
+		let mut header = self.client.header(id)
+			.map_err(|e| Error::MissingBlockComponent(format!("Header: {}", e)))?
+			.ok_or_else(|| Error::MissingBlockComponent("Header not found".into()))?;
+		let extrinsics = self.client.block_body(&id)
+			.map_err(|e| Error::MissingBlockComponent(format!("Extrinsics: {}", e)))?
+			.ok_or_else(|| Error::MissingBlockComponent("Extrinsics not found".into()))?;
+		let parent_hash = *header.parent_hash();
+		// Seals are added by the consensus engines after the block is built,
+		// the runtime does not expect them.
+		header.digest_mut().logs.retain(|d| d.as_seal().is_none());
+		let block = Block::new(header, extrinsics);
+
+		let targets = self.targets.as_deref().unwrap_or(DEFAULT_TARGETS);
+		let spans = Arc::new(Mutex::new(Vec::new()));
+		let events = Arc::new(Mutex::new(Vec::new()));
+		let layer = ProfilingLayer::new_with_handler(
+			Box::new(BlockTraceHandler { spans: spans.clone(), events: events.clone() }),
+			targets,
+		);
+		let dispatch = Dispatch::new(tracing_subscriber::registry().with(layer));
+
+		dispatcher::with_default(&dispatch, || {
+			self.client.runtime_api().execute_block(&BlockId::Hash(parent_hash), block)
+		}).map_err(|e| Error::Dispatch(format!("{:?}", e)))?;
+
+		let filter = TraceFilter::new(targets, self.storage_keys.as_deref().unwrap_or(""));
+		let spans = std::mem::take(&mut *spans.lock())
+			.into_iter()
+			.filter(|span| filter.check_target(&span.target, &span.level))
+			.map(into_span)
+			.collect();
+		let events = std::mem::take(&mut *events.lock())
+			.into_iter()
+			.filter(|event| filter.check_event(event))
+			.map(into_event)
+			.collect();
+
+		Ok(BlockTrace {
+			block_hash: format!("{:?}", self.block),
+			parent_hash: format!("{:?}", parent_hash),
+			tracing_targets: targets.to_owned(),
+			storage_keys: filter.storage_keys.join(","),
+			spans,
+			events,
+		})
+	}
+}
+
+/// Filters the recorded spans and events by target and storage key.
+struct TraceFilter {
+	targets: Vec<(String, Level)>,
+	storage_keys: Vec<String>,
+}
+
+impl TraceFilter {
+	fn new(targets: &str, storage_keys: &str) -> Self {
+		Self {
+			targets: targets.split(',').map(parse_target).collect(),
+			storage_keys: storage_keys.split(',')
+				.map(|key| key.trim().trim_start_matches("0x").to_lowercase())
+				.filter(|key| !key.is_empty())
+				.collect(),
+		}
+	}
+
+	fn check_target(&self, target: &str, level: &Level) -> bool {
+		self.targets.iter().any(|t| target.starts_with(t.0.as_str()) && level <= &t.1)
+	}
+
+	/// Storage events are only kept if their key starts with one of the storage keys,
+	/// or if no storage keys were given.
+	fn check_event(&self, event: &TraceEvent) -> bool {
+		if !self.check_target(&event.target, &event.level) {
+			return false
+		}
+		if event.target != STORAGE_TARGET {
+			return true
+		}
+		match event.values.string_values.get("key") {
+			Some(key) => self.storage_keys.is_empty() ||
+				self.storage_keys.iter().any(|prefix| key.starts_with(prefix.as_str())),
+			// log lines forwarded to tracing, the structured event is kept instead
+			None => false,
+		}
+	}
+}
+
+fn into_data(values: Values) -> Data {
+	Data {
+		bool_values: values.bool_values.into_iter().collect(),
+		i64_values: values.i64_values.into_iter().collect(),
+		u64_values: values.u64_values.into_iter().collect(),
+		string_values: values.string_values.into_iter().collect(),
+	}
+}
+
+fn into_span(mut span: SpanDatum) -> Span {
+	let wasm = span.values.bool_values.remove("wasm").unwrap_or(false);
+	Span {
+		id: span.id.into_u64(),
+		parent_id: span.parent_id.map(|id| id.into_u64()),
+		name: span.name,
+		target: span.target,
+		line: span.line,
+		wasm,
+		overall_time: span.overall_time.as_nanos() as u64,
+		values: into_data(span.values),
+	}
+}
+
+fn into_event(event: TraceEvent) -> Event {
+	Event {
+		name: event.name.to_owned(),
+		target: event.target,
+		parent_id: event.parent_id.map(|id| id.into_u64()),
+		data: into_data(event.values),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn storage_event(key: Option<&str>) -> TraceEvent {
+		let mut values = Values::new();
+		if let Some(key) = key {
+			values.string_values.insert("key".into(), key.into());
+		}
+		TraceEvent {
+			name: "event",
+			target: STORAGE_TARGET.into(),
+			level: Level::TRACE,
+			values,
+			parent_id: None,
+		}
+	}
+
+	#[test]
+	fn should_filter_storage_events_by_key() {
+		let filter = TraceFilter::new(DEFAULT_TARGETS, "0x26AA, 3a636f6465");
+		assert_eq!(filter.storage_keys, vec!["26aa".to_owned(), "3a636f6465".to_owned()]);
+		assert!(filter.check_event(&storage_event(Some("26aa394eea5630e0"))));
+		assert!(filter.check_event(&storage_event(Some("3a636f6465"))));
+		assert!(!filter.check_event(&storage_event(Some("c2261276cc9d1f85"))));
+		assert!(!filter.check_event(&storage_event(None)));
+
+		let filter = TraceFilter::new(DEFAULT_TARGETS, "");
+		assert!(filter.check_event(&storage_event(Some("c2261276cc9d1f85"))));
+	}
+
+	#[test]
+	fn should_filter_by_target() {
+		let filter = TraceFilter::new("pallet,frame=debug", "");
+		assert!(filter.check_target("pallet_social_treasury", &Level::TRACE));
+		assert!(filter.check_target("frame_executive", &Level::DEBUG));
+		assert!(!filter.check_target("frame_executive", &Level::TRACE));
+		assert!(!filter.check_target("state", &Level::TRACE));
+		assert!(!filter.check_event(&storage_event(Some("26aa"))));
+	}
+}
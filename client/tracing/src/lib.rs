@@ -23,6 +23,10 @@
 //! See `sp-tracing` for examples on how to use tracing.
 //!
 //! Currently we provide `Log` (default), `Telemetry` variants for `Receiver`
+//!
+//! The [`block`] module re-executes historical blocks and collects their spans and events.
+
+pub mod block;
 
 use rustc_hash::FxHashMap;
 use std::fmt;
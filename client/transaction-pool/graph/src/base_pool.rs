@@ -220,6 +220,7 @@ const RECENTLY_PRUNED_TAGS: usize = 2;
 #[cfg_attr(not(target_os = "unknown"), derive(parity_util_mem::MallocSizeOf))]
 pub struct BasePool<Hash: hash::Hash + Eq, Ex> {
 	reject_future_transactions: bool,
+	sender_policy: SenderPolicy,
 	future: FutureTransactions<Hash, Ex>,
 	ready: ReadyTransactions<Hash, Ex>,
 	/// Store recently pruned tags (for last two invocations).
@@ -239,10 +240,21 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> Default for Bas
 impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash, Ex> {
 	/// Create new pool given reject_future_transactions flag.
 	pub fn new(reject_future_transactions: bool) -> Self {
+		Self::with_sender_policy(reject_future_transactions, Default::default())
+	}
+
+	/// Create new pool given reject_future_transactions flag and the per-sender policy.
+	pub fn with_sender_policy(reject_future_transactions: bool, sender_policy: SenderPolicy) -> Self {
+		let mut ready = ReadyTransactions::default();
+		ready.set_min_replacement_bump(sender_policy.min_replacement_bump);
+		ready.set_sender_tag_prefix(sender_policy.sender_tag_prefix);
+		let mut future = FutureTransactions::default();
+		future.set_sender_tag_prefix(sender_policy.sender_tag_prefix);
 		BasePool {
 			reject_future_transactions,
-			future: Default::default(),
-			ready: Default::default(),
+			sender_policy,
+			future,
+			ready,
 			recently_pruned: Default::default(),
 			recently_pruned_index: 0,
 		}
@@ -281,6 +293,11 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 			return Err(error::Error::AlreadyImported(Box::new(tx.hash)))
 		}
 
+		if self.exceeds_sender_limit(&tx) {
+			debug!(target: "txpool", "[{:?}] Too many transactions from the sender", tx.hash);
+			return Err(error::Error::TooManyFromSender)
+		}
+
 		let tx = WaitingTransaction::new(
 			tx,
 			self.ready.provided_tags(),
@@ -308,6 +325,23 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 		self.import_to_ready(tx)
 	}
 
+	/// Returns true if the sender of the transaction already has the maximal number of
+	/// transactions in the pool.
+	///
+	/// Transactions replacing others through one of their `provides` tags are not limited.
+	fn exceeds_sender_limit(&self, tx: &Transaction<Hash, Ex>) -> bool {
+		let (sender, max) = match (self.ready.senders().sender_of(tx), self.sender_policy.max_per_sender) {
+			(Some(sender), Some(max)) => (sender, max),
+			_ => return false,
+		};
+		let provided_tags = self.ready.provided_tags();
+		if tx.provides.iter().any(|tag| provided_tags.contains_key(tag)) {
+			return false
+		}
+
+		self.ready.senders().count(sender) + self.future.senders().count(sender) >= max
+	}
+
 	/// Imports transaction to ready queue.
 	///
 	/// NOTE the transaction has to have all requirements satisfied.
@@ -408,10 +442,17 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 	/// Removes and returns worst transactions from the queues and all transactions that depend on them.
 	/// Technically the worst transaction should be evaluated by computing the entire pending set.
 	/// We use a simplified approach to remove the transaction that occupies the pool for the longest time.
+	/// If the sender policy identifies senders, the lowest priority transaction of the sender with
+	/// the most transactions in the queue is removed instead.
 	pub fn enforce_limits(&mut self, ready: &Limit, future: &Limit) -> Vec<Arc<Transaction<Hash, Ex>>> {
 		let mut removed = vec![];
 
 		while ready.is_exceeded(self.ready.len(), self.ready.bytes()) {
+			if let Some(worst) = self.ready.senders().worst_of_largest().cloned() {
+				removed.append(&mut self.remove_subtree(&[worst]));
+				continue;
+			}
+
 			// find the worst transaction
 			let minimal = self.ready
 				.fold(|minimal, current| {
@@ -433,6 +474,11 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 		}
 
 		while future.is_exceeded(self.future.len(), self.future.bytes()) {
+			if let Some(worst) = self.future.senders().worst_of_largest().cloned() {
+				removed.append(&mut self.remove_subtree(&[worst]));
+				continue;
+			}
+
 			// find the worst transaction
 			let minimal = self.future
 				.fold(|minimal, current| {
@@ -527,6 +573,21 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 	}
 }
 
+/// Per-sender policy of the pool.
+///
+/// The sender of a transaction is identified by a prefix of its first `provides` tag,
+/// e.g. the account id of the `(AccountId, Index)` tag provided by `CheckNonce`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(not(target_os = "unknown"), derive(parity_util_mem::MallocSizeOf))]
+pub struct SenderPolicy {
+	/// Length of the `provides` tag prefix identifying the sender, if senders are tracked.
+	pub sender_tag_prefix: Option<usize>,
+	/// Maximal number of transactions of a single sender in the pool.
+	pub max_per_sender: Option<usize>,
+	/// Minimal priority increase in percent required to replace a transaction.
+	pub min_replacement_bump: u8,
+}
+
 /// Queue limits
 #[derive(Debug, Clone)]
 pub struct Limit {
@@ -1201,4 +1262,118 @@ source: TransactionSource::External, requires: [03,02], provides: [04], data: [4
 		assert_eq!(pool.reject_future_transactions, true);
 		assert_eq!(pool.future.len(), 1);
 	}
+
+	fn sender_tx(hash: Hash, priority: u64, requires: Vec<Tag>, provides: Vec<Tag>) -> Transaction<Hash, Vec<u8>> {
+		Transaction {
+			data: vec![hash as u8],
+			bytes: 1,
+			hash,
+			priority,
+			valid_till: 64u64,
+			requires,
+			provides,
+			propagate: true,
+			source: Source::External,
+		}
+	}
+
+	#[test]
+	fn should_limit_transactions_per_sender() {
+		// given
+		let mut pool = BasePool::with_sender_policy(false, SenderPolicy {
+			sender_tag_prefix: Some(1),
+			max_per_sender: Some(2),
+			min_replacement_bump: 0,
+		});
+		pool.import(sender_tx(1, 5, vec![], vec![vec![1, 0]])).unwrap();
+		pool.import(sender_tx(2, 5, vec![vec![1, 0]], vec![vec![1, 1]])).unwrap();
+
+		// when
+		let err = pool.import(sender_tx(3, 5, vec![vec![1, 1]], vec![vec![1, 2]])).unwrap_err();
+		let future_err = pool.import(sender_tx(4, 5, vec![vec![1, 5]], vec![vec![1, 6]])).unwrap_err();
+
+		// then
+		assert!(matches!(err, error::Error::TooManyFromSender));
+		assert!(matches!(future_err, error::Error::TooManyFromSender));
+		// other senders are not affected
+		pool.import(sender_tx(5, 5, vec![], vec![vec![2, 0]])).unwrap();
+		// and replacements are still possible
+		pool.import(sender_tx(6, 10, vec![vec![1, 0]], vec![vec![1, 1]])).unwrap();
+		let mut ready = pool.ready().map(|tx| tx.hash).collect::<Vec<_>>();
+		ready.sort();
+		assert_eq!(ready, vec![1, 5, 6]);
+	}
+
+	#[test]
+	fn should_require_minimal_priority_bump_for_replacement() {
+		// given
+		let mut pool = BasePool::with_sender_policy(false, SenderPolicy {
+			min_replacement_bump: 10,
+			..Default::default()
+		});
+		pool.import(sender_tx(1, 100, vec![], vec![vec![1, 0]])).unwrap();
+
+		// when
+		let err = pool.import(sender_tx(2, 110, vec![], vec![vec![1, 0]])).unwrap_err();
+
+		// then
+		assert!(matches!(err, error::Error::TooLowPriority { old: 100, new: 110 }));
+		pool.import(sender_tx(3, 111, vec![], vec![vec![1, 0]])).unwrap();
+		assert_eq!(pool.ready().map(|tx| tx.hash).collect::<Vec<_>>(), vec![3]);
+	}
+
+	#[test]
+	fn should_evict_lowest_priority_transaction_of_largest_sender() {
+		// given
+		let mut pool = BasePool::with_sender_policy(false, SenderPolicy {
+			sender_tag_prefix: Some(1),
+			..Default::default()
+		});
+		pool.import(sender_tx(1, 1, vec![], vec![vec![2, 0]])).unwrap();
+		pool.import(sender_tx(2, 5, vec![], vec![vec![1, 0]])).unwrap();
+		pool.import(sender_tx(3, 3, vec![], vec![vec![1, 1]])).unwrap();
+		pool.import(sender_tx(4, 3, vec![], vec![vec![1, 2]])).unwrap();
+
+		// when
+		let removed = pool.enforce_limits(
+			&Limit { count: 2, total_bytes: 1024 },
+			&Limit { count: 16, total_bytes: 1024 },
+		);
+
+		// then
+		assert_eq!(removed.iter().map(|tx| tx.hash).collect::<Vec<_>>(), vec![4, 3]);
+		let mut ready = pool.ready().map(|tx| tx.hash).collect::<Vec<_>>();
+		ready.sort();
+		assert_eq!(ready, vec![1, 2]);
+	}
+
+	#[test]
+	fn should_track_senders_across_promotion_and_pruning() {
+		// given
+		let mut pool = BasePool::with_sender_policy(false, SenderPolicy {
+			sender_tag_prefix: Some(1),
+			max_per_sender: Some(2),
+			min_replacement_bump: 0,
+		});
+		pool.import(sender_tx(1, 5, vec![vec![1, 0]], vec![vec![1, 1]])).unwrap();
+		pool.import(sender_tx(2, 5, vec![], vec![vec![1, 0]])).unwrap();
+		assert_eq!(pool.ready().count(), 2);
+		assert_eq!(pool.ready.senders().count(&[1]), 2);
+		assert_eq!(pool.future.senders().count(&[1]), 0);
+
+		// when
+		pool.prune_tags(vec![vec![1, 0]]);
+		pool.clear_future();
+
+		// then
+		assert_eq!(pool.ready.senders().count(&[1]), 1);
+		pool.import(sender_tx(3, 5, vec![vec![1, 1]], vec![vec![1, 2]])).unwrap();
+		let err = pool.import(sender_tx(4, 5, vec![vec![1, 2]], vec![vec![1, 3]])).unwrap_err();
+		assert!(matches!(err, error::Error::TooManyFromSender));
+
+		pool.remove_subtree(&[1]);
+		assert_eq!(pool.ready.senders().count(&[1]), 0);
+		pool.import(sender_tx(4, 5, vec![vec![1, 2]], vec![vec![1, 3]])).unwrap();
+		assert_eq!(pool.future.senders().count(&[1]), 1);
+	}
 }
@@ -29,7 +29,7 @@ use sp_runtime::transaction_validity::{
 };
 use wasm_timer::Instant;
 
-use crate::base_pool::Transaction;
+use crate::{base_pool::Transaction, senders::Senders};
 
 #[cfg_attr(not(target_os = "unknown"), derive(parity_util_mem::MallocSizeOf))]
 /// Transaction with partially satisfied dependencies.
@@ -119,6 +119,10 @@ pub struct FutureTransactions<Hash: hash::Hash + Eq, Ex> {
 	wanted_tags: HashMap<Tag, HashSet<Hash>>,
 	/// Transactions waiting for a particular other transaction
 	waiting: HashMap<Hash, WaitingTransaction<Hash, Ex>>,
+	/// Import id of the last imported transaction
+	import_id: u64,
+	/// Waiting transactions indexed by their sender.
+	senders: Senders<Hash>,
 }
 
 impl<Hash: hash::Hash + Eq, Ex> Default for FutureTransactions<Hash, Ex> {
@@ -126,6 +130,8 @@ impl<Hash: hash::Hash + Eq, Ex> Default for FutureTransactions<Hash, Ex> {
 		FutureTransactions {
 			wanted_tags: Default::default(),
 			waiting: Default::default(),
+			import_id: 0,
+			senders: Default::default(),
 		}
 	}
 }
@@ -138,6 +144,16 @@ qed
 #";
 
 impl<Hash: hash::Hash + Eq + Clone, Ex> FutureTransactions<Hash, Ex> {
+	/// Set the length of the `provides` tag prefix identifying the sender of a transaction.
+	pub fn set_sender_tag_prefix(&mut self, prefix: Option<usize>) {
+		self.senders.set_prefix(prefix);
+	}
+
+	/// Borrows the index of the transactions in this queue by their sender.
+	pub fn senders(&self) -> &Senders<Hash> {
+		&self.senders
+	}
+
 	/// Import transaction to Future queue.
 	///
 	/// Only transactions that don't have all their tags satisfied should occupy
@@ -155,6 +171,8 @@ impl<Hash: hash::Hash + Eq + Clone, Ex> FutureTransactions<Hash, Ex> {
 		}
 
 		// Add the transaction to a by-hash waiting map
+		self.import_id += 1;
+		self.senders.insert(&tx.transaction, self.import_id);
 		self.waiting.insert(tx.transaction.hash.clone(), tx);
 	}
 
@@ -186,6 +204,7 @@ impl<Hash: hash::Hash + Eq + Clone, Ex> FutureTransactions<Hash, Ex> {
 
 					if is_ready {
 						let tx = self.waiting.remove(&hash).expect(WAITING_PROOF);
+						self.senders.remove(&tx.transaction);
 						became_ready.push(tx);
 					}
 				}
@@ -202,6 +221,7 @@ impl<Hash: hash::Hash + Eq + Clone, Ex> FutureTransactions<Hash, Ex> {
 		let mut removed = vec![];
 		for hash in hashes {
 			if let Some(waiting_tx) = self.waiting.remove(hash) {
+				self.senders.remove(&waiting_tx.transaction);
 				// remove from wanted_tags as well
 				for tag in waiting_tx.missing_tags {
 					let remove = if let Some(wanted) = self.wanted_tags.get_mut(&tag) {
@@ -234,6 +254,7 @@ impl<Hash: hash::Hash + Eq + Clone, Ex> FutureTransactions<Hash, Ex> {
 	/// Removes and returns all future transactions.
 	pub fn clear(&mut self) -> Vec<Arc<Transaction<Hash, Ex>>> {
 		self.wanted_tags.clear();
+		self.senders.clear();
 		self.waiting.drain().map(|(_, tx)| tx.transaction).collect()
 	}
 
@@ -31,14 +31,15 @@ mod listener;
 mod pool;
 mod ready;
 mod rotator;
+mod senders;
 mod validated_pool;
 mod tracked_map;
 
 pub mod base_pool;
 pub mod watcher;
 
-pub use self::base_pool::Transaction;
+pub use self::base_pool::{Transaction, SenderPolicy};
 pub use self::pool::{
-	Pool, Options, ChainApi, EventStream, ExtrinsicFor, ExtrinsicHash,
+	Pool, Options, JournalOptions, ChainApi, EventStream, ExtrinsicFor, ExtrinsicHash,
 	BlockHash, NumberFor, TransactionFor, ValidatedTransaction,
 };
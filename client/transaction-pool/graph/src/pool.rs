@@ -107,6 +107,21 @@ pub struct Options {
 	pub future: base::Limit,
 	/// Reject future transactions.
 	pub reject_future_transactions: bool,
+	/// Per-sender limits and replacement policy.
+	pub sender_policy: base::SenderPolicy,
+	/// Persist the pooled transactions across restarts.
+	pub journal: Option<JournalOptions>,
+}
+
+/// Transaction journal configuration options.
+#[derive(Debug, Clone)]
+pub struct JournalOptions {
+	/// Path of the journal file.
+	pub path: std::path::PathBuf,
+	/// Maximum number of transactions kept in the journal.
+	pub max_transactions: usize,
+	/// Transactions first seen longer ago than this are not restored.
+	pub max_age: std::time::Duration,
 }
 
 impl Default for Options {
@@ -121,6 +136,8 @@ impl Default for Options {
 				total_bytes: 1 * 1024 * 1024,
 			},
 			reject_future_transactions: false,
+			sender_policy: Default::default(),
+			journal: None,
 		}
 	}
 }
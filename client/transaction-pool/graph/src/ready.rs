@@ -34,6 +34,7 @@ use sp_transaction_pool::error;
 use crate::{
 	base_pool::Transaction,
 	future::WaitingTransaction,
+	senders::Senders,
 	tracked_map::{self, ReadOnlyTrackedMap, TrackedMap},
 };
 
@@ -118,6 +119,10 @@ pub struct ReadyTransactions<Hash: hash::Hash + Eq, Ex> {
 	ready: TrackedMap<Hash, ReadyTx<Hash, Ex>>,
 	/// Best transactions that are ready to be included to the block without any other previous transaction.
 	best: BTreeSet<TransactionRef<Hash, Ex>>,
+	/// Minimum priority increase in percent for a transaction to replace others.
+	min_replacement_bump: u64,
+	/// Ready transactions indexed by their sender.
+	senders: Senders<Hash>,
 }
 
 impl<Hash, Ex> tracked_map::Size for ReadyTx<Hash, Ex> {
@@ -133,11 +138,28 @@ impl<Hash: hash::Hash + Eq, Ex> Default for ReadyTransactions<Hash, Ex> {
 			provided_tags: Default::default(),
 			ready: Default::default(),
 			best: Default::default(),
+			min_replacement_bump: 0,
+			senders: Default::default(),
 		}
 	}
 }
 
 impl<Hash: hash::Hash + Member + Serialize, Ex> ReadyTransactions<Hash, Ex> {
+	/// Set the minimum priority increase in percent for a transaction to replace others.
+	pub fn set_min_replacement_bump(&mut self, percent: u8) {
+		self.min_replacement_bump = percent.into();
+	}
+
+	/// Set the length of the `provides` tag prefix identifying the sender of a transaction.
+	pub fn set_sender_tag_prefix(&mut self, prefix: Option<usize>) {
+		self.senders.set_prefix(prefix);
+	}
+
+	/// Borrows the index of the transactions in this queue by their sender.
+	pub fn senders(&self) -> &Senders<Hash> {
+		&self.senders
+	}
+
 	/// Borrows a map of tags that are provided by transactions in this queue.
 	pub fn provided_tags(&self) -> &HashMap<Tag, Hash> {
 		&self.provided_tags
@@ -218,6 +240,7 @@ impl<Hash: hash::Hash + Member + Serialize, Ex> ReadyTransactions<Hash, Ex> {
 		}
 
 		// insert to Ready
+		self.senders.insert(&transaction.transaction, insertion_id);
 		ready.insert(hash, ReadyTx {
 			transaction,
 			unlocks,
@@ -277,6 +300,7 @@ impl<Hash: hash::Hash + Member + Serialize, Ex> ReadyTransactions<Hash, Ex> {
 		let mut ready = self.ready.write();
 		while let Some(hash) = to_remove.pop() {
 			if let Some(mut tx) = ready.remove(&hash) {
+				self.senders.remove(&tx.transaction.transaction);
 				let invalidated = tx.transaction.transaction.provides
 					.iter()
 					.filter(|tag| provides_tag_filter
@@ -332,6 +356,7 @@ impl<Hash: hash::Hash + Member + Serialize, Ex> ReadyTransactions<Hash, Ex> {
 				.and_then(|hash| self.ready.write().remove(&hash));
 
 			if let Some(tx) = res {
+				self.senders.remove(&tx.transaction.transaction);
 				let unlocks = tx.unlocks;
 
 				// Make sure we remove it from best txs
@@ -441,7 +466,10 @@ impl<Hash: hash::Hash + Member + Serialize, Ex> ReadyTransactions<Hash, Ex> {
 			};
 
 			// bail - the transaction has too low priority to replace the old ones
-			if old_priority >= tx.priority {
+			let required_priority = old_priority.saturating_add(
+				old_priority.saturating_mul(self.min_replacement_bump) / 100
+			);
+			if required_priority >= tx.priority {
 				return Err(error::Error::TooLowPriority { old: old_priority, new: tx.priority })
 			}
 
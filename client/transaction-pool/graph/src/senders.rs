@@ -0,0 +1,172 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2018-2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Index of the transactions of a queue by their sender.
+
+use std::{collections::HashMap, hash};
+
+use sp_runtime::transaction_validity::TransactionPriority as Priority;
+
+use crate::base_pool::Transaction;
+
+/// Transactions of a queue indexed by their sender.
+///
+/// The sender of a transaction is identified by a prefix of its first `provides` tag, see
+/// [`crate::SenderPolicy`]. Transactions are only indexed once a prefix is set.
+#[derive(Debug, parity_util_mem::MallocSizeOf)]
+pub struct Senders<Hash: hash::Hash + Eq> {
+	/// Length of the `provides` tag prefix identifying the sender.
+	prefix: Option<usize>,
+	/// The priority and the position in the queue of the transactions of each sender.
+	by_sender: HashMap<Vec<u8>, HashMap<Hash, (Priority, u64)>>,
+}
+
+impl<Hash: hash::Hash + Eq> Default for Senders<Hash> {
+	fn default() -> Self {
+		Senders {
+			prefix: None,
+			by_sender: Default::default(),
+		}
+	}
+}
+
+impl<Hash: hash::Hash + Eq + Clone> Senders<Hash> {
+	/// Set the length of the `provides` tag prefix identifying the sender.
+	///
+	/// NOTE: Should be set before any transaction is indexed.
+	pub fn set_prefix(&mut self, prefix: Option<usize>) {
+		debug_assert!(self.by_sender.is_empty(), "The prefix is set before indexing transactions; qed");
+		self.prefix = prefix;
+	}
+
+	/// Returns the sender of the transaction, if senders are tracked.
+	///
+	/// Transactions that don't provide any tag share the empty sender.
+	pub fn sender_of<'a, Ex>(&self, tx: &'a Transaction<Hash, Ex>) -> Option<&'a [u8]> {
+		let prefix = self.prefix?;
+		Some(tx.provides.first().map(|tag| &tag[..prefix.min(tag.len())]).unwrap_or(&[]))
+	}
+
+	/// Index the transaction at the given position in the queue.
+	pub fn insert<Ex>(&mut self, tx: &Transaction<Hash, Ex>, order: u64) {
+		if let Some(sender) = self.sender_of(tx) {
+			self.by_sender
+				.entry(sender.to_vec())
+				.or_default()
+				.insert(tx.hash.clone(), (tx.priority, order));
+		}
+	}
+
+	/// Remove the transaction from the index.
+	pub fn remove<Ex>(&mut self, tx: &Transaction<Hash, Ex>) {
+		if let Some(sender) = self.sender_of(tx) {
+			let is_empty = match self.by_sender.get_mut(sender) {
+				Some(transactions) => {
+					transactions.remove(&tx.hash);
+					transactions.is_empty()
+				},
+				None => false,
+			};
+			if is_empty {
+				self.by_sender.remove(sender);
+			}
+		}
+	}
+
+	/// Remove all transactions from the index.
+	pub fn clear(&mut self) {
+		self.by_sender.clear();
+	}
+
+	/// Returns the number of transactions of the sender.
+	pub fn count(&self, sender: &[u8]) -> usize {
+		self.by_sender.get(sender).map_or(0, HashMap::len)
+	}
+
+	/// Returns the lowest priority transaction of the sender with the most transactions.
+	///
+	/// Ties in the number of transactions are broken by picking the smallest sender, ties in
+	/// priority by picking the transaction that is last in the queue.
+	pub fn worst_of_largest(&self) -> Option<&Hash> {
+		self.by_sender
+			.iter()
+			.max_by(|(a_sender, a), (b_sender, b)| a.len().cmp(&b.len()).then_with(|| b_sender.cmp(a_sender)))
+			.and_then(|(_, transactions)| transactions
+				.iter()
+				.min_by(|(_, (a_priority, a_order)), (_, (b_priority, b_order))|
+					a_priority.cmp(b_priority).then_with(|| b_order.cmp(a_order))
+				)
+			)
+			.map(|(hash, _)| hash)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_runtime::transaction_validity::TransactionSource;
+
+	fn tx(hash: u64, priority: Priority, provides: Vec<u8>) -> Transaction<u64, ()> {
+		Transaction {
+			data: (),
+			bytes: 1,
+			hash,
+			priority,
+			valid_till: 64,
+			requires: vec![],
+			provides: vec![provides],
+			propagate: true,
+			source: TransactionSource::External,
+		}
+	}
+
+	#[test]
+	fn should_count_and_pick_the_worst_of_the_largest_sender() {
+		let mut senders = Senders::default();
+		senders.set_prefix(Some(1));
+		senders.insert(&tx(1, 5, vec![1, 0]), 1);
+		senders.insert(&tx(2, 3, vec![1, 1]), 2);
+		senders.insert(&tx(3, 3, vec![1, 2]), 3);
+		senders.insert(&tx(4, 1, vec![2, 0]), 4);
+
+		assert_eq!(senders.count(&[1]), 3);
+		assert_eq!(senders.count(&[2]), 1);
+		assert_eq!(senders.count(&[3]), 0);
+		// lowest priority of sender 1, the last in the queue on ties
+		assert_eq!(senders.worst_of_largest(), Some(&3));
+
+		senders.remove(&tx(3, 3, vec![1, 2]));
+		senders.remove(&tx(2, 3, vec![1, 1]));
+		assert_eq!(senders.count(&[1]), 1);
+		// ties between senders pick the smallest one
+		assert_eq!(senders.worst_of_largest(), Some(&1));
+
+		senders.remove(&tx(1, 5, vec![1, 0]));
+		assert_eq!(senders.worst_of_largest(), Some(&4));
+		senders.clear();
+		assert_eq!(senders.worst_of_largest(), None);
+	}
+
+	#[test]
+	fn should_not_index_without_prefix() {
+		let mut senders = Senders::default();
+		senders.insert(&tx(1, 5, vec![1, 0]), 1);
+		assert_eq!(senders.sender_of(&tx(1, 5, vec![1, 0])), None);
+		assert_eq!(senders.worst_of_largest(), None);
+	}
+}
@@ -117,7 +117,10 @@ where
 impl<B: ChainApi> ValidatedPool<B> {
 	/// Create a new transaction pool.
 	pub fn new(options: Options, api: Arc<B>) -> Self {
-		let base_pool = base::BasePool::new(options.reject_future_transactions);
+		let base_pool = base::BasePool::with_sender_policy(
+			options.reject_future_transactions,
+			options.sender_policy.clone(),
+		);
 		ValidatedPool {
 			options,
 			listener: Default::default(),
@@ -591,6 +594,13 @@ impl<B: ChainApi> ValidatedPool<B> {
 		self.pool.read().ready()
 	}
 
+	/// Get the future transactions, as `(hash, source, extrinsic)`.
+	pub fn futures(&self) -> Vec<(ExtrinsicHash<B>, TransactionSource, ExtrinsicFor<B>)> {
+		self.pool.read().futures()
+			.map(|tx| (tx.hash.clone(), tx.source, tx.data.clone()))
+			.collect()
+	}
+
 	/// Returns pool status.
 	pub fn status(&self) -> PoolStatus {
 		self.pool.read().status()
@@ -0,0 +1,222 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Journal persisting the pooled transactions across restarts.
+
+use std::{
+	collections::{HashMap, HashSet},
+	fs,
+	hash,
+	io,
+	time::{SystemTime, UNIX_EPOCH},
+};
+
+use codec::{Decode, Encode};
+use sc_transaction_graph::JournalOptions;
+use sp_runtime::transaction_validity::TransactionSource;
+
+/// A transaction as stored in the journal.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct JournalEntry<Hash> {
+	/// Hash of the transaction.
+	pub hash: Hash,
+	/// Source the transaction was originally submitted from.
+	pub source: TransactionSource,
+	/// The encoded extrinsic.
+	pub extrinsic: Vec<u8>,
+	/// When the transaction was first seen, in seconds since the UNIX epoch.
+	pub first_seen: u64,
+}
+
+/// Journal of the transactions in the pool.
+///
+/// The journal is a snapshot of the pool rather than a log: it is rewritten as a whole on every
+/// [`Journal::sync`], which keeps the file bounded by `max_transactions` without compaction.
+pub struct Journal<Hash> {
+	options: JournalOptions,
+	/// When the transactions of the last snapshot were first seen.
+	first_seen: HashMap<Hash, u64>,
+	/// Transactions loaded from disk that were not yet restored into the pool.
+	pending: Vec<JournalEntry<Hash>>,
+}
+
+fn now() -> u64 {
+	SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default()
+}
+
+impl<Hash: hash::Hash + Eq + Clone + Encode + Decode> Journal<Hash> {
+	/// Open the journal, loading the transactions persisted by a previous run.
+	///
+	/// A missing or corrupted journal file is treated as empty.
+	pub fn open(options: JournalOptions) -> Self {
+		let pending = match fs::read(&options.path) {
+			Ok(bytes) => Vec::<JournalEntry<Hash>>::decode(&mut &bytes[..]).unwrap_or_else(|e| {
+				log::warn!(
+					target: "txpool",
+					"Ignoring corrupted transaction journal {}: {:?}",
+					options.path.display(),
+					e,
+				);
+				Vec::new()
+			}),
+			Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+			Err(e) => {
+				log::warn!(
+					target: "txpool",
+					"Failed to read transaction journal {}: {:?}",
+					options.path.display(),
+					e,
+				);
+				Vec::new()
+			}
+		};
+
+		let mut journal = Journal {
+			options,
+			first_seen: HashMap::new(),
+			pending: Vec::new(),
+		};
+		journal.pending = journal.limit(pending, now());
+		journal.first_seen = journal.pending.iter()
+			.map(|entry| (entry.hash.clone(), entry.first_seen))
+			.collect();
+		log::debug!(target: "txpool", "Loaded {} transactions from the journal", journal.pending.len());
+		journal
+	}
+
+	/// Take the transactions that still need to be restored into the pool.
+	pub fn take_pending(&mut self) -> Vec<JournalEntry<Hash>> {
+		std::mem::take(&mut self.pending)
+	}
+
+	/// Replace the journal with the given pooled transactions and write it to disk.
+	///
+	/// Transactions that are still waiting to be restored are kept.
+	pub fn sync(&mut self, pooled: impl IntoIterator<Item=(Hash, TransactionSource, Vec<u8>)>) {
+		let now = now();
+		let mut entries = pooled.into_iter()
+			.filter(|(_, source, _)| *source != TransactionSource::InBlock)
+			.map(|(hash, source, extrinsic)| {
+				let first_seen = self.first_seen.get(&hash).copied().unwrap_or(now);
+				JournalEntry { hash, source, extrinsic, first_seen }
+			})
+			.collect::<Vec<_>>();
+		let pooled = entries.iter().map(|entry| entry.hash.clone()).collect::<HashSet<_>>();
+		entries.extend(self.pending.iter().filter(|entry| !pooled.contains(&entry.hash)).cloned());
+		let entries = self.limit(entries, now);
+
+		self.first_seen = entries.iter()
+			.map(|entry| (entry.hash.clone(), entry.first_seen))
+			.collect();
+		if let Err(e) = self.write(&entries) {
+			log::warn!(
+				target: "txpool",
+				"Failed to write transaction journal {}: {:?}",
+				self.options.path.display(),
+				e,
+			);
+		}
+	}
+
+	/// Drop the entries that are too old, keeping at most `max_transactions` of the newest ones.
+	fn limit(&self, mut entries: Vec<JournalEntry<Hash>>, now: u64) -> Vec<JournalEntry<Hash>> {
+		let max_age = self.options.max_age.as_secs();
+		entries.retain(|entry| now.saturating_sub(entry.first_seen) <= max_age);
+		entries.sort_by(|a, b| b.first_seen.cmp(&a.first_seen));
+		entries.truncate(self.options.max_transactions);
+		entries
+	}
+
+	/// Write the entries to a temporary file, then move it over the journal.
+	fn write(&self, entries: &[JournalEntry<Hash>]) -> io::Result<()> {
+		if let Some(parent) = self.options.path.parent() {
+			fs::create_dir_all(parent)?;
+		}
+		let tmp = self.options.path.with_extension("tmp");
+		fs::write(&tmp, entries.encode())?;
+		fs::rename(&tmp, &self.options.path)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::time::Duration;
+
+	fn options(dir: &std::path::Path, max_transactions: usize) -> JournalOptions {
+		JournalOptions {
+			path: dir.join("txpool").join("journal"),
+			max_transactions,
+			max_age: Duration::from_secs(3600),
+		}
+	}
+
+	#[test]
+	fn should_restore_synced_transactions() {
+		let dir = tempfile::tempdir().unwrap();
+		let mut journal = Journal::<u64>::open(options(dir.path(), 10));
+		assert!(journal.take_pending().is_empty());
+
+		journal.sync(vec![
+			(1, TransactionSource::External, vec![1]),
+			(2, TransactionSource::InBlock, vec![2]),
+			(3, TransactionSource::Local, vec![3]),
+		]);
+
+		let mut journal = Journal::<u64>::open(options(dir.path(), 10));
+		let mut pending = journal.take_pending();
+		pending.sort_by_key(|entry| entry.hash);
+		assert_eq!(
+			pending.iter().map(|e| (e.hash, e.source, e.extrinsic.clone())).collect::<Vec<_>>(),
+			vec![(1, TransactionSource::External, vec![1]), (3, TransactionSource::Local, vec![3])],
+		);
+	}
+
+	#[test]
+	fn should_limit_size_and_age() {
+		let dir = tempfile::tempdir().unwrap();
+		let options = options(dir.path(), 2);
+		let now = now();
+		let entry = |hash, age| JournalEntry {
+			hash,
+			source: TransactionSource::External,
+			extrinsic: vec![hash as u8],
+			first_seen: now - age,
+		};
+		fs::create_dir_all(options.path.parent().unwrap()).unwrap();
+		fs::write(
+			&options.path,
+			vec![entry(1, 10), entry(2, 7200), entry(3, 20), entry(4, 5)].encode(),
+		).unwrap();
+
+		let mut journal = Journal::<u64>::open(options);
+		let pending = journal.take_pending();
+		assert_eq!(pending.iter().map(|e| e.hash).collect::<Vec<_>>(), vec![4, 1]);
+	}
+
+	#[test]
+	fn should_ignore_corrupted_journal() {
+		let dir = tempfile::tempdir().unwrap();
+		let options = options(dir.path(), 10);
+		fs::create_dir_all(options.path.parent().unwrap()).unwrap();
+		fs::write(&options.path, &[0xff]).unwrap();
+
+		let mut journal = Journal::<u64>::open(options);
+		assert!(journal.take_pending().is_empty());
+	}
+}
@@ -23,6 +23,7 @@
 #![warn(unused_extern_crates)]
 
 mod api;
+mod journal;
 mod revalidation;
 mod metrics;
 
@@ -34,7 +35,9 @@ pub mod testing;
 pub use sc_transaction_graph as txpool;
 pub use crate::api::{FullChainApi, LightChainApi};
 
-use std::{collections::{HashMap, HashSet}, sync::Arc, pin::Pin, convert::TryInto};
+use std::{
+	collections::{HashMap, HashSet}, sync::{Arc, Weak}, pin::Pin, convert::TryInto, time::Duration,
+};
 use futures::{prelude::*, future::{self, ready}, channel::oneshot};
 use parking_lot::Mutex;
 
@@ -49,10 +52,12 @@ use sp_transaction_pool::{
 	TransactionSource,
 };
 use sc_transaction_graph::{ChainApi, ExtrinsicHash};
+use codec::{Decode, Encode};
 use wasm_timer::Instant;
 
 use prometheus_endpoint::Registry as PrometheusRegistry;
 use crate::metrics::MetricsLink as PrometheusMetrics;
+use crate::journal::Journal;
 
 type BoxedReadyIterator<Hash, Data> = Box<
 	dyn Iterator<Item=Arc<sc_transaction_graph::base_pool::Transaction<Hash, Data>>> + Send
@@ -64,6 +69,12 @@ type ReadyIteratorFor<PoolApi> = BoxedReadyIterator<
 
 type PolledIterator<PoolApi> = Pin<Box<dyn Future<Output=ReadyIteratorFor<PoolApi>> + Send>>;
 
+/// How often the pooled transactions are written to the journal, in addition to the shutdown of
+/// the pool.
+///
+/// The journal is not written on new best blocks, so that block import never waits on the disk.
+const JOURNAL_SYNC_INTERVAL: Duration = Duration::from_secs(10);
+
 /// A transaction pool for a full node.
 pub type FullPool<Block, Client> = BasicPool<FullChainApi<Client, Block>, Block>;
 /// A transaction pool for a light node.
@@ -81,6 +92,7 @@ pub struct BasicPool<PoolApi, Block>
 	revalidation_queue: Arc<revalidation::RevalidationQueue<PoolApi>>,
 	ready_poll: Arc<Mutex<ReadyPoll<ReadyIteratorFor<PoolApi>, Block>>>,
 	metrics: PrometheusMetrics,
+	journal: Option<Arc<Mutex<Journal<ExtrinsicHash<PoolApi>>>>>,
 }
 
 struct ReadyPoll<T, Block: BlockT> {
@@ -174,6 +186,7 @@ impl<PoolApi, Block> BasicPool<PoolApi, Block>
 				revalidation_strategy: Arc::new(Mutex::new(RevalidationStrategy::Always)),
 				ready_poll: Default::default(),
 				metrics: Default::default(),
+				journal: None,
 			},
 			background_task,
 			notifier,
@@ -182,6 +195,8 @@ impl<PoolApi, Block> BasicPool<PoolApi, Block>
 
 	/// Create new basic transaction pool with provided api and custom
 	/// revalidation type.
+	///
+	/// Transactions of the journal are restored on the first new best block.
 	pub fn with_revalidation_type(
 		options: sc_transaction_graph::Options,
 		pool_api: Arc<PoolApi>,
@@ -189,7 +204,48 @@ impl<PoolApi, Block> BasicPool<PoolApi, Block>
 		revalidation_type: RevalidationType,
 		spawner: impl SpawnNamed,
 	) -> Self {
+		Self::with_journal_restored_at(options, pool_api, prometheus, revalidation_type, spawner, None)
+	}
+
+	/// Create new basic transaction pool, restoring the transactions of the journal at the given
+	/// block right away.
+	fn with_journal_restored_at(
+		options: sc_transaction_graph::Options,
+		pool_api: Arc<PoolApi>,
+		prometheus: Option<&PrometheusRegistry>,
+		revalidation_type: RevalidationType,
+		spawner: impl SpawnNamed,
+		restore_at: Option<BlockId<Block>>,
+	) -> Self {
+		let journal = options.journal.clone()
+			.map(|options| Arc::new(Mutex::new(Journal::open(options))));
 		let pool = Arc::new(sc_transaction_graph::Pool::new(options, pool_api.clone()));
+
+		if let Some(journal) = &journal {
+			if let Some(at) = restore_at {
+				let journaled = journal.lock().take_pending();
+				let api = pool_api.clone();
+				let pool = pool.clone();
+				spawner.spawn("txpool-journal-restore", async move {
+					match api.block_id_to_number(&at) {
+						Ok(Some(number)) if !journaled.is_empty() =>
+							restore_journaled_txs(at, number, &*api, &*pool, journaled).await,
+						Ok(Some(_)) => {},
+						_ => log::warn!(
+							target: "txpool",
+							"Cannot restore journaled transactions - no number for block {:?}",
+							at,
+						),
+					}
+				}.boxed());
+			}
+			// Writing the journal blocks on the disk.
+			spawner.spawn_blocking("txpool-journal", sync_journal_periodically(
+				Arc::downgrade(&pool),
+				Arc::downgrade(journal),
+			).boxed());
+		}
+
 		let (revalidation_queue, background_task) = match revalidation_type {
 			RevalidationType::Light => (revalidation::RevalidationQueue::new(pool_api.clone(), pool.clone()), None),
 			RevalidationType::Full => {
@@ -214,6 +270,7 @@ impl<PoolApi, Block> BasicPool<PoolApi, Block>
 			)),
 			ready_poll: Default::default(),
 			metrics: PrometheusMetrics::new(prometheus),
+			journal,
 		}
 	}
 
@@ -244,9 +301,19 @@ impl<PoolApi, Block> TransactionPool for BasicPool<PoolApi, Block>
 		let pool = self.pool.clone();
 		let at = *at;
 
+		let metrics = self.metrics.clone();
+
 		self.metrics.report(|metrics| metrics.submitted_transactions.inc_by(xts.len() as u64));
 
-		async move { pool.submit_at(&at, source, xts).await }.boxed()
+		async move {
+			pool.submit_at(&at, source, xts)
+				.map(|result| result.map(|results| results
+					.into_iter()
+					.map(|result| result.map_err(|e| metrics.report_rejected(e)))
+					.collect()
+				))
+				.await
+		}.boxed()
 	}
 
 	fn submit_one(
@@ -258,9 +325,15 @@ impl<PoolApi, Block> TransactionPool for BasicPool<PoolApi, Block>
 		let pool = self.pool.clone();
 		let at = *at;
 
+		let metrics = self.metrics.clone();
+
 		self.metrics.report(|metrics| metrics.submitted_transactions.inc());
 
-		async move { pool.submit_one(&at, source, xt).await }.boxed()
+		async move {
+			pool.submit_one(&at, source, xt)
+				.map(|result| result.map_err(|e| metrics.report_rejected(e)))
+				.await
+		}.boxed()
 	}
 
 	fn submit_and_watch(
@@ -272,11 +345,16 @@ impl<PoolApi, Block> TransactionPool for BasicPool<PoolApi, Block>
 		let at = *at;
 		let pool = self.pool.clone();
 
+		let metrics = self.metrics.clone();
+
 		self.metrics.report(|metrics| metrics.submitted_transactions.inc());
 
 		async move {
 			pool.submit_and_watch(&at, source, xt)
-				.map(|result| result.map(|watcher| Box::new(watcher.into_stream()) as _))
+				.map(|result| result
+					.map(|watcher| Box::new(watcher.into_stream()) as _)
+					.map_err(|e| metrics.report_rejected(e))
+				)
 				.await
 		}.boxed()
 	}
@@ -344,9 +422,10 @@ where
 		client: Arc<Client>,
 		fetcher: Arc<Fetcher>,
 	) -> Self {
+		let best = BlockId::hash(sp_blockchain::HeaderBackend::info(&*client).best_hash);
 		let pool_api = Arc::new(LightChainApi::new(client, fetcher));
-		Self::with_revalidation_type(
-			options, pool_api, prometheus, RevalidationType::Light, spawner,
+		Self::with_journal_restored_at(
+			options, pool_api, prometheus, RevalidationType::Light, spawner, Some(best),
 		)
 	}
 }
@@ -356,7 +435,8 @@ where
 	Block: BlockT,
 	Client: sp_api::ProvideRuntimeApi<Block>
 		+ sc_client_api::BlockBackend<Block>
-		+ sp_runtime::traits::BlockIdTo<Block>,
+		+ sp_runtime::traits::BlockIdTo<Block>
+		+ sp_blockchain::HeaderBackend<Block>,
 	Client: sc_client_api::ExecutorProvider<Block> + Send + Sync + 'static,
 	Client::Api: sp_transaction_pool::runtime_api::TaggedTransactionQueue<Block>,
 	sp_api::ApiErrorFor<Client, Block>: Send + std::fmt::Display,
@@ -368,9 +448,10 @@ where
 		spawner: impl SpawnNamed,
 		client: Arc<Client>,
 	) -> Arc<Self> {
+		let best = BlockId::hash(sp_blockchain::HeaderBackend::info(&*client).best_hash);
 		let pool_api = Arc::new(FullChainApi::new(client.clone(), prometheus));
-		let pool = Arc::new(Self::with_revalidation_type(
-			options, pool_api, prometheus, RevalidationType::Full, spawner
+		let pool = Arc::new(Self::with_journal_restored_at(
+			options, pool_api, prometheus, RevalidationType::Full, spawner, Some(best),
 		));
 
 		// make transaction pool available for off-chain runtime calls.
@@ -542,6 +623,107 @@ async fn prune_known_txs_for_block<Block: BlockT, Api: ChainApi<Block = Block>>(
 	hashes
 }
 
+/// Restore the journaled transactions that are still valid at the given block.
+///
+/// Transactions that were included in the meantime or became invalid fail validation and are
+/// dropped from the journal on its next sync.
+async fn restore_journaled_txs<Api: ChainApi>(
+	block_id: BlockId<Api::Block>,
+	block_number: NumberFor<Api::Block>,
+	api: &Api,
+	pool: &sc_transaction_graph::Pool<Api>,
+	entries: Vec<journal::JournalEntry<ExtrinsicHash<Api>>>,
+) {
+	use sc_transaction_graph::ValidatedTransaction;
+	use sp_runtime::traits::SaturatedConversion;
+
+	let journaled = entries.len();
+	let mut validated = Vec::with_capacity(journaled);
+	for entry in entries {
+		let xt = match sc_transaction_graph::ExtrinsicFor::<Api>::decode(&mut &entry.extrinsic[..]) {
+			Ok(xt) => xt,
+			Err(e) => {
+				log::debug!(target: "txpool", "[{:?}] Undecodable journaled transaction: {:?}", entry.hash, e);
+				continue;
+			}
+		};
+		match api.validate_transaction(&block_id, entry.source, xt.clone()).await {
+			Ok(Ok(validity)) => {
+				let (hash, bytes) = api.hash_and_length(&xt);
+				validated.push(ValidatedTransaction::valid_at(
+					block_number.saturated_into::<u64>(),
+					hash,
+					entry.source,
+					xt,
+					bytes,
+					validity,
+				));
+			},
+			Ok(Err(e)) => log::debug!(
+				target: "txpool",
+				"[{:?}] Dropping journaled transaction: {:?}",
+				entry.hash,
+				e,
+			),
+			Err(e) => log::debug!(
+				target: "txpool",
+				"[{:?}] Error validating journaled transaction: {:?}",
+				entry.hash,
+				e,
+			),
+		}
+	}
+
+	let restored = pool.validated_pool().submit(validated).into_iter().filter(Result::is_ok).count();
+	log::info!(target: "txpool", "Restored {} of {} journaled transactions", restored, journaled);
+}
+
+/// Write the transactions currently in the pool to the journal.
+fn sync_journal<Api: ChainApi>(
+	pool: &sc_transaction_graph::Pool<Api>,
+	journal: &Mutex<Journal<ExtrinsicHash<Api>>>,
+) {
+	let validated_pool = pool.validated_pool();
+	let pooled = validated_pool.ready()
+		.map(|tx| (tx.hash.clone(), tx.source, tx.data.encode()))
+		.chain(
+			validated_pool.futures()
+				.into_iter()
+				.map(|(hash, source, xt)| (hash, source, xt.encode()))
+		);
+	journal.lock().sync(pooled);
+}
+
+/// Write the transactions in the pool to the journal every `JOURNAL_SYNC_INTERVAL`, until the
+/// pool is dropped.
+async fn sync_journal_periodically<Api: ChainApi>(
+	pool: Weak<sc_transaction_graph::Pool<Api>>,
+	journal: Weak<Mutex<Journal<ExtrinsicHash<Api>>>>,
+) {
+	use intervalier::IntoStream;
+
+	let interval = intervalier::Interval::new(JOURNAL_SYNC_INTERVAL).into_stream();
+	futures::pin_mut!(interval);
+	while interval.next().await.is_some() {
+		match (pool.upgrade(), journal.upgrade()) {
+			(Some(pool), Some(journal)) => sync_journal(&*pool, &*journal),
+			_ => break,
+		}
+	}
+}
+
+impl<PoolApi, Block> Drop for BasicPool<PoolApi, Block>
+	where
+		Block: BlockT,
+		PoolApi: ChainApi<Block=Block>,
+{
+	fn drop(&mut self) {
+		if let Some(journal) = &self.journal {
+			sync_journal(&self.pool, journal);
+		}
+	}
+}
+
 impl<PoolApi, Block> MaintainedTransactionPool for BasicPool<PoolApi, Block>
 	where
 		Block: BlockT,
@@ -575,8 +757,18 @@ impl<PoolApi, Block> MaintainedTransactionPool for BasicPool<PoolApi, Block>
 				let revalidation_queue = self.revalidation_queue.clone();
 				let ready_poll = self.ready_poll.clone();
 				let metrics = self.metrics.clone();
+				let journal = self.journal.clone();
 
 				async move {
+					// Transactions of the journal are restored on the first new best block after
+					// the start, as they can only be validated against a known block.
+					let journaled = journal.as_ref()
+						.map(|journal| journal.lock().take_pending())
+						.unwrap_or_default();
+					if !journaled.is_empty() {
+						restore_journaled_txs(id.clone(), block_number, &*api, &*pool, journaled).await;
+					}
+
 					// We keep track of everything we prune so that later we won't add
 					// tranactions with those hashes from the retracted blocks.
 					let mut pruned_log = HashSet::<ExtrinsicHash<PoolApi>>::new();
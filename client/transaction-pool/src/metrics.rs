@@ -20,7 +20,8 @@
 
 use std::sync::Arc;
 
-use prometheus_endpoint::{register, Counter, PrometheusError, Registry, U64};
+use prometheus_endpoint::{register, Counter, CounterVec, Opts, PrometheusError, Registry, U64};
+use sp_transaction_pool::error::{Error as TxPoolError, IntoPoolError};
 
 #[derive(Clone, Default)]
 pub struct MetricsLink(Arc<Option<Metrics>>);
@@ -41,6 +42,42 @@ impl MetricsLink {
 			do_this(metrics);
 		}
 	}
+
+	/// Report the reason a submitted transaction was rejected for, passing the error through.
+	pub fn report_rejected<E: IntoPoolError + From<TxPoolError>>(&self, error: E) -> E {
+		if self.0.is_none() {
+			return error
+		}
+		match error.into_pool_error() {
+			Ok(error) => {
+				self.report(|metrics| {
+					metrics.rejected_transactions.with_label_values(&[rejection_reason(&error)]).inc()
+				});
+				error.into()
+			},
+			Err(error) => {
+				self.report(|metrics| metrics.rejected_transactions.with_label_values(&["other"]).inc());
+				error
+			},
+		}
+	}
+}
+
+/// The label of the rejection reason of a transaction.
+fn rejection_reason(error: &TxPoolError) -> &'static str {
+	match error {
+		TxPoolError::UnknownTransaction(_) => "unknown",
+		TxPoolError::InvalidTransaction(_) => "invalid",
+		TxPoolError::NoTagsProvided => "no_tags_provided",
+		TxPoolError::TemporarilyBanned => "temporarily_banned",
+		TxPoolError::AlreadyImported(_) => "already_imported",
+		TxPoolError::TooLowPriority { .. } => "too_low_priority",
+		TxPoolError::CycleDetected => "cycle_detected",
+		TxPoolError::ImmediatelyDropped => "immediately_dropped",
+		TxPoolError::InvalidBlockId(_) => "invalid_block_id",
+		TxPoolError::RejectedFutureTransaction => "future_rejected",
+		TxPoolError::TooManyFromSender => "sender_limit",
+	}
 }
 
 /// Transaction pool Prometheus metrics.
@@ -49,6 +86,7 @@ pub struct Metrics {
 	pub validations_invalid: Counter<U64>,
 	pub block_transactions_pruned: Counter<U64>,
 	pub block_transactions_resubmitted: Counter<U64>,
+	pub rejected_transactions: CounterVec<U64>,
 }
 
 impl Metrics {
@@ -82,6 +120,16 @@ impl Metrics {
 				)?,
 				registry,
 			)?,
+			rejected_transactions: register(
+				CounterVec::new(
+					Opts::new(
+						"sub_txpool_rejected_transactions",
+						"Total number of submitted transactions rejected by the pool, by reason",
+					),
+					&["reason"],
+				)?,
+				registry,
+			)?,
 		})
 	}
 }
@@ -1038,3 +1038,37 @@ fn only_revalidate_on_best_block() {
 
 	assert_eq!(pool.status().ready, 1);
 }
+
+#[test]
+fn should_restore_journaled_transactions_after_a_restart() {
+	let dir = tempfile::tempdir().unwrap();
+	let options = || txpool::Options {
+		journal: Some(txpool::JournalOptions {
+			path: dir.path().join("txpool").join("journal"),
+			max_transactions: 10,
+			max_age: std::time::Duration::from_secs(3600),
+		}),
+		..Default::default()
+	};
+	let client = Arc::new(substrate_test_runtime_client::new());
+	let spawner = sp_core::testing::TaskExecutor::new();
+
+	let pool = BasicPool::new_full(options(), None, spawner.clone(), client.clone());
+	let xt = uxt(Alice, 0);
+	block_on(pool.submit_one(&BlockId::number(0), SOURCE, xt.clone())).expect("1. Imported");
+	assert_eq!(pool.status().ready, 1);
+
+	// The journal is written when the pool is dropped.
+	drop(pool);
+
+	// The transaction is restored when the pool is created, without waiting for a new block.
+	let pool = BasicPool::new_full(options(), None, spawner, client);
+	for _ in 0..50 {
+		if pool.status().ready > 0 {
+			break;
+		}
+		std::thread::sleep(std::time::Duration::from_millis(100));
+	}
+	assert_eq!(pool.status().ready, 1);
+	assert_eq!(pool.ready().next().unwrap().data, xt);
+}
@@ -43,7 +43,8 @@ use sp_timestamp::OnTimestampSet;
 use sp_consensus_babe::{
 	digests::{NextConfigDescriptor, NextEpochDescriptor, PreDigest},
 	inherents::{BabeInherentData, INHERENT_IDENTIFIER},
-	BabeAuthorityWeight, ConsensusLog, EquivocationProof, SlotNumber, BABE_ENGINE_ID,
+	BabeAuthorityWeight, BabeEpochConfiguration, ConsensusLog, Epoch, EquivocationProof, SlotNumber,
+	BABE_ENGINE_ID,
 };
 use sp_consensus_vrf::schnorrkel;
 use sp_inherents::{InherentData, InherentIdentifier, MakeFatalError, ProvideInherent};
@@ -192,6 +193,9 @@ decl_storage! {
 		/// Next epoch randomness.
 		NextRandomness: schnorrkel::Randomness;
 
+		/// Next epoch authorities.
+		NextAuthorities: Vec<(AuthorityId, BabeAuthorityWeight)>;
+
 		/// Randomness under construction.
 		///
 		/// We make a tradeoff between storage accesses and list length.
@@ -460,6 +464,8 @@ impl<T: Trait> Module<T> {
 		// so that nodes can track changes.
 		let next_randomness = NextRandomness::get();
 
+		NextAuthorities::put(&next_authorities);
+
 		let next_epoch = NextEpochDescriptor {
 			authorities: next_authorities,
 			randomness: next_randomness,
@@ -478,6 +484,42 @@ impl<T: Trait> Module<T> {
 		(EpochIndex::get() * T::EpochDuration::get()) + GenesisSlot::get()
 	}
 
+	/// Produces information about the current epoch, under the given configuration.
+	pub fn current_epoch(config: BabeEpochConfiguration) -> Epoch {
+		Epoch {
+			epoch_index: EpochIndex::get(),
+			start_slot: Self::current_epoch_start(),
+			duration: T::EpochDuration::get(),
+			authorities: Self::authorities(),
+			randomness: Self::randomness(),
+			config,
+		}
+	}
+
+	/// Produces information about the next epoch (which was already previously
+	/// announced), under the given configuration.
+	pub fn next_epoch(config: BabeEpochConfiguration) -> Epoch {
+		let epoch_index = EpochIndex::get()
+			.checked_add(1)
+			.expect("epoch indices will never reach 2^64 before the death of the universe; qed");
+
+		// the first block announces epoch #1 with the genesis randomness, see `do_initialize`.
+		let randomness = if epoch_index == 1 {
+			Self::randomness()
+		} else {
+			NextRandomness::get()
+		};
+
+		Epoch {
+			epoch_index,
+			start_slot: Self::current_epoch_start() + T::EpochDuration::get(),
+			duration: T::EpochDuration::get(),
+			authorities: NextAuthorities::get(),
+			randomness,
+			config,
+		}
+	}
+
 	fn deposit_consensus<U: Encode>(new: U) {
 		let log: DigestItem<T::Hash> = DigestItem::Consensus(BABE_ENGINE_ID, new.encode());
 		<frame_system::Module<T>>::deposit_log(log.into())
@@ -606,6 +648,7 @@ impl<T: Trait> Module<T> {
 		if !authorities.is_empty() {
 			assert!(Authorities::get().is_empty(), "Authorities are already initialized!");
 			Authorities::put(authorities);
+			NextAuthorities::put(authorities);
 		}
 	}
 
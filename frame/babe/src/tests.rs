@@ -188,6 +188,50 @@ fn can_enact_next_config() {
 	});
 }
 
+#[test]
+fn can_fetch_current_and_next_epoch_data() {
+	new_test_ext(5).execute_with(|| {
+		let config = BabeEpochConfiguration {
+			c: (1, 4),
+			allowed_slots: AllowedSlots::PrimarySlots,
+		};
+
+		// genesis authorities are used for the first two epochs
+		assert_eq!(
+			Babe::current_epoch(config.clone()).authorities,
+			Babe::next_epoch(config.clone()).authorities,
+		);
+
+		// this sets the genesis slot to 6, epoch #1 starts at block #4 (slot 9)
+		go_to_block(1, 6);
+		progress_to_block(4);
+
+		let current_epoch = Babe::current_epoch(config.clone());
+		assert_eq!(current_epoch.epoch_index, 1);
+		assert_eq!(current_epoch.start_slot, 9);
+		assert_eq!(current_epoch.duration, 3);
+		assert_eq!(current_epoch.authorities, Babe::authorities());
+		assert_eq!(current_epoch.randomness, Babe::randomness());
+		assert_eq!(current_epoch.config, config);
+
+		// the next epoch is the one announced by this block
+		let announced = System::digest().logs.iter()
+			.filter_map(|log| log.as_consensus())
+			.filter(|(id, _)| *id == BABE_ENGINE_ID)
+			.find_map(|(_, mut data)| match ConsensusLog::decode(&mut data) {
+				Ok(ConsensusLog::NextEpochData(next)) => Some(next),
+				_ => None,
+			})
+			.expect("epoch change announces the next epoch");
+
+		let next_epoch = Babe::next_epoch(config);
+		assert_eq!(next_epoch.epoch_index, 2);
+		assert_eq!(next_epoch.start_slot, 12);
+		assert_eq!(next_epoch.authorities, announced.authorities);
+		assert_eq!(next_epoch.randomness, announced.randomness);
+	});
+}
+
 #[test]
 fn report_equivocation_current_session_works() {
 	let (pairs, mut ext) = new_test_ext_with_pairs(3);
@@ -182,7 +182,7 @@ decl_storage! {
         ChainNonces get(fn chains): map hasher(opaque_blake2_256) ChainId => Option<DepositNonce>;
 
         /// Number of votes required for a proposal to execute
-        RelayerThreshold get(fn relayer_threshold): u32 = DEFAULT_RELAYER_THRESHOLD;
+        RelayerThreshold get(fn relayer_threshold) config(): u32 = DEFAULT_RELAYER_THRESHOLD;
 
         /// Tracks current relayer set
         pub Relayers get(fn relayers): map hasher(opaque_blake2_256) T::AccountId => bool;
@@ -200,6 +200,29 @@ decl_storage! {
         pub Resources get(fn resources):
             map hasher(opaque_blake2_256) ResourceId => Option<Vec<u8>>
     }
+    add_extra_genesis {
+        /// The initial relayer set.
+        config(relayers): Vec<T::AccountId>;
+        /// Chains whitelisted for transfers.
+        config(chains): Vec<ChainId>;
+        /// Resource IDs and the methods they map to.
+        config(resources): Vec<(ResourceId, Vec<u8>)>;
+        build(|config: &GenesisConfig<T>| {
+            assert!(config.relayer_threshold > 0, "Genesis relayer threshold must be positive");
+            for relayer in &config.relayers {
+                assert!(!<Relayers<T>>::get(relayer), "Relayer registered twice at genesis");
+                <Relayers<T>>::insert(relayer, true);
+            }
+            <RelayerCount>::put(config.relayers.len() as u32);
+            for chain in &config.chains {
+                assert!(*chain != T::ChainId::get(), "Cannot whitelist this chain at genesis");
+                <ChainNonces>::insert(chain, 0);
+            }
+            for (id, method) in &config.resources {
+                <Resources>::insert(id, method);
+            }
+        });
+    }
 }
 
 decl_module! {
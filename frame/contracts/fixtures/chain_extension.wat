@@ -0,0 +1,44 @@
+;; Call the function of the chain extension whose id is given by the first 4 bytes of the input
+;; with the rest of the input, and return the return value of the function followed by its
+;; output.
+(module
+	(import "seal0" "seal_input" (func $seal_input (param i32 i32)))
+	(import "seal0" "seal_call_chain_extension"
+		(func $seal_call_chain_extension (param i32 i32 i32 i32 i32) (result i32)))
+	(import "seal0" "seal_return" (func $seal_return (param i32 i32 i32)))
+	(import "env" "memory" (memory 1 1))
+
+	;; [0, 4) size of the input buffer
+	(data (i32.const 0) "\00\01")
+
+	;; [4, 260) input buffer: the id of the function, then its input
+
+	;; [260, 264) size of the output buffer
+	(data (i32.const 260) "\00\01")
+
+	;; [264, 268) return value of the function
+
+	;; [268, 524) output buffer
+
+	(func (export "deploy"))
+
+	(func (export "call")
+		(call $seal_input (i32.const 4) (i32.const 0))
+		(i32.store
+			(i32.const 264)
+			(call $seal_call_chain_extension
+				(i32.load (i32.const 4)) ;; id of the function
+				(i32.const 8) ;; Pointer to the input of the function
+				(i32.sub (i32.load (i32.const 0)) (i32.const 4)) ;; Length of the input
+				(i32.const 268) ;; Pointer to the output buffer
+				(i32.const 260) ;; Pointer to the length of the output buffer
+			)
+		)
+		;; return the return value of the function, and its output.
+		(call $seal_return
+			(i32.const 0)
+			(i32.const 264)
+			(i32.add (i32.load (i32.const 260)) (i32.const 4))
+		)
+	)
+)
@@ -0,0 +1,129 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate. If not, see <http://www.gnu.org/licenses/>.
+
+//! Extending the functions available to contracts with functions of the runtime.
+//!
+//! Contracts call into a chain extension with the `seal_call_chain_extension` host function,
+//! giving the id of the function to call and an input buffer. The runtime provides the
+//! extension as `Trait::ChainExtension`, which dispatches on the function id and works with the
+//! call through an [`Environment`]: it decodes the input, charges gas for the work it does,
+//! and writes the output copied back to the contract.
+//!
+//! The `u32` returned by the extension is returned to the contract, so that functions can
+//! report expected failures without trapping. An error traps the contract, reverting every
+//! change of the call.
+
+use crate::{Error, Trait, gas::{Gas, GasMeter, GasMeterResult, Token}};
+use codec::Decode;
+use frame_support::dispatch::{DispatchError, DispatchResult};
+use sp_std::prelude::*;
+
+/// A set of functions of the runtime contracts can call.
+pub trait ChainExtension<T: Trait> {
+	/// Call the function `func_id` of the extension.
+	///
+	/// Returns the value handed to the contract as the return value of
+	/// `seal_call_chain_extension`. Returning an error traps the contract.
+	fn call(func_id: u32, env: &mut Environment<T>) -> Result<u32, DispatchError>;
+
+	/// Whether contracts may call the extension.
+	///
+	/// Calls to a disabled extension trap.
+	fn enabled() -> bool {
+		true
+	}
+}
+
+/// No chain extension: contracts calling `seal_call_chain_extension` trap.
+impl<T: Trait> ChainExtension<T> for () {
+	fn call(_func_id: u32, _env: &mut Environment<T>) -> Result<u32, DispatchError> {
+		Err(Error::<T>::NoChainExtension.into())
+	}
+
+	fn enabled() -> bool {
+		false
+	}
+}
+
+/// The gas charged by a chain extension for the work it does.
+#[cfg_attr(test, derive(Debug, PartialEq, Eq))]
+#[derive(Copy, Clone)]
+struct ChainExtensionToken(Gas);
+
+impl<T: Trait> Token<T> for ChainExtensionToken {
+	type Metadata = ();
+
+	fn calculate_amount(&self, _metadata: &()) -> Gas {
+		self.0
+	}
+}
+
+/// What a chain extension works with while handling a call of a contract.
+pub struct Environment<'a, T: Trait> {
+	address: T::AccountId,
+	input: Vec<u8>,
+	output: Vec<u8>,
+	gas_meter: &'a mut GasMeter<T>,
+}
+
+impl<'a, T: Trait> Environment<'a, T> {
+	/// The environment of a call of the contract at `address` with `input`, charging gas to
+	/// `gas_meter`.
+	pub fn new(address: T::AccountId, input: Vec<u8>, gas_meter: &'a mut GasMeter<T>) -> Self {
+		Environment {
+			address,
+			input,
+			output: Vec::new(),
+			gas_meter,
+		}
+	}
+
+	/// The address of the contract calling the extension.
+	pub fn address(&self) -> &T::AccountId {
+		&self.address
+	}
+
+	/// The input buffer passed by the contract.
+	pub fn input(&self) -> &[u8] {
+		&self.input
+	}
+
+	/// Decode the whole input buffer as a `D`.
+	pub fn read_as<D: Decode>(&self) -> Result<D, DispatchError> {
+		D::decode(&mut &self.input[..]).map_err(|_| Error::<T>::DecodingFailed.into())
+	}
+
+	/// Charge `amount` of gas for the work of the extension.
+	///
+	/// Fails with `OutOfGas` if the contract has not that much gas left, in which case the gas
+	/// left is consumed.
+	pub fn charge_weight(&mut self, amount: Gas) -> DispatchResult {
+		match self.gas_meter.charge(&(), ChainExtensionToken(amount)) {
+			GasMeterResult::Proceed => Ok(()),
+			GasMeterResult::OutOfGas => Err(Error::<T>::OutOfGas.into()),
+		}
+	}
+
+	/// Set the output buffer copied back to the contract, replacing any written before.
+	pub fn write(&mut self, output: &[u8]) {
+		self.output = output.to_vec();
+	}
+
+	/// The output buffer copied back to the contract.
+	pub fn into_output(self) -> Vec<u8> {
+		self.output
+	}
+}
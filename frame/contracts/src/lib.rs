@@ -86,6 +86,7 @@ mod exec;
 mod wasm;
 mod rent;
 mod benchmarking;
+pub mod chain_extension;
 
 #[cfg(test)]
 mod tests;
@@ -373,6 +374,10 @@ pub trait Trait: frame_system::Trait {
 	/// Used to answer contracts's queries regarding the current weight price. This is **not**
 	/// used to calculate the actual fee and is only for informational purposes.
 	type WeightPrice: Convert<Weight, BalanceOf<Self>>;
+
+	/// The functions of the runtime contracts may call with `seal_call_chain_extension`.
+	/// `()` for none.
+	type ChainExtension: chain_extension::ChainExtension<Self>;
 }
 
 /// Simple contract address determiner.
@@ -445,6 +450,8 @@ decl_error! {
 		DecodingFailed,
 		/// Contract trapped during execution.
 		ContractTrapped,
+		/// A contract called the chain extension, but the runtime provides none.
+		NoChainExtension,
 	}
 }
 
@@ -17,7 +17,7 @@
 use crate::{
 	BalanceOf, ContractAddressFor, ContractInfo, ContractInfoOf, GenesisConfig, Module,
 	RawAliveContractInfo, RawEvent, Trait, TrieId, Schedule, TrieIdGenerator, gas::Gas,
-	Error, Config, RuntimeReturnCode,
+	Error, Config, RuntimeReturnCode, chain_extension::{ChainExtension, Environment},
 };
 use assert_matches::assert_matches;
 use hex_literal::*;
@@ -32,7 +32,7 @@ use frame_support::{
 	impl_outer_origin, parameter_types, StorageMap, StorageValue,
 	traits::{Currency, Get, ReservableCurrency},
 	weights::{Weight, PostDispatchInfo},
-	dispatch::DispatchErrorWithPostInfo,
+	dispatch::{DispatchError, DispatchErrorWithPostInfo},
 };
 use std::cell::RefCell;
 use frame_system::{self as system, EventRecord, Phase};
@@ -182,6 +182,32 @@ impl Convert<Weight, BalanceOf<Self>> for Test {
 	}
 }
 
+/// A chain extension echoing its input, telling the address of the calling contract and running
+/// out of gas.
+pub struct TestExtension;
+impl ChainExtension<Test> for TestExtension {
+	fn call(func_id: u32, env: &mut Environment<Test>) -> Result<u32, DispatchError> {
+		match func_id {
+			0 => {
+				let input = env.input().to_vec();
+				env.charge_weight(input.len() as Weight)?;
+				env.write(&input);
+				Ok(0)
+			},
+			1 => {
+				let address = env.address().encode();
+				env.write(&address);
+				Ok(1)
+			},
+			2 => {
+				env.charge_weight(Weight::max_value())?;
+				Ok(0)
+			},
+			_ => Err(DispatchError::Other("unknown function")),
+		}
+	}
+}
+
 impl Trait for Test {
 	type Time = Timestamp;
 	type Randomness = Randomness;
@@ -199,6 +225,7 @@ impl Trait for Test {
 	type MaxDepth = MaxDepth;
 	type MaxValueSize = MaxValueSize;
 	type WeightPrice = Self;
+	type ChainExtension = TestExtension;
 }
 
 type Balances = pallet_balances::Module<Test>;
@@ -1827,3 +1854,36 @@ fn instantiate_return_code() {
 
 	});
 }
+
+#[test]
+fn chain_extension_works() {
+	let (code, hash) = compile_module::<Test>("chain_extension").unwrap();
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		let subsistence = Config::<Test>::subsistence_threshold_uncached();
+		let _ = Balances::deposit_creating(&ALICE, 10 * subsistence);
+		assert_ok!(Contracts::put_code(Origin::signed(ALICE), code));
+		assert_ok!(
+			Contracts::instantiate(
+				Origin::signed(ALICE),
+				subsistence,
+				GAS_LIMIT,
+				hash.into(),
+				vec![],
+			),
+		);
+
+		// The contract returns the return value of the function followed by its output.
+		let input = (0u32, [7u8; 3]).encode();
+		let result = Contracts::bare_call(ALICE, BOB, 0, GAS_LIMIT, input.clone()).0.unwrap();
+		assert_eq!(result.data, input);
+
+		let result = Contracts::bare_call(ALICE, BOB, 0, GAS_LIMIT, 1u32.encode()).0.unwrap();
+		assert_eq!(result.data, (1u32, BOB).encode());
+
+		// Failing functions trap.
+		let result = Contracts::bare_call(ALICE, BOB, 0, GAS_LIMIT, 2u32.encode()).0;
+		assert_eq!(result.unwrap_err().error, Error::<Test>::OutOfGas.into());
+		let result = Contracts::bare_call(ALICE, BOB, 0, GAS_LIMIT, 3u32.encode()).0;
+		assert_eq!(result.unwrap_err().error, DispatchError::Other("unknown function"));
+	});
+}
@@ -17,6 +17,7 @@
 //! Environment definition of the wasm smart-contract runtime.
 
 use crate::{Schedule, Trait, CodeHash, BalanceOf, Error};
+use crate::chain_extension::{ChainExtension, Environment};
 use crate::exec::{
 	Ext, ExecResult, ExecReturnValue, StorageKey, TopicOf, ReturnFlags, ExecError
 };
@@ -1247,6 +1248,53 @@ define_env!(Env, <E: Ext>,
 	seal_hash_blake2_128(ctx, input_ptr: u32, input_len: u32, output_ptr: u32) => {
 		compute_hash_on_intermediate_buffer(ctx, blake2_128, input_ptr, input_len, output_ptr)
 	},
+
+	// Call a function of the chain extension provided by the runtime.
+	//
+	// The output buffer written by the function is copied to `output_ptr` and its length to
+	// `output_len_ptr`. The copy can be skipped by supplying the sentinel value of
+	// `u32::max_value()` to `output_ptr`.
+	//
+	// # Parameters
+	//
+	// - func_id: the id of the function of the extension to call.
+	// - input_ptr: a pointer to the input buffer of the function.
+	// - input_len: length of the input buffer.
+	// - output_ptr: a pointer where the output buffer is copied to.
+	// - output_len_ptr: in-out pointer to where the length of the buffer is read from
+	//   and the actual length is written to.
+	//
+	// # Return Value
+	//
+	// The value returned by the function, whose meaning is defined by the extension.
+	//
+	// # Traps
+	//
+	// - If the runtime provides no chain extension.
+	// - If the function fails, such as for an unknown `func_id` or for running out of gas.
+	seal_call_chain_extension(
+		ctx,
+		func_id: u32,
+		input_ptr: u32,
+		input_len: u32,
+		output_ptr: u32,
+		output_len_ptr: u32
+	) -> u32 => {
+		if !<<E::T as Trait>::ChainExtension as ChainExtension<E::T>>::enabled() {
+			Err(store_err(ctx, Error::<E::T>::NoChainExtension))?
+		}
+		let input = read_sandbox_memory(ctx, input_ptr, input_len)?;
+		let mut env = Environment::new(ctx.ext.address().clone(), input, &mut *ctx.gas_meter);
+		let result = <<E::T as Trait>::ChainExtension as ChainExtension<E::T>>::call(func_id, &mut env);
+		let output = env.into_output();
+		match result {
+			Ok(code) => {
+				write_sandbox_output(ctx, output_ptr, output_len_ptr, &output, true)?;
+				Ok(code)
+			},
+			Err(err) => Err(store_err(ctx, err)),
+		}
+	},
 );
 
 /// Computes the given hash function on the supplied input.
@@ -116,6 +116,19 @@ pub struct AttributeTransaction<Signature, AccountId> {
     pub identity: AccountId,
 }
 
+// A value placed in storage that represents the current version of the DID storage.
+// This value is used by storage migrations to determine whether they need to run.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+enum Releases {
+    V1_0_0,
+}
+
+impl Default for Releases {
+    fn default() -> Self {
+        Releases::V1_0_0
+    }
+}
+
 pub trait Trait: frame_system::Trait + pallet_timestamp::Trait {
     type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
     type Public: IdentifyAccount<AccountId = Self::AccountId>;
@@ -136,6 +149,26 @@ decl_storage! {
         pub OwnerOf get(fn owner_of): map hasher(blake2_128_concat) T::AccountId => Option<T::AccountId>;
         /// Tracking the latest identity update.
         pub UpdatedBy get(fn updated_by): map hasher(blake2_128_concat) T::AccountId => (T::AccountId, T::BlockNumber, T::Moment);
+        /// Storage version of the pallet.
+        ///
+        /// New networks start with the latest version.
+        StorageVersion build(|_: &GenesisConfig<T>| Releases::V1_0_0): Releases;
+    }
+    add_extra_genesis {
+        /// Identities owned by another account, as `(identity, owner)`.
+        config(owners): Vec<(T::AccountId, T::AccountId)>;
+        /// Delegates of identities that never expire, as `(identity, delegate_type, delegate)`.
+        config(delegates): Vec<(T::AccountId, Vec<u8>, T::AccountId)>;
+        build(|config: &GenesisConfig<T>| {
+            for (identity, owner) in &config.owners {
+                <OwnerOf<T>>::insert(identity, owner);
+            }
+            for (identity, delegate_type, delegate) in &config.delegates {
+                assert!(delegate_type.len() <= 64, "Genesis delegate type is too long");
+                let validity: T::BlockNumber = u32::max_value().into();
+                <DelegateOf<T>>::insert((identity, delegate_type, delegate), validity);
+            }
+        });
     }
 }
 
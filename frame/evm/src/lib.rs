@@ -24,7 +24,7 @@ mod backend;
 mod tests;
 pub mod precompiles;
 
-pub use crate::precompiles::{Precompile, Precompiles};
+pub use crate::precompiles::{Precompile, Precompiles, is_called_by_transaction};
 pub use crate::backend::{Account, Log, Vicinity, Backend};
 
 use sp_std::vec::Vec;
@@ -40,7 +40,7 @@ use frame_system::RawOrigin;
 use sp_core::{U256, H256, H160, Hasher};
 use sp_runtime::{AccountId32, traits::{UniqueSaturatedInto, SaturatedConversion, BadOrigin}};
 use sha3::{Digest, Keccak256};
-pub use evm::{ExitReason, ExitSucceed, ExitError, ExitRevert, ExitFatal};
+pub use evm::{Context, ExitReason, ExitSucceed, ExitError, ExitRevert, ExitFatal};
 use evm::Config;
 use evm::executor::StackExecutor;
 use evm::backend::ApplyBackend;
@@ -463,6 +463,14 @@ impl<T: Trait> Module<T> {
 		}
 	}
 
+	/// Deposit an Ethereum log, such as one of a precompile.
+	///
+	/// Precompiles cannot return logs to the EVM engine, so the log is deposited as it is made,
+	/// and is only removed if the whole transaction fails.
+	pub fn deposit_log(log: Log) {
+		Module::<T>::deposit_event(Event::<T>::Log(log));
+	}
+
 	/// Get the account basic in EVM format.
 	pub fn account_basic(address: &H160) -> Account {
 		let account_id = T::AddressMapping::into_account_id(*address);
@@ -611,7 +619,16 @@ impl<T: Trait> Module<T> {
 			ensure!(source_account.nonce == nonce, Error::<T>::InvalidNonce);
 		}
 
-		let (retv, reason) = f(&mut executor);
+		// precompiles write to the storage directly, these writes are kept only if the
+		// execution succeeds and its state is applied.
+		let (retv, reason) = frame_support::storage::with_transaction(|| {
+			use frame_support::storage::TransactionOutcome::*;
+			let output = precompiles::using_transaction_source(source, || f(&mut executor));
+			match output {
+				(ExitReason::Succeed(_), _) if apply_state => Commit(output),
+				_ => Rollback(output),
+			}
+		});
 
 		let used_gas = U256::from(executor.used_gas());
 		let actual_fee = executor.fee(gas_price);
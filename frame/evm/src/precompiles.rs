@@ -19,10 +19,26 @@
 
 use sp_std::{cmp::min, vec::Vec};
 use sp_core::H160;
-use evm::{ExitError, ExitSucceed};
+use evm::{Context, ExitError, ExitSucceed};
 use ripemd160::Digest;
 use impl_trait_for_tuples::impl_for_tuples;
 
+environmental::environmental!(transaction_source: H160);
+
+/// Execute `f`, running a transaction sent by `source`.
+pub(crate) fn using_transaction_source<R>(mut source: H160, f: impl FnOnce() -> R) -> R {
+	transaction_source::using(&mut source, f)
+}
+
+/// Whether the precompile of `context` is called by the transaction itself, not by a contract.
+///
+/// Precompiles write to the storage as they run. These writes are reverted along with the
+/// transaction when it fails, but not when only the frame of a contract calling the precompile
+/// reverts. Precompiles changing the state must only do so when called by the transaction.
+pub fn is_called_by_transaction(context: &Context) -> bool {
+	transaction_source::with(|source| *source == context.caller).unwrap_or(false)
+}
+
 /// Custom precompiles to be used by EVM engine.
 pub trait Precompiles {
 	/// Try to execute the code address as precompile. If the code address is not
@@ -30,10 +46,13 @@ pub trait Precompiles {
 	/// Otherwise, calculate the amount of gas needed with given `input` and
 	/// `target_gas`. Return `Some(Ok(status, output, gas_used))` if the execution
 	/// is successful. Otherwise return `Some(Err(_))`.
+	///
+	/// The `context` of the call tells the caller of the precompile.
 	fn execute(
 		address: H160,
 		input: &[u8],
 		target_gas: Option<usize>,
+		context: &Context,
 	) -> Option<core::result::Result<(ExitSucceed, Vec<u8>, usize), ExitError>>;
 }
 
@@ -45,6 +64,7 @@ pub trait Precompile {
 	fn execute(
 		input: &[u8],
 		target_gas: Option<usize>,
+		context: &Context,
 	) -> core::result::Result<(ExitSucceed, Vec<u8>, usize), ExitError>;
 }
 
@@ -57,13 +77,14 @@ impl Precompiles for Tuple {
 		address: H160,
 		input: &[u8],
 		target_gas: Option<usize>,
+		context: &Context,
 	) -> Option<core::result::Result<(ExitSucceed, Vec<u8>, usize), ExitError>> {
 		let mut index = 0;
 
 		for_tuples!( #(
 			index += 1;
 			if address == H160::from_low_u64_be(index) {
-				return Some(Tuple::execute(input, target_gas))
+				return Some(Tuple::execute(input, target_gas, context))
 			}
 		)* );
 
@@ -98,6 +119,7 @@ impl Precompile for Identity {
 	fn execute(
 		input: &[u8],
 		target_gas: Option<usize>,
+		_context: &Context,
 	) -> core::result::Result<(ExitSucceed, Vec<u8>, usize), ExitError> {
 		let cost = ensure_linear_cost(target_gas, input.len(), 15, 3)?;
 
@@ -112,6 +134,7 @@ impl Precompile for ECRecover {
 	fn execute(
 		i: &[u8],
 		target_gas: Option<usize>,
+		_context: &Context,
 	) -> core::result::Result<(ExitSucceed, Vec<u8>, usize), ExitError> {
 		let cost = ensure_linear_cost(target_gas, i.len(), 3000, 0)?;
 
@@ -142,6 +165,7 @@ impl Precompile for Ripemd160 {
 	fn execute(
 		input: &[u8],
 		target_gas: Option<usize>,
+		_context: &Context,
 	) -> core::result::Result<(ExitSucceed, Vec<u8>, usize), ExitError> {
 		let cost = ensure_linear_cost(target_gas, input.len(), 600, 120)?;
 
@@ -157,6 +181,7 @@ impl Precompile for Sha256 {
 	fn execute(
 		input: &[u8],
 		target_gas: Option<usize>,
+		_context: &Context,
 	) -> core::result::Result<(ExitSucceed, Vec<u8>, usize), ExitError> {
 		let cost = ensure_linear_cost(target_gas, input.len(), 60, 12)?;
 
@@ -91,6 +91,33 @@ impl FeeCalculator for FixedGasPrice {
 	}
 }
 
+/// Returns its caller.
+pub struct Caller;
+impl Precompile for Caller {
+	fn execute(
+		_input: &[u8],
+		_target_gas: Option<usize>,
+		context: &Context,
+	) -> core::result::Result<(ExitSucceed, Vec<u8>, usize), ExitError> {
+		Ok((ExitSucceed::Returned, context.caller.as_bytes().to_vec(), 0))
+	}
+}
+
+/// Writes to `WRITTEN` whether it is called by the transaction.
+pub struct Writer;
+impl Precompile for Writer {
+	fn execute(
+		_input: &[u8],
+		_target_gas: Option<usize>,
+		context: &Context,
+	) -> core::result::Result<(ExitSucceed, Vec<u8>, usize), ExitError> {
+		sp_io::storage::set(WRITTEN, &[is_called_by_transaction(context) as u8]);
+		Ok((ExitSucceed::Stopped, Vec::new(), 0))
+	}
+}
+
+const WRITTEN: &[u8] = b"written";
+
 impl Trait for Test {
 	type FeeCalculator = FixedGasPrice;
 
@@ -101,7 +128,7 @@ impl Trait for Test {
 	type Currency = Balances;
 
 	type Event = Event<Test>;
-	type Precompiles = ();
+	type Precompiles = (Caller, Writer);
 	type ChainId = SystemChainId;
 }
 
@@ -187,3 +214,94 @@ fn mutate_account_works() {
 		});
 	});
 }
+
+#[test]
+fn precompiles_are_given_their_caller() {
+	new_test_ext().execute_with(|| {
+		let source = H160::from_str("1000000000000000000000000000000000000001").unwrap();
+		let precompile = H160::from_low_u64_be(1);
+		let (reason, output, _, _) = EVM::execute_call(
+			source,
+			precompile,
+			Vec::new(),
+			U256::default(),
+			1000000,
+			U256::default(),
+			None,
+			true,
+		).unwrap();
+		assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned));
+		assert_eq!(output, source.as_bytes().to_vec());
+
+		// a contract calling the precompile and returning its output.
+		let contract = H160::from_str("1000000000000000000000000000000000000003").unwrap();
+		AccountCodes::insert(contract, vec![
+			0x60, 0x14, // PUSH1 20
+			0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, // PUSH1 0 (x4)
+			0x60, 0x01, // PUSH1 1
+			0x5a, // GAS
+			0xf1, // CALL
+			0x50, // POP
+			0x60, 0x14, // PUSH1 20
+			0x60, 0x00, // PUSH1 0
+			0xf3, // RETURN
+		]);
+		let (reason, output, _, _) = EVM::execute_call(
+			source,
+			contract,
+			Vec::new(),
+			U256::default(),
+			1000000,
+			U256::default(),
+			None,
+			true,
+		).unwrap();
+		assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned));
+		assert_eq!(output, contract.as_bytes().to_vec());
+	});
+}
+
+#[test]
+fn precompile_writes_are_reverted_with_the_transaction() {
+	new_test_ext().execute_with(|| {
+		let source = H160::from_str("1000000000000000000000000000000000000001").unwrap();
+		let writer = H160::from_low_u64_be(2);
+		let call = |target: H160, apply_state: bool| EVM::execute_call(
+			source,
+			target,
+			Vec::new(),
+			U256::default(),
+			1000000,
+			U256::default(),
+			None,
+			apply_state,
+		).unwrap().0;
+
+		assert_eq!(call(writer, false), ExitReason::Succeed(ExitSucceed::Stopped));
+		assert_eq!(sp_io::storage::get(WRITTEN), None);
+		assert_eq!(call(writer, true), ExitReason::Succeed(ExitSucceed::Stopped));
+		assert_eq!(sp_io::storage::get(WRITTEN), Some(vec![1]));
+		sp_io::storage::clear(WRITTEN);
+
+		// contracts calling the writer, then stopping or reverting.
+		let call_writer = vec![
+			0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, // PUSH1 0 (x5)
+			0x60, 0x02, // PUSH1 2
+			0x5a, // GAS
+			0xf1, // CALL
+			0x50, // POP
+		];
+		let stopping = H160::from_str("1000000000000000000000000000000000000003").unwrap();
+		AccountCodes::insert(stopping, [&call_writer[..], &[0x00]].concat()); // STOP
+		let reverting = H160::from_str("1000000000000000000000000000000000000004").unwrap();
+		AccountCodes::insert(reverting, [&call_writer[..], &[
+			0x60, 0x00, 0x60, 0x00, // PUSH1 0 (x2)
+			0xfd, // REVERT
+		]].concat());
+
+		assert_eq!(call(reverting, true), ExitReason::Revert(ExitRevert::Reverted));
+		assert_eq!(sp_io::storage::get(WRITTEN), None);
+		assert_eq!(call(stopping, true), ExitReason::Succeed(ExitSucceed::Stopped));
+		assert_eq!(sp_io::storage::get(WRITTEN), Some(vec![0]));
+	});
+}
@@ -112,6 +112,22 @@
 //!
 //! pub type Executive = executive::Executive<Runtime, Block, Context, Runtime, AllModules, CustomOnRuntimeUpgrade>;
 //! ```
+//!
+//! ### Storage migrations
+//!
+//! Storage migrations implementing [`frame_support::traits::Migration`] can be run in order on a
+//! runtime upgrade by passing them as a tuple to [`Migrations`], which implements
+//! `OnRuntimeUpgrade`. The `pre_upgrade` and `post_upgrade` checks of the migrations are not run
+//! on-chain; they are run by [`Executive::dry_run_upgrade`], which runs every upgrade hook in the
+//! order of a real upgrade and which a runtime exposes through the [`MigrationApi`] runtime api to
+//! dry-run an upgrade offline against some exported state.
+//!
+//! ```ignore
+//! pub type Executive = executive::Executive<
+//!     Runtime, Block, Context, Runtime, AllModules,
+//!     executive::Migrations<(pallet_example::migrations::MigrateToV2<Runtime>,)>,
+//! >;
+//! ```
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
@@ -132,6 +148,10 @@ use sp_runtime::{
 use codec::{Codec, Encode};
 use frame_system::{extrinsics_root, DigestOf};
 
+mod migrations;
+
+pub use migrations::*;
+
 /// Trait that can be used to execute a block.
 pub trait ExecuteBlock<Block: BlockT> {
 	/// Actually execute all transitions for `block`.
@@ -184,6 +204,28 @@ where
 	}
 }
 
+impl<
+	System: frame_system::Trait,
+	Block,
+	Context,
+	UnsignedValidator,
+	AllModules: OnRuntimeUpgrade,
+	COnRuntimeUpgrade: DryRunUpgrade,
+> Executive<System, Block, Context, UnsignedValidator, AllModules, COnRuntimeUpgrade> {
+	/// Execute all runtime upgrade hooks like an upgrade would, with the checks of the migrations.
+	///
+	/// The hooks of `frame_system` and `AllModules` have no checks, but are reported with their
+	/// weight. This writes to storage and is meant to be called from a runtime api whose changes
+	/// are discarded.
+	pub fn dry_run_upgrade() -> MigrationReport {
+		let mut report = MigrationReport::default();
+		migrations::dry_run_hook::<frame_system::Module<System>>("System", &mut report);
+		COnRuntimeUpgrade::dry_run(&mut report);
+		migrations::dry_run_hook::<AllModules>("AllModules", &mut report);
+		report
+	}
+}
+
 impl<
 	System: frame_system::Trait,
 	Block: traits::Block<Header=System::Header, Hash=System::Hash>,
@@ -1058,4 +1100,102 @@ mod tests {
 			assert_eq!(sp_io::storage::get(CUSTOM_ON_RUNTIME_KEY).unwrap(), true.encode());
 		});
 	}
+
+	const MIGRATION_KEY: &[u8] = &*b":migration:log";
+
+	struct AppendMigration<N: frame_support::traits::Get<u8>>(PhantomData<N>);
+	impl<N: frame_support::traits::Get<u8>> frame_support::traits::Migration for AppendMigration<N> {
+		const NAME: &'static str = "append";
+
+		fn migrate() -> Weight {
+			let mut log = sp_io::storage::get(MIGRATION_KEY).unwrap_or_default();
+			log.push(N::get());
+			sp_io::storage::set(MIGRATION_KEY, &log);
+			N::get() as Weight
+		}
+
+		fn post_upgrade() -> Result<(), &'static str> {
+			let log = sp_io::storage::get(MIGRATION_KEY).unwrap_or_default();
+			if log.last() == Some(&N::get()) { Ok(()) } else { Err("not the last migration") }
+		}
+	}
+
+	struct FailingMigration;
+	impl frame_support::traits::Migration for FailingMigration {
+		const NAME: &'static str = "failing";
+
+		fn pre_upgrade() -> Result<(), &'static str> {
+			Err("unexpected state")
+		}
+
+		fn migrate() -> Weight {
+			0
+		}
+	}
+
+	parameter_types! {
+		pub const One: u8 = 1;
+		pub const Two: u8 = 2;
+	}
+
+	type TestMigrations = Migrations<(AppendMigration<One>, AppendMigration<Two>)>;
+
+	#[test]
+	fn migrations_should_run_in_order() {
+		new_test_ext(1).execute_with(|| {
+			assert_eq!(<TestMigrations as OnRuntimeUpgrade>::on_runtime_upgrade(), 3);
+			assert_eq!(sp_io::storage::get(MIGRATION_KEY).unwrap(), vec![1, 2]);
+		});
+	}
+
+	#[test]
+	fn migrations_dry_run_should_report_checks() {
+		new_test_ext(1).execute_with(|| {
+			let report = TestMigrations::dry_run();
+			assert_eq!(report.weight, 3);
+			assert_eq!(report.migrations.len(), 2);
+			assert!(report.migrations.iter().all(|m| m.name == b"append".to_vec()));
+			assert!(report.is_ok());
+
+			// A failing check is reported without stopping the other migrations.
+			let mut report = MigrationReport::default();
+			<(AppendMigration<Two>, AppendMigration<One>, FailingMigration) as MigrationSet>::dry_run(
+				&mut report,
+			);
+			assert_eq!(report.weight, 3);
+			assert_eq!(report.migrations[0].post_upgrade, Ok(()));
+			assert_eq!(report.migrations[1].post_upgrade, Ok(()));
+			assert_eq!(report.migrations[2].pre_upgrade, Err(b"unexpected state".to_vec()));
+			assert!(!report.is_ok());
+			assert_eq!(sp_io::storage::get(MIGRATION_KEY).unwrap(), vec![1, 2, 2, 1]);
+		});
+	}
+
+	#[test]
+	fn dry_run_upgrade_should_run_all_hooks_in_order() {
+		type MigratingExecutive = super::Executive<
+			Runtime,
+			Block<TestXt>,
+			ChainContext<Runtime>,
+			Runtime,
+			AllModules,
+			TestMigrations,
+		>;
+
+		new_test_ext(1).execute_with(|| {
+			let report = MigratingExecutive::dry_run_upgrade();
+			let names: Vec<_> = report.migrations.iter().map(|m| m.name.clone()).collect();
+			assert_eq!(names, vec![
+				b"System".to_vec(),
+				b"append".to_vec(),
+				b"append".to_vec(),
+				b"AllModules".to_vec(),
+			]);
+			assert_eq!(report.migrations[1].weight + report.migrations[2].weight, 3);
+			assert!(report.is_ok());
+			assert_eq!(sp_io::storage::get(MIGRATION_KEY).unwrap(), vec![1, 2]);
+			// The hooks of the modules ran after the migrations.
+			assert_eq!(&sp_io::storage::get(TEST_KEY).unwrap()[..], *b"module");
+		});
+	}
 }
@@ -0,0 +1,169 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Harness running an ordered set of storage migrations on a runtime upgrade.
+
+use sp_std::{prelude::*, marker::PhantomData};
+use frame_support::{traits::{Migration, OnRuntimeUpgrade}, weights::Weight};
+use sp_runtime::{RuntimeDebug, traits::Saturating};
+use codec::{Encode, Decode};
+
+/// The outcome of a single migration during a dry run.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct MigrationResult {
+	/// The name of the migration.
+	pub name: Vec<u8>,
+	/// The outcome of the `pre_upgrade` check.
+	pub pre_upgrade: Result<(), Vec<u8>>,
+	/// The weight consumed by the migration itself.
+	pub weight: Weight,
+	/// The outcome of the `post_upgrade` check.
+	pub post_upgrade: Result<(), Vec<u8>>,
+}
+
+impl MigrationResult {
+	/// Whether both checks of the migration passed.
+	pub fn is_ok(&self) -> bool {
+		self.pre_upgrade.is_ok() && self.post_upgrade.is_ok()
+	}
+}
+
+/// The report of a dry run of all migrations of a runtime.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Default, RuntimeDebug)]
+pub struct MigrationReport {
+	/// The total weight consumed by the migrations.
+	pub weight: Weight,
+	/// The outcome of each migration, in execution order.
+	pub migrations: Vec<MigrationResult>,
+}
+
+impl MigrationReport {
+	/// Whether all checks of all migrations passed.
+	pub fn is_ok(&self) -> bool {
+		self.migrations.iter().all(MigrationResult::is_ok)
+	}
+}
+
+/// An ordered set of migrations, implemented for tuples of [`Migration`].
+pub trait MigrationSet {
+	/// Execute all migrations in order, returning the total weight consumed.
+	fn migrate() -> Weight;
+
+	/// Execute all migrations in order with their checks, appending the outcomes to `report`.
+	fn dry_run(report: &mut MigrationReport);
+}
+
+impl MigrationSet for () {
+	fn migrate() -> Weight { 0 }
+
+	fn dry_run(_: &mut MigrationReport) {}
+}
+
+fn dry_run_one<M: Migration>(report: &mut MigrationReport) {
+	let pre_upgrade = M::pre_upgrade().map_err(|e| e.as_bytes().to_vec());
+	let weight = M::migrate();
+	let post_upgrade = M::post_upgrade().map_err(|e| e.as_bytes().to_vec());
+	report.weight = report.weight.saturating_add(weight);
+	report.migrations.push(MigrationResult {
+		name: M::NAME.as_bytes().to_vec(),
+		pre_upgrade,
+		weight,
+		post_upgrade,
+	});
+}
+
+macro_rules! impl_migration_set_for_tuples {
+	() => {};
+	($first:ident $(, $rest:ident)*) => {
+		impl<$first: Migration, $($rest: Migration),*> MigrationSet for ($first, $($rest,)*) {
+			fn migrate() -> Weight {
+				let mut weight = $first::migrate();
+				$( weight = weight.saturating_add($rest::migrate()); )*
+				weight
+			}
+
+			fn dry_run(report: &mut MigrationReport) {
+				dry_run_one::<$first>(report);
+				$( dry_run_one::<$rest>(report); )*
+			}
+		}
+
+		impl_migration_set_for_tuples!($($rest),*);
+	};
+}
+
+impl_migration_set_for_tuples!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P);
+
+/// Runs the migrations of `Set` in order on a runtime upgrade.
+///
+/// Meant to be passed as the `OnRuntimeUpgrade` parameter of [`crate::Executive`]. The checks
+/// of the migrations are only run by [`Migrations::dry_run`], never on-chain.
+pub struct Migrations<Set>(PhantomData<Set>);
+
+impl<Set: MigrationSet> Migrations<Set> {
+	/// Execute all migrations with their `pre_upgrade` and `post_upgrade` checks.
+	///
+	/// This writes to storage like a real upgrade would, and is meant to be called from a
+	/// runtime api whose changes are discarded.
+	pub fn dry_run() -> MigrationReport {
+		let mut report = MigrationReport::default();
+		Set::dry_run(&mut report);
+		report
+	}
+}
+
+impl<Set: MigrationSet> OnRuntimeUpgrade for Migrations<Set> {
+	fn on_runtime_upgrade() -> Weight {
+		Set::migrate()
+	}
+}
+
+/// Runtime upgrade logic that can be dry-run with the checks of its migrations.
+pub trait DryRunUpgrade: OnRuntimeUpgrade {
+	/// Execute the upgrade logic with its checks, appending the outcomes to `report`.
+	fn dry_run(report: &mut MigrationReport);
+}
+
+impl DryRunUpgrade for () {
+	fn dry_run(_: &mut MigrationReport) {}
+}
+
+impl<Set: MigrationSet> DryRunUpgrade for Migrations<Set> {
+	fn dry_run(report: &mut MigrationReport) {
+		Set::dry_run(report);
+	}
+}
+
+/// Execute an upgrade hook without checks, appending its outcome to `report` under `name`.
+pub(crate) fn dry_run_hook<H: OnRuntimeUpgrade>(name: &str, report: &mut MigrationReport) {
+	let weight = H::on_runtime_upgrade();
+	report.weight = report.weight.saturating_add(weight);
+	report.migrations.push(MigrationResult {
+		name: name.as_bytes().to_vec(),
+		pre_upgrade: Ok(()),
+		weight,
+		post_upgrade: Ok(()),
+	});
+}
+
+sp_api::decl_runtime_apis! {
+	/// Runtime api to dry-run the storage migrations of a runtime against some state.
+	pub trait MigrationApi {
+		/// Execute all migrations with their checks and report the outcome.
+		fn dry_run_upgrade() -> MigrationReport;
+	}
+}
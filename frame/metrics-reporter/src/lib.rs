@@ -0,0 +1,341 @@
+//! # Metrics Reporter Pallet
+//!
+//! Reports on-chain metrics to an HTTP endpoint from the offchain worker.
+//!
+//! ## Overview
+//!
+//! Every `ReportInterval` blocks, the offchain worker of a node collects the metrics of the
+//! runtime's `MetricsSource` into a JSON report:
+//!
+//! ```json
+//! {"block":100,"metrics":[{"name":"mission_token_total_issuance","labels":{"token":1},"value":"1000"}]}
+//! ```
+//!
+//! Values are strings, as balances do not fit into the integers of every JSON reader. The report
+//! is signed with the first local key of type `mrep`, and POSTed to the endpoint stored raw in the
+//! persistent offchain storage under `ENDPOINT_KEY`, with the hex encoded public key and signature
+//! in the `X-Metrics-Public` and `X-Metrics-Signature` headers. Nodes without an endpoint or a key
+//! do not report.
+//!
+//! A report that cannot be delivered is retried with exponential backoff, until `MaxAttempts`
+//! deliveries failed or the next report supersedes it. The `DeliveryStatus` is kept in the
+//! persistent offchain storage under `STATUS_KEY`.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::{Decode, Encode};
+use frame_support::{debug, decl_module, traits::Get, Parameter};
+use sp_core::crypto::KeyTypeId;
+use sp_runtime::{
+    offchain::{
+        http,
+        storage::StorageValueRef,
+        storage_lock::{StorageLock, Time},
+        Duration, StorageKind,
+    },
+    traits::{Member, Saturating, UniqueSaturatedInto},
+    RuntimeAppPublic, RuntimeDebug,
+};
+use sp_std::prelude::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+/// Key type of the keys signing reports.
+pub const KEY_TYPE: KeyTypeId = KeyTypeId(*b"mrep");
+
+/// Key of the endpoint reports are POSTed to, in the persistent offchain storage.
+pub const ENDPOINT_KEY: &[u8] = b"metrics-reporter::endpoint";
+/// Key of the `DeliveryStatus`, in the persistent offchain storage.
+pub const STATUS_KEY: &[u8] = b"metrics-reporter::status";
+/// Key of the lock keeping offchain workers of different blocks from reporting at once.
+const LOCK_KEY: &[u8] = b"metrics-reporter::lock";
+
+/// Time given to the endpoint to answer a report.
+const HTTP_TIMEOUT_MS: u64 = 5_000;
+
+/// Keys signing reports, of type `mrep`.
+pub mod sr25519 {
+    mod app_sr25519 {
+        use sp_application_crypto::{app_crypto, sr25519};
+        app_crypto!(sr25519, super::super::KEY_TYPE);
+    }
+
+    /// A key signing metrics reports.
+    pub type ReporterId = app_sr25519::Public;
+}
+
+/// A value of a metric.
+#[derive(Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct Metric {
+    /// Name of the metric, such as `mission_token_total_issuance`.
+    ///
+    /// Names and label names are written into the report as they are and must not need escaping
+    /// in a JSON string.
+    pub name: &'static str,
+    /// Labels telling apart values of the same metric, such as the mission token of an issuance.
+    pub labels: Vec<(&'static str, u64)>,
+    /// The value.
+    pub value: u128,
+}
+
+/// Metrics to report.
+pub trait MetricsSource {
+    /// The current value of the metrics.
+    fn metrics() -> Vec<Metric>;
+}
+
+#[impl_trait_for_tuples::impl_for_tuples(8)]
+impl MetricsSource for Tuple {
+    fn metrics() -> Vec<Metric> {
+        let mut metrics = Vec::new();
+        for_tuples!( #( metrics.extend(Tuple::metrics()); )* );
+        metrics
+    }
+}
+
+/// A report waiting to be delivered.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct PendingReport<BlockNumber> {
+    /// Block the report was made at.
+    pub block: BlockNumber,
+    /// The JSON report.
+    pub body: Vec<u8>,
+    /// Key the report is signed with.
+    pub public: Vec<u8>,
+    /// Signature of `body`.
+    pub signature: Vec<u8>,
+    /// Number of failed deliveries of the report.
+    pub attempts: u32,
+    /// Block from which the report is next delivered.
+    pub next_attempt: BlockNumber,
+}
+
+/// The delivery status of reports.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Default, RuntimeDebug)]
+pub struct DeliveryStatus<BlockNumber> {
+    /// Block the last report was made at.
+    pub last_report: Option<BlockNumber>,
+    /// Block the last delivered report was made at.
+    pub last_delivered: Option<BlockNumber>,
+    /// The report waiting to be delivered, if any.
+    pub pending: Option<PendingReport<BlockNumber>>,
+    /// Number of reports given up on, for failing `MaxAttempts` deliveries or being superseded.
+    pub dropped: u32,
+}
+
+impl<BlockNumber: Copy + PartialOrd + Saturating + From<u32>> DeliveryStatus<BlockNumber> {
+    /// Whether a report is due at `now`.
+    pub fn is_report_due(&self, now: BlockNumber, interval: BlockNumber) -> bool {
+        self.last_report
+            .map_or(true, |last| now >= last.saturating_add(interval))
+    }
+
+    /// Make `report` the report waiting to be delivered, superseding any other.
+    pub fn reported(&mut self, report: PendingReport<BlockNumber>) {
+        if self.pending.is_some() {
+            self.dropped = self.dropped.saturating_add(1);
+        }
+        self.last_report = Some(report.block);
+        self.pending = Some(report);
+    }
+
+    /// The pending report was delivered.
+    pub fn delivered(&mut self) {
+        if let Some(report) = self.pending.take() {
+            self.last_delivered = Some(report.block);
+        }
+    }
+
+    /// Delivering the pending report at `now` failed.
+    ///
+    /// The report is retried after 1, 2, 4... blocks, never waiting longer than `interval`,
+    /// and given up on after `max_attempts` failed deliveries.
+    pub fn failed(&mut self, now: BlockNumber, max_attempts: u32, interval: BlockNumber) {
+        let report = match self.pending.as_mut() {
+            Some(report) => report,
+            None => return,
+        };
+        report.attempts = report.attempts.saturating_add(1);
+        if report.attempts >= max_attempts {
+            self.pending = None;
+            self.dropped = self.dropped.saturating_add(1);
+            return;
+        }
+        let backoff = BlockNumber::from(1u32 << (report.attempts - 1).min(16));
+        let backoff = if backoff > interval { interval } else { backoff };
+        report.next_attempt = now.saturating_add(backoff);
+    }
+}
+
+pub trait Trait: frame_system::Trait {
+    /// The identifier type of the keys signing reports.
+    type AuthorityId: Member + Parameter + RuntimeAppPublic + Default + Ord;
+
+    /// The metrics reported.
+    type Metrics: MetricsSource;
+
+    /// Number of blocks between two reports.
+    type ReportInterval: Get<Self::BlockNumber>;
+
+    /// Number of failed deliveries after which a report is given up on.
+    type MaxAttempts: Get<u32>;
+}
+
+decl_module! {
+    pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+        const ReportInterval: T::BlockNumber = T::ReportInterval::get();
+
+        const MaxAttempts: u32 = T::MaxAttempts::get();
+
+        fn offchain_worker(now: T::BlockNumber) {
+            if let Err(e) = Self::report(now) {
+                debug::warn!("Metrics report at block {:?} failed: {}", now, e);
+            }
+        }
+    }
+}
+
+impl<T: Trait> Module<T> {
+    /// Make a report at `now` if one is due, and deliver the pending report if it is time to.
+    fn report(now: T::BlockNumber) -> Result<(), &'static str> {
+        let endpoint = match sp_io::offchain::local_storage_get(StorageKind::PERSISTENT, ENDPOINT_KEY) {
+            Some(endpoint) => endpoint,
+            None => return Ok(()),
+        };
+        let endpoint = sp_std::str::from_utf8(&endpoint).map_err(|_| "endpoint is not UTF-8")?;
+
+        // another offchain worker is reporting.
+        let mut lock = StorageLock::<Time>::new(LOCK_KEY);
+        let _guard = match lock.try_lock() {
+            Ok(guard) => guard,
+            Err(_) => return Ok(()),
+        };
+
+        let status_ref = StorageValueRef::persistent(STATUS_KEY);
+        let mut status = status_ref
+            .get::<DeliveryStatus<T::BlockNumber>>()
+            .flatten()
+            .unwrap_or_default();
+        let interval = T::ReportInterval::get();
+
+        let mut result = Ok(());
+        if status.is_report_due(now, interval) {
+            match Self::make_report(now) {
+                Some(report) => status.reported(report),
+                None => {
+                    // retry when the next report is due rather than warning every block.
+                    status.last_report = Some(now);
+                    result = Err("no key to sign reports with");
+                }
+            }
+        }
+
+        let deliver = status.pending.as_ref().filter(|report| report.next_attempt <= now);
+        if let Some(report) = deliver {
+            match Self::deliver(endpoint, report) {
+                Ok(()) => status.delivered(),
+                Err(e) => {
+                    status.failed(now, T::MaxAttempts::get(), interval);
+                    result = Err(e);
+                }
+            }
+        }
+
+        status_ref.set(&status);
+        result
+    }
+
+    /// A report of the current metrics, signed with the first local key.
+    fn make_report(now: T::BlockNumber) -> Option<PendingReport<T::BlockNumber>> {
+        let key = T::AuthorityId::all().into_iter().next()?;
+        let body = encode_report(now.unique_saturated_into(), &T::Metrics::metrics());
+        let signature = key.sign(&body)?;
+        Some(PendingReport {
+            block: now,
+            body,
+            public: key.encode(),
+            signature: signature.encode(),
+            attempts: 0,
+            next_attempt: now,
+        })
+    }
+
+    /// POST `report` to `endpoint`.
+    fn deliver(endpoint: &str, report: &PendingReport<T::BlockNumber>) -> Result<(), &'static str> {
+        let (public, signature) = (to_hex(&report.public), to_hex(&report.signature));
+        let public = sp_std::str::from_utf8(&public).expect("hex digits are ASCII; qed");
+        let signature = sp_std::str::from_utf8(&signature).expect("hex digits are ASCII; qed");
+        let deadline = sp_io::offchain::timestamp().add(Duration::from_millis(HTTP_TIMEOUT_MS));
+
+        let pending = http::Request::post(endpoint, vec![report.body.clone()])
+            .add_header("Content-Type", "application/json")
+            .add_header("X-Metrics-Public", public)
+            .add_header("X-Metrics-Signature", signature)
+            .deadline(deadline)
+            .send()
+            .map_err(|_| "cannot send the report")?;
+        let response = pending
+            .try_wait(deadline)
+            .map_err(|_| "the endpoint did not answer in time")?
+            .map_err(|_| "the endpoint did not answer")?;
+        if response.code / 100 != 2 {
+            return Err("the endpoint refused the report");
+        }
+        Ok(())
+    }
+}
+
+/// The JSON report of `metrics` at `block`.
+pub fn encode_report(block: u64, metrics: &[Metric]) -> Vec<u8> {
+    let mut json = b"{\"block\":".to_vec();
+    push_number(&mut json, block as u128);
+    json.extend_from_slice(b",\"metrics\":[");
+    for (i, metric) in metrics.iter().enumerate() {
+        if i > 0 {
+            json.push(b',');
+        }
+        json.extend_from_slice(b"{\"name\":\"");
+        json.extend_from_slice(metric.name.as_bytes());
+        json.extend_from_slice(b"\",\"labels\":{");
+        for (j, (label, value)) in metric.labels.iter().enumerate() {
+            if j > 0 {
+                json.push(b',');
+            }
+            json.push(b'"');
+            json.extend_from_slice(label.as_bytes());
+            json.extend_from_slice(b"\":");
+            push_number(&mut json, *value as u128);
+        }
+        json.extend_from_slice(b"},\"value\":\"");
+        push_number(&mut json, metric.value);
+        json.extend_from_slice(b"\"}");
+    }
+    json.extend_from_slice(b"]}");
+    json
+}
+
+fn push_number(json: &mut Vec<u8>, mut number: u128) {
+    let mut digits = Vec::new();
+    loop {
+        digits.push(b'0' + (number % 10) as u8);
+        number /= 10;
+        if number == 0 {
+            break;
+        }
+    }
+    json.extend(digits.into_iter().rev());
+}
+
+fn to_hex(bytes: &[u8]) -> Vec<u8> {
+    const DIGITS: &[u8] = b"0123456789abcdef";
+    let mut hex = b"0x".to_vec();
+    for byte in bytes {
+        hex.push(DIGITS[(byte >> 4) as usize]);
+        hex.push(DIGITS[(byte & 0xf) as usize]);
+    }
+    hex
+}
@@ -0,0 +1,92 @@
+use crate::{Metric, MetricsSource, Module, Trait};
+use frame_support::{impl_outer_origin, parameter_types, weights::Weight};
+use frame_system as system;
+use parking_lot::RwLock;
+use sp_core::{
+    offchain::{testing, OffchainExt},
+    H256,
+};
+use sp_runtime::{
+    testing::{Header, UintAuthorityId},
+    traits::{BlakeTwo256, IdentityLookup},
+    Perbill,
+};
+use std::sync::Arc;
+
+impl_outer_origin! {
+    pub enum Origin for Test {}
+}
+
+#[derive(Clone, Eq, PartialEq)]
+pub struct Test;
+parameter_types! {
+    pub const BlockHashCount: u64 = 250;
+    pub const MaximumBlockWeight: Weight = 1024;
+    pub const MaximumBlockLength: u32 = 2 * 1024;
+    pub const AvailableBlockRatio: Perbill = Perbill::from_percent(75);
+}
+
+impl system::Trait for Test {
+    type BaseCallFilter = ();
+    type Origin = Origin;
+    type Call = ();
+    type Index = u64;
+    type BlockNumber = u64;
+    type Hash = H256;
+    type Hashing = BlakeTwo256;
+    type AccountId = u64;
+    type Lookup = IdentityLookup<Self::AccountId>;
+    type Header = Header;
+    type Event = ();
+    type BlockHashCount = BlockHashCount;
+    type MaximumBlockWeight = MaximumBlockWeight;
+    type DbWeight = ();
+    type BlockExecutionWeight = ();
+    type ExtrinsicBaseWeight = ();
+    type MaximumExtrinsicWeight = MaximumBlockWeight;
+    type MaximumBlockLength = MaximumBlockLength;
+    type AvailableBlockRatio = AvailableBlockRatio;
+    type Version = ();
+    type PalletInfo = ();
+    type AccountData = ();
+    type OnNewAccount = ();
+    type OnKilledAccount = ();
+    type SystemWeightInfo = ();
+}
+
+pub struct TestMetrics;
+impl MetricsSource for TestMetrics {
+    fn metrics() -> Vec<Metric> {
+        vec![
+            Metric { name: "mission_token_total_issuance", labels: vec![("token", 1)], value: 1_000 },
+            Metric { name: "username_registrations", labels: vec![], value: 3 },
+        ]
+    }
+}
+
+parameter_types! {
+    pub const ReportInterval: u64 = 10;
+    pub const MaxAttempts: u32 = 3;
+}
+
+impl Trait for Test {
+    type AuthorityId = UintAuthorityId;
+    type Metrics = TestMetrics;
+    type ReportInterval = ReportInterval;
+    type MaxAttempts = MaxAttempts;
+}
+
+pub type MetricsReporter = Module<Test>;
+
+pub const ENDPOINT: &str = "http://localhost:8080/metrics";
+
+/// Externalities with an offchain extension answering the requests expected of the state.
+pub fn new_test_ext() -> (sp_io::TestExternalities, Arc<RwLock<testing::OffchainState>>) {
+    let (offchain, state) = testing::TestOffchainExt::new();
+    let mut ext: sp_io::TestExternalities = system::GenesisConfig::default()
+        .build_storage::<Test>()
+        .unwrap()
+        .into();
+    ext.register_extension(OffchainExt::new(offchain));
+    (ext, state)
+}
@@ -0,0 +1,115 @@
+use crate::{mock::*, *};
+use frame_support::traits::OffchainWorker;
+use sp_core::offchain::testing::PendingRequest;
+use sp_runtime::testing::UintAuthorityId;
+
+fn set_endpoint() {
+    sp_io::offchain::local_storage_set(StorageKind::PERSISTENT, ENDPOINT_KEY, ENDPOINT.as_bytes());
+}
+
+fn status() -> Option<DeliveryStatus<u64>> {
+    StorageValueRef::persistent(STATUS_KEY).get().flatten()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    String::from_utf8(to_hex(bytes)).unwrap()
+}
+
+fn pending_report(block: u64) -> PendingReport<u64> {
+    PendingReport {
+        block,
+        body: b"{}".to_vec(),
+        public: vec![],
+        signature: vec![],
+        attempts: 0,
+        next_attempt: block,
+    }
+}
+
+#[test]
+fn report_is_encoded_as_json() {
+    assert_eq!(
+        encode_report(100, &TestMetrics::metrics()),
+        br#"{"block":100,"metrics":[{"name":"mission_token_total_issuance","labels":{"token":1},"value":"1000"},{"name":"username_registrations","labels":{},"value":"3"}]}"#.to_vec(),
+    );
+    assert_eq!(encode_report(0, &[]), br#"{"block":0,"metrics":[]}"#.to_vec());
+    assert_eq!(<(TestMetrics, TestMetrics)>::metrics().len(), 4);
+}
+
+#[test]
+fn signed_report_is_posted_to_endpoint() {
+    UintAuthorityId::set_all_keys(vec![7]);
+    let (mut ext, state) = new_test_ext();
+    let body = encode_report(1, &TestMetrics::metrics());
+    let signature = UintAuthorityId(7).sign(&body).unwrap();
+    state.write().expect_request(PendingRequest {
+        method: "POST".into(),
+        uri: ENDPOINT.into(),
+        headers: vec![
+            ("Content-Type".into(), "application/json".into()),
+            ("X-Metrics-Public".into(), hex(&UintAuthorityId(7).encode())),
+            ("X-Metrics-Signature".into(), hex(&signature.encode())),
+        ],
+        body,
+        sent: true,
+        response: Some(b"ok".to_vec()),
+        ..Default::default()
+    });
+
+    ext.execute_with(|| {
+        set_endpoint();
+        MetricsReporter::offchain_worker(1);
+        assert_eq!(
+            status(),
+            Some(DeliveryStatus { last_report: Some(1), last_delivered: Some(1), pending: None, dropped: 0 }),
+        );
+
+        // no report is due before the next interval, so no request is made.
+        MetricsReporter::offchain_worker(2);
+        assert_eq!(status().unwrap().last_report, Some(1));
+    });
+}
+
+#[test]
+fn nothing_is_reported_without_endpoint_or_key() {
+    UintAuthorityId::set_all_keys(Vec::<u64>::new());
+    let (mut ext, _state) = new_test_ext();
+    ext.execute_with(|| {
+        MetricsReporter::offchain_worker(1);
+        assert_eq!(status(), None);
+
+        set_endpoint();
+        MetricsReporter::offchain_worker(1);
+        assert_eq!(
+            status(),
+            Some(DeliveryStatus { last_report: Some(1), last_delivered: None, pending: None, dropped: 0 }),
+        );
+    });
+}
+
+#[test]
+fn failed_deliveries_back_off_until_given_up() {
+    let mut status = DeliveryStatus::<u64>::default();
+    assert!(status.is_report_due(1, 10));
+    status.reported(pending_report(1));
+    assert!(!status.is_report_due(10, 10));
+    assert!(status.is_report_due(11, 10));
+
+    status.failed(1, 4, 10);
+    assert_eq!(status.pending.as_ref().map(|r| (r.attempts, r.next_attempt)), Some((1, 2)));
+    status.failed(2, 4, 10);
+    assert_eq!(status.pending.as_ref().map(|r| (r.attempts, r.next_attempt)), Some((2, 4)));
+    // backoff never exceeds the report interval.
+    status.failed(4, 4, 3);
+    assert_eq!(status.pending.as_ref().map(|r| (r.attempts, r.next_attempt)), Some((3, 7)));
+    status.failed(7, 4, 10);
+    assert_eq!(status.pending, None);
+    assert_eq!(status.dropped, 1);
+
+    // a report superseded by the next one is dropped too.
+    status.reported(pending_report(11));
+    status.reported(pending_report(21));
+    assert_eq!(status.dropped, 2);
+    status.delivered();
+    assert_eq!((status.last_report, status.last_delivered, status.pending), (Some(21), Some(21), None));
+}
@@ -0,0 +1,585 @@
+//! # Mission Council Pallet
+//!
+//! A council for every mission.
+//!
+//! ## Overview
+//!
+//! Each mission has its own set of council members. A member can propose a motion to the council
+//! of their mission; once enough members approved it, the motion is dispatched with the
+//! `Members(token_id, approvals, members)` origin. Other pallets restrict calls to the council of
+//! a given mission through `EnsureMissionMembers`, `EnsureMissionProportionMoreThan` and
+//! `EnsureMissionProportionAtLeast`, which all return the mission token id so that the caller can
+//! check it against the mission the call acts on.
+//!
+//! The members of a mission council are set by `MembershipOrigin`, which also returns the
+//! mission token id. In the runtime this is a passed referendum of the mission's token holders,
+//! so that councils are elected by the holders of their mission's token.
+//!
+//! ### Dispatchable Functions
+//!
+//! * `set_members` - Set the members of a mission council.
+//! * `propose` - Propose a motion to the council of a mission.
+//! * `vote` - Vote on a motion.
+//! * `close` - Close a motion that is approved, disapproved or whose voting period has ended.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::{Decode, Encode};
+use frame_support::{
+    decl_error, decl_event, decl_module, decl_storage,
+    dispatch::{DispatchError, DispatchResult, DispatchResultWithPostInfo, PostDispatchInfo},
+    ensure,
+    traits::{EnsureOrigin, Get},
+    weights::{DispatchClass, GetDispatchInfo, Weight},
+    Parameter,
+};
+use frame_system::{self as system, ensure_signed};
+use sp_core::u32_trait::Value as U32;
+use sp_io::storage;
+use sp_runtime::{
+    traits::{Dispatchable, Hash},
+    RuntimeDebug,
+};
+use sp_std::{marker::PhantomData, prelude::*, result};
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+/// Simple index type for proposal counting.
+pub type ProposalIndex = u32;
+
+/// A number of members.
+pub type MemberCount = u32;
+
+type TokenId<T> = <T as pallet_mission_tokens::Trait>::MissionTokenId;
+
+pub trait Trait: frame_system::Trait + pallet_mission_tokens::Trait {
+    /// The overarching event type.
+    type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
+
+    /// The outer origin type.
+    type Origin: From<RawOrigin<TokenId<Self>>>;
+
+    /// The outer call dispatch type.
+    type Proposal: Parameter
+        + Dispatchable<Origin = <Self as Trait>::Origin, PostInfo = PostDispatchInfo>
+        + GetDispatchInfo;
+
+    /// The time-out for council motions.
+    type MotionDuration: Get<Self::BlockNumber>;
+
+    /// Maximum number of proposals allowed to be active in parallel in a single mission council.
+    type MaxProposals: Get<ProposalIndex>;
+
+    /// The maximum number of members of a single mission council.
+    type MaxMembers: Get<MemberCount>;
+
+    /// Origin which may set the members of the council of the mission it returns.
+    type MembershipOrigin: EnsureOrigin<
+        <Self as frame_system::Trait>::Origin,
+        Success = TokenId<Self>,
+    >;
+
+    /// Origin which may set the members of any mission council.
+    type ForceOrigin: EnsureOrigin<<Self as frame_system::Trait>::Origin>;
+}
+
+/// Origin for the mission council module.
+#[derive(PartialEq, Eq, Clone, RuntimeDebug, Encode, Decode)]
+pub enum RawOrigin<MissionTokenId> {
+    /// It has been condoned by a given number of members of the council of a mission from a
+    /// given total.
+    Members(MissionTokenId, MemberCount, MemberCount),
+}
+
+/// Origin for the mission council module.
+pub type Origin<T> = RawOrigin<<T as pallet_mission_tokens::Trait>::MissionTokenId>;
+
+/// Info for keeping track of a motion being voted on.
+#[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug)]
+pub struct Votes<AccountId, BlockNumber> {
+    /// The proposal's unique index.
+    index: ProposalIndex,
+    /// The number of approval votes that are needed to pass the motion.
+    threshold: MemberCount,
+    /// The current set of voters that approved it.
+    ayes: Vec<AccountId>,
+    /// The current set of voters that rejected it.
+    nays: Vec<AccountId>,
+    /// The hard end time of this vote.
+    end: BlockNumber,
+}
+
+decl_storage! {
+    trait Store for Module<T: Trait> as MissionCouncil {
+        /// The hashes of the active proposals of each mission council.
+        pub Proposals get(fn proposals): map hasher(twox_64_concat) TokenId<T> => Vec<T::Hash>;
+
+        /// Actual proposal for a given mission and hash, if it's current.
+        pub ProposalOf get(fn proposal_of):
+            double_map hasher(twox_64_concat) TokenId<T>, hasher(identity) T::Hash
+            => Option<<T as Trait>::Proposal>;
+
+        /// Votes on a given proposal, if it is ongoing.
+        pub Voting get(fn voting):
+            double_map hasher(twox_64_concat) TokenId<T>, hasher(identity) T::Hash
+            => Option<Votes<T::AccountId, T::BlockNumber>>;
+
+        /// Proposals so far, across all missions.
+        pub ProposalCount get(fn proposal_count): ProposalIndex;
+
+        /// The current members of each mission council. This is stored sorted (just by value).
+        pub Members get(fn members): map hasher(twox_64_concat) TokenId<T> => Vec<T::AccountId>;
+    }
+}
+
+decl_event!(
+    pub enum Event<T>
+    where
+        AccountId = <T as frame_system::Trait>::AccountId,
+        Hash = <T as frame_system::Trait>::Hash,
+        MissionTokenId = <T as pallet_mission_tokens::Trait>::MissionTokenId,
+    {
+        /// The members of a mission council have been set. \[mission_token_id, members\]
+        MembersSet(MissionTokenId, Vec<AccountId>),
+        /// A motion has been proposed to a mission council.
+        /// \[account, mission_token_id, proposal_index, proposal_hash, threshold\]
+        Proposed(AccountId, MissionTokenId, ProposalIndex, Hash, MemberCount),
+        /// A motion has been voted on by given account, leaving a tally (yes votes and no votes
+        /// given respectively as `MemberCount`).
+        /// \[account, mission_token_id, proposal_hash, voted, yes, no\]
+        Voted(AccountId, MissionTokenId, Hash, bool, MemberCount, MemberCount),
+        /// A motion was approved by the required threshold. \[mission_token_id, proposal_hash\]
+        Approved(MissionTokenId, Hash),
+        /// A motion was not approved by the required threshold.
+        /// \[mission_token_id, proposal_hash\]
+        Disapproved(MissionTokenId, Hash),
+        /// A motion was executed; result will be `Ok` if it returned without error.
+        /// \[mission_token_id, proposal_hash, result\]
+        Executed(MissionTokenId, Hash, DispatchResult),
+        /// A proposal was closed because its threshold was reached or after its duration was up.
+        /// \[mission_token_id, proposal_hash, yes, no\]
+        Closed(MissionTokenId, Hash, MemberCount, MemberCount),
+    }
+);
+
+decl_error! {
+    pub enum Error for Module<T: Trait> {
+        /// Account is not a member of the mission council
+        NotMember,
+        /// Duplicate proposals not allowed
+        DuplicateProposal,
+        /// Proposal must exist
+        ProposalMissing,
+        /// Mismatched index
+        WrongIndex,
+        /// Duplicate vote ignored
+        DuplicateVote,
+        /// The close call was made too early, before the end of the voting.
+        TooEarly,
+        /// There can only be a maximum of `MaxProposals` active proposals.
+        TooManyProposals,
+        /// There can only be a maximum of `MaxMembers` members.
+        TooManyMembers,
+        /// The given weight bound for the proposal was too low.
+        WrongProposalWeight,
+        /// The given length bound for the proposal was too low.
+        WrongProposalLength,
+        /// The origin may only act on another mission.
+        WrongMission,
+    }
+}
+
+/// Return the weight of a dispatch call result as an `Option`.
+///
+/// Will return the weight regardless of what the state of the result is.
+fn get_result_weight(result: DispatchResultWithPostInfo) -> Option<Weight> {
+    match result {
+        Ok(post_info) => post_info.actual_weight,
+        Err(err) => err.post_info.actual_weight,
+    }
+}
+
+// Note that councillor operations are assigned to the operational class.
+decl_module! {
+    pub struct Module<T: Trait> for enum Call where origin: <T as frame_system::Trait>::Origin {
+        type Error = Error<T>;
+
+        /// The time-out for council motions.
+        const MotionDuration: T::BlockNumber = T::MotionDuration::get();
+
+        /// Maximum number of proposals allowed to be active in parallel in a mission council.
+        const MaxProposals: ProposalIndex = T::MaxProposals::get();
+
+        /// The maximum number of members of a mission council.
+        const MaxMembers: MemberCount = T::MaxMembers::get();
+
+        fn deposit_event() = default;
+
+        /// Set the members of the council of `token_id`.
+        ///
+        /// The dispatch origin of this call must be `MembershipOrigin` for the same mission, or
+        /// `ForceOrigin`.
+        ///
+        /// Votes of outgoing members on ongoing motions are discarded.
+        #[weight = (
+            30_000_000 + T::DbWeight::get().reads_writes(2, 1 + T::MaxProposals::get() as Weight),
+            DispatchClass::Operational
+        )]
+        fn set_members(origin, #[compact] token_id: TokenId<T>, new_members: Vec<T::AccountId>) {
+            match T::MembershipOrigin::try_origin(origin) {
+                Ok(id) => {
+                    ensure!(id == token_id, Error::<T>::WrongMission);
+                }
+                Err(origin) => {
+                    T::ForceOrigin::ensure_origin(origin)?;
+                }
+            }
+            <pallet_mission_tokens::Module<T>>::validate_mission_token_id(token_id)?;
+            ensure!(
+                new_members.len() <= T::MaxMembers::get() as usize,
+                Error::<T>::TooManyMembers
+            );
+
+            let mut new_members = new_members;
+            new_members.sort();
+            new_members.dedup();
+            for hash in Self::proposals(token_id) {
+                <Voting<T>>::mutate(token_id, hash, |v| {
+                    if let Some(mut votes) = v.take() {
+                        votes.ayes.retain(|a| new_members.binary_search(a).is_ok());
+                        votes.nays.retain(|a| new_members.binary_search(a).is_ok());
+                        *v = Some(votes);
+                    }
+                });
+            }
+            <Members<T>>::insert(token_id, &new_members);
+
+            Self::deposit_event(RawEvent::MembersSet(token_id, new_members));
+        }
+
+        /// Add a new motion to the council of `token_id` to either be voted on or executed
+        /// directly.
+        ///
+        /// Requires the sender to be a member of that council.
+        ///
+        /// `threshold` determines whether `proposal` is executed directly (`threshold < 2`)
+        /// or put up for voting.
+        #[weight = (
+            if *threshold < 2 {
+                30_000_000u64
+                    .saturating_add(T::DbWeight::get().reads(2))
+                    .saturating_add(proposal.get_dispatch_info().weight)
+            } else {
+                40_000_000u64.saturating_add(T::DbWeight::get().reads_writes(3, 4))
+            },
+            DispatchClass::Operational
+        )]
+        fn propose(origin,
+            #[compact] token_id: TokenId<T>,
+            #[compact] threshold: MemberCount,
+            proposal: Box<<T as Trait>::Proposal>,
+            #[compact] length_bound: u32
+        ) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+            let members = Self::members(token_id);
+            ensure!(members.contains(&who), Error::<T>::NotMember);
+
+            let proposal_len = proposal.using_encoded(|x| x.len());
+            ensure!(proposal_len <= length_bound as usize, Error::<T>::WrongProposalLength);
+            let proposal_hash = T::Hashing::hash_of(&proposal);
+            ensure!(
+                !<ProposalOf<T>>::contains_key(token_id, proposal_hash),
+                Error::<T>::DuplicateProposal
+            );
+
+            if threshold < 2 {
+                let seats = members.len() as MemberCount;
+                let result = proposal.dispatch(RawOrigin::Members(token_id, 1, seats).into());
+                Self::deposit_event(
+                    RawEvent::Executed(token_id, proposal_hash, result.map(|_| ()).map_err(|e| e.error))
+                );
+
+                Ok(get_result_weight(result).map(|w| {
+                    30_000_000u64
+                        .saturating_add(T::DbWeight::get().reads(2))
+                        .saturating_add(w)
+                }).into())
+            } else {
+                <Proposals<T>>::try_mutate(token_id, |proposals| -> DispatchResult {
+                    proposals.push(proposal_hash);
+                    ensure!(
+                        proposals.len() <= T::MaxProposals::get() as usize,
+                        Error::<T>::TooManyProposals
+                    );
+                    Ok(())
+                })?;
+                let index = Self::proposal_count();
+                ProposalCount::mutate(|i| *i += 1);
+                <ProposalOf<T>>::insert(token_id, proposal_hash, *proposal);
+                let end = system::Module::<T>::block_number() + T::MotionDuration::get();
+                let votes = Votes { index, threshold, ayes: vec![who.clone()], nays: vec![], end };
+                <Voting<T>>::insert(token_id, proposal_hash, votes);
+
+                Self::deposit_event(RawEvent::Proposed(who, token_id, index, proposal_hash, threshold));
+                Ok(None.into())
+            }
+        }
+
+        /// Add an aye or nay vote for the sender to the given motion of the council of
+        /// `token_id`.
+        ///
+        /// Requires the sender to be a member of that council.
+        #[weight = (
+            20_000_000 + T::DbWeight::get().reads_writes(2, 1),
+            DispatchClass::Operational
+        )]
+        fn vote(origin,
+            #[compact] token_id: TokenId<T>,
+            proposal: T::Hash,
+            #[compact] index: ProposalIndex,
+            approve: bool,
+        ) {
+            let who = ensure_signed(origin)?;
+            ensure!(Self::members(token_id).contains(&who), Error::<T>::NotMember);
+
+            let mut voting = Self::voting(token_id, &proposal).ok_or(Error::<T>::ProposalMissing)?;
+            ensure!(voting.index == index, Error::<T>::WrongIndex);
+
+            let position_yes = voting.ayes.iter().position(|a| a == &who);
+            let position_no = voting.nays.iter().position(|a| a == &who);
+
+            if approve {
+                ensure!(position_yes.is_none(), Error::<T>::DuplicateVote);
+                voting.ayes.push(who.clone());
+                if let Some(pos) = position_no {
+                    voting.nays.swap_remove(pos);
+                }
+            } else {
+                ensure!(position_no.is_none(), Error::<T>::DuplicateVote);
+                voting.nays.push(who.clone());
+                if let Some(pos) = position_yes {
+                    voting.ayes.swap_remove(pos);
+                }
+            }
+
+            let yes_votes = voting.ayes.len() as MemberCount;
+            let no_votes = voting.nays.len() as MemberCount;
+            Self::deposit_event(RawEvent::Voted(who, token_id, proposal, approve, yes_votes, no_votes));
+
+            <Voting<T>>::insert(token_id, &proposal, voting);
+        }
+
+        /// Close a motion of the council of `token_id` that is either approved, disapproved or
+        /// whose voting period has ended.
+        ///
+        /// May be called by any signed account in order to finish voting and close the proposal.
+        ///
+        /// If called after the end of the voting period abstentions are counted as rejections.
+        ///
+        /// + `proposal_weight_bound`: The maximum amount of weight consumed by executing the closed proposal.
+        /// + `length_bound`: The upper bound for the length of the proposal in storage. Checked via
+        ///                   `storage::read` so it is `size_of::<u32>() == 4` larger than the pure length.
+        #[weight = (
+            50_000_000u64
+                .saturating_add(T::DbWeight::get().reads_writes(4, 3))
+                .saturating_add(*proposal_weight_bound),
+            DispatchClass::Operational
+        )]
+        fn close(origin,
+            #[compact] token_id: TokenId<T>,
+            proposal_hash: T::Hash,
+            #[compact] index: ProposalIndex,
+            #[compact] proposal_weight_bound: Weight,
+            #[compact] length_bound: u32
+        ) -> DispatchResultWithPostInfo {
+            let _ = ensure_signed(origin)?;
+
+            let voting = Self::voting(token_id, &proposal_hash).ok_or(Error::<T>::ProposalMissing)?;
+            ensure!(voting.index == index, Error::<T>::WrongIndex);
+
+            let no_votes = voting.nays.len() as MemberCount;
+            let yes_votes = voting.ayes.len() as MemberCount;
+            let seats = Self::members(token_id).len() as MemberCount;
+            let approved = yes_votes >= voting.threshold;
+            let disapproved = seats.saturating_sub(no_votes) < voting.threshold;
+
+            // Only allow closing an undecided proposal after the voting period has ended. Any
+            // abstentions are then counted as rejections.
+            if !approved && !disapproved {
+                ensure!(system::Module::<T>::block_number() >= voting.end, Error::<T>::TooEarly);
+            }
+
+            if approved {
+                let proposal = Self::validate_and_get_proposal(
+                    token_id,
+                    &proposal_hash,
+                    length_bound,
+                    proposal_weight_bound
+                )?;
+                Self::deposit_event(RawEvent::Closed(token_id, proposal_hash, yes_votes, no_votes));
+                let proposal_weight =
+                    Self::do_approve_proposal(token_id, seats, voting, proposal_hash, proposal);
+                Ok(Some(
+                    50_000_000u64
+                        .saturating_add(T::DbWeight::get().reads_writes(4, 3))
+                        .saturating_add(proposal_weight)
+                ).into())
+            } else {
+                Self::deposit_event(RawEvent::Closed(token_id, proposal_hash, yes_votes, no_votes));
+                Self::do_disapprove_proposal(token_id, proposal_hash);
+                Ok(Some(
+                    50_000_000u64.saturating_add(T::DbWeight::get().reads_writes(3, 3))
+                ).into())
+            }
+        }
+    }
+}
+
+impl<T: Trait> Module<T> {
+    /// Check whether `who` is a member of the council of `token_id`.
+    pub fn is_member(token_id: TokenId<T>, who: &T::AccountId) -> bool {
+        Self::members(token_id).contains(who)
+    }
+
+    /// Ensure that the right proposal bounds were passed and get the proposal from storage.
+    ///
+    /// Checks the length in storage via `storage::read` which adds an extra `size_of::<u32>() == 4`
+    /// to the length.
+    fn validate_and_get_proposal(
+        token_id: TokenId<T>,
+        hash: &T::Hash,
+        length_bound: u32,
+        weight_bound: Weight,
+    ) -> Result<<T as Trait>::Proposal, DispatchError> {
+        let key = <ProposalOf<T>>::hashed_key_for(token_id, hash);
+        // read the length of the proposal storage entry directly
+        let proposal_len =
+            storage::read(&key, &mut [0; 0], 0).ok_or(Error::<T>::ProposalMissing)?;
+        ensure!(proposal_len <= length_bound, Error::<T>::WrongProposalLength);
+        let proposal = <ProposalOf<T>>::get(token_id, hash).ok_or(Error::<T>::ProposalMissing)?;
+        ensure!(
+            proposal.get_dispatch_info().weight <= weight_bound,
+            Error::<T>::WrongProposalWeight
+        );
+        Ok(proposal)
+    }
+
+    /// Dispatch an approved motion with the `Members` origin of its mission and return the weight
+    /// it consumed.
+    fn do_approve_proposal(
+        token_id: TokenId<T>,
+        seats: MemberCount,
+        voting: Votes<T::AccountId, T::BlockNumber>,
+        proposal_hash: T::Hash,
+        proposal: <T as Trait>::Proposal,
+    ) -> Weight {
+        Self::deposit_event(RawEvent::Approved(token_id, proposal_hash));
+
+        let dispatch_weight = proposal.get_dispatch_info().weight;
+        let approvals = voting.ayes.len() as MemberCount;
+        let origin = RawOrigin::Members(token_id, approvals, seats).into();
+        let result = proposal.dispatch(origin);
+        Self::deposit_event(RawEvent::Executed(
+            token_id,
+            proposal_hash,
+            result.map(|_| ()).map_err(|e| e.error),
+        ));
+
+        Self::remove_proposal(token_id, proposal_hash);
+        // default to the dispatch info weight for safety
+        get_result_weight(result).unwrap_or(dispatch_weight)
+    }
+
+    fn do_disapprove_proposal(token_id: TokenId<T>, proposal_hash: T::Hash) {
+        Self::deposit_event(RawEvent::Disapproved(token_id, proposal_hash));
+        Self::remove_proposal(token_id, proposal_hash);
+    }
+
+    // Removes a proposal from the pallet, cleaning up votes and the vector of proposals.
+    fn remove_proposal(token_id: TokenId<T>, proposal_hash: T::Hash) {
+        <ProposalOf<T>>::remove(token_id, &proposal_hash);
+        <Voting<T>>::remove(token_id, &proposal_hash);
+        <Proposals<T>>::mutate(token_id, |proposals| {
+            proposals.retain(|h| h != &proposal_hash);
+        });
+    }
+}
+
+/// Ensure that the origin is at least `N` members of some mission council. Returns the mission
+/// token id.
+pub struct EnsureMissionMembers<N: U32, MissionTokenId>(PhantomData<(N, MissionTokenId)>);
+impl<
+        O: Into<Result<RawOrigin<MissionTokenId>, O>> + From<RawOrigin<MissionTokenId>>,
+        N: U32,
+        MissionTokenId: Default,
+    > EnsureOrigin<O> for EnsureMissionMembers<N, MissionTokenId>
+{
+    type Success = MissionTokenId;
+    fn try_origin(o: O) -> result::Result<Self::Success, O> {
+        o.into().and_then(|o| match o {
+            RawOrigin::Members(id, n, _) if n >= N::VALUE => Ok(id),
+            r => Err(O::from(r)),
+        })
+    }
+
+    #[cfg(feature = "runtime-benchmarks")]
+    fn successful_origin() -> O {
+        O::from(RawOrigin::Members(Default::default(), N::VALUE, N::VALUE))
+    }
+}
+
+/// Ensure that the origin is more than `N / D` of the members of some mission council. Returns the
+/// mission token id.
+pub struct EnsureMissionProportionMoreThan<N: U32, D: U32, MissionTokenId>(
+    PhantomData<(N, D, MissionTokenId)>,
+);
+impl<
+        O: Into<Result<RawOrigin<MissionTokenId>, O>> + From<RawOrigin<MissionTokenId>>,
+        N: U32,
+        D: U32,
+        MissionTokenId: Default,
+    > EnsureOrigin<O> for EnsureMissionProportionMoreThan<N, D, MissionTokenId>
+{
+    type Success = MissionTokenId;
+    fn try_origin(o: O) -> result::Result<Self::Success, O> {
+        o.into().and_then(|o| match o {
+            RawOrigin::Members(id, n, m) if n * D::VALUE > N::VALUE * m => Ok(id),
+            r => Err(O::from(r)),
+        })
+    }
+
+    #[cfg(feature = "runtime-benchmarks")]
+    fn successful_origin() -> O {
+        O::from(RawOrigin::Members(Default::default(), 1u32, 0u32))
+    }
+}
+
+/// Ensure that the origin is at least `N / D` of the members of some mission council. Returns the
+/// mission token id.
+pub struct EnsureMissionProportionAtLeast<N: U32, D: U32, MissionTokenId>(
+    PhantomData<(N, D, MissionTokenId)>,
+);
+impl<
+        O: Into<Result<RawOrigin<MissionTokenId>, O>> + From<RawOrigin<MissionTokenId>>,
+        N: U32,
+        D: U32,
+        MissionTokenId: Default,
+    > EnsureOrigin<O> for EnsureMissionProportionAtLeast<N, D, MissionTokenId>
+{
+    type Success = MissionTokenId;
+    fn try_origin(o: O) -> result::Result<Self::Success, O> {
+        o.into().and_then(|o| match o {
+            RawOrigin::Members(id, n, m) if n * D::VALUE >= N::VALUE * m => Ok(id),
+            r => Err(O::from(r)),
+        })
+    }
+
+    #[cfg(feature = "runtime-benchmarks")]
+    fn successful_origin() -> O {
+        O::from(RawOrigin::Members(Default::default(), 0u32, 0u32))
+    }
+}
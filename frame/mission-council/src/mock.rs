@@ -0,0 +1,151 @@
+use crate as mission_council;
+use crate::{EnsureMissionProportionMoreThan, Module, Trait};
+use frame_support::{
+    impl_outer_dispatch, impl_outer_origin, parameter_types, traits::OriginTrait, weights::Weight,
+};
+use frame_system::{self as system, EnsureRoot};
+use sp_core::{
+    u32_trait::{_1, _2},
+    H256,
+};
+use sp_runtime::{
+    testing::Header,
+    traits::{BlakeTwo256, IdentityLookup},
+    Perbill,
+};
+
+/// A pallet that records the origin of every call it receives.
+pub mod logger {
+    use super::*;
+    use frame_support::decl_module;
+    use std::cell::RefCell;
+
+    thread_local! {
+        static LOG: RefCell<Vec<(OriginCaller, u32)>> = RefCell::new(Vec::new());
+    }
+    pub fn log() -> Vec<(OriginCaller, u32)> {
+        LOG.with(|log| log.borrow().clone())
+    }
+    pub trait Trait: system::Trait {}
+    decl_module! {
+        pub struct Module<T: Trait> for enum Call
+        where
+            origin: <T as system::Trait>::Origin,
+            <T as system::Trait>::Origin: OriginTrait<PalletsOrigin = OriginCaller>
+        {
+            #[weight = 0]
+            fn log(origin, i: u32) {
+                LOG.with(|log| {
+                    log.borrow_mut().push((origin.caller().clone(), i));
+                })
+            }
+        }
+    }
+}
+
+impl_outer_origin! {
+    pub enum Origin for Test where system = frame_system {
+        mission_council<T>,
+    }
+}
+
+impl_outer_dispatch! {
+    pub enum Call for Test where origin: Origin {
+        frame_system::System,
+        logger::Logger,
+        mission_council::MissionCouncil,
+    }
+}
+
+// Configure a mock runtime to test the pallet.
+
+#[derive(Clone, Eq, PartialEq)]
+pub struct Test;
+parameter_types! {
+    pub const BlockHashCount: u64 = 250;
+    pub const MaximumBlockWeight: Weight = 1_000_000_000_000;
+    pub const MaximumBlockLength: u32 = 2 * 1024;
+    pub const AvailableBlockRatio: Perbill = Perbill::from_percent(75);
+}
+
+impl system::Trait for Test {
+    type BaseCallFilter = ();
+    type Origin = Origin;
+    type Call = Call;
+    type Index = u64;
+    type BlockNumber = u64;
+    type Hash = H256;
+    type Hashing = BlakeTwo256;
+    type AccountId = u64;
+    type Lookup = IdentityLookup<Self::AccountId>;
+    type Header = Header;
+    type Event = ();
+    type BlockHashCount = BlockHashCount;
+    type MaximumBlockWeight = MaximumBlockWeight;
+    type DbWeight = ();
+    type BlockExecutionWeight = ();
+    type ExtrinsicBaseWeight = ();
+    type MaximumExtrinsicWeight = MaximumBlockWeight;
+    type MaximumBlockLength = MaximumBlockLength;
+    type AvailableBlockRatio = AvailableBlockRatio;
+    type Version = ();
+    type PalletInfo = ();
+    type AccountData = ();
+    type OnNewAccount = ();
+    type OnKilledAccount = ();
+    type SystemWeightInfo = ();
+}
+
+impl logger::Trait for Test {}
+
+parameter_types! {
+    pub const ExistentialDeposit: u64 = 1;
+    pub const MaxMissionTokensSupply: u128 = 1_000_000;
+}
+
+impl pallet_mission_tokens::Trait for Test {
+    type Event = ();
+    type Balance = u64;
+    type MissionTokenId = u32;
+    type ExistentialDeposit = ExistentialDeposit;
+    type OnNewAccount = ();
+    type MaxMissionTokensSupply = MaxMissionTokensSupply;
+    type MissionCreatorOrigin = EnsureRoot<u64>;
+}
+
+parameter_types! {
+    pub const MotionDuration: u64 = 3;
+    pub const MaxProposals: u32 = 100;
+    pub const MaxMembers: u32 = 5;
+}
+
+impl Trait for Test {
+    type Event = ();
+    type Origin = Origin;
+    type Proposal = Call;
+    type MotionDuration = MotionDuration;
+    type MaxProposals = MaxProposals;
+    type MaxMembers = MaxMembers;
+    // A majority of a mission council can change its own members.
+    type MembershipOrigin = EnsureMissionProportionMoreThan<_1, _2, u32>;
+    type ForceOrigin = EnsureRoot<u64>;
+}
+
+pub type System = system::Module<Test>;
+pub type Logger = logger::Module<Test>;
+pub type MissionCouncil = Module<Test>;
+
+// Build genesis storage according to the mock runtime.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+    let mut ext: sp_io::TestExternalities = system::GenesisConfig::default()
+        .build_storage::<Test>()
+        .unwrap()
+        .into();
+    ext.execute_with(|| {
+        System::set_block_number(1);
+        // Mission 1 is run by accounts 1 to 3, mission 2 by accounts 4 and 5.
+        MissionCouncil::set_members(Origin::root(), 1, vec![3, 1, 2]).unwrap();
+        MissionCouncil::set_members(Origin::root(), 2, vec![4, 5]).unwrap();
+    });
+    ext
+}
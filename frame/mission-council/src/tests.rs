@@ -0,0 +1,194 @@
+use crate::{mock::*, Error, RawOrigin};
+use codec::Encode;
+use frame_support::{assert_noop, assert_ok};
+use sp_core::H256;
+use sp_runtime::traits::{BadOrigin, BlakeTwo256, Hash};
+
+fn log_proposal(i: u32) -> Call {
+    Call::Logger(logger::Call::log(i))
+}
+
+fn make_proposal(proposal: &Call) -> (H256, u32) {
+    (BlakeTwo256::hash_of(proposal), proposal.encode().len() as u32)
+}
+
+#[test]
+fn set_members_should_work() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(MissionCouncil::members(1), vec![1, 2, 3]);
+        assert_eq!(MissionCouncil::members(2), vec![4, 5]);
+        assert!(MissionCouncil::is_member(1, &2));
+        assert!(!MissionCouncil::is_member(2, &2));
+
+        assert_noop!(
+            MissionCouncil::set_members(Origin::signed(1), 1, vec![1]),
+            BadOrigin
+        );
+        assert_noop!(
+            MissionCouncil::set_members(Origin::root(), 1, vec![1, 2, 3, 4, 5, 6]),
+            Error::<Test>::TooManyMembers
+        );
+        assert_noop!(
+            MissionCouncil::set_members(Origin::root(), 100, vec![1]),
+            pallet_mission_tokens::Error::<Test>::InvalidMissionTokenId
+        );
+    });
+}
+
+#[test]
+fn propose_with_threshold_one_should_execute_directly() {
+    new_test_ext().execute_with(|| {
+        let proposal = log_proposal(42);
+        let (_, len) = make_proposal(&proposal);
+        assert_ok!(MissionCouncil::propose(
+            Origin::signed(1),
+            1,
+            1,
+            Box::new(proposal),
+            len
+        ));
+        assert_eq!(
+            logger::log(),
+            vec![(OriginCaller::mission_council(RawOrigin::Members(1, 1, 3)), 42)]
+        );
+    });
+}
+
+#[test]
+fn only_members_of_the_mission_council_can_propose_and_vote() {
+    new_test_ext().execute_with(|| {
+        let proposal = log_proposal(42);
+        let (hash, len) = make_proposal(&proposal);
+        assert_noop!(
+            MissionCouncil::propose(Origin::signed(4), 1, 2, Box::new(proposal.clone()), len),
+            Error::<Test>::NotMember
+        );
+        assert_ok!(MissionCouncil::propose(
+            Origin::signed(1),
+            1,
+            2,
+            Box::new(proposal),
+            len
+        ));
+        assert_noop!(
+            MissionCouncil::vote(Origin::signed(4), 1, hash, 0, true),
+            Error::<Test>::NotMember
+        );
+        assert_noop!(
+            MissionCouncil::vote(Origin::signed(1), 1, hash, 0, true),
+            Error::<Test>::DuplicateVote
+        );
+        assert_noop!(
+            MissionCouncil::vote(Origin::signed(2), 1, hash, 1, true),
+            Error::<Test>::WrongIndex
+        );
+    });
+}
+
+#[test]
+fn approved_motion_should_dispatch_with_mission_origin() {
+    new_test_ext().execute_with(|| {
+        let proposal = log_proposal(42);
+        let (hash, len) = make_proposal(&proposal);
+        assert_ok!(MissionCouncil::propose(
+            Origin::signed(1),
+            1,
+            2,
+            Box::new(proposal),
+            len
+        ));
+        assert_noop!(
+            MissionCouncil::close(Origin::signed(4), 1, hash, 0, 0, len),
+            Error::<Test>::TooEarly
+        );
+        assert_ok!(MissionCouncil::vote(Origin::signed(2), 1, hash, 0, true));
+        assert_ok!(MissionCouncil::close(Origin::signed(4), 1, hash, 0, 0, len));
+
+        assert_eq!(
+            logger::log(),
+            vec![(OriginCaller::mission_council(RawOrigin::Members(1, 2, 3)), 42)]
+        );
+        assert!(MissionCouncil::proposals(1).is_empty());
+        assert_eq!(MissionCouncil::voting(1, hash), None);
+    });
+}
+
+#[test]
+fn abstentions_should_count_as_rejections_after_motion_duration() {
+    new_test_ext().execute_with(|| {
+        let proposal = log_proposal(42);
+        let (hash, len) = make_proposal(&proposal);
+        assert_ok!(MissionCouncil::propose(
+            Origin::signed(1),
+            1,
+            3,
+            Box::new(proposal),
+            len
+        ));
+        assert_ok!(MissionCouncil::vote(Origin::signed(2), 1, hash, 0, true));
+
+        System::set_block_number(4);
+        assert_ok!(MissionCouncil::close(Origin::signed(4), 1, hash, 0, 0, len));
+        assert!(logger::log().is_empty());
+        assert_eq!(MissionCouncil::proposal_of(1, hash), None);
+    });
+}
+
+fn pass_motion(index: u32, proposal: Call) {
+    let (hash, len) = make_proposal(&proposal);
+    assert_ok!(MissionCouncil::propose(
+        Origin::signed(1),
+        1,
+        2,
+        Box::new(proposal),
+        len
+    ));
+    assert_ok!(MissionCouncil::vote(Origin::signed(3), 1, hash, index, true));
+    assert_ok!(MissionCouncil::close(
+        Origin::signed(1),
+        1,
+        hash,
+        index,
+        1_000_000_000,
+        len
+    ));
+}
+
+#[test]
+fn mission_council_can_only_set_its_own_members() {
+    new_test_ext().execute_with(|| {
+        pass_motion(
+            0,
+            Call::MissionCouncil(crate::Call::set_members(2, vec![1, 2])),
+        );
+        assert_eq!(MissionCouncil::members(2), vec![4, 5]);
+
+        pass_motion(
+            1,
+            Call::MissionCouncil(crate::Call::set_members(1, vec![1, 2])),
+        );
+        assert_eq!(MissionCouncil::members(1), vec![1, 2]);
+    });
+}
+
+#[test]
+fn set_members_should_drop_votes_of_outgoing_members() {
+    new_test_ext().execute_with(|| {
+        let proposal = log_proposal(42);
+        let (hash, len) = make_proposal(&proposal);
+        assert_ok!(MissionCouncil::propose(
+            Origin::signed(1),
+            1,
+            2,
+            Box::new(proposal),
+            len
+        ));
+        assert_ok!(MissionCouncil::set_members(Origin::root(), 1, vec![2, 3]));
+        assert_ok!(MissionCouncil::vote(Origin::signed(2), 1, hash, 0, true));
+
+        // Only one of the two remaining members approved.
+        System::set_block_number(4);
+        assert_ok!(MissionCouncil::close(Origin::signed(2), 1, hash, 0, 0, len));
+        assert!(logger::log().is_empty());
+    });
+}
@@ -0,0 +1,518 @@
+//! # Mission Democracy Pallet
+//!
+//! Referenda decided by the holders of a single mission token.
+//!
+//! ## Overview
+//!
+//! Any holder of a mission token can put a call up for a referendum of that mission by reserving
+//! `MinimumDeposit` of the mission's token. Holders then vote with their mission token balance and
+//! a `Conviction` multiplier, exactly like in `pallet_democracy`, except that the balance is locked
+//! through the `Locks` of `pallet_mission_tokens` instead of the native currency.
+//!
+//! When the voting period ends the referendum is tallied against the total issuance of the mission
+//! token. A passing referendum is scheduled for enactment after `EnactmentPeriod` and dispatched
+//! with the `Mission(token_id)` origin of this pallet, which other pallets can require through
+//! `EnsureMission`.
+//!
+//! ### Dispatchable Functions
+//!
+//! * `propose` - Start a referendum of a mission on a call.
+//! * `vote` - Vote in a referendum with some of the mission token balance.
+//! * `remove_vote` - Remove a vote, keeping the conviction lock if it is still due.
+//! * `unlock` - Recompute the vote lock of an account in a mission.
+//! * `cancel_referendum` - Cancel an ongoing referendum.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::{Decode, Encode};
+use frame_support::{
+    decl_error, decl_event, decl_module, decl_storage, ensure,
+    traits::{
+        schedule::{DispatchTime, Named as ScheduleNamed},
+        EnsureOrigin, Get, LockIdentifier, WithdrawReason,
+    },
+    weights::Weight,
+    Parameter,
+};
+use frame_system::{self as system, ensure_signed};
+use pallet_democracy::{AccountVote, Approved, Tally, VoteThreshold};
+use sp_runtime::{
+    traits::{Dispatchable, Zero},
+    DispatchError, DispatchResult, RuntimeDebug,
+};
+use sp_std::prelude::*;
+
+mod types;
+pub use types::{ReferendumInfo, ReferendumStatus, Voting};
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+const MISSION_DEMOCRACY_ID: LockIdentifier = *b"missdemo";
+
+/// A referendum index.
+pub type ReferendumIndex = u32;
+
+type BalanceOf<T> = <T as pallet_mission_tokens::Trait>::Balance;
+type TokenId<T> = <T as pallet_mission_tokens::Trait>::MissionTokenId;
+type MissionReferendumInfo<T> = ReferendumInfo<
+    <T as frame_system::Trait>::AccountId,
+    BalanceOf<T>,
+    <T as frame_system::Trait>::BlockNumber,
+    TokenId<T>,
+    <T as Trait>::Proposal,
+>;
+type MissionReferendumStatus<T> = ReferendumStatus<
+    <T as frame_system::Trait>::AccountId,
+    BalanceOf<T>,
+    <T as frame_system::Trait>::BlockNumber,
+    TokenId<T>,
+    <T as Trait>::Proposal,
+>;
+
+pub trait Trait: frame_system::Trait + pallet_mission_tokens::Trait {
+    /// The overarching event type.
+    type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
+
+    /// The call that a mission referendum dispatches when it passes.
+    type Proposal: Parameter + Dispatchable<Origin = Self::Origin>;
+
+    /// The minimum amount of mission tokens reserved for starting a referendum.
+    type MinimumDeposit: Get<BalanceOf<Self>>;
+
+    /// How long (in blocks) a mission referendum is open for voting.
+    type VotingPeriod: Get<Self::BlockNumber>;
+
+    /// The period between a referendum passing and its enactment. It is also the unit of the
+    /// conviction lock periods.
+    type EnactmentPeriod: Get<Self::BlockNumber>;
+
+    /// The maximum number of concurrent votes an account may have in a single mission.
+    type MaxVotes: Get<u32>;
+
+    /// Origin from which any mission referendum may be cancelled in an emergency.
+    type CancellationOrigin: EnsureOrigin<Self::Origin>;
+
+    /// The Scheduler.
+    type Scheduler: ScheduleNamed<Self::BlockNumber, Self::Proposal, Self::PalletsOrigin>;
+
+    /// Overarching type of all pallets origins.
+    type PalletsOrigin: From<RawOrigin<TokenId<Self>>>;
+}
+
+/// Origin for the mission democracy module.
+#[derive(PartialEq, Eq, Clone, RuntimeDebug, Encode, Decode)]
+pub enum RawOrigin<MissionTokenId> {
+    /// It has been condoned by a referendum of the holders of the given mission token.
+    Mission(MissionTokenId),
+}
+
+/// Origin for the mission democracy module.
+pub type Origin<T> = RawOrigin<<T as pallet_mission_tokens::Trait>::MissionTokenId>;
+
+/// Ensure that the origin is a passed referendum of some mission. Returns the mission token id.
+pub struct EnsureMission<MissionTokenId>(sp_std::marker::PhantomData<MissionTokenId>);
+impl<
+        O: Into<Result<RawOrigin<MissionTokenId>, O>> + From<RawOrigin<MissionTokenId>>,
+        MissionTokenId: Default,
+    > EnsureOrigin<O> for EnsureMission<MissionTokenId>
+{
+    type Success = MissionTokenId;
+    fn try_origin(o: O) -> Result<Self::Success, O> {
+        o.into().and_then(|o| match o {
+            RawOrigin::Mission(id) => Ok(id),
+        })
+    }
+
+    #[cfg(feature = "runtime-benchmarks")]
+    fn successful_origin() -> O {
+        O::from(RawOrigin::Mission(Default::default()))
+    }
+}
+
+decl_storage! {
+    trait Store for Module<T: Trait> as MissionDemocracy {
+        /// The next free referendum index, aka the number of referenda started so far.
+        pub ReferendumCount get(fn referendum_count): ReferendumIndex;
+
+        /// The lowest referendum index representing an unbaked referendum. Equal to
+        /// `ReferendumCount` if there isn't a unbaked referendum.
+        pub LowestUnbaked get(fn lowest_unbaked): ReferendumIndex;
+
+        /// Information concerning any given referendum.
+        pub ReferendumInfoOf get(fn referendum_info):
+            map hasher(twox_64_concat) ReferendumIndex => Option<MissionReferendumInfo<T>>;
+
+        /// All votes of an account in the referenda of a mission.
+        pub VotingOf get(fn voting_of):
+            double_map hasher(twox_64_concat) TokenId<T>, hasher(blake2_128_concat) T::AccountId
+            => Voting<BalanceOf<T>, T::BlockNumber>;
+    }
+}
+
+decl_event!(
+    pub enum Event<T>
+    where
+        AccountId = <T as frame_system::Trait>::AccountId,
+        MissionTokenId = <T as pallet_mission_tokens::Trait>::MissionTokenId,
+    {
+        /// A referendum has begun. \[ref_index, mission_token_id, proposer\]
+        Started(ReferendumIndex, MissionTokenId, AccountId),
+        /// An account has voted in a referendum. \[ref_index, voter\]
+        Voted(ReferendumIndex, AccountId),
+        /// A referendum has passed and its call is scheduled. \[ref_index, mission_token_id\]
+        Passed(ReferendumIndex, MissionTokenId),
+        /// A referendum has not passed. \[ref_index, mission_token_id\]
+        NotPassed(ReferendumIndex, MissionTokenId),
+        /// A referendum has been cancelled. \[ref_index\]
+        Cancelled(ReferendumIndex),
+        /// The vote lock of an account in a mission was updated. \[who, mission_token_id\]
+        Unlocked(AccountId, MissionTokenId),
+    }
+);
+
+decl_error! {
+    pub enum Error for Module<T: Trait> {
+        /// The proposer does not hold enough mission tokens for the deposit.
+        InsufficientDeposit,
+        /// Vote given for invalid referendum.
+        ReferendumInvalid,
+        /// The voter does not hold enough mission tokens for the vote.
+        InsufficientFunds,
+        /// The account has reached the maximum number of votes in this mission.
+        MaxVotesReached,
+        /// The account has not voted in this referendum.
+        NotVoter,
+        /// The vote lock of a finished referendum is still in effect.
+        NoPermission,
+        /// Unexpected integer underflow.
+        Underflow,
+        /// Unexpected integer overflow.
+        Overflow,
+    }
+}
+
+decl_module! {
+    pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+        type Error = Error<T>;
+
+        /// The minimum amount of mission tokens reserved for starting a referendum.
+        const MinimumDeposit: BalanceOf<T> = T::MinimumDeposit::get();
+
+        /// How long (in blocks) a mission referendum is open for voting.
+        const VotingPeriod: T::BlockNumber = T::VotingPeriod::get();
+
+        /// The period between a referendum passing and its enactment.
+        const EnactmentPeriod: T::BlockNumber = T::EnactmentPeriod::get();
+
+        fn deposit_event() = default;
+
+        /// Start a referendum of the holders of `token_id` on `proposal`.
+        ///
+        /// The dispatch origin of this call must be _Signed_ and the sender must have
+        /// `MinimumDeposit` of free `token_id` balance, which is reserved until the referendum
+        /// is baked or cancelled.
+        ///
+        /// Emits `Started`.
+        #[weight = 60_000_000 + T::DbWeight::get().reads_writes(3, 3)]
+        fn propose(origin, #[compact] token_id: TokenId<T>, proposal: Box<T::Proposal>) {
+            let who = ensure_signed(origin)?;
+            <pallet_mission_tokens::Module<T>>::validate_mission_token_id(token_id)?;
+
+            let deposit = T::MinimumDeposit::get();
+            <pallet_mission_tokens::Module<T>>::reserve(&who, token_id, deposit)
+                .map_err(|_| Error::<T>::InsufficientDeposit)?;
+
+            let index = Self::referendum_count();
+            ReferendumCount::put(index + 1);
+            let end = system::Module::<T>::block_number() + T::VotingPeriod::get();
+            let status = ReferendumStatus {
+                mission_token_id: token_id,
+                proposer: who.clone(),
+                deposit,
+                end,
+                proposal: *proposal,
+                threshold: VoteThreshold::SuperMajorityApprove,
+                tally: Tally::default(),
+            };
+            <ReferendumInfoOf<T>>::insert(index, ReferendumInfo::Ongoing(status));
+
+            Self::deposit_event(RawEvent::Started(index, token_id, who));
+        }
+
+        /// Vote in a referendum with mission tokens of the referendum's mission.
+        ///
+        /// The dispatch origin of this call must be _Signed_. The balance of the vote is locked
+        /// in `pallet_mission_tokens` until the vote is removed and, for a vote on the winning
+        /// side, until the conviction lock period has passed.
+        ///
+        /// - `ref_index`: The index of the referendum to vote for.
+        /// - `vote`: The vote configuration.
+        #[weight = 50_000_000 + T::DbWeight::get().reads_writes(4, 3)]
+        fn vote(origin, #[compact] ref_index: ReferendumIndex, vote: AccountVote<BalanceOf<T>>) {
+            let who = ensure_signed(origin)?;
+            Self::try_vote(&who, ref_index, vote)?;
+            Self::deposit_event(RawEvent::Voted(ref_index, who));
+        }
+
+        /// Remove a vote for a referendum.
+        ///
+        /// The dispatch origin of this call must be _Signed_. If the referendum is ongoing the
+        /// vote is removed from the tally. If it passed and the vote was on the winning side, the
+        /// lock stays in place until the conviction lock period has passed. If it was cancelled,
+        /// the vote is dropped.
+        #[weight = 50_000_000 + T::DbWeight::get().reads_writes(3, 3)]
+        fn remove_vote(origin, #[compact] ref_index: ReferendumIndex) {
+            let who = ensure_signed(origin)?;
+            Self::try_remove_vote(&who, ref_index)?;
+        }
+
+        /// Unlock the mission tokens of `target` that are no longer needed for voting.
+        ///
+        /// The dispatch origin of this call must be _Signed_.
+        #[weight = 30_000_000 + T::DbWeight::get().reads_writes(3, 3)]
+        fn unlock(origin, #[compact] token_id: TokenId<T>, target: T::AccountId) {
+            ensure_signed(origin)?;
+            Self::update_lock(&target, token_id);
+            Self::deposit_event(RawEvent::Unlocked(target, token_id));
+        }
+
+        /// Cancel an ongoing referendum and return the deposit of its proposer.
+        ///
+        /// The dispatch origin of this call must be `CancellationOrigin`.
+        #[weight = 30_000_000 + T::DbWeight::get().reads_writes(2, 2)]
+        fn cancel_referendum(origin, #[compact] ref_index: ReferendumIndex) {
+            T::CancellationOrigin::ensure_origin(origin)?;
+
+            let status = Self::referendum_status(ref_index)?;
+            <pallet_mission_tokens::Module<T>>::unreserve(
+                &status.proposer,
+                status.mission_token_id,
+                status.deposit,
+            );
+            // the mission is kept for voters to remove their votes.
+            <ReferendumInfoOf<T>>::insert(
+                ref_index,
+                ReferendumInfo::Cancelled { mission_token_id: status.mission_token_id },
+            );
+
+            Self::deposit_event(RawEvent::Cancelled(ref_index));
+        }
+
+        /// Tally up the referenda whose voting period ends at this block.
+        fn on_initialize(n: T::BlockNumber) -> Weight {
+            Self::begin_block(n)
+        }
+    }
+}
+
+impl<T: Trait> Module<T> {
+    /// Get the status of an ongoing referendum.
+    fn referendum_status(
+        ref_index: ReferendumIndex,
+    ) -> Result<MissionReferendumStatus<T>, DispatchError> {
+        match <ReferendumInfoOf<T>>::get(ref_index) {
+            Some(ReferendumInfo::Ongoing(status)) => Ok(status),
+            _ => Err(Error::<T>::ReferendumInvalid.into()),
+        }
+    }
+
+    /// Actually enact a vote, if legit.
+    fn try_vote(
+        who: &T::AccountId,
+        ref_index: ReferendumIndex,
+        vote: AccountVote<BalanceOf<T>>,
+    ) -> DispatchResult {
+        let mut status = Self::referendum_status(ref_index)?;
+        let token_id = status.mission_token_id;
+        ensure!(
+            vote.balance() <= <pallet_mission_tokens::Module<T>>::free_balance(who, token_id),
+            Error::<T>::InsufficientFunds
+        );
+
+        <VotingOf<T>>::try_mutate(token_id, who, |voting| -> DispatchResult {
+            match voting.votes.binary_search_by_key(&ref_index, |i| i.0) {
+                Ok(i) => {
+                    // Shouldn't be possible to fail, but we handle it gracefully.
+                    status
+                        .tally
+                        .remove(voting.votes[i].1)
+                        .ok_or(Error::<T>::Underflow)?;
+                    voting.votes[i].1 = vote;
+                }
+                Err(i) => {
+                    ensure!(
+                        (voting.votes.len() as u32) < T::MaxVotes::get(),
+                        Error::<T>::MaxVotesReached
+                    );
+                    voting.votes.insert(i, (ref_index, vote));
+                }
+            }
+            // Shouldn't be possible to fail, but we handle it gracefully.
+            status.tally.add(vote).ok_or(Error::<T>::Overflow)?;
+            Ok(())
+        })?;
+
+        // Extend the lock to `balance` (rather than setting it) since we don't know what other
+        // votes are in place.
+        <pallet_mission_tokens::Module<T>>::extend_lock(
+            MISSION_DEMOCRACY_ID,
+            who,
+            token_id,
+            vote.balance(),
+            WithdrawReason::Transfer.into(),
+        );
+        <ReferendumInfoOf<T>>::insert(ref_index, ReferendumInfo::Ongoing(status));
+        Ok(())
+    }
+
+    /// Remove the account's vote for the given referendum if possible. This is possible when:
+    /// - The referendum has not finished.
+    /// - The referendum has finished and the voter lost their direction.
+    /// - The referendum has finished and the voter's lock period is up.
+    ///
+    /// A vote of a finished referendum whose lock is still due is turned into a prior lock. A vote
+    /// of a cancelled referendum is dropped.
+    fn try_remove_vote(who: &T::AccountId, ref_index: ReferendumIndex) -> DispatchResult {
+        let info = <ReferendumInfoOf<T>>::get(ref_index).ok_or(Error::<T>::ReferendumInvalid)?;
+        let token_id = info.mission_token_id();
+
+        <VotingOf<T>>::try_mutate(token_id, who, |voting| -> DispatchResult {
+            let i = voting
+                .votes
+                .binary_search_by_key(&ref_index, |i| i.0)
+                .map_err(|_| Error::<T>::NotVoter)?;
+            match info {
+                ReferendumInfo::Ongoing(mut status) => {
+                    // Shouldn't be possible to fail, but we handle it gracefully.
+                    status
+                        .tally
+                        .remove(voting.votes[i].1)
+                        .ok_or(Error::<T>::Underflow)?;
+                    <ReferendumInfoOf<T>>::insert(ref_index, ReferendumInfo::Ongoing(status));
+                }
+                ReferendumInfo::Finished { end, approved, .. } => {
+                    if let Some((lock_periods, balance)) = voting.votes[i].1.locked_if(approved) {
+                        let unlock_at = end + T::EnactmentPeriod::get() * lock_periods.into();
+                        if system::Module::<T>::block_number() < unlock_at {
+                            voting.accumulate_prior(unlock_at, balance);
+                        }
+                    }
+                }
+                // the vote has no tally to leave and locks nothing.
+                ReferendumInfo::Cancelled { .. } => {}
+            }
+            voting.votes.remove(i);
+            Ok(())
+        })?;
+
+        Self::update_lock(who, token_id);
+        Ok(())
+    }
+
+    /// Rejig the lock on the mission tokens of an account. It will never get more stringent
+    /// (since that would indicate a security hole) but may be reduced from what they are
+    /// currently.
+    fn update_lock(who: &T::AccountId, token_id: TokenId<T>) {
+        let now = system::Module::<T>::block_number();
+        let lock_needed = <VotingOf<T>>::mutate_exists(token_id, who, |maybe_voting| {
+            let mut voting = maybe_voting.take().unwrap_or_default();
+            voting.rejig(now);
+            let lock_needed = voting.locked_balance();
+            if !voting.is_empty() {
+                *maybe_voting = Some(voting);
+            }
+            lock_needed
+        });
+        if lock_needed.is_zero() {
+            <pallet_mission_tokens::Module<T>>::remove_lock(MISSION_DEMOCRACY_ID, who, token_id);
+        } else {
+            <pallet_mission_tokens::Module<T>>::set_lock(
+                MISSION_DEMOCRACY_ID,
+                who,
+                token_id,
+                lock_needed,
+                WithdrawReason::Transfer.into(),
+            );
+        }
+    }
+
+    /// Tally a referendum whose voting period has ended and schedule its call if it passed.
+    fn bake_referendum(
+        now: T::BlockNumber,
+        index: ReferendumIndex,
+        status: MissionReferendumStatus<T>,
+    ) -> bool {
+        let token_id = status.mission_token_id;
+        let electorate = <pallet_mission_tokens::TotalIssuance<T>>::get(token_id);
+        let approved = status.threshold.approved(status.tally, electorate);
+
+        <pallet_mission_tokens::Module<T>>::unreserve(&status.proposer, token_id, status.deposit);
+
+        if approved {
+            Self::deposit_event(RawEvent::Passed(index, token_id));
+            if T::Scheduler::schedule_named(
+                (MISSION_DEMOCRACY_ID, index).encode(),
+                DispatchTime::At(now + T::EnactmentPeriod::get()),
+                None,
+                63,
+                RawOrigin::Mission(token_id).into(),
+                status.proposal,
+            )
+            .is_err()
+            {
+                frame_support::print("LOGIC ERROR: bake_referendum/schedule_named failed");
+            }
+        } else {
+            Self::deposit_event(RawEvent::NotPassed(index, token_id));
+        }
+
+        approved
+    }
+
+    /// Bake all referenda maturing at `now`.
+    ///
+    /// # <weight>
+    /// - Complexity: `O(R)` where `R` is the number of unbaked referenda.
+    /// # </weight>
+    fn begin_block(now: T::BlockNumber) -> Weight {
+        let next = Self::lowest_unbaked();
+        let last = Self::referendum_count();
+        let mut weight = T::DbWeight::get().reads(2 + last.saturating_sub(next) as Weight);
+
+        for index in next..last {
+            if let Some(ReferendumInfo::Ongoing(status)) = <ReferendumInfoOf<T>>::get(index) {
+                if status.end == now {
+                    let token_id = status.mission_token_id;
+                    let approved = Self::bake_referendum(now, index, status);
+                    <ReferendumInfoOf<T>>::insert(
+                        index,
+                        ReferendumInfo::Finished {
+                            mission_token_id: token_id,
+                            approved,
+                            end: now,
+                        },
+                    );
+                    weight = weight.saturating_add(T::DbWeight::get().reads_writes(3, 4));
+                }
+            }
+        }
+
+        // Referenda all end after the same voting period, so everything before the first ongoing
+        // referendum has been baked (or cancelled).
+        let lowest = (next..last)
+            .find(|index| {
+                matches!(
+                    <ReferendumInfoOf<T>>::get(index),
+                    Some(ReferendumInfo::Ongoing(_))
+                )
+            })
+            .unwrap_or(last);
+        LowestUnbaked::put(lowest);
+
+        weight
+    }
+}
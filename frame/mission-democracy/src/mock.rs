@@ -0,0 +1,178 @@
+use crate as mission_democracy;
+use crate::{Module, Trait};
+use frame_support::{
+    impl_outer_dispatch, impl_outer_origin, parameter_types,
+    traits::{OnInitialize, OriginTrait},
+    weights::Weight,
+};
+use frame_system::{self as system, EnsureRoot};
+use sp_core::H256;
+use sp_runtime::{
+    testing::Header,
+    traits::{BlakeTwo256, IdentityLookup},
+    Perbill,
+};
+
+/// A pallet that records the origin of every call it receives.
+pub mod logger {
+    use super::*;
+    use frame_support::decl_module;
+    use std::cell::RefCell;
+
+    thread_local! {
+        static LOG: RefCell<Vec<(OriginCaller, u32)>> = RefCell::new(Vec::new());
+    }
+    pub fn log() -> Vec<(OriginCaller, u32)> {
+        LOG.with(|log| log.borrow().clone())
+    }
+    pub trait Trait: system::Trait {}
+    decl_module! {
+        pub struct Module<T: Trait> for enum Call
+        where
+            origin: <T as system::Trait>::Origin,
+            <T as system::Trait>::Origin: OriginTrait<PalletsOrigin = OriginCaller>
+        {
+            #[weight = 0]
+            fn log(origin, i: u32) {
+                LOG.with(|log| {
+                    log.borrow_mut().push((origin.caller().clone(), i));
+                })
+            }
+        }
+    }
+}
+
+impl_outer_origin! {
+    pub enum Origin for Test where system = frame_system {
+        mission_democracy<T>,
+    }
+}
+
+impl_outer_dispatch! {
+    pub enum Call for Test where origin: Origin {
+        frame_system::System,
+        logger::Logger,
+    }
+}
+
+// Configure a mock runtime to test the pallet.
+
+#[derive(Clone, Eq, PartialEq)]
+pub struct Test;
+parameter_types! {
+    pub const BlockHashCount: u64 = 250;
+    pub const MaximumBlockWeight: Weight = 1_000_000_000_000;
+    pub const MaximumBlockLength: u32 = 2 * 1024;
+    pub const AvailableBlockRatio: Perbill = Perbill::from_percent(75);
+}
+
+impl system::Trait for Test {
+    type BaseCallFilter = ();
+    type Origin = Origin;
+    type Call = Call;
+    type Index = u64;
+    type BlockNumber = u64;
+    type Hash = H256;
+    type Hashing = BlakeTwo256;
+    type AccountId = u64;
+    type Lookup = IdentityLookup<Self::AccountId>;
+    type Header = Header;
+    type Event = ();
+    type BlockHashCount = BlockHashCount;
+    type MaximumBlockWeight = MaximumBlockWeight;
+    type DbWeight = ();
+    type BlockExecutionWeight = ();
+    type ExtrinsicBaseWeight = ();
+    type MaximumExtrinsicWeight = MaximumBlockWeight;
+    type MaximumBlockLength = MaximumBlockLength;
+    type AvailableBlockRatio = AvailableBlockRatio;
+    type Version = ();
+    type PalletInfo = ();
+    type AccountData = ();
+    type OnNewAccount = ();
+    type OnKilledAccount = ();
+    type SystemWeightInfo = ();
+}
+
+impl logger::Trait for Test {}
+
+parameter_types! {
+    pub const ExistentialDeposit: u64 = 1;
+    pub const MaxMissionTokensSupply: u128 = 1_000_000;
+}
+
+impl pallet_mission_tokens::Trait for Test {
+    type Event = ();
+    type Balance = u64;
+    type MissionTokenId = u32;
+    type ExistentialDeposit = ExistentialDeposit;
+    type OnNewAccount = ();
+    type MaxMissionTokensSupply = MaxMissionTokensSupply;
+    type MissionCreatorOrigin = EnsureRoot<u64>;
+}
+
+parameter_types! {
+    pub MaximumSchedulerWeight: Weight = Perbill::from_percent(80) * MaximumBlockWeight::get();
+}
+
+impl pallet_scheduler::Trait for Test {
+    type Event = ();
+    type Origin = Origin;
+    type PalletsOrigin = OriginCaller;
+    type Call = Call;
+    type MaximumWeight = MaximumSchedulerWeight;
+    type ScheduleOrigin = EnsureRoot<u64>;
+    type MaxScheduledPerBlock = ();
+    type WeightInfo = ();
+}
+
+parameter_types! {
+    pub const MinimumDeposit: u64 = 10;
+    pub const VotingPeriod: u64 = 2;
+    pub const EnactmentPeriod: u64 = 2;
+    pub const MaxVotes: u32 = 100;
+}
+
+impl Trait for Test {
+    type Event = ();
+    type Proposal = Call;
+    type MinimumDeposit = MinimumDeposit;
+    type VotingPeriod = VotingPeriod;
+    type EnactmentPeriod = EnactmentPeriod;
+    type MaxVotes = MaxVotes;
+    type CancellationOrigin = EnsureRoot<u64>;
+    type Scheduler = Scheduler;
+    type PalletsOrigin = OriginCaller;
+}
+
+pub type System = system::Module<Test>;
+pub type Logger = logger::Module<Test>;
+pub type Scheduler = pallet_scheduler::Module<Test>;
+pub type MissionTokens = pallet_mission_tokens::Module<Test>;
+pub type MissionDemocracy = Module<Test>;
+
+// Build genesis storage according to the mock runtime.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+    let mut ext: sp_io::TestExternalities = system::GenesisConfig::default()
+        .build_storage::<Test>()
+        .unwrap()
+        .into();
+    ext.execute_with(|| {
+        System::set_block_number(1);
+        // Mission 1 is held by accounts 1 to 3, for a total issuance of 100.
+        MissionTokens::mint(1, 1, 60);
+        MissionTokens::mint(2, 1, 30);
+        MissionTokens::mint(3, 1, 10);
+    });
+    ext
+}
+
+/// Run blocks up to and including `n`.
+pub fn run_to_block(n: u64) {
+    while System::block_number() < n {
+        let next = System::block_number() + 1;
+        System::set_block_number(next);
+        Scheduler::on_initialize(next);
+        MissionDemocracy::on_initialize(next);
+    }
+}
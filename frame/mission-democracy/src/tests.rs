@@ -0,0 +1,203 @@
+use crate::{mock::*, Error, RawOrigin, ReferendumInfo};
+use frame_support::{assert_noop, assert_ok};
+use pallet_democracy::{AccountVote, Conviction, Vote};
+use pallet_mission_tokens::Error as MissionTokensError;
+
+fn aye(balance: u64, conviction: Conviction) -> AccountVote<u64> {
+    AccountVote::Standard {
+        vote: Vote {
+            aye: true,
+            conviction,
+        },
+        balance,
+    }
+}
+
+fn nay(balance: u64) -> AccountVote<u64> {
+    AccountVote::Standard {
+        vote: Vote {
+            aye: false,
+            conviction: Conviction::None,
+        },
+        balance,
+    }
+}
+
+fn log_proposal(i: u32) -> Box<Call> {
+    Box::new(Call::Logger(logger::Call::log(i)))
+}
+
+#[test]
+fn propose_should_reserve_deposit() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(MissionDemocracy::propose(Origin::signed(1), 1, log_proposal(42)));
+        assert_eq!(MissionDemocracy::referendum_count(), 1);
+        assert_eq!(MissionTokens::free_balance(1, 1), 50);
+        assert_eq!(MissionTokens::reserved_balance(1, 1), 10);
+    });
+}
+
+#[test]
+fn propose_without_deposit_should_not_work() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            MissionDemocracy::propose(Origin::signed(4), 1, log_proposal(42)),
+            Error::<Test>::InsufficientDeposit
+        );
+        assert_noop!(
+            MissionDemocracy::propose(Origin::signed(1), 100, log_proposal(42)),
+            MissionTokensError::<Test>::InvalidMissionTokenId
+        );
+    });
+}
+
+#[test]
+fn passing_referendum_should_dispatch_with_mission_origin() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(MissionDemocracy::propose(Origin::signed(1), 1, log_proposal(42)));
+        assert_ok!(MissionDemocracy::vote(
+            Origin::signed(1),
+            0,
+            aye(50, Conviction::Locked1x)
+        ));
+        assert_ok!(MissionDemocracy::vote(Origin::signed(3), 0, nay(10)));
+
+        run_to_block(3);
+        assert_eq!(
+            MissionDemocracy::referendum_info(0),
+            Some(ReferendumInfo::Finished {
+                mission_token_id: 1,
+                approved: true,
+                end: 3,
+            })
+        );
+        assert_eq!(MissionDemocracy::lowest_unbaked(), 1);
+        assert_eq!(MissionTokens::reserved_balance(1, 1), 0);
+        assert!(logger::log().is_empty());
+
+        run_to_block(5);
+        assert_eq!(
+            logger::log(),
+            vec![(OriginCaller::mission_democracy(RawOrigin::Mission(1)), 42)]
+        );
+    });
+}
+
+#[test]
+fn failing_referendum_should_not_dispatch() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(MissionDemocracy::propose(Origin::signed(1), 1, log_proposal(42)));
+        assert_ok!(MissionDemocracy::vote(Origin::signed(2), 0, nay(30)));
+        assert_ok!(MissionDemocracy::vote(
+            Origin::signed(3),
+            0,
+            aye(10, Conviction::None)
+        ));
+
+        run_to_block(5);
+        assert_eq!(
+            MissionDemocracy::referendum_info(0),
+            Some(ReferendumInfo::Finished {
+                mission_token_id: 1,
+                approved: false,
+                end: 3,
+            })
+        );
+        assert!(logger::log().is_empty());
+    });
+}
+
+#[test]
+fn voting_should_lock_mission_tokens() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(MissionDemocracy::propose(Origin::signed(1), 1, log_proposal(42)));
+        assert_ok!(MissionDemocracy::vote(Origin::signed(2), 0, nay(20)));
+        assert_noop!(
+            MissionTokens::transfer(Origin::signed(2), 1, 4, 15),
+            MissionTokensError::<Test>::LiquidityRestrictions
+        );
+        assert_ok!(MissionTokens::transfer(Origin::signed(2), 1, 4, 10));
+
+        assert_ok!(MissionDemocracy::remove_vote(Origin::signed(2), 0));
+        assert!(MissionTokens::locks((1, 2)).is_empty());
+        assert_ok!(MissionTokens::transfer(Origin::signed(2), 1, 4, 15));
+    });
+}
+
+#[test]
+fn voting_more_than_free_balance_should_not_work() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(MissionDemocracy::propose(Origin::signed(1), 1, log_proposal(42)));
+        assert_noop!(
+            MissionDemocracy::vote(Origin::signed(3), 0, nay(11)),
+            Error::<Test>::InsufficientFunds
+        );
+        assert_noop!(
+            MissionDemocracy::vote(Origin::signed(3), 1, nay(10)),
+            Error::<Test>::ReferendumInvalid
+        );
+    });
+}
+
+#[test]
+fn conviction_lock_should_outlive_winning_vote() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(MissionDemocracy::propose(Origin::signed(1), 1, log_proposal(42)));
+        assert_ok!(MissionDemocracy::vote(
+            Origin::signed(2),
+            0,
+            aye(30, Conviction::Locked2x)
+        ));
+        run_to_block(3);
+
+        // Locked for two enactment periods after the end of the referendum.
+        assert_ok!(MissionDemocracy::remove_vote(Origin::signed(2), 0));
+        assert_eq!(MissionTokens::locks((1, 2))[0].amount, 30);
+
+        run_to_block(6);
+        assert_ok!(MissionDemocracy::unlock(Origin::signed(1), 1, 2));
+        assert_eq!(MissionTokens::locks((1, 2))[0].amount, 30);
+
+        run_to_block(7);
+        assert_ok!(MissionDemocracy::unlock(Origin::signed(1), 1, 2));
+        assert!(MissionTokens::locks((1, 2)).is_empty());
+    });
+}
+
+#[test]
+fn cancelled_referendum_should_return_deposit() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(MissionDemocracy::propose(Origin::signed(1), 1, log_proposal(42)));
+        assert_ok!(MissionDemocracy::vote(Origin::signed(2), 0, nay(20)));
+        assert_noop!(
+            MissionDemocracy::cancel_referendum(Origin::signed(1), 0),
+            sp_runtime::traits::BadOrigin
+        );
+        assert_ok!(MissionDemocracy::cancel_referendum(Origin::root(), 0));
+        assert_eq!(
+            MissionDemocracy::referendum_info(0),
+            Some(ReferendumInfo::Cancelled {
+                mission_token_id: 1
+            })
+        );
+        assert_eq!(MissionTokens::free_balance(1, 1), 60);
+
+        // voters unlock their mission tokens after the cancellation.
+        assert_noop!(
+            MissionDemocracy::remove_vote(Origin::signed(3), 0),
+            Error::<Test>::NotVoter
+        );
+        assert_noop!(
+            MissionDemocracy::remove_vote(Origin::signed(2), 1),
+            Error::<Test>::ReferendumInvalid
+        );
+        assert_ok!(MissionDemocracy::remove_vote(Origin::signed(2), 0));
+        assert!(MissionDemocracy::voting_of(1, 2).votes.is_empty());
+        assert!(MissionTokens::locks((1, 2)).is_empty());
+        assert_ok!(MissionTokens::transfer(Origin::signed(2), 1, 4, 15));
+
+        run_to_block(5);
+        assert_eq!(MissionDemocracy::lowest_unbaked(), 1);
+        assert!(logger::log().is_empty());
+    });
+}
@@ -0,0 +1,99 @@
+//! Miscellaneous datatypes of the mission democracy pallet.
+
+use codec::{Decode, Encode};
+use pallet_democracy::{AccountVote, Tally, VoteThreshold};
+use sp_runtime::{
+    traits::{Saturating, Zero},
+    RuntimeDebug,
+};
+use sp_std::prelude::*;
+
+use crate::ReferendumIndex;
+
+/// Info regarding an ongoing mission referendum.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct ReferendumStatus<AccountId, Balance, BlockNumber, MissionTokenId, Proposal> {
+    /// The mission whose token holders decide this referendum.
+    pub mission_token_id: MissionTokenId,
+    /// The account that started the referendum.
+    pub proposer: AccountId,
+    /// The amount of mission tokens reserved from the proposer.
+    pub deposit: Balance,
+    /// When voting on this referendum will end.
+    pub end: BlockNumber,
+    /// The call that is dispatched with the mission origin if the referendum passes.
+    pub proposal: Proposal,
+    /// The thresholding mechanism to determine whether it passed.
+    pub threshold: VoteThreshold,
+    /// The current tally of votes in this referendum.
+    pub tally: Tally<Balance>,
+}
+
+/// Info regarding a mission referendum, present or past.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub enum ReferendumInfo<AccountId, Balance, BlockNumber, MissionTokenId, Proposal> {
+    /// Referendum is happening.
+    Ongoing(ReferendumStatus<AccountId, Balance, BlockNumber, MissionTokenId, Proposal>),
+    /// Referendum finished at `end`, and has been `approved` or rejected.
+    Finished {
+        mission_token_id: MissionTokenId,
+        approved: bool,
+        end: BlockNumber,
+    },
+    /// Referendum was cancelled before it finished.
+    Cancelled { mission_token_id: MissionTokenId },
+}
+
+impl<AccountId, Balance, BlockNumber, MissionTokenId: Copy, Proposal>
+    ReferendumInfo<AccountId, Balance, BlockNumber, MissionTokenId, Proposal>
+{
+    /// The mission this referendum belongs to.
+    pub fn mission_token_id(&self) -> MissionTokenId {
+        match self {
+            ReferendumInfo::Ongoing(status) => status.mission_token_id,
+            ReferendumInfo::Finished {
+                mission_token_id, ..
+            } => *mission_token_id,
+            ReferendumInfo::Cancelled { mission_token_id } => *mission_token_id,
+        }
+    }
+}
+
+/// The votes of an account in the referenda of a single mission.
+#[derive(Encode, Decode, Default, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct Voting<Balance, BlockNumber> {
+    /// The current votes of the account, ordered by referendum index.
+    pub votes: Vec<(ReferendumIndex, AccountVote<Balance>)>,
+    /// A lock that outlives removed votes, as `(unlock_at, amount)`.
+    pub prior: (BlockNumber, Balance),
+}
+
+impl<Balance: Saturating + Ord + Zero + Copy, BlockNumber: Ord + Copy + Zero>
+    Voting<Balance, BlockNumber>
+{
+    /// Keep the prior lock of a removed vote until `until`.
+    pub fn accumulate_prior(&mut self, until: BlockNumber, amount: Balance) {
+        self.prior.0 = self.prior.0.max(until);
+        self.prior.1 = self.prior.1.max(amount);
+    }
+
+    /// Drop the prior lock if it has expired at `now`.
+    pub fn rejig(&mut self, now: BlockNumber) {
+        if now >= self.prior.0 {
+            self.prior = (Zero::zero(), Zero::zero());
+        }
+    }
+
+    /// The amount of mission tokens that must currently be locked due to voting.
+    pub fn locked_balance(&self) -> Balance {
+        self.votes
+            .iter()
+            .map(|i| i.1.balance())
+            .fold(self.prior.1, |a, i| a.max(i))
+    }
+
+    /// Whether the account holds neither votes nor a prior lock.
+    pub fn is_empty(&self) -> bool {
+        self.votes.is_empty() && self.prior.1.is_zero()
+    }
+}
@@ -0,0 +1,381 @@
+//! # Mission Streams Pallet
+//!
+//! Streaming payments and vesting schedules for mission tokens.
+//!
+//! ## Overview
+//!
+//! A stream moves a deposit of mission tokens from its sender into the escrow account of this
+//! pallet. From `start` on, `rate_per_block` of the deposit vests to the recipient every block
+//! until the whole deposit has vested. Nothing can be withdrawn before `cliff`, after which the
+//! recipient can withdraw everything that has vested so far at any time.
+//!
+//! The sender can cancel a stream at any time: the recipient is paid what has vested and not yet
+//! been withdrawn, and the unvested remainder is returned to the sender. `CancelOrigin` may cancel
+//! streams on behalf of a sender that cannot sign, such as the social treasury.
+//!
+//! Other pallets create streams through the `StreamPayments` trait of `pallet_mission_tokens`,
+//! which the social treasury uses to pay approved proposals over time.
+//!
+//! ### Dispatchable Functions
+//!
+//! * `create_stream` - Start streaming mission tokens to a recipient.
+//! * `withdraw` - Withdraw the vested balance of a stream as its recipient.
+//! * `cancel_stream` - Cancel a stream as its sender, or on its behalf as `CancelOrigin`, and
+//!   reclaim the unvested remainder.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::{Decode, Encode};
+use frame_support::{
+    decl_error, decl_event, decl_module, decl_storage, ensure,
+    traits::{
+        EnsureOrigin,
+        ExistenceRequirement::{AllowDeath, KeepAlive},
+        Get,
+    },
+};
+use frame_system::{self as system, ensure_signed};
+use pallet_mission_tokens::StreamPayments;
+use sp_runtime::{
+    traits::{AccountIdConversion, Convert, Saturating, StaticLookup, Zero},
+    DispatchResult, ModuleId, RuntimeDebug,
+};
+use sp_std::prelude::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+/// An index of a stream. Just a `u64`.
+pub type StreamIndex = u64;
+
+type BalanceOf<T> = <T as pallet_mission_tokens::Trait>::Balance;
+type TokenId<T> = <T as pallet_mission_tokens::Trait>::MissionTokenId;
+
+pub trait Trait: frame_system::Trait + pallet_mission_tokens::Trait {
+    /// The overarching event type.
+    type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
+
+    /// The streams' pallet id, used for deriving the escrow account.
+    type ModuleId: Get<ModuleId>;
+
+    /// The minimum amount of mission tokens a stream must carry.
+    type MinimumDeposit: Get<BalanceOf<Self>>;
+
+    /// Convert the block number into a balance.
+    type BlockNumberToBalance: Convert<Self::BlockNumber, BalanceOf<Self>>;
+
+    /// Origin that may cancel the streams of the sender it returns, for senders that cannot sign
+    /// for themselves.
+    type CancelOrigin: EnsureOrigin<Self::Origin, Success = Self::AccountId>;
+}
+
+/// A stream of mission tokens from a sender to a recipient.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct Stream<AccountId, Balance, BlockNumber, MissionTokenId> {
+    /// The account that funded the stream and may cancel it.
+    pub sender: AccountId,
+    /// The account the stream vests to.
+    pub recipient: AccountId,
+    /// The mission token being streamed.
+    pub mission_token_id: MissionTokenId,
+    /// The total amount held in escrow for this stream when it was created.
+    pub deposit: Balance,
+    /// The amount vesting every block from `start`.
+    pub rate_per_block: Balance,
+    /// The block from which the deposit starts vesting.
+    pub start: BlockNumber,
+    /// The block before which nothing can be withdrawn.
+    pub cliff: BlockNumber,
+    /// The amount already withdrawn by the recipient.
+    pub withdrawn: Balance,
+}
+
+impl<AccountId, Balance, BlockNumber, MissionTokenId>
+    Stream<AccountId, Balance, BlockNumber, MissionTokenId>
+where
+    Balance: Saturating + Copy + Ord + Zero,
+    BlockNumber: Saturating + Copy + Ord,
+{
+    /// The amount of the deposit that has vested at block `n`.
+    pub fn vested_at<BlockNumberToBalance: Convert<BlockNumber, Balance>>(
+        &self,
+        n: BlockNumber,
+    ) -> Balance {
+        if n < self.cliff {
+            return Zero::zero();
+        }
+        let elapsed = BlockNumberToBalance::convert(n.saturating_sub(self.start));
+        self.rate_per_block.saturating_mul(elapsed).min(self.deposit)
+    }
+
+    /// The amount the recipient can withdraw at block `n`.
+    pub fn withdrawable_at<BlockNumberToBalance: Convert<BlockNumber, Balance>>(
+        &self,
+        n: BlockNumber,
+    ) -> Balance {
+        self.vested_at::<BlockNumberToBalance>(n).saturating_sub(self.withdrawn)
+    }
+}
+
+type StreamOf<T> = Stream<
+    <T as frame_system::Trait>::AccountId,
+    BalanceOf<T>,
+    <T as frame_system::Trait>::BlockNumber,
+    TokenId<T>,
+>;
+
+decl_storage! {
+    trait Store for Module<T: Trait> as MissionStreams {
+        /// Number of streams that have been created.
+        pub StreamCount get(fn stream_count): StreamIndex;
+
+        /// Streams that are still running.
+        pub Streams get(fn streams): map hasher(twox_64_concat) StreamIndex => Option<StreamOf<T>>;
+    }
+}
+
+decl_event!(
+    pub enum Event<T>
+    where
+        AccountId = <T as frame_system::Trait>::AccountId,
+        MissionTokenBalance = <T as pallet_mission_tokens::Trait>::Balance,
+        MissionTokenId = <T as pallet_mission_tokens::Trait>::MissionTokenId,
+    {
+        /// A new stream has been created.
+        /// \[stream_index, sender, recipient, mission_token_id, deposit\]
+        StreamCreated(StreamIndex, AccountId, AccountId, MissionTokenId, MissionTokenBalance),
+        /// The recipient withdrew from a stream. \[stream_index, recipient, amount\]
+        Withdrawn(StreamIndex, AccountId, MissionTokenBalance),
+        /// A stream has been cancelled by its sender.
+        /// \[stream_index, paid_to_recipient, returned_to_sender\]
+        StreamCancelled(StreamIndex, MissionTokenBalance, MissionTokenBalance),
+        /// A stream has been withdrawn in full. \[stream_index\]
+        StreamFinished(StreamIndex),
+    }
+);
+
+decl_error! {
+    pub enum Error for Module<T: Trait> {
+        /// No stream at that index.
+        InvalidIndex,
+        /// The deposit is below the minimum deposit of a stream.
+        DepositTooLow,
+        /// The rate per block must not be zero.
+        ZeroRate,
+        /// The stream must not start in the past.
+        StartInPast,
+        /// The cliff must not be before the start.
+        CliffBeforeStart,
+        /// Only the recipient of the stream may do this.
+        NotRecipient,
+        /// Only the sender of the stream may do this.
+        NotSender,
+        /// Nothing has vested that has not been withdrawn yet.
+        NothingToWithdraw,
+    }
+}
+
+decl_module! {
+    pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+        type Error = Error<T>;
+
+        /// The streams' pallet id, used for deriving the escrow account.
+        const ModuleId: ModuleId = T::ModuleId::get();
+
+        /// The minimum amount of mission tokens a stream must carry.
+        const MinimumDeposit: BalanceOf<T> = T::MinimumDeposit::get();
+
+        fn deposit_event() = default;
+
+        /// Stream `deposit` of `token_id` from the sender to `recipient`.
+        ///
+        /// The deposit is moved into escrow. It vests `rate_per_block` every block from `start`
+        /// on, but nothing can be withdrawn before `cliff`.
+        ///
+        /// The dispatch origin for this call must be _Signed_.
+        ///
+        /// Emits `StreamCreated`.
+        #[weight = 50_000_000 + T::DbWeight::get().reads_writes(4, 4)]
+        fn create_stream(
+            origin,
+            recipient: <T::Lookup as StaticLookup>::Source,
+            #[compact] token_id: TokenId<T>,
+            #[compact] deposit: BalanceOf<T>,
+            #[compact] rate_per_block: BalanceOf<T>,
+            start: T::BlockNumber,
+            cliff: T::BlockNumber,
+        ) {
+            let sender = ensure_signed(origin)?;
+            let recipient = T::Lookup::lookup(recipient)?;
+            ensure!(start >= system::Module::<T>::block_number(), Error::<T>::StartInPast);
+            Self::do_create_stream(
+                &sender,
+                &recipient,
+                token_id,
+                deposit,
+                rate_per_block,
+                start,
+                cliff,
+            )?;
+        }
+
+        /// Withdraw everything that has vested in stream `stream_id` and not been withdrawn yet.
+        ///
+        /// The dispatch origin for this call must be the recipient of the stream.
+        ///
+        /// Emits `Withdrawn`, and `StreamFinished` once the whole deposit has been withdrawn.
+        #[weight = 40_000_000 + T::DbWeight::get().reads_writes(3, 3)]
+        fn withdraw(origin, #[compact] stream_id: StreamIndex) {
+            let who = ensure_signed(origin)?;
+            let mut stream = Self::streams(stream_id).ok_or(Error::<T>::InvalidIndex)?;
+            ensure!(stream.recipient == who, Error::<T>::NotRecipient);
+
+            let now = system::Module::<T>::block_number();
+            let amount = stream.withdrawable_at::<T::BlockNumberToBalance>(now);
+            ensure!(!amount.is_zero(), Error::<T>::NothingToWithdraw);
+
+            <pallet_mission_tokens::Module<T>>::do_transfer(
+                &Self::account_id(),
+                &who,
+                stream.mission_token_id,
+                amount,
+                AllowDeath,
+            )?;
+            stream.withdrawn = stream.withdrawn.saturating_add(amount);
+            Self::deposit_event(RawEvent::Withdrawn(stream_id, who, amount));
+
+            if stream.withdrawn >= stream.deposit {
+                <Streams<T>>::remove(stream_id);
+                Self::deposit_event(RawEvent::StreamFinished(stream_id));
+            } else {
+                <Streams<T>>::insert(stream_id, stream);
+            }
+        }
+
+        /// Cancel stream `stream_id`. The recipient is paid what has vested and not been withdrawn
+        /// yet; the unvested remainder is returned to the sender.
+        ///
+        /// The dispatch origin for this call must be the sender of the stream, or
+        /// `CancelOrigin` acting for the sender.
+        ///
+        /// Emits `StreamCancelled`.
+        #[weight = 50_000_000 + T::DbWeight::get().reads_writes(4, 4)]
+        fn cancel_stream(origin, #[compact] stream_id: StreamIndex) {
+            let who = match T::CancelOrigin::try_origin(origin) {
+                Ok(who) => who,
+                Err(origin) => ensure_signed(origin)?,
+            };
+            let stream = Self::streams(stream_id).ok_or(Error::<T>::InvalidIndex)?;
+            ensure!(stream.sender == who, Error::<T>::NotSender);
+
+            let now = system::Module::<T>::block_number();
+            let vested = stream.vested_at::<T::BlockNumberToBalance>(now);
+            let to_recipient = vested.saturating_sub(stream.withdrawn);
+            let to_sender = stream.deposit.saturating_sub(vested);
+            let escrow = Self::account_id();
+
+            <pallet_mission_tokens::Module<T>>::do_transfer(
+                &escrow,
+                &stream.recipient,
+                stream.mission_token_id,
+                to_recipient,
+                AllowDeath,
+            )?;
+            <pallet_mission_tokens::Module<T>>::do_transfer(
+                &escrow,
+                &stream.sender,
+                stream.mission_token_id,
+                to_sender,
+                AllowDeath,
+            )?;
+            <Streams<T>>::remove(stream_id);
+
+            Self::deposit_event(RawEvent::StreamCancelled(stream_id, to_recipient, to_sender));
+        }
+    }
+}
+
+impl<T: Trait> Module<T> {
+    /// The account ID of the escrow holding the deposits of all streams.
+    ///
+    /// This actually does computation. If you need to keep using it, then make sure you cache the
+    /// value and only call this once.
+    pub fn account_id() -> T::AccountId {
+        T::ModuleId::get().into_account()
+    }
+
+    /// The amount the recipient of stream `stream_id` can currently withdraw.
+    pub fn withdrawable(stream_id: StreamIndex) -> BalanceOf<T> {
+        let now = system::Module::<T>::block_number();
+        Self::streams(stream_id)
+            .map(|stream| stream.withdrawable_at::<T::BlockNumberToBalance>(now))
+            .unwrap_or_else(Zero::zero)
+    }
+
+    fn do_create_stream(
+        sender: &T::AccountId,
+        recipient: &T::AccountId,
+        token_id: TokenId<T>,
+        deposit: BalanceOf<T>,
+        rate_per_block: BalanceOf<T>,
+        start: T::BlockNumber,
+        cliff: T::BlockNumber,
+    ) -> DispatchResult {
+        <pallet_mission_tokens::Module<T>>::validate_mission_token_id(token_id)?;
+        ensure!(deposit >= T::MinimumDeposit::get(), Error::<T>::DepositTooLow);
+        ensure!(!rate_per_block.is_zero(), Error::<T>::ZeroRate);
+        ensure!(cliff >= start, Error::<T>::CliffBeforeStart);
+
+        <pallet_mission_tokens::Module<T>>::do_transfer(
+            sender,
+            &Self::account_id(),
+            token_id,
+            deposit,
+            KeepAlive,
+        )?;
+
+        let index = Self::stream_count();
+        StreamCount::put(index + 1);
+        <Streams<T>>::insert(
+            index,
+            Stream {
+                sender: sender.clone(),
+                recipient: recipient.clone(),
+                mission_token_id: token_id,
+                deposit,
+                rate_per_block,
+                start,
+                cliff,
+                withdrawn: Zero::zero(),
+            },
+        );
+
+        Self::deposit_event(RawEvent::StreamCreated(
+            index,
+            sender.clone(),
+            recipient.clone(),
+            token_id,
+            deposit,
+        ));
+        Ok(())
+    }
+}
+
+impl<T: Trait> StreamPayments<T::AccountId, TokenId<T>, BalanceOf<T>, T::BlockNumber>
+    for Module<T>
+{
+    fn create_stream(
+        sender: &T::AccountId,
+        recipient: &T::AccountId,
+        token_id: TokenId<T>,
+        deposit: BalanceOf<T>,
+        rate_per_block: BalanceOf<T>,
+        start: T::BlockNumber,
+        cliff: T::BlockNumber,
+    ) -> DispatchResult {
+        Self::do_create_stream(sender, recipient, token_id, deposit, rate_per_block, start, cliff)
+    }
+}
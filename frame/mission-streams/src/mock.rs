@@ -0,0 +1,121 @@
+use crate::{Module, Trait};
+use frame_support::{
+    impl_outer_origin, parameter_types,
+    traits::{EnsureOrigin, Get},
+    weights::Weight,
+};
+use frame_system::{self as system, EnsureRoot, RawOrigin};
+use std::marker::PhantomData;
+use sp_core::H256;
+use sp_runtime::{
+    testing::Header,
+    traits::{BlakeTwo256, ConvertInto, IdentityLookup},
+    ModuleId, Perbill,
+};
+
+impl_outer_origin! {
+    pub enum Origin for Test where system = frame_system {}
+}
+
+// Configure a mock runtime to test the pallet.
+
+#[derive(Clone, Eq, PartialEq)]
+pub struct Test;
+parameter_types! {
+    pub const BlockHashCount: u64 = 250;
+    pub const MaximumBlockWeight: Weight = 1024;
+    pub const MaximumBlockLength: u32 = 2 * 1024;
+    pub const AvailableBlockRatio: Perbill = Perbill::from_percent(75);
+}
+
+impl system::Trait for Test {
+    type BaseCallFilter = ();
+    type Origin = Origin;
+    type Call = ();
+    type Index = u64;
+    type BlockNumber = u64;
+    type Hash = H256;
+    type Hashing = BlakeTwo256;
+    type AccountId = u64;
+    type Lookup = IdentityLookup<Self::AccountId>;
+    type Header = Header;
+    type Event = ();
+    type BlockHashCount = BlockHashCount;
+    type MaximumBlockWeight = MaximumBlockWeight;
+    type DbWeight = ();
+    type BlockExecutionWeight = ();
+    type ExtrinsicBaseWeight = ();
+    type MaximumExtrinsicWeight = MaximumBlockWeight;
+    type MaximumBlockLength = MaximumBlockLength;
+    type AvailableBlockRatio = AvailableBlockRatio;
+    type Version = ();
+    type PalletInfo = ();
+    type AccountData = ();
+    type OnNewAccount = ();
+    type OnKilledAccount = ();
+    type SystemWeightInfo = ();
+}
+
+parameter_types! {
+    pub const ExistentialDeposit: u64 = 1;
+    pub const MaxMissionTokensSupply: u128 = 1_000_000;
+}
+
+impl pallet_mission_tokens::Trait for Test {
+    type Event = ();
+    type Balance = u64;
+    type MissionTokenId = u32;
+    type ExistentialDeposit = ExistentialDeposit;
+    type OnNewAccount = ();
+    type MaxMissionTokensSupply = MaxMissionTokensSupply;
+    type MissionCreatorOrigin = EnsureRoot<u64>;
+}
+
+parameter_types! {
+    pub const StreamsModuleId: ModuleId = ModuleId(*b"py/mstrm");
+    pub const MinimumDeposit: u64 = 10;
+    pub const Treasury: u64 = 1;
+}
+
+/// Lets root act for the account `Who`.
+pub struct EnsureRootAs<Who>(PhantomData<Who>);
+impl<Who: Get<u64>> EnsureOrigin<Origin> for EnsureRootAs<Who> {
+    type Success = u64;
+    fn try_origin(o: Origin) -> Result<u64, Origin> {
+        Into::<Result<RawOrigin<u64>, Origin>>::into(o).and_then(|o| match o {
+            RawOrigin::Root => Ok(Who::get()),
+            r => Err(Origin::from(r)),
+        })
+    }
+
+    #[cfg(feature = "runtime-benchmarks")]
+    fn successful_origin() -> Origin {
+        Origin::root()
+    }
+}
+
+impl Trait for Test {
+    type Event = ();
+    type ModuleId = StreamsModuleId;
+    type MinimumDeposit = MinimumDeposit;
+    type BlockNumberToBalance = ConvertInto;
+    type CancelOrigin = EnsureRootAs<Treasury>;
+}
+
+pub type System = system::Module<Test>;
+pub type MissionTokens = pallet_mission_tokens::Module<Test>;
+pub type MissionStreams = Module<Test>;
+
+// Build genesis storage according to the mock runtime.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+    let mut ext: sp_io::TestExternalities = system::GenesisConfig::default()
+        .build_storage::<Test>()
+        .unwrap()
+        .into();
+    ext.execute_with(|| {
+        System::set_block_number(1);
+        MissionTokens::mint(1, 1, 1_000);
+        MissionTokens::mint(2, 1, 10);
+    });
+    ext
+}
@@ -0,0 +1,146 @@
+use crate::{mock::*, Error, Stream};
+use frame_support::{assert_noop, assert_ok};
+use pallet_mission_tokens::StreamPayments;
+
+#[test]
+fn create_stream_should_escrow_deposit() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(MissionStreams::create_stream(Origin::signed(1), 2, 1, 100, 10, 5, 7));
+        assert_eq!(MissionTokens::free_balance(1, 1), 900);
+        assert_eq!(MissionTokens::free_balance(MissionStreams::account_id(), 1), 100);
+        assert_eq!(
+            MissionStreams::streams(0),
+            Some(Stream {
+                sender: 1,
+                recipient: 2,
+                mission_token_id: 1,
+                deposit: 100,
+                rate_per_block: 10,
+                start: 5,
+                cliff: 7,
+                withdrawn: 0,
+            })
+        );
+        assert_eq!(MissionStreams::stream_count(), 1);
+    });
+}
+
+#[test]
+fn create_stream_should_check_terms() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            MissionStreams::create_stream(Origin::signed(1), 2, 1, 9, 1, 5, 5),
+            Error::<Test>::DepositTooLow
+        );
+        assert_noop!(
+            MissionStreams::create_stream(Origin::signed(1), 2, 1, 100, 0, 5, 5),
+            Error::<Test>::ZeroRate
+        );
+        assert_noop!(
+            MissionStreams::create_stream(Origin::signed(1), 2, 1, 100, 10, 5, 4),
+            Error::<Test>::CliffBeforeStart
+        );
+        assert_noop!(
+            MissionStreams::create_stream(Origin::signed(1), 2, 1, 100, 10, 0, 4),
+            Error::<Test>::StartInPast
+        );
+        assert_noop!(
+            MissionStreams::create_stream(Origin::signed(1), 2, 100, 100, 10, 5, 5),
+            pallet_mission_tokens::Error::<Test>::InvalidMissionTokenId
+        );
+    });
+}
+
+#[test]
+fn withdraw_should_pay_accrued_amount_after_cliff() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(MissionStreams::create_stream(Origin::signed(1), 2, 1, 100, 10, 5, 7));
+
+        System::set_block_number(6);
+        assert_eq!(MissionStreams::withdrawable(0), 0);
+        assert_noop!(
+            MissionStreams::withdraw(Origin::signed(2), 0),
+            Error::<Test>::NothingToWithdraw
+        );
+
+        System::set_block_number(7);
+        assert_eq!(MissionStreams::withdrawable(0), 20);
+        assert_noop!(
+            MissionStreams::withdraw(Origin::signed(1), 0),
+            Error::<Test>::NotRecipient
+        );
+        assert_ok!(MissionStreams::withdraw(Origin::signed(2), 0));
+        assert_eq!(MissionTokens::free_balance(2, 1), 30);
+        assert_eq!(MissionStreams::withdrawable(0), 0);
+
+        System::set_block_number(100);
+        assert_ok!(MissionStreams::withdraw(Origin::signed(2), 0));
+        assert_eq!(MissionTokens::free_balance(2, 1), 110);
+        assert_eq!(MissionStreams::streams(0), None);
+    });
+}
+
+#[test]
+fn cancel_stream_should_return_unvested_remainder() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(MissionStreams::create_stream(Origin::signed(1), 2, 1, 100, 10, 5, 7));
+
+        System::set_block_number(8);
+        assert_ok!(MissionStreams::withdraw(Origin::signed(2), 0));
+        assert_eq!(MissionTokens::free_balance(2, 1), 40);
+
+        System::set_block_number(10);
+        assert_noop!(
+            MissionStreams::cancel_stream(Origin::signed(2), 0),
+            Error::<Test>::NotSender
+        );
+        assert_ok!(MissionStreams::cancel_stream(Origin::signed(1), 0));
+        assert_eq!(MissionTokens::free_balance(2, 1), 60);
+        assert_eq!(MissionTokens::free_balance(1, 1), 950);
+        assert_eq!(MissionStreams::streams(0), None);
+    });
+}
+
+#[test]
+fn cancel_before_cliff_should_return_everything() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(MissionStreams::create_stream(Origin::signed(1), 2, 1, 100, 10, 5, 7));
+
+        System::set_block_number(6);
+        assert_ok!(MissionStreams::cancel_stream(Origin::signed(1), 0));
+        assert_eq!(MissionTokens::free_balance(1, 1), 1_000);
+        assert_eq!(MissionTokens::free_balance(2, 1), 10);
+    });
+}
+
+#[test]
+fn cancel_origin_should_cancel_streams_of_its_account() {
+    new_test_ext().execute_with(|| {
+        MissionTokens::mint(2, 1, 100);
+        assert_ok!(MissionStreams::create_stream(Origin::signed(1), 2, 1, 100, 10, 5, 7));
+        assert_ok!(MissionStreams::create_stream(Origin::signed(2), 3, 1, 100, 10, 5, 7));
+
+        System::set_block_number(8);
+        // Root acts for account 1 only.
+        assert_noop!(
+            MissionStreams::cancel_stream(Origin::root(), 1),
+            Error::<Test>::NotSender
+        );
+        assert_ok!(MissionStreams::cancel_stream(Origin::root(), 0));
+        assert_eq!(MissionTokens::free_balance(2, 1), 40);
+        assert_eq!(MissionTokens::free_balance(1, 1), 970);
+        assert_eq!(MissionStreams::streams(0), None);
+        assert!(MissionStreams::streams(1).is_some());
+    });
+}
+
+#[test]
+fn stream_payments_should_create_stream() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(<MissionStreams as StreamPayments<_, _, _, _>>::create_stream(
+            &1, &2, 1, 100, 25, 1, 1
+        ));
+        System::set_block_number(3);
+        assert_eq!(MissionStreams::withdrawable(0), 50);
+    });
+}
@@ -23,6 +23,8 @@ use sp_runtime::{
 use sp_std::prelude::*;
 use sp_std::{cmp, convert::Infallible, ops::BitOr, result};
 
+pub mod migrations;
+
 #[cfg(test)]
 mod mock;
 
@@ -82,6 +84,22 @@ impl BitOr for Reasons {
     }
 }
 
+// A value placed in storage that represents the current version of the MissionTokens storage.
+// This value is used by the migrations to determine whether they need to run.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+enum Releases {
+    /// `TotalIssuance` was not tracked by `mint`.
+    V1_0_0,
+    /// `TotalIssuance` matches the sum of all account balances.
+    V2_0_0,
+}
+
+impl Default for Releases {
+    fn default() -> Self {
+        Releases::V1_0_0
+    }
+}
+
 /// A single lock on a balance. There can be many of these on an account and they "overlap", so the
 /// same balance is frozen by multiple locks.
 #[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
@@ -141,8 +159,8 @@ impl<Balance: Saturating + Copy + Ord> AccountData<Balance> {
 
 decl_storage! {
     trait Store for Module<T: Trait> as MissionTokens {
-        MaxMissionTokenId get(fn max_mission_token_id): T::MissionTokenId = 17.into();
-        MinMissionTokenId get(fn min_mission_token_id): T::MissionTokenId = 1.into();
+        MaxMissionTokenId get(fn max_mission_token_id) config(): T::MissionTokenId = 17.into();
+        MinMissionTokenId get(fn min_mission_token_id) config(): T::MissionTokenId = 1.into();
 
         pub TotalIssuance: map hasher(blake2_128_concat) T::MissionTokenId => T::Balance;
 
@@ -153,6 +171,32 @@ decl_storage! {
         /// Any liquidity locks on some account balances.
         /// NOTE: Should only be accessed when setting, changing and freeing a lock.
         pub Locks get(fn locks): map hasher(blake2_128_concat) (T::MissionTokenId, T::AccountId) => Vec<BalanceLock<T::Balance>>;
+
+        /// The amount of the mission tokens of an owner a spender may transfer.
+        pub Allowances get(fn allowance):
+            double_map hasher(blake2_128_concat) (T::MissionTokenId, T::AccountId), hasher(blake2_128_concat) T::AccountId => T::Balance;
+
+        /// Storage version of the pallet.
+        ///
+        /// New networks start with the latest version, as determined by the genesis build.
+        StorageVersion build(|_: &GenesisConfig<T>| Releases::V2_0_0): Releases;
+    }
+    add_extra_genesis {
+        /// The initial mission token balances, as `(mission_token_id, account, balance)`.
+        config(balances): Vec<(T::MissionTokenId, T::AccountId, T::Balance)>;
+        build(|config: &GenesisConfig<T>| {
+            assert!(
+                config.min_mission_token_id <= config.max_mission_token_id,
+                "Genesis mission token ids must form a range"
+            );
+            for &(token_id, ref who, balance) in &config.balances {
+                assert!(
+                    token_id >= config.min_mission_token_id && token_id <= config.max_mission_token_id,
+                    "Genesis balance of an unknown mission token"
+                );
+                <Module<T>>::mint(who.clone(), token_id, balance);
+            }
+        });
     }
 }
 
@@ -183,6 +227,9 @@ decl_event!(
         /// A new \[account\] was created.
         NewAccount(AccountId, MissionTokenId),
         MissionCreated(MissionTokenId),
+        /// A spender may transfer an amount of the mission tokens of an owner.
+        /// \[owner, spender, mission_token_id, amount\]
+        Approval(AccountId, AccountId, MissionTokenId, MissionTokenBalance),
     }
 );
 
@@ -209,6 +256,8 @@ decl_error! {
         ExistingVestingSchedule,
         /// Beneficiary account must pre-exist
         DeadAccount,
+        /// The allowance of the spender is lower than the transfer amount
+        AllowanceLow,
     }
 }
 
@@ -283,6 +332,9 @@ impl<T: Trait> Module<T> {
                 .checked_add(&allowed_value)
                 .unwrap_or_else(|| T::MaxMissionTokensSupply::get().saturated_into())
         });
+        <TotalIssuance<T>>::mutate(token_id, |issued| {
+            *issued = issued.saturating_add(allowed_value)
+        });
     }
 
     pub fn validate_mission_token_id(token_id: T::MissionTokenId) -> DispatchResult {
@@ -358,6 +410,56 @@ impl<T: Trait> Module<T> {
         Ok(())
     }
 
+    /// Allow `spender` to transfer `amount` of the `token_id` tokens of `owner`, replacing any
+    /// previous allowance.
+    pub fn approve(
+        owner: &T::AccountId,
+        spender: &T::AccountId,
+        token_id: T::MissionTokenId,
+        amount: T::Balance,
+    ) -> DispatchResult {
+        Self::validate_mission_token_id(token_id)?;
+        if amount.is_zero() {
+            <Allowances<T>>::remove((token_id, owner), spender);
+        } else {
+            <Allowances<T>>::insert((token_id, owner), spender, amount);
+        }
+
+        Self::deposit_event(RawEvent::Approval(
+            owner.clone(),
+            spender.clone(),
+            token_id,
+            amount,
+        ));
+
+        Ok(())
+    }
+
+    /// Transfer `value` of the `token_id` tokens of `owner` to `dest` on behalf of `spender`,
+    /// spending its allowance.
+    pub fn transfer_from(
+        spender: &T::AccountId,
+        owner: &T::AccountId,
+        dest: &T::AccountId,
+        token_id: T::MissionTokenId,
+        value: T::Balance,
+    ) -> DispatchResult {
+        Self::validate_mission_token_id(token_id)?;
+        let allowance = Self::allowance((token_id, owner), spender)
+            .checked_sub(&value)
+            .ok_or(Error::<T>::AllowanceLow)?;
+
+        Self::do_transfer(owner, dest, token_id, value, ExistenceRequirement::AllowDeath)?;
+
+        if allowance.is_zero() {
+            <Allowances<T>>::remove((token_id, owner), spender);
+        } else {
+            <Allowances<T>>::insert((token_id, owner), spender, allowance);
+        }
+
+        Ok(())
+    }
+
     /// Move `value` from the free balance from `who` to their reserved balance.
     ///
     /// Is a no-op if value to be reserved is zero.
@@ -685,6 +787,153 @@ impl<T: Trait> Module<T> {
         <T as Trait>::OnNewAccount::on_new_account(&who);
         Self::deposit_event(RawEvent::NewAccount(who.1, who.0));
     }
+
+    /// Create a lock on the `token_id` balance of `who`, replacing any existing lock with the
+    /// same `id`.
+    ///
+    /// Is a no-op if lock amount is zero or `reasons` `is_none()`.
+    pub fn set_lock(
+        id: LockIdentifier,
+        who: &T::AccountId,
+        token_id: T::MissionTokenId,
+        amount: T::Balance,
+        reasons: WithdrawReasons,
+    ) {
+        if amount.is_zero() || reasons.is_none() {
+            return;
+        }
+        let mut new_lock = Some(BalanceLock {
+            id,
+            amount,
+            reasons: reasons.into(),
+        });
+        let mut locks = Self::locks((token_id, who.clone()))
+            .into_iter()
+            .filter_map(|l| if l.id == id { new_lock.take() } else { Some(l) })
+            .collect::<Vec<_>>();
+        if let Some(lock) = new_lock {
+            locks.push(lock)
+        }
+        Self::update_locks(who, token_id, &locks[..]);
+    }
+
+    /// Extend a lock on the `token_id` balance of `who`. The resulting lock covers the maximum of
+    /// the existing and the new amount, and the union of the reasons.
+    ///
+    /// Is a no-op if lock amount is zero or `reasons` `is_none()`.
+    pub fn extend_lock(
+        id: LockIdentifier,
+        who: &T::AccountId,
+        token_id: T::MissionTokenId,
+        amount: T::Balance,
+        reasons: WithdrawReasons,
+    ) {
+        if amount.is_zero() || reasons.is_none() {
+            return;
+        }
+        let mut new_lock = Some(BalanceLock {
+            id,
+            amount,
+            reasons: reasons.into(),
+        });
+        let mut locks = Self::locks((token_id, who.clone()))
+            .into_iter()
+            .filter_map(|l| {
+                if l.id == id {
+                    new_lock.take().map(|nl| BalanceLock {
+                        id: l.id,
+                        amount: l.amount.max(nl.amount),
+                        reasons: l.reasons | nl.reasons,
+                    })
+                } else {
+                    Some(l)
+                }
+            })
+            .collect::<Vec<_>>();
+        if let Some(lock) = new_lock {
+            locks.push(lock)
+        }
+        Self::update_locks(who, token_id, &locks[..]);
+    }
+
+    /// Remove the lock with the given `id` from the `token_id` balance of `who`.
+    pub fn remove_lock(id: LockIdentifier, who: &T::AccountId, token_id: T::MissionTokenId) {
+        let mut locks = Self::locks((token_id, who.clone()));
+        locks.retain(|l| l.id != id);
+        Self::update_locks(who, token_id, &locks[..]);
+    }
+
+    /// Update the frozen parts of the account of `who` for `token_id` given the complete set of
+    /// `locks` and store the locks.
+    fn update_locks(
+        who: &T::AccountId,
+        token_id: T::MissionTokenId,
+        locks: &[BalanceLock<T::Balance>],
+    ) {
+        Self::mutate_account(who, token_id, |b| {
+            b.misc_frozen = Zero::zero();
+            b.fee_frozen = Zero::zero();
+            for l in locks.iter() {
+                if l.reasons == Reasons::All || l.reasons == Reasons::Misc {
+                    b.misc_frozen = b.misc_frozen.max(l.amount);
+                }
+                if l.reasons == Reasons::All || l.reasons == Reasons::Fee {
+                    b.fee_frozen = b.fee_frozen.max(l.amount);
+                }
+            }
+        });
+
+        let key = (token_id, who.clone());
+        if locks.is_empty() {
+            <Locks<T>>::remove(&key);
+        } else {
+            <Locks<T>>::insert(&key, locks);
+        }
+    }
+}
+
+/// Something that can pay mission tokens out gradually over time.
+pub trait StreamPayments<AccountId, MissionTokenId, Balance, BlockNumber> {
+    /// Move `deposit` of `token_id` from `sender` into a new stream to `recipient`. The stream
+    /// vests `rate_per_block` every block from `start` on, but nothing can be withdrawn before
+    /// `cliff`.
+    fn create_stream(
+        sender: &AccountId,
+        recipient: &AccountId,
+        token_id: MissionTokenId,
+        deposit: Balance,
+        rate_per_block: Balance,
+        start: BlockNumber,
+        cliff: BlockNumber,
+    ) -> DispatchResult;
+}
+
+impl<AccountId, MissionTokenId, Balance, BlockNumber>
+    StreamPayments<AccountId, MissionTokenId, Balance, BlockNumber> for ()
+{
+    fn create_stream(
+        _sender: &AccountId,
+        _recipient: &AccountId,
+        _token_id: MissionTokenId,
+        _deposit: Balance,
+        _rate_per_block: Balance,
+        _start: BlockNumber,
+        _cliff: BlockNumber,
+    ) -> DispatchResult {
+        Err(DispatchError::Other("Streams are not supported"))
+    }
+}
+
+/// Something that can tell the worth of mission tokens in a reference unit, such as a currency.
+pub trait MissionTokenValue<MissionTokenId, Balance> {
+    /// The worth of `amount` of `token_id` in the reference unit, if it is known.
+    fn value_of(token_id: MissionTokenId, amount: Balance) -> Option<Balance>;
+}
+
+impl<MissionTokenId, Balance> MissionTokenValue<MissionTokenId, Balance> for () {
+    fn value_of(_token_id: MissionTokenId, _amount: Balance) -> Option<Balance> {
+        None
+    }
 }
 
 // wrapping these imbalances in a private module is necessary to ensure absolute privacy
@@ -0,0 +1,68 @@
+//! Storage migrations of the mission tokens pallet.
+
+use crate::{Module, Releases, StorageVersion, SystemAccount, TotalIssuance, Trait};
+use frame_support::{
+    storage::{IterableStorageMap, StorageMap, StorageValue},
+    traits::{Get, Migration},
+    weights::Weight,
+};
+use sp_runtime::traits::{CheckedAdd, One, Saturating};
+use sp_std::{collections::btree_map::BTreeMap, marker::PhantomData};
+
+/// Sum the balances of all accounts, per mission token.
+fn account_totals<T: Trait>() -> (BTreeMap<T::MissionTokenId, T::Balance>, u64) {
+    let mut totals = BTreeMap::<T::MissionTokenId, T::Balance>::new();
+    let mut accounts = 0u64;
+    for ((token_id, _), account) in SystemAccount::<T>::iter() {
+        let total = totals.entry(token_id).or_default();
+        *total = total.saturating_add(account.data.total());
+        accounts += 1;
+    }
+    (totals, accounts)
+}
+
+/// Recompute `TotalIssuance` from the account balances.
+///
+/// `mint` did not record the minted amount in `TotalIssuance` before `V2_0_0`, so the issuance
+/// of every mission token is rebuilt by summing the free and reserved balances of its holders.
+pub struct RecomputeTotalIssuance<T>(PhantomData<T>);
+
+impl<T: Trait> Migration for RecomputeTotalIssuance<T> {
+    const NAME: &'static str = "MissionTokens::RecomputeTotalIssuance";
+
+    fn migrate() -> Weight {
+        if StorageVersion::get() != Releases::V1_0_0 {
+            return T::DbWeight::get().reads(1);
+        }
+
+        let (totals, accounts) = account_totals::<T>();
+        let min = <Module<T>>::min_mission_token_id();
+        let max = <Module<T>>::max_mission_token_id();
+        let mut token_id = min;
+        let mut tokens = 0u64;
+        while token_id <= max {
+            TotalIssuance::<T>::insert(token_id, totals.get(&token_id).copied().unwrap_or_default());
+            tokens += 1;
+            token_id = match token_id.checked_add(&One::one()) {
+                Some(next) => next,
+                None => break,
+            };
+        }
+        StorageVersion::put(Releases::V2_0_0);
+
+        T::DbWeight::get().reads_writes(accounts + 3, tokens + 1)
+    }
+
+    fn post_upgrade() -> Result<(), &'static str> {
+        if StorageVersion::get() != Releases::V2_0_0 {
+            return Err("storage version was not bumped to V2_0_0");
+        }
+        let (totals, _) = account_totals::<T>();
+        for (token_id, total) in totals {
+            if TotalIssuance::<T>::get(token_id) != total {
+                return Err("total issuance does not match the account balances");
+            }
+        }
+        Ok(())
+    }
+}
@@ -21,6 +21,8 @@ parameter_types! {
     pub const MaximumBlockWeight: Weight = 1024;
     pub const MaximumBlockLength: u32 = 2 * 1024;
     pub const AvailableBlockRatio: Perbill = Perbill::from_percent(75);
+    pub const ExistentialDeposit: u64 = 1;
+    pub const MaxMissionTokensSupply: u128 = 1_000_000;
 }
 
 impl system::Trait for Test {
@@ -55,6 +57,10 @@ impl Trait for Test {
     type Event = ();
     type Balance = u64;
     type MissionTokenId = u32;
+    type ExistentialDeposit = ExistentialDeposit;
+    type OnNewAccount = ();
+    type MaxMissionTokensSupply = MaxMissionTokensSupply;
+    type MissionCreatorOrigin = system::EnsureRoot<u64>;
 }
 
 pub type MissionTokens = Module<Test>;
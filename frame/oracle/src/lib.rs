@@ -0,0 +1,437 @@
+//! # Oracle Pallet
+//!
+//! Gives the chain a reference for the worth of the native token and of the mission tokens.
+//!
+//! ## Overview
+//!
+//! Feeders, accounts added by `FeederOrigin`, submit the prices of the tokens with
+//! `feed_prices`. The price of a token is the median of the prices fed for it by the current
+//! feeders in the last `StalePeriod` blocks, known once at least `MinFeeds` feeders fed one.
+//! Prices are the worth of the smallest unit of a token in the smallest unit of the reference,
+//! such as a currency, the feeders agree on.
+//!
+//! Every `FeedInterval` blocks, the offchain worker of a node holding an `orcl` key fetches the
+//! prices from the URL stored raw in its persistent offchain storage under `SOURCE_KEY`, and
+//! submits them in a transaction signed with the account of the key. The source answers with a
+//! JSON object of the prices by token, `native` for the native token and the id for mission
+//! tokens:
+//!
+//! ```json
+//! {"native": 1.25, "1": 0.5, "2": 0.0125}
+//! ```
+//!
+//! Feeding is free for feeders.
+//!
+//! Other pallets read prices with `price`, or value amounts of mission tokens through the
+//! `MissionTokenValue` implementation of the module.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::{Decode, Encode};
+use frame_support::{
+    debug, decl_error, decl_event, decl_module, decl_storage,
+    dispatch::DispatchResultWithPostInfo,
+    ensure,
+    traits::{EnsureOrigin, Get},
+    weights::Pays,
+};
+use frame_system::{
+    ensure_signed,
+    offchain::{AppCrypto, CreateSignedTransaction, SendSignedTransaction, Signer},
+};
+use lite_json::json::JsonValue;
+use pallet_mission_tokens::MissionTokenValue;
+use sp_core::crypto::KeyTypeId;
+use sp_runtime::{
+    offchain::{http, Duration, StorageKind},
+    traits::{CheckedAdd, One, Saturating, UniqueSaturatedFrom, UniqueSaturatedInto, Zero},
+    FixedPointNumber, FixedU128, RuntimeDebug,
+};
+use sp_std::prelude::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+/// Key type of the keys feeding prices.
+pub const KEY_TYPE: KeyTypeId = KeyTypeId(*b"orcl");
+
+/// Key of the URL prices are fetched from, in the persistent offchain storage.
+pub const SOURCE_KEY: &[u8] = b"oracle::source";
+
+/// Time given to the source to answer.
+const HTTP_TIMEOUT_MS: u64 = 5_000;
+
+/// Keys feeding prices, of type `orcl`.
+pub mod crypto {
+    use super::KEY_TYPE;
+    use sp_core::sr25519::Signature as Sr25519Signature;
+    use sp_runtime::{
+        app_crypto::{app_crypto, sr25519},
+        traits::Verify,
+        MultiSignature, MultiSigner,
+    };
+    app_crypto!(sr25519, KEY_TYPE);
+
+    /// Signs the transactions feeding prices with an `orcl` key.
+    pub struct FeederAuthId;
+
+    impl frame_system::offchain::AppCrypto<MultiSigner, MultiSignature> for FeederAuthId {
+        type RuntimeAppPublic = Public;
+        type GenericSignature = sp_core::sr25519::Signature;
+        type GenericPublic = sp_core::sr25519::Public;
+    }
+
+    impl frame_system::offchain::AppCrypto<<Sr25519Signature as Verify>::Signer, Sr25519Signature>
+        for FeederAuthId
+    {
+        type RuntimeAppPublic = Public;
+        type GenericSignature = sp_core::sr25519::Signature;
+        type GenericPublic = sp_core::sr25519::Public;
+    }
+}
+
+type TokenId<T> = <T as pallet_mission_tokens::Trait>::MissionTokenId;
+type BalanceOf<T> = <T as pallet_mission_tokens::Trait>::Balance;
+
+/// The worth of the smallest unit of a token in the smallest unit of the reference.
+pub type Price = FixedU128;
+
+/// A token with a price.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum Token<MissionTokenId> {
+    /// The native token of the chain.
+    Native,
+    /// A mission token.
+    Mission(MissionTokenId),
+}
+
+pub trait Trait: CreateSignedTransaction<Call<Self>> + pallet_mission_tokens::Trait {
+    /// The identifier type of the keys feeding prices.
+    type AuthorityId: AppCrypto<Self::Public, Self::Signature>;
+
+    /// The overarching event type.
+    type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
+
+    /// The overarching dispatch call type.
+    type Call: From<Call<Self>>;
+
+    /// Origin from which feeders are added and removed.
+    type FeederOrigin: EnsureOrigin<Self::Origin>;
+
+    /// Maximum number of feeders.
+    type MaxFeeders: Get<u32>;
+
+    /// Number of blocks between two feeds of the offchain worker.
+    type FeedInterval: Get<Self::BlockNumber>;
+
+    /// Number of blocks after which a fed price is no longer taken into account.
+    type StalePeriod: Get<Self::BlockNumber>;
+
+    /// Number of feeders that must have fed a price for a token for it to be known.
+    type MinFeeds: Get<u32>;
+}
+
+decl_storage! {
+    trait Store for Module<T: Trait> as Oracle {
+        /// The accounts allowed to feed prices, sorted.
+        pub Feeders get(fn feeders): Vec<T::AccountId>;
+
+        /// The last price fed by a feeder for a token, and the block it was fed at.
+        pub Feeds get(fn feeds):
+            double_map hasher(blake2_128_concat) T::AccountId, hasher(twox_64_concat) Token<TokenId<T>>
+            => Option<(Price, T::BlockNumber)>;
+    }
+}
+
+decl_event!(
+    pub enum Event<T>
+    where
+        AccountId = <T as frame_system::Trait>::AccountId,
+    {
+        /// A feeder was added. [feeder]
+        FeederAdded(AccountId),
+        /// A feeder was removed, and its prices with it. [feeder]
+        FeederRemoved(AccountId),
+        /// A feeder fed prices. [feeder, count]
+        PricesFed(AccountId, u32),
+    }
+);
+
+decl_error! {
+    pub enum Error for Module<T: Trait> {
+        /// The account is already a feeder.
+        AlreadyFeeder,
+        /// The account is not a feeder.
+        NotFeeder,
+        /// There are as many feeders as there may be.
+        TooManyFeeders,
+        /// More prices were fed than there are tokens.
+        TooManyPrices,
+        /// A price of zero was fed.
+        ZeroPrice,
+    }
+}
+
+decl_module! {
+    pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+        type Error = Error<T>;
+
+        const MaxFeeders: u32 = T::MaxFeeders::get();
+
+        const FeedInterval: T::BlockNumber = T::FeedInterval::get();
+
+        const StalePeriod: T::BlockNumber = T::StalePeriod::get();
+
+        const MinFeeds: u32 = T::MinFeeds::get();
+
+        fn deposit_event() = default;
+
+        /// Allow `who` to feed prices.
+        ///
+        /// May only be called from `T::FeederOrigin`.
+        ///
+        /// # <weight>
+        /// - Complexity: O(F) where F is the number of feeders, bounded by `MaxFeeders`
+        /// - DbReads: `Feeders`
+        /// - DbWrites: `Feeders`
+        /// # </weight>
+        #[weight = 20_000_000 + T::DbWeight::get().reads_writes(1, 1)]
+        fn add_feeder(origin, who: T::AccountId) {
+            T::FeederOrigin::ensure_origin(origin)?;
+
+            <Feeders<T>>::try_mutate(|feeders| -> Result<(), Error<T>> {
+                let index = feeders.binary_search(&who).err().ok_or(Error::<T>::AlreadyFeeder)?;
+                ensure!(feeders.len() < T::MaxFeeders::get() as usize, Error::<T>::TooManyFeeders);
+                feeders.insert(index, who.clone());
+                Ok(())
+            })?;
+
+            Self::deposit_event(RawEvent::FeederAdded(who));
+        }
+
+        /// Stop `who` from feeding prices, and forget the prices it fed.
+        ///
+        /// May only be called from `T::FeederOrigin`.
+        ///
+        /// # <weight>
+        /// - Complexity: O(F + T) where F is the number of feeders, bounded by `MaxFeeders`, and
+        ///   T the number of tokens
+        /// - DbReads: `Feeders`
+        /// - DbWrites: `Feeders`, `Feeds`
+        /// # </weight>
+        #[weight = 20_000_000 + T::DbWeight::get().reads_writes(1, 2)]
+        fn remove_feeder(origin, who: T::AccountId) {
+            T::FeederOrigin::ensure_origin(origin)?;
+
+            <Feeders<T>>::try_mutate(|feeders| -> Result<(), Error<T>> {
+                let index = feeders.binary_search(&who).map_err(|_| Error::<T>::NotFeeder)?;
+                feeders.remove(index);
+                Ok(())
+            })?;
+            <Feeds<T>>::remove_prefix(&who);
+
+            Self::deposit_event(RawEvent::FeederRemoved(who));
+        }
+
+        /// Feed the current prices of tokens.
+        ///
+        /// The origin must be a feeder, which does not pay for the transaction.
+        ///
+        /// # <weight>
+        /// - Complexity: O(F + P) where F is the number of feeders, bounded by `MaxFeeders`, and
+        ///   P the number of prices, bounded by the number of tokens
+        /// - DbReads: `Feeders`, `MinMissionTokenId`, `MaxMissionTokenId`
+        /// - DbWrites: `Feeds` per price
+        /// # </weight>
+        #[weight = 20_000_000 + T::DbWeight::get().reads_writes(3, prices.len() as u64)]
+        fn feed_prices(origin, prices: Vec<(Token<TokenId<T>>, Price)>) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+            ensure!(Self::feeders().binary_search(&who).is_ok(), Error::<T>::NotFeeder);
+            ensure!(prices.len() <= Self::tokens().len(), Error::<T>::TooManyPrices);
+            for (token, price) in prices.iter() {
+                if let Token::Mission(token_id) = token {
+                    <pallet_mission_tokens::Module<T>>::validate_mission_token_id(*token_id)?;
+                }
+                ensure!(!price.is_zero(), Error::<T>::ZeroPrice);
+            }
+
+            let now = <frame_system::Module<T>>::block_number();
+            for (token, price) in prices.iter() {
+                <Feeds<T>>::insert(&who, token, (*price, now));
+            }
+
+            Self::deposit_event(RawEvent::PricesFed(who, prices.len() as u32));
+            Ok(Pays::No.into())
+        }
+
+        fn offchain_worker(now: T::BlockNumber) {
+            let interval = T::FeedInterval::get();
+            if interval.is_zero() || !(now % interval).is_zero() {
+                return;
+            }
+            if let Err(e) = Self::fetch_and_feed() {
+                debug::warn!("Feeding prices at block {:?} failed: {}", now, e);
+            }
+        }
+    }
+}
+
+impl<T: Trait> Module<T> {
+    /// The price of `token`: the median of the prices fed by the feeders in the last
+    /// `StalePeriod` blocks, if at least `MinFeeds` feeders fed one.
+    pub fn price(token: Token<TokenId<T>>) -> Option<Price> {
+        let now = <frame_system::Module<T>>::block_number();
+        let stale_period = T::StalePeriod::get();
+        let mut prices = Self::feeders()
+            .iter()
+            .filter_map(|feeder| <Feeds<T>>::get(feeder, token))
+            .filter(|(_, fed_at)| now.saturating_sub(*fed_at) <= stale_period)
+            .map(|(price, _)| price)
+            .collect::<Vec<_>>();
+        if prices.is_empty() || prices.len() < T::MinFeeds::get() as usize {
+            return None;
+        }
+
+        prices.sort();
+        let middle = prices.len() / 2;
+        if prices.len() % 2 == 1 {
+            Some(prices[middle])
+        } else {
+            let (low, high) = (prices[middle - 1].into_inner(), prices[middle].into_inner());
+            Some(Price::from_inner(low + (high - low) / 2))
+        }
+    }
+
+    /// The tokens with a price: the native token and every mission token.
+    pub fn tokens() -> Vec<Token<TokenId<T>>> {
+        let (min, max) = <pallet_mission_tokens::Module<T>>::mission_token_ids();
+        let mut tokens = vec![Token::Native];
+        let mut token_id = min;
+        while token_id <= max {
+            tokens.push(Token::Mission(token_id));
+            token_id = match token_id.checked_add(&One::one()) {
+                Some(next) => next,
+                None => break,
+            };
+        }
+        tokens
+    }
+
+    /// Fetch the prices from the source and feed them with every local key.
+    fn fetch_and_feed() -> Result<(), &'static str> {
+        let signer = Signer::<T, T::AuthorityId>::all_accounts();
+        if !signer.can_sign() {
+            return Ok(());
+        }
+        let source = match sp_io::offchain::local_storage_get(StorageKind::PERSISTENT, SOURCE_KEY) {
+            Some(source) => source,
+            None => return Ok(()),
+        };
+        let source = sp_std::str::from_utf8(&source).map_err(|_| "source is not UTF-8")?;
+
+        let prices = Self::fetch_prices(source)?;
+        if prices.is_empty() {
+            return Err("the source has no price of any token");
+        }
+
+        let results = signer.send_signed_transaction(|_account| Call::feed_prices(prices.clone()));
+        for (account, result) in &results {
+            if result.is_err() {
+                debug::warn!("[{:?}] Failed to submit the prices", account.id);
+            }
+        }
+        Ok(())
+    }
+
+    /// GET the prices of the tokens from `source`.
+    fn fetch_prices(source: &str) -> Result<Vec<(Token<TokenId<T>>, Price)>, &'static str> {
+        let deadline = sp_io::offchain::timestamp().add(Duration::from_millis(HTTP_TIMEOUT_MS));
+        let pending = http::Request::get(source)
+            .deadline(deadline)
+            .send()
+            .map_err(|_| "cannot request the prices")?;
+        let response = pending
+            .try_wait(deadline)
+            .map_err(|_| "the source did not answer in time")?
+            .map_err(|_| "the source did not answer")?;
+        if response.code != 200 {
+            return Err("the source refused to give the prices");
+        }
+
+        let body = response.body().collect::<Vec<u8>>();
+        let body = sp_std::str::from_utf8(&body).map_err(|_| "the prices are not UTF-8")?;
+        Self::parse_prices(body).ok_or("the prices are not a JSON object of numbers")
+    }
+
+    /// Parse the prices of the tokens from a JSON object of the prices by token. Tokens unknown
+    /// to the chain, and zero prices, are left out.
+    fn parse_prices(body: &str) -> Option<Vec<(Token<TokenId<T>>, Price)>> {
+        let object = match lite_json::parse_json(body).ok()? {
+            JsonValue::Object(object) => object,
+            _ => return None,
+        };
+
+        let tokens = Self::tokens();
+        let mut prices = Vec::new();
+        for (key, value) in object {
+            let price = match value {
+                JsonValue::Number(number) => {
+                    to_price(number.integer, number.fraction, number.fraction_length)?
+                }
+                _ => return None,
+            };
+            let token = match parse_token::<TokenId<T>>(&key) {
+                Some(token) if tokens.contains(&token) => token,
+                _ => continue,
+            };
+            if !price.is_zero() {
+                prices.push((token, price));
+            }
+        }
+        Some(prices)
+    }
+}
+
+impl<T: Trait> MissionTokenValue<TokenId<T>, BalanceOf<T>> for Module<T> {
+    fn value_of(token_id: TokenId<T>, amount: BalanceOf<T>) -> Option<BalanceOf<T>> {
+        let price = Self::price(Token::Mission(token_id))?;
+        let amount: u128 = amount.unique_saturated_into();
+        Some(BalanceOf::<T>::unique_saturated_from(price.saturating_mul_int(amount)))
+    }
+}
+
+/// The token of a key of the prices object: `native`, or the id of a mission token.
+fn parse_token<MissionTokenId: UniqueSaturatedFrom<u64>>(key: &[char]) -> Option<Token<MissionTokenId>> {
+    if key.iter().copied().eq("native".chars()) {
+        return Some(Token::Native);
+    }
+    if key.is_empty() || key.len() > 19 {
+        return None;
+    }
+    let mut id: u64 = 0;
+    for c in key {
+        id = id * 10 + c.to_digit(10)? as u64;
+    }
+    Some(Token::Mission(MissionTokenId::unique_saturated_from(id)))
+}
+
+/// The price of the JSON number `integer.fraction`, `fraction` having `fraction_length` digits.
+fn to_price(integer: i64, fraction: u64, fraction_length: u32) -> Option<Price> {
+    if integer < 0 {
+        return None;
+    }
+    // the digits of the fraction beyond the precision of a price are dropped.
+    const PRECISION: u32 = 18;
+    let fraction = if fraction_length > PRECISION {
+        fraction as u128 / 10u128.checked_pow(fraction_length - PRECISION)?
+    } else {
+        fraction as u128 * 10u128.pow(PRECISION - fraction_length)
+    };
+    (integer as u128)
+        .checked_mul(Price::accuracy())?
+        .checked_add(fraction)
+        .map(Price::from_inner)
+}
@@ -0,0 +1,153 @@
+use crate::{Call, Module, Trait};
+use codec::{Decode, Encode};
+use frame_support::{impl_outer_origin, parameter_types, weights::Weight};
+use frame_system as system;
+use parking_lot::RwLock;
+use sp_core::{
+    offchain::{testing, OffchainExt, TransactionPoolExt},
+    sr25519::{self, Signature},
+    testing::KeyStore,
+    traits::KeystoreExt,
+    H256,
+};
+use sp_runtime::{
+    testing::{Header, TestXt},
+    traits::{BlakeTwo256, Extrinsic as ExtrinsicT, IdentityLookup, Verify},
+    Perbill,
+};
+use std::sync::Arc;
+
+impl_outer_origin! {
+    pub enum Origin for Test {}
+}
+
+#[derive(Clone, Eq, PartialEq, Encode, Decode)]
+pub struct Test;
+parameter_types! {
+    pub const BlockHashCount: u64 = 250;
+    pub const MaximumBlockWeight: Weight = 1024;
+    pub const MaximumBlockLength: u32 = 2 * 1024;
+    pub const AvailableBlockRatio: Perbill = Perbill::from_percent(75);
+}
+
+pub type AccountId = sr25519::Public;
+
+impl system::Trait for Test {
+    type BaseCallFilter = ();
+    type Origin = Origin;
+    type Call = ();
+    type Index = u64;
+    type BlockNumber = u64;
+    type Hash = H256;
+    type Hashing = BlakeTwo256;
+    type AccountId = AccountId;
+    type Lookup = IdentityLookup<Self::AccountId>;
+    type Header = Header;
+    type Event = ();
+    type BlockHashCount = BlockHashCount;
+    type MaximumBlockWeight = MaximumBlockWeight;
+    type DbWeight = ();
+    type BlockExecutionWeight = ();
+    type ExtrinsicBaseWeight = ();
+    type MaximumExtrinsicWeight = MaximumBlockWeight;
+    type MaximumBlockLength = MaximumBlockLength;
+    type AvailableBlockRatio = AvailableBlockRatio;
+    type Version = ();
+    type PalletInfo = ();
+    type AccountData = ();
+    type OnNewAccount = ();
+    type OnKilledAccount = ();
+    type SystemWeightInfo = ();
+}
+
+parameter_types! {
+    pub const ExistentialDeposit: u64 = 1;
+    pub const MaxMissionTokensSupply: u128 = 1_000_000;
+}
+
+impl pallet_mission_tokens::Trait for Test {
+    type Event = ();
+    type Balance = u64;
+    type MissionTokenId = u32;
+    type ExistentialDeposit = ExistentialDeposit;
+    type OnNewAccount = ();
+    type MaxMissionTokensSupply = MaxMissionTokensSupply;
+    type MissionCreatorOrigin = system::EnsureRoot<AccountId>;
+}
+
+pub type Extrinsic = TestXt<Call<Test>, ()>;
+
+impl system::offchain::SigningTypes for Test {
+    type Public = <Signature as Verify>::Signer;
+    type Signature = Signature;
+}
+
+impl<LocalCall> system::offchain::SendTransactionTypes<LocalCall> for Test
+where
+    Call<Test>: From<LocalCall>,
+{
+    type OverarchingCall = Call<Test>;
+    type Extrinsic = Extrinsic;
+}
+
+impl<LocalCall> system::offchain::CreateSignedTransaction<LocalCall> for Test
+where
+    Call<Test>: From<LocalCall>,
+{
+    fn create_transaction<C: system::offchain::AppCrypto<Self::Public, Self::Signature>>(
+        call: Call<Test>,
+        _public: <Signature as Verify>::Signer,
+        _account: AccountId,
+        nonce: u64,
+    ) -> Option<(Call<Test>, <Extrinsic as ExtrinsicT>::SignaturePayload)> {
+        Some((call, (nonce, ())))
+    }
+}
+
+parameter_types! {
+    pub const MaxFeeders: u32 = 3;
+    pub const FeedInterval: u64 = 5;
+    pub const StalePeriod: u64 = 10;
+    pub const MinFeeds: u32 = 2;
+}
+
+impl Trait for Test {
+    type AuthorityId = crate::crypto::FeederAuthId;
+    type Event = ();
+    type Call = Call<Test>;
+    type FeederOrigin = system::EnsureRoot<AccountId>;
+    type MaxFeeders = MaxFeeders;
+    type FeedInterval = FeedInterval;
+    type StalePeriod = StalePeriod;
+    type MinFeeds = MinFeeds;
+}
+
+pub type System = system::Module<Test>;
+pub type Oracle = Module<Test>;
+
+pub const SOURCE: &str = "http://localhost:8080/prices";
+
+/// The account of feeder `n`.
+pub fn feeder(n: u8) -> AccountId {
+    sr25519::Public::from_raw([n; 32])
+}
+
+/// Externalities with a keystore, a transaction pool and an offchain extension answering the
+/// requests expected of the offchain state.
+pub fn new_test_ext() -> (
+    sp_io::TestExternalities,
+    Arc<RwLock<testing::OffchainState>>,
+    Arc<RwLock<testing::PoolState>>,
+) {
+    let (offchain, offchain_state) = testing::TestOffchainExt::new();
+    let (pool, pool_state) = testing::TestTransactionPoolExt::new();
+    let mut ext: sp_io::TestExternalities = system::GenesisConfig::default()
+        .build_storage::<Test>()
+        .unwrap()
+        .into();
+    ext.register_extension(OffchainExt::new(offchain));
+    ext.register_extension(TransactionPoolExt::new(pool));
+    ext.register_extension(KeystoreExt(KeyStore::new()));
+    ext.execute_with(|| system::Module::<Test>::set_block_number(1));
+    (ext, offchain_state, pool_state)
+}
@@ -0,0 +1,152 @@
+use crate::{mock::*, *};
+use frame_support::{assert_noop, assert_ok, traits::OffchainWorker};
+use sp_core::offchain::testing::PendingRequest;
+use sp_runtime::{DispatchError, RuntimeAppPublic};
+
+fn price(n: u128, d: u128) -> Price {
+    Price::saturating_from_rational(n, d)
+}
+
+fn add_feeders(count: u8) {
+    for n in 1..=count {
+        assert_ok!(Oracle::add_feeder(Origin::root(), feeder(n)));
+    }
+}
+
+fn feed(n: u8, token: Token<u32>, price: Price) {
+    assert_ok!(Oracle::feed_prices(Origin::signed(feeder(n)), vec![(token, price)]));
+}
+
+#[test]
+fn feeders_are_added_and_removed_by_feeder_origin() {
+    new_test_ext().0.execute_with(|| {
+        assert_noop!(Oracle::add_feeder(Origin::signed(feeder(1)), feeder(1)), DispatchError::BadOrigin);
+        add_feeders(3);
+        assert_eq!(Oracle::feeders(), vec![feeder(1), feeder(2), feeder(3)]);
+        assert_noop!(Oracle::add_feeder(Origin::root(), feeder(1)), Error::<Test>::AlreadyFeeder);
+        assert_noop!(Oracle::add_feeder(Origin::root(), feeder(4)), Error::<Test>::TooManyFeeders);
+
+        feed(2, Token::Native, price(1, 1));
+        assert_ok!(Oracle::remove_feeder(Origin::root(), feeder(2)));
+        assert_eq!(Oracle::feeders(), vec![feeder(1), feeder(3)]);
+        assert_eq!(Oracle::feeds(feeder(2), Token::Native), None);
+        assert_noop!(Oracle::remove_feeder(Origin::root(), feeder(2)), Error::<Test>::NotFeeder);
+    });
+}
+
+#[test]
+fn only_feeders_feed_valid_prices() {
+    new_test_ext().0.execute_with(|| {
+        add_feeders(1);
+        let one = price(1, 1);
+        assert_noop!(
+            Oracle::feed_prices(Origin::signed(feeder(2)), vec![(Token::Native, one)]),
+            Error::<Test>::NotFeeder
+        );
+        assert_noop!(
+            Oracle::feed_prices(Origin::signed(feeder(1)), vec![(Token::Native, Price::zero())]),
+            Error::<Test>::ZeroPrice
+        );
+        assert_noop!(
+            Oracle::feed_prices(Origin::signed(feeder(1)), vec![(Token::Mission(18), one)]),
+            pallet_mission_tokens::Error::<Test>::InvalidMissionTokenId
+        );
+        assert_noop!(
+            Oracle::feed_prices(Origin::signed(feeder(1)), vec![(Token::Native, one); 19]),
+            Error::<Test>::TooManyPrices
+        );
+
+        let info = Oracle::feed_prices(Origin::signed(feeder(1)), vec![(Token::Mission(1), one)]).unwrap();
+        assert_eq!(info.pays_fee, Pays::No);
+        assert_eq!(Oracle::feeds(feeder(1), Token::Mission(1)), Some((one, 1)));
+    });
+}
+
+#[test]
+fn price_is_median_of_fresh_feeds() {
+    new_test_ext().0.execute_with(|| {
+        add_feeders(3);
+        feed(1, Token::Native, price(1, 1));
+        // a single feed is not enough.
+        assert_eq!(Oracle::price(Token::Native), None);
+
+        feed(2, Token::Native, price(2, 1));
+        assert_eq!(Oracle::price(Token::Native), Some(price(3, 2)));
+
+        feed(3, Token::Native, price(10, 1));
+        assert_eq!(Oracle::price(Token::Native), Some(price(2, 1)));
+        assert_eq!(Oracle::price(Token::Mission(1)), None);
+
+        // the feeds of feeders 1 and 2 go stale.
+        System::set_block_number(5);
+        feed(3, Token::Native, price(4, 1));
+        System::set_block_number(12);
+        assert_eq!(Oracle::price(Token::Native), None);
+        feed(1, Token::Native, price(3, 1));
+        assert_eq!(Oracle::price(Token::Native), Some(price(7, 2)));
+    });
+}
+
+#[test]
+fn mission_tokens_are_valued_at_their_price() {
+    new_test_ext().0.execute_with(|| {
+        add_feeders(2);
+        assert_eq!(Oracle::value_of(1, 1_000), None);
+        feed(1, Token::Mission(1), price(1, 4));
+        feed(2, Token::Mission(1), price(1, 2));
+        assert_eq!(Oracle::value_of(1, 1_000), Some(375));
+        assert_eq!(Oracle::value_of(2, 1_000), None);
+    });
+}
+
+#[test]
+fn prices_are_parsed_for_known_tokens() {
+    new_test_ext().0.execute_with(|| {
+        assert_eq!(
+            Oracle::parse_prices(r#"{"native": 1.25, "1": 0.5, "17": 2, "18": 1, "usd": 1, "2": 0}"#),
+            Some(vec![
+                (Token::Native, price(5, 4)),
+                (Token::Mission(1), price(1, 2)),
+                (Token::Mission(17), price(2, 1)),
+            ])
+        );
+        assert_eq!(Oracle::parse_prices(r#"{"native": "1.25"}"#), None);
+        assert_eq!(Oracle::parse_prices(r#"[1.25]"#), None);
+        assert_eq!(to_price(0, 1, 20), Some(Price::zero()));
+        assert_eq!(to_price(0, 125, 4), Some(price(1, 80)));
+        assert_eq!(to_price(-1, 0, 0), None);
+    });
+}
+
+#[test]
+fn offchain_worker_feeds_fetched_prices() {
+    let (mut ext, offchain_state, pool_state) = new_test_ext();
+    offchain_state.write().expect_request(PendingRequest {
+        method: "GET".into(),
+        uri: SOURCE.into(),
+        response: Some(br#"{"native": 1.5, "3": 0.25}"#.to_vec()),
+        sent: true,
+        ..Default::default()
+    });
+
+    ext.execute_with(|| {
+        // nothing is fed without a key or a source.
+        Oracle::offchain_worker(5);
+        crate::crypto::Public::generate_pair(None);
+        Oracle::offchain_worker(5);
+        assert!(pool_state.read().transactions.is_empty());
+
+        sp_io::offchain::local_storage_set(StorageKind::PERSISTENT, SOURCE_KEY, SOURCE.as_bytes());
+        // not a feeding block.
+        Oracle::offchain_worker(6);
+        Oracle::offchain_worker(10);
+
+        let tx = pool_state.write().transactions.pop().unwrap();
+        assert!(pool_state.read().transactions.is_empty());
+        let tx = Extrinsic::decode(&mut &*tx).unwrap();
+        assert_eq!(
+            tx.call,
+            Call::feed_prices(vec![(Token::Native, price(3, 2)), (Token::Mission(3), price(1, 4))])
+        );
+    });
+}
@@ -182,7 +182,8 @@ impl pallet_staking::Trait for Test {
 	type SlashCancelOrigin = frame_system::EnsureRoot<Self::AccountId>;
 	type BondingDuration = ();
 	type SessionInterface = Self;
-	type RewardCurve = RewardCurve;
+	type EraPayout = pallet_staking::ConvertCurve<RewardCurve>;
+	type EraPayoutOrigin = frame_system::EnsureRoot<Self::AccountId>;
 	type NextNewSession = Session;
 	type ElectionLookahead = ();
 	type Call = Call;
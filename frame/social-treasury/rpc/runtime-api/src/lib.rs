@@ -0,0 +1,58 @@
+//! Runtime API definition for the social treasury module.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::{Codec, Decode, Encode};
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+use sp_runtime::RuntimeDebug;
+use sp_std::prelude::*;
+
+/// An index of a quadratic funding round. Just a `u32`.
+pub type RoundIndex = u32;
+
+/// An index of a project within a quadratic funding round. Just a `u32`.
+pub type ProjectIndex = u32;
+
+/// The funding of a single project of a quadratic funding round.
+#[derive(Eq, PartialEq, Encode, Decode, Clone, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct ProjectFunding<AccountId, Balance> {
+    /// The index of the project within its round.
+    pub project_index: ProjectIndex,
+    /// The account receiving the funds of the project.
+    pub beneficiary: AccountId,
+    /// The number of distinct persons who contributed to the project.
+    pub contributors: u32,
+    /// The sum of all contributions made to the project.
+    pub contributed: Balance,
+    /// The share of the matching pool the project receives on top of its contributions.
+    pub matched: Balance,
+}
+
+/// The outcome of a quadratic funding round, or its projection while it is still open.
+#[derive(Eq, PartialEq, Encode, Decode, Clone, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct RoundResults<AccountId, Balance, MissionTokenId> {
+    /// The mission whose pot funds the matching pool of the round.
+    pub mission_token_id: MissionTokenId,
+    /// The amount set aside to match contributions with.
+    pub matching_pool: Balance,
+    /// Whether the round has been finalized and the funds paid out.
+    pub finalized: bool,
+    /// The funding of every project registered in the round.
+    pub projects: Vec<ProjectFunding<AccountId, Balance>>,
+}
+
+sp_api::decl_runtime_apis! {
+    pub trait SocialTreasuryApi<AccountId, Balance, MissionTokenId> where
+        AccountId: Codec,
+        Balance: Codec,
+        MissionTokenId: Codec,
+    {
+        /// The results of the quadratic funding round `round`, if it exists.
+        fn round_results(round: RoundIndex) -> Option<RoundResults<AccountId, Balance, MissionTokenId>>;
+    }
+}
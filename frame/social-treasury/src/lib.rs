@@ -1,6 +1,7 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 use codec::{Decode, Encode};
+use frame_support::traits::schedule::{DispatchTime, Named as ScheduleNamed};
 use frame_support::dispatch::DispatchResultWithPostInfo;
 use frame_support::traits::{Contains, ContainsLengthBound, EnsureOrigin};
 use frame_support::traits::{
@@ -9,14 +10,17 @@ use frame_support::traits::{
 };
 use frame_support::weights::{DispatchClass, Weight};
 use frame_support::{decl_error, decl_event, decl_module, decl_storage, ensure, print, Parameter};
-use frame_system::{self as system, ensure_signed};
+use frame_system::{self as system, ensure_root, ensure_signed};
+use pallet_mission_tokens::{MissionTokenValue, StreamPayments};
+use pallet_social_treasury_rpc_runtime_api::{ProjectFunding, RoundResults};
+pub use pallet_social_treasury_rpc_runtime_api::{ProjectIndex, RoundIndex};
 use pallet_staking::EraIndex;
 #[cfg(feature = "std")]
 use serde::{Deserialize, Serialize};
 use sp_runtime::traits::UniqueSaturatedInto;
 use sp_runtime::{
-    traits::{BadOrigin, Hash, Saturating, StaticLookup, Zero},
-    DispatchResult, Percent, Permill, RuntimeDebug,
+    traits::{AccountIdConversion, BadOrigin, Convert, Hash, IntegerSquareRoot, One, Saturating, StaticLookup, Zero},
+    DispatchError, DispatchResult, Perbill, Percent, Permill, RuntimeDebug,
 };
 use sp_std::prelude::*;
 
@@ -69,6 +73,14 @@ pub trait Trait:
     /// Origin from which rejections must come.
     type RejectOrigin: EnsureOrigin<Self::Origin>;
 
+    /// Origin from which approvals concerning the pot of a single mission may come. Returns the
+    /// mission token id it may act on.
+    type MissionApproveOrigin: EnsureOrigin<Self::Origin, Success = TokenId<Self>>;
+
+    /// Origin from which rejections concerning the pot of a single mission may come. Returns the
+    /// mission token id it may act on.
+    type MissionRejectOrigin: EnsureOrigin<Self::Origin, Success = TokenId<Self>>;
+
     /// Origin from which tippers must come.
     ///
     /// `ContainsLengthBound::max_len` must be cost free (i.e. no storage read or heavy operation).
@@ -128,6 +140,78 @@ pub trait Trait:
 
     /// Weight information for extrinsics in this pallet.
     type WeightInfo: WeightInfo;
+
+    /// Pays approved proposals out over time when they ask for it.
+    type Streams: StreamPayments<Self::AccountId, TokenId<Self>, BalanceOf<Self>, Self::BlockNumber>;
+
+    /// Convert the block number into a balance.
+    type BlockNumberToBalance: Convert<Self::BlockNumber, BalanceOf<Self>>;
+
+    /// Tells which accounts belong to distinct persons, so that the contributions to a
+    /// quadratic funding round can be weighted one per person.
+    type UniquePersons: UniquePersons<Self::AccountId>;
+
+    /// The maximum number of projects that can be registered in a quadratic funding round.
+    type MaxRoundProjects: Get<u32>;
+
+    /// The maximum number of distinct persons who can contribute to a project of a quadratic
+    /// funding round.
+    type MaxProjectContributors: Get<u32>;
+
+    /// The minimum contribution that can be made to a project of a quadratic funding round.
+    type MinimumContribution: Get<BalanceOf<Self>>;
+
+    /// The call scheduled to release the tranche of an approved milestone.
+    type GrantCall: From<Call<Self>>;
+
+    /// The scheduler releasing the tranches of approved milestones.
+    type Scheduler: ScheduleNamed<Self::BlockNumber, Self::GrantCall, Self::PalletsOrigin>;
+
+    /// Overarching type of all pallets origins.
+    type PalletsOrigin: From<system::RawOrigin<Self::AccountId>>;
+
+    /// The number of blocks between the approval of a milestone and the release of its tranche.
+    type GrantReleaseDelay: Get<Self::BlockNumber>;
+
+    /// Maximum number of milestones in a grant.
+    type MaxMilestones: Get<u32>;
+
+    /// Tells the worth of mission tokens in a reference unit.
+    type TokenValue: MissionTokenValue<TokenId<Self>, BalanceOf<Self>>;
+
+    /// Maximum worth of a spend proposal, a bounty or a grant, in the reference unit of
+    /// `TokenValue`.
+    ///
+    /// The cap fails open: proposals in mission tokens of unknown worth, e.g. while the oracle
+    /// behind `TokenValue` has too few fresh prices, are not capped. They still need the approval
+    /// of the council, and failing closed would halt all spending for as long as prices are
+    /// unknown.
+    type MaxProposalValue: Get<BalanceOf<Self>>;
+}
+
+/// Something that can tell whether an account belongs to a verified, unique person.
+pub trait UniquePersons<AccountId> {
+    /// Whether `who` is known to be controlled by a person who holds no other such account.
+    fn is_unique_person(who: &AccountId) -> bool;
+}
+
+impl<AccountId> UniquePersons<AccountId> for () {
+    fn is_unique_person(_who: &AccountId) -> bool {
+        false
+    }
+}
+
+// A value placed in storage that represents the current version of the SocialTreasury storage.
+// This value is used by storage migrations to determine whether they need to run.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+enum Releases {
+    V1_0_0,
+}
+
+impl Default for Releases {
+    fn default() -> Self {
+        Releases::V1_0_0
+    }
 }
 
 /// An index of a proposal. Just a `u32`.
@@ -148,6 +232,101 @@ pub struct Proposal<AccountId, Balance, MissionTokenId> {
     mission_token_id: MissionTokenId,
 }
 
+/// How an approved proposal is paid out over time instead of in one lump.
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub struct StreamTerms<BlockNumber> {
+    /// The number of blocks over which the value of the proposal vests.
+    duration: BlockNumber,
+    /// The number of blocks after the award before the beneficiary can withdraw anything.
+    cliff: BlockNumber,
+}
+
+/// A quadratic funding round, matching the contributions made to the projects of a mission
+/// with funds from the pot of that mission.
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct FundingRound<Balance, BlockNumber, MissionTokenId> {
+    /// The mission whose pot funds the matching pool.
+    mission_token_id: MissionTokenId,
+    /// The amount set aside from the pot to match contributions with.
+    matching_pool: Balance,
+    /// The block from which no more contributions are accepted and the round can be finalized.
+    end: BlockNumber,
+    /// The number of projects registered in the round.
+    project_count: ProjectIndex,
+    /// Whether the funds of the round have been paid out.
+    finalized: bool,
+}
+
+/// A project taking part in a quadratic funding round.
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct RoundProject<AccountId, Balance> {
+    /// The account receiving the funds of the project.
+    beneficiary: AccountId,
+    /// The number of distinct persons who contributed to the project.
+    contributors: u32,
+    /// The sum of all contributions.
+    contributed: Balance,
+    /// The sum of the square roots of the total contribution of each person.
+    sqrt_sum: Balance,
+}
+
+/// An index of a milestone-based grant. Just a `u32`.
+pub type GrantIndex = u32;
+
+/// The identifier under which the releases of milestone tranches are scheduled.
+const GRANT_ID: [u8; 8] = *b"py/grant";
+
+/// A milestone of a grant, whose tranche is released once the evidence of its completion has
+/// been approved.
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct Milestone<Balance, Hash> {
+    /// The amount released when the milestone is completed.
+    value: Balance,
+    /// The hash of the description of the milestone.
+    description: Hash,
+    /// The hash of the evidence of completion last submitted by the beneficiary.
+    evidence: Option<Hash>,
+    /// The status of this milestone.
+    status: MilestoneStatus,
+}
+
+/// The status of a milestone of a grant.
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum MilestoneStatus {
+    /// Waiting for the beneficiary to submit evidence of its completion.
+    Pending,
+    /// Evidence of completion has been submitted and is waiting for approval.
+    Submitted,
+    /// The evidence has been approved and the release of the tranche is scheduled.
+    Approved,
+    /// The tranche has been paid to the beneficiary.
+    Released,
+}
+
+/// A grant paid out in tranches, one for each of its milestones.
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct Grant<AccountId, Balance, Hash, MissionTokenId> {
+    /// The account proposing it.
+    proposer: AccountId,
+    /// The account to whom the tranches are paid.
+    beneficiary: AccountId,
+    /// The account that may approve milestones besides the approval origins, if any.
+    curator: Option<AccountId>,
+    /// The amount held on deposit (reserved) for making this proposal.
+    bond: Balance,
+    mission_token_id: MissionTokenId,
+    /// Whether the grant has been approved and its value set aside from the pot.
+    active: bool,
+    /// The milestones of the grant, in the order they are meant to be completed.
+    milestones: Vec<Milestone<Balance, Hash>>,
+}
+
 /// An open tipping "motion". Retains all details of a tip including information on the finder
 /// and the members who have voted.
 #[derive(Clone, Eq, PartialEq, Encode, Decode, RuntimeDebug)]
@@ -246,6 +425,10 @@ decl_storage! {
         /// Proposal indices that have been approved but not yet awarded.
         Approvals get(fn approvals): Vec<ProposalIndex>;
 
+        /// How proposals that asked to be paid out over time are streamed once awarded.
+        pub ProposalStreams get(fn proposal_streams):
+            map hasher(twox_64_concat) ProposalIndex => Option<StreamTerms<T::BlockNumber>>;
+
         /// Tips that are not yet completed. Keyed by the hash of `(reason, who)` from the value.
         /// This has the insecure enumerable hash function since the key itself is already
         /// guaranteed to be a secure hash.
@@ -270,6 +453,37 @@ decl_storage! {
 
         /// Bounty indices that have been approved but not yet funded.
         pub BountyApprovals get(fn bounty_approvals): Vec<BountyIndex>;
+
+        /// Number of quadratic funding rounds that have been started.
+        pub RoundCount get(fn round_count): RoundIndex;
+
+        /// Quadratic funding rounds that have been started.
+        pub Rounds get(fn rounds):
+            map hasher(twox_64_concat) RoundIndex
+            => Option<FundingRound<BalanceOf<T>, T::BlockNumber, TokenId<T>>>;
+
+        /// The projects registered in each quadratic funding round.
+        pub RoundProjects get(fn round_projects):
+            double_map hasher(twox_64_concat) RoundIndex, hasher(twox_64_concat) ProjectIndex
+            => Option<RoundProject<T::AccountId, BalanceOf<T>>>;
+
+        /// Number of grants that have been proposed.
+        pub GrantCount get(fn grant_count): GrantIndex;
+
+        /// Grants that have been proposed and are not yet completed or cancelled.
+        pub Grants get(fn grants):
+            map hasher(twox_64_concat) GrantIndex
+            => Option<Grant<T::AccountId, BalanceOf<T>, T::Hash, TokenId<T>>>;
+
+        /// The total contribution of each person to a project of an open quadratic funding round.
+        pub Contributions get(fn contributions):
+            double_map hasher(twox_64_concat) (RoundIndex, ProjectIndex), hasher(blake2_128_concat) T::AccountId
+            => BalanceOf<T>;
+
+        /// Storage version of the pallet.
+        ///
+        /// New networks start with the latest version.
+        StorageVersion build(|_| Releases::V1_0_0): Releases;
     }
 }
 
@@ -292,6 +506,14 @@ decl_event!(
             MissionTokenBalance,
             AccountId,
         ),
+        /// Some funds have been allocated as a stream to the beneficiary.
+        /// \[proposal_index, award, beneficiary\]
+        AwardedAsStream(
+            ProposalIndex,
+            MissionTokenId,
+            MissionTokenBalance,
+            AccountId,
+        ),
         /// A proposal was rejected; funds were slashed. \[proposal_index, slashed\]
         Rejected(ProposalIndex, MissionTokenId, MissionTokenBalance),
         /// Some of our funds have been burnt. \[burn\]
@@ -323,6 +545,36 @@ decl_event!(
         BountyCanceled(BountyIndex),
         /// A bounty expiry is extended. [index]
         BountyExtended(BountyIndex),
+        /// A quadratic funding round has started.
+        /// \[round_index, mission_token_id, matching_pool\]
+        RoundStarted(RoundIndex, MissionTokenId, MissionTokenBalance),
+        /// A project has been registered in a funding round. \[round_index, project_index, beneficiary\]
+        ProjectRegistered(RoundIndex, ProjectIndex, AccountId),
+        /// A person contributed to a project. \[round_index, project_index, who, value\]
+        Contributed(RoundIndex, ProjectIndex, AccountId, MissionTokenBalance),
+        /// A project has been paid its contributions and its share of the matching pool.
+        /// \[round_index, project_index, beneficiary, contributed, matched\]
+        ProjectFunded(RoundIndex, ProjectIndex, AccountId, MissionTokenBalance, MissionTokenBalance),
+        /// A funding round has been finalized; the unused funds went back to the pot.
+        /// \[round_index, unused\]
+        RoundFinalized(RoundIndex, MissionTokenBalance),
+        /// New milestone-based grant proposal. \[grant_index\]
+        GrantProposed(GrantIndex),
+        /// A grant has been approved and its value set aside from the pot. \[grant_index, value\]
+        GrantApproved(GrantIndex, MissionTokenId, MissionTokenBalance),
+        /// Evidence of the completion of a milestone has been submitted.
+        /// \[grant_index, milestone, evidence\]
+        MilestoneSubmitted(GrantIndex, u32, Hash),
+        /// A milestone has been approved and the release of its tranche scheduled.
+        /// \[grant_index, milestone\]
+        MilestoneApproved(GrantIndex, u32),
+        /// The tranche of a milestone has been paid. \[grant_index, milestone, beneficiary, value\]
+        MilestoneReleased(GrantIndex, u32, AccountId, MissionTokenBalance),
+        /// Every milestone of a grant has been released. \[grant_index\]
+        GrantCompleted(GrantIndex),
+        /// A grant has been cancelled; its unreleased funds went back to the pot.
+        /// \[grant_index, returned\]
+        GrantCancelled(GrantIndex, MissionTokenId, MissionTokenBalance),
     }
 );
 
@@ -360,6 +612,41 @@ decl_error! {
         /// A bounty payout is pending.
         /// To cancel the bounty, you must unassign and slash the curator.
         PendingPayout,
+        /// A stream must last at least one block and its cliff must not be after its end.
+        InvalidStreamTerms,
+        /// The origin may only act on the pot of another mission.
+        WrongMission,
+        /// No funding round at that index.
+        UnknownRound,
+        /// No project at that index in the funding round.
+        UnknownProject,
+        /// A funding round must last at least one block.
+        InvalidRoundDuration,
+        /// The funding round no longer accepts projects or contributions.
+        RoundClosed,
+        /// The funding round is still accepting contributions.
+        RoundNotEnded,
+        /// The funding round has already been paid out.
+        RoundAlreadyFinalized,
+        /// The funding round has as many projects as it can hold.
+        TooManyProjects,
+        /// The project has as many contributors as it can hold.
+        TooManyContributors,
+        /// The contribution is below the minimum.
+        ContributionTooLow,
+        /// Only verified, unique persons may do this.
+        NotUniquePerson,
+        /// A grant must have at least one milestone, no more than `MaxMilestones`, and none of
+        /// them may be worthless.
+        InvalidMilestones,
+        /// No milestone at that index in the grant.
+        UnknownMilestone,
+        /// Require grant beneficiary.
+        RequireBeneficiary,
+        /// The release of the tranche could not be scheduled.
+        ScheduleFailed,
+        /// The proposal is worth more than `MaxProposalValue`.
+        ProposalValueTooHigh,
     }
 }
 
@@ -371,7 +658,7 @@ decl_module! {
 
         /// Put forward a suggestion for spending. A deposit proportional to the value
         /// is reserved and slashed if the proposal is rejected. It is returned once the
-        /// proposal is awarded.
+        /// proposal is awarded. The value may be worth no more than `MaxProposalValue`.
         ///
         /// # <weight>
         /// - Complexity: O(1)
@@ -387,22 +674,40 @@ decl_module! {
         ) {
             let proposer = ensure_signed(origin)?;
             let beneficiary = T::Lookup::lookup(beneficiary)?;
-            <pallet_mission_tokens::Module<T>>::validate_mission_token_id(token_id)?;
-
-            let bond = Self::calculate_bond(value);
-            <pallet_mission_tokens::Module<T>>::reserve(&proposer, token_id, bond)
-                .map_err(|_| Error::<T>::InsufficientProposersBalance)?;
+            Self::do_propose_spend(proposer, value, beneficiary, token_id)?;
+        }
 
-            let c = Self::proposal_count();
-            <ProposalCount>::put(c + 1);
-            <Proposals<T>>::insert(c, Proposal { proposer, value, beneficiary, bond, mission_token_id: token_id });
+        /// Put forward a suggestion for spending that is paid out as a stream of mission tokens
+        /// vesting over `duration` blocks, nothing of which can be withdrawn during the first
+        /// `cliff` blocks. The deposit is handled as in `propose_spend`.
+        ///
+        /// # <weight>
+        /// - Complexity: O(1)
+        /// - DbReads: `ProposalCount`, `origin account`
+        /// - DbWrites: `ProposalCount`, `Proposals`, `ProposalStreams`, `origin account`
+        /// # </weight>
+        #[weight = <T as Trait>::WeightInfo::propose_spend()
+            .saturating_add(T::DbWeight::get().writes(1))]
+        fn propose_streamed_spend(
+            origin,
+            #[compact] value: BalanceOf<T>,
+            beneficiary: <T::Lookup as StaticLookup>::Source,
+            token_id: TokenId<T>,
+            duration: T::BlockNumber,
+            cliff: T::BlockNumber,
+        ) {
+            let proposer = ensure_signed(origin)?;
+            let beneficiary = T::Lookup::lookup(beneficiary)?;
+            ensure!(!duration.is_zero() && cliff <= duration, Error::<T>::InvalidStreamTerms);
 
-            Self::deposit_event(RawEvent::Proposed(c));
+            let index = Self::do_propose_spend(proposer, value, beneficiary, token_id)?;
+            <ProposalStreams<T>>::insert(index, StreamTerms { duration, cliff });
         }
 
         /// Reject a proposed spend. The original deposit will be slashed.
         ///
-        /// May only be called from `T::RejectOrigin`.
+        /// May only be called from `T::RejectOrigin`, or from `T::MissionRejectOrigin` of the
+        /// mission of the proposal.
         ///
         /// # <weight>
         /// - Complexity: O(1)
@@ -411,9 +716,10 @@ decl_module! {
         /// # </weight>
         #[weight = (<T as Trait>::WeightInfo::reject_proposal(), DispatchClass::Operational)]
         fn reject_proposal(origin, #[compact] proposal_id: ProposalIndex) {
-            <T as Trait>::RejectOrigin::ensure_origin(origin)?;
-
-            let proposal = <Proposals<T>>::take(&proposal_id).ok_or(Error::<T>::InvalidIndex)?;
+            let proposal = <Proposals<T>>::get(&proposal_id).ok_or(Error::<T>::InvalidIndex)?;
+            Self::ensure_reject_origin(origin, proposal.mission_token_id)?;
+            <Proposals<T>>::remove(&proposal_id);
+            <ProposalStreams<T>>::remove(&proposal_id);
             let value = proposal.bond;
             let imbalance = <pallet_mission_tokens::Module<T>>::slash_reserved(
                 &proposal.proposer,
@@ -428,7 +734,8 @@ decl_module! {
         /// Approve a proposal. At a later time, the proposal will be allocated to the beneficiary
         /// and the original deposit will be returned.
         ///
-        /// May only be called from `T::ApproveOrigin`.
+        /// May only be called from `T::ApproveOrigin`, or from `T::MissionApproveOrigin` of the
+        /// mission of the proposal.
         ///
         /// # <weight>
         /// - Complexity: O(1).
@@ -437,9 +744,9 @@ decl_module! {
         /// # </weight>
         #[weight = (<T as Trait>::WeightInfo::approve_proposal(), DispatchClass::Operational)]
         fn approve_proposal(origin, #[compact] proposal_id: ProposalIndex) {
-            <T as Trait>::ApproveOrigin::ensure_origin(origin)?;
+            let proposal = <Proposals<T>>::get(proposal_id).ok_or(Error::<T>::InvalidIndex)?;
+            Self::ensure_approve_origin(origin, proposal.mission_token_id)?;
 
-            ensure!(<Proposals<T>>::contains_key(proposal_id), Error::<T>::InvalidIndex);
             Approvals::append(proposal_id);
         }
 
@@ -650,7 +957,8 @@ decl_module! {
         ///
         /// - `curator`: The curator account whom will manage this bounty.
         /// - `fee`: The curator fee.
-        /// - `value`: The total payment amount of this bounty, curator fee included.
+        /// - `value`: The total payment amount of this bounty, curator fee included. It may be
+        ///   worth no more than `MaxProposalValue`.
         /// - `description`: The description of this bounty.
         #[weight = <T as Trait>::WeightInfo::propose_bounty(description.len() as u32)]
         fn propose_bounty(
@@ -667,7 +975,8 @@ decl_module! {
         /// Approve a bounty proposal. At a later time, the bounty will be funded and become active
         /// and the original deposit will be returned.
         ///
-        /// May only be called from `T::ApproveOrigin`.
+        /// May only be called from `T::ApproveOrigin`, or from `T::MissionApproveOrigin` of the
+        /// mission of the bounty.
         ///
         /// # <weight>
         /// - O(1).
@@ -676,7 +985,7 @@ decl_module! {
         /// # </weight>
         #[weight = <T as Trait>::WeightInfo::approve_bounty()]
         fn approve_bounty(origin, #[compact] bounty_id: ProposalIndex) {
-            <T as Trait>::ApproveOrigin::ensure_origin(origin)?;
+            Self::ensure_approve_origin(origin, Self::bounty_mission(bounty_id)?)?;
 
             Bounties::<T>::try_mutate_exists(bounty_id, |maybe_bounty| -> DispatchResult {
                 let mut bounty = maybe_bounty.as_mut().ok_or(Error::<T>::InvalidIndex)?;
@@ -692,7 +1001,8 @@ decl_module! {
 
         /// Assign a curator to a funded bounty.
         ///
-        /// May only be called from `T::ApproveOrigin`.
+        /// May only be called from `T::ApproveOrigin`, or from `T::MissionApproveOrigin` of the
+        /// mission of the bounty.
         ///
         /// # <weight>
         /// - O(1).
@@ -706,7 +1016,7 @@ decl_module! {
             curator: <T::Lookup as StaticLookup>::Source,
             #[compact] fee: BalanceOf<T>,
         ) {
-            <T as Trait>::ApproveOrigin::ensure_origin(origin)?;
+            Self::ensure_approve_origin(origin, Self::bounty_mission(bounty_id)?)?;
 
             let curator = T::Lookup::lookup(curator)?;
             Bounties::<T>::try_mutate_exists(bounty_id, |maybe_bounty| -> DispatchResult {
@@ -727,9 +1037,10 @@ decl_module! {
 
         /// Unassign curator from a bounty.
         ///
-        /// This function can only be called by the `RejectOrigin` a signed origin.
+        /// This function can only be called by the `RejectOrigin`, the `MissionRejectOrigin` of the
+        /// mission of the bounty or a signed origin.
         ///
-        /// If this function is called by the `RejectOrigin`, we assume that the curator is malicious
+        /// If this function is called by a reject origin, we assume that the curator is malicious
         /// or inactive. As a result, we will slash the curator when possible.
         ///
         /// If the origin is the curator, we take this as a sign they are unable to do their job and
@@ -750,9 +1061,10 @@ decl_module! {
             origin,
             #[compact] bounty_id: ProposalIndex,
         ) {
+            let mission_token_id = Self::bounty_mission(bounty_id)?;
             let maybe_sender = ensure_signed(origin.clone())
                 .map(Some)
-                .or_else(|_| <T as Trait>::RejectOrigin::ensure_origin(origin).map(|_| None))?;
+                .or_else(|_| Self::ensure_reject_origin(origin, mission_token_id).map(|_| None))?;
 
             Bounties::<T>::try_mutate_exists(bounty_id, |maybe_bounty| -> DispatchResult {
                 let mut bounty = maybe_bounty.as_mut().ok_or(Error::<T>::InvalidIndex)?;
@@ -922,12 +1234,13 @@ decl_module! {
         /// Cancel a proposed or active bounty. All the funds will be sent to treasury and
         /// the curator deposit will be unreserved if possible.
         ///
-        /// Only `T::RejectOrigin` is able to cancel a bounty.
+        /// Only `T::RejectOrigin` or `T::MissionRejectOrigin` of the mission of the bounty is able
+        /// to cancel a bounty.
         ///
         /// - `bounty_id`: Bounty ID to cancel.
         #[weight = <T as Trait>::WeightInfo::close_bounty_proposed().max(<T as Trait>::WeightInfo::close_bounty_active())]
         fn close_bounty(origin, #[compact] bounty_id: BountyIndex) -> DispatchResultWithPostInfo {
-            <T as Trait>::RejectOrigin::ensure_origin(origin)?;
+            Self::ensure_reject_origin(origin, Self::bounty_mission(bounty_id)?)?;
 
             Bounties::<T>::try_mutate_exists(bounty_id, |maybe_bounty| -> DispatchResultWithPostInfo {
                 let bounty = maybe_bounty.as_ref().ok_or(Error::<T>::InvalidIndex)?;
@@ -1015,6 +1328,504 @@ decl_module! {
             Self::deposit_event(Event::<T>::BountyExtended(bounty_id));
         }
 
+        /// Put forward a milestone-based grant, paid to `beneficiary` in one tranche per
+        /// milestone. Each milestone is given as its value and the hash of its description. A
+        /// deposit proportional to the total value is reserved and slashed if the grant is
+        /// rejected. It is returned once the grant is approved. The total value may be worth no
+        /// more than `MaxProposalValue`.
+        ///
+        /// # <weight>
+        /// - Complexity: O(M) where M is the number of milestones, bounded by `MaxMilestones`
+        /// - DbReads: `GrantCount`, `origin account`
+        /// - DbWrites: `GrantCount`, `Grants`, `origin account`
+        /// # </weight>
+        #[weight = 50_000_000 + T::DbWeight::get().reads_writes(2, 3)]
+        fn propose_grant(
+            origin,
+            beneficiary: <T::Lookup as StaticLookup>::Source,
+            token_id: TokenId<T>,
+            milestones: Vec<(BalanceOf<T>, T::Hash)>,
+        ) {
+            let proposer = ensure_signed(origin)?;
+            let beneficiary = T::Lookup::lookup(beneficiary)?;
+            <pallet_mission_tokens::Module<T>>::validate_mission_token_id(token_id)?;
+            ensure!(
+                !milestones.is_empty()
+                    && milestones.len() <= T::MaxMilestones::get() as usize
+                    && milestones.iter().all(|(value, _)| !value.is_zero()),
+                Error::<T>::InvalidMilestones
+            );
+
+            let total = milestones
+                .iter()
+                .fold(BalanceOf::<T>::zero(), |total, (value, _)| total.saturating_add(*value));
+            Self::ensure_within_cap(token_id, total)?;
+            let bond = Self::calculate_bond(total);
+            <pallet_mission_tokens::Module<T>>::reserve(&proposer, token_id, bond)
+                .map_err(|_| Error::<T>::InsufficientProposersBalance)?;
+
+            let index = Self::grant_count();
+            GrantCount::put(index + 1);
+            <Grants<T>>::insert(index, Grant {
+                proposer,
+                beneficiary,
+                curator: None,
+                bond,
+                mission_token_id: token_id,
+                active: false,
+                milestones: milestones
+                    .into_iter()
+                    .map(|(value, description)| Milestone {
+                        value,
+                        description,
+                        evidence: None,
+                        status: MilestoneStatus::Pending,
+                    })
+                    .collect(),
+            });
+
+            Self::deposit_event(RawEvent::GrantProposed(index));
+        }
+
+        /// Approve a grant, setting its total value aside from the pot of its mission and
+        /// returning the deposit of the proposer. The milestones of the grant may then be
+        /// approved by the approval origins or by `curator`, if given.
+        ///
+        /// May only be called from `T::ApproveOrigin`, or from `T::MissionApproveOrigin` of the
+        /// mission of the grant.
+        ///
+        /// # <weight>
+        /// - Complexity: O(M) where M is the number of milestones, bounded by `MaxMilestones`
+        /// - DbReads: `Grants`, `pot account data`, `grant account data`, `proposer account`
+        /// - DbWrites: `Grants`, `pot account data`, `grant account data`, `proposer account`
+        /// # </weight>
+        #[weight = 50_000_000 + T::DbWeight::get().reads_writes(4, 4)]
+        fn approve_grant(
+            origin,
+            #[compact] grant_id: GrantIndex,
+            curator: Option<<T::Lookup as StaticLookup>::Source>,
+        ) {
+            let mut grant = Self::grants(grant_id).ok_or(Error::<T>::InvalidIndex)?;
+            Self::ensure_approve_origin(origin, grant.mission_token_id)?;
+            ensure!(!grant.active, Error::<T>::UnexpectedStatus);
+            let curator = curator.map(T::Lookup::lookup).transpose()?;
+
+            let value = grant
+                .milestones
+                .iter()
+                .fold(BalanceOf::<T>::zero(), |total, m| total.saturating_add(m.value));
+            <pallet_mission_tokens::Module<T>>::do_transfer(
+                &Self::account_id(),
+                &Self::grant_account_id(grant_id),
+                grant.mission_token_id,
+                value,
+                KeepAlive,
+            )?;
+            let _ = <pallet_mission_tokens::Module<T>>::unreserve(
+                &grant.proposer,
+                grant.mission_token_id,
+                grant.bond,
+            );
+
+            grant.active = true;
+            grant.curator = curator;
+            let token_id = grant.mission_token_id;
+            <Grants<T>>::insert(grant_id, grant);
+
+            Self::deposit_event(RawEvent::GrantApproved(grant_id, token_id, value));
+        }
+
+        /// Submit the hash of the evidence that a milestone of an approved grant is completed,
+        /// replacing any evidence submitted before for it.
+        ///
+        /// The dispatch origin for this call must be the beneficiary of the grant.
+        ///
+        /// # <weight>
+        /// - Complexity: O(M) where M is the number of milestones, bounded by `MaxMilestones`
+        /// - DbReads: `Grants`
+        /// - DbWrites: `Grants`
+        /// # </weight>
+        #[weight = 30_000_000 + T::DbWeight::get().reads_writes(1, 1)]
+        fn submit_milestone(
+            origin,
+            #[compact] grant_id: GrantIndex,
+            #[compact] milestone: u32,
+            evidence: T::Hash,
+        ) {
+            let signer = ensure_signed(origin)?;
+
+            Grants::<T>::try_mutate(grant_id, |maybe_grant| -> DispatchResult {
+                let grant = maybe_grant.as_mut().ok_or(Error::<T>::InvalidIndex)?;
+                ensure!(grant.beneficiary == signer, Error::<T>::RequireBeneficiary);
+                ensure!(grant.active, Error::<T>::UnexpectedStatus);
+
+                let m = grant.milestones
+                    .get_mut(milestone as usize)
+                    .ok_or(Error::<T>::UnknownMilestone)?;
+                match m.status {
+                    MilestoneStatus::Pending | MilestoneStatus::Submitted => {
+                        m.evidence = Some(evidence);
+                        m.status = MilestoneStatus::Submitted;
+                    },
+                    _ => return Err(Error::<T>::UnexpectedStatus.into()),
+                }
+
+                Ok(())
+            })?;
+
+            Self::deposit_event(RawEvent::MilestoneSubmitted(grant_id, milestone, evidence));
+        }
+
+        /// Approve the evidence submitted for a milestone, scheduling the release of its tranche
+        /// after `GrantReleaseDelay`.
+        ///
+        /// May only be called by the curator of the grant, from `T::ApproveOrigin`, or from
+        /// `T::MissionApproveOrigin` of the mission of the grant.
+        ///
+        /// # <weight>
+        /// - Complexity: O(M) where M is the number of milestones, bounded by `MaxMilestones`
+        /// - DbReads: `Grants`, `Agenda`, `Lookup`
+        /// - DbWrites: `Grants`, `Agenda`, `Lookup`
+        /// # </weight>
+        #[weight = 50_000_000 + T::DbWeight::get().reads_writes(3, 3)]
+        fn approve_milestone(origin, #[compact] grant_id: GrantIndex, #[compact] milestone: u32) {
+            let mut grant = Self::grants(grant_id).ok_or(Error::<T>::InvalidIndex)?;
+            let is_curator = ensure_signed(origin.clone())
+                .map_or(false, |who| grant.curator.as_ref() == Some(&who));
+            if !is_curator {
+                Self::ensure_approve_origin(origin, grant.mission_token_id)?;
+            }
+            ensure!(grant.active, Error::<T>::UnexpectedStatus);
+
+            let m = grant.milestones
+                .get_mut(milestone as usize)
+                .ok_or(Error::<T>::UnknownMilestone)?;
+            ensure!(m.status == MilestoneStatus::Submitted, Error::<T>::UnexpectedStatus);
+
+            T::Scheduler::schedule_named(
+                (GRANT_ID, grant_id, milestone).encode(),
+                DispatchTime::After(T::GrantReleaseDelay::get()),
+                None,
+                63,
+                system::RawOrigin::Root.into(),
+                Call::<T>::release_milestone(grant_id, milestone).into(),
+            ).map_err(|_| Error::<T>::ScheduleFailed)?;
+
+            m.status = MilestoneStatus::Approved;
+            <Grants<T>>::insert(grant_id, grant);
+
+            Self::deposit_event(RawEvent::MilestoneApproved(grant_id, milestone));
+        }
+
+        /// Pay the tranche of an approved milestone to the beneficiary of the grant. The grant
+        /// is completed once all of its milestones are released.
+        ///
+        /// May only be called from root; it is scheduled by `approve_milestone`.
+        ///
+        /// # <weight>
+        /// - Complexity: O(M) where M is the number of milestones, bounded by `MaxMilestones`
+        /// - DbReads: `Grants`, `grant account data`, `beneficiary account data`
+        /// - DbWrites: `Grants`, `grant account data`, `beneficiary account data`
+        /// # </weight>
+        #[weight = 50_000_000 + T::DbWeight::get().reads_writes(3, 3)]
+        fn release_milestone(origin, #[compact] grant_id: GrantIndex, #[compact] milestone: u32) {
+            ensure_root(origin)?;
+
+            let mut grant = Self::grants(grant_id).ok_or(Error::<T>::InvalidIndex)?;
+            let grant_account = Self::grant_account_id(grant_id);
+            let m = grant.milestones
+                .get_mut(milestone as usize)
+                .ok_or(Error::<T>::UnknownMilestone)?;
+            ensure!(m.status == MilestoneStatus::Approved, Error::<T>::UnexpectedStatus);
+
+            <pallet_mission_tokens::Module<T>>::do_transfer(
+                &grant_account,
+                &grant.beneficiary,
+                grant.mission_token_id,
+                m.value,
+                AllowDeath,
+            )?;
+            m.status = MilestoneStatus::Released;
+            let value = m.value;
+
+            Self::deposit_event(RawEvent::MilestoneReleased(
+                grant_id,
+                milestone,
+                grant.beneficiary.clone(),
+                value,
+            ));
+
+            if grant.milestones.iter().all(|m| m.status == MilestoneStatus::Released) {
+                <Grants<T>>::remove(grant_id);
+                Self::deposit_event(RawEvent::GrantCompleted(grant_id));
+            } else {
+                <Grants<T>>::insert(grant_id, grant);
+            }
+        }
+
+        /// Cancel a grant. The deposit of a grant that was not approved yet is slashed. The
+        /// tranches of an approved grant that were not released yet go back to the pot, even
+        /// those of milestones already approved.
+        ///
+        /// May only be called from `T::RejectOrigin`, or from `T::MissionRejectOrigin` of the
+        /// mission of the grant.
+        ///
+        /// # <weight>
+        /// - Complexity: O(M) where M is the number of milestones, bounded by `MaxMilestones`
+        /// - DbReads: `Grants`, `grant account data`, `pot account data`, per approved milestone:
+        ///   `Agenda`, `Lookup`
+        /// - DbWrites: `Grants`, `grant account data`, `pot account data`, per approved milestone:
+        ///   `Agenda`, `Lookup`
+        /// # </weight>
+        #[weight = 50_000_000
+            + (T::MaxMilestones::get() as Weight).saturating_mul(20_000_000)
+            + T::DbWeight::get().reads_writes(
+                3 + 2 * T::MaxMilestones::get() as Weight,
+                3 + 2 * T::MaxMilestones::get() as Weight,
+            )]
+        fn cancel_grant(origin, #[compact] grant_id: GrantIndex) {
+            let grant = Self::grants(grant_id).ok_or(Error::<T>::InvalidIndex)?;
+            let token_id = grant.mission_token_id;
+            Self::ensure_reject_origin(origin, token_id)?;
+            <Grants<T>>::remove(grant_id);
+
+            if !grant.active {
+                let imbalance = <pallet_mission_tokens::Module<T>>::slash_reserved(
+                    &grant.proposer,
+                    token_id,
+                    grant.bond,
+                ).0;
+                <T as Trait>::OnSlash::on_unbalanced(imbalance);
+                Self::deposit_event(RawEvent::GrantCancelled(grant_id, token_id, Zero::zero()));
+                return Ok(())
+            }
+
+            for (index, m) in grant.milestones.iter().enumerate() {
+                if m.status == MilestoneStatus::Approved {
+                    let _ = T::Scheduler::cancel_named((GRANT_ID, grant_id, index as u32).encode());
+                }
+            }
+
+            let grant_account = Self::grant_account_id(grant_id);
+            let returned = <pallet_mission_tokens::Module<T>>::free_balance(&grant_account, token_id);
+            let _ = <pallet_mission_tokens::Module<T>>::do_transfer(
+                &grant_account,
+                &Self::account_id(),
+                token_id,
+                returned,
+                AllowDeath,
+            ); // should not fail
+
+            Self::deposit_event(RawEvent::GrantCancelled(grant_id, token_id, returned));
+        }
+
+        /// Start a quadratic funding round for the projects of a mission, moving `matching_pool`
+        /// out of the pot of the mission to be matched with the contributions made to them
+        /// during the next `duration` blocks.
+        ///
+        /// May only be called from `T::ApproveOrigin`, or from `T::MissionApproveOrigin` of the
+        /// mission.
+        ///
+        /// # <weight>
+        /// - Complexity: O(1)
+        /// - DbReads: `RoundCount`, `pot account data`, `round account data`
+        /// - DbWrites: `RoundCount`, `Rounds`, `pot account data`, `round account data`
+        /// # </weight>
+        #[weight = 50_000_000 + T::DbWeight::get().reads_writes(3, 4)]
+        fn start_round(
+            origin,
+            token_id: TokenId<T>,
+            #[compact] matching_pool: BalanceOf<T>,
+            duration: T::BlockNumber,
+        ) {
+            Self::ensure_approve_origin(origin, token_id)?;
+            <pallet_mission_tokens::Module<T>>::validate_mission_token_id(token_id)?;
+            ensure!(!duration.is_zero(), Error::<T>::InvalidRoundDuration);
+
+            let index = Self::round_count();
+            <pallet_mission_tokens::Module<T>>::do_transfer(
+                &Self::account_id(),
+                &Self::round_account_id(index),
+                token_id,
+                matching_pool,
+                KeepAlive,
+            )?;
+
+            RoundCount::put(index + 1);
+            <Rounds<T>>::insert(index, FundingRound {
+                mission_token_id: token_id,
+                matching_pool,
+                end: system::Module::<T>::block_number() + duration,
+                project_count: 0,
+                finalized: false,
+            });
+
+            Self::deposit_event(RawEvent::RoundStarted(index, token_id, matching_pool));
+        }
+
+        /// Register a project in an open quadratic funding round. The funds of the project are
+        /// paid to `beneficiary` once the round is finalized.
+        ///
+        /// The dispatch origin for this call must be _Signed_ by a verified, unique person.
+        ///
+        /// # <weight>
+        /// - Complexity: O(1)
+        /// - DbReads: `Rounds`
+        /// - DbWrites: `Rounds`, `RoundProjects`
+        /// # </weight>
+        #[weight = 50_000_000 + T::DbWeight::get().reads_writes(1, 2)]
+        fn register_project(
+            origin,
+            #[compact] round_index: RoundIndex,
+            beneficiary: <T::Lookup as StaticLookup>::Source,
+        ) {
+            let who = ensure_signed(origin)?;
+            let beneficiary = T::Lookup::lookup(beneficiary)?;
+            ensure!(T::UniquePersons::is_unique_person(&who), Error::<T>::NotUniquePerson);
+
+            let project_index = Rounds::<T>::try_mutate(round_index, |maybe_round| -> Result<_, DispatchError> {
+                let round = maybe_round.as_mut().ok_or(Error::<T>::UnknownRound)?;
+                ensure!(system::Module::<T>::block_number() < round.end, Error::<T>::RoundClosed);
+                ensure!(round.project_count < T::MaxRoundProjects::get(), Error::<T>::TooManyProjects);
+
+                round.project_count += 1;
+                Ok(round.project_count - 1)
+            })?;
+
+            <RoundProjects<T>>::insert(round_index, project_index, RoundProject {
+                beneficiary: beneficiary.clone(),
+                contributors: 0,
+                contributed: Zero::zero(),
+                sqrt_sum: Zero::zero(),
+            });
+
+            Self::deposit_event(RawEvent::ProjectRegistered(round_index, project_index, beneficiary));
+        }
+
+        /// Contribute `value` mission tokens to a project of an open quadratic funding round.
+        ///
+        /// The dispatch origin for this call must be _Signed_ by a verified, unique person. The
+        /// contributions of a person to a project add up, and the project is matched on the
+        /// square root of their total so that every person weighs the same however much they give.
+        /// A project accepts contributions from up to `MaxProjectContributors` persons.
+        ///
+        /// # <weight>
+        /// - Complexity: O(1)
+        /// - DbReads: `Rounds`, `RoundProjects`, `Contributions`, `origin account`, `round account data`
+        /// - DbWrites: `RoundProjects`, `Contributions`, `origin account`, `round account data`
+        /// # </weight>
+        #[weight = 70_000_000 + T::DbWeight::get().reads_writes(5, 4)]
+        fn contribute(
+            origin,
+            #[compact] round_index: RoundIndex,
+            #[compact] project_index: ProjectIndex,
+            #[compact] value: BalanceOf<T>,
+        ) {
+            let who = ensure_signed(origin)?;
+            ensure!(value >= T::MinimumContribution::get(), Error::<T>::ContributionTooLow);
+            ensure!(T::UniquePersons::is_unique_person(&who), Error::<T>::NotUniquePerson);
+
+            let round = Self::rounds(round_index).ok_or(Error::<T>::UnknownRound)?;
+            ensure!(system::Module::<T>::block_number() < round.end, Error::<T>::RoundClosed);
+            let mut project = Self::round_projects(round_index, project_index)
+                .ok_or(Error::<T>::UnknownProject)?;
+            let previous = Self::contributions((round_index, project_index), &who);
+            ensure!(
+                !previous.is_zero() || project.contributors < T::MaxProjectContributors::get(),
+                Error::<T>::TooManyContributors
+            );
+
+            <pallet_mission_tokens::Module<T>>::do_transfer(
+                &who,
+                &Self::round_account_id(round_index),
+                round.mission_token_id,
+                value,
+                KeepAlive,
+            )?;
+
+            let total = previous.saturating_add(value);
+            if previous.is_zero() {
+                project.contributors = project.contributors.saturating_add(1);
+            }
+            project.contributed = project.contributed.saturating_add(value);
+            project.sqrt_sum = project.sqrt_sum
+                .saturating_sub(previous.integer_sqrt())
+                .saturating_add(total.integer_sqrt());
+
+            <Contributions<T>>::insert((round_index, project_index), &who, total);
+            <RoundProjects<T>>::insert(round_index, project_index, project);
+
+            Self::deposit_event(RawEvent::Contributed(round_index, project_index, who, value));
+        }
+
+        /// Finalize a quadratic funding round that has ended: every project is paid what it has
+        /// been contributed along with its share of the matching pool, and whatever is left goes
+        /// back to the pot of the mission, including the funds of any project that could not be
+        /// paid.
+        ///
+        /// The dispatch origin for this call must be _Signed_.
+        ///
+        /// # <weight>
+        /// - Complexity: O(P + C) where P is the number of projects, bounded by
+        ///   `MaxRoundProjects`, and C the number of their contributors, bounded by
+        ///   `MaxProjectContributors` per project
+        /// - DbReads: `Rounds`, `pot account data`, `round account data`, per project:
+        ///   `RoundProjects`, `beneficiary account data`
+        /// - DbWrites: `Rounds`, `pot account data`, `round account data`, per project:
+        ///   `beneficiary account data`, per contributor: `Contributions`
+        /// # </weight>
+        #[weight = Module::<T>::finalize_round_weight(
+            T::MaxRoundProjects::get(),
+            T::MaxRoundProjects::get().saturating_mul(T::MaxProjectContributors::get()),
+        )]
+        fn finalize_round(origin, #[compact] round_index: RoundIndex) -> DispatchResultWithPostInfo {
+            ensure_signed(origin)?;
+
+            let mut round = Self::rounds(round_index).ok_or(Error::<T>::UnknownRound)?;
+            ensure!(!round.finalized, Error::<T>::RoundAlreadyFinalized);
+            ensure!(system::Module::<T>::block_number() >= round.end, Error::<T>::RoundNotEnded);
+
+            let round_account = Self::round_account_id(round_index);
+            let token_id = round.mission_token_id;
+            let fundings = Self::project_fundings(round_index, &round);
+            let projects = fundings.len() as u32;
+            let mut contributors = 0u32;
+            for funding in fundings {
+                // Should not fail; if it does, the payout goes back to the pot below.
+                if <pallet_mission_tokens::Module<T>>::do_transfer(
+                    &round_account,
+                    &funding.beneficiary,
+                    token_id,
+                    funding.contributed.saturating_add(funding.matched),
+                    AllowDeath,
+                ).is_ok() {
+                    Self::deposit_event(RawEvent::ProjectFunded(
+                        round_index,
+                        funding.project_index,
+                        funding.beneficiary,
+                        funding.contributed,
+                        funding.matched,
+                    ));
+                }
+                <Contributions<T>>::remove_prefix((round_index, funding.project_index));
+                contributors = contributors.saturating_add(funding.contributors);
+            }
+
+            let unused = <pallet_mission_tokens::Module<T>>::free_balance(&round_account, token_id);
+            let _ = <pallet_mission_tokens::Module<T>>::do_transfer(
+                &round_account,
+                &Self::account_id(),
+                token_id,
+                unused,
+                AllowDeath,
+            ); // should not fail
+            round.finalized = true;
+            <Rounds<T>>::insert(round_index, round);
+
+            Self::deposit_event(RawEvent::RoundFinalized(round_index, unused));
+            Ok(Some(Self::finalize_round_weight(projects, contributors)).into())
+        }
+
         /// # <weight>
         /// - Complexity: `O(A)` where `A` is the number of approvals
         /// - Db reads and writes: `Approvals`, `pot account data`
@@ -1079,6 +1890,111 @@ impl<T: Trait> Module<T> {
         <pallet_treasury::Module<T>>::bounty_account_id(id)
     }
 
+    /// The account ID of the funds set aside for a grant.
+    pub fn grant_account_id(index: GrantIndex) -> T::AccountId {
+        <T as pallet_treasury::Trait>::ModuleId::get().into_sub_account(("gr", index))
+    }
+
+    /// The account ID of the matching pool of a quadratic funding round.
+    pub fn round_account_id(index: RoundIndex) -> T::AccountId {
+        <T as pallet_treasury::Trait>::ModuleId::get().into_sub_account(("qf", index))
+    }
+
+    /// The results of the quadratic funding round `index`; a projection of them while the
+    /// round is still open.
+    pub fn round_results(
+        index: RoundIndex,
+    ) -> Option<RoundResults<T::AccountId, BalanceOf<T>, TokenId<T>>> {
+        Self::rounds(index).map(|round| RoundResults {
+            mission_token_id: round.mission_token_id,
+            matching_pool: round.matching_pool,
+            finalized: round.finalized,
+            projects: Self::project_fundings(index, &round),
+        })
+    }
+
+    /// The funding of every project of a round. The matching pool is shared in proportion to
+    /// the quadratic match of each project: the square of the sum of the square roots of the
+    /// contributions made to it, less the contributions themselves.
+    fn project_fundings(
+        index: RoundIndex,
+        round: &FundingRound<BalanceOf<T>, T::BlockNumber, TokenId<T>>,
+    ) -> Vec<ProjectFunding<T::AccountId, BalanceOf<T>>> {
+        let projects = (0..round.project_count)
+            .filter_map(|i| Self::round_projects(index, i).map(|project| (i, project)))
+            .collect::<Vec<_>>();
+        let matches = projects
+            .iter()
+            .map(|(_, p)| p.sqrt_sum.saturating_mul(p.sqrt_sum).saturating_sub(p.contributed))
+            .collect::<Vec<_>>();
+        let total_match = matches
+            .iter()
+            .fold(BalanceOf::<T>::zero(), |total, m| total.saturating_add(*m));
+
+        projects
+            .into_iter()
+            .zip(matches)
+            .map(|((project_index, project), m)| ProjectFunding {
+                project_index,
+                beneficiary: project.beneficiary,
+                contributors: project.contributors,
+                contributed: project.contributed,
+                matched: if total_match.is_zero() {
+                    Zero::zero()
+                } else {
+                    Perbill::from_rational_approximation(m, total_match) * round.matching_pool
+                },
+            })
+            .collect()
+    }
+
+    /// The weight of finalizing a round of `projects` projects with `contributors` contributors
+    /// in all.
+    fn finalize_round_weight(projects: u32, contributors: u32) -> Weight {
+        let projects = projects as Weight;
+        (50_000_000 as Weight)
+            .saturating_add(projects.saturating_mul(50_000_000))
+            .saturating_add(T::DbWeight::get().reads(3 + 2 * projects))
+            .saturating_add(
+                T::DbWeight::get().writes((3 + projects).saturating_add(contributors as Weight)),
+            )
+    }
+
+    /// Ensure that `origin` may approve spending from the pot of `token_id`, either as
+    /// `ApproveOrigin` or as the `MissionApproveOrigin` of that mission.
+    fn ensure_approve_origin(origin: T::Origin, token_id: TokenId<T>) -> DispatchResult {
+        match <T as Trait>::MissionApproveOrigin::try_origin(origin) {
+            Ok(mission_token_id) => {
+                ensure!(mission_token_id == token_id, Error::<T>::WrongMission);
+                Ok(())
+            }
+            Err(origin) => <T as Trait>::ApproveOrigin::ensure_origin(origin)
+                .map(|_| ())
+                .map_err(Into::into),
+        }
+    }
+
+    /// Ensure that `origin` may reject spending from the pot of `token_id`, either as
+    /// `RejectOrigin` or as the `MissionRejectOrigin` of that mission.
+    fn ensure_reject_origin(origin: T::Origin, token_id: TokenId<T>) -> DispatchResult {
+        match <T as Trait>::MissionRejectOrigin::try_origin(origin) {
+            Ok(mission_token_id) => {
+                ensure!(mission_token_id == token_id, Error::<T>::WrongMission);
+                Ok(())
+            }
+            Err(origin) => <T as Trait>::RejectOrigin::ensure_origin(origin)
+                .map(|_| ())
+                .map_err(Into::into),
+        }
+    }
+
+    /// The mission whose pot funds the bounty `bounty_id`.
+    fn bounty_mission(bounty_id: BountyIndex) -> Result<TokenId<T>, DispatchError> {
+        Self::bounties(bounty_id)
+            .map(|bounty| bounty.mission_token_id)
+            .ok_or_else(|| Error::<T>::InvalidIndex.into())
+    }
+
     /// The needed bond for a proposal whose spend is `value`.
     fn calculate_bond(value: BalanceOf<T>) -> BalanceOf<T> {
         <T as Trait>::ProposalBondMinimum::get().max(<T as Trait>::ProposalBond::get() * value)
@@ -1227,21 +2143,33 @@ impl<T: Trait> Module<T> {
                             p.bond,
                         );
 
-                        // provide the allocation.
-                        imbalances[id].subsume(
-                            <pallet_mission_tokens::Module<T>>::deposit_creating(
-                                &p.beneficiary,
+                        // provide the allocation, as a stream if the proposal asked for it.
+                        let streamed = <ProposalStreams<T>>::take(index).map_or(false, |terms| {
+                            Self::stream_award(&account_id, &p, terms)
+                        });
+                        if streamed {
+                            Self::deposit_event(RawEvent::AwardedAsStream(
+                                index,
                                 p.mission_token_id,
                                 p.value,
-                            ),
-                        );
+                                p.beneficiary,
+                            ));
+                        } else {
+                            imbalances[id].subsume(
+                                <pallet_mission_tokens::Module<T>>::deposit_creating(
+                                    &p.beneficiary,
+                                    p.mission_token_id,
+                                    p.value,
+                                ),
+                            );
 
-                        Self::deposit_event(RawEvent::Awarded(
-                            index,
-                            p.mission_token_id,
-                            p.value,
-                            p.beneficiary,
-                        ));
+                            Self::deposit_event(RawEvent::Awarded(
+                                index,
+                                p.mission_token_id,
+                                p.value,
+                                p.beneficiary,
+                            ));
+                        }
                         false
                     } else {
                         missed_any[id] = true;
@@ -1340,9 +2268,72 @@ impl<T: Trait> Module<T> {
         total_weight
     }
 
+    /// Pay an awarded proposal out of the pot as a stream to its beneficiary. Returns whether the
+    /// stream could be created; if not the proposal should be paid in one lump instead.
+    fn stream_award(
+        treasury: &T::AccountId,
+        proposal: &Proposal<T::AccountId, BalanceOf<T>, TokenId<T>>,
+        terms: StreamTerms<T::BlockNumber>,
+    ) -> bool {
+        let now = system::Module::<T>::block_number();
+        let duration = T::BlockNumberToBalance::convert(terms.duration);
+        // Round up so that the whole value has vested by the end of the stream.
+        let rate_per_block = if (proposal.value % duration).is_zero() {
+            proposal.value / duration
+        } else {
+            proposal.value / duration + One::one()
+        };
+        match T::Streams::create_stream(
+            treasury,
+            &proposal.beneficiary,
+            proposal.mission_token_id,
+            proposal.value,
+            rate_per_block.max(One::one()),
+            now,
+            now + terms.cliff,
+        ) {
+            Ok(()) => true,
+            Err(_) => {
+                print("Couldn't stream awarded proposal; paying it in one lump instead");
+                false
+            }
+        }
+    }
+
+    /// Put forward a spending proposal and return its index.
+    fn do_propose_spend(
+        proposer: T::AccountId,
+        value: BalanceOf<T>,
+        beneficiary: T::AccountId,
+        token_id: TokenId<T>,
+    ) -> Result<ProposalIndex, DispatchError> {
+        <pallet_mission_tokens::Module<T>>::validate_mission_token_id(token_id)?;
+        Self::ensure_within_cap(token_id, value)?;
+
+        let bond = Self::calculate_bond(value);
+        <pallet_mission_tokens::Module<T>>::reserve(&proposer, token_id, bond)
+            .map_err(|_| Error::<T>::InsufficientProposersBalance)?;
+
+        let c = Self::proposal_count();
+        <ProposalCount>::put(c + 1);
+        <Proposals<T>>::insert(c, Proposal { proposer, value, beneficiary, bond, mission_token_id: token_id });
+
+        Self::deposit_event(RawEvent::Proposed(c));
+        Ok(c)
+    }
+
+    /// Ensure `value` of `token_id` is worth no more than `MaxProposalValue`, if its worth is
+    /// known; see `Trait::MaxProposalValue` for why an unknown worth passes.
+    fn ensure_within_cap(token_id: TokenId<T>, value: BalanceOf<T>) -> DispatchResult {
+        if let Some(worth) = T::TokenValue::value_of(token_id, value) {
+            ensure!(worth <= T::MaxProposalValue::get(), Error::<T>::ProposalValueTooHigh);
+        }
+        Ok(())
+    }
+
     /// Return the amount of money in the pot.
     // The existential deposit is not part of the pot so treasury account never gets deleted.
-    fn pot(token_id: TokenId<T>) -> BalanceOf<T> {
+    pub fn pot(token_id: TokenId<T>) -> BalanceOf<T> {
         <pallet_mission_tokens::Module<T>>::free_balance(&Self::account_id(), token_id)
             .saturating_sub(<pallet_mission_tokens::Module<T>>::minimum_balance())
     }
@@ -1361,6 +2352,7 @@ impl<T: Trait> Module<T> {
             value >= <T as Trait>::BountyValueMinimum::get(),
             Error::<T>::InvalidValue
         );
+        Self::ensure_within_cap(token_id, value)?;
 
         let index = Self::bounty_count();
 
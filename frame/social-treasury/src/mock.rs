@@ -1,9 +1,12 @@
-use crate::{Module, Trait};
+use crate as social_treasury;
+use crate::{Module, Trait, UniquePersons};
 use frame_support::{
     impl_outer_dispatch, impl_outer_origin, parameter_types,
-    traits::{Contains, ContainsLengthBound},
+    traits::{Contains, ContainsLengthBound, EnsureOrigin, Get, OnInitialize},
     weights::Weight,
 };
+use frame_system::{EnsureRoot, RawOrigin};
+use pallet_mission_tokens::MissionTokenValue;
 use pallet_session::{SessionHandler, SessionManager, ShouldEndSession};
 use pallet_staking::EraIndex;
 use sp_core::{crypto::key_types::DUMMY, H256};
@@ -11,11 +14,11 @@ use sp_runtime::{
     curve::PiecewiseLinear,
     impl_opaque_keys,
     testing::{Header, TestXt, UintAuthorityId},
-    traits::{BlakeTwo256, Convert, IdentityLookup, OpaqueKeys},
+    traits::{BlakeTwo256, Convert, ConvertInto, IdentityLookup, OpaqueKeys},
     ModuleId, Perbill, Percent, Permill, RuntimeAppPublic, SaturatedConversion,
 };
 use sp_staking::SessionIndex;
-use std::cell::RefCell;
+use std::{cell::RefCell, collections::BTreeMap, marker::PhantomData};
 
 use frame_system as system;
 
@@ -25,7 +28,9 @@ impl_outer_origin! {
 
 impl_outer_dispatch! {
     pub enum Call for Test where origin: Origin {
-        staking::Staking,
+        frame_system::System,
+        pallet_staking::Staking,
+        social_treasury::SocialTreasury,
     }
 }
 
@@ -33,7 +38,7 @@ impl_outer_dispatch! {
 pub struct Test;
 parameter_types! {
     pub const BlockHashCount: u64 = 250;
-    pub const MaximumBlockWeight: Weight = 1024;
+    pub const MaximumBlockWeight: Weight = 1_000_000_000_000;
     pub const MaximumBlockLength: u32 = 2 * 1024;
     pub const AvailableBlockRatio: Perbill = Perbill::from_percent(75);
     pub const DisabledValidatorsThreshold: Perbill = Perbill::from_percent(16);
@@ -67,9 +72,6 @@ impl system::Trait for Test {
     type SystemWeightInfo = ();
 }
 
-parameter_types! {
-    pub const ExistentialDepositC: u64 = 1;
-}
 thread_local! {
     static TEN_TO_FOURTEEN: RefCell<Vec<u128>> = RefCell::new(vec![10,11,12,13,14]);
 
@@ -133,7 +135,7 @@ parameter_types! {
     pub const BountyDepositBase: u64 = 80;
     pub const BountyDepositPayoutDelay: u64 = 3;
     pub const TreasuryModuleId: ModuleId = ModuleId(*b"py/trsry");
-    pub const BountyUpdatePeriod: u32 = 20;
+    pub const BountyUpdatePeriod: u64 = 20;
     pub const MaximumReasonLength: u32 = 16384;
     pub const BountyCuratorDeposit: Permill = Permill::from_percent(50);
     pub const BountyValueMinimum: u64 = 1;
@@ -163,10 +165,13 @@ impl pallet_treasury::Trait for Test {
     type BurnDestination = (); // Just gets burned.
     type WeightInfo = ();
 }
-type System = system::Module<Test>;
-type Balances = pallet_balances::Module<Test>;
-type Session = pallet_session::Module<Test>;
-type Staking = pallet_staking::Module<Test>;
+pub type System = system::Module<Test>;
+pub type Balances = pallet_balances::Module<Test>;
+pub type Session = pallet_session::Module<Test>;
+pub type Staking = pallet_staking::Module<Test>;
+pub type Scheduler = pallet_scheduler::Module<Test>;
+pub type MissionTokens = pallet_mission_tokens::Module<Test>;
+pub type MissionStreams = pallet_mission_streams::Module<Test>;
 
 impl<C> frame_system::offchain::SendTransactionTypes<C> for Test
 where
@@ -343,7 +348,8 @@ impl pallet_staking::Trait for Test {
     type SlashCancelOrigin = frame_system::EnsureRoot<Self::AccountId>;
     type SessionInterface = Self;
     type UnixTime = pallet_timestamp::Module<Test>;
-    type RewardCurve = RewardCurve;
+    type EraPayout = pallet_staking::ConvertCurve<RewardCurve>;
+    type EraPayoutOrigin = frame_system::EnsureRoot<Self::AccountId>;
     type MaxNominatorRewardedPerValidator = MaxNominatorRewardedPerValidator;
     type NextNewSession = Session;
     type ElectionLookahead = ElectionLookahead;
@@ -365,27 +371,195 @@ impl pallet_offences::Trait for Test {
     type WeightSoftLimit = OffencesWeightSoftLimit;
 }
 
+parameter_types! {
+    pub const MaxMissionTokensSupply: u128 = 1_000_000;
+}
+
 impl pallet_mission_tokens::Trait for Test {
     type Event = ();
     type Balance = u64;
     type MissionTokenId = u32;
+    type ExistentialDeposit = ExistentialDeposit;
+    type OnNewAccount = ();
+    type MaxMissionTokensSupply = MaxMissionTokensSupply;
+    type MissionCreatorOrigin = EnsureRoot<u64>;
 }
 
 impl pallet_validator_registry::Trait for Test {
     type Event = ();
-    type MissionId = u32;
+}
+
+parameter_types! {
+    pub const StreamsModuleId: ModuleId = ModuleId(*b"py/mstrm");
+    pub const MinimumStreamDeposit: u64 = 10;
+}
+
+/// Lets root act for the pot of the treasury.
+pub struct EnsureTreasury;
+impl EnsureOrigin<Origin> for EnsureTreasury {
+    type Success = u64;
+    fn try_origin(o: Origin) -> Result<u64, Origin> {
+        Into::<Result<RawOrigin<u64>, Origin>>::into(o).and_then(|o| match o {
+            RawOrigin::Root => Ok(SocialTreasury::account_id()),
+            r => Err(Origin::from(r)),
+        })
+    }
+
+    #[cfg(feature = "runtime-benchmarks")]
+    fn successful_origin() -> Origin {
+        Origin::root()
+    }
+}
+
+impl pallet_mission_streams::Trait for Test {
+    type Event = ();
+    type ModuleId = StreamsModuleId;
+    type MinimumDeposit = MinimumStreamDeposit;
+    type BlockNumberToBalance = ConvertInto;
+    type CancelOrigin = EnsureTreasury;
+}
+
+parameter_types! {
+    pub MaximumSchedulerWeight: Weight = Perbill::from_percent(80) * MaximumBlockWeight::get();
+}
+
+impl pallet_scheduler::Trait for Test {
+    type Event = ();
+    type Origin = Origin;
+    type PalletsOrigin = OriginCaller;
+    type Call = Call;
+    type MaximumWeight = MaximumSchedulerWeight;
+    type ScheduleOrigin = EnsureRoot<u64>;
+    type MaxScheduledPerBlock = ();
+    type WeightInfo = ();
+}
+
+parameter_types! {
+    pub const ApproveCouncils: u64 = 100;
+    pub const RejectCouncils: u64 = 200;
+}
+
+/// Stands in for the councils of the missions: account `Base + n` acts as the council of
+/// mission `n`, for `n` below 100.
+pub struct EnsureMissionCouncil<Base>(PhantomData<Base>);
+impl<Base: Get<u64>> EnsureOrigin<Origin> for EnsureMissionCouncil<Base> {
+    type Success = u32;
+    fn try_origin(o: Origin) -> Result<u32, Origin> {
+        Into::<Result<RawOrigin<u64>, Origin>>::into(o).and_then(|o| match o {
+            RawOrigin::Signed(who) if who > Base::get() && who < Base::get() + 100 => {
+                Ok((who - Base::get()) as u32)
+            }
+            r => Err(Origin::from(r)),
+        })
+    }
+
+    #[cfg(feature = "runtime-benchmarks")]
+    fn successful_origin() -> Origin {
+        Origin::signed(Base::get() + 1)
+    }
+}
+
+thread_local! {
+    static PERSONS: RefCell<Vec<u64>> = RefCell::new(vec![1, 2, 3, 4, 5]);
+    static PRICES: RefCell<BTreeMap<u32, u64>> = RefCell::new(BTreeMap::new());
+}
+
+/// Accounts 1 to 5 are verified, unique persons.
+pub struct TestPersons;
+impl UniquePersons<u64> for TestPersons {
+    fn is_unique_person(who: &u64) -> bool {
+        PERSONS.with(|p| p.borrow().contains(who))
+    }
+}
+
+/// Mission tokens are worth what `set_price` says, and of unknown worth otherwise.
+pub struct TestTokenValue;
+impl MissionTokenValue<u32, u64> for TestTokenValue {
+    fn value_of(token_id: u32, amount: u64) -> Option<u64> {
+        PRICES.with(|p| p.borrow().get(&token_id).map(|price| price.saturating_mul(amount)))
+    }
+}
+
+/// Set the worth of one mission token of `token_id`.
+pub fn set_price(token_id: u32, price: u64) {
+    PRICES.with(|p| p.borrow_mut().insert(token_id, price));
+}
+
+parameter_types! {
+    pub const MaxRoundProjects: u32 = 3;
+    pub const MaxProjectContributors: u32 = 4;
+    pub const MinimumContribution: u64 = 1;
+    pub const GrantReleaseDelay: u64 = 2;
+    pub const MaxMilestones: u32 = 3;
+    pub const MaxProposalValue: u64 = 5_000;
 }
 
 impl Trait for Test {
+    type ApproveOrigin = EnsureRoot<u64>;
+    type RejectOrigin = EnsureRoot<u64>;
+    type MissionApproveOrigin = EnsureMissionCouncil<ApproveCouncils>;
+    type MissionRejectOrigin = EnsureMissionCouncil<RejectCouncils>;
+    type Tippers = TenToFourteen;
+    type TipCountdown = TipCountdown;
+    type TipFindersFee = TipFindersFee;
+    type TipReportDepositBase = TipReportDepositBase;
+    type DataDepositPerByte = DataDepositPerByte;
     type Event = ();
+    type OnSlash = ();
+    type ProposalBond = ProposalBond;
+    type ProposalBondMinimum = ProposalBondMinimum;
+    type SpendPeriod = SpendPeriod;
+    type Burn = Burn;
+    type BountyDepositBase = BountyDepositBase;
+    type BountyDepositPayoutDelay = BountyDepositPayoutDelay;
+    type BountyUpdatePeriod = BountyUpdatePeriod;
+    type BountyCuratorDeposit = BountyCuratorDeposit;
+    type BountyValueMinimum = BountyValueMinimum;
+    type MaximumReasonLength = MaximumReasonLength;
+    type BurnDestination = ();
+    type WeightInfo = ();
+    type Streams = MissionStreams;
+    type BlockNumberToBalance = ConvertInto;
+    type UniquePersons = TestPersons;
+    type MaxRoundProjects = MaxRoundProjects;
+    type MaxProjectContributors = MaxProjectContributors;
+    type MinimumContribution = MinimumContribution;
+    type GrantCall = Call;
+    type Scheduler = Scheduler;
+    type PalletsOrigin = OriginCaller;
+    type GrantReleaseDelay = GrantReleaseDelay;
+    type MaxMilestones = MaxMilestones;
+    type TokenValue = TestTokenValue;
+    type MaxProposalValue = MaxProposalValue;
 }
 
 pub type SocialTreasury = Module<Test>;
 
 // Build genesis storage according to the mock runtime.
 pub fn new_test_ext() -> sp_io::TestExternalities {
-    system::GenesisConfig::default()
+    let mut ext: sp_io::TestExternalities = system::GenesisConfig::default()
         .build_storage::<Test>()
         .unwrap()
-        .into()
+        .into();
+    ext.execute_with(|| {
+        System::set_block_number(1);
+        // Accounts 1 to 5 hold 1_000 of missions 1 and 2, whose pots hold 10_000 each.
+        for who in 1..=5 {
+            MissionTokens::mint(who, 1, 1_000);
+            MissionTokens::mint(who, 2, 1_000);
+        }
+        MissionTokens::mint(SocialTreasury::account_id(), 1, 10_000);
+        MissionTokens::mint(SocialTreasury::account_id(), 2, 10_000);
+    });
+    ext
+}
+
+/// Run blocks up to and including `n`.
+pub fn run_to_block(n: u64) {
+    while System::block_number() < n {
+        let next = System::block_number() + 1;
+        System::set_block_number(next);
+        Scheduler::on_initialize(next);
+        SocialTreasury::on_initialize(next);
+    }
 }
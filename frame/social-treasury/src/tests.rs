@@ -1,20 +1,517 @@
-use crate::{mock::*, Error};
-use frame_support::{assert_noop, assert_ok};
+use crate::{mock::*, BountyStatus, Error, MilestoneStatus, Proposal, StreamTerms, GRANT_ID};
+use codec::Encode;
+use frame_support::{assert_noop, assert_ok, traits::OnInitialize};
+use pallet_mission_streams::Stream;
+use sp_core::H256;
+use sp_runtime::traits::BadOrigin;
 
 #[test]
-fn it_works_for_default_value() {
+fn mission_council_can_approve_proposals_of_its_mission() {
     new_test_ext().execute_with(|| {
-        assert_ok!(SocialTreasury::do_something(Origin::signed(1), 42));
-        assert_eq!(SocialTreasury::something(), Some(42));
+        assert_ok!(SocialTreasury::propose_spend(Origin::signed(1), 100, 3, 1));
+        assert_eq!(MissionTokens::reserved_balance(1, 1), 5);
+
+        assert_ok!(SocialTreasury::approve_proposal(Origin::signed(101), 0));
+        assert_eq!(SocialTreasury::approvals(), vec![0]);
+
+        run_to_block(2);
+        assert_eq!(MissionTokens::free_balance(3, 1), 1_100);
+        assert_eq!(MissionTokens::free_balance(1, 1), 1_000);
+        assert_eq!(MissionTokens::reserved_balance(1, 1), 0);
+        assert_eq!(SocialTreasury::proposals(0), None);
+    });
+}
+
+#[test]
+fn mission_council_can_reject_proposals_of_its_mission() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(SocialTreasury::propose_spend(Origin::signed(1), 100, 3, 2));
+
+        assert_ok!(SocialTreasury::reject_proposal(Origin::signed(202), 0));
+        assert_eq!(SocialTreasury::proposals(0), None);
+        assert_eq!(MissionTokens::free_balance(1, 2), 995);
+        assert_eq!(MissionTokens::reserved_balance(1, 2), 0);
     });
 }
 
 #[test]
-fn correct_error_for_none_value() {
+fn mission_council_cannot_act_on_other_missions() {
     new_test_ext().execute_with(|| {
+        assert_ok!(SocialTreasury::propose_spend(Origin::signed(1), 100, 3, 1));
+
         assert_noop!(
-            SocialTreasury::cause_error(Origin::signed(1)),
-            Error::<Test>::NoneValue
+            SocialTreasury::approve_proposal(Origin::signed(102), 0),
+            Error::<Test>::WrongMission
         );
+        assert_noop!(
+            SocialTreasury::reject_proposal(Origin::signed(202), 0),
+            Error::<Test>::WrongMission
+        );
+    });
+}
+
+#[test]
+fn approving_and_rejecting_need_their_own_origins() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(SocialTreasury::propose_spend(Origin::signed(1), 100, 3, 1));
+
+        assert_noop!(SocialTreasury::approve_proposal(Origin::signed(201), 0), BadOrigin);
+        assert_noop!(SocialTreasury::reject_proposal(Origin::signed(101), 0), BadOrigin);
+        assert_noop!(SocialTreasury::approve_proposal(Origin::signed(1), 0), BadOrigin);
+
+        // The treasury-wide origins act on every mission.
+        assert_ok!(SocialTreasury::approve_proposal(Origin::root(), 0));
+        assert_ok!(SocialTreasury::propose_spend(Origin::signed(1), 100, 3, 2));
+        assert_ok!(SocialTreasury::reject_proposal(Origin::root(), 1));
+    });
+}
+
+#[test]
+fn mission_council_can_approve_bounties_of_its_mission() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(SocialTreasury::propose_bounty(Origin::signed(1), 50, b"12345".to_vec(), 1));
+        assert_eq!(MissionTokens::reserved_balance(1, 1), 85);
+
+        assert_noop!(
+            SocialTreasury::approve_bounty(Origin::signed(102), 0),
+            Error::<Test>::WrongMission
+        );
+        assert_noop!(SocialTreasury::approve_bounty(Origin::signed(201), 0), BadOrigin);
+        assert_ok!(SocialTreasury::approve_bounty(Origin::signed(101), 0));
+        assert_eq!(SocialTreasury::bounties(0).unwrap().status, BountyStatus::Approved);
+
+        run_to_block(2);
+        assert_eq!(SocialTreasury::bounties(0).unwrap().status, BountyStatus::Funded);
+        assert_eq!(MissionTokens::free_balance(SocialTreasury::bounty_account_id(0), 1), 50);
+        assert_eq!(MissionTokens::reserved_balance(1, 1), 0);
+    });
+}
+
+#[test]
+fn mission_council_can_close_bounties_of_its_mission() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(SocialTreasury::propose_bounty(Origin::signed(1), 50, b"12345".to_vec(), 1));
+
+        assert_noop!(
+            SocialTreasury::close_bounty(Origin::signed(202), 0),
+            Error::<Test>::WrongMission
+        );
+        assert_noop!(SocialTreasury::close_bounty(Origin::signed(101), 0), BadOrigin);
+        assert_ok!(SocialTreasury::close_bounty(Origin::signed(201), 0));
+        assert_eq!(SocialTreasury::bounties(0), None);
+        assert_eq!(MissionTokens::free_balance(1, 1), 915);
+        assert_eq!(MissionTokens::reserved_balance(1, 1), 0);
+    });
+}
+
+#[test]
+fn streamed_award_should_vest_over_time() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            SocialTreasury::propose_streamed_spend(Origin::signed(1), 100, 3, 1, 30, 31),
+            Error::<Test>::InvalidStreamTerms
+        );
+        assert_ok!(SocialTreasury::propose_streamed_spend(Origin::signed(1), 100, 3, 1, 30, 5));
+        assert_ok!(SocialTreasury::approve_proposal(Origin::signed(101), 0));
+
+        run_to_block(2);
+        assert_eq!(SocialTreasury::proposal_streams(0), None);
+        assert_eq!(MissionTokens::free_balance(3, 1), 1_000);
+        assert_eq!(MissionTokens::free_balance(MissionStreams::account_id(), 1), 100);
+        // 100 over 30 blocks is rounded up so that it has all vested by the end.
+        assert_eq!(
+            MissionStreams::streams(0),
+            Some(Stream {
+                sender: SocialTreasury::account_id(),
+                recipient: 3,
+                mission_token_id: 1,
+                deposit: 100,
+                rate_per_block: 4,
+                start: 2,
+                cliff: 7,
+                withdrawn: 0,
+            })
+        );
+
+        run_to_block(10);
+        assert_ok!(MissionStreams::withdraw(Origin::signed(3), 0));
+        assert_eq!(MissionTokens::free_balance(3, 1), 1_032);
+
+        run_to_block(30);
+        assert_ok!(MissionStreams::withdraw(Origin::signed(3), 0));
+        assert_eq!(MissionTokens::free_balance(3, 1), 1_100);
+        assert_eq!(MissionStreams::streams(0), None);
+    });
+}
+
+#[test]
+fn streamed_award_can_be_cancelled_for_the_treasury() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(SocialTreasury::propose_streamed_spend(Origin::signed(1), 100, 3, 1, 10, 0));
+        assert_ok!(SocialTreasury::approve_proposal(Origin::root(), 0));
+        run_to_block(2);
+
+        System::set_block_number(5);
+        let pot = MissionTokens::free_balance(SocialTreasury::account_id(), 1);
+        assert_noop!(
+            MissionStreams::cancel_stream(Origin::signed(201), 0),
+            pallet_mission_streams::Error::<Test>::NotSender
+        );
+        assert_ok!(MissionStreams::cancel_stream(Origin::root(), 0));
+        assert_eq!(MissionTokens::free_balance(3, 1), 1_030);
+        assert_eq!(MissionTokens::free_balance(SocialTreasury::account_id(), 1), pot + 70);
+        assert_eq!(MissionStreams::streams(0), None);
+    });
+}
+
+#[test]
+fn stream_rate_should_not_overflow() {
+    new_test_ext().execute_with(|| {
+        let proposal = Proposal {
+            proposer: 1,
+            value: u64::max_value(),
+            beneficiary: 3,
+            bond: 0,
+            mission_token_id: 1,
+        };
+        let terms = StreamTerms { duration: 7, cliff: 0 };
+        // The pot cannot afford it, so the award falls back to a lump sum instead of panicking.
+        assert!(!SocialTreasury::stream_award(&SocialTreasury::account_id(), &proposal, terms));
+    });
+}
+
+/// Start a round of mission 1 matching 1_000 until block 11, with projects 0 and 1 paying
+/// accounts 10 and 11.
+fn start_round_with_two_projects() {
+    assert_ok!(SocialTreasury::start_round(Origin::signed(101), 1, 1_000, 10));
+    assert_ok!(SocialTreasury::register_project(Origin::signed(1), 0, 10));
+    assert_ok!(SocialTreasury::register_project(Origin::signed(2), 0, 11));
+}
+
+#[test]
+fn start_round_should_set_matching_pool_aside() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            SocialTreasury::start_round(Origin::signed(102), 1, 1_000, 10),
+            Error::<Test>::WrongMission
+        );
+        assert_noop!(
+            SocialTreasury::start_round(Origin::signed(101), 1, 1_000, 0),
+            Error::<Test>::InvalidRoundDuration
+        );
+
+        start_round_with_two_projects();
+        assert_eq!(MissionTokens::free_balance(SocialTreasury::round_account_id(0), 1), 1_000);
+        assert_eq!(SocialTreasury::pot(1), 8_999);
+        assert_ok!(SocialTreasury::register_project(Origin::signed(3), 0, 12));
+        assert_noop!(
+            SocialTreasury::register_project(Origin::signed(4), 0, 13),
+            Error::<Test>::TooManyProjects
+        );
+        assert_noop!(
+            SocialTreasury::register_project(Origin::signed(6), 0, 13),
+            Error::<Test>::NotUniquePerson
+        );
+    });
+}
+
+#[test]
+fn contributions_should_be_matched_quadratically() {
+    new_test_ext().execute_with(|| {
+        start_round_with_two_projects();
+
+        // Four persons give 25 each to project 0; the contributions of a person add up.
+        assert_ok!(SocialTreasury::contribute(Origin::signed(1), 0, 0, 16));
+        assert_ok!(SocialTreasury::contribute(Origin::signed(1), 0, 0, 9));
+        for who in 2..=4 {
+            assert_ok!(SocialTreasury::contribute(Origin::signed(who), 0, 0, 25));
+        }
+        // Two persons give 100 each to project 1.
+        assert_ok!(SocialTreasury::contribute(Origin::signed(1), 0, 1, 100));
+        assert_ok!(SocialTreasury::contribute(Origin::signed(2), 0, 1, 100));
+        assert_eq!(SocialTreasury::contributions((0, 0), 1), 25);
+
+        // Project 0 is matched (4 * 5)^2 - 100 = 300, project 1 (2 * 10)^2 - 200 = 200.
+        let results = SocialTreasury::round_results(0).unwrap();
+        assert_eq!(results.projects[0].contributors, 4);
+        assert_eq!(results.projects[0].contributed, 100);
+        assert_eq!(results.projects[0].matched, 600);
+        assert_eq!(results.projects[1].contributors, 2);
+        assert_eq!(results.projects[1].contributed, 200);
+        assert_eq!(results.projects[1].matched, 400);
+
+        assert_noop!(
+            SocialTreasury::finalize_round(Origin::signed(1), 0),
+            Error::<Test>::RoundNotEnded
+        );
+        System::set_block_number(11);
+        assert_noop!(
+            SocialTreasury::contribute(Origin::signed(5), 0, 0, 25),
+            Error::<Test>::RoundClosed
+        );
+        let pot = SocialTreasury::pot(1);
+        assert_ok!(SocialTreasury::finalize_round(Origin::signed(5), 0));
+        assert_eq!(MissionTokens::free_balance(10, 1), 700);
+        assert_eq!(MissionTokens::free_balance(11, 1), 600);
+        assert_eq!(MissionTokens::free_balance(SocialTreasury::round_account_id(0), 1), 0);
+        assert_eq!(SocialTreasury::pot(1), pot);
+        assert_eq!(SocialTreasury::contributions((0, 0), 1), 0);
+        assert!(SocialTreasury::rounds(0).unwrap().finalized);
+        assert_noop!(
+            SocialTreasury::finalize_round(Origin::signed(5), 0),
+            Error::<Test>::RoundAlreadyFinalized
+        );
+    });
+}
+
+#[test]
+fn contribute_should_check_contributors() {
+    new_test_ext().execute_with(|| {
+        start_round_with_two_projects();
+
+        assert_noop!(
+            SocialTreasury::contribute(Origin::signed(6), 0, 0, 25),
+            Error::<Test>::NotUniquePerson
+        );
+        assert_noop!(
+            SocialTreasury::contribute(Origin::signed(1), 0, 2, 25),
+            Error::<Test>::UnknownProject
+        );
+        for who in 1..=4 {
+            assert_ok!(SocialTreasury::contribute(Origin::signed(who), 0, 0, 25));
+        }
+        assert_noop!(
+            SocialTreasury::contribute(Origin::signed(5), 0, 0, 25),
+            Error::<Test>::TooManyContributors
+        );
+        // Those who already contributed may still give more.
+        assert_ok!(SocialTreasury::contribute(Origin::signed(4), 0, 0, 25));
+        assert_ok!(SocialTreasury::contribute(Origin::signed(5), 0, 1, 25));
+    });
+}
+
+#[test]
+fn unpaid_project_funds_should_go_back_to_the_pot() {
+    new_test_ext().execute_with(|| {
+        start_round_with_two_projects();
+        for who in 1..=4 {
+            assert_ok!(SocialTreasury::contribute(Origin::signed(who), 0, 0, 25));
+        }
+        assert_ok!(SocialTreasury::contribute(Origin::signed(1), 0, 1, 100));
+        assert_ok!(SocialTreasury::contribute(Origin::signed(2), 0, 1, 100));
+        // Paying project 1 would overflow the balance of its beneficiary.
+        MissionTokens::mutate_account(&11, 1, |account| account.free = u64::max_value());
+
+        System::set_block_number(11);
+        let pot = SocialTreasury::pot(1);
+        assert_ok!(SocialTreasury::finalize_round(Origin::signed(5), 0));
+        assert_eq!(MissionTokens::free_balance(10, 1), 700);
+        assert_eq!(MissionTokens::free_balance(11, 1), u64::max_value());
+        assert_eq!(SocialTreasury::pot(1), pot + 600);
+        assert_eq!(SocialTreasury::contributions((0, 1), 1), 0);
+    });
+}
+
+/// Milestones worth 100 and 200.
+fn milestones() -> Vec<(u64, H256)> {
+    vec![(100, H256::repeat_byte(1)), (200, H256::repeat_byte(2))]
+}
+
+#[test]
+fn propose_grant_should_check_milestones() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            SocialTreasury::propose_grant(Origin::signed(1), 3, 1, vec![]),
+            Error::<Test>::InvalidMilestones
+        );
+        assert_noop!(
+            SocialTreasury::propose_grant(Origin::signed(1), 3, 1, vec![(1, H256::zero()); 4]),
+            Error::<Test>::InvalidMilestones
+        );
+        assert_noop!(
+            SocialTreasury::propose_grant(Origin::signed(1), 3, 1, vec![(0, H256::zero())]),
+            Error::<Test>::InvalidMilestones
+        );
+        assert_ok!(SocialTreasury::propose_grant(
+            Origin::signed(1),
+            3,
+            1,
+            vec![(1, H256::zero()); 3]
+        ));
+        assert_ok!(SocialTreasury::propose_grant(Origin::signed(1), 3, 1, milestones()));
+        assert_eq!(SocialTreasury::grant_count(), 2);
+        assert_eq!(MissionTokens::reserved_balance(1, 1), 1 + 15);
+    });
+}
+
+#[test]
+fn grant_should_be_released_milestone_by_milestone() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(SocialTreasury::propose_grant(Origin::signed(1), 3, 1, milestones()));
+        assert_noop!(
+            SocialTreasury::approve_grant(Origin::signed(102), 0, None),
+            Error::<Test>::WrongMission
+        );
+        assert_noop!(
+            SocialTreasury::submit_milestone(Origin::signed(3), 0, 0, H256::zero()),
+            Error::<Test>::UnexpectedStatus
+        );
+        assert_ok!(SocialTreasury::approve_grant(Origin::signed(101), 0, Some(4)));
+        assert_eq!(MissionTokens::free_balance(SocialTreasury::grant_account_id(0), 1), 300);
+        assert_eq!(MissionTokens::reserved_balance(1, 1), 0);
+        assert_eq!(MissionTokens::free_balance(1, 1), 1_000);
+
+        assert_noop!(
+            SocialTreasury::submit_milestone(Origin::signed(4), 0, 0, H256::zero()),
+            Error::<Test>::RequireBeneficiary
+        );
+        assert_noop!(
+            SocialTreasury::submit_milestone(Origin::signed(3), 0, 2, H256::zero()),
+            Error::<Test>::UnknownMilestone
+        );
+        assert_noop!(
+            SocialTreasury::approve_milestone(Origin::signed(4), 0, 0),
+            Error::<Test>::UnexpectedStatus
+        );
+        assert_ok!(SocialTreasury::submit_milestone(Origin::signed(3), 0, 0, H256::zero()));
+
+        // Only the curator and the approval origins may approve a milestone.
+        assert_noop!(SocialTreasury::approve_milestone(Origin::signed(5), 0, 0), BadOrigin);
+        assert_ok!(SocialTreasury::approve_milestone(Origin::signed(4), 0, 0));
+        assert_eq!(
+            SocialTreasury::grants(0).unwrap().milestones[0].status,
+            MilestoneStatus::Approved
+        );
+        assert_noop!(
+            SocialTreasury::release_milestone(Origin::signed(3), 0, 0),
+            BadOrigin
+        );
+
+        // The tranche is released `GrantReleaseDelay` blocks later.
+        run_to_block(2);
+        assert_eq!(MissionTokens::free_balance(3, 1), 1_000);
+        run_to_block(3);
+        assert_eq!(MissionTokens::free_balance(3, 1), 1_100);
+        assert_eq!(
+            SocialTreasury::grants(0).unwrap().milestones[0].status,
+            MilestoneStatus::Released
+        );
+
+        assert_ok!(SocialTreasury::submit_milestone(Origin::signed(3), 0, 1, H256::zero()));
+        assert_ok!(SocialTreasury::approve_milestone(Origin::root(), 0, 1));
+        run_to_block(5);
+        assert_eq!(MissionTokens::free_balance(3, 1), 1_300);
+        assert_eq!(MissionTokens::free_balance(SocialTreasury::grant_account_id(0), 1), 0);
+        assert_eq!(SocialTreasury::grants(0), None);
+    });
+}
+
+#[test]
+fn cancel_grant_should_slash_bond_of_proposed_grant() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(SocialTreasury::propose_grant(Origin::signed(1), 3, 1, milestones()));
+        assert_noop!(SocialTreasury::cancel_grant(Origin::signed(101), 0), BadOrigin);
+        assert_noop!(
+            SocialTreasury::cancel_grant(Origin::signed(202), 0),
+            Error::<Test>::WrongMission
+        );
+        assert_ok!(SocialTreasury::cancel_grant(Origin::signed(201), 0));
+        assert_eq!(SocialTreasury::grants(0), None);
+        assert_eq!(MissionTokens::free_balance(1, 1), 985);
+        assert_eq!(MissionTokens::reserved_balance(1, 1), 0);
+    });
+}
+
+#[test]
+fn cancel_grant_should_return_unreleased_funds() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(SocialTreasury::propose_grant(Origin::signed(1), 3, 1, milestones()));
+        assert_ok!(SocialTreasury::approve_grant(Origin::root(), 0, None));
+        assert_ok!(SocialTreasury::submit_milestone(Origin::signed(3), 0, 0, H256::zero()));
+        assert_ok!(SocialTreasury::approve_milestone(Origin::signed(101), 0, 0));
+
+        let pot = SocialTreasury::pot(1);
+        assert_ok!(SocialTreasury::cancel_grant(Origin::root(), 0));
+        assert_eq!(SocialTreasury::pot(1), pot + 300);
+        assert_eq!(MissionTokens::free_balance(SocialTreasury::grant_account_id(0), 1), 0);
+
+        // The release of the approved milestone was cancelled along with the grant.
+        System::set_block_number(3);
+        Scheduler::on_initialize(3);
+        assert_eq!(MissionTokens::free_balance(3, 1), 1_000);
+    });
+}
+
+#[test]
+fn approve_milestone_should_fail_when_release_cannot_be_scheduled() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(SocialTreasury::propose_grant(Origin::signed(1), 3, 1, milestones()));
+        assert_ok!(SocialTreasury::approve_grant(Origin::root(), 0, None));
+        assert_ok!(SocialTreasury::submit_milestone(Origin::signed(3), 0, 0, H256::zero()));
+
+        // Something else is already scheduled under the name of the release.
+        assert_ok!(Scheduler::schedule_named(
+            Origin::root(),
+            (GRANT_ID, 0u32, 0u32).encode(),
+            10,
+            None,
+            63,
+            Box::new(Call::System(frame_system::Call::remark(vec![]))),
+        ));
+        assert_noop!(
+            SocialTreasury::approve_milestone(Origin::root(), 0, 0),
+            Error::<Test>::ScheduleFailed
+        );
+        assert_eq!(
+            SocialTreasury::grants(0).unwrap().milestones[0].status,
+            MilestoneStatus::Submitted
+        );
+    });
+}
+
+#[test]
+fn proposals_should_be_capped_by_value() {
+    new_test_ext().execute_with(|| {
+        // 500 of mission 1 are worth exactly `MaxProposalValue`.
+        set_price(1, 10);
+        assert_noop!(
+            SocialTreasury::propose_spend(Origin::signed(1), 501, 3, 1),
+            Error::<Test>::ProposalValueTooHigh
+        );
+        assert_noop!(
+            SocialTreasury::propose_streamed_spend(Origin::signed(1), 501, 3, 1, 10, 0),
+            Error::<Test>::ProposalValueTooHigh
+        );
+        assert_noop!(
+            SocialTreasury::propose_bounty(Origin::signed(1), 501, b"12345".to_vec(), 1),
+            Error::<Test>::ProposalValueTooHigh
+        );
+        assert_noop!(
+            SocialTreasury::propose_grant(
+                Origin::signed(1),
+                3,
+                1,
+                vec![(300, H256::zero()), (201, H256::zero())]
+            ),
+            Error::<Test>::ProposalValueTooHigh
+        );
+
+        assert_ok!(SocialTreasury::propose_spend(Origin::signed(1), 500, 3, 1));
+        assert_ok!(SocialTreasury::propose_bounty(Origin::signed(1), 500, b"12345".to_vec(), 1));
+        assert_ok!(SocialTreasury::propose_grant(
+            Origin::signed(1),
+            3,
+            1,
+            vec![(300, H256::zero()), (200, H256::zero())]
+        ));
+    });
+}
+
+#[test]
+fn proposals_of_unknown_worth_should_not_be_capped() {
+    new_test_ext().execute_with(|| {
+        set_price(1, 10);
+        assert_ok!(SocialTreasury::propose_spend(Origin::signed(1), 900, 3, 2));
+        assert_ok!(SocialTreasury::propose_bounty(Origin::signed(1), 900, b"12345".to_vec(), 2));
     });
 }
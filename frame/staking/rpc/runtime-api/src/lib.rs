@@ -0,0 +1,37 @@
+//! Runtime API definition for the staking module.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::{Codec, Decode, Encode};
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+use sp_runtime::RuntimeDebug;
+use sp_std::prelude::*;
+
+/// Counter for the number of eras that have passed.
+pub type EraIndex = u32;
+
+/// The projected payout made at the end of a future era.
+#[derive(Eq, PartialEq, Encode, Decode, Clone, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct EraPayoutProjection<Balance> {
+	/// The era at the end of which the payout is made.
+	pub era: EraIndex,
+	/// The amount minted for the validators and nominators of the era.
+	pub staker_payout: Balance,
+	/// The amount minted for the reward remainder, i.e. the treasury.
+	pub treasury_payout: Balance,
+	/// The total issuance once the payout is made.
+	pub total_issuance: Balance,
+}
+
+sp_api::decl_runtime_apis! {
+	pub trait StakingApi<Balance> where
+		Balance: Codec,
+	{
+		/// Project the payouts of the eras `from..=to` under the current era payout parameters,
+		/// assuming the share of the issuance that is staked stays as it is now.
+		fn era_payout_projection(from: EraIndex, to: EraIndex) -> Vec<EraPayoutProjection<Balance>>;
+	}
+}
@@ -23,6 +23,7 @@ use testing_utils::*;
 
 use sp_runtime::traits::One;
 use frame_system::RawOrigin;
+use frame_support::traits::UnfilteredDispatchable;
 pub use frame_benchmarking::{benchmarks, account, whitelisted_caller};
 const SEED: u32 = 0;
 const MAX_SPANS: u32 = 100;
@@ -240,6 +241,14 @@ benchmarks! {
 		assert_eq!(ValidatorCount::get(), validator_count);
 	}
 
+	set_era_payout_parameters {
+		let origin = T::EraPayoutOrigin::successful_origin();
+		let call = Call::<T>::set_era_payout_parameters(Default::default());
+	}: { call.dispatch_bypass_filter(origin)? }
+	verify {
+		assert_eq!(EraPayoutParameters::<T>::get(), EraPayoutParametersOf::<T>::default());
+	}
+
 	force_no_eras {}: _(RawOrigin::Root)
 	verify { assert_eq!(ForceEra::get(), Forcing::ForceNone); }
 
@@ -789,6 +798,7 @@ mod tests {
 			assert_ok!(test_benchmark_set_payee::<Test>());
 			assert_ok!(test_benchmark_set_controller::<Test>());
 			assert_ok!(test_benchmark_set_validator_count::<Test>());
+			assert_ok!(test_benchmark_set_era_payout_parameters::<Test>());
 			assert_ok!(test_benchmark_force_no_eras::<Test>());
 			assert_ok!(test_benchmark_force_new_era::<Test>());
 			assert_ok!(test_benchmark_force_new_era_always::<Test>());
@@ -166,4 +166,9 @@ impl crate::WeightInfo for () {
 			.saturating_add(DbWeight::get().reads((1 as Weight).saturating_mul(w as Weight)))
 			.saturating_add(DbWeight::get().writes(2 as Weight))
 	}
+	fn set_era_payout_parameters() -> Weight {
+		(5185000 as Weight)
+			.saturating_add(DbWeight::get().reads(2 as Weight))
+			.saturating_add(DbWeight::get().writes(1 as Weight))
+	}
 }
@@ -174,19 +174,23 @@
 //!
 //! ### Era payout
 //!
-//! The era payout is computed using yearly inflation curve defined at
-//! [`T::RewardCurve`](./trait.Trait.html#associatedtype.RewardCurve) as such:
+//! The era payout is computed by the model defined at
+//! [`T::EraPayout`](./trait.Trait.html#associatedtype.EraPayout), using its parameters held in
+//! the `EraPayoutParameters` storage item. These can be changed by
+//! [`T::EraPayoutOrigin`](./trait.Trait.html#associatedtype.EraPayoutOrigin) through
+//! `set_era_payout_parameters` without a runtime upgrade.
+//!
+//! The model returns the payout of the stakers, used to reward them as defined in next section,
+//! and the remaining payout. The remaining reward is send to the configurable end-point
+//! [`T::RewardRemainder`](./trait.Trait.html#associatedtype.RewardRemainder).
 //!
-//! ```nocompile
-//! staker_payout = yearly_inflation(npos_token_staked / total_tokens) * total_tokens / era_per_year
-//! ```
-//! This payout is used to reward stakers as defined in next section
+//! Two models are provided: [`SocialNetworkInflation`], a per-era inflation decaying until a
+//! cutoff era, and [`ConvertCurve`], which uses a yearly inflation curve as such:
 //!
 //! ```nocompile
+//! staker_payout = yearly_inflation(npos_token_staked / total_tokens) * total_tokens / era_per_year
 //! remaining_payout = max_yearly_inflation * total_tokens / era_per_year - staker_payout
 //! ```
-//! The remaining reward is send to the configurable end-point
-//! [`T::RewardRemainder`](./trait.Trait.html#associatedtype.RewardRemainder).
 //!
 //! ### Reward Calculation
 //!
@@ -280,8 +284,12 @@ pub mod slashing;
 pub mod offchain_election;
 pub mod inflation;
 pub mod default_weights;
+pub mod migrations;
 mod social_network_inflation;
 
+pub use social_network_inflation::{InflationParameters, SocialNetworkInflation};
+pub use pallet_staking_rpc_runtime_api::EraPayoutProjection;
+
 use sp_std::{
 	result,
 	prelude::*,
@@ -291,7 +299,7 @@ use sp_std::{
 };
 use codec::{HasCompact, Encode, Decode};
 use frame_support::{
-	decl_module, decl_event, decl_storage, ensure, decl_error,
+	decl_module, decl_event, decl_storage, ensure, decl_error, Parameter,
 	weights::{Weight, constants::{WEIGHT_PER_MICROS, WEIGHT_PER_NANOS}},
 	storage::IterableStorageMap,
 	dispatch::{
@@ -309,7 +317,7 @@ use sp_runtime::{
 	curve::PiecewiseLinear,
 	traits::{
 		Convert, Zero, StaticLookup, CheckedSub, Saturating, SaturatedConversion,
-		AtLeast32BitUnsigned, Dispatchable,
+		AtLeast32BitUnsigned, Dispatchable, MaybeSerializeDeserialize,
 	},
 	transaction_validity::{
 		TransactionValidityError, TransactionValidity, ValidTransaction, InvalidTransaction,
@@ -335,6 +343,8 @@ use sp_npos_elections::{
 const STAKING_ID: LockIdentifier = *b"staking ";
 pub const MAX_UNLOCKING_CHUNKS: usize = 32;
 pub const MAX_NOMINATIONS: usize = <CompactAssignments as VotingLimit>::LIMIT;
+/// Maximum number of eras projected by a single call to `project_era_payouts`.
+pub const MAX_PROJECTED_ERAS: EraIndex = 10_000;
 
 pub(crate) const LOG_TARGET: &'static str = "staking";
 
@@ -392,6 +402,10 @@ type PositiveImbalanceOf<T> =
 type NegativeImbalanceOf<T> =
 	<<T as Trait>::Currency as Currency<<T as frame_system::Trait>::AccountId>>::NegativeImbalance;
 
+/// The parameters of the era payout model of this module.
+pub type EraPayoutParametersOf<T> =
+	<<T as Trait>::EraPayout as EraPayout<BalanceOf<T>>>::Parameters;
+
 /// Information regarding the active era (era in used in session).
 #[derive(Encode, Decode, RuntimeDebug)]
 pub struct ActiveEraInfo {
@@ -795,6 +809,65 @@ pub trait WeightInfo {
 	fn reap_stash(s: u32, ) -> Weight;
 	fn new_era(v: u32, n: u32, ) -> Weight;
 	fn submit_solution_better(v: u32, n: u32, a: u32, w: u32, ) -> Weight;
+	fn set_era_payout_parameters() -> Weight;
+}
+
+/// Means of computing the amount minted at the end of every era.
+pub trait EraPayout<Balance> {
+	/// The parameters of the model. They are kept in storage so they can be tuned without a
+	/// runtime upgrade.
+	type Parameters: Parameter + Default + MaybeSerializeDeserialize;
+
+	/// Compute the payout of the era `era_index` that lasted `era_duration_millis`, given the
+	/// total amount staked in it and the current total issuance.
+	///
+	/// Returns the amount minted for the stakers and the remainder minted for
+	/// [`Trait::RewardRemainder`].
+	fn era_payout(
+		parameters: &Self::Parameters,
+		era_index: EraIndex,
+		total_staked: Balance,
+		total_issuance: Balance,
+		era_duration_millis: u64,
+	) -> (Balance, Balance);
+
+	/// Whether `parameters` can be applied from the active era `active_era` on, given the
+	/// current total issuance.
+	fn check_parameters(
+		_parameters: &Self::Parameters,
+		_active_era: EraIndex,
+		_total_issuance: Balance,
+	) -> bool {
+		true
+	}
+}
+
+/// The era payout following the yearly inflation curve `T`. It has no parameters; the curve is
+/// fixed at compile time.
+pub struct ConvertCurve<T>(sp_std::marker::PhantomData<T>);
+
+impl<Balance, T> EraPayout<Balance> for ConvertCurve<T> where
+	Balance: AtLeast32BitUnsigned + Clone,
+	T: Get<&'static PiecewiseLinear<'static>>,
+{
+	type Parameters = ();
+
+	fn era_payout(
+		_parameters: &(),
+		_era_index: EraIndex,
+		total_staked: Balance,
+		total_issuance: Balance,
+		era_duration_millis: u64,
+	) -> (Balance, Balance) {
+		let (validator_payout, max_payout) = inflation::compute_total_payout(
+			T::get(),
+			total_staked,
+			total_issuance,
+			era_duration_millis,
+		);
+		let rest = max_payout.saturating_sub(validator_payout.clone());
+		(validator_payout, rest)
+	}
 }
 
 pub trait Trait: frame_system::Trait + SendTransactionTypes<Call<Self>> {
@@ -845,9 +918,12 @@ pub trait Trait: frame_system::Trait + SendTransactionTypes<Call<Self>> {
 	/// Interface for interacting with a session module.
 	type SessionInterface: self::SessionInterface<Self::AccountId>;
 
-	/// The NPoS reward curve used to define yearly inflation.
+	/// The model computing the amount minted at the end of every era.
 	/// See [Era payout](./index.html#era-payout).
-	type RewardCurve: Get<&'static PiecewiseLinear<'static>>;
+	type EraPayout: EraPayout<BalanceOf<Self>>;
+
+	/// The origin which can change the parameters of the era payout model.
+	type EraPayoutOrigin: EnsureOrigin<Self::Origin>;
 
 	/// Something that can estimate the next session change, accurately or as a best effort guess.
 	type NextNewSession: EstimateNextNewSession<Self::BlockNumber>;
@@ -915,11 +991,12 @@ enum Releases {
 	V2_0_0,
 	V3_0_0,
 	V4_0_0,
+	V5_0_0,
 }
 
 impl Default for Releases {
 	fn default() -> Self {
-		Releases::V4_0_0
+		Releases::V5_0_0
 	}
 }
 
@@ -1031,6 +1108,9 @@ decl_storage! {
 		pub ErasTotalStake get(fn eras_total_stake):
 			map hasher(twox_64_concat) EraIndex => BalanceOf<T>;
 
+		/// The parameters of the era payout model, applied from the end of the active era on.
+		pub EraPayoutParameters get(fn era_payout_parameters) config(): EraPayoutParametersOf<T>;
+
 		/// Mode of era forcing.
 		pub ForceEra get(fn force_era) config(): Forcing;
 
@@ -1104,7 +1184,7 @@ decl_storage! {
 		/// Storage version of the pallet.
 		///
 		/// This is set to v3.0.0 for new networks.
-		StorageVersion build(|_: &GenesisConfig<T>| Releases::V4_0_0): Releases;
+		StorageVersion build(|_: &GenesisConfig<T>| Releases::V5_0_0): Releases;
 	}
 	add_extra_genesis {
 		config(stakers):
@@ -1168,6 +1248,8 @@ decl_event!(
 		/// An account has called `withdraw_unbonded` and removed unbonding chunks worth `Balance`
 		/// from the unlocking queue. \[stash, amount\]
 		Withdrawn(AccountId, Balance),
+		/// The parameters of the era payout model have been changed.
+		EraPayoutParametersSet,
 	}
 );
 
@@ -1239,6 +1321,8 @@ decl_error! {
 		IncorrectHistoryDepth,
 		/// Incorrect number of slashing spans provided.
 		IncorrectSlashingSpans,
+		/// The era payout parameters are rejected by the era payout model.
+		InvalidEraPayoutParameters,
 	}
 }
 
@@ -2042,6 +2126,28 @@ decl_module! {
 			T::Currency::remove_lock(STAKING_ID, &stash);
 		}
 
+		/// Set the parameters of the era payout model.
+		///
+		/// The dispatch origin must be `T::EraPayoutOrigin`. The new parameters are used from the
+		/// payout of the active era on, and must be accepted by the era payout model.
+		///
+		/// # <weight>
+		/// Weight: O(1)
+		/// Read: Active Era, Total Issuance
+		/// Write: Era Payout Parameters
+		/// # </weight>
+		#[weight = T::WeightInfo::set_era_payout_parameters()]
+		fn set_era_payout_parameters(origin, parameters: EraPayoutParametersOf<T>) {
+			T::EraPayoutOrigin::ensure_origin(origin)?;
+			let active_era = Self::active_era().map(|e| e.index).unwrap_or(0);
+			ensure!(
+				T::EraPayout::check_parameters(&parameters, active_era, T::Currency::total_issuance()),
+				Error::<T>::InvalidEraPayoutParameters,
+			);
+			EraPayoutParameters::<T>::put(parameters);
+			Self::deposit_event(RawEvent::EraPayoutParametersSet);
+		}
+
 		/// Submit an election result to the chain. If the solution:
 		///
 		/// 1. is valid.
@@ -2688,25 +2794,17 @@ impl<T: Trait> Module<T> {
 	/// Compute payout for era.
 	fn end_era(active_era: ActiveEraInfo, _session_index: SessionIndex) {
 		// Note: active_era_start can be None if end era is called during genesis config.
-		if let Some(_active_era_start) = active_era.start {
-			/*
+		if let Some(active_era_start) = active_era.start {
 			let now_as_millis_u64 = T::UnixTime::now().as_millis().saturated_into::<u64>();
 
-			let era_duration = now_as_millis_u64 - active_era_start;
-			let (validator_payout, max_payout) = inflation::compute_total_payout(
-				&T::RewardCurve::get(),
-				Self::eras_total_stake(&active_era.index),
-				T::Currency::total_issuance(),
-				// Duration of era; more than u64::MAX is rewarded as u64::MAX.
-				era_duration.saturated_into::<u64>(),
-			);
-			*/
-			let (validator_payout, max_payout) = social_network_inflation::compute_total_payout(
+			let era_duration = now_as_millis_u64.saturating_sub(active_era_start);
+			let (validator_payout, rest) = T::EraPayout::era_payout(
+				&Self::era_payout_parameters(),
 				active_era.index,
 				Self::eras_total_stake(&active_era.index),
 				T::Currency::total_issuance(),
+				era_duration,
 			);
-			let rest = max_payout.saturating_sub(validator_payout);
 
 			Self::deposit_event(RawEvent::EraPayout(active_era.index, validator_payout, rest));
 
@@ -2716,6 +2814,55 @@ impl<T: Trait> Module<T> {
 		}
 	}
 
+	/// Project the payouts of the eras `from..=to` under the current era payout parameters.
+	///
+	/// The eras from the active one on are simulated, assuming every era lasts
+	/// `era_duration_millis` and the share of the issuance that is staked stays the one of the
+	/// active era. Eras that have already been paid out are not projected, and neither are the
+	/// eras `MAX_PROJECTED_ERAS` or more after the active one, so that the work done is bounded.
+	pub fn project_era_payouts(
+		from: EraIndex,
+		to: EraIndex,
+		era_duration_millis: u64,
+	) -> Vec<EraPayoutProjection<BalanceOf<T>>> {
+		let active_era = Self::active_era().map(|e| e.index).unwrap_or(0);
+		let from = from.max(active_era);
+		let to = to.min(active_era.saturating_add(MAX_PROJECTED_ERAS - 1));
+		if from > to {
+			return Vec::new();
+		}
+
+		let parameters = Self::era_payout_parameters();
+		let mut total_issuance = T::Currency::total_issuance();
+		let staked_share = Perbill::from_rational_approximation(
+			Self::eras_total_stake(&active_era),
+			total_issuance,
+		);
+
+		let mut projection = Vec::with_capacity((to - from + 1) as usize);
+		for era in active_era..=to {
+			let (staker_payout, treasury_payout) = T::EraPayout::era_payout(
+				&parameters,
+				era,
+				staked_share.mul_floor(total_issuance),
+				total_issuance,
+				era_duration_millis,
+			);
+			total_issuance = total_issuance
+				.saturating_add(staker_payout)
+				.saturating_add(treasury_payout);
+			if era >= from {
+				projection.push(EraPayoutProjection {
+					era,
+					staker_payout,
+					treasury_payout,
+					total_issuance,
+				});
+			}
+		}
+		projection
+	}
+
 	/// Plan a new era. Return the potential new staking set.
 	fn new_era(start_session_index: SessionIndex) -> Option<Vec<T::AccountId>> {
 		// Increment or set current era.
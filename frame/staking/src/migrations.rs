@@ -0,0 +1,72 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Storage migrations of the staking module.
+
+use crate::{
+	BalanceOf, EraPayout, EraPayoutParameters, InflationParameters, Releases, StorageVersion, Trait,
+	social_network_inflation::{LEGACY_TERMINAL_ISSUANCE, TERMINAL_ISSUANCE},
+};
+use frame_support::{
+	storage::StorageValue,
+	traits::{Get, Migration},
+	weights::Weight,
+};
+use sp_runtime::SaturatedConversion;
+use sp_std::marker::PhantomData;
+
+/// Scale the terminal issuance of the social network inflation up to `DOLLARS`.
+///
+/// Before `V5_0_0` the default parameters set the terminal issuance to `7_777_777_777` raw units,
+/// far below the issuance of the network, so nothing would have been minted at the cutoff era.
+/// Parameters still holding that value get the intended `7_777_777_777 * DOLLARS`, any other
+/// value was set on purpose and is kept.
+pub struct ScaleTerminalIssuance<T>(PhantomData<T>);
+
+impl<T: Trait> Migration for ScaleTerminalIssuance<T> where
+	T::EraPayout: EraPayout<BalanceOf<T>, Parameters = InflationParameters<BalanceOf<T>>>,
+{
+	const NAME: &'static str = "Staking::ScaleTerminalIssuance";
+
+	fn migrate() -> Weight {
+		if StorageVersion::get() != Releases::V4_0_0 {
+			return T::DbWeight::get().reads(1);
+		}
+
+		let mut writes = 1;
+		let mut parameters = EraPayoutParameters::<T>::get();
+		if parameters.terminal_issuance == LEGACY_TERMINAL_ISSUANCE.saturated_into::<BalanceOf<T>>() {
+			parameters.terminal_issuance = TERMINAL_ISSUANCE.saturated_into();
+			EraPayoutParameters::<T>::put(parameters);
+			writes += 1;
+		}
+		StorageVersion::put(Releases::V5_0_0);
+
+		T::DbWeight::get().reads_writes(2, writes)
+	}
+
+	fn post_upgrade() -> Result<(), &'static str> {
+		if StorageVersion::get() != Releases::V5_0_0 {
+			return Err("storage version was not bumped to V5_0_0");
+		}
+		let terminal_issuance = EraPayoutParameters::<T>::get().terminal_issuance;
+		if terminal_issuance == LEGACY_TERMINAL_ISSUANCE.saturated_into::<BalanceOf<T>>() {
+			return Err("terminal issuance is still in raw units");
+		}
+		Ok(())
+	}
+}
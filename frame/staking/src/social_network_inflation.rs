@@ -1,40 +1,119 @@
-use crate::EraIndex;
-use sp_runtime::{Perbill, Percent, SaturatedConversion, traits::{AtLeast32BitUnsigned, Saturating, Zero}};
+use crate::{EraIndex, EraPayout};
+use codec::{Encode, Decode};
+use frame_support::Parameter;
+#[cfg(feature = "std")]
+use sp_runtime::{Serialize, Deserialize};
+use sp_runtime::{
+    Perbill, Percent, RuntimeDebug, SaturatedConversion,
+    traits::{AtLeast32BitUnsigned, MaybeSerializeDeserialize, Saturating, Zero},
+};
+
+/// One dollar in the smallest unit of the currency, as defined by the node runtime.
+const DOLLARS: u128 = 100_000_000_000_000;
+
+/// The total issuance the network launched to reach at the cutoff era.
+pub const TERMINAL_ISSUANCE: u128 = 7_777_777_777 * DOLLARS;
+
+/// The terminal issuance of the first release of the parameters, which was missing the
+/// `DOLLARS` factor.
+pub(crate) const LEGACY_TERMINAL_ISSUANCE: u128 = 7_777_777_777;
+
+/// The parameters of the social network inflation model.
+///
+/// The defaults are the ones the network launched with: 0.0233278% per era decaying by 0.005%
+/// every era, 99% of it for stakers, and a terminal top-up at era 360,000.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct InflationParameters<Balance> {
+    /// The share of the issuance minted in era 0.
+    pub inflation_rate: Perbill,
+    /// The factor the inflation rate is multiplied with every era.
+    pub decay: Perbill,
+    /// The share of the minted amount that may go to stakers. The rest goes to the treasury.
+    pub staker_share: Percent,
+    /// The era at the end of which the issuance is topped up to `terminal_issuance`. Nothing is
+    /// minted after it.
+    pub cutoff_era: EraIndex,
+    /// The total issuance reached at `cutoff_era`.
+    pub terminal_issuance: Balance,
+}
+
+impl<Balance: AtLeast32BitUnsigned> Default for InflationParameters<Balance> {
+    fn default() -> Self {
+        InflationParameters {
+            inflation_rate: Perbill::from_parts(233_278),
+            decay: Perbill::from_parts(999_950_000),
+            staker_share: Percent::from_percent(99),
+            cutoff_era: 360_000,
+            terminal_issuance: TERMINAL_ISSUANCE.saturated_into(),
+        }
+    }
+}
+
+/// The era payout model of the social network: a per-era inflation decaying geometrically until
+/// the cutoff era, at which the issuance is topped up once. The era duration plays no role.
+pub struct SocialNetworkInflation;
+
+impl<Balance> EraPayout<Balance> for SocialNetworkInflation where
+    Balance: AtLeast32BitUnsigned + Parameter + MaybeSerializeDeserialize,
+{
+    type Parameters = InflationParameters<Balance>;
+
+    fn era_payout(
+        parameters: &Self::Parameters,
+        era_index: EraIndex,
+        total_staked: Balance,
+        total_issuance: Balance,
+        _era_duration_millis: u64,
+    ) -> (Balance, Balance) {
+        let (staker_payout, maximum_payout) =
+            compute_total_payout(parameters, era_index, total_staked, total_issuance);
+        let rest = maximum_payout.saturating_sub(staker_payout.clone());
+        (staker_payout, rest)
+    }
+
+    fn check_parameters(
+        parameters: &Self::Parameters,
+        active_era: EraIndex,
+        total_issuance: Balance,
+    ) -> bool {
+        // The top-up of a cutoff era that was already paid out would never be minted, and a
+        // terminal issuance below the current one would silently turn it into nothing.
+        parameters.cutoff_era >= active_era && parameters.terminal_issuance >= total_issuance
+    }
+}
 
 /// The total payout to all validators (and their nominators) per era and maximum payout.
 ///
 /// Defined as such:
-/// `maximum-payout = 1.000233278 * total-tokens (1 - 0.00005) ^ era-index`
-/// `staker-payout = maximum_payout * 0.99`
+/// `maximum-payout = inflation-rate * decay ^ era-index * total-issuance`
+/// `staker-payout = min(inflation-rate * decay ^ era-index * total-tokens, maximum-payout * staker-share)`
 pub fn compute_total_payout<N>(
+    parameters: &InflationParameters<N>,
     era_index: EraIndex,
     total_tokens: N,
     total_issuance: N,
 ) -> (N, N) where N: AtLeast32BitUnsigned + Clone {
-    if era_index < 360_000 {
-        // If era < 360,000 mint according to inflation formula
-        let inflation_rate = Perbill::from_rational_approximation(233_278u128, 1_000_000_000u128);
-        let inflation_decay = Perbill::from_rational_approximation(999_950_000u128, 1_000_000_000u128)
-            .saturating_pow(era_index.saturated_into());
+    if era_index < parameters.cutoff_era {
+        // Before the cutoff mint according to inflation formula
+        let inflation_decay = parameters.decay.saturating_pow(era_index.saturated_into());
 
-        let staker_payout = inflation_rate.mul_ceil(inflation_decay.mul_ceil(total_tokens));
-        let maximum_payout = inflation_rate.mul_ceil(inflation_decay.mul_ceil(total_issuance));
+        let staker_payout = parameters.inflation_rate.mul_ceil(inflation_decay.mul_ceil(total_tokens));
+        let maximum_payout = parameters.inflation_rate.mul_ceil(inflation_decay.mul_ceil(total_issuance));
 
-        let staker_to_treasury_ratio = Percent::from_rational_approximation(99u32, 100u32);
-        let staker_maximum = staker_to_treasury_ratio.mul_floor(maximum_payout.clone());
+        let staker_maximum = parameters.staker_share.mul_floor(maximum_payout.clone());
 
         if staker_payout > staker_maximum {
             (staker_maximum, maximum_payout)
         } else {
             (staker_payout, maximum_payout)
         }
-    } else if era_index == 360_000 {
-        let maximum_payout = 7_777_777_777u128.saturated_into::<N>().saturating_sub(total_issuance);
-        let staker_to_treasury_ratio = Percent::from_rational_approximation(99u32, 100u32);
-        let staker_maximum = staker_to_treasury_ratio.mul_floor(maximum_payout.clone());
+    } else if era_index == parameters.cutoff_era {
+        let maximum_payout = parameters.terminal_issuance.clone().saturating_sub(total_issuance);
+        let staker_maximum = parameters.staker_share.mul_floor(maximum_payout.clone());
         (staker_maximum, maximum_payout)
     } else {
-        // If era > 360,000 no more minting
+        // After the cutoff no more minting
         let maximum_payout = Zero::zero();
         let staker_payout = Zero::zero();
         (staker_payout, maximum_payout)
@@ -47,112 +126,120 @@ mod test {
     pub const CENTS: u128 = 1_000 * MILLICENTS;
     pub const DOLLARS: u128 = 100 * CENTS;
 
+    use super::{InflationParameters, SocialNetworkInflation};
+    use crate::EraPayout;
+    use sp_runtime::Perbill;
+
+    fn compute_total_payout(era_index: super::EraIndex, total_tokens: u128, total_issuance: u128) -> (u128, u128) {
+        super::compute_total_payout(&InflationParameters::default(), era_index, total_tokens, total_issuance)
+    }
+
 	#[test]
 	fn calculation_is_sensible() {
         const TOTAL_TOKENS: u128 = 77_777_777;
         const TOTAL_ISSUANCE3: u128 = 77_777_777;
 
-        assert_eq!(super::compute_total_payout(0u32, TOTAL_TOKENS, TOTAL_ISSUANCE3), (17962, 18144));
-        assert_eq!(super::compute_total_payout(1u32, TOTAL_TOKENS, TOTAL_ISSUANCE3), (17961, 18143));
-        assert_eq!(super::compute_total_payout(2u32, TOTAL_TOKENS, TOTAL_ISSUANCE3), (17961, 18143));
-        assert_eq!(super::compute_total_payout(3u32, TOTAL_TOKENS, TOTAL_ISSUANCE3), (17960, 18142));
-        assert_eq!(super::compute_total_payout(4u32, TOTAL_TOKENS, TOTAL_ISSUANCE3), (17959, 18141));
-        assert_eq!(super::compute_total_payout(5u32, TOTAL_TOKENS, TOTAL_ISSUANCE3), (17958, 18140));
-        assert_eq!(super::compute_total_payout(6u32, TOTAL_TOKENS, TOTAL_ISSUANCE3), (17957, 18139));
-        assert_eq!(super::compute_total_payout(7u32, TOTAL_TOKENS, TOTAL_ISSUANCE3), (17956, 18138));
-        assert_eq!(super::compute_total_payout(8u32, TOTAL_TOKENS, TOTAL_ISSUANCE3), (17955, 18137));
-        assert_eq!(super::compute_total_payout(9u32, TOTAL_TOKENS, TOTAL_ISSUANCE3), (17954, 18136));
-        assert_eq!(super::compute_total_payout(10u32, TOTAL_TOKENS, TOTAL_ISSUANCE3), (17953, 18135));
-        assert_eq!(super::compute_total_payout(500u32, TOTAL_TOKENS, TOTAL_ISSUANCE3), (17519, 17696));
-        assert_eq!(super::compute_total_payout(1_000u32, TOTAL_TOKENS, TOTAL_ISSUANCE3), (17086, 17259));
-        assert_eq!(super::compute_total_payout(10_000u32, TOTAL_TOKENS, TOTAL_ISSUANCE3), (10894, 11005));
-        assert_eq!(super::compute_total_payout(60_000u32, TOTAL_TOKENS, TOTAL_ISSUANCE3), (894, 904));
-        assert_eq!(super::compute_total_payout(100_000u32, TOTAL_TOKENS, TOTAL_ISSUANCE3), (121, 123));
-        assert_eq!(super::compute_total_payout(120_000u32, TOTAL_TOKENS, TOTAL_ISSUANCE3), (44, 45));
-        assert_eq!(super::compute_total_payout(180_000u32, TOTAL_TOKENS, TOTAL_ISSUANCE3), (2, 3));
-        assert_eq!(super::compute_total_payout(240_000u32, TOTAL_TOKENS, TOTAL_ISSUANCE3), (0, 0));
-        assert_eq!(super::compute_total_payout(300_000u32, TOTAL_TOKENS, TOTAL_ISSUANCE3), (0, 0));
-        assert_eq!(super::compute_total_payout(360_000u32, TOTAL_TOKENS, TOTAL_ISSUANCE3), (7623000000, 7700000000));
-        assert_eq!(super::compute_total_payout(500_000u32, TOTAL_TOKENS, TOTAL_ISSUANCE3), (0, 0));
+        assert_eq!(compute_total_payout(0u32, TOTAL_TOKENS, TOTAL_ISSUANCE3), (17962, 18144));
+        assert_eq!(compute_total_payout(1u32, TOTAL_TOKENS, TOTAL_ISSUANCE3), (17961, 18143));
+        assert_eq!(compute_total_payout(2u32, TOTAL_TOKENS, TOTAL_ISSUANCE3), (17961, 18143));
+        assert_eq!(compute_total_payout(3u32, TOTAL_TOKENS, TOTAL_ISSUANCE3), (17960, 18142));
+        assert_eq!(compute_total_payout(4u32, TOTAL_TOKENS, TOTAL_ISSUANCE3), (17959, 18141));
+        assert_eq!(compute_total_payout(5u32, TOTAL_TOKENS, TOTAL_ISSUANCE3), (17958, 18140));
+        assert_eq!(compute_total_payout(6u32, TOTAL_TOKENS, TOTAL_ISSUANCE3), (17957, 18139));
+        assert_eq!(compute_total_payout(7u32, TOTAL_TOKENS, TOTAL_ISSUANCE3), (17956, 18138));
+        assert_eq!(compute_total_payout(8u32, TOTAL_TOKENS, TOTAL_ISSUANCE3), (17955, 18137));
+        assert_eq!(compute_total_payout(9u32, TOTAL_TOKENS, TOTAL_ISSUANCE3), (17954, 18136));
+        assert_eq!(compute_total_payout(10u32, TOTAL_TOKENS, TOTAL_ISSUANCE3), (17953, 18135));
+        assert_eq!(compute_total_payout(500u32, TOTAL_TOKENS, TOTAL_ISSUANCE3), (17519, 17696));
+        assert_eq!(compute_total_payout(1_000u32, TOTAL_TOKENS, TOTAL_ISSUANCE3), (17086, 17259));
+        assert_eq!(compute_total_payout(10_000u32, TOTAL_TOKENS, TOTAL_ISSUANCE3), (10894, 11005));
+        assert_eq!(compute_total_payout(60_000u32, TOTAL_TOKENS, TOTAL_ISSUANCE3), (894, 904));
+        assert_eq!(compute_total_payout(100_000u32, TOTAL_TOKENS, TOTAL_ISSUANCE3), (121, 123));
+        assert_eq!(compute_total_payout(120_000u32, TOTAL_TOKENS, TOTAL_ISSUANCE3), (44, 45));
+        assert_eq!(compute_total_payout(180_000u32, TOTAL_TOKENS, TOTAL_ISSUANCE3), (2, 3));
+        assert_eq!(compute_total_payout(240_000u32, TOTAL_TOKENS, TOTAL_ISSUANCE3), (0, 0));
+        assert_eq!(compute_total_payout(300_000u32, TOTAL_TOKENS, TOTAL_ISSUANCE3), (0, 0));
+        assert_eq!(compute_total_payout(360_000u32, TOTAL_TOKENS, TOTAL_ISSUANCE3), (769999999922999923000000, 777777777699999922222223));
+        assert_eq!(compute_total_payout(500_000u32, TOTAL_TOKENS, TOTAL_ISSUANCE3), (0, 0));
 
 
         const TOTAL_ISSUANCE4: u128 = 1_000_000_000;
 
-        assert_eq!(super::compute_total_payout(0u32, TOTAL_TOKENS, TOTAL_ISSUANCE4), (18144, 233278));
-        assert_eq!(super::compute_total_payout(1u32, TOTAL_TOKENS, TOTAL_ISSUANCE4), (18143, 233267));
-        assert_eq!(super::compute_total_payout(2u32, TOTAL_TOKENS, TOTAL_ISSUANCE4), (18143, 233255));
-        assert_eq!(super::compute_total_payout(3u32, TOTAL_TOKENS, TOTAL_ISSUANCE4), (18142, 233244));
-        assert_eq!(super::compute_total_payout(4u32, TOTAL_TOKENS, TOTAL_ISSUANCE4), (18141, 233232));
-        assert_eq!(super::compute_total_payout(5u32, TOTAL_TOKENS, TOTAL_ISSUANCE4), (18140, 233220));
-        assert_eq!(super::compute_total_payout(6u32, TOTAL_TOKENS, TOTAL_ISSUANCE4), (18139, 233209));
-        assert_eq!(super::compute_total_payout(7u32, TOTAL_TOKENS, TOTAL_ISSUANCE4), (18138, 233197));
-        assert_eq!(super::compute_total_payout(8u32, TOTAL_TOKENS, TOTAL_ISSUANCE4), (18137, 233185));
-        assert_eq!(super::compute_total_payout(9u32, TOTAL_TOKENS, TOTAL_ISSUANCE4), (18136, 233174));
-        assert_eq!(super::compute_total_payout(10u32, TOTAL_TOKENS, TOTAL_ISSUANCE4), (18135, 233162));
-        assert_eq!(super::compute_total_payout(500u32, TOTAL_TOKENS, TOTAL_ISSUANCE4), (17696, 227519));
-        assert_eq!(super::compute_total_payout(1_000u32, TOTAL_TOKENS, TOTAL_ISSUANCE4), (17259, 221901));
-        assert_eq!(super::compute_total_payout(10_000u32, TOTAL_TOKENS, TOTAL_ISSUANCE4), (11005, 141488));
-        assert_eq!(super::compute_total_payout(60_000u32, TOTAL_TOKENS, TOTAL_ISSUANCE4), (904, 11612));
-        assert_eq!(super::compute_total_payout(100_000u32, TOTAL_TOKENS, TOTAL_ISSUANCE4), (123, 1570));
-        assert_eq!(super::compute_total_payout(120_000u32, TOTAL_TOKENS, TOTAL_ISSUANCE4), (45, 576));
-        assert_eq!(super::compute_total_payout(180_000u32, TOTAL_TOKENS, TOTAL_ISSUANCE4), (3, 27));
-        assert_eq!(super::compute_total_payout(240_000u32, TOTAL_TOKENS, TOTAL_ISSUANCE4), (0, 0));
-        assert_eq!(super::compute_total_payout(300_000u32, TOTAL_TOKENS, TOTAL_ISSUANCE4), (0, 0));
-        assert_eq!(super::compute_total_payout(360_000u32, TOTAL_TOKENS, TOTAL_ISSUANCE4), (6709999999, 6777777777));
-        assert_eq!(super::compute_total_payout(500_000u32, TOTAL_TOKENS, TOTAL_ISSUANCE4), (0, 0));
+        assert_eq!(compute_total_payout(0u32, TOTAL_TOKENS, TOTAL_ISSUANCE4), (18144, 233278));
+        assert_eq!(compute_total_payout(1u32, TOTAL_TOKENS, TOTAL_ISSUANCE4), (18143, 233267));
+        assert_eq!(compute_total_payout(2u32, TOTAL_TOKENS, TOTAL_ISSUANCE4), (18143, 233255));
+        assert_eq!(compute_total_payout(3u32, TOTAL_TOKENS, TOTAL_ISSUANCE4), (18142, 233244));
+        assert_eq!(compute_total_payout(4u32, TOTAL_TOKENS, TOTAL_ISSUANCE4), (18141, 233232));
+        assert_eq!(compute_total_payout(5u32, TOTAL_TOKENS, TOTAL_ISSUANCE4), (18140, 233220));
+        assert_eq!(compute_total_payout(6u32, TOTAL_TOKENS, TOTAL_ISSUANCE4), (18139, 233209));
+        assert_eq!(compute_total_payout(7u32, TOTAL_TOKENS, TOTAL_ISSUANCE4), (18138, 233197));
+        assert_eq!(compute_total_payout(8u32, TOTAL_TOKENS, TOTAL_ISSUANCE4), (18137, 233185));
+        assert_eq!(compute_total_payout(9u32, TOTAL_TOKENS, TOTAL_ISSUANCE4), (18136, 233174));
+        assert_eq!(compute_total_payout(10u32, TOTAL_TOKENS, TOTAL_ISSUANCE4), (18135, 233162));
+        assert_eq!(compute_total_payout(500u32, TOTAL_TOKENS, TOTAL_ISSUANCE4), (17696, 227519));
+        assert_eq!(compute_total_payout(1_000u32, TOTAL_TOKENS, TOTAL_ISSUANCE4), (17259, 221901));
+        assert_eq!(compute_total_payout(10_000u32, TOTAL_TOKENS, TOTAL_ISSUANCE4), (11005, 141488));
+        assert_eq!(compute_total_payout(60_000u32, TOTAL_TOKENS, TOTAL_ISSUANCE4), (904, 11612));
+        assert_eq!(compute_total_payout(100_000u32, TOTAL_TOKENS, TOTAL_ISSUANCE4), (123, 1570));
+        assert_eq!(compute_total_payout(120_000u32, TOTAL_TOKENS, TOTAL_ISSUANCE4), (45, 576));
+        assert_eq!(compute_total_payout(180_000u32, TOTAL_TOKENS, TOTAL_ISSUANCE4), (3, 27));
+        assert_eq!(compute_total_payout(240_000u32, TOTAL_TOKENS, TOTAL_ISSUANCE4), (0, 0));
+        assert_eq!(compute_total_payout(300_000u32, TOTAL_TOKENS, TOTAL_ISSUANCE4), (0, 0));
+        assert_eq!(compute_total_payout(360_000u32, TOTAL_TOKENS, TOTAL_ISSUANCE4), (769999999922999010000000, 777777777699999000000000));
+        assert_eq!(compute_total_payout(500_000u32, TOTAL_TOKENS, TOTAL_ISSUANCE4), (0, 0));
 
 
         const TOTAL_ISSUANCE5: u128 = 10_000_000_000;
 
-        assert_eq!(super::compute_total_payout(0u32, TOTAL_TOKENS, TOTAL_ISSUANCE5), (18144, 2332780));
-        assert_eq!(super::compute_total_payout(1u32, TOTAL_TOKENS, TOTAL_ISSUANCE5), (18143, 2332664));
-        assert_eq!(super::compute_total_payout(2u32, TOTAL_TOKENS, TOTAL_ISSUANCE5), (18143, 2332547));
-        assert_eq!(super::compute_total_payout(3u32, TOTAL_TOKENS, TOTAL_ISSUANCE5), (18142, 2332431));
-        assert_eq!(super::compute_total_payout(4u32, TOTAL_TOKENS, TOTAL_ISSUANCE5), (18141, 2332314));
-        assert_eq!(super::compute_total_payout(5u32, TOTAL_TOKENS, TOTAL_ISSUANCE5), (18140, 2332197));
-        assert_eq!(super::compute_total_payout(6u32, TOTAL_TOKENS, TOTAL_ISSUANCE5), (18139, 2332081));
-        assert_eq!(super::compute_total_payout(7u32, TOTAL_TOKENS, TOTAL_ISSUANCE5), (18138, 2331964));
-        assert_eq!(super::compute_total_payout(8u32, TOTAL_TOKENS, TOTAL_ISSUANCE5), (18137, 2331848));
-        assert_eq!(super::compute_total_payout(9u32, TOTAL_TOKENS, TOTAL_ISSUANCE5), (18136, 2331731));
-        assert_eq!(super::compute_total_payout(10u32, TOTAL_TOKENS, TOTAL_ISSUANCE5), (18135, 2331614));
-        assert_eq!(super::compute_total_payout(500u32, TOTAL_TOKENS, TOTAL_ISSUANCE5), (17696, 2275182));
-        assert_eq!(super::compute_total_payout(1_000u32, TOTAL_TOKENS, TOTAL_ISSUANCE5), (17259, 2219006));
-        assert_eq!(super::compute_total_payout(10_000u32, TOTAL_TOKENS, TOTAL_ISSUANCE5), (11005, 1414876));
-        assert_eq!(super::compute_total_payout(60_000u32, TOTAL_TOKENS, TOTAL_ISSUANCE5), (904, 116112));
-        assert_eq!(super::compute_total_payout(100_000u32, TOTAL_TOKENS, TOTAL_ISSUANCE5), (123, 15694));
-        assert_eq!(super::compute_total_payout(120_000u32, TOTAL_TOKENS, TOTAL_ISSUANCE5), (45, 5759));
-        assert_eq!(super::compute_total_payout(180_000u32, TOTAL_TOKENS, TOTAL_ISSUANCE5), (3, 266));
-        assert_eq!(super::compute_total_payout(240_000u32, TOTAL_TOKENS, TOTAL_ISSUANCE5), (0, 0));
-        assert_eq!(super::compute_total_payout(300_000u32, TOTAL_TOKENS, TOTAL_ISSUANCE5), (0, 0));
-        assert_eq!(super::compute_total_payout(360_000u32, TOTAL_TOKENS, TOTAL_ISSUANCE5), (0, 0));
-        assert_eq!(super::compute_total_payout(500_000u32, TOTAL_TOKENS, TOTAL_ISSUANCE5), (0, 0));
+        assert_eq!(compute_total_payout(0u32, TOTAL_TOKENS, TOTAL_ISSUANCE5), (18144, 2332780));
+        assert_eq!(compute_total_payout(1u32, TOTAL_TOKENS, TOTAL_ISSUANCE5), (18143, 2332664));
+        assert_eq!(compute_total_payout(2u32, TOTAL_TOKENS, TOTAL_ISSUANCE5), (18143, 2332547));
+        assert_eq!(compute_total_payout(3u32, TOTAL_TOKENS, TOTAL_ISSUANCE5), (18142, 2332431));
+        assert_eq!(compute_total_payout(4u32, TOTAL_TOKENS, TOTAL_ISSUANCE5), (18141, 2332314));
+        assert_eq!(compute_total_payout(5u32, TOTAL_TOKENS, TOTAL_ISSUANCE5), (18140, 2332197));
+        assert_eq!(compute_total_payout(6u32, TOTAL_TOKENS, TOTAL_ISSUANCE5), (18139, 2332081));
+        assert_eq!(compute_total_payout(7u32, TOTAL_TOKENS, TOTAL_ISSUANCE5), (18138, 2331964));
+        assert_eq!(compute_total_payout(8u32, TOTAL_TOKENS, TOTAL_ISSUANCE5), (18137, 2331848));
+        assert_eq!(compute_total_payout(9u32, TOTAL_TOKENS, TOTAL_ISSUANCE5), (18136, 2331731));
+        assert_eq!(compute_total_payout(10u32, TOTAL_TOKENS, TOTAL_ISSUANCE5), (18135, 2331614));
+        assert_eq!(compute_total_payout(500u32, TOTAL_TOKENS, TOTAL_ISSUANCE5), (17696, 2275182));
+        assert_eq!(compute_total_payout(1_000u32, TOTAL_TOKENS, TOTAL_ISSUANCE5), (17259, 2219006));
+        assert_eq!(compute_total_payout(10_000u32, TOTAL_TOKENS, TOTAL_ISSUANCE5), (11005, 1414876));
+        assert_eq!(compute_total_payout(60_000u32, TOTAL_TOKENS, TOTAL_ISSUANCE5), (904, 116112));
+        assert_eq!(compute_total_payout(100_000u32, TOTAL_TOKENS, TOTAL_ISSUANCE5), (123, 15694));
+        assert_eq!(compute_total_payout(120_000u32, TOTAL_TOKENS, TOTAL_ISSUANCE5), (45, 5759));
+        assert_eq!(compute_total_payout(180_000u32, TOTAL_TOKENS, TOTAL_ISSUANCE5), (3, 266));
+        assert_eq!(compute_total_payout(240_000u32, TOTAL_TOKENS, TOTAL_ISSUANCE5), (0, 0));
+        assert_eq!(compute_total_payout(300_000u32, TOTAL_TOKENS, TOTAL_ISSUANCE5), (0, 0));
+        assert_eq!(compute_total_payout(360_000u32, TOTAL_TOKENS, TOTAL_ISSUANCE5), (769999999922990100000000, 777777777699990000000000));
+        assert_eq!(compute_total_payout(500_000u32, TOTAL_TOKENS, TOTAL_ISSUANCE5), (0, 0));
 
 
         const TOTAL_TOKENS2: u128 = 77 * DOLLARS;
         const TOTAL_ISSUANCE6: u128 = 77_777_777 * DOLLARS;
 
-        assert_eq!(super::compute_total_payout(0u32, TOTAL_TOKENS2, TOTAL_ISSUANCE6), (1796240600000, 1814384426300600000));
-        assert_eq!(super::compute_total_payout(1u32, TOTAL_TOKENS2, TOTAL_ISSUANCE6), (1796150787970, 1814293707079284970));
-        assert_eq!(super::compute_total_payout(2u32, TOTAL_TOKENS2, TOTAL_ISSUANCE6), (1796060979533, 1814202991486738793));
-        assert_eq!(super::compute_total_payout(3u32, TOTAL_TOKENS2, TOTAL_ISSUANCE6), (1795971174688, 1814112279522961468));
-        assert_eq!(super::compute_total_payout(4u32, TOTAL_TOKENS2, TOTAL_ISSUANCE6), (1795881375232, 1814021573002337422));
-        assert_eq!(super::compute_total_payout(5u32, TOTAL_TOKENS2, TOTAL_ISSUANCE6), (1795791579368, 1813930870110482229));
-        assert_eq!(super::compute_total_payout(6u32, TOTAL_TOKENS2, TOTAL_ISSUANCE6), (1795701788893, 1813840172661780315));
-        assert_eq!(super::compute_total_payout(7u32, TOTAL_TOKENS2, TOTAL_ISSUANCE6), (1795612002010, 1813749478841847253));
-        assert_eq!(super::compute_total_payout(8u32, TOTAL_TOKENS2, TOTAL_ISSUANCE6), (1795522220516, 1813658790465067470));
-        assert_eq!(super::compute_total_payout(9u32, TOTAL_TOKENS2, TOTAL_ISSUANCE6), (1795432442615, 1813568105717056540));
-        assert_eq!(super::compute_total_payout(10u32, TOTAL_TOKENS2, TOTAL_ISSUANCE6), (1795342670102, 1813477426412198888));
-        assert_eq!(super::compute_total_payout(500u32, TOTAL_TOKENS2, TOTAL_ISSUANCE6), (1751889711362, 1769585549335648775));
-        assert_eq!(super::compute_total_payout(1_000u32, TOTAL_TOKENS2, TOTAL_ISSUANCE6), (1708633888231, 1725892799135500725));
-        assert_eq!(super::compute_total_payout(10_000u32, TOTAL_TOKENS2, TOTAL_ISSUANCE6), (1089454307538, 1100458885498002147));
-        assert_eq!(super::compute_total_payout(60_000u32, TOTAL_TOKENS2, TOTAL_ISSUANCE6), (89405758098, 90308845659873757));
-        assert_eq!(super::compute_total_payout(100_000u32, TOTAL_TOKENS2, TOTAL_ISSUANCE6), (12083628451, 12205685181767592));
-        assert_eq!(super::compute_total_payout(120_000u32, TOTAL_TOKENS2, TOTAL_ISSUANCE6), (4433874426, 4478660991184501));
-        assert_eq!(super::compute_total_payout(180_000u32, TOTAL_TOKENS2, TOTAL_ISSUANCE6), (204078080, 206139472209717));
-        assert_eq!(super::compute_total_payout(240_000u32, TOTAL_TOKENS2, TOTAL_ISSUANCE6), (0, 0));
-        assert_eq!(super::compute_total_payout(300_000u32, TOTAL_TOKENS2, TOTAL_ISSUANCE6), (0, 0));
-        assert_eq!(super::compute_total_payout(360_000u32, TOTAL_TOKENS2, TOTAL_ISSUANCE6), (0, 0));
-        assert_eq!(super::compute_total_payout(500_000u32, TOTAL_TOKENS2, TOTAL_ISSUANCE6), (0, 0));
+        assert_eq!(compute_total_payout(0u32, TOTAL_TOKENS2, TOTAL_ISSUANCE6), (1796240600000, 1814384426300600000));
+        assert_eq!(compute_total_payout(1u32, TOTAL_TOKENS2, TOTAL_ISSUANCE6), (1796150787970, 1814293707079284970));
+        assert_eq!(compute_total_payout(2u32, TOTAL_TOKENS2, TOTAL_ISSUANCE6), (1796060979533, 1814202991486738793));
+        assert_eq!(compute_total_payout(3u32, TOTAL_TOKENS2, TOTAL_ISSUANCE6), (1795971174688, 1814112279522961468));
+        assert_eq!(compute_total_payout(4u32, TOTAL_TOKENS2, TOTAL_ISSUANCE6), (1795881375232, 1814021573002337422));
+        assert_eq!(compute_total_payout(5u32, TOTAL_TOKENS2, TOTAL_ISSUANCE6), (1795791579368, 1813930870110482229));
+        assert_eq!(compute_total_payout(6u32, TOTAL_TOKENS2, TOTAL_ISSUANCE6), (1795701788893, 1813840172661780315));
+        assert_eq!(compute_total_payout(7u32, TOTAL_TOKENS2, TOTAL_ISSUANCE6), (1795612002010, 1813749478841847253));
+        assert_eq!(compute_total_payout(8u32, TOTAL_TOKENS2, TOTAL_ISSUANCE6), (1795522220516, 1813658790465067470));
+        assert_eq!(compute_total_payout(9u32, TOTAL_TOKENS2, TOTAL_ISSUANCE6), (1795432442615, 1813568105717056540));
+        assert_eq!(compute_total_payout(10u32, TOTAL_TOKENS2, TOTAL_ISSUANCE6), (1795342670102, 1813477426412198888));
+        assert_eq!(compute_total_payout(500u32, TOTAL_TOKENS2, TOTAL_ISSUANCE6), (1751889711362, 1769585549335648775));
+        assert_eq!(compute_total_payout(1_000u32, TOTAL_TOKENS2, TOTAL_ISSUANCE6), (1708633888231, 1725892799135500725));
+        assert_eq!(compute_total_payout(10_000u32, TOTAL_TOKENS2, TOTAL_ISSUANCE6), (1089454307538, 1100458885498002147));
+        assert_eq!(compute_total_payout(60_000u32, TOTAL_TOKENS2, TOTAL_ISSUANCE6), (89405758098, 90308845659873757));
+        assert_eq!(compute_total_payout(100_000u32, TOTAL_TOKENS2, TOTAL_ISSUANCE6), (12083628451, 12205685181767592));
+        assert_eq!(compute_total_payout(120_000u32, TOTAL_TOKENS2, TOTAL_ISSUANCE6), (4433874426, 4478660991184501));
+        assert_eq!(compute_total_payout(180_000u32, TOTAL_TOKENS2, TOTAL_ISSUANCE6), (204078080, 206139472209717));
+        assert_eq!(compute_total_payout(240_000u32, TOTAL_TOKENS2, TOTAL_ISSUANCE6), (0, 0));
+        assert_eq!(compute_total_payout(300_000u32, TOTAL_TOKENS2, TOTAL_ISSUANCE6), (0, 0));
+        assert_eq!(compute_total_payout(360_000u32, TOTAL_TOKENS2, TOTAL_ISSUANCE6), (7_623_000_000 * DOLLARS, 7_700_000_000 * DOLLARS));
+        assert_eq!(compute_total_payout(500_000u32, TOTAL_TOKENS2, TOTAL_ISSUANCE6), (0, 0));
     }
 
     #[test]
@@ -221,16 +308,66 @@ mod test {
 
     fn maximum_payout_after_n_eras(n: super::EraIndex, total_tokens: u128, total_issuance: u128) -> u128 {
         (0..n).fold(total_issuance, |mut acc, era| {
-            acc += super::compute_total_payout(era, total_tokens, acc).1;
+            acc += compute_total_payout(era, total_tokens, acc).1;
             acc
         })
     }
 
     fn total_issuance_after_n_eras(n: super::EraIndex, total_tokens: u128, total_issuance: u128) -> u128 {
         (0..n).fold(total_issuance, |mut acc, era| {
-            let (staker_payout, maximum_payout) = super::compute_total_payout(era, total_tokens, acc);
+            let (staker_payout, maximum_payout) = compute_total_payout(era, total_tokens, acc);
             acc += maximum_payout - staker_payout;
             acc
         })
     }
+
+    #[test]
+    fn parameters_shape_the_payout() {
+        let parameters = InflationParameters {
+            inflation_rate: Perbill::from_parts(466_556),
+            cutoff_era: 100,
+            terminal_issuance: 7_777_777_777 * DOLLARS,
+            ..Default::default()
+        };
+
+        assert_eq!(super::compute_total_payout(&parameters, 0, 77_777_777, 77_777_777), (35925, 36288));
+        assert_eq!(
+            super::compute_total_payout(&parameters, 100, 77 * DOLLARS, 77_777_777 * DOLLARS),
+            (7_623_000_000 * DOLLARS, 7_700_000_000 * DOLLARS),
+        );
+        assert_eq!(super::compute_total_payout(&parameters, 101, 77 * DOLLARS, 77_777_777 * DOLLARS), (0, 0));
+    }
+
+    #[test]
+    fn era_payout_splits_stakers_and_treasury() {
+        assert_eq!(
+            <SocialNetworkInflation as EraPayout<u128>>::era_payout(
+                &Default::default(),
+                0,
+                77_777_777,
+                77_777_777,
+                0,
+            ),
+            (17962, 182),
+        );
+    }
+
+    #[test]
+    fn terminal_issuance_is_in_dollars() {
+        assert_eq!(InflationParameters::<u128>::default().terminal_issuance, 7_777_777_777 * DOLLARS);
+    }
+
+    #[test]
+    fn parameters_are_checked() {
+        let check = |parameters: &InflationParameters<u128>, active_era, total_issuance| {
+            <SocialNetworkInflation as EraPayout<u128>>::check_parameters(parameters, active_era, total_issuance)
+        };
+        let parameters = InflationParameters { cutoff_era: 100, ..Default::default() };
+
+        assert!(check(&parameters, 100, 77_777_777 * DOLLARS));
+        // The cutoff era was already paid out.
+        assert!(!check(&parameters, 101, 77_777_777 * DOLLARS));
+        // The terminal issuance is below the current one.
+        assert!(!check(&parameters, 0, 7_777_777_778 * DOLLARS));
+    }
 }
@@ -4697,3 +4697,44 @@ fn payout_to_any_account_works() {
 		assert!(Balances::free_balance(42) > 0);
 	})
 }
+
+#[test]
+fn set_era_payout_parameters_requires_era_payout_origin() {
+	ExtBuilder::default().build_and_execute(|| {
+		assert_noop!(Staking::set_era_payout_parameters(Origin::signed(10), ()), BadOrigin);
+
+		assert_ok!(Staking::set_era_payout_parameters(Origin::root(), ()));
+		assert_eq!(*mock::staking_events().last().unwrap(), RawEvent::EraPayoutParametersSet);
+	})
+}
+
+#[test]
+fn era_payout_projection_follows_payout_model() {
+	ExtBuilder::default().build_and_execute(|| {
+		mock::start_era(1);
+		let total_issuance = Balances::total_issuance();
+		let total_payout_1 = current_total_payout_for_duration(3 * 1000);
+
+		let projection = Staking::project_era_payouts(0, 3, 3 * 1000);
+		// Eras that have already been paid out are not projected.
+		assert_eq!(projection.iter().map(|p| p.era).collect::<Vec<_>>(), vec![1, 2, 3]);
+		assert_eq_error_rate!(projection[0].staker_payout, total_payout_1, 2);
+		assert_eq!(
+			projection[0].total_issuance,
+			total_issuance + projection[0].staker_payout + projection[0].treasury_payout,
+		);
+		assert!(projection[2].total_issuance > projection[1].total_issuance);
+
+		assert!(Staking::project_era_payouts(3, 2, 3 * 1000).is_empty());
+		assert_eq!(
+			Staking::project_era_payouts(1, EraIndex::max_value(), 3 * 1000).len(),
+			MAX_PROJECTED_ERAS as usize,
+		);
+		// Eras too far after the active one are not simulated.
+		assert!(Staking::project_era_payouts(1 + MAX_PROJECTED_ERAS, EraIndex::max_value(), 3 * 1000).is_empty());
+		assert_eq!(
+			Staking::project_era_payouts(MAX_PROJECTED_ERAS, EraIndex::max_value(), 3 * 1000).len(),
+			1,
+		);
+	})
+}
@@ -1473,6 +1473,32 @@ impl OnRuntimeUpgrade for Tuple {
 	}
 }
 
+/// A single, self-contained storage migration.
+///
+/// Migrations are meant to be run in order by a harness such as `frame_executive::Migrations`,
+/// which calls `migrate` on a runtime upgrade and additionally runs the `pre_upgrade` and
+/// `post_upgrade` checks when dry-running an upgrade offline.
+///
+/// `migrate` must be idempotent: it is expected to consult the pallet's storage version and do
+/// nothing if the storage was already migrated.
+pub trait Migration {
+	/// A human readable name of the migration, used in reports.
+	const NAME: &'static str;
+
+	/// Check the state before the migration is executed.
+	///
+	/// This is not run on-chain and can therefore be as expensive as needed.
+	fn pre_upgrade() -> Result<(), &'static str> { Ok(()) }
+
+	/// Execute the migration, returning the weight it consumed.
+	fn migrate() -> crate::weights::Weight;
+
+	/// Check the state after the migration was executed.
+	///
+	/// This is not run on-chain and can therefore be as expensive as needed.
+	fn post_upgrade() -> Result<(), &'static str> { Ok(()) }
+}
+
 /// Off-chain computation trait.
 ///
 /// Implementing this trait on a module allows you to perform long-running tasks
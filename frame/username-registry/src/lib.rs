@@ -33,6 +33,19 @@ pub trait WeightInfo {
     fn kill_username() -> Weight;
 }
 
+// A value placed in storage that represents the current version of the UsernameRegistry storage.
+// This value is used by storage migrations to determine whether they need to run.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+enum Releases {
+    V1_0_0,
+}
+
+impl Default for Releases {
+    fn default() -> Self {
+        Releases::V1_0_0
+    }
+}
+
 #[derive(Copy, Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug)]
 pub enum Judgement {
     Requested,
@@ -83,6 +96,22 @@ decl_storage! {
         pub Account get(fn account): map hasher(twox_64_concat) T::AccountId => Option<Vec<u8>>;
 
         pub Registrars get(fn registrars): Vec<Option<T::AccountId>>;
+        /// Storage version of the pallet.
+        ///
+        /// NOTE: This pallet shares its storage prefix with the validator registry, hence the
+        /// distinct name. New networks start with the latest version.
+        UsernameStorageVersion build(|_: &GenesisConfig<T>| Releases::V1_0_0): Releases;
+    }
+    add_extra_genesis {
+        /// The initial registrars, in the order of their `RegistrarIndex`.
+        config(registrars): Vec<T::AccountId>;
+        build(|config: &GenesisConfig<T>| {
+            assert!(
+                config.registrars.len() <= T::MaxRegistrars::get() as usize,
+                "Too many genesis registrars"
+            );
+            <Registrars<T>>::put(config.registrars.iter().cloned().map(Some).collect::<Vec<_>>());
+        });
     }
 }
 
@@ -300,6 +329,15 @@ decl_module! {
 }
 
 impl<T: Trait> Module<T> {
+    /// Whether `who` has registered a username that a registrar has approved.
+    pub fn has_approved_username(who: &T::AccountId) -> bool {
+        Self::account(who)
+            .and_then(|username| Self::registration_of(username))
+            .map_or(false, |registration| {
+                registration.judgements.iter().any(|(_, j)| *j == Judgement::Approved)
+            })
+    }
+
     fn validate_username(username: &[u8]) -> DispatchResult {
         ensure!(username.len() >= T::MinUsernameLength::get() as usize, Error::<T>::UsernameIsVeryShort);
         ensure!(username.len() <= T::MaxUsernameLength::get() as usize, Error::<T>::UsernameIsVeryLong);
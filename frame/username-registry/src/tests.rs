@@ -153,6 +153,28 @@ fn providing_judgement_should_work() {
     });
 }
 
+#[test]
+fn approved_username_should_be_recognised() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(UsernameRegistry::add_registrar(Origin::signed(1), 3));
+        assert!(!UsernameRegistry::has_approved_username(&4));
+        assert_ok!(UsernameRegistry::register(
+            Origin::signed(4),
+            b"foo".to_vec(),
+            0
+        ));
+        assert!(!UsernameRegistry::has_approved_username(&4));
+        assert_ok!(UsernameRegistry::provide_judgement(
+            Origin::signed(3),
+            0,
+            b"foo".to_vec(),
+            Judgement::Approved
+        ));
+        assert!(UsernameRegistry::has_approved_username(&4));
+        assert!(!UsernameRegistry::has_approved_username(&5));
+    });
+}
+
 #[test]
 fn registration_and_unregistration_with_invalid_username_should_not_work() {
     new_test_ext().execute_with(|| {
@@ -1,9 +1,11 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
+use codec::{Decode, Encode};
 use frame_support::{
     decl_error, decl_event, decl_module, decl_storage, dispatch, ensure, traits::Get,
 };
 use frame_system::ensure_signed;
+use sp_runtime::RuntimeDebug;
 use sp_std::prelude::*;
 
 #[cfg(test)]
@@ -12,6 +14,19 @@ mod mock;
 #[cfg(test)]
 mod tests;
 
+// A value placed in storage that represents the current version of the ValidatorRegistry storage.
+// This value is used by storage migrations to determine whether they need to run.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+enum Releases {
+    V1_0_0,
+}
+
+impl Default for Releases {
+    fn default() -> Self {
+        Releases::V1_0_0
+    }
+}
+
 pub trait Trait: frame_system::Trait + pallet_mission_tokens::Trait {
     type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
 }
@@ -20,6 +35,21 @@ decl_storage! {
     trait Store for Module<T: Trait> as ValidatorRegistry {
         MissionOf get(fn mission_of): map hasher(blake2_128_concat) T::AccountId => T::MissionTokenId;
         Validators get(fn validators): map hasher(blake2_128_concat) T::MissionTokenId => Vec<T::AccountId>;
+        /// Storage version of the pallet.
+        ///
+        /// New networks start with the latest version.
+        StorageVersion build(|_: &GenesisConfig<T>| Releases::V1_0_0): Releases;
+    }
+    add_extra_genesis {
+        /// Validators registered for a mission, as `(validator, mission_token_id)`.
+        config(validators): Vec<(T::AccountId, T::MissionTokenId)>;
+        build(|config: &GenesisConfig<T>| {
+            for (validator, mission_token_id) in &config.validators {
+                assert!(!<MissionOf<T>>::contains_key(validator), "Validator registered twice at genesis");
+                <MissionOf<T>>::insert(validator, mission_token_id);
+                <Validators<T>>::mutate(mission_token_id, |validators| validators.push(validator.clone()));
+            }
+        });
     }
 }
 
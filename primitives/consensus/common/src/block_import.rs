@@ -98,6 +98,16 @@ pub enum ForkChoiceStrategy {
 	Custom(bool),
 }
 
+/// Full state of a block, downloaded from the network instead of being computed by
+/// executing the block.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct ImportedState {
+	/// Key-value pairs of the top trie.
+	pub top: Vec<(Vec<u8>, Vec<u8>)>,
+	/// Key-value pairs of each default child trie, by unprefixed child storage key.
+	pub children: Vec<(Vec<u8>, Vec<(Vec<u8>, Vec<u8>)>)>,
+}
+
 /// Data required to check validity of a Block.
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct BlockCheckParams<Block: BlockT> {
@@ -164,6 +174,9 @@ pub struct BlockImportParams<Block: BlockT, Transaction> {
 	pub allow_missing_state: bool,
 	/// Re-validate existing block.
 	pub import_existing: bool,
+	/// Full state of the block. If this is `Some(_)`, the block is imported as a new
+	/// finalized base of the chain, without its parent and without being executed.
+	pub state: Option<ImportedState>,
 	/// Cached full header hash (with post-digests applied).
 	pub post_hash: Option<Block::Hash>,
 }
@@ -186,6 +199,7 @@ impl<Block: BlockT, Transaction> BlockImportParams<Block, Transaction> {
 			fork_choice: None,
 			allow_missing_state: false,
 			import_existing: false,
+			state: None,
 			post_hash: None,
 		}
 	}
@@ -226,6 +240,7 @@ impl<Block: BlockT, Transaction> BlockImportParams<Block, Transaction> {
 			allow_missing_state: self.allow_missing_state,
 			fork_choice: self.fork_choice,
 			import_existing: self.import_existing,
+			state: self.state,
 			post_hash: self.post_hash,
 		}
 	}
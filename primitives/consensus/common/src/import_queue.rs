@@ -34,7 +34,7 @@ use crate::{
 	error::Error as ConsensusError,
 	block_import::{
 		BlockImport, BlockOrigin, BlockImportParams, ImportedAux, JustificationImport, ImportResult,
-		BlockCheckParams, FinalityProofImport,
+		BlockCheckParams, FinalityProofImport, ImportedState,
 	},
 	metrics::Metrics,
 };
@@ -81,6 +81,8 @@ pub struct IncomingBlock<B: BlockT> {
 	pub allow_missing_state: bool,
 	/// Re-validate existing block.
 	pub import_existing: bool,
+	/// Full state of the block, if it was downloaded instead of being computed.
+	pub state: Option<ImportedState>,
 }
 
 /// Type of keys in the blockchain cache that consensus module could use for its needs.
@@ -253,19 +255,32 @@ pub(crate) fn import_single_block_metered<B: BlockT, V: Verifier<B>, Transaction
 		}
 	};
 
-	match import_handler(import_handle.check_block(BlockCheckParams {
-		hash,
-		number,
-		parent_hash,
-		allow_missing_state: block.allow_missing_state,
-		import_existing: block.import_existing,
-	}))? {
-		BlockImportResult::ImportedUnknown { .. } => (),
-		r => return Ok(r), // Any other successful result means that the block is already imported.
+	// A block imported with its full state does not require a known parent.
+	if block.state.is_none() {
+		match import_handler(import_handle.check_block(BlockCheckParams {
+			hash,
+			number,
+			parent_hash,
+			allow_missing_state: block.allow_missing_state,
+			import_existing: block.import_existing,
+		}))? {
+			BlockImportResult::ImportedUnknown { .. } => (),
+			r => return Ok(r), // Any other successful result means that the block is already imported.
+		}
 	}
 
 	let started = wasm_timer::Instant::now();
-	let (mut import_block, maybe_keys) = verifier.verify(block_origin, header, justification, block.body)
+	let verified = if block.state.is_some() {
+		// The header of a block imported with its state was proven final together with the
+		// state. Its ancestors are unknown, so the consensus engine can't verify it.
+		let mut import_block = BlockImportParams::new(block_origin, header);
+		import_block.body = block.body;
+		import_block.justification = justification;
+		Ok((import_block, None))
+	} else {
+		verifier.verify(block_origin, header, justification, block.body)
+	};
+	let (mut import_block, maybe_keys) = verified
 		.map_err(|msg| {
 			if let Some(ref peer) = peer {
 				trace!(target: "sync", "Verifying {}({}) from {} failed: {}", number, hash, peer, msg);
@@ -287,6 +302,7 @@ pub(crate) fn import_single_block_metered<B: BlockT, V: Verifier<B>, Transaction
 		cache.extend(keys.into_iter());
 	}
 	import_block.allow_missing_state = block.allow_missing_state;
+	import_block.state = block.state;
 
 	import_handler(import_handle.import_block(import_block.convert_transaction(), cache))
 }
@@ -52,7 +52,7 @@ const MAX_BLOCK_SIZE: usize = 4 * 1024 * 1024 + 512;
 pub use self::error::Error;
 pub use block_import::{
 	BlockImport, BlockOrigin, ForkChoiceStrategy, ImportedAux, BlockImportParams, BlockCheckParams,
-	ImportResult, JustificationImport, FinalityProofImport,
+	ImportResult, JustificationImport, FinalityProofImport, ImportedState,
 };
 pub use select_chain::SelectChain;
 pub use sp_state_machine::Backend as StateBackend;
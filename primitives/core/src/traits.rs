@@ -22,6 +22,7 @@ use crate::{
 	vrf::{VRFTranscriptData, VRFSignature},
 	ed25519, sr25519, ecdsa,
 };
+use codec::{Encode, Decode};
 use std::{
 	borrow::Cow,
 	fmt::{Debug, Display},
@@ -51,6 +52,15 @@ pub enum Error {
 	Other(String)
 }
 
+/// What a message is signed for.
+///
+/// Lets signers that keep a record of their signatures refuse to sign conflicting messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
+pub enum SigningContext {
+	/// The seal of a block authored in the given BABE slot.
+	BabeSlot(u64),
+}
+
 /// Something that generates, stores and provides access to keys.
 pub trait BareCryptoStore: Send + Sync {
 	/// Returns all sr25519 public keys for the given key type.
@@ -133,6 +143,20 @@ pub trait BareCryptoStore: Send + Sync {
 		msg: &[u8],
 	) -> Result<Vec<u8>, Error>;
 
+	/// Sign with key, declaring what the message is signed for.
+	///
+	/// Signers that protect against double signing use `context` to refuse messages
+	/// conflicting with ones they signed before. Defaults to `sign_with`.
+	fn sign_with_context(
+		&self,
+		id: KeyTypeId,
+		key: &CryptoTypePublicPair,
+		msg: &[u8],
+		_context: SigningContext,
+	) -> Result<Vec<u8>, Error> {
+		self.sign_with(id, key, msg)
+	}
+
 	/// Sign with any key
 	///
 	/// Given a list of public keys, find the first supported key and
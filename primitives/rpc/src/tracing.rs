@@ -0,0 +1,129 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Types for the traces of a block execution.
+
+use std::collections::BTreeMap;
+use serde::{Serialize, Deserialize};
+
+/// The spans and events recorded while executing a block.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockTrace {
+	/// Hash of the traced block.
+	pub block_hash: String,
+	/// Hash of the parent of the traced block, whose state the block was executed on.
+	pub parent_hash: String,
+	/// Comma separated list of the targets the spans and events were filtered by.
+	pub tracing_targets: String,
+	/// Comma separated list of the hex encoded storage key prefixes the storage
+	/// events were filtered by. Empty if all storage events are included.
+	pub storage_keys: String,
+	/// The spans, in the order they were exited.
+	///
+	/// Together with `parent_id` they form the span tree of the block execution.
+	pub spans: Vec<Span>,
+	/// The events, in the order they were emitted.
+	pub events: Vec<Event>,
+}
+
+/// A span recorded while executing a block.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Span {
+	/// Id of the span.
+	pub id: u64,
+	/// Id of the parent span, if any.
+	pub parent_id: Option<u64>,
+	/// Name of the span.
+	pub name: String,
+	/// Target of the span, typically the module.
+	pub target: String,
+	/// Line number in the source.
+	pub line: u32,
+	/// Whether the span was recorded inside the wasm runtime.
+	pub wasm: bool,
+	/// Total time spent inside the span, in nanoseconds.
+	pub overall_time: u64,
+	/// Values recorded to the span.
+	pub values: Data,
+}
+
+/// An event recorded while executing a block.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Event {
+	/// Name of the event.
+	pub name: String,
+	/// Target of the event, typically the module.
+	pub target: String,
+	/// Id of the span the event was emitted in, if any.
+	pub parent_id: Option<u64>,
+	/// Values recorded to the event.
+	pub data: Data,
+}
+
+/// Values recorded to a span or event.
+#[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Data {
+	/// `bool` values.
+	pub bool_values: BTreeMap<String, bool>,
+	/// `i64` values.
+	pub i64_values: BTreeMap<String, i64>,
+	/// `u64` values.
+	pub u64_values: BTreeMap<String, u64>,
+	/// `String` values.
+	pub string_values: BTreeMap<String, String>,
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::assert_deser;
+
+	#[test]
+	fn should_serialize_and_deserialize() {
+		let mut data = Data::default();
+		data.string_values.insert("key".into(), "26aa394eea5630e07c48ae0c9558cef7".into());
+		assert_deser(
+			r#"{"blockHash":"0x01","parentHash":"0x00","tracingTargets":"state","storageKeys":"","spans":[{"id":1,"parentId":null,"name":"execute_block","target":"frame_executive","line":3,"wasm":false,"overallTime":10,"values":{"boolValues":{},"i64Values":{},"u64Values":{},"stringValues":{}}}],"events":[{"name":"event","target":"state","parentId":1,"data":{"boolValues":{},"i64Values":{},"u64Values":{},"stringValues":{"key":"26aa394eea5630e07c48ae0c9558cef7"}}}]}"#,
+			BlockTrace {
+				block_hash: "0x01".into(),
+				parent_hash: "0x00".into(),
+				tracing_targets: "state".into(),
+				storage_keys: "".into(),
+				spans: vec![Span {
+					id: 1,
+					parent_id: None,
+					name: "execute_block".into(),
+					target: "frame_executive".into(),
+					line: 3,
+					wasm: false,
+					overall_time: 10,
+					values: Data::default(),
+				}],
+				events: vec![Event {
+					name: "event".into(),
+					target: "state".into(),
+					parent_id: Some(1),
+					data,
+				}],
+			},
+		);
+	}
+}
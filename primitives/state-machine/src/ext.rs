@@ -48,6 +48,28 @@ const BENCHMARKING_FN: &str = "\
 	Without client transactions the loop condition garantuees the success of the tx close.";
 
 
+/// Emit a structured `tracing` event for an access to the top-level storage.
+///
+/// Unlike the log lines, these events can be collected by a `tracing` subscriber, e.g.
+/// when tracing the execution of a block. The key and value are hex encoded.
+#[cfg(feature = "std")]
+macro_rules! trace_storage_event {
+	($method:expr, $ext_id:expr, $key:expr, $value:expr) => {
+		tracing::trace!(
+			target: "state",
+			method = $method,
+			ext_id = $ext_id,
+			key = %HexDisplay::from(&$key),
+			value = %$value.map(|v| HexDisplay::from(v).to_string()).unwrap_or_default(),
+		)
+	}
+}
+
+#[cfg(not(feature = "std"))]
+macro_rules! trace_storage_event {
+	($method:expr, $ext_id:expr, $key:expr, $value:expr) => { () }
+}
+
 #[cfg(feature = "std")]
 fn guard() -> sp_panic_handler::AbortGuard {
 	sp_panic_handler::AbortGuard::force_abort()
@@ -226,6 +248,7 @@ where
 			HexDisplay::from(&key),
 			result.as_ref().map(HexDisplay::from)
 		);
+		trace_storage_event!("Get", self.id, key, result.as_ref());
 		result
 	}
 
@@ -379,6 +402,7 @@ where
 			HexDisplay::from(&key),
 			value.as_ref().map(HexDisplay::from)
 		);
+		trace_storage_event!("Put", self.id, key, value.as_ref());
 		let _guard = guard();
 		if is_child_storage_key(&key) {
 			warn!(target: "trie", "Refuse to directly set child storage key");
@@ -429,6 +453,7 @@ where
 			self.id,
 			HexDisplay::from(&prefix),
 		);
+		trace_storage_event!("ClearPrefix", self.id, prefix, None::<&Vec<u8>>);
 		let _guard = guard();
 		if is_child_storage_key(prefix) {
 			warn!(target: "trie", "Refuse to directly clear prefix that is part of child storage key");
@@ -471,6 +496,7 @@ where
 			HexDisplay::from(&key),
 			HexDisplay::from(&value),
 		);
+		trace_storage_event!("Append", self.id, key, Some(&value));
 
 		let _guard = guard();
 		self.mark_dirty();
@@ -791,6 +791,62 @@ mod execution {
 		Ok(proving_backend.extract_proof())
 	}
 
+	/// Generate a proof of the storage entries following `start_at`, in key order.
+	///
+	/// Entries of the top trie, or of the child trie given by `child_info`, are visited until
+	/// the collected keys and values exceed `size_limit` bytes. Returns the proof and the
+	/// number of entries it covers.
+	pub fn prove_range_read_with_size<B, H>(
+		mut backend: B,
+		child_info: Option<&ChildInfo>,
+		start_at: &[u8],
+		size_limit: usize,
+	) -> Result<(StorageProof, u32), Box<dyn Error>>
+	where
+		B: Backend<H>,
+		H: Hasher,
+		H::Out: Ord + Codec,
+	{
+		let trie_backend = backend.as_trie_backend()
+			.ok_or_else(|| Box::new(ExecutionError::UnableToGenerateProof) as Box<dyn Error>)?;
+		prove_range_read_with_size_on_trie_backend(trie_backend, child_info, start_at, size_limit)
+	}
+
+	/// Generate range storage read proof on pre-created trie backend.
+	pub fn prove_range_read_with_size_on_trie_backend<S, H>(
+		trie_backend: &TrieBackend<S, H>,
+		child_info: Option<&ChildInfo>,
+		start_at: &[u8],
+		size_limit: usize,
+	) -> Result<(StorageProof, u32), Box<dyn Error>>
+	where
+		S: trie_backend_essence::TrieBackendStorage<H>,
+		H: Hasher,
+		H::Out: Ord + Codec,
+	{
+		let proving_backend = proving_backend::ProvingBackend::<_, H>::new(trie_backend);
+		let mut key = start_at.to_vec();
+		let mut count = 0;
+		let mut size = 0;
+		while size < size_limit {
+			let next = match child_info {
+				Some(child_info) => proving_backend.next_child_storage_key(child_info, &key),
+				None => proving_backend.next_storage_key(&key),
+			}.map_err(|e| Box::new(e) as Box<dyn Error>)?;
+			key = match next {
+				Some(next) => next,
+				None => break,
+			};
+			let value = match child_info {
+				Some(child_info) => proving_backend.child_storage(child_info, &key),
+				None => proving_backend.storage(&key),
+			}.map_err(|e| Box::new(e) as Box<dyn Error>)?;
+			size += key.len() + value.map_or(0, |v| v.len());
+			count += 1;
+		}
+		Ok((proving_backend.extract_proof(), count))
+	}
+
 	/// Check storage read proof, generated by `prove_read` call.
 	pub fn read_proof_check<H, I>(
 		root: H::Out,
@@ -838,6 +894,44 @@ mod execution {
 		Ok(result)
 	}
 
+	/// Check storage range proof, generated by `prove_range_read_with_size` call.
+	///
+	/// Returns the proven entries following `start_at`, and whether they reach the end of
+	/// the trie. An incomplete proof should be continued from the last returned key.
+	pub fn read_range_proof_check<H>(
+		root: H::Out,
+		proof: StorageProof,
+		child_info: Option<&ChildInfo>,
+		start_at: &[u8],
+	) -> Result<(Vec<(Vec<u8>, Vec<u8>)>, bool), Box<dyn Error>>
+	where
+		H: Hasher,
+		H::Out: Ord + Codec,
+	{
+		let proving_backend = create_proof_check_backend::<H>(root, proof)?;
+		let mut entries = Vec::new();
+		let mut key = start_at.to_vec();
+		loop {
+			let next = match child_info {
+				Some(child_info) => proving_backend.next_child_storage_key(child_info, &key),
+				None => proving_backend.next_storage_key(&key),
+			};
+			key = match next {
+				Ok(Some(next)) => next,
+				Ok(None) => return Ok((entries, true)),
+				// The proof does not reach the next entry.
+				Err(_) => return Ok((entries, false)),
+			};
+			let value = match child_info {
+				Some(child_info) => proving_backend.child_storage(child_info, &key),
+				None => proving_backend.storage(&key),
+			}
+				.map_err(|e| Box::new(e) as Box<dyn Error>)?
+				.ok_or_else(|| Box::new(ExecutionError::InvalidProof) as Box<dyn Error>)?;
+			entries.push((key.clone(), value));
+		}
+	}
+
 	/// Check storage read proof on pre-created proving backend.
 	pub fn read_proof_check_on_proving_backend<H>(
 		proving_backend: &TrieBackend<MemoryDB<H>, H>,
@@ -1399,6 +1493,57 @@ mod tests {
 		);
 	}
 
+	#[test]
+	fn prove_range_read_and_proof_check_works() {
+		let child_info = ChildInfo::new_default(b"sub1");
+		let remote_backend = trie_backend::tests::test_trie();
+		let remote_root = remote_backend.storage_root(::std::iter::empty()).0;
+
+		// collect the top trie in chunks of a single entry
+		let mut collected = Vec::new();
+		let mut start_at = Vec::new();
+		loop {
+			let (proof, count) = prove_range_read_with_size(
+				trie_backend::tests::test_trie(),
+				None,
+				&start_at,
+				1,
+			).unwrap();
+			let (entries, complete) = read_range_proof_check::<BlakeTwo256>(
+				remote_root,
+				proof,
+				None,
+				&start_at,
+			).unwrap();
+			assert!(entries.len() as u32 >= count);
+			if let Some((key, _)) = entries.last() {
+				start_at = key.clone();
+			}
+			collected.extend(entries);
+			if complete {
+				break;
+			}
+		}
+		assert_eq!(collected, remote_backend.pairs());
+
+		// on child trie, in a single chunk
+		let (proof, count) = prove_range_read_with_size(
+			trie_backend::tests::test_trie(),
+			Some(&child_info),
+			&[],
+			usize::max_value(),
+		).unwrap();
+		let (entries, complete) = read_range_proof_check::<BlakeTwo256>(
+			remote_root,
+			proof,
+			Some(&child_info),
+			&[],
+		).unwrap();
+		assert!(complete);
+		assert_eq!(count, 2);
+		assert_eq!(entries, vec![(b"value3".to_vec(), vec![142]), (b"value4".to_vec(), vec![124])]);
+	}
+
 	#[test]
 	fn child_storage_uuid() {
 
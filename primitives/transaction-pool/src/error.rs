@@ -64,6 +64,9 @@ pub enum Error {
 	/// The pool is not accepting future transactions.
 	#[display(fmt="The pool is not accepting future transactions")]
 	RejectedFutureTransaction,
+	/// The sender of the transaction already has the maximum number of transactions in the pool.
+	#[display(fmt="Too many transactions from the same sender in the pool")]
+	TooManyFromSender,
 }
 
 impl std::error::Error for Error {}
@@ -727,6 +727,20 @@ cfg_if! {
 					<pallet_babe::Module<Runtime>>::current_epoch_start()
 				}
 
+				fn current_epoch() -> sp_consensus_babe::Epoch {
+					<pallet_babe::Module<Runtime>>::current_epoch(sp_consensus_babe::BabeEpochConfiguration {
+						c: (3, 10),
+						allowed_slots: AllowedSlots::PrimaryAndSecondaryPlainSlots,
+					})
+				}
+
+				fn next_epoch() -> sp_consensus_babe::Epoch {
+					<pallet_babe::Module<Runtime>>::next_epoch(sp_consensus_babe::BabeEpochConfiguration {
+						c: (3, 10),
+						allowed_slots: AllowedSlots::PrimaryAndSecondaryPlainSlots,
+					})
+				}
+
 				fn submit_report_equivocation_unsigned_extrinsic(
 					_equivocation_proof: sp_consensus_babe::EquivocationProof<
 						<Block as BlockT>::Header,
@@ -768,6 +782,10 @@ cfg_if! {
 					Vec::new()
 				}
 
+				fn current_set_id() -> sp_finality_grandpa::SetId {
+					0
+				}
+
 				fn submit_report_equivocation_unsigned_extrinsic(
 					_equivocation_proof: sp_finality_grandpa::EquivocationProof<
 						<Block as BlockT>::Hash,
@@ -973,6 +991,20 @@ cfg_if! {
 					<pallet_babe::Module<Runtime>>::current_epoch_start()
 				}
 
+				fn current_epoch() -> sp_consensus_babe::Epoch {
+					<pallet_babe::Module<Runtime>>::current_epoch(sp_consensus_babe::BabeEpochConfiguration {
+						c: (3, 10),
+						allowed_slots: AllowedSlots::PrimaryAndSecondaryPlainSlots,
+					})
+				}
+
+				fn next_epoch() -> sp_consensus_babe::Epoch {
+					<pallet_babe::Module<Runtime>>::next_epoch(sp_consensus_babe::BabeEpochConfiguration {
+						c: (3, 10),
+						allowed_slots: AllowedSlots::PrimaryAndSecondaryPlainSlots,
+					})
+				}
+
 				fn submit_report_equivocation_unsigned_extrinsic(
 					_equivocation_proof: sp_consensus_babe::EquivocationProof<
 						<Block as BlockT>::Header,
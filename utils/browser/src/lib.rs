@@ -92,6 +92,9 @@ where
 		rpc_ipc: Default::default(),
 		rpc_ws: Default::default(),
 		rpc_ws_max_connections: Default::default(),
+		rpc_limits: Default::default(),
+		rpc_max_payload: Default::default(),
+		rpc_max_subscriptions_per_connection: Default::default(),
 		rpc_methods: Default::default(),
 		state_cache_child_ratio: Default::default(),
 		state_cache_size: Default::default(),
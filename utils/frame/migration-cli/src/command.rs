@@ -0,0 +1,107 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::DryRunUpgradeCmd;
+use codec::Decode;
+use frame_executive::MigrationReport;
+use sc_cli::{SharedParams, CliConfiguration, ExecutionStrategy, Result};
+use sc_executor::NativeExecutor;
+use sc_service::{Configuration, NativeExecutionDispatch};
+use sp_core::{storage::well_known_keys, testing::TaskExecutor};
+use sp_externalities::Extensions;
+use sp_runtime::traits::{Block as BlockT, HashFor, NumberFor};
+use sp_state_machine::{InMemoryBackend, StateMachine, backend::BackendRuntimeCode};
+
+impl DryRunUpgradeCmd {
+	/// Runs the command and prints the report of the migrations.
+	///
+	/// Fails if any migration check failed.
+	pub fn run<B, ExecDispatch>(&self, config: Configuration) -> Result<()>
+	where
+		B: BlockT,
+		ExecDispatch: NativeExecutionDispatch + 'static,
+	{
+		let spec = config.chain_spec;
+		let wasm_method = self.wasm_method.into();
+		// The new runtime is usually not the native one.
+		let strategy = self.execution.unwrap_or(ExecutionStrategy::Wasm);
+
+		let mut storage = spec.build_storage()?;
+		if let Some(runtime) = &self.runtime {
+			let code = std::fs::read(runtime)
+				.map_err(|e| format!("Failed to read runtime {}: {}", runtime.display(), e))?;
+			storage.top.insert(well_known_keys::CODE.to_vec(), code);
+		}
+
+		let state = InMemoryBackend::<HashFor<B>>::from(storage);
+		let mut changes = Default::default();
+		let mut offchain_changes = Default::default();
+		let executor = NativeExecutor::<ExecDispatch>::new(
+			wasm_method,
+			self.heap_pages,
+			2, // The runtime instances cache size.
+		);
+
+		let result = StateMachine::<_, _, NumberFor<B>, _>::new(
+			&state,
+			None,
+			&mut changes,
+			&mut offchain_changes,
+			&executor,
+			"MigrationApi_dry_run_upgrade",
+			&[],
+			Extensions::default(),
+			&BackendRuntimeCode::new(&state).runtime_code()?,
+			TaskExecutor::new(),
+		)
+		.execute(strategy.into())
+		.map_err(|e| format!("Error executing the migrations: {:?}", e))?;
+
+		let report = MigrationReport::decode(&mut &result[..])
+			.map_err(|e| format!("Failed to decode the migration report: {:?}", e))?;
+
+		for migration in &report.migrations {
+			println!(
+				"{}: weight {}, pre-upgrade {}, post-upgrade {}",
+				String::from_utf8_lossy(&migration.name),
+				migration.weight,
+				check_outcome(&migration.pre_upgrade),
+				check_outcome(&migration.post_upgrade),
+			);
+		}
+		println!("Total weight: {}", report.weight);
+
+		if report.is_ok() {
+			Ok(())
+		} else {
+			Err("Some migration checks failed".into())
+		}
+	}
+}
+
+fn check_outcome(outcome: &std::result::Result<(), Vec<u8>>) -> String {
+	match outcome {
+		Ok(()) => "ok".into(),
+		Err(e) => format!("failed ({})", String::from_utf8_lossy(e)),
+	}
+}
+
+impl CliConfiguration for DryRunUpgradeCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+}
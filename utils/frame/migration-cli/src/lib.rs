@@ -0,0 +1,61 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+mod command;
+
+use sc_cli::{ExecutionStrategy, WasmExecutionMethod};
+use std::path::PathBuf;
+
+/// The `dry-run-upgrade` command used to dry-run the storage migrations of a runtime.
+///
+/// The state is taken from the genesis of the chain spec given by `--chain`, which would usually
+/// be a chain spec written by `export-state`.
+#[derive(Debug, structopt::StructOpt)]
+pub struct DryRunUpgradeCmd {
+	/// Path to the Wasm blob of the new runtime.
+	///
+	/// If not given, the runtime of the exported state is used.
+	#[structopt(long, parse(from_os_str))]
+	pub runtime: Option<PathBuf>,
+
+	/// Set the heap pages while running the migrations.
+	#[structopt(long)]
+	pub heap_pages: Option<u64>,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: sc_cli::SharedParams,
+
+	/// The execution strategy that should be used for the migrations.
+	#[structopt(
+		long = "execution",
+		value_name = "STRATEGY",
+		possible_values = &ExecutionStrategy::variants(),
+		case_insensitive = true,
+	)]
+	pub execution: Option<ExecutionStrategy>,
+
+	/// Method for executing Wasm runtime code.
+	#[structopt(
+		long = "wasm-execution",
+		value_name = "METHOD",
+		possible_values = &WasmExecutionMethod::enabled_variants(),
+		case_insensitive = true,
+		default_value = "Interpreted"
+	)]
+	pub wasm_method: WasmExecutionMethod,
+}
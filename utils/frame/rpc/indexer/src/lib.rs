@@ -0,0 +1,589 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Block and event streaming RPC for indexers of FRAME based chains.
+//!
+//! A subscriber receives every block of the best chain with its extrinsics, their dispatch
+//! results and the events of the block, an explicit notification for every block retracted
+//! by a re-org, and a marker for every finalized block. A subscription may start from any
+//! block whose state was not pruned yet, which lets an indexer resume from the last block
+//! it processed. Unless unsafe RPCs are allowed, the start block must be at most
+//! [`MAX_SAFE_HISTORY`] blocks behind the best block.
+#![warn(missing_docs)]
+
+use std::{marker::PhantomData, sync::Arc};
+
+use codec::Decode;
+use frame_support::traits::PalletInfo;
+use frame_system::{EventRecord, Module as SystemModule};
+use futures::{future::ready, stream, StreamExt, TryStreamExt};
+use jsonrpc_core::futures::{
+	sink::Sink as Sink01,
+	stream::Stream as Stream01,
+	future::Future as Future01,
+	future::Executor as Executor01,
+};
+use jsonrpc_derive::rpc;
+use jsonrpc_pubsub::{typed::Subscriber, SubscriptionId, manager::SubscriptionManager};
+use log::warn;
+use sc_client_api::{
+	BlockBackend, BlockchainEvents, BlockImportNotification, StorageProvider, backend::Backend,
+};
+use sp_blockchain::HeaderBackend;
+use sp_core::{storage::StorageKey, twox_128};
+use sc_rpc_api::DenyUnsafe;
+use sp_runtime::{
+	generic::BlockId,
+	traits::{Block as BlockT, Header as HeaderT, NumberFor, One, Saturating},
+};
+
+mod notification;
+
+pub use notification::{
+	BlockRef, EventPhase, IndexedBlock, IndexedEvent, IndexedExtrinsic, IndexerNotification,
+};
+pub use self::gen_client::Client as IndexerClient;
+
+/// Maximal number of history blocks a subscription may start with, unless unsafe RPCs are
+/// allowed.
+pub const MAX_SAFE_HISTORY: u32 = 1024;
+
+/// Indexer RPC methods.
+#[rpc]
+pub trait IndexerApi<Number, Notification> {
+	/// RPC Metadata
+	type Metadata;
+
+	/// Stream the blocks of the best chain with their extrinsics and events.
+	///
+	/// If `from` is given, the blocks of the best chain starting at that number are sent
+	/// first, followed by the last finalized block. The state of `from` must not have been
+	/// pruned, and `from` must be at most [`MAX_SAFE_HISTORY`] blocks behind the best block
+	/// unless unsafe RPCs are allowed. If `pallets` is given, only the events of the pallets
+	/// with these indices are sent.
+	#[pubsub(subscription = "indexer_blocks", subscribe, name = "indexer_subscribeBlocks")]
+	fn subscribe_blocks(
+		&self,
+		metadata: Self::Metadata,
+		subscriber: Subscriber<Notification>,
+		from: Option<Number>,
+		pallets: Option<Vec<u8>>,
+	);
+
+	/// Unsubscribe from the block stream.
+	#[pubsub(subscription = "indexer_blocks", unsubscribe, name = "indexer_unsubscribeBlocks")]
+	fn unsubscribe_blocks(
+		&self,
+		metadata: Option<Self::Metadata>,
+		id: SubscriptionId,
+	) -> jsonrpc_core::Result<bool>;
+}
+
+/// Error type of this RPC api.
+#[derive(Debug, derive_more::Display, derive_more::From)]
+pub enum Error {
+	/// The client failed to provide a block component or its state.
+	#[display(fmt = "Client error: {}", _0)]
+	Client(sp_blockchain::Error),
+	/// The block is not known to the client.
+	#[display(fmt = "Unknown block: {}", _0)]
+	UnknownBlock(String),
+	/// The events of the block could not be decoded.
+	#[display(fmt = "Failed to decode events: {}", _0)]
+	Decode(codec::Error),
+	/// The history requested is too long to be sent by a node denying unsafe RPCs.
+	#[display(fmt = "Start block is more than {} blocks behind the best block", MAX_SAFE_HISTORY)]
+	HistoryTooLong,
+}
+
+impl From<Error> for jsonrpc_core::Error {
+	fn from(error: Error) -> Self {
+		let code = match error {
+			Error::Client(_) => 1,
+			Error::UnknownBlock(_) => 2,
+			Error::Decode(_) => 3,
+			Error::HistoryTooLong => 4,
+		};
+		jsonrpc_core::Error {
+			message: format!("{}", error),
+			code: jsonrpc_core::ErrorCode::ServerError(code),
+			data: None,
+		}
+	}
+}
+
+/// Storage key of the events of the block, `System::Events`.
+fn events_key() -> StorageKey {
+	StorageKey([twox_128(b"System"), twox_128(b"Events")].concat())
+}
+
+type Notification<Block> = IndexerNotification<<Block as BlockT>::Hash, NumberFor<Block>>;
+
+/// Reads the blocks and their events from the client.
+struct BlockSource<BE, Block, Client, Runtime> {
+	client: Arc<Client>,
+	pallets: Option<Vec<u8>>,
+	system_index: Option<u8>,
+	_marker: PhantomData<(BE, Block, Runtime)>,
+}
+
+impl<BE, Block, Client, Runtime> Clone for BlockSource<BE, Block, Client, Runtime> {
+	fn clone(&self) -> Self {
+		Self {
+			client: self.client.clone(),
+			pallets: self.pallets.clone(),
+			system_index: self.system_index,
+			_marker: PhantomData,
+		}
+	}
+}
+
+impl<BE, Block, Client, Runtime> BlockSource<BE, Block, Client, Runtime> where
+	BE: Backend<Block>,
+	Block: BlockT,
+	Client: HeaderBackend<Block> + BlockBackend<Block> + StorageProvider<Block, BE>,
+	Runtime: frame_system::Trait,
+{
+	fn new(client: Arc<Client>, pallets: Option<Vec<u8>>) -> Self {
+		// Outer events are encoded with the index of their pallet in the runtime.
+		let system_index = <Runtime::PalletInfo as PalletInfo>::index::<SystemModule<Runtime>>()
+			.map(|index| index as u8);
+		Self { client, pallets, system_index, _marker: PhantomData }
+	}
+
+	/// Fails if the events of the block can not be read, e.g. because its state was pruned.
+	fn check_state(&self, number: NumberFor<Block>) -> Result<(), Error> {
+		self.client.storage(&BlockId::Number(number), &events_key())?;
+		Ok(())
+	}
+
+	fn enacted_at(&self, number: NumberFor<Block>) -> Result<Notification<Block>, Error> {
+		let hash = self.client.hash(number)?
+			.ok_or_else(|| Error::UnknownBlock(format!("#{}", number)))?;
+		self.enacted(hash)
+	}
+
+	fn enacted(&self, hash: Block::Hash) -> Result<Notification<Block>, Error> {
+		let id = BlockId::Hash(hash);
+		let header = self.client.header(id)?
+			.ok_or_else(|| Error::UnknownBlock(format!("{}", hash)))?;
+		let extrinsics = self.client.block_body(&id)?.unwrap_or_default();
+		let records = self.client.storage(&id, &events_key())?
+			.map(|data| Vec::<EventRecord<Runtime::Event, Block::Hash>>::decode(&mut &data.0[..]))
+			.transpose()?;
+
+		let block = notification::index_block::<_, _, _, _, Runtime::AccountId>(
+			BlockRef { hash, number: *header.number() },
+			*header.parent_hash(),
+			extrinsics,
+			records,
+			self.system_index,
+			self.pallets.as_deref(),
+		);
+		Ok(IndexerNotification::Enacted(block))
+	}
+
+	/// The notifications of a new best block, preceded by those of the re-org it caused.
+	fn new_best(
+		&self,
+		import: BlockImportNotification<Block>,
+	) -> Vec<Result<Notification<Block>, Error>> {
+		let mut notifications = Vec::new();
+		if let Some(route) = import.tree_route {
+			notifications.extend(route.retracted().iter().map(|block| Ok(
+				IndexerNotification::Retracted(BlockRef { hash: block.hash, number: block.number })
+			)));
+			notifications.extend(route.enacted().iter().map(|block| self.enacted(block.hash)));
+		}
+		notifications.push(self.enacted(import.hash));
+		notifications
+	}
+}
+
+/// Implements the [`IndexerApi`] RPC trait for streaming blocks to indexers.
+pub struct Indexer<BE, Block, Client, Runtime> {
+	client: Arc<Client>,
+	manager: SubscriptionManager,
+	deny_unsafe: DenyUnsafe,
+	_marker: PhantomData<(BE, Block, Runtime)>,
+}
+
+impl<BE, Block, Client, Runtime> Indexer<BE, Block, Client, Runtime> {
+	/// Create new `Indexer` with the given reference to the client.
+	pub fn new<E>(client: Arc<Client>, executor: E, deny_unsafe: DenyUnsafe) -> Self where
+		E: Executor01<Box<dyn Future01<Item = (), Error = ()> + Send>> + Send + Sync + 'static,
+	{
+		Self {
+			client,
+			manager: SubscriptionManager::new(Arc::new(executor)),
+			deny_unsafe,
+			_marker: PhantomData,
+		}
+	}
+}
+
+impl<BE, Block, Client, Runtime> IndexerApi<NumberFor<Block>, Notification<Block>>
+	for Indexer<BE, Block, Client, Runtime>
+where
+	BE: Backend<Block> + Send + Sync + 'static,
+	Block: BlockT,
+	Client: HeaderBackend<Block> + BlockBackend<Block> + BlockchainEvents<Block>
+		+ StorageProvider<Block, BE> + Send + Sync + 'static,
+	Runtime: frame_system::Trait + Send + Sync,
+{
+	type Metadata = sc_rpc_api::Metadata;
+
+	fn subscribe_blocks(
+		&self,
+		_metadata: Self::Metadata,
+		subscriber: Subscriber<Notification<Block>>,
+		from: Option<NumberFor<Block>>,
+		pallets: Option<Vec<u8>>,
+	) {
+		let source = BlockSource::<BE, Block, Client, Runtime>::new(self.client.clone(), pallets);
+		// Registered before reading the best block, so no block is missed in between.
+		let imports = self.client.import_notification_stream();
+		let finality = self.client.finality_notification_stream();
+		let info = self.client.info();
+		let best = info.best_number;
+
+		let history = match from {
+			Some(from) => {
+				let max_history = NumberFor::<Block>::from(MAX_SAFE_HISTORY);
+				if best.saturating_sub(from) > max_history && self.deny_unsafe.check_if_safe().is_err() {
+					let _ = subscriber.reject(Error::HistoryTooLong.into());
+					return
+				}
+				if let Err(e) = source.check_state(from) {
+					let _ = subscriber.reject(e.into());
+					return
+				}
+				let numbers = std::iter::successors(Some(from), |n| Some(*n + One::one()))
+					.take_while(move |n| *n <= best);
+				let finalized = IndexerNotification::Finalized(BlockRef {
+					hash: info.finalized_hash,
+					number: info.finalized_number,
+				});
+				let history_source = source.clone();
+				stream::iter(numbers)
+					.map(move |n| history_source.enacted_at(n))
+					.chain(stream::once(ready(Ok(finalized))))
+					.boxed()
+			},
+			None => stream::empty().boxed(),
+		};
+
+		// Blocks up to the best one at subscription time were sent as history.
+		let imports = imports
+			.filter(move |import| ready(
+				import.is_new_best && (import.tree_route.is_some() || *import.header.number() > best)
+			))
+			.map(move |import| stream::iter(source.new_best(import)))
+			.flatten();
+		let finality = finality.map(|finalized| Ok(IndexerNotification::Finalized(BlockRef {
+			hash: finalized.hash,
+			number: *finalized.header.number(),
+		})));
+
+		// The stream ends at the first error, the subscriber can resume from its last block.
+		let stream = history
+			.chain(stream::select(imports, finality))
+			.take_while(|res| {
+				if let Err(e) = res {
+					warn!("Indexer subscription stopped: {}", e);
+				}
+				ready(res.is_ok())
+			})
+			.map_err(|_| ())
+			.compat();
+
+		self.manager.add(subscriber, |sink| {
+			let stream = stream.map(|res| Ok(res));
+			sink.sink_map_err(|e| warn!("Error sending notifications: {:?}", e))
+				.send_all(stream)
+				.map(|_| ())
+		});
+	}
+
+	fn unsubscribe_blocks(
+		&self,
+		_metadata: Option<Self::Metadata>,
+		id: SubscriptionId,
+	) -> jsonrpc_core::Result<bool> {
+		Ok(self.manager.cancel(id))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use codec::Encode;
+	use frame_support::{impl_outer_origin, parameter_types, weights::{DispatchInfo, Weight}};
+	use frame_system::{Phase, RawEvent};
+	use futures::{executor, compat::{Future01CompatExt, Stream01CompatExt}};
+	use sc_block_builder::BlockBuilderProvider;
+	use sc_rpc::testing::TaskExecutor;
+	use sp_core::H256;
+	use sp_runtime::{Perbill, traits::{BlakeTwo256, IdentityLookup}};
+	use substrate_test_runtime_client::{
+		prelude::*,
+		sp_consensus::BlockOrigin,
+		runtime::{Block, Runtime, Transfer},
+	};
+
+	type TestIndexer = Indexer<Backend, Block, TestClient, Runtime>;
+
+	// The test runtime has no events, the events of this runtime are written to the blocks
+	// instead.
+	impl_outer_origin! {
+		pub enum Origin for EventRuntime where system = frame_system {}
+	}
+
+	#[derive(Clone, Eq, PartialEq)]
+	pub struct EventRuntime;
+
+	/// Encoded like an outer event, with the system pallet at index 0.
+	#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug)]
+	pub enum TestEvent {
+		System(frame_system::Event<EventRuntime>),
+		Balances(u32),
+	}
+
+	impl From<frame_system::Event<EventRuntime>> for TestEvent {
+		fn from(event: frame_system::Event<EventRuntime>) -> Self {
+			TestEvent::System(event)
+		}
+	}
+
+	parameter_types! {
+		pub const BlockHashCount: u64 = 250;
+		pub const MaximumBlockWeight: Weight = 1024;
+		pub const MaximumBlockLength: u32 = 2 * 1024;
+		pub const AvailableBlockRatio: Perbill = Perbill::one();
+	}
+
+	impl frame_system::Trait for EventRuntime {
+		type BaseCallFilter = ();
+		type Origin = Origin;
+		type Index = u64;
+		type BlockNumber = u64;
+		type Call = ();
+		type Hash = H256;
+		type Hashing = BlakeTwo256;
+		type AccountId = u64;
+		type Lookup = IdentityLookup<Self::AccountId>;
+		type Header = sp_runtime::testing::Header;
+		type Event = TestEvent;
+		type BlockHashCount = BlockHashCount;
+		type MaximumBlockWeight = MaximumBlockWeight;
+		type DbWeight = ();
+		type BlockExecutionWeight = ();
+		type ExtrinsicBaseWeight = ();
+		type MaximumExtrinsicWeight = MaximumBlockWeight;
+		type AvailableBlockRatio = AvailableBlockRatio;
+		type MaximumBlockLength = MaximumBlockLength;
+		type Version = ();
+		type PalletInfo = ();
+		type AccountData = ();
+		type OnNewAccount = ();
+		type OnKilledAccount = ();
+		type SystemWeightInfo = ();
+	}
+
+	type EventIndexer = Indexer<Backend, Block, TestClient, EventRuntime>;
+
+	fn subscribe<BE, C, R>(
+		indexer: &Indexer<BE, Block, C, R>,
+		from: Option<u64>,
+		pallets: Option<Vec<u8>>,
+	) -> jsonrpc_core::futures::sync::mpsc::Receiver<String> where
+		Indexer<BE, Block, C, R>: IndexerApi<u64, Notification<Block>, Metadata = sc_rpc_api::Metadata>,
+	{
+		let (subscriber, id, transport) = Subscriber::new_test("test");
+		indexer.subscribe_blocks(Default::default(), subscriber, from, pallets);
+		assert!(matches!(executor::block_on(id.compat()), Ok(Ok(SubscriptionId::String(_)))));
+		transport
+	}
+
+	fn next(
+		transport: jsonrpc_core::futures::sync::mpsc::Receiver<String>,
+	) -> (serde_json::Value, jsonrpc_core::futures::sync::mpsc::Receiver<String>) {
+		let (notification, transport) = executor::block_on(transport.into_future().compat()).unwrap();
+		let notification: serde_json::Value = serde_json::from_str(&notification.unwrap()).unwrap();
+		(notification["params"]["result"].clone(), transport)
+	}
+
+	#[test]
+	fn should_stream_history_and_new_blocks() {
+		let mut client = Arc::new(substrate_test_runtime_client::new());
+		let mut builder = client.new_block(Default::default()).unwrap();
+		builder.push_transfer(Transfer {
+			from: AccountKeyring::Alice.into(),
+			to: AccountKeyring::Bob.into(),
+			amount: 42,
+			nonce: 0,
+		}).unwrap();
+		let block = builder.build().unwrap().block;
+		let block_hash = block.header.hash();
+		client.import(BlockOrigin::Own, block).unwrap();
+
+		let indexer = TestIndexer::new(client.clone(), TaskExecutor, DenyUnsafe::No);
+		let (subscriber, id, transport) = Subscriber::new_test("test");
+		indexer.subscribe_blocks(Default::default(), subscriber, Some(0), None);
+		assert!(matches!(executor::block_on(id.compat()), Ok(Ok(SubscriptionId::String(_)))));
+
+		let (genesis, transport) = next(transport);
+		assert_eq!(genesis["type"], "enacted");
+		assert_eq!(genesis["number"], 0);
+		assert_eq!(genesis["hash"], format!("{:?}", client.genesis_hash()));
+		let (first, transport) = next(transport);
+		assert_eq!(first["type"], "enacted");
+		assert_eq!(first["hash"], format!("{:?}", block_hash));
+		assert_eq!(first["extrinsics"].as_array().unwrap().len(), 1);
+		assert_eq!(first["extrinsics"][0]["index"], 0);
+		let (finalized, transport) = next(transport);
+		assert_eq!(finalized["type"], "finalized");
+		assert_eq!(finalized["number"], 0);
+
+		let block = client.new_block(Default::default()).unwrap().build().unwrap().block;
+		let block_hash = block.header.hash();
+		client.import(BlockOrigin::Own, block).unwrap();
+		let (second, _) = next(transport);
+		assert_eq!(second["type"], "enacted");
+		assert_eq!(second["number"], 2);
+		assert_eq!(second["hash"], format!("{:?}", block_hash));
+	}
+
+	#[test]
+	fn should_notify_blocks_retracted_and_enacted_by_a_reorg() {
+		let mut client = Arc::new(substrate_test_runtime_client::new());
+		let genesis_hash = client.genesis_hash();
+		let block = client.new_block(Default::default()).unwrap().build().unwrap().block;
+		let retracted_hash = block.header.hash();
+		client.import(BlockOrigin::Own, block).unwrap();
+
+		let indexer = TestIndexer::new(client.clone(), TaskExecutor, DenyUnsafe::No);
+		let transport = subscribe(&indexer, None, None);
+
+		// A fork of the same length does not become the best chain.
+		let mut builder = client.new_block_at(&BlockId::Hash(genesis_hash), Default::default(), false)
+			.unwrap();
+		builder.push_transfer(Transfer {
+			from: AccountKeyring::Alice.into(),
+			to: AccountKeyring::Bob.into(),
+			amount: 42,
+			nonce: 0,
+		}).unwrap();
+		let block = builder.build().unwrap().block;
+		let fork_hash = block.header.hash();
+		client.import(BlockOrigin::Own, block).unwrap();
+		let block = client.new_block_at(&BlockId::Hash(fork_hash), Default::default(), false)
+			.unwrap().build().unwrap().block;
+		let best_hash = block.header.hash();
+		client.import(BlockOrigin::Own, block).unwrap();
+
+		let (retracted, transport) = next(transport);
+		assert_eq!(retracted["type"], "retracted");
+		assert_eq!(retracted["number"], 1);
+		assert_eq!(retracted["hash"], format!("{:?}", retracted_hash));
+		let (fork, transport) = next(transport);
+		assert_eq!(fork["type"], "enacted");
+		assert_eq!(fork["number"], 1);
+		assert_eq!(fork["hash"], format!("{:?}", fork_hash));
+		assert_eq!(fork["extrinsics"].as_array().unwrap().len(), 1);
+		let (best, _) = next(transport);
+		assert_eq!(best["type"], "enacted");
+		assert_eq!(best["number"], 2);
+		assert_eq!(best["hash"], format!("{:?}", best_hash));
+		assert_eq!(best["parentHash"], format!("{:?}", fork_hash));
+	}
+
+	#[test]
+	fn should_filter_events_by_pallet() {
+		let mut client = Arc::new(substrate_test_runtime_client::new());
+		let records = vec![
+			EventRecord {
+				phase: Phase::ApplyExtrinsic(0),
+				event: TestEvent::System(RawEvent::ExtrinsicSuccess(DispatchInfo::default())),
+				topics: vec![],
+			},
+			EventRecord {
+				phase: Phase::ApplyExtrinsic(0),
+				event: TestEvent::Balances(5),
+				topics: vec![],
+			},
+			EventRecord { phase: Phase::Finalization, event: TestEvent::Balances(6), topics: vec![] },
+		];
+		let mut builder = client.new_block(Default::default()).unwrap();
+		builder.push_storage_change(events_key().0, Some(records.encode())).unwrap();
+		client.import(BlockOrigin::Own, builder.build().unwrap().block).unwrap();
+
+		let indexer = EventIndexer::new(client.clone(), TaskExecutor, DenyUnsafe::No);
+		let transport = subscribe(&indexer, Some(1), None);
+		let (block, _) = next(transport);
+		assert_eq!(block["extrinsics"][0]["events"].as_array().unwrap().len(), 2);
+		assert_eq!(block["events"].as_array().unwrap().len(), 1);
+
+		let transport = subscribe(&indexer, Some(1), Some(vec![1]));
+		let (block, _) = next(transport);
+		let events = block["extrinsics"][0]["events"].as_array().unwrap();
+		assert_eq!(events.len(), 1);
+		assert_eq!(events[0]["palletIndex"], 1);
+		assert_eq!(events[0]["data"], format!("0x{}", sp_core::hexdisplay::HexDisplay::from(
+			&TestEvent::Balances(5).encode()
+		)));
+		assert_eq!(block["events"].as_array().unwrap().len(), 1);
+		assert_eq!(block["events"][0]["phase"], "finalization");
+
+		let transport = subscribe(&indexer, Some(1), Some(vec![0]));
+		let (block, _) = next(transport);
+		let events = block["extrinsics"][0]["events"].as_array().unwrap();
+		assert_eq!(events.len(), 1);
+		assert_eq!(events[0]["palletIndex"], 0);
+		assert!(block["events"].as_array().unwrap().is_empty());
+	}
+
+	#[test]
+	fn should_bound_history_unless_unsafe_rpcs_are_allowed() {
+		let mut client = Arc::new(substrate_test_runtime_client::new());
+		for _ in 0..=MAX_SAFE_HISTORY {
+			let block = client.new_block(Default::default()).unwrap().build().unwrap().block;
+			client.import(BlockOrigin::Own, block).unwrap();
+		}
+
+		let indexer = TestIndexer::new(client.clone(), TaskExecutor, DenyUnsafe::Yes);
+		let (subscriber, id, _transport) = Subscriber::new_test("test");
+		indexer.subscribe_blocks(Default::default(), subscriber, Some(0), None);
+		assert!(matches!(executor::block_on(id.compat()), Ok(Err(_))));
+		// The last `MAX_SAFE_HISTORY` blocks can be streamed.
+		let (block, _) = next(subscribe(&indexer, Some(1), None));
+		assert_eq!(block["number"], 1);
+
+		let indexer = TestIndexer::new(client, TaskExecutor, DenyUnsafe::No);
+		let (block, _) = next(subscribe(&indexer, Some(0), None));
+		assert_eq!(block["number"], 0);
+	}
+
+	#[test]
+	fn should_reject_unknown_start_block() {
+		let client = Arc::new(substrate_test_runtime_client::new());
+		let indexer = TestIndexer::new(client, TaskExecutor, DenyUnsafe::No);
+		let (subscriber, id, _transport) = Subscriber::new_test("test");
+		indexer.subscribe_blocks(Default::default(), subscriber, Some(5), None);
+		assert!(matches!(executor::block_on(id.compat()), Ok(Err(_))));
+	}
+}
@@ -0,0 +1,286 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Notifications sent to the subscribers of the indexer.
+
+use std::fmt::Debug;
+
+use codec::{Decode, Encode};
+use frame_support::Parameter;
+use frame_system::{EventRecord, Phase, RawEvent};
+use serde::{Serialize, Deserialize};
+use sp_core::Bytes;
+use sp_runtime::traits::Member;
+
+/// A change of the best chain, or of the finalized block.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum IndexerNotification<Hash, Number> {
+	/// The block was added to the best chain.
+	Enacted(IndexedBlock<Hash, Number>),
+	/// The block was removed from the best chain by a re-org.
+	///
+	/// Retracted blocks are sent before the blocks enacted by the same re-org,
+	/// starting with the former best block.
+	Retracted(BlockRef<Hash, Number>),
+	/// The block, and all of its ancestors, were finalized.
+	Finalized(BlockRef<Hash, Number>),
+}
+
+/// Reference to a block.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockRef<Hash, Number> {
+	/// Hash of the block.
+	pub hash: Hash,
+	/// Number of the block.
+	pub number: Number,
+}
+
+/// A block with its extrinsics and events.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexedBlock<Hash, Number> {
+	/// Hash of the block.
+	pub hash: Hash,
+	/// Number of the block.
+	pub number: Number,
+	/// Hash of the parent block.
+	pub parent_hash: Hash,
+	/// The extrinsics of the block, in order.
+	pub extrinsics: Vec<IndexedExtrinsic<Hash>>,
+	/// The events emitted while initializing and finalizing the block.
+	pub events: Vec<IndexedEvent<Hash>>,
+}
+
+/// An extrinsic with its dispatch result and events.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexedExtrinsic<Hash> {
+	/// Index of the extrinsic in the block.
+	pub index: u32,
+	/// The SCALE encoded extrinsic.
+	pub data: Bytes,
+	/// Debug representation of the extrinsic.
+	pub decoded: String,
+	/// Whether the dispatch succeeded, if known from the system events of the block.
+	pub success: Option<bool>,
+	/// Debug representation of the dispatch error, if the dispatch failed.
+	pub error: Option<String>,
+	/// The events emitted while applying the extrinsic.
+	pub events: Vec<IndexedEvent<Hash>>,
+}
+
+/// A runtime event.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexedEvent<Hash> {
+	/// The phase of the block the event was emitted in.
+	pub phase: EventPhase,
+	/// Index of the emitting pallet in the runtime.
+	pub pallet_index: u8,
+	/// Index of the event in the events of the pallet.
+	pub event_index: u8,
+	/// The SCALE encoded event, including both indices.
+	pub data: Bytes,
+	/// Debug representation of the event.
+	pub decoded: String,
+	/// The topics of the event.
+	pub topics: Vec<Hash>,
+}
+
+/// The phase of a block's execution an event was emitted in.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum EventPhase {
+	/// Applying the extrinsic with the given index.
+	ApplyExtrinsic(u32),
+	/// Finalizing the block.
+	Finalization,
+	/// Initializing the block.
+	Initialization,
+}
+
+impl From<&Phase> for EventPhase {
+	fn from(phase: &Phase) -> Self {
+		match phase {
+			Phase::ApplyExtrinsic(index) => EventPhase::ApplyExtrinsic(*index),
+			Phase::Finalization => EventPhase::Finalization,
+			Phase::Initialization => EventPhase::Initialization,
+		}
+	}
+}
+
+/// Assembles an [`IndexedBlock`] from the extrinsics and the event records of a block.
+///
+/// `system_index` is the index of the system pallet in the runtime, whose `ExtrinsicSuccess`
+/// and `ExtrinsicFailed` events give the dispatch results. Only the events of the pallets in
+/// `pallets` are included, if given.
+pub(crate) fn index_block<Hash, Number, Extrinsic, Event, AccountId>(
+	block: BlockRef<Hash, Number>,
+	parent_hash: Hash,
+	extrinsics: Vec<Extrinsic>,
+	records: Option<Vec<EventRecord<Event, Hash>>>,
+	system_index: Option<u8>,
+	pallets: Option<&[u8]>,
+) -> IndexedBlock<Hash, Number> where
+	Hash: Clone,
+	Extrinsic: Encode + Debug,
+	Event: Parameter + Member,
+	AccountId: Decode,
+{
+	let mut extrinsics = extrinsics.into_iter().enumerate()
+		.map(|(index, extrinsic)| IndexedExtrinsic {
+			index: index as u32,
+			data: extrinsic.encode().into(),
+			decoded: format!("{:?}", extrinsic),
+			success: None,
+			error: None,
+			events: Vec::new(),
+		})
+		.collect::<Vec<_>>();
+	let mut events = Vec::new();
+
+	for record in records.unwrap_or_default() {
+		let data = record.event.encode();
+		let (pallet_index, event_index) = match data.as_slice() {
+			[pallet_index, event_index, ..] => (*pallet_index, *event_index),
+			_ => continue,
+		};
+		let extrinsic = match record.phase {
+			Phase::ApplyExtrinsic(index) => extrinsics.get_mut(index as usize),
+			_ => None,
+		};
+		if let Some(extrinsic) = extrinsic {
+			if Some(pallet_index) == system_index {
+				match RawEvent::<AccountId>::decode(&mut &data[1..]) {
+					Ok(RawEvent::ExtrinsicSuccess(_)) => extrinsic.success = Some(true),
+					Ok(RawEvent::ExtrinsicFailed(error, _)) => {
+						extrinsic.success = Some(false);
+						extrinsic.error = Some(format!("{:?}", error));
+					},
+					_ => {},
+				}
+			}
+		}
+		if pallets.map_or(false, |pallets| !pallets.contains(&pallet_index)) {
+			continue
+		}
+
+		let event = IndexedEvent {
+			phase: EventPhase::from(&record.phase),
+			pallet_index,
+			event_index,
+			decoded: format!("{:?}", record.event),
+			data: data.into(),
+			topics: record.topics,
+		};
+		match record.phase {
+			Phase::ApplyExtrinsic(index) => match extrinsics.get_mut(index as usize) {
+				Some(extrinsic) => extrinsic.events.push(event),
+				None => events.push(event),
+			},
+			_ => events.push(event),
+		}
+	}
+
+	IndexedBlock {
+		hash: block.hash,
+		number: block.number,
+		parent_hash,
+		extrinsics,
+		events,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use frame_support::weights::DispatchInfo;
+	use sp_runtime::DispatchError;
+
+	#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug)]
+	enum TestEvent {
+		System(RawEvent<u64>),
+		Treasury(u32),
+		Democracy(u64, u64),
+	}
+
+	fn record(phase: Phase, event: TestEvent) -> EventRecord<TestEvent, u64> {
+		EventRecord { phase, event, topics: vec![7] }
+	}
+
+	#[test]
+	fn should_index_block_events() {
+		let records = vec![
+			record(Phase::Initialization, TestEvent::Democracy(1, 2)),
+			record(Phase::ApplyExtrinsic(0), TestEvent::Treasury(5)),
+			record(Phase::ApplyExtrinsic(0), TestEvent::System(RawEvent::ExtrinsicSuccess(
+				DispatchInfo::default(),
+			))),
+			record(Phase::ApplyExtrinsic(1), TestEvent::System(RawEvent::ExtrinsicFailed(
+				DispatchError::BadOrigin,
+				DispatchInfo::default(),
+			))),
+			record(Phase::Finalization, TestEvent::Treasury(6)),
+		];
+
+		let block = index_block::<_, _, _, _, u64>(
+			BlockRef { hash: 1u64, number: 1u32 },
+			0,
+			vec![vec![1u8], vec![2u8]],
+			Some(records.clone()),
+			Some(0),
+			None,
+		);
+		assert_eq!(block.events.len(), 2);
+		assert_eq!(block.events[0].phase, EventPhase::Initialization);
+		assert_eq!((block.events[0].pallet_index, block.events[0].event_index), (2, 1));
+		assert_eq!(block.events[1].phase, EventPhase::Finalization);
+		assert_eq!(block.extrinsics[0].data, Bytes(vec![1, 1]));
+		assert_eq!(block.extrinsics[0].success, Some(true));
+		assert_eq!(block.extrinsics[0].events.len(), 2);
+		assert_eq!(block.extrinsics[0].events[0].data, Bytes(TestEvent::Treasury(5).encode()));
+		assert_eq!(block.extrinsics[0].events[0].topics, vec![7]);
+		assert_eq!(block.extrinsics[1].success, Some(false));
+		assert_eq!(block.extrinsics[1].error, Some("BadOrigin".into()));
+
+		let block = index_block::<_, _, _, _, u64>(
+			BlockRef { hash: 1u64, number: 1u32 },
+			0,
+			vec![vec![1u8], vec![2u8]],
+			Some(records),
+			Some(0),
+			Some(&[1]),
+		);
+		assert_eq!(block.events.len(), 1);
+		assert_eq!(block.extrinsics[0].events.len(), 1);
+		assert!(block.extrinsics[1].events.is_empty());
+		// dispatch results do not depend on the filter
+		assert_eq!(block.extrinsics[0].success, Some(true));
+		assert_eq!(block.extrinsics[1].success, Some(false));
+	}
+
+	#[test]
+	fn should_serialize_tagged_notifications() {
+		let notification = IndexerNotification::<u64, u32>::Retracted(BlockRef { hash: 5, number: 2 });
+		assert_eq!(
+			serde_json::to_string(&notification).unwrap(),
+			r#"{"type":"retracted","hash":5,"number":2}"#,
+		);
+	}
+}
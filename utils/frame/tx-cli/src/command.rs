@@ -0,0 +1,179 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+	AssembleCmd, ExtraParams, PayloadCmd, SignPayloadCmd, TxCmd, TxRuntime,
+	encode::{decode_hex, TypeEncoder},
+};
+use codec::{Decode, Encode};
+use frame_metadata::RuntimeMetadataPrefixed;
+use sc_cli::{Error, utils, with_crypto_scheme};
+use sp_core::{H256, Pair, Public, crypto::{AccountId32, SecretString, Ss58Codec}, hashing::blake2_256};
+use sp_runtime::{
+	MultiSignature, MultiSigner,
+	generic::{Era, UncheckedExtrinsic},
+	traits::{IdentifyAccount, Verify},
+};
+
+/// Payloads longer than this are hashed before being signed.
+const MAX_UNHASHED_PAYLOAD: usize = 256;
+
+/// The message actually signed for a transaction payload.
+fn signed_message(payload: Vec<u8>) -> Vec<u8> {
+	if payload.len() > MAX_UNHASHED_PAYLOAD {
+		blake2_256(&payload).to_vec()
+	} else {
+		payload
+	}
+}
+
+fn parse_hash(value: &str) -> Result<H256, Error> {
+	let bytes = decode_hex(value)?;
+	if bytes.len() != 32 {
+		return Err(format!("Expected a 32 bytes hash, got {} bytes", bytes.len()).into())
+	}
+	Ok(H256::from_slice(&bytes))
+}
+
+impl TxCmd {
+	/// Run the command
+	pub fn run<R: TxRuntime>(&self) -> Result<(), Error> {
+		match self {
+			TxCmd::Payload(cmd) => cmd.run::<R>(),
+			TxCmd::Sign(cmd) => cmd.run(),
+			TxCmd::Assemble(cmd) => cmd.run::<R>(),
+		}
+	}
+}
+
+impl PayloadCmd {
+	fn extra_params<R: TxRuntime>(&self) -> Result<ExtraParams, Error> {
+		let genesis_hash = parse_hash(&self.genesis_hash)?;
+		let (era, era_hash) = match (self.era_period, self.era_block_number, &self.era_block_hash) {
+			(None, _, _) => (Era::Immortal, genesis_hash),
+			(Some(period), Some(number), Some(hash)) => (Era::mortal(period, number), parse_hash(hash)?),
+			_ => return Err("`--era-period` requires `--era-block-number` and `--era-block-hash`".into()),
+		};
+		Ok(ExtraParams {
+			nonce: self.nonce,
+			tip: self.tip,
+			era,
+			era_hash,
+			genesis_hash,
+			spec_version: self.spec_version,
+			transaction_version: self.transaction_version.unwrap_or(R::TRANSACTION_VERSION),
+		})
+	}
+
+	/// Run the command
+	pub fn run<R: TxRuntime>(&self) -> Result<(), Error> {
+		// The metadata of the native runtime is not decoded, round-trip it.
+		let metadata = RuntimeMetadataPrefixed::decode(&mut &R::metadata().encode()[..])?;
+		let args = serde_json::from_str(&self.args)
+			.map_err(|e| Error::Input(format!("Invalid JSON arguments ({})", e)))?;
+		let address = |account: AccountId32| R::Address::from(account).encode();
+		let encoded = TypeEncoder::new(R::TYPE_ALIASES, &address)
+			.encode_call(&metadata, &self.call, &args)?;
+
+		let input = &mut &encoded[..];
+		let call = R::Call::decode(input)?;
+		if !input.is_empty() {
+			return Err(format!("{} bytes left after decoding the call", input.len()).into())
+		}
+		let (extra, additional) = R::signed_extra(&self.extra_params::<R>()?)?;
+
+		eprintln!("Call: {:?}", call);
+		println!("0x{}", hex::encode((call, extra, additional).encode()));
+		Ok(())
+	}
+}
+
+impl SignPayloadCmd {
+	/// Run the command
+	pub fn run(&self) -> Result<(), Error> {
+		let payload = decode_hex(&self.payload)?;
+		let suri = utils::read_uri(self.suri.as_ref())?;
+		let password = self.keystore_params.read_password()?;
+
+		let signature = with_crypto_scheme!(
+			self.crypto_scheme.scheme,
+			sign(&suri, password, signed_message(payload))
+		)?;
+
+		println!("{}", signature);
+		Ok(())
+	}
+}
+
+fn sign<P: Pair>(suri: &str, password: Option<SecretString>, message: Vec<u8>) -> Result<String, Error> {
+	let pair = utils::pair_from_suri::<P>(suri, password)?;
+	Ok(hex::encode(pair.sign(&message)))
+}
+
+impl AssembleCmd {
+	/// Run the command
+	pub fn run<R: TxRuntime>(&self) -> Result<(), Error> {
+		let payload = decode_hex(&self.payload)?;
+		let signature = decode_hex(&self.signature)?;
+		let (signer, signature) = with_crypto_scheme!(
+			self.crypto_scheme.scheme,
+			signer_and_signature(&self.signer, signature)
+		)?;
+
+		let input = &mut &payload[..];
+		let call = R::Call::decode(input)?;
+		let extra = R::Extra::decode(input)?;
+		let account = signer.into_account();
+		if !signature.verify(&signed_message(payload.clone())[..], &account) {
+			return Err(Error::Other("Signature invalid.".into()))
+		}
+
+		let extrinsic = UncheckedExtrinsic::<R::Address, R::Call, MultiSignature, R::Extra>::new_signed(
+			call,
+			account.into(),
+			signature,
+			extra,
+		);
+		println!("0x{}", hex::encode(extrinsic.encode()));
+		Ok(())
+	}
+}
+
+fn signer_and_signature<P>(signer: &str, data: Vec<u8>) -> Result<(MultiSigner, MultiSignature), Error>
+	where
+		P: Pair,
+		P::Public: Into<MultiSigner>,
+		P::Signature: Into<MultiSignature> + Default + AsMut<[u8]>,
+{
+	let mut signature = P::Signature::default();
+	if data.len() != signature.as_ref().len() {
+		return Err(Error::Other(format!(
+			"signature has an invalid length. read {} bytes, expected {} bytes",
+			data.len(),
+			signature.as_ref().len(),
+		)));
+	}
+	signature.as_mut().copy_from_slice(&data);
+
+	let public = match decode_hex(signer) {
+		Ok(public) if signer.starts_with("0x") && public.len() == P::Public::default().as_ref().len() =>
+			P::Public::from_slice(&public),
+		_ => P::Public::from_string(signer)
+			.map_err(|_| Error::Other("Invalid signer; expecting a hex or SS58 public key.".into()))?,
+	};
+	Ok((public.into(), signature.into()))
+}
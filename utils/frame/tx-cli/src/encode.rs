@@ -0,0 +1,341 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Encoding of calls from their JSON arguments, using the type names of the runtime metadata.
+
+use std::convert::TryFrom;
+
+use codec::{Compact, Encode};
+use frame_metadata::{DecodeDifferent, RuntimeMetadata, RuntimeMetadataPrefixed};
+use sc_cli::Error;
+use serde_json::Value;
+use sp_core::crypto::{AccountId32, Ss58Codec};
+
+/// Returns the decoded side of a metadata item.
+fn decoded<B, O>(item: &DecodeDifferent<B, O>) -> Result<&O, Error> {
+	match item {
+		DecodeDifferent::Decoded(item) => Ok(item),
+		DecodeDifferent::Encode(_) => Err("Metadata must be decoded before use".into()),
+	}
+}
+
+/// Decode a hex string, with or without `0x` prefix.
+pub(crate) fn decode_hex(value: &str) -> Result<Vec<u8>, Error> {
+	hex::decode(value.trim().trim_start_matches("0x"))
+		.map_err(|e| Error::Other(format!("Invalid hex ({})", e)))
+}
+
+/// Encodes the values of the argument types found in the metadata.
+pub(crate) struct TypeEncoder<'a> {
+	aliases: &'a [(&'static str, &'static str)],
+	address: &'a dyn Fn(AccountId32) -> Vec<u8>,
+}
+
+impl<'a> TypeEncoder<'a> {
+	/// Create an encoder resolving `aliases` first, and encoding lookup sources with `address`.
+	pub(crate) fn new(
+		aliases: &'a [(&'static str, &'static str)],
+		address: &'a dyn Fn(AccountId32) -> Vec<u8>,
+	) -> Self {
+		Self { aliases, address }
+	}
+
+	/// Find the call `Pallet.call` in the metadata and encode it with the given arguments.
+	///
+	/// `args` is either an array of the arguments in order, or an object keyed by their names.
+	pub(crate) fn encode_call(
+		&self,
+		metadata: &RuntimeMetadataPrefixed,
+		call: &str,
+		args: &Value,
+	) -> Result<Vec<u8>, Error> {
+		let mut parts = call.splitn(2, '.');
+		let (pallet, call) = match (parts.next(), parts.next()) {
+			(Some(pallet), Some(call)) => (pallet, call),
+			_ => return Err(format!("Call `{}` is not of the form `Pallet.call`", call).into()),
+		};
+
+		let modules = match &metadata.1 {
+			RuntimeMetadata::V12(metadata) => decoded(&metadata.modules)?,
+			_ => return Err("Unsupported metadata version".into()),
+		};
+		let module = modules.iter()
+			.map(|module| decoded(&module.name).map(|name| (name, module)))
+			.collect::<Result<Vec<_>, _>>()?
+			.into_iter()
+			.find(|(name, _)| name.as_str() == pallet)
+			.map(|(_, module)| module)
+			.ok_or_else(|| Error::Other(format!("Unknown pallet `{}`", pallet)))?;
+		let calls = match &module.calls {
+			Some(calls) => decoded(calls)?,
+			None => return Err(format!("Pallet `{}` has no calls", pallet).into()),
+		};
+		let mut found = None;
+		for (index, function) in calls.iter().enumerate() {
+			if decoded(&function.name)?.as_str() == call {
+				found = Some((index, function));
+				break
+			}
+		}
+		let (call_index, function) = found
+			.ok_or_else(|| Error::Other(format!("Unknown call `{}.{}`", pallet, call)))?;
+		let arguments = decoded(&function.arguments)?;
+
+		let mut encoded = vec![module.index, call_index as u8];
+		let values = match args {
+			Value::Array(values) => values.iter().collect::<Vec<_>>(),
+			Value::Object(values) => arguments.iter()
+				.map(|arg| {
+					let name = decoded(&arg.name)?;
+					values.get(name.as_str())
+						.ok_or_else(|| Error::Other(format!("Missing argument `{}`", name)))
+				})
+				.collect::<Result<Vec<_>, _>>()?,
+			_ => return Err("Arguments must be a JSON array or object".into()),
+		};
+		if values.len() != arguments.len() {
+			return Err(format!(
+				"`{}.{}` takes {} arguments, {} given",
+				pallet,
+				call,
+				arguments.len(),
+				values.len(),
+			).into())
+		}
+		for (arg, value) in arguments.iter().zip(values) {
+			let name = decoded(&arg.name)?;
+			let ty = decoded(&arg.ty)?;
+			self.encode(ty, value, &mut encoded)
+				.map_err(|e| Error::Other(format!("Argument `{}`: {}", name, e)))?;
+		}
+		Ok(encoded)
+	}
+
+	/// Encode `value` as the type named `ty`.
+	pub(crate) fn encode(&self, ty: &str, value: &Value, out: &mut Vec<u8>) -> Result<(), Error> {
+		let ty = self.resolve(ty);
+		let (name, params) = split_generics(&ty);
+		match (name, params.as_slice()) {
+			("bool", []) => match value {
+				Value::Bool(value) => value.encode_to(out),
+				_ => return Err(format!("Expected a bool, got {}", value).into()),
+			},
+			("u8", []) => unsigned_as::<u8>(value, name)?.encode_to(out),
+			("u16", []) => unsigned_as::<u16>(value, name)?.encode_to(out),
+			("u32", []) => unsigned_as::<u32>(value, name)?.encode_to(out),
+			("u64", []) => unsigned_as::<u64>(value, name)?.encode_to(out),
+			("u128", []) => unsigned(value)?.encode_to(out),
+			("Compact", [_]) => Compact(unsigned(value)?).encode_to(out),
+			("Box", [inner]) => self.encode(inner, value, out)?,
+			("Vec", ["u8"]) | ("Bytes", []) => bytes(value)?.encode_to(out),
+			("Vec", [inner]) => match value {
+				Value::Array(values) => {
+					Compact(values.len() as u32).encode_to(out);
+					for value in values {
+						self.encode(inner, value, out)?;
+					}
+				},
+				_ => return Err(format!("Expected an array, got {}", value).into()),
+			},
+			("Option", [inner]) => match value {
+				Value::Null => out.push(0),
+				value => {
+					out.push(1);
+					self.encode(inner, value, out)?;
+				},
+			},
+			("()", items) => match value {
+				Value::Array(values) if values.len() == items.len() => {
+					for (item, value) in items.iter().zip(values) {
+						self.encode(item, value, out)?;
+					}
+				},
+				_ => return Err(format!("Expected an array of {} items, got {}", items.len(), value).into()),
+			},
+			("H256", []) | ("Hash", []) => out.extend(fixed_bytes(value, 32)?),
+			("AccountId", []) => account_id(value)?.encode_to(out),
+			("LookupSource", []) | ("Address", []) => out.extend((self.address)(account_id(value)?)),
+			_ => match value {
+				// unknown types are given as their SCALE encoding
+				Value::String(value) if value.starts_with("0x") => out.extend(decode_hex(value)?),
+				_ => return Err(format!(
+					"Unsupported type `{}`, give the value as hex encoded SCALE",
+					ty,
+				).into()),
+			},
+		}
+		Ok(())
+	}
+
+	/// Normalize the type name and resolve aliases.
+	fn resolve(&self, ty: &str) -> String {
+		let mut ty = normalize(ty);
+		while let Some((_, alias)) = self.aliases.iter().find(|(name, _)| *name == ty) {
+			ty = normalize(alias);
+		}
+		ty
+	}
+}
+
+/// Strip the trait qualification from a type name, e.g. `<T as Trait>::Balance` to `Balance`.
+fn normalize(ty: &str) -> String {
+	let ty = ty.split_whitespace().collect::<Vec<_>>().join(" ");
+	if ty.ends_with("as StaticLookup>::Source") {
+		return "LookupSource".into()
+	}
+	let ty = match ty.rfind(">::") {
+		Some(index) if ty.starts_with('<') => &ty[index + 3..],
+		_ => &ty,
+	};
+	let ty = ty.trim_start_matches("T::");
+	match ty.find("Of<") {
+		// `BalanceOf<T>`
+		Some(index) if !ty[..index].contains('<') && !ty[index + 3..].contains('<') => ty[..index].into(),
+		_ => ty.into(),
+	}
+}
+
+/// Split a type name into its name and generic parameters, tuples are named `()`.
+fn split_generics(ty: &str) -> (&str, Vec<&str>) {
+	let (name, inner) = if ty.starts_with('(') && ty.ends_with(')') {
+		("()", &ty[1..ty.len() - 1])
+	} else {
+		match (ty.find('<'), ty.ends_with('>')) {
+			(Some(index), true) => (&ty[..index], &ty[index + 1..ty.len() - 1]),
+			_ => return (ty, Vec::new()),
+		}
+	};
+	let mut params = Vec::new();
+	let mut depth = 0;
+	let mut start = 0;
+	for (index, c) in inner.char_indices() {
+		match c {
+			'<' | '(' | '[' => depth += 1,
+			'>' | ')' | ']' => depth -= 1,
+			',' if depth == 0 => {
+				params.push(inner[start..index].trim());
+				start = index + 1;
+			},
+			_ => {},
+		}
+	}
+	if !inner[start..].trim().is_empty() {
+		params.push(inner[start..].trim());
+	}
+	(name, params)
+}
+
+/// An unsigned integer, given as a JSON number or a decimal string.
+fn unsigned(value: &Value) -> Result<u128, Error> {
+	match value {
+		Value::Number(number) => number.as_u64().map(Into::into),
+		Value::String(number) => number.parse().ok(),
+		_ => None,
+	}.ok_or_else(|| Error::Other(format!("Expected an unsigned integer, got {}", value)))
+}
+
+/// An unsigned integer that must fit in the type named `ty`.
+fn unsigned_as<T: TryFrom<u128>>(value: &Value, ty: &str) -> Result<T, Error> {
+	T::try_from(unsigned(value)?)
+		.map_err(|_| Error::Other(format!("{} does not fit in a {}", value, ty)))
+}
+
+/// Bytes, given as a hex string with `0x` prefix or as UTF-8 text.
+fn bytes(value: &Value) -> Result<Vec<u8>, Error> {
+	match value {
+		Value::String(value) if value.starts_with("0x") => decode_hex(value),
+		Value::String(value) => Ok(value.as_bytes().to_vec()),
+		_ => Err(format!("Expected a string, got {}", value).into()),
+	}
+}
+
+fn fixed_bytes(value: &Value, len: usize) -> Result<Vec<u8>, Error> {
+	let bytes = match value {
+		Value::String(value) => decode_hex(value)?,
+		_ => return Err(format!("Expected a hex string, got {}", value).into()),
+	};
+	if bytes.len() != len {
+		return Err(format!("Expected {} bytes, got {}", len, bytes.len()).into())
+	}
+	Ok(bytes)
+}
+
+/// An account, given as SS58 address or as hex encoded public key.
+fn account_id(value: &Value) -> Result<AccountId32, Error> {
+	match value {
+		Value::String(address) if address.starts_with("0x") => {
+			let mut account = [0u8; 32];
+			account.copy_from_slice(&fixed_bytes(value, 32)?);
+			Ok(account.into())
+		},
+		Value::String(address) => AccountId32::from_ss58check(address)
+			.map_err(|e| Error::Other(format!("Invalid address {} ({:?})", address, e))),
+		_ => Err(format!("Expected an address, got {}", value).into()),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use serde_json::json;
+
+	const ALICE: &str = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+
+	fn encode(ty: &str, value: Value) -> Result<Vec<u8>, Error> {
+		let address = |account: AccountId32| (0xffu8, account).encode();
+		let encoder = TypeEncoder::new(&[("Balance", "u128"), ("BlockNumber", "u32")], &address);
+		let mut out = Vec::new();
+		encoder.encode(ty, &value, &mut out).map(|_| out)
+	}
+
+	#[test]
+	fn should_normalize_type_names() {
+		assert_eq!(normalize("<T::Lookup as StaticLookup>::Source"), "LookupSource");
+		assert_eq!(normalize("<T as Trait<I>>::Balance"), "Balance");
+		assert_eq!(normalize("T::BlockNumber"), "BlockNumber");
+		assert_eq!(normalize("BalanceOf<T>"), "Balance");
+		assert_eq!(normalize("Vec<T::AccountId>"), "Vec<T::AccountId>");
+		assert_eq!(normalize("Vec<BalanceOf<T>>"), "Vec<BalanceOf<T>>");
+		assert_eq!(split_generics("(u32, Vec<u8>)"), ("()", vec!["u32", "Vec<u8>"]));
+		assert_eq!(split_generics("Vec<(u32, u64)>"), ("Vec", vec!["(u32, u64)"]));
+	}
+
+	#[test]
+	fn should_encode_arguments() {
+		let alice = AccountId32::from_ss58check(ALICE).unwrap();
+		assert_eq!(encode("Compact<T::Balance>", json!(1_000)).unwrap(), Compact(1_000u128).encode());
+		assert_eq!(encode("BalanceOf<T>", json!("1000000000000000000000")).unwrap(), 10u128.pow(21).encode());
+		assert_eq!(encode("T::BlockNumber", json!(7)).unwrap(), 7u32.encode());
+		assert_eq!(encode("Vec<u8>", json!("remark")).unwrap(), b"remark".to_vec().encode());
+		assert_eq!(encode("Vec<u8>", json!("0x0102")).unwrap(), vec![1u8, 2].encode());
+		assert_eq!(encode("Option<u32>", Value::Null).unwrap(), None::<u32>.encode());
+		assert_eq!(encode("(bool, u16)", json!([true, 3])).unwrap(), (true, 3u16).encode());
+		assert_eq!(encode("Vec<T::AccountId>", json!([ALICE])).unwrap(), vec![alice.clone()].encode());
+		assert_eq!(
+			encode("<T::Lookup as StaticLookup>::Source", json!(ALICE)).unwrap(),
+			(0xffu8, alice).encode(),
+		);
+		assert_eq!(encode("T::MissionId", json!("0x2a000000")).unwrap(), 42u32.encode());
+		assert!(encode("T::MissionId", json!(42)).is_err());
+		assert!(encode("u32", json!(true)).is_err());
+		// values that do not fit are rejected rather than truncated
+		assert_eq!(encode("u16", json!(65_535)).unwrap(), 65_535u16.encode());
+		assert!(encode("u8", json!(256)).is_err());
+		assert!(encode("T::BlockNumber", json!(4_294_967_296u64)).is_err());
+		assert!(encode("u64", json!("18446744073709551616")).is_err());
+	}
+}
@@ -0,0 +1,186 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Offline construction and signing of the transactions of a FRAME runtime.
+//!
+//! A transaction is built in three steps, so that the secret key never has to be on a machine
+//! that is connected to the network:
+//!
+//! 1. `tx payload` encodes a call from its name and JSON arguments, resolved from the runtime
+//!    metadata, together with the signed extensions into a signing payload.
+//! 2. `tx sign` signs the payload, on the air-gapped machine holding the key.
+//! 3. `tx assemble` checks the signature and emits the hex encoded extrinsic, ready to be
+//!    submitted with `author_submitExtrinsic`.
+
+mod command;
+mod encode;
+
+use std::fmt::Debug;
+
+use codec::Codec;
+use sc_cli::{CryptoSchemeFlag, KeystoreParams};
+use sp_core::H256;
+use sp_runtime::{AccountId32, generic::Era, traits::SignedExtension};
+use structopt::StructOpt;
+
+pub use frame_metadata::RuntimeMetadataPrefixed;
+
+/// The runtime specific parts of a transaction.
+pub trait TxRuntime {
+	/// The call of the runtime.
+	type Call: Codec + Debug;
+	/// The address format of the runtime.
+	type Address: Codec + From<AccountId32>;
+	/// The signed extensions of the runtime.
+	type Extra: SignedExtension;
+
+	/// Aliases of the argument types in the metadata to the types they are encoded as,
+	/// e.g. `("Balance", "u128")`.
+	const TYPE_ALIASES: &'static [(&'static str, &'static str)];
+
+	/// The transaction version of the native runtime, used if none is given.
+	const TRANSACTION_VERSION: u32;
+
+	/// The metadata of the runtime.
+	fn metadata() -> RuntimeMetadataPrefixed;
+
+	/// The signed extensions and the data they add to the signing payload.
+	fn signed_extra(
+		params: &ExtraParams,
+	) -> Result<(Self::Extra, <Self::Extra as SignedExtension>::AdditionalSigned), sc_cli::Error>;
+}
+
+/// The parameters of the signed extensions of a transaction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtraParams {
+	/// The nonce of the signer.
+	pub nonce: u64,
+	/// The tip paid to the block author.
+	pub tip: u128,
+	/// The period the transaction is valid in.
+	pub era: Era,
+	/// The hash of the block the era starts at, the genesis hash for immortal transactions.
+	pub era_hash: H256,
+	/// The genesis hash of the chain.
+	pub genesis_hash: H256,
+	/// The spec version of the runtime.
+	pub spec_version: u32,
+	/// The transaction version of the runtime.
+	pub transaction_version: u32,
+}
+
+/// The `tx` command used to construct and sign transactions offline.
+#[derive(Debug, StructOpt)]
+pub enum TxCmd {
+	/// Build the signing payload of a transaction.
+	Payload(PayloadCmd),
+
+	/// Sign a transaction payload with a given (secret) key.
+	Sign(SignPayloadCmd),
+
+	/// Assemble the extrinsic from a transaction payload and its signature.
+	Assemble(AssembleCmd),
+}
+
+/// The `tx payload` command.
+#[derive(Debug, StructOpt)]
+pub struct PayloadCmd {
+	/// The call, as `Pallet.call`, e.g. `Balances.transfer`.
+	pub call: String,
+
+	/// The arguments of the call, as a JSON array or a JSON object keyed by argument name.
+	///
+	/// Arguments of types that are not known are given as hex encoded SCALE.
+	#[structopt(long, default_value = "[]")]
+	pub args: String,
+
+	/// The nonce of the signer.
+	#[structopt(long)]
+	pub nonce: u64,
+
+	/// The tip paid to the block author.
+	#[structopt(long, default_value = "0")]
+	pub tip: u128,
+
+	/// The genesis hash of the chain, hex encoded.
+	#[structopt(long)]
+	pub genesis_hash: String,
+
+	/// The spec version of the runtime.
+	#[structopt(long)]
+	pub spec_version: u32,
+
+	/// The transaction version of the runtime, the one of the native runtime if not given.
+	#[structopt(long)]
+	pub transaction_version: Option<u32>,
+
+	/// The number of blocks the transaction is valid for, immortal if not given.
+	///
+	/// Requires `--era-block-number` and `--era-block-hash`.
+	#[structopt(long)]
+	pub era_period: Option<u64>,
+
+	/// The number of the block the era starts at, usually the best block.
+	#[structopt(long)]
+	pub era_block_number: Option<u64>,
+
+	/// The hash of the block the era starts at, hex encoded.
+	#[structopt(long)]
+	pub era_block_hash: Option<String>,
+}
+
+/// The `tx sign` command.
+#[derive(Debug, StructOpt)]
+pub struct SignPayloadCmd {
+	/// The transaction payload, hex encoded.
+	#[structopt(long)]
+	pub payload: String,
+
+	/// The secret key URI.
+	/// If the value is a file, the file content is used as URI.
+	/// If not given, you will be prompted for the URI.
+	#[structopt(long)]
+	pub suri: Option<String>,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub keystore_params: KeystoreParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub crypto_scheme: CryptoSchemeFlag,
+}
+
+/// The `tx assemble` command.
+#[derive(Debug, StructOpt)]
+pub struct AssembleCmd {
+	/// The transaction payload, hex encoded.
+	#[structopt(long)]
+	pub payload: String,
+
+	/// The public key of the signer, hex or SS58 encoded.
+	#[structopt(long)]
+	pub signer: String,
+
+	/// The signature of the payload, hex encoded.
+	#[structopt(long)]
+	pub signature: String,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub crypto_scheme: CryptoSchemeFlag,
+}